@@ -0,0 +1,158 @@
+//! zip 归档读写，基于 `zip` crate。
+//!
+//! 归档始终落在匿名临时文件上（关闭即删除），上传与下载都按流处理，
+//! 不把整个归档载入内存；条目数或大小超出传统 zip 的限制时自动使用 zip64。
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use axum::body::Bytes;
+use futures_util::{Stream, StreamExt};
+use tokio::io::AsyncWriteExt;
+use zip::CompressionMethod;
+use zip::result::{ZipError, ZipResult};
+use zip::write::SimpleFileOptions;
+
+/// 基于临时文件的归档读取器
+pub type Reader = zip::ZipArchive<File>;
+/// 基于临时文件的归档写入器
+pub type Writer = zip::ZipWriter<File>;
+
+/// 在匿名临时文件上创建写入器
+pub fn writer() -> io::Result<Writer> {
+    Ok(zip::ZipWriter::new(tempfile::tempfile()?))
+}
+
+/// 开始一个 deflate 压缩的条目。大小事先未知，一律允许 zip64
+pub fn start_deflated(zip: &mut Writer, name: &str) -> ZipResult<()> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    zip.start_file(name, options)
+}
+
+/// 开始一个原样存放的条目（图片、视频等已压缩的数据）
+pub fn start_stored(zip: &mut Writer, name: &str, size: u64) -> ZipResult<()> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(size >= u64::from(u32::MAX));
+    zip.start_file(name, options)
+}
+
+/// 写出中央目录，返回回到开头、可直接读取的归档文件
+pub fn finish(zip: Writer) -> ZipResult<File> {
+    let mut file = zip.finish()?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+/// 打开归档文件
+pub fn open(file: File) -> io::Result<Reader> {
+    zip::ZipArchive::new(file).map_err(io::Error::from)
+}
+
+/// 把条目解压写入 `out`，返回写入的字节数；条目不存在时返回 None
+pub fn copy(zip: &mut Reader, name: &str, out: &mut impl Write) -> io::Result<Option<u64>> {
+    match zip.by_name(name) {
+        Ok(mut entry) => io::copy(&mut entry, out).map(Some),
+        Err(ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 读取整个条目；不存在时返回 None，解压后超过 `limit` 字节视为无效（防止 zip 炸弹）
+pub fn read(zip: &mut Reader, name: &str, limit: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut data = Vec::new();
+    (&mut entry).take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{name} 过大"),
+        ));
+    }
+    Ok(Some(data))
+}
+
+/// 把上传的数据流写入匿名临时文件，返回回到开头的文件
+pub async fn spool<S, E>(mut body: S) -> io::Result<File>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut file = tokio::fs::File::from_std(tempfile::tempfile()?);
+    while let Some(chunk) = body.next().await {
+        file.write_all(&chunk.map_err(io::Error::other)?).await?;
+    }
+    file.flush().await?;
+    let mut file = file.into_std().await;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn roundtrip_stored_and_deflated() {
+        let text = "你好，zip ".repeat(100);
+        let mut w = writer().unwrap();
+        start_deflated(&mut w, "data/a.json").unwrap();
+        w.write_all(text.as_bytes()).unwrap();
+        start_stored(&mut w, "media/b.bin", 4).unwrap();
+        w.write_all(&[0, 1, 2, 255]).unwrap();
+        start_deflated(&mut w, "empty").unwrap();
+        let file = finish(w).unwrap();
+        // 重复文本应被压缩
+        assert!(file.metadata().unwrap().len() < text.len() as u64);
+
+        let mut zip = open(file).unwrap();
+        assert_eq!(
+            read(&mut zip, "data/a.json", 1 << 20).unwrap().unwrap(),
+            text.as_bytes()
+        );
+        let mut out = Vec::new();
+        assert_eq!(copy(&mut zip, "media/b.bin", &mut out).unwrap(), Some(4));
+        assert_eq!(out, [0, 1, 2, 255]);
+        assert_eq!(read(&mut zip, "empty", 0).unwrap().unwrap(), b"");
+        assert!(read(&mut zip, "missing", 10).unwrap().is_none());
+        assert!(read(&mut zip, "data/a.json", 10).is_err());
+    }
+
+    #[test]
+    fn more_than_65535_entries_use_zip64() {
+        let mut w = writer().unwrap();
+        for i in 0..65_600 {
+            start_stored(&mut w, &i.to_string(), 0).unwrap();
+        }
+        let mut zip = open(finish(w).unwrap()).unwrap();
+        assert_eq!(zip.len(), 65_600);
+        assert!(read(&mut zip, "65599", 0).unwrap().is_some());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"not a zip at all, definitely").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        assert!(open(file).is_err());
+    }
+
+    #[tokio::test]
+    async fn spool_writes_stream_to_file() {
+        let chunks = futures_util::stream::iter(vec![
+            Ok::<_, io::Error>(Bytes::from_static(b"ab")),
+            Ok(Bytes::from_static(b"cd")),
+        ]);
+        let mut file = spool(chunks).await.unwrap();
+        let mut data = String::new();
+        file.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "abcd");
+    }
+}
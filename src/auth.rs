@@ -1,13 +1,16 @@
 use axum::{
-    extract::{Request, State},
-    http::StatusCode,
+    extract::{OriginalUri, Request, State},
+    http::{HeaderMap, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Json, Response},
 };
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::error::ErrorBody;
+use crate::modules::api_token::model::{TOKEN_PREFIX, scope_allows};
+use crate::modules::user::UserStatus;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,8 +18,18 @@ pub struct Claims {
     pub sub: i32,     // user_id
     pub role: String, // "admin" | "user"
     pub exp: usize,   // expiry
+    /// 所属会话 id（session 表主键）
+    pub sid: String,
+    /// token 唯一 id，吊销时写入黑名单
+    pub jti: String,
+    /// 个人访问令牌的作用域；登录会话为 None（不受限）
+    #[serde(skip)]
+    pub scopes: Option<Vec<String>>,
 }
 
+/// access token 有效期（秒）。过期后用 refresh token 换新。
+pub const ACCESS_TOKEN_TTL: usize = 15 * 60;
+
 // ============================================================
 // JWT 工具函数
 // ============================================================
@@ -31,28 +44,45 @@ fn verify_token(token: &str, secret: &str) -> Option<Claims> {
     .map(|d| d.claims)
 }
 
-/// 生成 JWT（240h 有效）
-pub fn create_token(user_id: i32, role: &str, secret: &str) -> String {
+/// 为会话 `sid` 生成 access token（[`ACCESS_TOKEN_TTL`] 有效），同时返回其 Claims
+pub fn create_token(user_id: i32, role: &str, sid: &str, secret: &str) -> (String, Claims) {
     let exp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .expect("系统时间早于 Unix 纪元")
         .as_secs() as usize
-        + 864000;
+        + ACCESS_TOKEN_TTL;
     let claims = Claims {
         sub: user_id,
         role: role.to_string(),
         exp,
+        sid: sid.to_string(),
+        jti: uuid::Uuid::new_v4().to_string(),
+        scopes: None,
     };
-    encode(
+    let token = encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
-    .expect("JWT 编码失败")
+    .expect("JWT 编码失败");
+    (token, claims)
+}
+
+/// 不可逆保存的 token（refresh token / API 令牌）统一取 SHA-256（十六进制）
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
 }
 
 fn extract_token(req: &Request) -> Option<String> {
-    req.headers()
+    bearer_token(req.headers())
+}
+
+/// `Authorization: Bearer <token>` 中的 token
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
         .get("Authorization")
         .and_then(|v| v.to_str().ok())
         .and_then(|s| s.strip_prefix("Bearer "))
@@ -63,48 +93,127 @@ fn extract_token(req: &Request) -> Option<String> {
 // 中间件
 // ============================================================
 
-/// 认证中间件：验证 JWT，将 Claims 注入 request extensions。
-/// 未登录返回 401。
+/// 认证中间件：验证 JWT 并检查 jti 黑名单，将 Claims 注入 request extensions。
+/// 未登录、过期或会话已吊销返回 401；账号已停用返回 403。
+/// 管理员重置密码后、用户改密前只放行修改密码与注销（其余 403）。
+///
+/// Claims 中的角色以数据库当前值为准，管理员调整角色即时生效。
 ///
 /// 用法：挂载到需要登录的路由组上。
 ///   Router::new().nest(…).layer(from_fn_with_state(state, auth::auth))
 pub async fn auth(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
-    let secret = &state.jwt_secret;
+    let Some(token) = extract_token(&request) else {
+        drain_rejected_body(&mut request).await;
+        return reject(StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "请先登录");
+    };
 
-    let token = match extract_token(&request) {
-        Some(t) => t,
-        None => {
+    let (mut claims, status) = match authenticate(&state, &token).await {
+        Ok(found) => found,
+        Err(rejection) => {
             drain_rejected_body(&mut request).await;
-            return (
+            return rejection;
+        }
+    };
+    if status.must_change_password && !allowed_before_password_change(&request) {
+        drain_rejected_body(&mut request).await;
+        return reject(
+            StatusCode::FORBIDDEN,
+            "PASSWORD_CHANGE_REQUIRED",
+            "密码已被管理员重置，请先修改密码",
+        );
+    }
+    claims.role = status.role;
+
+    request.extensions_mut().insert(claims);
+    next.run(request).await
+}
+
+/// 校验 token 并取账号当前状态：签名与过期、会话是否注销（API 令牌查库校验）、
+/// 账号是否存在、是否停用
+async fn authenticate(state: &AppState, token: &str) -> Result<(Claims, UserStatus), Response> {
+    // 个人访问令牌：查库校验，作用域由 require_scope 按路由组检查
+    let claims = if token.starts_with(TOKEN_PREFIX) {
+        match state.api_token.authenticate(token).await {
+            Ok(Some(claims)) => claims,
+            Ok(None) => {
+                return Err(reject(
+                    StatusCode::UNAUTHORIZED,
+                    "TOKEN_INVALID",
+                    "API 令牌无效或已过期",
+                ));
+            }
+            Err(e) => return Err(crate::error::internal(e, "校验 API 令牌")),
+        }
+    } else {
+        let Some(claims) = verify_token(token, &state.jwt_secret) else {
+            return Err(reject(
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorBody {
-                    code: "UNAUTHORIZED".to_string(),
-                    message: "请先登录".to_string(),
-                    details: None,
-                }),
-            )
-                .into_response();
+                "TOKEN_EXPIRED",
+                "登录已过期，请重新登录",
+            ));
+        };
+
+        match state.session.is_denied(&claims.jti).await {
+            Ok(false) => {}
+            Ok(true) => {
+                return Err(reject(
+                    StatusCode::UNAUTHORIZED,
+                    "SESSION_REVOKED",
+                    "会话已注销，请重新登录",
+                ));
+            }
+            Err(e) => return Err(crate::error::internal(e, "校验会话")),
         }
+        claims
     };
 
-    let claims = match verify_token(&token, secret) {
-        Some(c) => c,
-        None => {
-            drain_rejected_body(&mut request).await;
-            return (
+    let status = match state.user.status(claims.sub).await {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            return Err(reject(
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorBody {
-                    code: "TOKEN_EXPIRED".to_string(),
-                    message: "登录已过期，请重新登录".to_string(),
-                    details: None,
-                }),
-            )
-                .into_response();
+                "UNAUTHORIZED",
+                "账号不存在",
+            ));
         }
+        Err(e) => return Err(crate::error::internal(e, "校验账号")),
     };
+    if status.disabled {
+        return Err(reject(
+            StatusCode::FORBIDDEN,
+            "ACCOUNT_DISABLED",
+            "账号已停用",
+        ));
+    }
+    Ok((claims, status))
+}
 
-    request.extensions_mut().insert(claims);
-    next.run(request).await
+/// 长连接（SSE）期间复核建立连接时的凭证：过期、会话注销、API 令牌吊销、
+/// 账号删除或停用后返回 false
+pub async fn still_authenticated(state: &AppState, token: &str) -> bool {
+    authenticate(state, token).await.is_ok()
+}
+
+/// 密码被重置后仍可访问的接口：修改密码、注销
+fn allowed_before_password_change(request: &Request) -> bool {
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or(request.uri().path(), |uri| uri.path());
+    *request.method() == Method::POST
+        && (path.ends_with("/user/password") || path.ends_with("/user/logout"))
+}
+
+fn reject(status: StatusCode, code: &str, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorBody {
+            code: code.to_string(),
+            message: message.to_string(),
+            details: None,
+        }),
+    )
+        .into_response()
 }
 
 /// 拒绝请求前消费（丢弃）请求体。
@@ -113,7 +222,7 @@ pub async fn auth(State(state): State<AppState>, mut request: Request, next: Nex
 /// 大文件上传场景（如书签导入 multipart）下客户端会收到
 /// "Request has been truncated" / NetworkError，而非 401。
 /// 最多丢弃 8MB，防止恶意无限 body 拖垮连接。
-async fn drain_rejected_body(request: &mut Request) {
+pub(crate) async fn drain_rejected_body(request: &mut Request) {
     let body = std::mem::take(request.body_mut());
     let _ = axum::body::to_bytes(body, 8 * 1024 * 1024).await;
 }
@@ -149,6 +258,46 @@ pub async fn require_admin(request: Request, next: Next) -> Response {
     }
 }
 
+/// 作用域中间件：个人访问令牌须持有 `resource` 的相应作用域（GET/HEAD 为读，其余为写）。
+/// 登录会话不受限。必须在 [`auth`] 中间件之后使用。
+///
+/// 用法：挂载到单个路由组上，state 为资源名。
+///   mem::routes().layer(from_fn_with_state("mem", auth::require_scope))
+pub async fn require_scope(
+    State(resource): State<&'static str>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let write = !matches!(*request.method(), Method::GET | Method::HEAD);
+    match request.extensions().get::<Claims>() {
+        Some(Claims {
+            scopes: Some(scopes),
+            ..
+        }) if !scope_allows(scopes, resource, write) => {
+            let needed = format!("{resource}:{}", if write { "write" } else { "read" });
+            reject(
+                StatusCode::FORBIDDEN,
+                "INSUFFICIENT_SCOPE",
+                &format!("API 令牌缺少 {needed} 作用域"),
+            )
+        }
+        _ => next.run(request).await,
+    }
+}
+
+/// 仅限登录会话：账号管理、管理员接口不接受个人访问令牌（403）。
+/// 必须在 [`auth`] 中间件之后使用。
+pub async fn require_session(request: Request, next: Next) -> Response {
+    match request.extensions().get::<Claims>() {
+        Some(c) if c.scopes.is_some() => reject(
+            StatusCode::FORBIDDEN,
+            "SESSION_REQUIRED",
+            "该接口不接受 API 令牌，请登录后操作",
+        ),
+        _ => next.run(request).await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -160,7 +309,7 @@ mod tests {
 
     #[test]
     fn create_and_verify_user_token() {
-        let token = create_token(42, "user", TEST_SECRET);
+        let token = create_token(42, "user", "sid", TEST_SECRET).0;
         let claims = verify_token(&token, TEST_SECRET).expect("应能验证 token");
         assert_eq!(claims.sub, 42);
         assert_eq!(claims.role, "user");
@@ -169,15 +318,23 @@ mod tests {
 
     #[test]
     fn create_and_verify_admin_token() {
-        let token = create_token(1, "admin", TEST_SECRET);
+        let token = create_token(1, "admin", "sid", TEST_SECRET).0;
         let claims = verify_token(&token, TEST_SECRET).expect("应能验证 token");
         assert_eq!(claims.sub, 1);
         assert_eq!(claims.role, "admin");
     }
 
+    #[test]
+    fn each_token_gets_unique_jti() {
+        let (_, a) = create_token(1, "user", "sid", TEST_SECRET);
+        let (_, b) = create_token(1, "user", "sid", TEST_SECRET);
+        assert_ne!(a.jti, b.jti);
+        assert_eq!(a.sid, "sid");
+    }
+
     #[test]
     fn verify_with_wrong_secret_returns_none() {
-        let token = create_token(7, "user", TEST_SECRET);
+        let token = create_token(7, "user", "sid", TEST_SECRET).0;
         assert!(verify_token(&token, "wrong-secret").is_none());
     }
 
@@ -193,8 +350,8 @@ mod tests {
 
     #[test]
     fn tokens_with_different_secrets_are_independent() {
-        let token_a = create_token(1, "user", "secret-a");
-        let token_b = create_token(2, "admin", "secret-b");
+        let token_a = create_token(1, "user", "sid", "secret-a").0;
+        let token_b = create_token(2, "admin", "sid", "secret-b").0;
         assert!(verify_token(&token_a, "secret-a").is_some());
         assert!(verify_token(&token_a, "secret-b").is_none());
         assert!(verify_token(&token_b, "secret-b").is_some());
@@ -204,7 +361,7 @@ mod tests {
     #[test]
     fn token_contains_correct_user_id() {
         for id in [1, 100, 9999] {
-            let token = create_token(id, "user", TEST_SECRET);
+            let token = create_token(id, "user", "sid", TEST_SECRET).0;
             let claims = verify_token(&token, TEST_SECRET).unwrap();
             assert_eq!(claims.sub, id, "user_id {} 应正确编码", id);
         }
@@ -271,6 +428,38 @@ mod tests {
         assert_eq!(extract_token(&req), Some(" my-token".to_string()));
     }
 
+    #[test]
+    fn password_change_gate_allows_only_password_and_logout() {
+        use axum::body::Body;
+        let req = |method: Method, path: &str| {
+            let mut req = Request::builder()
+                .method(method)
+                .uri("/password")
+                .body(Body::empty())
+                .unwrap();
+            // nest 后 uri 已去掉前缀，判断以原始路径为准
+            req.extensions_mut()
+                .insert(OriginalUri(path.parse().unwrap()));
+            req
+        };
+        assert!(allowed_before_password_change(&req(
+            Method::POST,
+            "/api/user/password"
+        )));
+        assert!(allowed_before_password_change(&req(
+            Method::POST,
+            "/api/user/logout"
+        )));
+        assert!(!allowed_before_password_change(&req(
+            Method::GET,
+            "/api/user/password"
+        )));
+        assert!(!allowed_before_password_change(&req(
+            Method::GET,
+            "/api/mem"
+        )));
+    }
+
     // ── Claims serialization ──
 
     #[test]
@@ -279,11 +468,18 @@ mod tests {
             sub: 5,
             role: "admin".to_string(),
             exp: 9999999999,
+            sid: "s1".to_string(),
+            jti: "j1".to_string(),
+            scopes: Some(vec!["mem:read".to_string()]),
         };
         let json = serde_json::to_string(&claims).unwrap();
         let deserialized: Claims = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.sub, 5);
         assert_eq!(deserialized.role, "admin");
         assert_eq!(deserialized.exp, 9999999999);
+        assert_eq!(deserialized.sid, "s1");
+        assert_eq!(deserialized.jti, "j1");
+        // 作用域只在服务端构造，不进入 JWT
+        assert!(deserialized.scopes.is_none());
     }
 }
@@ -1,15 +1,16 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 // ── 请求 ──
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct BatchRequest<T> {
     pub items: Vec<T>,
 }
 
 // ── 响应 ──
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct BatchErrorDetail {
     pub index: usize,
     /// 错误类型，取自 `StatusCode::canonical_reason()`，如 "Not Found"
@@ -17,7 +18,7 @@ pub struct BatchErrorDetail {
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchResponse {
     pub ok: bool,
     pub processed: usize,
@@ -27,7 +28,7 @@ pub struct BatchResponse {
     pub errors: Option<Vec<BatchErrorDetail>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchDataResponse<T: Serialize> {
     pub ok: bool,
     pub processed: usize,
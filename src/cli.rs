@@ -0,0 +1,388 @@
+//! 命令行子命令。
+//!
+//! 不带参数或 `serve` 时启动 HTTP 服务；其余子命令连接同一数据库（配置同样来自
+//! 环境变量），复用各模块 service 完成运维操作后退出。
+
+use std::error::Error;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+
+use crate::config::Config;
+use crate::modules::mem::model::JsonMemItem;
+use crate::state::AppState;
+use crate::{db, modules, storage};
+
+pub const USAGE: &str = "\
+用法: brainbow [命令]
+
+命令:
+  serve                               启动 HTTP 服务（默认）
+  migrate                             应用数据库迁移后退出
+  create-user <用户名> [--admin]      创建账号，密码从标准输入读取
+  reset-password <用户名>             重置为一次性临时密码并吊销全部会话
+  export <文件.zip> --user <用户名>   导出账号全部数据到新文件
+  import <文件.zip> --user <用户名>   把归档导入到账号
+  mem optimize --user <用户名>        用该用户的复习记录训练其 FSRS 参数
+  mem import <文件> --user <用户名> [--tag <标签>]...
+                                      导入记忆卡片（按扩展名识别 csv / psv / json /
+                                      Anki 的 apkg / colpkg）
+  bookmarks import <文件.html> --user <用户名>
+                                      导入 Firefox 书签 HTML
+  backup <路径>                       把数据库在线备份到新文件（不含媒体文件）
+  help                                显示本帮助";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Serve,
+    Help,
+    Migrate,
+    CreateUser {
+        name: String,
+        admin: bool,
+    },
+    ResetPassword {
+        name: String,
+    },
+    Export {
+        user: String,
+        path: PathBuf,
+    },
+    Import {
+        user: String,
+        path: PathBuf,
+    },
+    MemOptimize {
+        user: String,
+    },
+    MemImport {
+        user: String,
+        path: PathBuf,
+        tags: Vec<String>,
+    },
+    BookmarksImport {
+        user: String,
+        path: PathBuf,
+    },
+    Backup {
+        path: PathBuf,
+    },
+}
+
+/// 解析 `argv[1..]`
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut words = Vec::new();
+    let mut user = None;
+    let mut tags = Vec::new();
+    let mut admin = false;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--admin" => admin = true,
+            "-u" | "--user" => user = Some(args.next().ok_or("--user 缺少用户名")?),
+            "--tag" => tags.push(args.next().ok_or("--tag 缺少标签名")?),
+            s if s.starts_with('-') => return Err(format!("未知选项: {s}")),
+            _ => words.push(arg),
+        }
+    }
+
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let user = || {
+        user.clone()
+            .ok_or_else(|| "缺少 --user <用户名>".to_string())
+    };
+    let command = match words.as_slice() {
+        [] | ["serve"] => Command::Serve,
+        ["help"] => Command::Help,
+        ["migrate"] => Command::Migrate,
+        ["create-user", name] => Command::CreateUser {
+            name: name.to_string(),
+            admin,
+        },
+        ["reset-password", name] => Command::ResetPassword {
+            name: name.to_string(),
+        },
+        ["export", path] => Command::Export {
+            user: user()?,
+            path: path.into(),
+        },
+        ["import", path] => Command::Import {
+            user: user()?,
+            path: path.into(),
+        },
+        ["mem", "optimize"] => Command::MemOptimize { user: user()? },
+        ["mem", "import", path] => Command::MemImport {
+            user: user()?,
+            path: path.into(),
+            tags: tags.clone(),
+        },
+        ["bookmarks", "import", path] => Command::BookmarksImport {
+            user: user()?,
+            path: path.into(),
+        },
+        ["backup", path] => Command::Backup { path: path.into() },
+        _ => return Err(format!("无法识别的命令: {}", words.join(" "))),
+    };
+    if admin && !matches!(command, Command::CreateUser { .. }) {
+        return Err("--admin 只能用于 create-user".into());
+    }
+    if !tags.is_empty() && !matches!(command, Command::MemImport { .. }) {
+        return Err("--tag 只能用于 mem import".into());
+    }
+    Ok(command)
+}
+
+/// 执行除 `serve` / `help` 外的子命令
+pub async fn run(command: Command, config: &Config) -> Result<(), Box<dyn Error>> {
+    let pool = SqlitePool::connect(&config.database_url).await?;
+    let applied = db::migrate::run(&pool).await?;
+    if command == Command::Migrate {
+        println!(
+            "数据库 schema 版本 {}（本次应用 {} 个迁移）",
+            db::migrate::current_version(&pool).await?,
+            applied
+        );
+        return Ok(());
+    }
+    if let Command::Backup { path } = &command {
+        return backup(&pool, path).await;
+    }
+
+    modules::mem::config::import_legacy_file(&pool, &config.mem_config_path).await?;
+    let state = AppState::new(Arc::new(pool), storage::from_config(config)?, config);
+
+    match command {
+        Command::CreateUser { name, admin } => {
+            let password = read_password()?;
+            let role = admin.then_some("admin");
+            let user = state.user.create(name, password, role).await?;
+            println!(
+                "已创建用户 {}（id {}，角色 {}）",
+                user.name, user.id, user.role
+            );
+        }
+        Command::ResetPassword { name } => {
+            let id = user_id(&state, &name).await?;
+            // 操作者记为 0：命令行不对应任何账号
+            let temporary = state.user.admin_reset_password(0, id).await?;
+            println!("临时密码: {temporary}");
+            println!("用户登录后须先修改密码");
+        }
+        Command::Export { user, path } => {
+            let id = user_id(&state, &user).await?;
+            let mut archive = state.account.export(id).await?;
+            let size = std::io::copy(&mut archive, &mut create_new(&path)?)?;
+            println!("已导出到 {}（{} 字节）", path.display(), size);
+        }
+        Command::Import { user, path } => {
+            let id = user_id(&state, &user).await?;
+            let report = state
+                .account
+                .import(id, std::fs::File::open(&path)?)
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Command::MemOptimize { user } => {
+            let id = user_id(&state, &user).await?;
+            match state.mem.optimize_params(id).await? {
+                Some(params) => println!("FSRS 参数已更新（{} 个）: {params:?}", params.len()),
+                None => println!("复习记录不足，未更新参数"),
+            }
+        }
+        Command::MemImport { user, path, tags } => {
+            let id = user_id(&state, &user).await?;
+            let ext = extension(&path);
+            if ext == "apkg" || ext == "colpkg" {
+                let report = state
+                    .mem
+                    .import_anki(&state.media, id, std::fs::File::open(&path)?, &tags)
+                    .await?;
+                for e in &report.errors {
+                    eprintln!("跳过: {e}");
+                }
+                println!(
+                    "已导入 {} 张卡片、{} 条复习记录、{} 个媒体文件",
+                    report.imported, report.reviews, report.media_files
+                );
+                return Ok(());
+            }
+            let data = std::fs::read_to_string(&path)?;
+            let (count, errors) = match ext.as_str() {
+                "csv" => state.mem.import_csv(&data, id, &tags).await,
+                "psv" => state.mem.import_psv(&data, id, &tags).await,
+                "json" => {
+                    let items: Vec<JsonMemItem> = serde_json::from_str(&data)?;
+                    state.mem.import_json(&items, id, &tags).await
+                }
+                ext => {
+                    return Err(format!(
+                        "不支持的文件类型: .{ext}（须为 csv / psv / json / apkg / colpkg）"
+                    )
+                    .into());
+                }
+            }
+            .map_err(|e| e.to_string())?;
+            for e in &errors {
+                eprintln!("跳过: {e}");
+            }
+            println!("已导入 {count} 条");
+        }
+        Command::BookmarksImport { user, path } => {
+            let id = user_id(&state, &user).await?;
+            let html = std::fs::read_to_string(&path)?;
+            let result = state.bookmark.import_netscape_html(id, &html).await?;
+            println!(
+                "共 {} 条：新建 {}，合并 {}",
+                result.total, result.created, result.merged
+            );
+        }
+        Command::Serve | Command::Help | Command::Migrate | Command::Backup { .. } => {}
+    }
+    Ok(())
+}
+
+/// `VACUUM INTO` 生成一致的快照，不阻塞正在运行的服务写入
+async fn backup(pool: &SqlitePool, path: &Path) -> Result<(), Box<dyn Error>> {
+    if path.exists() {
+        return Err(format!("{} 已存在", path.display()).into());
+    }
+    let target = path.to_str().ok_or("备份路径须为 UTF-8")?;
+    sqlx::query("VACUUM INTO ?")
+        .bind(target)
+        .execute(pool)
+        .await?;
+    println!("已备份到 {}", path.display());
+    Ok(())
+}
+
+/// 新建输出文件，与 backup 一样拒绝覆盖已有文件
+fn create_new(path: &Path) -> Result<std::fs::File, Box<dyn Error>> {
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            Err(format!("{} 已存在", path.display()).into())
+        }
+        file => Ok(file?),
+    }
+}
+
+async fn user_id(state: &AppState, name: &str) -> Result<i32, Box<dyn Error>> {
+    match state.user.find_by_name(name).await? {
+        Some(user) => Ok(user.id),
+        None => Err(format!("用户不存在: {name}").into()),
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// 从标准输入读取一行作为密码（可用管道传入）
+fn read_password() -> Result<String, Box<dyn Error>> {
+    eprint!("密码: ");
+    std::io::stderr().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn args(s: &str) -> Result<Command, String> {
+        parse(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults_to_serve() {
+        assert_eq!(args("").unwrap(), Command::Serve);
+        assert_eq!(args("serve").unwrap(), Command::Serve);
+        assert_eq!(args("mem --help").unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn parses_subcommands_and_options() {
+        assert_eq!(
+            args("create-user alice --admin").unwrap(),
+            Command::CreateUser {
+                name: "alice".into(),
+                admin: true
+            }
+        );
+        assert_eq!(
+            args("mem import cards.csv --tag 日语 -u bob --tag N3").unwrap(),
+            Command::MemImport {
+                user: "bob".into(),
+                path: "cards.csv".into(),
+                tags: vec!["日语".into(), "N3".into()],
+            }
+        );
+        assert_eq!(
+            args("backup /tmp/b.db").unwrap(),
+            Command::Backup {
+                path: "/tmp/b.db".into()
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_usage() {
+        assert!(args("export out.zip").unwrap_err().contains("--user"));
+        assert!(args("migrate --admin").is_err());
+        assert!(args("export out.zip --user a --tag x").is_err());
+        assert!(args("mem").is_err());
+        assert!(args("mem optimize").unwrap_err().contains("--user"));
+        assert!(args("serve --verbose").is_err());
+        assert!(args("create-user").is_err());
+    }
+
+    #[tokio::test]
+    async fn backup_writes_a_copy() {
+        let dir = std::env::temp_dir().join(format!("brainbow-backup-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // 内存库的 VACUUM INTO 仍写入内存，须用文件库测试
+        let source = format!("sqlite:{}?mode=rwc", dir.join("source.db").display());
+        let pool = SqlitePool::connect(&source).await.unwrap();
+        db::migrate::run(&pool).await.unwrap();
+        let path = dir.join("backup.db");
+        backup(&pool, &path).await.unwrap();
+
+        let copy = SqlitePool::connect(&format!("sqlite:{}", path.display()))
+            .await
+            .unwrap();
+        assert_eq!(
+            db::migrate::current_version(&copy).await.unwrap(),
+            db::migrate::current_version(&pool).await.unwrap()
+        );
+        // 不覆盖已有文件
+        assert!(backup(&pool, &path).await.is_err());
+        copy.close().await;
+        pool.close().await;
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn export_target_must_be_new() {
+        let path =
+            std::env::temp_dir().join(format!("brainbow-export-{}.zip", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"keep").unwrap();
+        let err = create_new(&path).unwrap_err();
+        assert!(err.to_string().contains("已存在"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep");
+
+        std::fs::remove_file(&path).unwrap();
+        create_new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -24,12 +24,59 @@ pub struct Config {
     /// 绑定地址
     pub bind_host: IpAddr,
 
-    /// 记忆系统配置路径
+    /// 旧版全局记忆配置文件路径，存在时启动时迁入数据库（按用户保存）
     pub mem_config_path: PathBuf,
 
-    /// 上传目录（预留，当前使用 `uploads` 硬编码）
-    #[allow(dead_code)]
+    /// 公开接口（登录、注册、favicon、conv）每 IP 每分钟请求数，0 为不限
+    pub rate_limit_public_per_min: u32,
+
+    /// 登录后接口每 IP + 用户每分钟请求数，0 为不限
+    pub rate_limit_authed_per_min: u32,
+
+    /// 同一 IP 对同一用户名连续登录失败多少次后开始锁定，0 为不锁定
+    pub login_lockout_threshold: u32,
+
+    /// 同一用户名（不论来自哪些 IP）累计登录失败多少次后锁定，0 为不锁定
+    pub login_account_lockout_threshold: u32,
+
+    /// 是否信任 `X-Forwarded-For` / `X-Real-IP`（部署在反向代理后时开启）
+    pub trust_proxy: bool,
+
+    /// 后台任务 worker 数
+    pub job_workers: usize,
+
+    /// `/metrics` 的 Bearer token，未设置时 `/metrics` 不对外开放
+    pub metrics_token: Option<String>,
+
+    /// 回收站保留天数，超过后由 `trash_purge` 任务彻底删除；0 为不自动清理
+    pub trash_retention_days: u32,
+
+    /// 文件存储后端（`STORAGE_BACKEND`：`local` 或 `s3`，默认 `local`）
+    pub storage_backend: StorageBackend,
+
+    /// 本地存储的根目录（默认 `uploads`）
     pub upload_dir: PathBuf,
+
+    /// S3 兼容存储配置，`storage_backend` 为 `s3` 时使用
+    pub s3: S3Config,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Local,
+    S3,
+}
+
+/// S3 兼容存储配置（`S3_*` 环境变量）
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// 服务地址，如 `https://s3.amazonaws.com` 或 `http://127.0.0.1:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    /// 签名用区域（默认 `us-east-1`，MinIO 一般无需修改）
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
 }
 
 impl Config {
@@ -79,9 +126,58 @@ impl Config {
                 .map(PathBuf::from)
                 .unwrap_or_else(|_| PathBuf::from("mem_config.json")),
 
+            rate_limit_public_per_min: vars("RATE_LIMIT_PUBLIC_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+
+            rate_limit_authed_per_min: vars("RATE_LIMIT_AUTHED_PER_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(600),
+
+            login_lockout_threshold: vars("LOGIN_LOCKOUT_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5),
+
+            login_account_lockout_threshold: vars("LOGIN_ACCOUNT_LOCKOUT_THRESHOLD")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50),
+
+            trust_proxy: vars("TRUST_PROXY")
+                .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+
+            job_workers: vars("JOB_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+
+            metrics_token: vars("METRICS_TOKEN").ok().filter(|t| !t.is_empty()),
+
+            trash_retention_days: vars("TRASH_RETENTION_DAYS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(30),
+
+            storage_backend: match vars("STORAGE_BACKEND").as_deref().map(str::trim) {
+                Ok("s3") => StorageBackend::S3,
+                _ => StorageBackend::Local,
+            },
+
             upload_dir: vars("UPLOAD_DIR")
                 .map(PathBuf::from)
                 .unwrap_or_else(|_| PathBuf::from("uploads")),
+
+            s3: S3Config {
+                endpoint: vars("S3_ENDPOINT").unwrap_or_else(|_| "https://s3.amazonaws.com".into()),
+                bucket: vars("S3_BUCKET").unwrap_or_default(),
+                region: vars("S3_REGION").unwrap_or_else(|_| "us-east-1".into()),
+                access_key_id: vars("S3_ACCESS_KEY_ID").unwrap_or_default(),
+                secret_access_key: vars("S3_SECRET_ACCESS_KEY").unwrap_or_default(),
+            },
         }
     }
 }
@@ -123,6 +219,44 @@ mod tests {
         assert_eq!(cfg.service_port, 3000);
     }
 
+    #[test]
+    fn config_parses_rate_limits() {
+        let vars = vars_with(&[
+            ("RATE_LIMIT_PUBLIC_PER_MIN", "0"),
+            ("LOGIN_LOCKOUT_THRESHOLD", "10"),
+            ("LOGIN_ACCOUNT_LOCKOUT_THRESHOLD", "100"),
+            ("TRUST_PROXY", "true"),
+            ("JOB_WORKERS", "4"),
+            ("METRICS_TOKEN", "t0ken"),
+            ("TRASH_RETENTION_DAYS", "0"),
+        ]);
+        let cfg = Config::from_vars(vars);
+        assert_eq!(cfg.rate_limit_public_per_min, 0);
+        assert_eq!(cfg.login_lockout_threshold, 10);
+        assert_eq!(cfg.login_account_lockout_threshold, 100);
+        assert!(cfg.trust_proxy);
+        assert_eq!(cfg.job_workers, 4);
+        assert_eq!(cfg.metrics_token.as_deref(), Some("t0ken"));
+        assert_eq!(cfg.trash_retention_days, 0);
+    }
+
+    #[test]
+    fn config_parses_s3_storage() {
+        let vars = vars_with(&[
+            ("STORAGE_BACKEND", "s3"),
+            ("S3_ENDPOINT", "http://127.0.0.1:9000"),
+            ("S3_BUCKET", "brainbow"),
+            ("S3_ACCESS_KEY_ID", "minio"),
+            ("S3_SECRET_ACCESS_KEY", "secret"),
+        ]);
+        let cfg = Config::from_vars(vars);
+        assert_eq!(cfg.storage_backend, StorageBackend::S3);
+        assert_eq!(cfg.s3.endpoint, "http://127.0.0.1:9000");
+        assert_eq!(cfg.s3.bucket, "brainbow");
+        assert_eq!(cfg.s3.access_key_id, "minio");
+        assert_eq!(cfg.s3.secret_access_key, "secret");
+    }
+
     #[test]
     fn config_missing_vars_use_defaults() {
         let cfg = Config::from_vars(vars_with(&[]));
@@ -131,6 +265,16 @@ mod tests {
         assert_eq!(cfg.cors_allow_origin, vec!["http://localhost:3000"]);
         assert_eq!(cfg.mem_config_path, PathBuf::from("mem_config.json"));
         assert_eq!(cfg.upload_dir, PathBuf::from("uploads"));
+        assert_eq!(cfg.storage_backend, StorageBackend::Local);
+        assert_eq!(cfg.s3.region, "us-east-1");
+        assert_eq!(cfg.rate_limit_public_per_min, 30);
+        assert_eq!(cfg.rate_limit_authed_per_min, 600);
+        assert_eq!(cfg.login_lockout_threshold, 5);
+        assert_eq!(cfg.login_account_lockout_threshold, 50);
+        assert!(!cfg.trust_proxy);
+        assert_eq!(cfg.job_workers, 2);
+        assert!(cfg.metrics_token.is_none());
+        assert_eq!(cfg.trash_retention_days, 30);
         assert!(cfg.jwt_secret.len() >= 36); // 随机 UUID
     }
 }
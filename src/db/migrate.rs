@@ -0,0 +1,408 @@
+//! 版本化 schema 迁移。
+//!
+//! 迁移脚本位于 `src/db/migrations/NNNN_name.sql`，编译期通过 `include_str!` 嵌入，
+//! 只有 up 方向。已应用的版本记录在 `schema_version` 表中（含脚本 SHA-256 校验和）。
+//!
+//! 启动时 [`run`]：
+//! 1. 数据库版本高于本二进制已知的最新版本 → 拒绝启动（防止旧版本覆盖新 schema）；
+//! 2. 已应用迁移的校验和与内嵌脚本不一致 → 拒绝启动（迁移脚本发布后不可修改）；
+//! 3. 按版本号顺序应用剩余迁移，每个迁移单独一个事务。
+//!
+//! 新增迁移：追加 `NNNN_xxx.sql` 并在 [`MIGRATIONS`] 末尾登记，版本号严格递增。
+
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+/// 一条 up 迁移
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    /// 脚本内容的 SHA-256（十六进制）
+    pub fn checksum(&self) -> String {
+        Sha256::digest(self.sql.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+}
+
+/// 全部迁移，按版本号升序
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        sql: include_str!("migrations/0001_baseline.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "sign_relation_columns",
+        sql: include_str!("migrations/0002_sign_relation_columns.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "user_ownership",
+        sql: include_str!("migrations/0003_user_ownership.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "session",
+        sql: include_str!("migrations/0004_session.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "api_token",
+        sql: include_str!("migrations/0005_api_token.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "search_fts",
+        sql: include_str!("migrations/0006_search_fts.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "audit_event",
+        sql: include_str!("migrations/0007_audit_event.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "job",
+        sql: include_str!("migrations/0008_job.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "webhook",
+        sql: include_str!("migrations/0009_webhook.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "user_admin",
+        sql: include_str!("migrations/0010_user_admin.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "soft_delete",
+        sql: include_str!("migrations/0011_soft_delete.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "sync",
+        sql: include_str!("migrations/0012_sync.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "mem_config",
+        sql: include_str!("migrations/0013_mem_config.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "mem_preset",
+        sql: include_str!("migrations/0014_mem_preset.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "mem_queued_at",
+        sql: include_str!("migrations/0015_mem_queued_at.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "row_version",
+        sql: include_str!("migrations/0016_row_version.sql"),
+    },
+];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// 数据库 schema 比当前程序新
+    SchemaTooNew {
+        db_version: i64,
+        latest: i64,
+    },
+    /// 已应用迁移的脚本被修改
+    ChecksumMismatch {
+        version: i64,
+        name: String,
+    },
+    /// 数据库中记录了本程序不认识的版本
+    UnknownVersion(i64),
+    Db(sqlx::Error),
+}
+
+impl std::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SchemaTooNew { db_version, latest } => write!(
+                f,
+                "数据库 schema 版本 {db_version} 高于程序支持的 {latest}，请升级程序"
+            ),
+            Self::ChecksumMismatch { version, name } => {
+                write!(f, "迁移 {version:04}_{name} 已应用但脚本内容已变更")
+            }
+            Self::UnknownVersion(v) => write!(f, "数据库记录了未知的迁移版本 {v}"),
+            Self::Db(e) => write!(f, "迁移失败: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<sqlx::Error> for MigrationError {
+    fn from(e: sqlx::Error) -> Self {
+        MigrationError::Db(e)
+    }
+}
+
+/// 应用全部待执行迁移，返回本次应用的数量。
+pub async fn run(pool: &SqlitePool) -> Result<usize, MigrationError> {
+    run_with(pool, MIGRATIONS).await
+}
+
+/// 当前数据库 schema 版本（未迁移过为 0）
+pub async fn current_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    ensure_version_table(pool).await?;
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+        .fetch_one(pool)
+        .await
+}
+
+async fn ensure_version_table(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMP DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn run_with(pool: &SqlitePool, migrations: &[Migration]) -> Result<usize, MigrationError> {
+    ensure_version_table(pool).await?;
+
+    let applied: Vec<(i64, String)> =
+        sqlx::query_as("SELECT version, checksum FROM schema_version ORDER BY version")
+            .fetch_all(pool)
+            .await?;
+
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    if let Some((db_version, _)) = applied.last()
+        && *db_version > latest
+    {
+        return Err(MigrationError::SchemaTooNew {
+            db_version: *db_version,
+            latest,
+        });
+    }
+
+    for (version, checksum) in &applied {
+        let m = migrations
+            .iter()
+            .find(|m| m.version == *version)
+            .ok_or(MigrationError::UnknownVersion(*version))?;
+        if m.checksum() != *checksum {
+            return Err(MigrationError::ChecksumMismatch {
+                version: m.version,
+                name: m.name.to_string(),
+            });
+        }
+    }
+
+    let mut count = 0;
+    for m in migrations {
+        if applied.iter().any(|(v, _)| *v == m.version) {
+            continue;
+        }
+        let mut tx = pool.begin().await?;
+        if let Err(e) = sqlx::raw_sql(m.sql).execute(&mut *tx).await {
+            // 显式回滚，保证返回前连接已释放写锁
+            tx.rollback().await?;
+            return Err(e.into());
+        }
+        sqlx::query("INSERT INTO schema_version (version, name, checksum) VALUES (?, ?, ?)")
+            .bind(m.version)
+            .bind(m.name)
+            .bind(m.checksum())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        tracing::info!("已应用迁移 {:04}_{}", m.version, m.name);
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    async fn pool() -> SqlitePool {
+        SqlitePool::connect("sqlite::memory:").await.unwrap()
+    }
+
+    #[test]
+    fn versions_strictly_increasing() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version);
+        }
+        assert_eq!(MIGRATIONS[0].version, 1);
+    }
+
+    #[tokio::test]
+    async fn fresh_database_applies_all() {
+        let pool = pool().await;
+        let n = run(&pool).await.unwrap();
+        assert_eq!(n, MIGRATIONS.len());
+        assert_eq!(
+            current_version(&pool).await.unwrap(),
+            MIGRATIONS.last().unwrap().version
+        );
+    }
+
+    #[tokio::test]
+    async fn second_run_is_noop() {
+        let pool = pool().await;
+        run(&pool).await.unwrap();
+        assert_eq!(run(&pool).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn sign_table_has_relation_columns() {
+        let pool = pool().await;
+        run(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO signifier_signified (signifier, signified, weight, relation_type, created_at) VALUES ('a', 'b', 0.5, 'direct', '2024-01-01T00:00:00Z')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn legacy_database_with_existing_tables_is_adopted() {
+        let pool = pool().await;
+        // 模拟迁移系统引入前由 create_tables 建好的库
+        sqlx::raw_sql(MIGRATIONS[0].sql)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        run(&pool).await.unwrap();
+        let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(users, 1);
+    }
+
+    #[tokio::test]
+    async fn legacy_rows_are_assigned_to_first_admin() {
+        let pool = pool().await;
+        run_with(&pool, &MIGRATIONS[..2]).await.unwrap();
+        sqlx::query(
+            "INSERT INTO user (name, password_hash, role) VALUES ('u', 'x', 'user'), ('root', 'x', 'admin')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO bookmark (url, title) VALUES ('https://a.example', 'a')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO bookmark_tag (name) VALUES ('rust')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO bookmark_tag_rel (bookmark_id, tag_id) VALUES (1, 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        run(&pool).await.unwrap();
+        let owner: i32 = sqlx::query_scalar("SELECT user_id FROM bookmark WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(owner, 2);
+        // 重建 bookmark_tag 时关联不能丢
+        let rels: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM bookmark_tag_rel")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rels, 1);
+    }
+
+    #[tokio::test]
+    async fn refuses_newer_schema() {
+        let pool = pool().await;
+        run(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO schema_version (version, name, checksum) VALUES (9999, 'future', '')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let err = run(&pool).await.unwrap_err();
+        assert!(matches!(
+            err,
+            MigrationError::SchemaTooNew {
+                db_version: 9999,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn detects_modified_migration() {
+        let pool = pool().await;
+        let v1 = [Migration {
+            version: 1,
+            name: "t",
+            sql: "CREATE TABLE t (id INTEGER)",
+        }];
+        run_with(&pool, &v1).await.unwrap();
+
+        let edited = [Migration {
+            version: 1,
+            name: "t",
+            sql: "CREATE TABLE t (id INTEGER, x TEXT)",
+        }];
+        let err = run_with(&pool, &edited).await.unwrap_err();
+        assert!(matches!(
+            err,
+            MigrationError::ChecksumMismatch { version: 1, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_migration_is_rolled_back() {
+        let pool = pool().await;
+        let bad = [Migration {
+            version: 1,
+            name: "bad",
+            sql: "CREATE TABLE ok_table (id INTEGER); SELECT * FROM missing_table;",
+        }];
+        assert!(run_with(&pool, &bad).await.is_err());
+        assert_eq!(current_version(&pool).await.unwrap(), 0);
+        let exists: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'ok_table'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(exists, 0);
+    }
+}
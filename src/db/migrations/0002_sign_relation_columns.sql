@@ -0,0 +1,12 @@
+-- 0002 能指所指：补齐 `sign::repository` 已在读写的权重、关系类型和创建时间列。
+
+ALTER TABLE signifier_signified ADD COLUMN weight REAL;
+
+ALTER TABLE signifier_signified ADD COLUMN relation_type TEXT;
+
+-- ADD COLUMN 不允许非常量默认值，已有行单独回填
+ALTER TABLE signifier_signified ADD COLUMN created_at TIMESTAMP;
+
+UPDATE signifier_signified
+SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+WHERE created_at IS NULL;
@@ -0,0 +1,93 @@
+-- 0003 数据归属：bookmark / reading / text / onto / sign / mem 按用户隔离。
+--
+-- 已有数据归属到第一个管理员（没有管理员时取 id 最小的用户）。
+
+-- ── 直接加列的表 ──
+
+ALTER TABLE bookmark ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE text_note ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE chunk ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE mem ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE onto ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE signifier_signified ADD COLUMN user_id INTEGER REFERENCES user(id);
+ALTER TABLE reading_article ADD COLUMN user_id INTEGER REFERENCES user(id);
+
+-- ── bookmark_tag：name 唯一 → (name, user_id) 唯一，需重建 ──
+-- 先搬走 bookmark_tag_rel，避免 DROP bookmark_tag 时级联删除关联
+
+CREATE TEMP TABLE bookmark_tag_rel_backup AS SELECT bookmark_id, tag_id FROM bookmark_tag_rel;
+DROP TABLE bookmark_tag_rel;
+
+CREATE TABLE bookmark_tag_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    user_id INTEGER,
+    created_at TIMESTAMP DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    FOREIGN KEY (user_id) REFERENCES user(id),
+    UNIQUE(name, user_id)
+);
+INSERT INTO bookmark_tag_new (id, name, created_at) SELECT id, name, created_at FROM bookmark_tag;
+DROP TABLE bookmark_tag;
+ALTER TABLE bookmark_tag_new RENAME TO bookmark_tag;
+
+CREATE TABLE bookmark_tag_rel (
+    bookmark_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (bookmark_id, tag_id),
+    FOREIGN KEY (bookmark_id) REFERENCES bookmark(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES bookmark_tag(id) ON DELETE CASCADE
+);
+INSERT INTO bookmark_tag_rel (bookmark_id, tag_id) SELECT bookmark_id, tag_id FROM bookmark_tag_rel_backup;
+DROP TABLE bookmark_tag_rel_backup;
+CREATE INDEX IF NOT EXISTS idx_bookmark_tag_rel_tag ON bookmark_tag_rel(tag_id);
+
+-- ── reading_user_word：word 唯一 → (word, user_id) 唯一，需重建 ──
+
+CREATE TABLE reading_user_word_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    word TEXT NOT NULL,
+    user_id INTEGER,
+    status TEXT NOT NULL DEFAULT 'unknown',
+    unknown_count INTEGER NOT NULL DEFAULT 0,
+    known_count INTEGER NOT NULL DEFAULT 0,
+    first_seen_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES user(id),
+    UNIQUE(word, user_id)
+);
+INSERT INTO reading_user_word_new (id, word, status, unknown_count, known_count, first_seen_at, updated_at)
+    SELECT id, word, status, unknown_count, known_count, first_seen_at, updated_at FROM reading_user_word;
+DROP TABLE reading_user_word;
+ALTER TABLE reading_user_word_new RENAME TO reading_user_word;
+
+-- ── 已有数据归属 ──
+
+CREATE TEMP TABLE owner AS
+    SELECT COALESCE(
+        (SELECT MIN(id) FROM user WHERE role = 'admin'),
+        (SELECT MIN(id) FROM user)
+    ) AS id;
+
+UPDATE bookmark SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE bookmark_tag SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE text_note SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE chunk SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE mem SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE onto SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE signifier_signified SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE reading_article SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+UPDATE reading_user_word SET user_id = (SELECT id FROM owner) WHERE user_id IS NULL;
+
+DROP TABLE owner;
+
+-- ── 索引 ──
+
+CREATE INDEX IF NOT EXISTS idx_bookmark_user ON bookmark(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bookmark_tag_user ON bookmark_tag(user_id);
+CREATE INDEX IF NOT EXISTS idx_text_note_user ON text_note(user_id);
+CREATE INDEX IF NOT EXISTS idx_chunk_user ON chunk(user_id);
+CREATE INDEX IF NOT EXISTS idx_mem_user_due ON mem(user_id, due_at);
+CREATE INDEX IF NOT EXISTS idx_onto_user ON onto(user_id);
+CREATE INDEX IF NOT EXISTS idx_sign_user ON signifier_signified(user_id);
+CREATE INDEX IF NOT EXISTS idx_reading_article_user ON reading_article(user_id);
+CREATE INDEX IF NOT EXISTS idx_reading_user_word_user ON reading_user_word(user_id);
@@ -0,0 +1,27 @@
+-- 0004 会话：短期 access token + 可轮换的 refresh token，支持服务端吊销。
+
+-- 每次登录一条会话；refresh token 只存 SHA-256
+CREATE TABLE IF NOT EXISTS session (
+    id TEXT PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    refresh_token_hash TEXT NOT NULL UNIQUE,
+    -- 上一个 refresh token，被再次使用说明已泄露，整个会话作废
+    prev_token_hash TEXT,
+    -- 当前 access token 的 jti 与过期时间，吊销时写入黑名单
+    access_jti TEXT NOT NULL,
+    access_exp INTEGER NOT NULL,
+    user_agent TEXT NOT NULL DEFAULT '',
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    last_used_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_user ON session(user_id);
+
+-- 已吊销但尚未过期的 access token；exp 为 Unix 秒，过期后可清理
+CREATE TABLE IF NOT EXISTS revoked_jti (
+    jti TEXT PRIMARY KEY,
+    exp INTEGER NOT NULL
+);
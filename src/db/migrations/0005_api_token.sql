@@ -0,0 +1,19 @@
+-- 0005 个人访问令牌：供脚本 / 集成调用 API，按作用域授权，可随时吊销。
+
+CREATE TABLE IF NOT EXISTS api_token (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    -- 明文只在创建时返回一次，库里只存 SHA-256
+    token_hash TEXT NOT NULL UNIQUE,
+    -- 明文前若干位，便于在列表里辨认
+    prefix TEXT NOT NULL,
+    -- 空格分隔，如 "mem:read bookmarks:write"
+    scopes TEXT NOT NULL,
+    expires_at TEXT,
+    last_used_at TEXT,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_token_user ON api_token(user_id);
@@ -0,0 +1,215 @@
+-- 0006 全文检索：cards / chunk / bookmark / task / text_note / reading_article / conv 的 FTS5 索引。
+--
+-- 统一使用 trigram 分词：不依赖空格切词，中日韩文本按任意子串即可命中。
+-- 少于 3 个字符的检索词无法走 MATCH，由 `search` 模块回退为 LIKE。
+--
+-- 有整数主键的源表用外部内容表（content=...），索引只存词条；
+-- conv / articles / conv_titles 没有稳定的整数主键，合并进一张自带内容的 conv_fts。
+
+-- ── card ──
+
+CREATE VIRTUAL TABLE card_fts USING fts5(
+    content,
+    content = 'card', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER card_fts_ai AFTER INSERT ON card BEGIN
+    INSERT INTO card_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER card_fts_ad AFTER DELETE ON card BEGIN
+    INSERT INTO card_fts (card_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER card_fts_au AFTER UPDATE OF content ON card BEGIN
+    INSERT INTO card_fts (card_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    INSERT INTO card_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+INSERT INTO card_fts (card_fts) VALUES ('rebuild');
+
+-- ── chunk（mem 的 cue / target） ──
+
+CREATE VIRTUAL TABLE chunk_fts USING fts5(
+    content,
+    content = 'chunk', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER chunk_fts_ai AFTER INSERT ON chunk BEGIN
+    INSERT INTO chunk_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+CREATE TRIGGER chunk_fts_ad AFTER DELETE ON chunk BEGIN
+    INSERT INTO chunk_fts (chunk_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+
+CREATE TRIGGER chunk_fts_au AFTER UPDATE OF content ON chunk BEGIN
+    INSERT INTO chunk_fts (chunk_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    INSERT INTO chunk_fts (rowid, content) VALUES (new.id, new.content);
+END;
+
+INSERT INTO chunk_fts (chunk_fts) VALUES ('rebuild');
+
+-- 检索命中 chunk 后按正反面回查所属 mem
+CREATE INDEX IF NOT EXISTS idx_mem_cue_chunk ON mem(cue_chunk_id);
+CREATE INDEX IF NOT EXISTS idx_mem_target_chunk ON mem(target_chunk_id);
+
+-- ── bookmark ──
+
+CREATE VIRTUAL TABLE bookmark_fts USING fts5(
+    title, url, description,
+    content = 'bookmark', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER bookmark_fts_ai AFTER INSERT ON bookmark BEGIN
+    INSERT INTO bookmark_fts (rowid, title, url, description)
+    VALUES (new.id, new.title, new.url, new.description);
+END;
+
+CREATE TRIGGER bookmark_fts_ad AFTER DELETE ON bookmark BEGIN
+    INSERT INTO bookmark_fts (bookmark_fts, rowid, title, url, description)
+    VALUES ('delete', old.id, old.title, old.url, old.description);
+END;
+
+CREATE TRIGGER bookmark_fts_au AFTER UPDATE OF title, url, description ON bookmark BEGIN
+    INSERT INTO bookmark_fts (bookmark_fts, rowid, title, url, description)
+    VALUES ('delete', old.id, old.title, old.url, old.description);
+    INSERT INTO bookmark_fts (rowid, title, url, description)
+    VALUES (new.id, new.title, new.url, new.description);
+END;
+
+INSERT INTO bookmark_fts (bookmark_fts) VALUES ('rebuild');
+
+-- ── task ──
+
+CREATE VIRTUAL TABLE task_fts USING fts5(
+    title, description,
+    content = 'task', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER task_fts_ai AFTER INSERT ON task BEGIN
+    INSERT INTO task_fts (rowid, title, description) VALUES (new.id, new.title, new.description);
+END;
+
+CREATE TRIGGER task_fts_ad AFTER DELETE ON task BEGIN
+    INSERT INTO task_fts (task_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+END;
+
+CREATE TRIGGER task_fts_au AFTER UPDATE OF title, description ON task BEGIN
+    INSERT INTO task_fts (task_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+    INSERT INTO task_fts (rowid, title, description) VALUES (new.id, new.title, new.description);
+END;
+
+INSERT INTO task_fts (task_fts) VALUES ('rebuild');
+
+-- ── text_note ──
+
+CREATE VIRTUAL TABLE text_note_fts USING fts5(
+    name, content,
+    content = 'text_note', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER text_note_fts_ai AFTER INSERT ON text_note BEGIN
+    INSERT INTO text_note_fts (rowid, name, content) VALUES (new.id, new.name, new.content);
+END;
+
+CREATE TRIGGER text_note_fts_ad AFTER DELETE ON text_note BEGIN
+    INSERT INTO text_note_fts (text_note_fts, rowid, name, content)
+    VALUES ('delete', old.id, old.name, old.content);
+END;
+
+CREATE TRIGGER text_note_fts_au AFTER UPDATE OF name, content ON text_note BEGIN
+    INSERT INTO text_note_fts (text_note_fts, rowid, name, content)
+    VALUES ('delete', old.id, old.name, old.content);
+    INSERT INTO text_note_fts (rowid, name, content) VALUES (new.id, new.name, new.content);
+END;
+
+INSERT INTO text_note_fts (text_note_fts) VALUES ('rebuild');
+
+-- ── reading_article ──
+
+CREATE VIRTUAL TABLE reading_article_fts USING fts5(
+    title, content, notes,
+    content = 'reading_article', content_rowid = 'id', tokenize = 'trigram'
+);
+
+CREATE TRIGGER reading_article_fts_ai AFTER INSERT ON reading_article BEGIN
+    INSERT INTO reading_article_fts (rowid, title, content, notes)
+    VALUES (new.id, new.title, new.content, new.notes);
+END;
+
+CREATE TRIGGER reading_article_fts_ad AFTER DELETE ON reading_article BEGIN
+    INSERT INTO reading_article_fts (reading_article_fts, rowid, title, content, notes)
+    VALUES ('delete', old.id, old.title, old.content, old.notes);
+END;
+
+CREATE TRIGGER reading_article_fts_au AFTER UPDATE OF title, content, notes ON reading_article BEGIN
+    INSERT INTO reading_article_fts (reading_article_fts, rowid, title, content, notes)
+    VALUES ('delete', old.id, old.title, old.content, old.notes);
+    INSERT INTO reading_article_fts (rowid, title, content, notes)
+    VALUES (new.id, new.title, new.content, new.notes);
+END;
+
+INSERT INTO reading_article_fts (reading_article_fts) VALUES ('rebuild');
+
+-- ── conv：标题 / 问答 / 文章合并索引，source 区分来源，source_id 为来源行标识 ──
+
+CREATE VIRTUAL TABLE conv_fts USING fts5(
+    title, content,
+    conv_id UNINDEXED, source UNINDEXED, source_id UNINDEXED,
+    tokenize = 'trigram'
+);
+
+CREATE TRIGGER conv_titles_fts_ai AFTER INSERT ON conv_titles BEGIN
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (new.title, '', new.conv_id, 'title', new.id);
+END;
+
+CREATE TRIGGER conv_titles_fts_ad AFTER DELETE ON conv_titles BEGIN
+    DELETE FROM conv_fts WHERE source = 'title' AND source_id = old.id;
+END;
+
+CREATE TRIGGER conv_titles_fts_au AFTER UPDATE ON conv_titles BEGIN
+    DELETE FROM conv_fts WHERE source = 'title' AND source_id = old.id;
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (new.title, '', new.conv_id, 'title', new.id);
+END;
+
+CREATE TRIGGER conv_qa_fts_ai AFTER INSERT ON conv BEGIN
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (COALESCE(new.question, ''), COALESCE(new.answer, ''), new.conv_id, 'qa', new.qa_id);
+END;
+
+CREATE TRIGGER conv_qa_fts_ad AFTER DELETE ON conv BEGIN
+    DELETE FROM conv_fts WHERE source = 'qa' AND conv_id = old.conv_id AND source_id = old.qa_id;
+END;
+
+CREATE TRIGGER conv_qa_fts_au AFTER UPDATE ON conv BEGIN
+    DELETE FROM conv_fts WHERE source = 'qa' AND conv_id = old.conv_id AND source_id = old.qa_id;
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (COALESCE(new.question, ''), COALESCE(new.answer, ''), new.conv_id, 'qa', new.qa_id);
+END;
+
+CREATE TRIGGER articles_fts_ai AFTER INSERT ON articles BEGIN
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (new.title, COALESCE(new.content, ''), new.conv_id, 'article', new.id);
+END;
+
+CREATE TRIGGER articles_fts_ad AFTER DELETE ON articles BEGIN
+    DELETE FROM conv_fts WHERE source = 'article' AND source_id = old.id;
+END;
+
+CREATE TRIGGER articles_fts_au AFTER UPDATE ON articles BEGIN
+    DELETE FROM conv_fts WHERE source = 'article' AND source_id = old.id;
+    INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    VALUES (new.title, COALESCE(new.content, ''), new.conv_id, 'article', new.id);
+END;
+
+INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    SELECT title, '', conv_id, 'title', id FROM conv_titles;
+INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    SELECT COALESCE(question, ''), COALESCE(answer, ''), conv_id, 'qa', qa_id FROM conv;
+INSERT INTO conv_fts (title, content, conv_id, source, source_id)
+    SELECT title, COALESCE(content, ''), conv_id, 'article', id FROM articles;
@@ -0,0 +1,28 @@
+-- 0007 审计日志：各模块 service 层在增删改后追加一条事件，记录操作者、对象与字段差异。
+--
+-- 只追加：UPDATE / DELETE 由触发器拒绝。actor_id 不设外键，用户删除后记录仍保留。
+
+CREATE TABLE IF NOT EXISTS audit_event (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    actor_id INTEGER NOT NULL,
+    -- mem / task / bookmark / card / media
+    entity_type TEXT NOT NULL,
+    -- 实体标识；媒体为 stored_id，其余为整数主键
+    entity_id TEXT NOT NULL,
+    -- create / update / delete
+    action TEXT NOT NULL,
+    -- {"字段": [旧值, 新值]}，新建时旧值为 null，删除时新值为 null
+    diff TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_event_actor ON audit_event(actor_id, id);
+CREATE INDEX IF NOT EXISTS idx_audit_event_entity ON audit_event(entity_type, entity_id, id);
+
+CREATE TRIGGER audit_event_no_update BEFORE UPDATE ON audit_event BEGIN
+    SELECT RAISE(ABORT, 'audit_event 只可追加');
+END;
+
+CREATE TRIGGER audit_event_no_delete BEFORE DELETE ON audit_event BEGIN
+    SELECT RAISE(ABORT, 'audit_event 只可追加');
+END;
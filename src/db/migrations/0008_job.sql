@@ -0,0 +1,36 @@
+-- 0008 后台任务：耗时操作（参数优化、书签导入、favicon 抓取、清理）入队后由 job 模块的 worker 执行。
+--
+-- 失败按指数退避重试，超过 max_attempts 后标记 failed。
+-- 进程重启时 running 状态的任务回到 queued。
+
+CREATE TABLE IF NOT EXISTS job (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}',
+    -- 提交者；定时任务为 NULL
+    user_id INTEGER,
+    -- queued / running / succeeded / failed
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    -- 最早可执行时间（重试时推后）
+    run_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    -- 相同 key 的任务同时只排队 / 运行一个
+    dedupe_key TEXT,
+    result TEXT,
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    started_at TEXT,
+    finished_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_due ON job(status, run_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_job_dedupe ON job(dedupe_key)
+    WHERE dedupe_key IS NOT NULL AND status IN ('queued', 'running');
+
+-- 周期任务的下次触发时间，调度定义在代码中
+CREATE TABLE IF NOT EXISTS job_schedule (
+    name TEXT PRIMARY KEY,
+    next_run_at TEXT NOT NULL,
+    last_job_id INTEGER
+);
@@ -0,0 +1,41 @@
+-- 0009 出站 webhook：用户订阅领域事件，事件发生时向其 URL POST 带 HMAC 签名的 JSON。
+--
+-- 每次推送一条 webhook_delivery，经 job 队列投递并按退避重试，记录每次尝试的结果。
+
+CREATE TABLE IF NOT EXISTS webhook (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    -- HMAC-SHA256 密钥；签名需要原文，不能只存哈希
+    secret TEXT NOT NULL,
+    -- 空格分隔的事件类型，如 "task.completed mem.leeched"；"*" 为全部
+    events TEXT NOT NULL,
+    active INTEGER NOT NULL DEFAULT 1,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_user ON webhook(user_id);
+
+CREATE TABLE IF NOT EXISTS webhook_delivery (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    -- 请求体原文，签名基于它计算
+    payload TEXT NOT NULL,
+    -- pending / succeeded / failed
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- 最近一次尝试的 HTTP 状态码与响应体（截断）
+    response_status INTEGER,
+    response_body TEXT,
+    error TEXT,
+    duration_ms INTEGER,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    delivered_at TEXT,
+    FOREIGN KEY (webhook_id) REFERENCES webhook(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_delivery_webhook ON webhook_delivery(webhook_id, id);
@@ -0,0 +1,7 @@
+-- 0010 用户管理：管理员可停用账号、强制重置密码。
+--
+-- disabled = 1 的账号无法登录，已有会话与 API 令牌由 auth 中间件拒绝。
+-- must_change_password = 1 时只能修改密码，改密后清除。
+
+ALTER TABLE user ADD COLUMN disabled INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE user ADD COLUMN must_change_password INTEGER NOT NULL DEFAULT 0;
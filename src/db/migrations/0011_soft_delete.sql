@@ -0,0 +1,16 @@
+-- 0011 回收站：mem / task / bookmark / card / media 改为软删除。
+--
+-- deleted_at 非空表示已移入回收站，普通查询一律过滤；
+-- 可从回收站恢复（清空 deleted_at）或彻底删除，超过保留期的由 trash_purge 任务清理。
+
+ALTER TABLE mem ADD COLUMN deleted_at TEXT;
+ALTER TABLE task ADD COLUMN deleted_at TEXT;
+ALTER TABLE bookmark ADD COLUMN deleted_at TEXT;
+ALTER TABLE card ADD COLUMN deleted_at TEXT;
+ALTER TABLE media ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_mem_deleted_at ON mem(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_task_deleted_at ON task(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_bookmark_deleted_at ON bookmark(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_card_deleted_at ON card(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_media_deleted_at ON media(deleted_at) WHERE deleted_at IS NOT NULL;
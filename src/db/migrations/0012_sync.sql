@@ -0,0 +1,169 @@
+-- 0012 增量同步：记录 mem / chunk / tag / task / time_window / bookmark 的行级变更。
+--
+-- 每个实体在 sync_change 中只保留最近一次变更：触发器先删旧行再插入，seq 随之递增。
+-- seq 即下发给客户端的同步令牌，拉取 seq 更大的行即得到令牌之后的全部变更。
+-- deleted = 1 为物理删除的墓碑；软删除的行按 deleted_at 在读取时视为墓碑。
+-- user_id 为 NULL 表示不按用户隔离（task / time_window 与其列表接口一致）。
+--
+-- mem_tag / bookmark_tag_rel 的增删记为所属 mem / bookmark 的变更。
+
+CREATE TABLE IF NOT EXISTS sync_change (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity TEXT NOT NULL,
+    entity_id INTEGER NOT NULL,
+    user_id INTEGER,
+    deleted INTEGER NOT NULL DEFAULT 0,
+    UNIQUE (entity, entity_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_change_user ON sync_change(user_id, seq);
+
+-- 已推送的客户端变更，按 (user_id, mutation_id) 去重，重发时原样返回上次结果
+CREATE TABLE IF NOT EXISTS sync_mutation (
+    user_id INTEGER NOT NULL,
+    mutation_id TEXT NOT NULL,
+    result TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    PRIMARY KEY (user_id, mutation_id),
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
+
+-- ── mem ──
+
+CREATE TRIGGER sync_mem_ai AFTER INSERT ON mem BEGIN
+    DELETE FROM sync_change WHERE entity = 'mem' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('mem', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_mem_au AFTER UPDATE ON mem BEGIN
+    DELETE FROM sync_change WHERE entity = 'mem' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('mem', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_mem_ad AFTER DELETE ON mem BEGIN
+    DELETE FROM sync_change WHERE entity = 'mem' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, user_id, deleted) VALUES ('mem', old.id, old.user_id, 1);
+END;
+
+-- mem 已被删除时（级联）不再记录
+CREATE TRIGGER sync_mem_tag_ai AFTER INSERT ON mem_tag BEGIN
+    DELETE FROM sync_change WHERE entity = 'mem' AND entity_id = new.mem_id
+        AND EXISTS (SELECT 1 FROM mem WHERE id = new.mem_id);
+    INSERT INTO sync_change (entity, entity_id, user_id)
+        SELECT 'mem', id, user_id FROM mem WHERE id = new.mem_id;
+END;
+
+CREATE TRIGGER sync_mem_tag_ad AFTER DELETE ON mem_tag BEGIN
+    DELETE FROM sync_change WHERE entity = 'mem' AND entity_id = old.mem_id
+        AND EXISTS (SELECT 1 FROM mem WHERE id = old.mem_id);
+    INSERT INTO sync_change (entity, entity_id, user_id)
+        SELECT 'mem', id, user_id FROM mem WHERE id = old.mem_id;
+END;
+
+-- ── chunk ──
+
+CREATE TRIGGER sync_chunk_ai AFTER INSERT ON chunk BEGIN
+    DELETE FROM sync_change WHERE entity = 'chunk' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('chunk', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_chunk_au AFTER UPDATE ON chunk BEGIN
+    DELETE FROM sync_change WHERE entity = 'chunk' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('chunk', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_chunk_ad AFTER DELETE ON chunk BEGIN
+    DELETE FROM sync_change WHERE entity = 'chunk' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, user_id, deleted) VALUES ('chunk', old.id, old.user_id, 1);
+END;
+
+-- ── tag（mem 标签） ──
+
+CREATE TRIGGER sync_tag_ai AFTER INSERT ON tag BEGIN
+    DELETE FROM sync_change WHERE entity = 'tag' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('tag', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_tag_au AFTER UPDATE ON tag BEGIN
+    DELETE FROM sync_change WHERE entity = 'tag' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('tag', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_tag_ad AFTER DELETE ON tag BEGIN
+    DELETE FROM sync_change WHERE entity = 'tag' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, user_id, deleted) VALUES ('tag', old.id, old.user_id, 1);
+END;
+
+-- ── task ──
+
+CREATE TRIGGER sync_task_ai AFTER INSERT ON task BEGIN
+    DELETE FROM sync_change WHERE entity = 'task' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id) VALUES ('task', new.id);
+END;
+
+CREATE TRIGGER sync_task_au AFTER UPDATE ON task BEGIN
+    DELETE FROM sync_change WHERE entity = 'task' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id) VALUES ('task', new.id);
+END;
+
+CREATE TRIGGER sync_task_ad AFTER DELETE ON task BEGIN
+    DELETE FROM sync_change WHERE entity = 'task' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, deleted) VALUES ('task', old.id, 1);
+END;
+
+-- ── time_window ──
+
+CREATE TRIGGER sync_time_window_ai AFTER INSERT ON time_window BEGIN
+    DELETE FROM sync_change WHERE entity = 'time_window' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id) VALUES ('time_window', new.id);
+END;
+
+CREATE TRIGGER sync_time_window_au AFTER UPDATE ON time_window BEGIN
+    DELETE FROM sync_change WHERE entity = 'time_window' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id) VALUES ('time_window', new.id);
+END;
+
+CREATE TRIGGER sync_time_window_ad AFTER DELETE ON time_window BEGIN
+    DELETE FROM sync_change WHERE entity = 'time_window' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, deleted) VALUES ('time_window', old.id, 1);
+END;
+
+-- ── bookmark ──
+
+CREATE TRIGGER sync_bookmark_ai AFTER INSERT ON bookmark BEGIN
+    DELETE FROM sync_change WHERE entity = 'bookmark' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('bookmark', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_bookmark_au AFTER UPDATE ON bookmark BEGIN
+    DELETE FROM sync_change WHERE entity = 'bookmark' AND entity_id = new.id;
+    INSERT INTO sync_change (entity, entity_id, user_id) VALUES ('bookmark', new.id, new.user_id);
+END;
+
+CREATE TRIGGER sync_bookmark_ad AFTER DELETE ON bookmark BEGIN
+    DELETE FROM sync_change WHERE entity = 'bookmark' AND entity_id = old.id;
+    INSERT INTO sync_change (entity, entity_id, user_id, deleted) VALUES ('bookmark', old.id, old.user_id, 1);
+END;
+
+CREATE TRIGGER sync_bookmark_tag_rel_ai AFTER INSERT ON bookmark_tag_rel BEGIN
+    DELETE FROM sync_change WHERE entity = 'bookmark' AND entity_id = new.bookmark_id
+        AND EXISTS (SELECT 1 FROM bookmark WHERE id = new.bookmark_id);
+    INSERT INTO sync_change (entity, entity_id, user_id)
+        SELECT 'bookmark', id, user_id FROM bookmark WHERE id = new.bookmark_id;
+END;
+
+CREATE TRIGGER sync_bookmark_tag_rel_ad AFTER DELETE ON bookmark_tag_rel BEGIN
+    DELETE FROM sync_change WHERE entity = 'bookmark' AND entity_id = old.bookmark_id
+        AND EXISTS (SELECT 1 FROM bookmark WHERE id = old.bookmark_id);
+    INSERT INTO sync_change (entity, entity_id, user_id)
+        SELECT 'bookmark', id, user_id FROM bookmark WHERE id = old.bookmark_id;
+END;
+
+-- ── 已有数据：首次同步（令牌为空）即全量 ──
+
+INSERT INTO sync_change (entity, entity_id, user_id) SELECT 'chunk', id, user_id FROM chunk;
+INSERT INTO sync_change (entity, entity_id, user_id) SELECT 'tag', id, user_id FROM tag;
+INSERT INTO sync_change (entity, entity_id, user_id) SELECT 'mem', id, user_id FROM mem;
+INSERT INTO sync_change (entity, entity_id) SELECT 'task', id FROM task;
+INSERT INTO sync_change (entity, entity_id) SELECT 'time_window', id FROM time_window;
+INSERT INTO sync_change (entity, entity_id, user_id) SELECT 'bookmark', id, user_id FROM bookmark;
@@ -0,0 +1,11 @@
+-- 0013 按用户保存记忆配置（FSRS 参数、学习 / 重学步进、毕业间隔、期望回忆率）。
+--
+-- config 为 MemConfig 的 JSON，缺省字段按默认值解析；没有记录的用户使用默认配置。
+-- 取代早期的全局 mem_config.json，该文件在启动时迁入本表。
+
+CREATE TABLE IF NOT EXISTS mem_config (
+    user_id INTEGER PRIMARY KEY,
+    config TEXT NOT NULL,
+    updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
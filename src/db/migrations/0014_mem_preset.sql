@@ -0,0 +1,19 @@
+-- 0014 卡组预设：命名的调度选项与每日上限，标签可指定一个预设。
+--
+-- options 为 PresetOptions 的 JSON（步进、毕业间隔、期望回忆率、每日上限）。
+-- mem 的多个标签指向不同预设时 priority 大的优先，相同时取 id 小的。
+
+CREATE TABLE IF NOT EXISTS mem_preset (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    options TEXT NOT NULL,
+    created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    UNIQUE (user_id, name),
+    FOREIGN KEY (user_id) REFERENCES user(id) ON DELETE CASCADE
+);
+
+ALTER TABLE tag ADD COLUMN preset_id INTEGER REFERENCES mem_preset(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tag_preset ON tag(preset_id);
@@ -0,0 +1,9 @@
+-- 0015 记录新卡排入学习队列的时刻，只在当天的新卡额度中计入当天排入的卡。
+--
+-- 已在学习队列但尚未复习的卡，排入时 due_at 被设为当时，以此回填。
+
+ALTER TABLE mem ADD COLUMN queued_at TEXT;
+
+UPDATE mem SET queued_at = due_at
+WHERE state = 'learning'
+  AND NOT EXISTS (SELECT 1 FROM revlog WHERE revlog.mem_id = mem.id);
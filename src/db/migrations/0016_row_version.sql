@@ -0,0 +1,43 @@
+-- 0016 行版本号：ETag 取自 version，带 If-Match 的更新在同一条 UPDATE 中校验版本。
+--
+-- task / bookmark 的任何修改都使版本加一；显式写 version 的 UPDATE 不再重复递增。
+-- mem 的 ETag 只表示正反面内容，版本仅随所引用块的内容与块引用变化，复习不影响。
+-- bookmark_tag_rel 的增删计入所属 bookmark 的版本（标签是书签表示的一部分）。
+
+ALTER TABLE mem ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE task ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE bookmark ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- ── mem ──
+
+CREATE TRIGGER version_mem_au AFTER UPDATE OF cue_chunk_id, target_chunk_id ON mem
+WHEN new.version = old.version BEGIN
+    UPDATE mem SET version = old.version + 1 WHERE id = new.id;
+END;
+
+CREATE TRIGGER version_chunk_au AFTER UPDATE OF content ON chunk BEGIN
+    UPDATE mem SET version = version + 1
+    WHERE cue_chunk_id = new.id OR target_chunk_id = new.id;
+END;
+
+-- ── task ──
+
+CREATE TRIGGER version_task_au AFTER UPDATE ON task
+WHEN new.version = old.version BEGIN
+    UPDATE task SET version = old.version + 1 WHERE id = new.id;
+END;
+
+-- ── bookmark ──
+
+CREATE TRIGGER version_bookmark_au AFTER UPDATE ON bookmark
+WHEN new.version = old.version BEGIN
+    UPDATE bookmark SET version = old.version + 1 WHERE id = new.id;
+END;
+
+CREATE TRIGGER version_bookmark_tag_ai AFTER INSERT ON bookmark_tag_rel BEGIN
+    UPDATE bookmark SET version = version + 1 WHERE id = new.bookmark_id;
+END;
+
+CREATE TRIGGER version_bookmark_tag_ad AFTER DELETE ON bookmark_tag_rel BEGIN
+    UPDATE bookmark SET version = version + 1 WHERE id = old.bookmark_id;
+END;
@@ -15,9 +15,10 @@ use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// 统一的 API 错误响应体
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ErrorBody {
     /// HTTP 状态码标准短语，如 "Bad Request", "Not Found"
     pub code: String,
@@ -56,6 +57,24 @@ pub fn not_found(message: impl Into<String>) -> Response {
     resp(StatusCode::NOT_FOUND, message)
 }
 
+/// 429，附 `Retry-After` 头，details 中给出 `retry_after` 秒数
+pub fn too_many_requests(retry_after_secs: u64) -> Response {
+    let status = StatusCode::TOO_MANY_REQUESTS;
+    (
+        status,
+        [(
+            axum::http::header::RETRY_AFTER,
+            retry_after_secs.to_string(),
+        )],
+        axum::Json(ErrorBody {
+            code: status.canonical_reason().unwrap_or("Unknown").to_string(),
+            message: format!("请求过于频繁，请 {} 秒后重试", retry_after_secs),
+            details: Some(serde_json::json!({ "retry_after": retry_after_secs })),
+        }),
+    )
+        .into_response()
+}
+
 /// 500，自动拼 "{operation}失败: {error}"
 pub fn internal(e: impl std::fmt::Display, operation: &str) -> Response {
     resp(
@@ -114,11 +133,22 @@ pub fn deleted_or<E: std::fmt::Display>(result: Result<u64, E>, operation: &str)
 #[derive(Debug)]
 pub enum ServiceError {
     InvalidInput(String),
+    /// 凭证无效或已失效（401）
+    Unauthorized(String),
+    /// 已认证但无权执行（403），如账号已停用
+    Forbidden(String),
     NotFound(String),
     #[allow(dead_code)]
     AlreadyExists(String),
     /// 资源仍被内容引用，删除被拒绝（409）
     InUse(String),
+    /// 触发限流 / 登录锁定，值为需等待的秒数（429）
+    RateLimited(u64),
+    /// `If-Match` 与当前版本不符（412），附最新 ETag 与表示
+    PreconditionFailed {
+        etag: String,
+        current: serde_json::Value,
+    },
     Internal(String),
     Db(sqlx::Error),
 }
@@ -128,8 +158,12 @@ impl ServiceError {
     pub fn status_code(&self) -> StatusCode {
         match self {
             Self::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
             Self::AlreadyExists(_) | Self::InUse(_) => StatusCode::CONFLICT,
+            Self::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::PreconditionFailed { .. } => StatusCode::PRECONDITION_FAILED,
             Self::Internal(_) | Self::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -137,9 +171,15 @@ impl ServiceError {
     pub fn into_response(self) -> Response {
         match self {
             Self::InvalidInput(msg) => resp(StatusCode::BAD_REQUEST, msg),
+            Self::Unauthorized(msg) => resp(StatusCode::UNAUTHORIZED, msg),
+            Self::Forbidden(msg) => resp(StatusCode::FORBIDDEN, msg),
             Self::NotFound(msg) => resp(StatusCode::NOT_FOUND, msg),
             Self::AlreadyExists(msg) => resp(StatusCode::CONFLICT, msg),
             Self::InUse(msg) => resp(StatusCode::CONFLICT, msg),
+            Self::RateLimited(secs) => too_many_requests(secs),
+            Self::PreconditionFailed { etag, current } => {
+                crate::etag::precondition_failed(&etag, current)
+            }
             Self::Internal(msg) => resp(StatusCode::INTERNAL_SERVER_ERROR, msg),
             Self::Db(e) => resp(
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -153,15 +193,21 @@ impl std::fmt::Display for ServiceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::InvalidInput(msg) => write!(f, "{}", msg),
+            Self::Unauthorized(msg) => write!(f, "{}", msg),
+            Self::Forbidden(msg) => write!(f, "{}", msg),
             Self::NotFound(msg) => write!(f, "{}", msg),
             Self::AlreadyExists(msg) => write!(f, "{}", msg),
             Self::InUse(msg) => write!(f, "{}", msg),
+            Self::RateLimited(secs) => write!(f, "请求过于频繁，请 {} 秒后重试", secs),
+            Self::PreconditionFailed { .. } => write!(f, "资源已被修改"),
             Self::Internal(msg) => write!(f, "{}", msg),
             Self::Db(e) => write!(f, "数据库错误: {}", e),
         }
     }
 }
 
+impl std::error::Error for ServiceError {}
+
 impl From<sqlx::Error> for ServiceError {
     fn from(e: sqlx::Error) -> Self {
         ServiceError::Db(e)
@@ -170,7 +216,7 @@ impl From<sqlx::Error> for ServiceError {
 
 #[cfg(test)]
 mod tests {
-    #![allow(clippy::unwrap_used)]
+    #![allow(clippy::unwrap_used, clippy::io_other_error)]
     use super::*;
 
     #[test]
@@ -193,6 +239,17 @@ mod tests {
         assert_eq!(body.code, "Not Found");
     }
 
+    #[tokio::test]
+    async fn too_many_requests_sets_retry_after() {
+        let r = too_many_requests(42);
+        assert_eq!(r.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(r.headers()["retry-after"], "42");
+        let bytes = axum::body::to_bytes(r.into_body(), 1024).await.unwrap();
+        let body: ErrorBody = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body.code, "Too Many Requests");
+        assert_eq!(body.details.unwrap()["retry_after"], 42);
+    }
+
     // ── ok_or ──
 
     #[tokio::test]
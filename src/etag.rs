@@ -0,0 +1,198 @@
+//! 乐观并发控制：ETag 与 `If-Match`
+//!
+//! 可编辑资源在读取和更新的响应里带 `ETag` 头。任务、书签与记忆卡片取行的
+//! `version` 列（[`version`]），带 `If-Match` 的更新把版本条件写进同一条
+//! `UPDATE`（[`push_guard`]），没有行命中即返回 412；文本取全部标签页的
+//! SHA-256 摘要前 16 字节（[`of`]），在写事务内比较。客户端更新时把 ETag 放进
+//! `If-Match`；若服务端当前版本已变化则返回 412，`details.current` 为最新表示，
+//! `details.etag` 为最新 ETag，客户端据此合并后重试。
+//!
+//! 未带 `If-Match` 的请求照旧无条件写入，兼容旧客户端。
+
+use axum::Json;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::error::ErrorBody;
+
+/// 计算 `value` 的强 ETag（带引号）
+pub fn of<T: Serialize + ?Sized>(value: &T) -> String {
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    let hex: String = Sha256::digest(&bytes)[..16]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("\"{hex}\"")
+}
+
+/// 行版本号对应的强 ETag（带引号）
+pub fn version(v: i64) -> String {
+    format!("\"v{v}\"")
+}
+
+/// 连同行版本号一起读出的记录
+#[derive(Debug, sqlx::FromRow)]
+pub struct Versioned<T> {
+    #[sqlx(flatten)]
+    pub row: T,
+    pub version: i64,
+}
+
+/// 请求中的 `If-Match` 前置条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IfMatch {
+    /// 未带该头：无条件写入
+    #[default]
+    Absent,
+    /// `If-Match: *`：资源存在即可
+    Any,
+    /// 逗号分隔的 ETag 列表
+    Tags(Vec<String>),
+}
+
+impl IfMatch {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let values: Vec<&str> = headers
+            .get_all(header::IF_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            Self::Absent
+        } else if values.contains(&"*") {
+            Self::Any
+        } else {
+            Self::Tags(values.into_iter().map(String::from).collect())
+        }
+    }
+
+    /// 当前版本是否满足前置条件。`If-Match` 按强比较，弱 ETag（`W/`）永不匹配
+    pub fn matches(&self, current: &str) -> bool {
+        match self {
+            Self::Absent | Self::Any => true,
+            Self::Tags(tags) => tags.iter().any(|t| !t.starts_with("W/") && t == current),
+        }
+    }
+
+    /// 可接受的行版本号；`None` 表示不限版本（未带该头或 `*`）。
+    /// 弱 ETag 及不是版本号的 ETag 不匹配任何版本
+    pub fn versions(&self) -> Option<Vec<i64>> {
+        match self {
+            Self::Absent | Self::Any => None,
+            Self::Tags(tags) => Some(
+                tags.iter()
+                    .filter_map(|t| t.strip_prefix("\"v")?.strip_suffix('"')?.parse().ok())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// 在 `UPDATE … WHERE` 之后追加版本条件；`versions` 为空列表时不命中任何行
+pub fn push_guard(qb: &mut QueryBuilder<Sqlite>, versions: Option<&[i64]>) {
+    let Some(versions) = versions else {
+        return;
+    };
+    if versions.is_empty() {
+        qb.push(" AND 0");
+        return;
+    }
+    qb.push(" AND version IN (");
+    let mut list = qb.separated(", ");
+    for v in versions {
+        list.push_bind(*v);
+    }
+    qb.push(")");
+}
+
+/// 412，附当前 `ETag` 头，details 中给出 `etag` 与 `current`
+pub fn precondition_failed(etag: &str, current: serde_json::Value) -> Response {
+    let status = StatusCode::PRECONDITION_FAILED;
+    let mut resp = (
+        status,
+        Json(ErrorBody {
+            code: status.canonical_reason().unwrap_or("Unknown").to_string(),
+            message: "资源已被修改，请基于最新版本重试".into(),
+            details: Some(serde_json::json!({ "etag": etag, "current": current })),
+        }),
+    )
+        .into_response();
+    set(&mut resp, etag);
+    resp
+}
+
+/// 给任意响应附上 `ETag` 头
+pub fn with(etag: &str, resp: impl IntoResponse) -> Response {
+    let mut resp = resp.into_response();
+    set(&mut resp, etag);
+    resp
+}
+
+fn set(resp: &mut Response, etag: &str) {
+    if let Ok(v) = HeaderValue::from_str(etag) {
+        resp.headers_mut().insert(header::ETAG, v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn headers(v: &str) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert(header::IF_MATCH, HeaderValue::from_str(v).unwrap());
+        h
+    }
+
+    #[test]
+    fn etag_is_quoted_and_stable() {
+        let a = of(&serde_json::json!({ "x": 1 }));
+        assert_eq!(a, of(&serde_json::json!({ "x": 1 })));
+        assert_ne!(a, of(&serde_json::json!({ "x": 2 })));
+        assert!(a.starts_with('"') && a.ends_with('"'));
+        assert_eq!(a.len(), 34);
+    }
+
+    #[test]
+    fn parses_if_match_forms() {
+        assert_eq!(IfMatch::from_headers(&HeaderMap::new()), IfMatch::Absent);
+        assert_eq!(IfMatch::from_headers(&headers("*")), IfMatch::Any);
+        let m = IfMatch::from_headers(&headers("\"a\", \"b\""));
+        assert!(m.matches("\"a\""));
+        assert!(m.matches("\"b\""));
+        assert!(!m.matches("\"c\""));
+    }
+
+    #[test]
+    fn weak_tags_never_match() {
+        assert!(!IfMatch::from_headers(&headers("W/\"a\"")).matches("\"a\""));
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(IfMatch::Absent.versions(), None);
+        assert_eq!(IfMatch::Any.versions(), None);
+        let m = IfMatch::from_headers(&headers("\"v3\", W/\"v4\", \"abc\""));
+        assert_eq!(m.versions(), Some(vec![3]));
+        assert!(m.matches(&version(3)));
+        assert_eq!(
+            IfMatch::from_headers(&headers("\"old\"")).versions(),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn stale_returns_412_with_current() {
+        let current = serde_json::json!({ "title": "new" });
+        let resp = precondition_failed(&version(2), current);
+        assert_eq!(resp.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(resp.headers()[header::ETAG], "\"v2\"");
+    }
+}
@@ -1,15 +1,22 @@
 // 禁止生产代码使用 .unwrap()（测试模块内已 #![allow]）
 #![deny(clippy::unwrap_used)]
 
+mod archive;
 mod auth;
 mod batch;
+mod cli;
 mod config;
 mod db;
 mod error;
+mod etag;
+mod metrics;
 mod modules;
+mod openapi;
 mod pagination;
+mod rate_limit;
 mod routes;
 mod state;
+mod storage;
 
 use std::net::SocketAddr;
 use std::time::Instant;
@@ -66,22 +73,55 @@ async fn shutdown_signal() {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let command = match cli::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(msg) => {
+            eprintln!("{msg}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     init_logging();
 
     // 加载配置
     let config = Config::from_env();
 
+    match command {
+        cli::Command::Serve => serve(config).await,
+        cli::Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        command => cli::run(command, &config).await,
+    }
+}
+
+/// 启动 HTTP 服务直到收到退出信号
+async fn serve(config: Config) -> Result<(), Box<dyn std::error::Error>> {
     // 连接数据库
     let pool = SqlitePool::connect(&config.database_url).await?;
 
-    // 创建数据库表（如果不存在）
-    db::create_tables(&pool).await?;
+    // 应用 schema 迁移（数据库版本高于程序时拒绝启动）
+    let applied = db::migrate::run(&pool).await?;
+    info!(
+        "数据库 schema 版本 {}（本次应用 {} 个迁移）",
+        db::migrate::current_version(&pool).await?,
+        applied
+    );
+
+    // 旧版全局记忆配置文件迁入各用户的配置
+    if let Err(e) = modules::mem::config::import_legacy_file(&pool, &config.mem_config_path).await {
+        tracing::warn!("迁移旧版记忆配置失败: {e}");
+    }
 
-    // 加载记忆配置（FSRS 参数 + 调度配置）
-    modules::mem::config::load_and_init_mem_config(Some(&config.mem_config_path));
+    // 文件存储后端（本地目录或 S3）
+    let storage = storage::from_config(&config)?;
 
     // 创建应用状态
-    let state = AppState::new(Arc::new(pool), &config);
+    let state = AppState::new(Arc::new(pool), storage, &config);
+
+    // 启动后台任务 worker 与周期调度
+    modules::job::runner::spawn(state.clone(), config.job_workers);
 
     // 创建路由
     let app = create_router(state.clone());
@@ -118,9 +158,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
     info!("Listening on http://{}", listener.local_addr()?);
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // 带上对端地址，限流按 IP 计数
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }
@@ -0,0 +1,498 @@
+//! Prometheus 指标（`GET /metrics`，text exposition 格式）。
+//!
+//! - HTTP：按方法、路由模板、状态码统计请求数与耗时直方图，由 [`track`] 中间件采集
+//! - 连接池：SQLite 连接池当前连接数 / 空闲数 / 上限
+//! - 业务：按 `CardState` 的 mem 数、到期数、今日复习数、FSRS 参数优化次数、
+//!   按类型的媒体占用字节数、按 `TaskStatus` 的任务数
+//!
+//! HTTP 与优化次数在进程内存中，重启即清零；其余在抓取时从数据库实时查询。
+//! 需携带 `Authorization: Bearer <METRICS_TOKEN>`；未配置 `METRICS_TOKEN` 时拒绝所有抓取。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sqlx::SqlitePool;
+use subtle::ConstantTimeEq;
+
+use crate::modules::mem::model::CardState;
+use crate::modules::task::TaskStatus;
+use crate::state::AppState;
+
+/// 请求耗时直方图的桶上界（秒）
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const CARD_STATES: [CardState; 5] = [
+    CardState::New,
+    CardState::Learning,
+    CardState::Review,
+    CardState::Relearning,
+    CardState::Suspended,
+];
+
+const TASK_STATUSES: [TaskStatus; 4] = [
+    TaskStatus::Backlog,
+    TaskStatus::Active,
+    TaskStatus::Completed,
+    TaskStatus::Archived,
+];
+
+const MEDIA_TYPES: [&str; 3] = ["image", "video", "audio"];
+
+/// FSRS 参数优化的结果分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptimizerOutcome {
+    Ok,
+    /// 复习记录不足，未产出参数
+    Insufficient,
+    Error,
+}
+
+impl OptimizerOutcome {
+    const ALL: [Self; 3] = [Self::Ok, Self::Insufficient, Self::Error];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Insufficient => "insufficient",
+            Self::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RouteKey {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// 各桶内（非累计）的观测次数
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(i) = BUCKETS.iter().position(|&le| seconds <= le) {
+            self.buckets[i] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// 进程内指标注册表，挂在 [`AppState`] 上
+pub struct Metrics {
+    token: Option<String>,
+    http: Mutex<BTreeMap<RouteKey, Histogram>>,
+    optimizer_runs: Mutex<BTreeMap<OptimizerOutcome, u64>>,
+}
+
+impl Metrics {
+    /// `token` 为空时 `/metrics` 拒绝所有请求
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            token: token.filter(|t| !t.is_empty()),
+            http: Mutex::new(BTreeMap::new()),
+            optimizer_runs: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let key = RouteKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        let mut http = self.http.lock().unwrap_or_else(|e| e.into_inner());
+        http.entry(key).or_default().observe(seconds);
+    }
+
+    pub fn optimizer_run(&self, outcome: OptimizerOutcome) {
+        let mut runs = self
+            .optimizer_runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        *runs.entry(outcome).or_default() += 1;
+    }
+
+    /// 令牌按常量时间比较，避免按响应耗时逐字节猜测
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = &self.token else {
+            return false;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|v| bool::from(v.trim().as_bytes().ct_eq(token.as_bytes())))
+    }
+
+    /// 输出进程内的指标（HTTP、优化次数）
+    fn render_process(&self, out: &mut String) {
+        let http = self.http.lock().unwrap_or_else(|e| e.into_inner());
+
+        header(
+            out,
+            "brainbow_http_requests_total",
+            "counter",
+            "HTTP 请求数",
+        );
+        for (key, h) in http.iter() {
+            let _ = writeln!(
+                out,
+                "brainbow_http_requests_total{{{}}} {}",
+                key.labels(),
+                h.count
+            );
+        }
+
+        header(
+            out,
+            "brainbow_http_request_duration_seconds",
+            "histogram",
+            "HTTP 请求处理耗时",
+        );
+        for (key, h) in http.iter() {
+            let labels = key.labels();
+            let mut cumulative = 0;
+            for (le, n) in BUCKETS.iter().zip(h.buckets) {
+                cumulative += n;
+                let _ = writeln!(
+                    out,
+                    "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"{le}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                h.count
+            );
+            let _ = writeln!(
+                out,
+                "brainbow_http_request_duration_seconds_sum{{{labels}}} {}",
+                h.sum
+            );
+            let _ = writeln!(
+                out,
+                "brainbow_http_request_duration_seconds_count{{{labels}}} {}",
+                h.count
+            );
+        }
+        drop(http);
+
+        let runs = self
+            .optimizer_runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        header(
+            out,
+            "brainbow_fsrs_optimizer_runs_total",
+            "counter",
+            "FSRS 参数优化执行次数",
+        );
+        for outcome in OptimizerOutcome::ALL {
+            let n = runs.get(&outcome).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "brainbow_fsrs_optimizer_runs_total{{result=\"{}\"}} {n}",
+                outcome.as_str()
+            );
+        }
+    }
+}
+
+impl RouteKey {
+    fn labels(&self) -> String {
+        format!(
+            "method=\"{}\",route=\"{}\",status=\"{}\"",
+            escape(&self.method),
+            escape(&self.route),
+            self.status
+        )
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// 转义标签值中的 `\`、`"` 与换行
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 按路由模板（如 `/api/mem/{id}`）记录请求，须以 `route_layer` 挂载，
+/// 未命中路由的请求（静态文件回退）不计入，避免标签基数随任意路径膨胀
+pub async fn track(State(metrics): State<Arc<Metrics>>, req: Request, next: Next) -> Response {
+    let Some(route) = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+    else {
+        return next.run(req).await;
+    };
+    let method = req.method().to_string();
+    let start = Instant::now();
+    let res = next.run(req).await;
+    metrics.observe_request(
+        &method,
+        &route,
+        res.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+    res
+}
+
+/// `GET /metrics`
+pub async fn metrics_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if !state.metrics.authorized(&headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let mut out = String::new();
+    state.metrics.render_process(&mut out);
+    render_pool(&state.db, &mut out);
+    if let Err(e) = render_domain(&state.db, &mut out).await {
+        tracing::error!("采集业务指标失败: {e}");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        out,
+    )
+        .into_response()
+}
+
+fn render_pool(pool: &SqlitePool, out: &mut String) {
+    let size = pool.size() as usize;
+    let idle = pool.num_idle();
+    header(
+        out,
+        "brainbow_db_pool_connections",
+        "gauge",
+        "SQLite 连接池连接数",
+    );
+    let _ = writeln!(
+        out,
+        "brainbow_db_pool_connections{{state=\"active\"}} {}",
+        size.saturating_sub(idle)
+    );
+    let _ = writeln!(out, "brainbow_db_pool_connections{{state=\"idle\"}} {idle}");
+    header(
+        out,
+        "brainbow_db_pool_max_connections",
+        "gauge",
+        "SQLite 连接池上限",
+    );
+    let _ = writeln!(
+        out,
+        "brainbow_db_pool_max_connections {}",
+        pool.options().get_max_connections()
+    );
+}
+
+/// 抓取时从数据库统计的业务指标（全部用户合计）
+async fn render_domain(pool: &SqlitePool, out: &mut String) -> Result<(), sqlx::Error> {
+    let mems: BTreeMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT state, COUNT(*) FROM mem WHERE deleted_at IS NULL GROUP BY state",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    header(out, "brainbow_mems", "gauge", "按状态的 mem 数");
+    for state in CARD_STATES {
+        let n = mems.get(state.as_str()).copied().unwrap_or(0);
+        let _ = writeln!(out, "brainbow_mems{{state=\"{state}\"}} {n}");
+    }
+
+    let (review_due, learning_due): (i64, i64) = sqlx::query_as(
+        r#"SELECT
+               COALESCE(SUM(state = 'review'), 0),
+               COALESCE(SUM(state IN ('learning', 'relearning')), 0)
+           FROM mem
+           WHERE buried = 0 AND deleted_at IS NULL AND due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now')"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    header(out, "brainbow_mems_due", "gauge", "当前已到期的 mem 数");
+    let _ = writeln!(out, "brainbow_mems_due{{queue=\"review\"}} {review_due}");
+    let _ = writeln!(
+        out,
+        "brainbow_mems_due{{queue=\"learning\"}} {learning_due}"
+    );
+
+    let reviews_today: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM revlog WHERE review_time >= strftime('%Y-%m-%dT00:00:00Z', 'now')",
+    )
+    .fetch_one(pool)
+    .await?;
+    header(
+        out,
+        "brainbow_reviews_today",
+        "gauge",
+        "今日（UTC）复习次数",
+    );
+    let _ = writeln!(out, "brainbow_reviews_today {reviews_today}");
+
+    let media: BTreeMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT media_type, COALESCE(SUM(size_bytes), 0) FROM media GROUP BY media_type",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    header(
+        out,
+        "brainbow_media_bytes",
+        "gauge",
+        "按类型的媒体文件占用字节数",
+    );
+    for kind in MEDIA_TYPES {
+        let n = media.get(kind).copied().unwrap_or(0);
+        let _ = writeln!(out, "brainbow_media_bytes{{type=\"{kind}\"}} {n}");
+    }
+
+    let tasks: BTreeMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT status, COUNT(*) FROM task WHERE deleted_at IS NULL GROUP BY status",
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+    header(out, "brainbow_tasks", "gauge", "按状态的任务数");
+    for status in TASK_STATUSES {
+        let n = tasks.get(status.as_str()).copied().unwrap_or(0);
+        let _ = writeln!(out, "brainbow_tasks{{status=\"{}\"}} {n}", status.as_str());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    use axum::Router;
+    use axum::middleware;
+    use axum::routing::get;
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let m = Metrics::new(None);
+        m.observe_request("GET", "/api/mem/{id}", 200, 0.003);
+        m.observe_request("GET", "/api/mem/{id}", 200, 0.2);
+        m.observe_request("GET", "/api/mem/{id}", 200, 30.0);
+        m.optimizer_run(OptimizerOutcome::Ok);
+
+        let mut out = String::new();
+        m.render_process(&mut out);
+        let labels = r#"method="GET",route="/api/mem/{id}",status="200""#;
+        assert!(out.contains(&format!("brainbow_http_requests_total{{{labels}}} 3")));
+        assert!(out.contains(&format!(
+            "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"0.005\"}} 1"
+        )));
+        assert!(out.contains(&format!(
+            "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"0.25\"}} 2"
+        )));
+        assert!(out.contains(&format!(
+            "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"10\"}} 2"
+        )));
+        assert!(out.contains(&format!(
+            "brainbow_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 3"
+        )));
+        assert!(out.contains("brainbow_fsrs_optimizer_runs_total{result=\"ok\"} 1"));
+        assert!(out.contains("brainbow_fsrs_optimizer_runs_total{result=\"error\"} 0"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn token_is_checked_when_configured() {
+        let mut headers = HeaderMap::new();
+        assert!(!Metrics::new(None).authorized(&headers));
+        assert!(!Metrics::new(Some(String::new())).authorized(&headers));
+
+        let m = Metrics::new(Some("s3cret".into()));
+        assert!(!m.authorized(&headers));
+        headers.insert(header::AUTHORIZATION, "Bearer nope".parse().unwrap());
+        assert!(!m.authorized(&headers));
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret".parse().unwrap());
+        assert!(m.authorized(&headers));
+        headers.insert(header::AUTHORIZATION, "Bearer s3cret2".parse().unwrap());
+        assert!(!m.authorized(&headers));
+        // 未配置令牌时任何凭证都不放行
+        assert!(!Metrics::new(None).authorized(&headers));
+    }
+
+    #[tokio::test]
+    async fn track_labels_nested_routes_by_template() {
+        let metrics = Arc::new(Metrics::new(None));
+        let api = Router::new().route("/mem/{id}", get(|| async { "ok" }));
+        let app = Router::new()
+            .nest("/api", api)
+            .route_layer(middleware::from_fn_with_state(metrics.clone(), track))
+            .fallback(|| async { StatusCode::NOT_FOUND });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        for path in ["/api/mem/1", "/api/mem/2", "/nope"] {
+            reqwest::get(format!("http://{addr}{path}")).await.unwrap();
+        }
+
+        let http = metrics.http.lock().unwrap();
+        let keys: Vec<_> = http.keys().map(|k| k.labels()).collect();
+        assert_eq!(keys, [r#"method="GET",route="/api/mem/{id}",status="200""#]);
+        assert_eq!(http.values().next().unwrap().count, 2);
+    }
+
+    #[tokio::test]
+    async fn domain_gauges_are_zero_filled() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        let uid: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('u','x') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO task (user_id, title, status) VALUES (?, 't', 'active')")
+            .bind(uid)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut out = String::new();
+        render_domain(&pool, &mut out).await.unwrap();
+        assert!(out.contains("brainbow_mems{state=\"review\"} 0"));
+        assert!(out.contains("brainbow_mems_due{queue=\"review\"} 0"));
+        assert!(out.contains("brainbow_reviews_today 0"));
+        assert!(out.contains("brainbow_media_bytes{type=\"video\"} 0"));
+        assert!(out.contains("brainbow_tasks{status=\"active\"} 1"));
+        assert!(out.contains("brainbow_tasks{status=\"archived\"} 0"));
+    }
+}
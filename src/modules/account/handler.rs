@@ -0,0 +1,81 @@
+use axum::{
+    Extension, Json,
+    body::Body,
+    extract::{Multipart, State},
+    http::header,
+    response::IntoResponse,
+};
+use tokio_util::io::ReaderStream;
+
+use crate::archive;
+use crate::auth::Claims;
+use crate::error;
+use crate::openapi::{Binary, FileUpload};
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/export",
+    summary = "导出账号全部数据（zip）",
+    responses((status = 200, description = "成功", body = Binary, content_type = "application/zip"))
+)]
+pub async fn export_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    match state.account.export(claims.sub).await {
+        Ok(file) => {
+            let size = file.metadata().map(|m| m.len()).unwrap_or_default();
+            let body = Body::from_stream(ReaderStream::new(tokio::fs::File::from_std(file)));
+            let file_name = format!(
+                "brainbow-{}.zip",
+                chrono::Utc::now().format("%Y%m%d-%H%M%S")
+            );
+            (
+                [
+                    (header::CONTENT_TYPE, "application/zip".to_string()),
+                    (header::CONTENT_LENGTH, size.to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{file_name}\""),
+                    ),
+                ],
+                body,
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/import",
+    summary = "从 zip 归档导入到当前账号",
+    request_body(content = FileUpload, content_type = "multipart/form-data"),
+    responses((status = 200, description = "成功", body = super::model::ImportReport))
+)]
+pub async fn import_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut upload = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() != Some("file") {
+            continue;
+        }
+        match archive::spool(field).await {
+            Ok(file) => upload = Some(file),
+            Err(e) => return error::bad_request(format!("读取文件失败: {}", e)),
+        }
+    }
+    let Some(upload) = upload else {
+        return error::bad_request("缺少 'file' 字段");
+    };
+
+    match state.account.import(claims.sub, upload).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
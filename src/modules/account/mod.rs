@@ -0,0 +1,23 @@
+//! 账号整体导出 / 导入。
+//!
+//! 归档是一个 zip：`manifest.json`（格式版本、各表行数）、`data/<表名>.json`
+//! 与 `media/<类型>/<stored_id>`。导入到任意账号时所有 id 重新分配。
+
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use service::AccountService;
+use utoipa_axum::router::{OpenApiRouter, UtoipaMethodRouterExt};
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+use axum::extract::DefaultBodyLimit;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::export_handler))
+        // 归档逐块写入临时文件，不受 body 大小限制（可超过 4 GiB）
+        .routes(routes!(handler::import_handler).layer(DefaultBodyLimit::disable()))
+}
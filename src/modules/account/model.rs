@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// 归档格式标识
+pub const FORMAT: &str = "brainbow-account";
+/// 归档格式版本。表结构的增减列不需要升级（导入时按目标库的列对齐），
+/// 只有归档布局或语义变化时才递增
+pub const FORMAT_VERSION: u32 = 1;
+
+/// 归档根目录的 `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    /// 导出时数据库的迁移版本
+    pub schema_version: i64,
+    pub exported_at: DateTime<Utc>,
+    /// 导出账号的用户名
+    pub user: String,
+    /// 每张表导出的行数，数据位于 `data/<表名>.json`
+    pub tables: BTreeMap<String, usize>,
+    /// 打包的媒体文件数，位于 `media/<类型>/<stored_id>`
+    pub media_files: usize,
+    /// 数据库中有记录但磁盘上找不到文件的 stored_id
+    #[serde(default)]
+    pub missing_media: Vec<String>,
+}
+
+/// 单张表的导入结果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TableReport {
+    pub inserted: usize,
+    /// 目标账号已有同名记录（标签、生词、媒体），直接复用
+    pub merged: usize,
+    /// 引用的记录不在归档中，无法挂接而跳过
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportReport {
+    /// 归档的格式版本
+    pub version: u32,
+    pub tables: BTreeMap<String, TableReport>,
+    /// 写入磁盘的媒体文件数
+    pub media_files: usize,
+}
@@ -0,0 +1,181 @@
+//! 按表名通用地读写行数据。表名与过滤条件都来自 [`super::service`] 中的常量表，
+//! 列名在导入时先与目标库的 `PRAGMA table_info` 求交，不会拼入归档里的任意字符串。
+
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{
+    Arguments, AssertSqlSafe, Column, Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef,
+};
+
+pub type JsonRow = Map<String, Value>;
+
+#[derive(Clone)]
+pub struct AccountRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl AccountRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub async fn user_name(&self, user_id: i32) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT name FROM user WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(&*self.pool)
+            .await
+    }
+
+    /// 导出 `table` 中满足 `filter` 的全部行（`?1` 绑定为用户 id），按 rowid 排序
+    pub async fn dump(
+        &self,
+        table: &str,
+        filter: &str,
+        user_id: i32,
+    ) -> Result<Vec<JsonRow>, sqlx::Error> {
+        let rows = sqlx::query(AssertSqlSafe(format!(
+            "SELECT * FROM {table} WHERE {filter} ORDER BY rowid"
+        )))
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let mut obj = Map::new();
+                for (i, col) in row.columns().iter().enumerate() {
+                    obj.insert(col.name().to_string(), cell(row, i)?);
+                }
+                Ok(obj)
+            })
+            .collect()
+    }
+}
+
+/// 按存储类型（而非声明类型）取值
+fn cell(row: &sqlx::sqlite::SqliteRow, i: usize) -> Result<Value, sqlx::Error> {
+    let raw = row.try_get_raw(i)?;
+    if raw.is_null() {
+        return Ok(Value::Null);
+    }
+    let value = match raw.type_info().name() {
+        "INTEGER" => Value::from(row.try_get_unchecked::<i64, _>(i)?),
+        "REAL" => Value::from(row.try_get_unchecked::<f64, _>(i)?),
+        // 业务表没有 BLOB 列；万一出现按文本读出
+        _ => Value::from(row.try_get_unchecked::<String, _>(i)?),
+    };
+    Ok(value)
+}
+
+/// 目标库中表的列名
+pub async fn columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(AssertSqlSafe(format!(
+        "SELECT name FROM pragma_table_info('{table}')"
+    )))
+    .fetch_all(conn)
+    .await
+}
+
+/// 插入一行（只取 `allowed` 中的列），返回新 rowid
+pub async fn insert(
+    conn: &mut SqliteConnection,
+    table: &str,
+    row: &JsonRow,
+    allowed: &[String],
+) -> Result<i64, sqlx::Error> {
+    let cols: Vec<&String> = allowed.iter().filter(|c| row.contains_key(*c)).collect();
+    let sql = if cols.is_empty() {
+        format!("INSERT INTO {table} DEFAULT VALUES")
+    } else {
+        let names: Vec<String> = cols.iter().map(|c| format!("\"{c}\"")).collect();
+        format!(
+            "INSERT INTO {table} ({}) VALUES ({})",
+            names.join(", "),
+            vec!["?"; cols.len()].join(", ")
+        )
+    };
+    let args = arguments(cols.iter().map(|c| &row[*c]))?;
+    Ok(sqlx::query_with(AssertSqlSafe(sql), args)
+        .execute(conn)
+        .await?
+        .last_insert_rowid())
+}
+
+/// 按 `unique` 列的值查找已有记录的主键
+pub async fn find_existing(
+    conn: &mut SqliteConnection,
+    table: &str,
+    key: &str,
+    unique: &[&str],
+    row: &JsonRow,
+) -> Result<Option<i64>, sqlx::Error> {
+    let cond: Vec<String> = unique.iter().map(|c| format!("\"{c}\" IS ?")).collect();
+    let args = arguments(unique.iter().map(|c| row.get(*c).unwrap_or(&Value::Null)))?;
+    sqlx::query_scalar_with(
+        AssertSqlSafe(format!(
+            "SELECT {key} FROM {table} WHERE {} LIMIT 1",
+            cond.join(" AND ")
+        )),
+        args,
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// 回填自引用列（如 `task.parent_task_id`）
+pub async fn set_ref(
+    conn: &mut SqliteConnection,
+    table: &str,
+    key: &str,
+    id: i64,
+    column: &str,
+    value: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(AssertSqlSafe(format!(
+        "UPDATE {table} SET \"{column}\" = ? WHERE {key} = ?"
+    )))
+    .bind(value)
+    .bind(id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 删除 `table` 中满足 `filter` 的行（`?1` 绑定为用户 id）
+pub async fn delete_where(
+    conn: &mut SqliteConnection,
+    table: &str,
+    filter: &str,
+    user_id: i32,
+) -> Result<u64, sqlx::Error> {
+    sqlx::query(AssertSqlSafe(format!("DELETE FROM {table} WHERE {filter}")))
+        .bind(user_id)
+        .execute(conn)
+        .await
+        .map(|r| r.rows_affected())
+}
+
+/// JSON 值按对应的 SQLite 存储类型绑定
+fn arguments<'a>(values: impl Iterator<Item = &'a Value>) -> Result<SqliteArguments, sqlx::Error> {
+    let mut args = SqliteArguments::default();
+    for value in values {
+        match value {
+            Value::Null => args.add(None::<i64>),
+            Value::Bool(b) => args.add(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => args.add(i),
+                None => args.add(n.as_f64()),
+            },
+            Value::String(s) => args.add(s.clone()),
+            other => args.add(other.to_string()),
+        }
+        .map_err(sqlx::Error::Encode)?;
+    }
+    Ok(args)
+}
@@ -0,0 +1,1010 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use super::model::{FORMAT, FORMAT_VERSION, ImportReport, Manifest, TableReport};
+use super::repository::{self, AccountRepo, JsonRow};
+use crate::archive;
+use crate::error::ServiceError;
+use crate::modules::media::service::{MediaService, dir_for_type};
+use crate::storage::Storage;
+
+/// 归档中的一张表
+struct Table {
+    name: &'static str,
+    /// 选出该用户数据的条件，`?1` 为用户 id
+    filter: &'static str,
+    /// 自增主键列：导入时丢弃旧值，记录新旧 id 映射
+    key: Option<&'static str>,
+    /// 外键列 → 被引用的表（须排在前面，或是自身）
+    refs: &'static [(&'static str, &'static str)],
+    /// 导入时改写为目标用户 id 的列
+    owner: Option<&'static str>,
+    /// 目标库已有这些列取值相同的记录时直接复用（同名标签、生词、同一媒体文件）
+    unique: &'static [&'static str],
+}
+
+/// task / card 中未归属（`user_id IS NULL`）的共享数据不属于任何账号，不导出，
+/// 否则每次导入都会把它们复制成导入者的私有数据
+const OWN: &str = "user_id = ?1";
+const OWN_MEMS: &str = "mem_id IN (SELECT id FROM mem WHERE user_id = ?1)";
+const OWN_TASKS: &str = "task_id IN (SELECT id FROM task WHERE user_id = ?1)";
+
+/// 由服务端维护、不随归档导入的列：导入的记录一律是新建的有效版本
+const RESET_COLUMNS: &[&str] = &["deleted_at", "version"];
+
+const fn table(name: &'static str, filter: &'static str) -> Table {
+    Table {
+        name,
+        filter,
+        key: Some("id"),
+        refs: &[],
+        owner: None,
+        unique: &[],
+    }
+}
+
+/// 导出 / 导入顺序：被引用的表在前
+const TABLES: &[Table] = &[
+    // ── mem ──
+    Table {
+        owner: Some("user_id"),
+        ..table("chunk", OWN)
+    },
+    Table {
+        refs: &[("cue_chunk_id", "chunk"), ("target_chunk_id", "chunk")],
+        owner: Some("user_id"),
+        ..table("mem", OWN)
+    },
+    Table {
+        refs: &[("mem_id", "mem"), ("requires_mem_id", "mem")],
+        ..table("mem_prerequisite", OWN_MEMS)
+    },
+    Table {
+        refs: &[("mem_id", "mem")],
+        ..table("revlog", OWN_MEMS)
+    },
+    Table {
+        key: None,
+        refs: &[("mem_id", "mem")],
+        ..table("mem_mnemonic", OWN_MEMS)
+    },
+    Table {
+        owner: Some("user_id"),
+        unique: &["name", "user_id"],
+        ..table("mem_preset", OWN)
+    },
+    Table {
+        refs: &[("preset_id", "mem_preset")],
+        owner: Some("user_id"),
+        unique: &["name", "user_id"],
+        ..table("tag", OWN)
+    },
+    Table {
+        key: None,
+        refs: &[("mem_id", "mem"), ("tag_id", "tag")],
+        ..table("mem_tag", OWN_MEMS)
+    },
+    // 每个用户一行，目标账号已有配置时保留目标的
+    Table {
+        key: Some("user_id"),
+        owner: Some("user_id"),
+        unique: &["user_id"],
+        ..table("mem_config", OWN)
+    },
+    // ── bookmark ──
+    Table {
+        owner: Some("user_id"),
+        ..table("bookmark", OWN)
+    },
+    Table {
+        owner: Some("user_id"),
+        unique: &["name", "user_id"],
+        ..table("bookmark_tag", OWN)
+    },
+    Table {
+        key: None,
+        refs: &[("bookmark_id", "bookmark"), ("tag_id", "bookmark_tag")],
+        ..table(
+            "bookmark_tag_rel",
+            "bookmark_id IN (SELECT id FROM bookmark WHERE user_id = ?1)",
+        )
+    },
+    // ── task / time window ──
+    Table {
+        refs: &[("parent_task_id", "task")],
+        owner: Some("user_id"),
+        ..table("task", OWN)
+    },
+    Table {
+        refs: &[("task_id", "task"), ("depends_on_task_id", "task")],
+        ..table(
+            "task_dependency",
+            concat!(
+                "task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+                " AND depends_on_task_id IN (SELECT id FROM task WHERE user_id = ?1)"
+            ),
+        )
+    },
+    Table {
+        refs: &[("parent_task_id", "task"), ("child_task_id", "task")],
+        ..table(
+            "task_decomposition",
+            concat!(
+                "parent_task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+                " AND child_task_id IN (SELECT id FROM task WHERE user_id = ?1)"
+            ),
+        )
+    },
+    Table {
+        refs: &[("task_id", "task")],
+        owner: Some("user_id"),
+        ..table(
+            "time_window",
+            "(user_id = ?1 OR user_id IS NULL) AND task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+        )
+    },
+    Table {
+        refs: &[("task_id", "task"), ("time_window_id", "time_window")],
+        ..table("task_time_allocation", OWN_TASKS)
+    },
+    // ── reading ──
+    Table {
+        owner: Some("user_id"),
+        ..table("reading_article", OWN)
+    },
+    Table {
+        refs: &[("article_id", "reading_article")],
+        ..table(
+            "reading_article_word",
+            "article_id IN (SELECT id FROM reading_article WHERE user_id = ?1)",
+        )
+    },
+    Table {
+        owner: Some("user_id"),
+        unique: &["word", "user_id"],
+        ..table("reading_user_word", OWN)
+    },
+    // ── 其他 ──
+    Table {
+        owner: Some("user_id"),
+        ..table("card", OWN)
+    },
+    Table {
+        owner: Some("user_id"),
+        ..table("text_note", OWN)
+    },
+    Table {
+        owner: Some("user_id"),
+        ..table("onto", OWN)
+    },
+    Table {
+        refs: &[("onto_id", "onto")],
+        owner: Some("user_id"),
+        ..table("signifier_signified", OWN)
+    },
+    // stored_id 全局唯一：同一服务器上重复导入时复用已有文件
+    Table {
+        owner: Some("user_id"),
+        unique: &["stored_id"],
+        ..table("media", OWN)
+    },
+];
+
+/// 删除账号时清理的表与条件（`?1` 为用户 id），引用方在前。
+///
+/// 与 [`TABLES`] 一样只涉及用户自己的数据，task / card 中未归属的共享数据保留；
+/// 会话、API 令牌、webhook 随 user 行级联删除
+const PURGE: &[(&str, &str)] = &[
+    // ── mem ──
+    (
+        "mem_prerequisite",
+        concat!(
+            "mem_id IN (SELECT id FROM mem WHERE user_id = ?1)",
+            " OR requires_mem_id IN (SELECT id FROM mem WHERE user_id = ?1)"
+        ),
+    ),
+    ("revlog", OWN_MEMS),
+    ("mem_mnemonic", OWN_MEMS),
+    (
+        "mem_tag",
+        concat!(
+            "mem_id IN (SELECT id FROM mem WHERE user_id = ?1)",
+            " OR tag_id IN (SELECT id FROM tag WHERE user_id = ?1)"
+        ),
+    ),
+    ("mem", OWN),
+    ("tag", OWN),
+    ("chunk", OWN),
+    // ── bookmark ──
+    (
+        "bookmark_tag_rel",
+        concat!(
+            "bookmark_id IN (SELECT id FROM bookmark WHERE user_id = ?1)",
+            " OR tag_id IN (SELECT id FROM bookmark_tag WHERE user_id = ?1)"
+        ),
+    ),
+    ("bookmark", OWN),
+    ("bookmark_tag", OWN),
+    // ── task / time window ──
+    (
+        "task_time_allocation",
+        concat!(
+            "task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+            " OR time_window_id IN (SELECT id FROM time_window WHERE user_id = ?1",
+            " OR task_id IN (SELECT id FROM task WHERE user_id = ?1))"
+        ),
+    ),
+    (
+        "time_window",
+        "user_id = ?1 OR task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+    ),
+    (
+        "task_dependency",
+        concat!(
+            "task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+            " OR depends_on_task_id IN (SELECT id FROM task WHERE user_id = ?1)"
+        ),
+    ),
+    (
+        "task_decomposition",
+        concat!(
+            "parent_task_id IN (SELECT id FROM task WHERE user_id = ?1)",
+            " OR child_task_id IN (SELECT id FROM task WHERE user_id = ?1)"
+        ),
+    ),
+    ("task", OWN),
+    // ── reading ──
+    (
+        "reading_article_word",
+        "article_id IN (SELECT id FROM reading_article WHERE user_id = ?1)",
+    ),
+    ("reading_article", OWN),
+    ("reading_user_word", OWN),
+    // ── 其他 ──
+    ("card", OWN),
+    ("text_note", OWN),
+    ("signifier_signified", OWN),
+    ("onto", OWN),
+    // 文件由定期的孤儿清理任务回收
+    ("media", OWN),
+    ("job", OWN),
+];
+
+/// 清单与单张表数据解压后的大小上限
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// 归档解析结果。媒体文件留在归档中，写入存储时逐个读出
+struct Parsed {
+    manifest: Manifest,
+    tables: Vec<(&'static str, Vec<JsonRow>)>,
+    /// 归档中带有文件的 stored_id
+    media: HashSet<String>,
+    zip: Arc<Mutex<archive::Reader>>,
+}
+
+impl Parsed {
+    /// 读出归档中的媒体文件
+    async fn media_bytes(&self, entry: String) -> Result<Vec<u8>, ServiceError> {
+        let zip = self.zip.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut zip = zip
+                .lock()
+                .map_err(|_| ServiceError::Internal("归档读取器不可用".into()))?;
+            let mut data = Vec::new();
+            archive::copy(&mut zip, &entry, &mut data)
+                .map_err(|e| ServiceError::InvalidInput(format!("读取 {entry} 失败: {e}")))?
+                .ok_or_else(|| ServiceError::InvalidInput(format!("归档缺少 {entry}")))?;
+            Ok(data)
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(format!("解析任务异常: {e}")))?
+    }
+}
+
+#[derive(Clone)]
+pub struct AccountService {
+    repo: AccountRepo,
+    /// 媒体文件存储
+    storage: Arc<dyn Storage>,
+}
+
+impl AccountService {
+    pub fn new(db: Arc<SqlitePool>, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            repo: AccountRepo::new(db),
+            storage,
+        }
+    }
+
+    /// 导出账号全部数据为 zip，写在匿名临时文件中（关闭即删除），返回时位于文件开头。
+    ///
+    /// 媒体文件逐个从存储读出写入归档，清单最后写入
+    pub async fn export(&self, user_id: i32) -> Result<File, ServiceError> {
+        let user = self
+            .repo
+            .user_name(user_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("用户不存在".into()))?;
+
+        let mut tables = Vec::with_capacity(TABLES.len());
+        for t in TABLES {
+            tables.push((t.name, self.repo.dump(t.name, t.filter, user_id).await?));
+        }
+
+        let media: Vec<(String, String)> = tables
+            .iter()
+            .find(|(name, _)| *name == "media")
+            .map_or(&[][..], |(_, rows)| rows)
+            .iter()
+            .filter_map(|row| Some((str_field(row, "media_type")?, str_field(row, "stored_id")?)))
+            .map(|(media_type, stored_id)| (media_type.to_string(), stored_id.to_string()))
+            .collect();
+
+        let mut manifest = Manifest {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            schema_version: crate::db::migrate::current_version(self.repo.pool()).await?,
+            exported_at: chrono::Utc::now(),
+            user,
+            tables: tables
+                .iter()
+                .map(|(name, rows)| (name.to_string(), rows.len()))
+                .collect(),
+            media_files: 0,
+            missing_media: Vec::new(),
+        };
+
+        let storage = self.storage.clone();
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || -> zip::result::ZipResult<File> {
+            let mut zip = archive::writer()?;
+            for (media_type, stored_id) in &media {
+                let key = MediaService::key(media_type, stored_id);
+                match runtime.block_on(storage.get(&key)) {
+                    Ok(Some(bytes)) => {
+                        let path = media_entry(media_type, stored_id);
+                        archive::start_stored(&mut zip, &path, bytes.len() as u64)?;
+                        zip.write_all(&bytes)?;
+                        manifest.media_files += 1;
+                    }
+                    Ok(None) => {
+                        tracing::warn!("导出时媒体文件不存在 stored_id={stored_id}");
+                        manifest.missing_media.push(stored_id.clone());
+                    }
+                    Err(e) => {
+                        tracing::warn!("导出时读取媒体文件失败 stored_id={stored_id}: {e}");
+                        manifest.missing_media.push(stored_id.clone());
+                    }
+                }
+            }
+            for (name, rows) in &tables {
+                archive::start_deflated(&mut zip, &format!("data/{name}.json"))?;
+                serde_json::to_writer(&mut zip, rows).map_err(io::Error::from)?;
+            }
+            archive::start_deflated(&mut zip, "manifest.json")?;
+            serde_json::to_writer_pretty(&mut zip, &manifest).map_err(io::Error::from)?;
+            archive::finish(zip)
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(format!("打包任务异常: {e}")))?
+        .map_err(|e| ServiceError::Internal(format!("打包失败: {e}")))
+    }
+
+    /// 把归档导入到 `user_id` 的账号：所有 id 重新分配，引用关系按新 id 改写。
+    ///
+    /// 数据库部分在一个事务内完成；媒体文件在提交前写入存储，已存在的文件不覆盖。
+    pub async fn import(&self, user_id: i32, file: File) -> Result<ImportReport, ServiceError> {
+        let parsed = tokio::task::spawn_blocking(move || parse(file))
+            .await
+            .map_err(|e| ServiceError::Internal(format!("解析任务异常: {e}")))??;
+
+        let mut tx = self.repo.pool().begin().await?;
+        let mut id_maps: HashMap<&str, HashMap<i64, i64>> = HashMap::new();
+        let mut reports = BTreeMap::new();
+        let mut files = Vec::new();
+
+        for (t, rows) in TABLES.iter().filter_map(|t| {
+            parsed
+                .tables
+                .iter()
+                .find(|(name, _)| *name == t.name)
+                .map(|(_, rows)| (t, rows))
+        }) {
+            let allowed = repository::columns(&mut tx, t.name).await?;
+            let mut report = TableReport::default();
+            let mut ids = HashMap::new();
+            // 自引用：(新 id, 列, 旧的被引用 id)，整表插入后回填
+            let mut deferred = Vec::new();
+
+            'rows: for row in rows {
+                // 回收站中的记录不导入
+                if row.get("deleted_at").is_some_and(|v| !v.is_null()) {
+                    report.skipped += 1;
+                    continue;
+                }
+                let mut row = row.clone();
+                for col in RESET_COLUMNS {
+                    row.remove(*col);
+                }
+                let old_id = t.key.and_then(|k| row.remove(k)).and_then(|v| v.as_i64());
+                if let Some(owner) = t.owner {
+                    row.insert(owner.to_string(), Value::from(user_id));
+                }
+
+                let mut self_refs = Vec::new();
+                for &(col, target) in t.refs {
+                    let Some(old_ref) = row.get(col).and_then(Value::as_i64) else {
+                        continue;
+                    };
+                    if target == t.name {
+                        row.insert(col.to_string(), Value::Null);
+                        self_refs.push((col, old_ref));
+                        continue;
+                    }
+                    match id_maps.get(target).and_then(|m| m.get(&old_ref)) {
+                        Some(&new_ref) => {
+                            row.insert(col.to_string(), Value::from(new_ref));
+                        }
+                        None => {
+                            report.skipped += 1;
+                            continue 'rows;
+                        }
+                    }
+                }
+
+                if let Some(key) = t.key
+                    && !t.unique.is_empty()
+                    && let Some(existing) =
+                        repository::find_existing(&mut tx, t.name, key, t.unique, &row).await?
+                {
+                    if let Some(old) = old_id {
+                        ids.insert(old, existing);
+                    }
+                    report.merged += 1;
+                    continue;
+                }
+
+                if t.name == "media" {
+                    match self.media_file(&row, &parsed.media).await? {
+                        Some(MediaFile::Archived(key, entry)) => files.push((key, entry)),
+                        Some(MediaFile::Stored) => {}
+                        None => {
+                            report.skipped += 1;
+                            continue;
+                        }
+                    }
+                }
+
+                let new_id = repository::insert(&mut tx, t.name, &row, &allowed).await?;
+                if let Some(old) = old_id {
+                    ids.insert(old, new_id);
+                }
+                deferred.extend(self_refs.into_iter().map(|(col, r)| (new_id, col, r)));
+                report.inserted += 1;
+            }
+
+            for (new_id, col, old_ref) in deferred {
+                if let (Some(key), Some(&new_ref)) = (t.key, ids.get(&old_ref)) {
+                    repository::set_ref(&mut tx, t.name, key, new_id, col, new_ref).await?;
+                }
+            }
+            id_maps.insert(t.name, ids);
+            reports.insert(t.name.to_string(), report);
+        }
+
+        let mut written = 0;
+        for (key, entry) in &files {
+            if self.storage.exists(key).await.unwrap_or(false) {
+                continue;
+            }
+            let data = parsed.media_bytes(entry.clone()).await?;
+            self.storage
+                .put(key, &data)
+                .await
+                .map_err(|e| ServiceError::Internal(format!("写入媒体文件失败: {e}")))?;
+            written += 1;
+        }
+
+        tx.commit().await?;
+        Ok(ImportReport {
+            version: parsed.manifest.version,
+            tables: reports,
+            media_files: written,
+        })
+    }
+
+    /// 删除账号及其全部数据（一个事务）。返回 false 表示用户不存在
+    pub async fn delete(&self, user_id: i32) -> Result<bool, ServiceError> {
+        let mut tx = self.repo.pool().begin().await?;
+        for (table, filter) in PURGE {
+            repository::delete_where(&mut tx, table, filter, user_id).await?;
+        }
+        if repository::delete_where(&mut tx, "user", "id = ?1", user_id).await? == 0 {
+            return Ok(false);
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// 媒体行对应的文件来源；归档和存储中都没有时返回 None
+    async fn media_file(
+        &self,
+        row: &JsonRow,
+        archived: &HashSet<String>,
+    ) -> Result<Option<MediaFile>, ServiceError> {
+        let (Some(media_type), Some(stored_id)) =
+            (str_field(row, "media_type"), str_field(row, "stored_id"))
+        else {
+            return Ok(None);
+        };
+        let key = MediaService::key(media_type, stored_id);
+        Ok(if archived.contains(stored_id) {
+            Some(MediaFile::Archived(key, media_entry(media_type, stored_id)))
+        } else if self
+            .storage
+            .exists(&key)
+            .await
+            .map_err(|e| ServiceError::Internal(format!("读取媒体文件失败: {e}")))?
+        {
+            Some(MediaFile::Stored)
+        } else {
+            None
+        })
+    }
+}
+
+enum MediaFile {
+    /// 需把归档中的条目（第二项）写出到该 key
+    Archived(String, String),
+    /// 存储中已有
+    Stored,
+}
+
+fn str_field<'a>(row: &'a JsonRow, key: &str) -> Option<&'a str> {
+    row.get(key).and_then(Value::as_str)
+}
+
+fn media_entry(media_type: &str, stored_id: &str) -> String {
+    format!("media/{}/{stored_id}", dir_for_type(media_type))
+}
+
+/// stored_id 会拼进文件路径，只接受 nanoid 字符集
+fn valid_stored_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse(file: File) -> Result<Parsed, ServiceError> {
+    let bad = |msg: String| ServiceError::InvalidInput(msg);
+    let mut zip = archive::open(file).map_err(|e| bad(format!("无法读取归档: {e}")))?;
+    let read = |zip: &mut archive::Reader, name: &str| {
+        archive::read(zip, name, MAX_ENTRY_SIZE).map_err(|e| bad(format!("读取 {name} 失败: {e}")))
+    };
+
+    let manifest: Manifest = read(&mut zip, "manifest.json")?
+        .ok_or_else(|| bad("归档缺少 manifest.json".into()))
+        .and_then(|b| {
+            serde_json::from_slice(&b).map_err(|e| bad(format!("manifest.json 无效: {e}")))
+        })?;
+    if manifest.format != FORMAT {
+        return Err(bad(format!("不是账号归档（format = {}）", manifest.format)));
+    }
+    if manifest.version > FORMAT_VERSION {
+        return Err(bad(format!(
+            "归档格式版本 {} 高于当前支持的 {FORMAT_VERSION}，请升级程序",
+            manifest.version
+        )));
+    }
+
+    let mut tables = Vec::new();
+    let mut media = HashSet::new();
+    for t in TABLES {
+        let Some(data) = read(&mut zip, &format!("data/{}.json", t.name))? else {
+            continue;
+        };
+        let rows: Vec<JsonRow> = serde_json::from_slice(&data)
+            .map_err(|e| bad(format!("data/{}.json 无效: {e}", t.name)))?;
+
+        if t.name == "media" {
+            for row in &rows {
+                let (Some(media_type), Some(stored_id)) =
+                    (str_field(row, "media_type"), str_field(row, "stored_id"))
+                else {
+                    continue;
+                };
+                if !valid_stored_id(stored_id) {
+                    return Err(bad(format!("非法的 stored_id: {stored_id}")));
+                }
+                if zip
+                    .index_for_name(&media_entry(media_type, stored_id))
+                    .is_some()
+                {
+                    media.insert(stored_id.to_string());
+                }
+            }
+        }
+        tables.push((t.name, rows));
+    }
+
+    Ok(Parsed {
+        manifest,
+        tables,
+        media,
+        zip: Arc::new(Mutex::new(zip)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use std::io::{Seek, SeekFrom};
+    use std::path::PathBuf;
+
+    /// 读出归档中的条目（不移动 `file` 的读取位置）
+    fn entry(file: &File, name: &str) -> Vec<u8> {
+        let mut copy = file.try_clone().unwrap();
+        let mut zip = archive::open(copy.try_clone().unwrap()).unwrap();
+        let data = archive::read(&mut zip, name, 1 << 20).unwrap().unwrap();
+        copy.seek(SeekFrom::Start(0)).unwrap();
+        data
+    }
+
+    async fn setup() -> (AccountService, Arc<SqlitePool>, PathBuf) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let root = std::env::temp_dir().join(format!("brainbow-account-{}", uuid::Uuid::new_v4()));
+        let storage = Arc::new(crate::storage::LocalStorage::new(&root));
+        let svc = AccountService::new(pool.clone(), storage);
+        (svc, pool, root)
+    }
+
+    async fn add_user(pool: &SqlitePool, name: &str) -> i32 {
+        sqlx::query_scalar("INSERT INTO user (name, password_hash) VALUES (?, 'x') RETURNING id")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn exec(pool: &SqlitePool, sql: String) {
+        sqlx::raw_sql(sqlx::AssertSqlSafe(sql))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn count(pool: &SqlitePool, sql: &'static str, user_id: i32) -> i64 {
+        sqlx::query_scalar(sql)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// alice：两张卡片（第二张依赖第一张）、复习记录、标签、书签、任务依赖
+    async fn seed(pool: &SqlitePool, uid: i32) {
+        exec(
+            pool,
+            format!(
+                "INSERT INTO chunk (id, content, user_id) VALUES (1, 'cue1', {uid}), (2, 'target1', {uid}),
+                                                           (3, 'cue2', {uid}), (4, 'target2', {uid});
+                 INSERT INTO mem (id, cue_chunk_id, target_chunk_id, state, user_id) VALUES (10, 1, 2, 'review', {uid}), (11, 3, 4, 'new', {uid});
+                 INSERT INTO mem_prerequisite (mem_id, requires_mem_id) VALUES (11, 10);
+                 INSERT INTO revlog (mem_id, review_time, rating, delta_t) VALUES (10, '2024-01-01T00:00:00Z', 3, 0);
+                 INSERT INTO mem_mnemonic (mem_id, content) VALUES (10, '口诀');
+                 INSERT INTO tag (id, name, user_id) VALUES (5, 'rust', {uid});
+                 INSERT INTO mem_tag (mem_id, tag_id) VALUES (10, 5), (11, 5);
+                 INSERT INTO bookmark (id, title, url, user_id) VALUES (3, 'Rust Book', 'https://doc.rust-lang.org', {uid});
+                 INSERT INTO bookmark_tag (id, name, user_id) VALUES (8, 'docs', {uid});
+                 INSERT INTO bookmark_tag_rel (bookmark_id, tag_id) VALUES (3, 8);
+                 INSERT INTO task (id, title, user_id) VALUES (20, 'parent', {uid});
+                 INSERT INTO task (id, title, parent_task_id, user_id) VALUES (21, 'child', 20, {uid});
+                 INSERT INTO task_dependency (task_id, depends_on_task_id) VALUES (21, 20);
+                 INSERT INTO text_note (name, content, user_id) VALUES ('草稿', '内容', {uid});"
+            ),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn export_contains_manifest_and_user_data_only() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        let bob = add_user(&pool, "bob").await;
+        seed(&pool, alice).await;
+        exec(
+            &pool,
+            format!(
+                "INSERT INTO bookmark (title, url, user_id) VALUES ('bob', 'https://b', {bob})"
+            ),
+        )
+        .await;
+
+        let file = svc.export(alice).await.unwrap();
+        let manifest: Manifest = serde_json::from_slice(&entry(&file, "manifest.json")).unwrap();
+        assert_eq!(manifest.format, FORMAT);
+        assert_eq!(manifest.version, FORMAT_VERSION);
+        assert_eq!(manifest.user, "alice");
+        assert_eq!(manifest.tables["mem"], 2);
+        assert_eq!(manifest.tables["bookmark"], 1);
+        assert_eq!(manifest.tables["mem_prerequisite"], 1);
+
+        let bookmarks: Vec<JsonRow> =
+            serde_json::from_slice(&entry(&file, "data/bookmark.json")).unwrap();
+        assert_eq!(bookmarks[0]["title"], "Rust Book");
+    }
+
+    #[tokio::test]
+    async fn import_remaps_ids_and_edges() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        seed(&pool, alice).await;
+        let file = svc.export(alice).await.unwrap();
+
+        let carol = add_user(&pool, "carol").await;
+        let report = svc.import(carol, file).await.unwrap();
+        assert_eq!(report.tables["mem"].inserted, 2);
+        assert_eq!(report.tables["task"].inserted, 2);
+
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM mem WHERE user_id = ?", carol).await,
+            2
+        );
+        // 依赖边指向 carol 自己的新卡片
+        let edge: (String, String) = sqlx::query_as(
+            "SELECT c1.content, c2.content FROM mem_prerequisite p
+             JOIN mem m1 ON m1.id = p.mem_id JOIN chunk c1 ON c1.id = m1.cue_chunk_id
+             JOIN mem m2 ON m2.id = p.requires_mem_id JOIN chunk c2 ON c2.id = m2.cue_chunk_id
+             WHERE m1.user_id = ? AND m2.user_id = m1.user_id",
+        )
+        .bind(carol)
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        assert_eq!(edge, ("cue2".into(), "cue1".into()));
+
+        // 任务父子与依赖关系按新 id 改写
+        let (child, parent): (String, String) = sqlx::query_as(
+            "SELECT c.title, p.title FROM task c
+             JOIN task_dependency d ON d.task_id = c.id
+             JOIN task p ON p.id = d.depends_on_task_id AND p.id = c.parent_task_id
+             WHERE c.user_id = ?",
+        )
+        .bind(carol)
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        assert_eq!((child.as_str(), parent.as_str()), ("child", "parent"));
+
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM mem_tag mt JOIN tag t ON t.id = mt.tag_id WHERE t.user_id = ?",
+                carol
+            )
+            .await,
+            2
+        );
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM revlog r JOIN mem m ON m.id = r.mem_id WHERE m.user_id = ?",
+                carol
+            )
+            .await,
+            1
+        );
+        // 原账号数据不受影响
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM mem WHERE user_id = ?", alice).await,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn roundtrip_skips_shared_and_trashed_rows_and_resets_versions() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        seed(&pool, alice).await;
+        exec(
+            &pool,
+            format!(
+                "INSERT INTO task (id, title) VALUES (30, 'shared');
+                 INSERT INTO task_dependency (task_id, depends_on_task_id) VALUES (20, 30);
+                 UPDATE task SET version = 7 WHERE id = 20;
+                 INSERT INTO bookmark (title, url, user_id, deleted_at)
+                     VALUES ('trashed', 'https://t', {alice}, '2024-01-01T00:00:00Z');"
+            ),
+        )
+        .await;
+        let file = svc.export(alice).await.unwrap();
+        let manifest: Manifest = serde_json::from_slice(&entry(&file, "manifest.json")).unwrap();
+        assert_eq!(manifest.tables["task"], 2);
+        assert_eq!(manifest.tables["task_dependency"], 1);
+
+        let carol = add_user(&pool, "carol").await;
+        let report = svc.import(carol, file).await.unwrap();
+        assert_eq!(report.tables["bookmark"].inserted, 1);
+        assert_eq!(report.tables["bookmark"].skipped, 1);
+        // 共享任务没有被复制
+        let shared: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE title = 'shared'")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(shared, 1);
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM task WHERE user_id = ? AND title = 'parent' AND version = 1",
+                carol
+            )
+            .await,
+            1
+        );
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM bookmark WHERE user_id = ? AND deleted_at IS NULL",
+                carol
+            )
+            .await,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn reimport_into_same_account_merges_named_rows() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        seed(&pool, alice).await;
+        let file = svc.export(alice).await.unwrap();
+
+        let report = svc.import(alice, file).await.unwrap();
+        assert_eq!(report.tables["tag"].merged, 1);
+        assert_eq!(report.tables["bookmark_tag"].merged, 1);
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM tag WHERE user_id = ?", alice).await,
+            1
+        );
+        assert_eq!(
+            count(&pool, "SELECT COUNT(*) FROM mem WHERE user_id = ?", alice).await,
+            4
+        );
+    }
+
+    #[tokio::test]
+    async fn media_files_roundtrip() {
+        let (svc, pool, root) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        exec(
+            &pool,
+            format!(
+                "INSERT INTO media (stored_id, original_name, media_type, mime_type, user_id)
+                 VALUES ('abcDEF123456', 'a.png', 'image', 'image/png', {alice}),
+                        ('gone00000000', 'b.png', 'image', 'image/png', {alice})"
+            ),
+        )
+        .await;
+        svc.storage
+            .put("image/abcDEF123456", b"png-bytes")
+            .await
+            .unwrap();
+
+        let file = svc.export(alice).await.unwrap();
+        assert_eq!(entry(&file, "media/image/abcDEF123456"), b"png-bytes");
+
+        // 换一台「服务器」导入：新库、空的媒体目录
+        let (svc2, pool2, root2) = setup().await;
+        let bob = add_user(&pool2, "bob").await;
+        let report = svc2.import(bob, file).await.unwrap();
+        assert_eq!(report.media_files, 1);
+        assert_eq!(report.tables["media"].inserted, 1);
+        assert_eq!(report.tables["media"].skipped, 1);
+        assert_eq!(
+            std::fs::read(root2.join("image/abcDEF123456")).unwrap(),
+            b"png-bytes"
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+        let _ = std::fs::remove_dir_all(root2);
+    }
+
+    #[tokio::test]
+    async fn rejects_foreign_or_newer_archives() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+
+        let mut garbage = tempfile::tempfile().unwrap();
+        garbage.write_all(b"nope").unwrap();
+        garbage.seek(SeekFrom::Start(0)).unwrap();
+        assert!(matches!(
+            svc.import(alice, garbage).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+
+        let mut manifest = serde_json::json!({
+            "format": FORMAT, "version": FORMAT_VERSION + 1, "schema_version": 1,
+            "exported_at": "2024-01-01T00:00:00Z", "user": "x", "tables": {}, "media_files": 0
+        });
+        let zip_of = |m: &Value| {
+            let mut w = archive::writer().unwrap();
+            archive::start_deflated(&mut w, "manifest.json").unwrap();
+            w.write_all(m.to_string().as_bytes()).unwrap();
+            archive::finish(w).unwrap()
+        };
+        assert!(svc.import(alice, zip_of(&manifest)).await.is_err());
+
+        manifest["version"] = FORMAT_VERSION.into();
+        manifest["format"] = "other".into();
+        assert!(svc.import(alice, zip_of(&manifest)).await.is_err());
+    }
+
+    #[test]
+    fn tables_reference_earlier_tables() {
+        for (i, t) in TABLES.iter().enumerate() {
+            for (_, target) in t.refs {
+                let pos = TABLES.iter().position(|x| x.name == *target).unwrap();
+                assert!(pos <= i, "{} 引用的 {target} 须排在前面", t.name);
+                assert!(TABLES[pos].key.is_some(), "{target} 没有主键映射");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_removes_user_data_and_keeps_others() {
+        let (svc, pool, _) = setup().await;
+        let alice = add_user(&pool, "alice").await;
+        let bob = add_user(&pool, "bob").await;
+        seed(&pool, alice).await;
+        exec(
+            &pool,
+            format!(
+                "INSERT INTO bookmark (title, url, user_id) VALUES ('bob', 'https://b', {bob});
+                 INSERT INTO task (title) VALUES ('shared');
+                 INSERT INTO media (stored_id, original_name, media_type, mime_type, user_id)
+                     VALUES ('m1', 'a.png', 'image', 'image/png', {alice});
+                 INSERT INTO session (id, user_id, refresh_token_hash, access_jti, access_exp, expires_at)
+                     VALUES ('s1', {alice}, 'h', 'j', 0, '2099-01-01T00:00:00Z');"
+            ),
+        )
+        .await;
+
+        assert!(svc.delete(alice).await.unwrap());
+        for sql in [
+            "SELECT COUNT(*) FROM mem WHERE user_id = ?",
+            "SELECT COUNT(*) FROM chunk WHERE user_id = ?",
+            "SELECT COUNT(*) FROM bookmark WHERE user_id = ?",
+            "SELECT COUNT(*) FROM task WHERE user_id = ?",
+            "SELECT COUNT(*) FROM media WHERE user_id = ?",
+            "SELECT COUNT(*) FROM session WHERE user_id = ?",
+            "SELECT COUNT(*) FROM user WHERE id = ?",
+        ] {
+            assert_eq!(count(&pool, sql, alice).await, 0, "{sql}");
+        }
+        assert_eq!(
+            count(
+                &pool,
+                "SELECT COUNT(*) FROM bookmark WHERE user_id = ?",
+                bob
+            )
+            .await,
+            1
+        );
+        // 共享任务保留
+        let shared: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE user_id IS NULL")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(shared, 1);
+        assert!(!svc.delete(alice).await.unwrap());
+    }
+}
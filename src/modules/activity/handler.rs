@@ -0,0 +1,38 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::{IntoResponse, Json},
+};
+
+use super::model::{ActivityQuery, AuditEvent};
+use crate::auth::Claims;
+use crate::pagination::{PaginatedResponse, Pagination};
+use crate::state::AppState;
+
+/// GET /api/activity
+#[utoipa::path(
+    get,
+    path = "/activity",
+    summary = "活动记录（普通用户仅自己的操作）",
+    params(ActivityQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<AuditEvent>))
+)]
+pub async fn list_activity_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ActivityQuery>,
+) -> impl IntoResponse {
+    let pagination = Pagination {
+        page: query.page.unwrap_or(1),
+        page_size: query.page_size.unwrap_or(20),
+        cursor: None,
+    };
+    match state
+        .activity
+        .list(claims.sub, claims.role == "admin", &query, &pagination)
+        .await
+    {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
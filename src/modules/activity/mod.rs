@@ -0,0 +1,21 @@
+//! 审计日志与活动流。
+//!
+//! 各模块 service 通过 [`AuditLog`] 在增删改后追加 `audit_event`，
+//! `/api/activity` 按操作者、实体、动作与时间分页查询。
+
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use model::Entity;
+pub use service::{ActivityService, AuditLog};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+/// 活动流
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::list_activity_handler))
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+/// 被审计的实体类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    Mem,
+    Task,
+    Bookmark,
+    Card,
+    Media,
+}
+
+impl Entity {
+    pub const ALL: [Entity; 5] = [
+        Entity::Mem,
+        Entity::Task,
+        Entity::Bookmark,
+        Entity::Card,
+        Entity::Media,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entity::Mem => "mem",
+            Entity::Task => "task",
+            Entity::Bookmark => "bookmark",
+            Entity::Card => "card",
+            Entity::Media => "media",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str() == s)
+    }
+}
+
+/// 操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Update,
+    /// 移入回收站
+    Delete,
+    /// 从回收站恢复
+    Restore,
+    /// 从回收站彻底删除
+    Purge,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::Create,
+        Action::Update,
+        Action::Delete,
+        Action::Restore,
+        Action::Purge,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Restore => "restore",
+            Action::Purge => "purge",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == s)
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditEventRow {
+    pub id: i64,
+    pub actor_id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    pub diff: String,
+    pub created_at: String,
+}
+
+/// 活动流中的一条事件
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub actor_id: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub action: String,
+    /// `{"字段": [旧值, 新值]}`
+    pub diff: Value,
+    pub created_at: String,
+}
+
+impl From<AuditEventRow> for AuditEvent {
+    fn from(r: AuditEventRow) -> Self {
+        Self {
+            id: r.id,
+            actor_id: r.actor_id,
+            entity_type: r.entity_type,
+            entity_id: r.entity_id,
+            action: r.action,
+            diff: serde_json::from_str(&r.diff).unwrap_or(Value::Null),
+            created_at: r.created_at,
+        }
+    }
+}
+
+/// GET /api/activity 查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    /// 按操作者过滤，仅管理员可查看他人
+    pub actor: Option<i64>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub action: Option<String>,
+    /// 起始时间（含），RFC 3339
+    pub since: Option<String>,
+    /// 截止时间（不含），RFC 3339
+    pub until: Option<String>,
+}
+
+/// 校验后的过滤条件
+#[derive(Debug, Default, Clone)]
+pub struct ActivityFilter {
+    pub actor: Option<i64>,
+    pub entity: Option<Entity>,
+    pub entity_id: Option<String>,
+    pub action: Option<Action>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
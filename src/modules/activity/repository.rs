@@ -0,0 +1,82 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use std::sync::Arc;
+
+use super::model::{ActivityFilter, AuditEventRow};
+
+#[derive(Clone)]
+pub struct ActivityRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl ActivityRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert(
+        &self,
+        actor_id: i32,
+        entity_type: &str,
+        entity_id: &str,
+        action: &str,
+        diff: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO audit_event (actor_id, entity_type, entity_id, action, diff)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(actor_id)
+        .bind(entity_type)
+        .bind(entity_id)
+        .bind(action)
+        .bind(diff)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 按过滤条件分页，最新的在前
+    pub async fn find_paginated(
+        &self,
+        filter: &ActivityFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<AuditEventRow>, i64), sqlx::Error> {
+        let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM audit_event WHERE 1 = 1");
+        push_filter(&mut count, filter);
+        let total: i64 = count.build_query_scalar().fetch_one(&*self.pool).await?;
+
+        let mut q = QueryBuilder::<Sqlite>::new(
+            "SELECT id, actor_id, entity_type, entity_id, action, diff, created_at
+             FROM audit_event WHERE 1 = 1",
+        );
+        push_filter(&mut q, filter);
+        q.push(" ORDER BY id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let rows = q.build_query_as().fetch_all(&*self.pool).await?;
+        Ok((rows, total))
+    }
+}
+
+fn push_filter(q: &mut QueryBuilder<Sqlite>, f: &ActivityFilter) {
+    if let Some(actor) = f.actor {
+        q.push(" AND actor_id = ").push_bind(actor);
+    }
+    if let Some(entity) = f.entity {
+        q.push(" AND entity_type = ").push_bind(entity.as_str());
+    }
+    if let Some(ref id) = f.entity_id {
+        q.push(" AND entity_id = ").push_bind(id.clone());
+    }
+    if let Some(action) = f.action {
+        q.push(" AND action = ").push_bind(action.as_str());
+    }
+    if let Some(ref since) = f.since {
+        q.push(" AND created_at >= ").push_bind(since.clone());
+    }
+    if let Some(ref until) = f.until {
+        q.push(" AND created_at < ").push_bind(until.clone());
+    }
+}
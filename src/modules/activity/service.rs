@@ -0,0 +1,383 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use super::model::{Action, ActivityFilter, ActivityQuery, AuditEvent, Entity};
+use super::repository::ActivityRepo;
+use crate::error::ServiceError;
+use crate::modules::events::ChangeFeed;
+use crate::pagination::{PaginatedResponse, Pagination};
+
+/// 审计日志写入端，由各模块 service 持有。
+///
+/// 写入失败只记 warn，不影响已完成的业务操作。每条记录同时发布到
+/// [`ChangeFeed`]，供 `/api/events` 推送给操作者的其他客户端。
+#[derive(Clone)]
+pub struct AuditLog {
+    repo: ActivityRepo,
+    changes: ChangeFeed,
+}
+
+impl AuditLog {
+    pub fn new(db: Arc<SqlitePool>, changes: ChangeFeed) -> Self {
+        Self {
+            repo: ActivityRepo::new(db),
+            changes,
+        }
+    }
+
+    pub async fn created(
+        &self,
+        actor: i32,
+        entity: Entity,
+        id: impl Display,
+        after: &impl Serialize,
+    ) {
+        let diff = diff(None, to_value(after));
+        self.record(actor, entity, id, Action::Create, diff).await;
+    }
+
+    /// 没有字段变化时不记录
+    pub async fn updated<T: Serialize>(
+        &self,
+        actor: i32,
+        entity: Entity,
+        id: impl Display,
+        before: &T,
+        after: &T,
+    ) {
+        let diff = diff(to_value(before), to_value(after));
+        if !diff.is_empty() {
+            self.record(actor, entity, id, Action::Update, diff).await;
+        }
+    }
+
+    pub async fn deleted(
+        &self,
+        actor: i32,
+        entity: Entity,
+        id: impl Display,
+        before: &impl Serialize,
+    ) {
+        let diff = diff(to_value(before), None);
+        self.record(actor, entity, id, Action::Delete, diff).await;
+    }
+
+    pub async fn restored(&self, actor: i32, entity: Entity, id: impl Display) {
+        self.record(actor, entity, id, Action::Restore, Map::new())
+            .await;
+    }
+
+    pub async fn purged(&self, actor: i32, entity: Entity, id: impl Display) {
+        self.record(actor, entity, id, Action::Purge, Map::new())
+            .await;
+    }
+
+    async fn record(
+        &self,
+        actor: i32,
+        entity: Entity,
+        id: impl Display,
+        action: Action,
+        diff: Map<String, Value>,
+    ) {
+        let id = id.to_string();
+        self.changes.publish(actor, entity, &id, action.into());
+        let diff = Value::Object(diff).to_string();
+        if let Err(e) = self
+            .repo
+            .insert(actor, entity.as_str(), &id, action.as_str(), &diff)
+            .await
+        {
+            tracing::warn!(
+                "写入审计日志失败 {} {}#{}: {e}",
+                action.as_str(),
+                entity.as_str(),
+                id
+            );
+        }
+    }
+}
+
+fn to_value(v: &impl Serialize) -> Option<Value> {
+    serde_json::to_value(v).ok()
+}
+
+/// 逐字段比较两个对象快照，返回 `{"字段": [旧值, 新值]}`。
+///
+/// `updated_at` 不计入：事件本身带时间。
+pub fn diff(before: Option<Value>, after: Option<Value>) -> Map<String, Value> {
+    let into_map = |v: Option<Value>| match v {
+        Some(Value::Object(m)) => m,
+        _ => Map::new(),
+    };
+    let before = into_map(before);
+    let mut after = into_map(after);
+
+    let mut out = Map::new();
+    for (key, old) in before {
+        let new = after.remove(&key).unwrap_or(Value::Null);
+        if old != new {
+            out.insert(key, Value::Array(vec![old, new]));
+        }
+    }
+    for (key, new) in after {
+        if !new.is_null() {
+            out.insert(key, Value::Array(vec![Value::Null, new]));
+        }
+    }
+    out.remove("updated_at");
+    out
+}
+
+/// 活动流查询
+#[derive(Clone)]
+pub struct ActivityService {
+    repo: ActivityRepo,
+}
+
+impl ActivityService {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self {
+            repo: ActivityRepo::new(db),
+        }
+    }
+
+    /// 普通用户只能看到自己的操作；管理员可看全部，或用 `actor` 指定用户
+    pub async fn list(
+        &self,
+        viewer: i32,
+        is_admin: bool,
+        query: &ActivityQuery,
+        pagination: &Pagination,
+    ) -> Result<PaginatedResponse<AuditEvent>, ServiceError> {
+        let filter = ActivityFilter {
+            actor: if is_admin {
+                query.actor
+            } else {
+                Some(viewer as i64)
+            },
+            entity: parse_opt(query.entity_type.as_deref(), Entity::parse, "entity_type")?,
+            entity_id: query.entity_id.clone().filter(|s| !s.is_empty()),
+            action: parse_opt(query.action.as_deref(), Action::parse, "action")?,
+            since: parse_time(query.since.as_deref(), "since")?,
+            until: parse_time(query.until.as_deref(), "until")?,
+        };
+        let (rows, total) = self
+            .repo
+            .find_paginated(&filter, pagination.limit(), pagination.offset())
+            .await?;
+        let items = rows.into_iter().map(AuditEvent::from).collect();
+        Ok(PaginatedResponse::new(items, total, pagination))
+    }
+}
+
+fn parse_opt<T>(
+    value: Option<&str>,
+    parse: fn(&str) -> Option<T>,
+    name: &str,
+) -> Result<Option<T>, ServiceError> {
+    match value.filter(|s| !s.is_empty()) {
+        None => Ok(None),
+        Some(s) => parse(s)
+            .map(Some)
+            .ok_or_else(|| ServiceError::InvalidInput(format!("无效的 {name}: {s}"))),
+    }
+}
+
+/// RFC 3339 → 与 `created_at` 同格式的 UTC 字符串，便于直接按字典序比较
+fn parse_time(value: Option<&str>, name: &str) -> Result<Option<String>, ServiceError> {
+    let Some(s) = value.filter(|s| !s.is_empty()) else {
+        return Ok(None);
+    };
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|t| {
+            Some(
+                t.with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+                    .to_string(),
+            )
+        })
+        .map_err(|_| ServiceError::InvalidInput(format!("{name} 须为 RFC 3339 时间: {s}")))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use serde_json::json;
+
+    async fn setup() -> (AuditLog, ActivityService, Arc<SqlitePool>) {
+        let (log, svc, pool, _) = setup_with_feed().await;
+        (log, svc, pool)
+    }
+
+    async fn setup_with_feed() -> (AuditLog, ActivityService, Arc<SqlitePool>, ChangeFeed) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let feed = ChangeFeed::new();
+        (
+            AuditLog::new(pool.clone(), feed.clone()),
+            ActivityService::new(pool.clone()),
+            pool,
+            feed,
+        )
+    }
+
+    fn query() -> ActivityQuery {
+        ActivityQuery {
+            page: None,
+            page_size: None,
+            actor: None,
+            entity_type: None,
+            entity_id: None,
+            action: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    fn page() -> Pagination {
+        Pagination {
+            page: 1,
+            page_size: 20,
+            cursor: None,
+        }
+    }
+
+    #[test]
+    fn diff_reports_changed_fields_only() {
+        let d = diff(
+            Some(json!({"id": 1, "title": "a", "url": "u", "updated_at": "t1"})),
+            Some(json!({"id": 1, "title": "b", "url": "u", "updated_at": "t2"})),
+        );
+        assert_eq!(Value::Object(d), json!({"title": ["a", "b"]}));
+
+        let created = diff(None, Some(json!({"id": 3, "note": null})));
+        assert_eq!(Value::Object(created), json!({"id": [null, 3]}));
+
+        let deleted = diff(Some(json!({"id": 3, "title": "x"})), None);
+        assert_eq!(
+            Value::Object(deleted),
+            json!({"id": [3, null], "title": ["x", null]})
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_changes_are_published() {
+        let (log, _, _, feed) = setup_with_feed().await;
+        let actor = 1;
+        let last = feed.latest_id();
+        log.restored(actor, Entity::Media, "abc").await;
+
+        let missed = feed.subscribe(actor, Some(&last)).missed.unwrap();
+        assert_eq!(missed.len(), 1);
+        let e = &missed[0];
+        assert_eq!(e.entity, Entity::Media);
+        assert_eq!(e.id, "abc");
+        assert_eq!(e.kind, crate::modules::events::ChangeKind::Restored);
+    }
+
+    #[tokio::test]
+    async fn records_and_filters_events() {
+        let (log, svc, _) = setup().await;
+        log.created(1, Entity::Task, 7, &json!({"id": 7, "title": "t"}))
+            .await;
+        log.updated(
+            1,
+            Entity::Task,
+            7,
+            &json!({"title": "t"}),
+            &json!({"title": "u"}),
+        )
+        .await;
+        // 无变化的更新不记录
+        log.updated(
+            1,
+            Entity::Task,
+            7,
+            &json!({"title": "u"}),
+            &json!({"title": "u"}),
+        )
+        .await;
+        log.deleted(2, Entity::Bookmark, 9, &json!({"id": 9})).await;
+
+        let all = svc.list(0, true, &query(), &page()).await.unwrap();
+        assert_eq!(all.total, 3);
+        // 最新的在前
+        assert_eq!(all.items[0].entity_type, "bookmark");
+        assert_eq!(all.items[1].diff, json!({"title": ["t", "u"]}));
+
+        let own = svc.list(1, false, &query(), &page()).await.unwrap();
+        assert_eq!(own.total, 2);
+        assert!(own.items.iter().all(|e| e.actor_id == 1));
+
+        // 非管理员传 actor 无效
+        let q = ActivityQuery {
+            actor: Some(2),
+            ..query()
+        };
+        assert_eq!(svc.list(1, false, &q, &page()).await.unwrap().total, 2);
+        assert_eq!(svc.list(1, true, &q, &page()).await.unwrap().total, 1);
+
+        let q = ActivityQuery {
+            entity_type: Some("task".into()),
+            action: Some("update".into()),
+            ..query()
+        };
+        let hits = svc.list(1, true, &q, &page()).await.unwrap();
+        assert_eq!(hits.total, 1);
+        assert_eq!(hits.items[0].entity_id, "7");
+
+        let q = ActivityQuery {
+            since: Some("2999-01-01T00:00:00+08:00".into()),
+            ..query()
+        };
+        assert_eq!(svc.list(1, true, &q, &page()).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_filters() {
+        let (_, svc, _) = setup().await;
+        for q in [
+            ActivityQuery {
+                entity_type: Some("nope".into()),
+                ..query()
+            },
+            ActivityQuery {
+                action: Some("archive".into()),
+                ..query()
+            },
+            ActivityQuery {
+                since: Some("yesterday".into()),
+                ..query()
+            },
+        ] {
+            assert!(matches!(
+                svc.list(1, false, &q, &page()).await,
+                Err(ServiceError::InvalidInput(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn events_are_append_only() {
+        let (log, _, pool) = setup().await;
+        log.deleted(1, Entity::Card, 1, &json!({"content": "x"}))
+            .await;
+        assert!(
+            sqlx::query("UPDATE audit_event SET actor_id = 2")
+                .execute(&*pool)
+                .await
+                .is_err()
+        );
+        assert!(
+            sqlx::query("DELETE FROM audit_event")
+                .execute(&*pool)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{
+    Extension,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use super::model::{ApiTokenInfo, CreateApiTokenRequest, CreatedApiToken};
+use crate::auth::Claims;
+use crate::error::{deleted_or, ok_or};
+use crate::state::AppState;
+
+/// GET /api/user/tokens
+#[utoipa::path(
+    get,
+    path = "/user/tokens",
+    summary = "API 令牌列表",
+    responses((status = 200, description = "成功", body = Vec<ApiTokenInfo>))
+)]
+pub async fn list_tokens_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    ok_or(state.api_token.list(claims.sub).await, "获取 API 令牌")
+}
+
+/// POST /api/user/tokens — 明文 token 仅在响应中出现一次
+#[utoipa::path(
+    post,
+    path = "/user/tokens",
+    summary = "创建 API 令牌（明文仅返回一次）",
+    request_body = CreateApiTokenRequest,
+    responses((status = 201, description = "已创建", body = CreatedApiToken))
+)]
+pub async fn create_token_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> impl IntoResponse {
+    match state.api_token.create(claims.sub, payload).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/user/tokens/{id}
+#[utoipa::path(
+    delete,
+    path = "/user/tokens/{id}",
+    summary = "吊销 API 令牌",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn revoke_token_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    deleted_or(
+        state.api_token.revoke(claims.sub, id).await,
+        "吊销 API 令牌",
+    )
+}
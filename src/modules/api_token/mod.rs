@@ -0,0 +1,20 @@
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use service::ApiTokenService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+/// API 令牌管理
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::list_tokens_handler,
+            handler::create_token_handler
+        ))
+        .routes(routes!(handler::revoke_token_handler))
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// 个人访问令牌明文前缀，auth 中间件据此区分 PAT 与 JWT
+pub const TOKEN_PREFIX: &str = "bb_pat_";
+
+/// 可授权的资源，对应 `routes::api` 中的路由组路径
+pub const SCOPE_RESOURCES: &[&str] = &[
+    "mem",
+    "bookmarks",
+    "tasks",
+    "cards",
+    "media",
+    "text",
+    "onto",
+    "sign",
+    "reading",
+    "time-windows",
+];
+
+/// 作用域格式 `资源:read` / `资源:write` / `资源:*`
+pub fn is_valid_scope(scope: &str) -> bool {
+    match scope.split_once(':') {
+        Some((resource, action)) => {
+            SCOPE_RESOURCES.contains(&resource) && matches!(action, "read" | "write" | "*")
+        }
+        None => false,
+    }
+}
+
+/// 作用域是否允许访问资源。`write` 与 `*` 同时覆盖读。
+pub fn scope_allows(scopes: &[String], resource: &str, write: bool) -> bool {
+    scopes.iter().any(|s| match s.split_once(':') {
+        Some((r, action)) if r == resource => match action {
+            "*" | "write" => true,
+            "read" => !write,
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiTokenRow {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// 令牌列表项（不含明文）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ApiTokenInfo {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<ApiTokenRow> for ApiTokenInfo {
+    fn from(r: ApiTokenRow) -> Self {
+        Self {
+            id: r.id,
+            name: r.name,
+            prefix: r.prefix,
+            scopes: r.scopes.split_whitespace().map(String::from).collect(),
+            expires_at: r.expires_at,
+            last_used_at: r.last_used_at,
+            created_at: r.created_at,
+        }
+    }
+}
+
+/// 创建成功的响应：明文 token 只出现这一次
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub info: ApiTokenInfo,
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// 有效天数，不填则永不过期
+    pub expires_in_days: Option<i64>,
+}
+
+/// 按明文查到的有效令牌
+#[derive(Debug, Clone, FromRow)]
+pub struct TokenOwner {
+    pub user_id: i32,
+    pub role: String,
+    pub scopes: String,
+    pub expires_at: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn valid_scopes() {
+        assert!(is_valid_scope("mem:read"));
+        assert!(is_valid_scope("bookmarks:write"));
+        assert!(is_valid_scope("tasks:*"));
+        assert!(!is_valid_scope("mem"));
+        assert!(!is_valid_scope("mem:delete"));
+        assert!(!is_valid_scope("db:read"));
+    }
+
+    #[test]
+    fn read_scope_blocks_write() {
+        let s = scopes(&["mem:read"]);
+        assert!(scope_allows(&s, "mem", false));
+        assert!(!scope_allows(&s, "mem", true));
+    }
+
+    #[test]
+    fn write_and_wildcard_cover_read() {
+        let s = scopes(&["bookmarks:write", "tasks:*"]);
+        assert!(scope_allows(&s, "bookmarks", false));
+        assert!(scope_allows(&s, "bookmarks", true));
+        assert!(scope_allows(&s, "tasks", true));
+        assert!(!scope_allows(&s, "mem", false));
+    }
+}
@@ -0,0 +1,73 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::model::{ApiTokenRow, TokenOwner};
+
+#[derive(Clone)]
+pub struct ApiTokenRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl ApiTokenRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        prefix: &str,
+        scopes: &str,
+        expires_at: Option<&str>,
+    ) -> Result<ApiTokenRow, sqlx::Error> {
+        sqlx::query_as::<_, ApiTokenRow>(
+            "INSERT INTO api_token (user_id, name, token_hash, prefix, scopes, expires_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             RETURNING id, name, prefix, scopes, expires_at, last_used_at, created_at",
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(token_hash)
+        .bind(prefix)
+        .bind(scopes)
+        .bind(expires_at)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn list(&self, user_id: i32) -> Result<Vec<ApiTokenRow>, sqlx::Error> {
+        sqlx::query_as::<_, ApiTokenRow>(
+            "SELECT id, name, prefix, scopes, expires_at, last_used_at, created_at
+             FROM api_token WHERE user_id = ? ORDER BY id DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM api_token WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 按 hash 查未过期的令牌，命中时顺带刷新 last_used_at
+    pub async fn touch(&self, token_hash: &str) -> Result<Option<TokenOwner>, sqlx::Error> {
+        sqlx::query_as::<_, TokenOwner>(
+            "UPDATE api_token SET last_used_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE token_hash = ?
+               AND (expires_at IS NULL OR expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+             RETURNING user_id,
+                       (SELECT role FROM user WHERE user.id = api_token.user_id) AS role,
+                       scopes, expires_at",
+        )
+        .bind(token_hash)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+}
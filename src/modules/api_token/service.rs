@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+
+use super::model::{
+    ApiTokenInfo, CreateApiTokenRequest, CreatedApiToken, TOKEN_PREFIX, is_valid_scope,
+};
+use super::repository::ApiTokenRepo;
+use crate::auth::{Claims, hash_token};
+use crate::error::ServiceError;
+
+/// 列表中展示的明文长度（含前缀）
+const DISPLAY_PREFIX_LEN: usize = TOKEN_PREFIX.len() + 6;
+
+#[derive(Clone)]
+pub struct ApiTokenService {
+    repo: ApiTokenRepo,
+}
+
+impl ApiTokenService {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self {
+            repo: ApiTokenRepo::new(db),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i32,
+        req: CreateApiTokenRequest,
+    ) -> Result<CreatedApiToken, ServiceError> {
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(ServiceError::InvalidInput("令牌名称不能为空".into()));
+        }
+        if req.scopes.is_empty() {
+            return Err(ServiceError::InvalidInput("至少需要一个作用域".into()));
+        }
+        if let Some(bad) = req.scopes.iter().find(|s| !is_valid_scope(s)) {
+            return Err(ServiceError::InvalidInput(format!("无效的作用域: {bad}")));
+        }
+        let expires_at = match req.expires_in_days {
+            Some(days) if days <= 0 => {
+                return Err(ServiceError::InvalidInput("有效天数必须为正数".into()));
+            }
+            Some(days) => Some(
+                (chrono::Utc::now() + chrono::Duration::days(days))
+                    .format("%Y-%m-%dT%H:%M:%SZ")
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let token = format!("{TOKEN_PREFIX}{}", nanoid::nanoid!(40));
+        let row = self
+            .repo
+            .create(
+                user_id,
+                name,
+                &hash_token(&token),
+                &token[..DISPLAY_PREFIX_LEN],
+                &req.scopes.join(" "),
+                expires_at.as_deref(),
+            )
+            .await?;
+
+        Ok(CreatedApiToken {
+            info: row.into(),
+            token,
+        })
+    }
+
+    pub async fn list(&self, user_id: i32) -> Result<Vec<ApiTokenInfo>, sqlx::Error> {
+        Ok(self
+            .repo
+            .list(user_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// 吊销（删除）令牌，返回受影响行数
+    pub async fn revoke(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        self.repo.delete(user_id, id).await
+    }
+
+    /// 校验明文令牌，有效时构造带作用域的 Claims（无 sid / jti，不参与会话吊销）
+    pub async fn authenticate(&self, token: &str) -> Result<Option<Claims>, sqlx::Error> {
+        let Some(owner) = self.repo.touch(&hash_token(token)).await? else {
+            return Ok(None);
+        };
+        let exp = owner
+            .expires_at
+            .as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.timestamp() as usize)
+            .unwrap_or(0);
+        Ok(Some(Claims {
+            sub: owner.user_id,
+            role: owner.role,
+            exp,
+            sid: String::new(),
+            jti: String::new(),
+            scopes: Some(owner.scopes.split_whitespace().map(String::from).collect()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    async fn setup() -> (ApiTokenService, Arc<SqlitePool>, i32) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let uid: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('bot', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        (ApiTokenService::new(pool.clone()), pool, uid)
+    }
+
+    fn request(scopes: &[&str], expires_in_days: Option<i64>) -> CreateApiTokenRequest {
+        CreateApiTokenRequest {
+            name: "import script".into(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+            expires_in_days,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_and_authenticate() {
+        let (svc, _, uid) = setup().await;
+        let created = svc
+            .create(uid, request(&["mem:write", "bookmarks:write"], Some(30)))
+            .await
+            .unwrap();
+        assert!(created.token.starts_with(TOKEN_PREFIX));
+        assert!(created.token.starts_with(&created.info.prefix));
+
+        let claims = svc.authenticate(&created.token).await.unwrap().unwrap();
+        assert_eq!(claims.sub, uid);
+        assert_eq!(claims.role, "user");
+        assert_eq!(
+            claims.scopes.unwrap(),
+            vec!["mem:write".to_string(), "bookmarks:write".to_string()]
+        );
+        assert!(claims.exp > 0);
+
+        let listed = svc.list(uid).await.unwrap();
+        assert!(listed[0].last_used_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn plaintext_is_not_stored() {
+        let (svc, pool, uid) = setup().await;
+        let created = svc.create(uid, request(&["mem:read"], None)).await.unwrap();
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_token WHERE token_hash = ?")
+            .bind(&created.token)
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    #[tokio::test]
+    async fn invalid_scope_rejected() {
+        let (svc, _, uid) = setup().await;
+        let err = svc
+            .create(uid, request(&["db:read"], None))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::InvalidInput(_)));
+        let err = svc.create(uid, request(&[], None)).await.unwrap_err();
+        assert!(matches!(err, ServiceError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn expired_token_rejected() {
+        let (svc, pool, uid) = setup().await;
+        let created = svc
+            .create(uid, request(&["mem:read"], Some(1)))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE api_token SET expires_at = '2000-01-01T00:00:00Z'")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        assert!(svc.authenticate(&created.token).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn revoked_token_rejected() {
+        let (svc, _, uid) = setup().await;
+        let created = svc.create(uid, request(&["tasks:*"], None)).await.unwrap();
+        assert_eq!(svc.revoke(uid + 1, created.info.id).await.unwrap(), 0);
+        assert_eq!(svc.revoke(uid, created.info.id).await.unwrap(), 1);
+        assert!(svc.authenticate(&created.token).await.unwrap().is_none());
+    }
+}
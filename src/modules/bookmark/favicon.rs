@@ -1,33 +1,39 @@
 //! 网页 favicon 获取与缓存。
 //!
 //! `GET /api/bookmarks/favicon?url=…` 返回书签网站的图标：
-//! 1. 优先读磁盘缓存 `uploads/favicons/{host}.{ext}`
-//! 2. 未命中则抓取 `https://{host}/favicon.ico`
+//! 1. 优先读存储中的缓存 `favicons/{host}.{ext}`
+//! 2. 未命中则排队 `favicon_fetch` 任务抓取 `https://{host}/favicon.ico`
 //! 3. 失败则解析首页 HTML 的 `<link rel="icon">` 提取
 //!
 //! 公开接口（favicon 无敏感信息，且 `<img>` 无法携带 Authorization 头）。
 //! 安全限制：仅接受域名格式的 host（拒绝 IP/内网地址），避免 SSRF；
 //! 文件大小与抓取耗时均有上限。
 
-use std::path::PathBuf;
 use std::time::Duration;
 
 use axum::{
-    extract::Query,
+    extract::{Query, State},
     http::{StatusCode, header},
     response::Response,
 };
 use serde::Deserialize;
-use tokio::io::AsyncWriteExt;
+use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::error;
+use crate::modules::job::{JobKind, NewJob};
+use crate::openapi::Binary;
+use crate::state::AppState;
+use crate::storage::Storage;
 
-const FAVICON_CACHE_DIR: &str = "uploads/favicons";
 /// 单个 favicon 最大字节数
 const MAX_FAVICON_BYTES: u64 = 512 * 1024;
 const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// 请求内等待抓取任务的上限（最多串行三次抓取）
+const FETCH_WAIT: Duration = Duration::from_secs(16);
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FaviconQuery {
     pub url: String,
 }
@@ -85,46 +91,38 @@ fn looks_like_domain(host: &str) -> bool {
     })
 }
 
-fn cache_dir() -> PathBuf {
-    PathBuf::from(FAVICON_CACHE_DIR)
+fn cache_key(host: &str, ext: &str) -> String {
+    format!("favicons/{host}.{ext}")
 }
 
-fn cache_path(host: &str, ext: &str) -> PathBuf {
-    cache_dir().join(format!("{host}.{ext}"))
-}
-
-fn read_cached(host: &str) -> Option<(Vec<u8>, &'static str)> {
+async fn read_cached(storage: &dyn Storage, host: &str) -> Option<(Vec<u8>, &'static str)> {
     for ext in ["ico", "png"] {
-        let path = cache_path(host, ext);
-        if let Ok(bytes) = std::fs::read(&path) {
-            if !bytes.is_empty() {
-                return Some((
-                    bytes,
-                    if ext == "ico" {
-                        "image/x-icon"
-                    } else {
-                        "image/png"
-                    },
-                ));
-            }
+        if let Ok(Some(bytes)) = storage.get(&cache_key(host, ext)).await
+            && !bytes.is_empty()
+        {
+            return Some((
+                bytes,
+                if ext == "ico" {
+                    "image/x-icon"
+                } else {
+                    "image/png"
+                },
+            ));
         }
     }
     None
 }
 
-async fn save_cache(host: &str, bytes: &[u8]) {
-    let dir = cache_dir();
-    tokio::fs::create_dir_all(&dir).await.ok();
+async fn save_cache(storage: &dyn Storage, host: &str, bytes: &[u8]) -> Result<(), String> {
     // 用 infer 判断类型；未知则按 ico 存
     let ext = match infer::get(bytes) {
         Some(t) if t.mime_type() == "image/png" => "png",
         _ => "ico",
     };
-    let path = cache_path(host, ext);
-    if let Ok(mut f) = tokio::fs::File::create(&path).await {
-        f.write_all(bytes).await.ok();
-        f.sync_all().await.ok();
-    }
+    storage
+        .put(&cache_key(host, ext), bytes)
+        .await
+        .map_err(|e| format!("写入 {host} 的图标缓存失败: {e}"))
 }
 
 fn file_response(bytes: Vec<u8>, mime: &'static str) -> Response {
@@ -139,14 +137,25 @@ fn file_response(bytes: Vec<u8>, mime: &'static str) -> Response {
     resp
 }
 
-fn build_client() -> Result<reqwest::Client, Response> {
+fn build_client() -> reqwest::Result<reqwest::Client> {
     reqwest::Client::builder()
         .timeout(FETCH_TIMEOUT)
         .connect_timeout(Duration::from_secs(3))
         .redirect(reqwest::redirect::Policy::limited(3))
         .user_agent("Mozilla/5.0 (compatible; Brainbow/1.0)")
         .build()
-        .map_err(|e| error::internal(e, "创建抓取客户端"))
+}
+
+/// 抓取 `host` 的图标并写入缓存（`favicon_fetch` 后台任务）
+pub(crate) async fn fetch_and_cache(storage: &dyn Storage, host: &str) -> Result<(), String> {
+    if !looks_like_domain(host) {
+        return Err(format!("无效的域名: {host}"));
+    }
+    let client = build_client().map_err(|e| format!("创建抓取客户端失败: {e}"))?;
+    let (bytes, _) = fetch_favicon(&client, host)
+        .await
+        .ok_or_else(|| format!("未找到 {host} 的图标"))?;
+    save_cache(storage, host, &bytes).await
 }
 
 /// 抓取 favicon：先试 /favicon.ico，再解析首页 HTML 的 link rel=icon
@@ -178,15 +187,15 @@ async fn fetch_url_favicon(client: &reqwest::Client, url: &str) -> Option<(Vec<u
         return None;
     }
     // 只接受图片类型
-    if let Some(t) = infer::get(&bytes) {
-        if t.mime_type().starts_with("image/") {
-            let mime = if t.mime_type() == "image/png" {
-                "image/png"
-            } else {
-                "image/x-icon"
-            };
-            return Some((bytes.to_vec(), mime));
-        }
+    if let Some(t) = infer::get(&bytes)
+        && t.mime_type().starts_with("image/")
+    {
+        let mime = if t.mime_type() == "image/png" {
+            "image/png"
+        } else {
+            "image/x-icon"
+        };
+        return Some((bytes.to_vec(), mime));
     }
     // infer 识别不了的（如部分 .ico 变体）也兜底接收
     Some((bytes.to_vec(), "image/x-icon"))
@@ -208,12 +217,11 @@ fn extract_link_icon_href(html: &str) -> Option<String> {
             || tag_lower.contains("rel='icon'")
             || tag_lower.contains("rel=\"shortcut icon\"")
             || tag_lower.contains("rel='shortcut icon'");
-        if has_icon_rel {
-            if let Some(href) = extract_attr(tag_lower, tag, "href") {
-                if !href.is_empty() {
-                    return Some(href);
-                }
-            }
+        if has_icon_rel
+            && let Some(href) = extract_attr(tag_lower, tag, "href")
+            && !href.is_empty()
+        {
+            return Some(href);
         }
         search = link_start + link_end_rel;
     }
@@ -258,25 +266,41 @@ fn resolve_href(host: &str, href: &str) -> Option<String> {
     }
 }
 
-pub async fn favicon_handler(Query(q): Query<FaviconQuery>) -> Response {
+/// 缓存未命中时交给后台任务抓取，并在此等待其结束。
+/// 同一 host 的并发请求共用一个任务。
+#[utoipa::path(
+    get,
+    path = "/bookmarks/favicon",
+    summary = "获取站点图标",
+    params(FaviconQuery),
+    responses((status = 200, description = "成功", body = Binary, content_type = "image/*"))
+)]
+pub async fn favicon_handler(
+    State(state): State<AppState>,
+    Query(q): Query<FaviconQuery>,
+) -> Response {
     let Some(host) = extract_host(&q.url) else {
         return error::bad_request("无效的 URL");
     };
 
-    if let Some((bytes, mime)) = read_cached(&host) {
+    if let Some((bytes, mime)) = read_cached(state.storage.as_ref(), &host).await {
         return file_response(bytes, mime);
     }
 
-    let client = match build_client() {
-        Ok(c) => c,
-        Err(e) => return e,
+    let job = NewJob::new(JobKind::FaviconFetch)
+        .payload(json!({ "host": host }))
+        .max_attempts(1)
+        .dedupe(format!("favicon:{host}"));
+    let job = match state.jobs.enqueue(job).await {
+        Ok(job) => job,
+        Err(e) => return e.into_response(),
     };
+    if let Err(e) = state.jobs.wait(job.id, FETCH_WAIT).await {
+        return e.into_response();
+    }
 
-    match fetch_favicon(&client, &host).await {
-        Some((bytes, mime)) => {
-            save_cache(&host, &bytes).await;
-            file_response(bytes, mime)
-        }
+    match read_cached(state.storage.as_ref(), &host).await {
+        Some((bytes, mime)) => file_response(bytes, mime),
         None => error::not_found("未找到该网站的图标"),
     }
 }
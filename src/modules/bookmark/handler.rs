@@ -1,18 +1,26 @@
 use axum::{
+    Extension,
     extract::{Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
 
+use crate::auth::Claims;
 use crate::error;
-use crate::pagination::{PaginatedResponse, Pagination};
+use crate::etag::{self, IfMatch};
+use crate::modules::job::{JobKind, JobResponse, NewJob};
+use crate::openapi::{FileUpload, PreconditionFailed};
+use crate::pagination::{CursorParam, PaginatedResponse, Pagination};
 use crate::state::AppState;
 
 use super::model::{
     Bookmark, CreateBookmarkRequest, SetBookmarkTagsRequest, UpdateBookmarkRequest,
 };
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BookmarkResponse {
     pub id: i32,
     pub title: String,
@@ -37,7 +45,7 @@ impl From<Bookmark> for BookmarkResponse {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BookmarkTagResponse {
     pub id: i32,
     pub name: String,
@@ -52,7 +60,7 @@ impl From<super::model::BookmarkTag> for BookmarkTagResponse {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BookmarkTagWithCountResponse {
     pub id: i32,
     pub name: String,
@@ -70,6 +78,7 @@ impl From<super::model::BookmarkTagWithCount> for BookmarkTagWithCountResponse {
 }
 
 /// 校验 URL：必须带 http/https 协议
+#[allow(clippy::result_large_err)]
 fn validate_url(url: &str) -> Result<(), Response> {
     let url = url.trim();
     if url.is_empty() {
@@ -81,8 +90,16 @@ fn validate_url(url: &str) -> Result<(), Response> {
     Ok(())
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建书签",
+    request_body = CreateBookmarkRequest,
+    responses((status = 201, description = "已创建", body = BookmarkResponse))
+)]
 pub async fn create_bookmark_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateBookmarkRequest>,
 ) -> impl IntoResponse {
     let title = payload.title.trim();
@@ -104,16 +121,20 @@ pub async fn create_bookmark_handler(
 
     let result = state
         .bookmark
-        .create(title, url, description, &tags)
+        .create(claims.sub, title, url, description, &tags)
         .await
         .map(BookmarkResponse::from);
     error::created_or(result, "创建书签")
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListBookmarksQuery {
     pub page: Option<i64>,
     pub page_size: Option<i64>,
+    /// 游标分页，见 [`Pagination::cursor`]
+    #[param(value_type = Option<String>)]
+    pub cursor: Option<CursorParam>,
     /// 按标签名过滤
     pub tag: Option<String>,
 }
@@ -123,13 +144,22 @@ impl ListBookmarksQuery {
         Pagination {
             page: self.page.unwrap_or(1),
             page_size: self.page_size.unwrap_or(20),
+            cursor: self.cursor.clone(),
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "书签列表",
+    params(ListBookmarksQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<BookmarkResponse>))
+)]
 pub async fn get_bookmarks_handler(
     Query(params): Query<ListBookmarksQuery>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let pagination = params.pagination();
     let tag = params
@@ -139,31 +169,50 @@ pub async fn get_bookmarks_handler(
         .filter(|s| !s.is_empty());
     let result = state
         .bookmark
-        .list(pagination.limit(), pagination.offset(), tag)
+        .list(claims.sub, &pagination.window(), tag)
         .await
-        .map(|(items, total)| {
+        .map(|(items, total, next)| {
             let items: Vec<BookmarkResponse> =
                 items.into_iter().map(BookmarkResponse::from).collect();
-            PaginatedResponse::new(items, total, &pagination)
+            PaginatedResponse::new(items, total, &pagination).with_cursor(next)
         });
     error::ok_or(result, "获取书签列表")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取书签",
+    responses((status = 200, description = "成功", body = BookmarkResponse, headers(("ETag" = String, description = "资源当前版本"))))
+)]
 pub async fn get_bookmark_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    let result = state
-        .bookmark
-        .by_id(id)
-        .await
-        .map(|opt| opt.map(BookmarkResponse::from));
-    error::found_or(result, "获取书签")
+    match state.bookmark.versioned(claims.sub, id).await {
+        Ok(Some(bookmark)) => etag::with(
+            &etag::version(bookmark.version),
+            Json(BookmarkResponse::from(bookmark.row)),
+        ),
+        Ok(None) => error::not_found("获取书签失败: 记录不存在"),
+        Err(e) => error::internal(e, "获取书签"),
+    }
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "更新书签",
+    params(IfMatch),
+    request_body = UpdateBookmarkRequest,
+    responses((status = 200, description = "成功", body = BookmarkResponse, headers(("ETag" = String, description = "资源当前版本"))), PreconditionFailed)
+)]
 pub async fn update_bookmark_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateBookmarkRequest>,
 ) -> impl IntoResponse {
     let title = payload
@@ -178,28 +227,42 @@ pub async fn update_bookmark_handler(
         .filter(|s| !s.is_empty());
     let url = payload.url.as_deref().map(str::trim);
 
-    if let Some(u) = url {
-        if let Err(resp) = validate_url(u) {
-            return resp;
-        }
+    if let Some(u) = url
+        && let Err(resp) = validate_url(u)
+    {
+        return resp;
     }
 
-    let result = state
+    let if_match = IfMatch::from_headers(&headers);
+    match state
         .bookmark
-        .update(id, title, url, description)
+        .update_if(claims.sub, id, title, url, description, &if_match)
         .await
-        .map(BookmarkResponse::from);
-    error::ok_or(result, "更新书签")
+    {
+        Ok(bookmark) => etag::with(
+            &etag::version(bookmark.version),
+            Json(BookmarkResponse::from(bookmark.row)),
+        ),
+        Err(e) => e.into_response(),
+    }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除书签",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_bookmark_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    error::deleted_or(state.bookmark.delete(id).await, "删除书签")
+    error::deleted_or(state.bookmark.delete(claims.sub, id).await, "删除书签")
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchBookmarksQuery {
     pub q: String,
     pub page: Option<i64>,
@@ -213,13 +276,22 @@ impl SearchBookmarksQuery {
         Pagination {
             page: self.page.unwrap_or(1),
             page_size: self.page_size.unwrap_or(20),
+            cursor: None,
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/search",
+    summary = "搜索书签",
+    params(SearchBookmarksQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<BookmarkResponse>))
+)]
 pub async fn search_bookmarks_handler(
     Query(params): Query<SearchBookmarksQuery>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     if params.q.trim().is_empty() {
         return error::bad_request("搜索关键词不能为空");
@@ -233,6 +305,7 @@ pub async fn search_bookmarks_handler(
     let result = state
         .bookmark
         .search(
+            claims.sub,
             params.q.trim(),
             tag,
             pagination.limit(),
@@ -251,9 +324,17 @@ pub async fn search_bookmarks_handler(
 
 /// 导入 Firefox 书签 HTML（multipart 上传，字段名 `file`）。
 ///
-/// 文件夹路径作为标签；按 URL 去重合并。
+/// 文件夹路径作为标签；按 URL 去重合并。校验通过后排队 `bookmark_import` 任务，返回 202。
+#[utoipa::path(
+    post,
+    path = "/import",
+    summary = "导入 Firefox 书签 HTML（后台任务，结果为 ImportResult）",
+    request_body(content = FileUpload, content_type = "multipart/form-data"),
+    responses((status = 202, description = "已排队", body = JobResponse))
+)]
 pub async fn import_bookmarks_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
     let mut html: Option<String> = None;
@@ -294,23 +375,37 @@ pub async fn import_bookmarks_handler(
         ));
     }
 
-    match state.bookmark.import_netscape_html(&html).await {
-        Ok(result) => Json(result).into_response(),
+    // 大文件逐条合并较慢，交给后台任务；前端轮询 /jobs/{id} 取 ImportResult
+    let job = NewJob::new(JobKind::BookmarkImport)
+        .payload(json!({ "html": html }))
+        .user(claims.sub)
+        .max_attempts(1);
+    match state.jobs.enqueue(job).await {
+        Ok(job) => (StatusCode::ACCEPTED, Json(JobResponse::from(job))).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchTagsQuery {
     pub q: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/tags",
+    summary = "搜索标签（含书签数）",
+    params(SearchTagsQuery),
+    responses((status = 200, description = "成功", body = Vec<BookmarkTagWithCountResponse>))
+)]
 pub async fn search_tags_handler(
     Query(params): Query<SearchTagsQuery>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let q = params.q.as_deref().map(str::trim).filter(|s| !s.is_empty());
-    match state.bookmark.search_tags(q).await {
+    match state.bookmark.search_tags(claims.sub, q).await {
         Ok(tags) => {
             let tags: Vec<BookmarkTagWithCountResponse> = tags
                 .into_iter()
@@ -322,13 +417,21 @@ pub async fn search_tags_handler(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTagRequest {
     pub name: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/tags",
+    summary = "创建标签",
+    request_body = CreateTagRequest,
+    responses((status = 201, description = "已创建", body = BookmarkTagResponse))
+)]
 pub async fn create_tag_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateTagRequest>,
 ) -> impl IntoResponse {
     let name = payload.name.trim();
@@ -337,24 +440,38 @@ pub async fn create_tag_handler(
     }
     let result = state
         .bookmark
-        .create_tag(name)
+        .create_tag(claims.sub, name)
         .await
         .map(BookmarkTagResponse::from);
     error::created_or(result, "创建标签")
 }
 
+#[utoipa::path(
+    delete,
+    path = "/tags/{id}",
+    summary = "删除标签",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_tag_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    error::deleted_or(state.bookmark.delete_tag(id).await, "删除标签")
+    error::deleted_or(state.bookmark.delete_tag(claims.sub, id).await, "删除标签")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}/tags",
+    summary = "书签的标签",
+    responses((status = 200, description = "成功", body = Vec<BookmarkTagResponse>))
+)]
 pub async fn get_bookmark_tags_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    match state.bookmark.get_bookmark_tags(id).await {
+    match state.bookmark.get_bookmark_tags(claims.sub, id).await {
         Ok(tags) => {
             let tags: Vec<BookmarkTagResponse> =
                 tags.into_iter().map(BookmarkTagResponse::from).collect();
@@ -364,12 +481,24 @@ pub async fn get_bookmark_tags_handler(
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/{id}/tags",
+    summary = "设置书签标签",
+    request_body = SetBookmarkTagsRequest,
+    responses((status = 200, description = "成功", body = Vec<BookmarkTagResponse>))
+)]
 pub async fn set_bookmark_tags_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
     Json(payload): Json<SetBookmarkTagsRequest>,
 ) -> impl IntoResponse {
-    match state.bookmark.set_bookmark_tags(id, &payload.tags).await {
+    match state
+        .bookmark
+        .set_bookmark_tags(claims.sub, id, &payload.tags)
+        .await
+    {
         Ok(tags) => {
             let tags: Vec<BookmarkTagResponse> =
                 tags.into_iter().map(BookmarkTagResponse::from).collect();
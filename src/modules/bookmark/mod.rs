@@ -5,45 +5,44 @@ mod model;
 pub mod repository;
 pub mod service;
 
-pub use favicon::favicon_handler;
-pub use handler::{
-    create_bookmark_handler, create_tag_handler, delete_bookmark_handler, delete_tag_handler,
-    get_bookmark_handler, get_bookmark_tags_handler, get_bookmarks_handler,
-    import_bookmarks_handler, search_bookmarks_handler, search_tags_handler,
-    update_bookmark_handler,
-};
+pub(crate) use favicon::fetch_and_cache;
+pub use handler::BookmarkResponse;
 pub use service::BookmarkService;
+use utoipa_axum::router::{OpenApiRouter, UtoipaMethodRouterExt};
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{
-    Router,
-    extract::DefaultBodyLimit,
-    routing::{get, post},
-};
+use axum::extract::DefaultBodyLimit;
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        // 静态路径优先于 /{id}，避免 "tags" 被当作 id 解析
-        .route("/tags", get(search_tags_handler).post(create_tag_handler))
-        .route("/tags/{id}", axum::routing::delete(delete_tag_handler))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::search_tags_handler,
+            handler::create_tag_handler
+        ))
+        .routes(routes!(handler::delete_tag_handler))
         // 导入：Firefox 书签 HTML 可能很大（数 MB），放宽 body 限制
-        .route(
-            "/import",
-            post(import_bookmarks_handler).layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
+        .routes(
+            routes!(handler::import_bookmarks_handler)
+                .layer(DefaultBodyLimit::max(64 * 1024 * 1024)),
         )
-        .route(
-            "/",
-            get(get_bookmarks_handler).post(create_bookmark_handler),
-        )
-        .route(
-            "/{id}",
-            get(get_bookmark_handler)
-                .patch(update_bookmark_handler)
-                .delete(delete_bookmark_handler),
-        )
-        .route(
-            "/{id}/tags",
-            get(get_bookmark_tags_handler).put(handler::set_bookmark_tags_handler),
-        )
-        .route("/search", get(search_bookmarks_handler))
+        .routes(routes!(
+            handler::get_bookmarks_handler,
+            handler::create_bookmark_handler
+        ))
+        .routes(routes!(
+            handler::get_bookmark_handler,
+            handler::update_bookmark_handler,
+            handler::delete_bookmark_handler
+        ))
+        .routes(routes!(
+            handler::get_bookmark_tags_handler,
+            handler::set_bookmark_tags_handler
+        ))
+        .routes(routes!(handler::search_bookmarks_handler))
+}
+
+/// `/bookmarks/favicon`：公开路由，挂在根下
+pub fn favicon_route() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(favicon::favicon_handler))
 }
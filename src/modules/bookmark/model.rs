@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
 /// 网页书签
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -63,7 +64,7 @@ impl BookmarkRow {
 }
 
 /// 创建书签请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateBookmarkRequest {
     pub title: String,
     pub url: String,
@@ -74,7 +75,7 @@ pub struct CreateBookmarkRequest {
 }
 
 /// 更新书签请求（可选字段，未提供的字段保持不变）
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateBookmarkRequest {
     pub title: Option<String>,
     pub url: Option<String>,
@@ -82,7 +83,7 @@ pub struct UpdateBookmarkRequest {
 }
 
 /// 设置书签标签请求（按名称整体替换，不存在的标签自动创建）
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetBookmarkTagsRequest {
     pub tags: Vec<String>,
 }
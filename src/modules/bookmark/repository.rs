@@ -1,11 +1,13 @@
 use chrono::Utc;
-use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite, SqlitePool};
 use std::sync::Arc;
 
 use super::model::{Bookmark, BookmarkRow, BookmarkTag, BookmarkTagWithCount};
+use crate::etag::{self, Versioned};
+use crate::pagination::{Cursor, Window};
 
 /// 书签行公共 SELECT（含聚合标签子查询，按名称排序保证与 get_bookmark_tags 一致）
-const BOOKMARK_SELECT: &str = "SELECT id, title, url, description, created_at, updated_at, \
+const BOOKMARK_SELECT: &str = "SELECT id, title, url, description, created_at, updated_at, version, \
     (SELECT GROUP_CONCAT(name, char(31)) FROM \
         (SELECT t.name FROM bookmark_tag_rel r \
          JOIN bookmark_tag t ON t.id = r.tag_id \
@@ -37,14 +39,30 @@ impl BookmarkRepo {
         Self { pool }
     }
 
-    /// 获取所有书签（分页，按创建时间倒序；可选按标签过滤）
+    /// 获取所有书签（偏移分页，按创建时间倒序；可选按标签过滤）
     pub async fn find_all_paginated(
         &self,
+        user_id: i32,
         limit: i64,
         offset: i64,
         tag: Option<&str>,
     ) -> Result<(Vec<Bookmark>, i64), sqlx::Error> {
-        let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM bookmark WHERE 1=1");
+        let window = Window::Offset { limit, offset };
+        let (items, total, _) = self.find_page(user_id, &window, tag).await?;
+        Ok((items, total))
+    }
+
+    /// 获取一页书签（按 `(created_at, id)` 倒序），游标模式附下一页游标
+    pub async fn find_page(
+        &self,
+        user_id: i32,
+        window: &Window,
+        tag: Option<&str>,
+    ) -> Result<(Vec<Bookmark>, i64, Option<Cursor>), sqlx::Error> {
+        let mut count_builder = QueryBuilder::new(
+            "SELECT COUNT(*) FROM bookmark WHERE deleted_at IS NULL AND user_id = ",
+        );
+        count_builder.push_bind(user_id);
         if let Some(t) = tag {
             tags_filter_clause(&mut count_builder, t);
         }
@@ -54,37 +72,65 @@ impl BookmarkRepo {
             .await?;
 
         let mut fetch_builder = QueryBuilder::new(BOOKMARK_SELECT);
-        fetch_builder.push(" WHERE 1=1");
+        fetch_builder.push(" WHERE deleted_at IS NULL AND user_id = ");
+        fetch_builder.push_bind(user_id);
         if let Some(t) = tag {
             tags_filter_clause(&mut fetch_builder, t);
         }
-        fetch_builder.push(" ORDER BY created_at DESC LIMIT ");
-        fetch_builder.push_bind(limit);
-        fetch_builder.push(" OFFSET ");
-        fetch_builder.push_bind(offset);
+        window.push_after(&mut fetch_builder, "created_at", "id", true);
+        fetch_builder.push(" ORDER BY created_at DESC, id DESC");
+        window.push_limit(&mut fetch_builder);
 
-        let items: Vec<BookmarkRow> = fetch_builder
-            .build_query_as()
-            .fetch_all(&*self.pool)
-            .await?;
-        let items = items.into_iter().map(BookmarkRow::into_bookmark).collect();
+        let rows = fetch_builder.build().fetch_all(&*self.pool).await?;
+        let (rows, next) = window.finish(rows, "created_at", "id")?;
+        let items = rows
+            .iter()
+            .map(|r| BookmarkRow::from_row(r).map(BookmarkRow::into_bookmark))
+            .collect::<Result<_, _>>()?;
 
-        Ok((items, total))
+        Ok((items, total, next))
     }
 
     /// 根据 ID 获取书签
-    pub async fn find_by_id(&self, id: i32) -> Result<Option<Bookmark>, sqlx::Error> {
-        let row = sqlx::query_as::<_, BookmarkRow>(select_with("WHERE id = ?"))
-            .bind(id)
-            .fetch_optional(&*self.pool)
-            .await?;
+    pub async fn find_by_id(&self, user_id: i32, id: i32) -> Result<Option<Bookmark>, sqlx::Error> {
+        let row = sqlx::query_as::<_, BookmarkRow>(select_with(
+            "WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await?;
 
         Ok(row.map(BookmarkRow::into_bookmark))
     }
 
+    /// 连同行版本号获取书签，供 ETag 使用
+    pub async fn find_versioned(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<Versioned<Bookmark>>, sqlx::Error> {
+        let row = sqlx::query(select_with(
+            "WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        row.map(|r| {
+            Ok(Versioned {
+                row: BookmarkRow::from_row(&r)?.into_bookmark(),
+                version: r.try_get("version")?,
+            })
+        })
+        .transpose()
+    }
+
     /// 创建书签
     pub async fn create(
         &self,
+        user_id: i32,
         title: &str,
         url: &str,
         description: &str,
@@ -94,19 +140,20 @@ impl BookmarkRepo {
         let mut tx = self.pool.begin().await?;
 
         let result = sqlx::query(
-            "INSERT INTO bookmark (title, url, description, created_at, updated_at) \
-             VALUES (?, ?, ?, ?, ?) RETURNING id, title, url, description, created_at, updated_at",
+            "INSERT INTO bookmark (title, url, description, user_id, created_at, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?) RETURNING id, title, url, description, created_at, updated_at",
         )
         .bind(title)
         .bind(url)
         .bind(description)
+        .bind(user_id)
         .bind(now)
         .bind(now)
         .fetch_one(&mut *tx)
         .await?;
 
         let id: i32 = result.try_get("id")?;
-        self.replace_tags_in_tx(&mut tx, id, tags).await?;
+        self.replace_tags_in_tx(&mut tx, user_id, id, tags).await?;
         tx.commit().await?;
 
         let mut bookmark = Bookmark {
@@ -119,7 +166,7 @@ impl BookmarkRepo {
             updated_at: result.try_get("updated_at")?,
         };
         bookmark.tags = self
-            .get_bookmark_tags(id)
+            .get_bookmark_tags(user_id, id)
             .await?
             .into_iter()
             .map(|t| t.name)
@@ -128,23 +175,32 @@ impl BookmarkRepo {
     }
 
     /// 根据 URL 获取书签（导入时按 URL 去重/合并）
-    pub async fn find_by_url(&self, url: &str) -> Result<Option<Bookmark>, sqlx::Error> {
-        let row = sqlx::query_as::<_, BookmarkRow>(select_with("WHERE url = ?"))
-            .bind(url)
-            .fetch_optional(&*self.pool)
-            .await?;
+    pub async fn find_by_url(
+        &self,
+        user_id: i32,
+        url: &str,
+    ) -> Result<Option<Bookmark>, sqlx::Error> {
+        let row = sqlx::query_as::<_, BookmarkRow>(select_with(
+            "WHERE url = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(url)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await?;
 
         Ok(row.map(BookmarkRow::into_bookmark))
     }
 
-    /// 更新书签（仅更新提供的字段）
+    /// 更新书签（仅更新提供的字段）；`expected` 为可接受的行版本号，版本不符时不写入并返回 `None`
     pub async fn update(
         &self,
+        user_id: i32,
         id: i32,
         title: Option<&str>,
         url: Option<&str>,
         description: Option<&str>,
-    ) -> Result<Bookmark, sqlx::Error> {
+        expected: Option<&[i64]>,
+    ) -> Result<Option<Versioned<Bookmark>>, sqlx::Error> {
         let now = Utc::now();
 
         let mut builder = QueryBuilder::new("UPDATE bookmark SET ");
@@ -172,11 +228,18 @@ impl BookmarkRepo {
         }
         field!("updated_at = ", now);
         assert!(field_count > 0, "update must set at least updated_at");
+        builder.push(", version = version + 1");
         builder.push(" WHERE id = ");
         builder.push_bind(id);
-        builder.push(" RETURNING id, title, url, description, created_at, updated_at");
-
-        let result = builder.build().fetch_one(&*self.pool).await?;
+        builder.push(" AND user_id = ");
+        builder.push_bind(user_id);
+        builder.push(" AND deleted_at IS NULL");
+        etag::push_guard(&mut builder, expected);
+        builder.push(" RETURNING id, title, url, description, created_at, updated_at, version");
+
+        let Some(result) = builder.build().fetch_optional(&*self.pool).await? else {
+            return Ok(None);
+        };
 
         let mut bookmark = Bookmark {
             id: result.try_get("id")?,
@@ -188,20 +251,54 @@ impl BookmarkRepo {
             updated_at: result.try_get("updated_at")?,
         };
         bookmark.tags = self
-            .get_bookmark_tags(id)
+            .get_bookmark_tags(user_id, id)
             .await?
             .into_iter()
             .map(|t| t.name)
             .collect();
-        Ok(bookmark)
+        Ok(Some(Versioned {
+            row: bookmark,
+            version: result.try_get("version")?,
+        }))
     }
 
-    /// 删除书签（关联标签关系由外键级联删除）
-    pub async fn delete(&self, id: i32) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query("DELETE FROM bookmark WHERE id = ?")
-            .bind(id)
-            .execute(&*self.pool)
-            .await?;
+    /// 删除书签（移入回收站，标签关系保留以便恢复）
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE bookmark SET deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 从回收站恢复书签
+    pub async fn restore(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE bookmark SET deleted_at = NULL \
+             WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 彻底删除回收站中的书签（关联标签关系由外键级联删除）
+    pub async fn purge(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM bookmark WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
 
         Ok(result.rows_affected())
     }
@@ -209,6 +306,7 @@ impl BookmarkRepo {
     /// 按关键词搜索书签（匹配标题/URL/备注，命中越多得分越高；可选按标签过滤）
     pub async fn search_paginated(
         &self,
+        user_id: i32,
         query: &str,
         tag: Option<&str>,
         limit: i64,
@@ -216,10 +314,14 @@ impl BookmarkRepo {
     ) -> Result<(Vec<Bookmark>, i64), sqlx::Error> {
         let keywords: Vec<&str> = query.split_whitespace().collect();
         if keywords.is_empty() {
-            return self.find_all_paginated(limit, offset, tag).await;
+            return self.find_all_paginated(user_id, limit, offset, tag).await;
         }
 
-        let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM bookmark WHERE ");
+        let mut count_builder = QueryBuilder::new(
+            "SELECT COUNT(*) FROM bookmark WHERE deleted_at IS NULL AND user_id = ",
+        );
+        count_builder.push_bind(user_id);
+        count_builder.push(" AND ");
         Self::append_keyword_where(&mut count_builder, &keywords);
         if let Some(t) = tag {
             tags_filter_clause(&mut count_builder, t);
@@ -230,7 +332,9 @@ impl BookmarkRepo {
             .await?;
 
         let mut fetch_builder = QueryBuilder::new(BOOKMARK_SELECT);
-        fetch_builder.push(" WHERE ");
+        fetch_builder.push(" WHERE deleted_at IS NULL AND user_id = ");
+        fetch_builder.push_bind(user_id);
+        fetch_builder.push(" AND ");
         Self::append_keyword_where(&mut fetch_builder, &keywords);
         if let Some(t) = tag {
             tags_filter_clause(&mut fetch_builder, t);
@@ -263,8 +367,9 @@ impl BookmarkRepo {
         Ok((items, total))
     }
 
-    /// 关键词 OR 条件片段（与评分逻辑共用同一组关键词）
+    /// 关键词 OR 条件片段（与评分逻辑共用同一组关键词；整体加括号，便于与其它条件 AND）
     fn append_keyword_where(builder: &mut QueryBuilder<Sqlite>, keywords: &[&str]) {
+        builder.push("(");
         for (i, kw) in keywords.iter().enumerate() {
             if i > 0 {
                 builder.push(" OR ");
@@ -277,6 +382,7 @@ impl BookmarkRepo {
             builder.push_bind(format!("%{}%", kw));
             builder.push(")");
         }
+        builder.push(")");
     }
 
     // ── 标签 ──
@@ -284,28 +390,34 @@ impl BookmarkRepo {
     /// 搜索标签（q 为空则返回全部），带使用次数
     pub async fn search_tags(
         &self,
+        user_id: i32,
         q: Option<&str>,
     ) -> Result<Vec<BookmarkTagWithCount>, sqlx::Error> {
         let q = q.unwrap_or("").trim();
         let rows = if q.is_empty() {
             sqlx::query_as::<_, BookmarkTagWithCount>(
-                "SELECT t.id, t.name, COUNT(r.bookmark_id) AS count \
+                "SELECT t.id, t.name, COUNT(b.id) AS count \
                  FROM bookmark_tag t \
                  LEFT JOIN bookmark_tag_rel r ON r.tag_id = t.id \
+                 LEFT JOIN bookmark b ON b.id = r.bookmark_id AND b.deleted_at IS NULL \
+                 WHERE t.user_id = ? \
                  GROUP BY t.id, t.name \
                  ORDER BY count DESC, t.name",
             )
+            .bind(user_id)
             .fetch_all(&*self.pool)
             .await?
         } else {
             sqlx::query_as::<_, BookmarkTagWithCount>(
-                "SELECT t.id, t.name, COUNT(r.bookmark_id) AS count \
+                "SELECT t.id, t.name, COUNT(b.id) AS count \
                  FROM bookmark_tag t \
                  LEFT JOIN bookmark_tag_rel r ON r.tag_id = t.id \
-                 WHERE t.name LIKE ? \
+                 LEFT JOIN bookmark b ON b.id = r.bookmark_id AND b.deleted_at IS NULL \
+                 WHERE t.user_id = ? AND t.name LIKE ? \
                  GROUP BY t.id, t.name \
                  ORDER BY count DESC, t.name",
             )
+            .bind(user_id)
             .bind(format!("%{}%", q))
             .fetch_all(&*self.pool)
             .await?
@@ -314,26 +426,20 @@ impl BookmarkRepo {
     }
 
     /// 创建标签；已存在时返回现有标签
-    pub async fn create_tag(&self, name: &str) -> Result<BookmarkTag, sqlx::Error> {
-        let result = sqlx::query("INSERT OR IGNORE INTO bookmark_tag (name) VALUES (?)")
+    pub async fn create_tag(&self, user_id: i32, name: &str) -> Result<BookmarkTag, sqlx::Error> {
+        sqlx::query("INSERT OR IGNORE INTO bookmark_tag (name, user_id) VALUES (?, ?)")
             .bind(name)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
-        if result.rows_affected() == 0 {
-            return self.find_tag_by_name(name).await;
-        }
-        let row =
-            sqlx::query_as::<_, BookmarkTag>("SELECT id, name FROM bookmark_tag WHERE name = ?")
-                .bind(name)
-                .fetch_one(&*self.pool)
-                .await?;
-        Ok(row)
+        self.find_tag_by_name(user_id, name).await
     }
 
     /// 删除标签（关联关系由外键级联删除）
-    pub async fn delete_tag(&self, id: i32) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query("DELETE FROM bookmark_tag WHERE id = ?")
+    pub async fn delete_tag(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM bookmark_tag WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         Ok(result.rows_affected())
@@ -342,37 +448,52 @@ impl BookmarkRepo {
     /// 获取书签的标签
     pub async fn get_bookmark_tags(
         &self,
+        user_id: i32,
         bookmark_id: i32,
     ) -> Result<Vec<BookmarkTag>, sqlx::Error> {
         let rows = sqlx::query_as::<_, BookmarkTag>(
             "SELECT t.id, t.name \
              FROM bookmark_tag t \
              JOIN bookmark_tag_rel r ON r.tag_id = t.id \
-             WHERE r.bookmark_id = ? \
+             JOIN bookmark b ON b.id = r.bookmark_id \
+             WHERE r.bookmark_id = ? AND b.user_id = ? AND b.deleted_at IS NULL \
              ORDER BY t.name",
         )
         .bind(bookmark_id)
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await?;
         Ok(rows)
     }
 
     /// 整体替换书签的标签（按名称，不存在的自动创建）
+    ///
+    /// 书签不存在或属于其他用户时返回 `RowNotFound`。
     pub async fn set_bookmark_tags(
         &self,
+        user_id: i32,
         bookmark_id: i32,
         names: &[String],
     ) -> Result<Vec<BookmarkTag>, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
-        self.replace_tags_in_tx(&mut tx, bookmark_id, names).await?;
+        sqlx::query_scalar::<_, i32>(
+            "SELECT id FROM bookmark WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        )
+        .bind(bookmark_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        self.replace_tags_in_tx(&mut tx, user_id, bookmark_id, names)
+            .await?;
         tx.commit().await?;
-        self.get_bookmark_tags(bookmark_id).await
+        self.get_bookmark_tags(user_id, bookmark_id).await
     }
 
     /// 在事务内：确保标签存在 → 清空 → 重建关联
     async fn replace_tags_in_tx(
         &self,
         tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        user_id: i32,
         bookmark_id: i32,
         names: &[String],
     ) -> Result<(), sqlx::Error> {
@@ -382,14 +503,17 @@ impl BookmarkRepo {
             if name.is_empty() {
                 continue;
             }
-            sqlx::query("INSERT OR IGNORE INTO bookmark_tag (name) VALUES (?)")
+            sqlx::query("INSERT OR IGNORE INTO bookmark_tag (name, user_id) VALUES (?, ?)")
                 .bind(name)
+                .bind(user_id)
                 .execute(&mut **tx)
                 .await?;
-            let id: i32 = sqlx::query_scalar("SELECT id FROM bookmark_tag WHERE name = ?")
-                .bind(name)
-                .fetch_one(&mut **tx)
-                .await?;
+            let id: i32 =
+                sqlx::query_scalar("SELECT id FROM bookmark_tag WHERE name = ? AND user_id = ?")
+                    .bind(name)
+                    .bind(user_id)
+                    .fetch_one(&mut **tx)
+                    .await?;
             tag_ids.push(id);
         }
 
@@ -410,12 +534,14 @@ impl BookmarkRepo {
         Ok(())
     }
 
-    async fn find_tag_by_name(&self, name: &str) -> Result<BookmarkTag, sqlx::Error> {
-        let row =
-            sqlx::query_as::<_, BookmarkTag>("SELECT id, name FROM bookmark_tag WHERE name = ?")
-                .bind(name)
-                .fetch_one(&*self.pool)
-                .await?;
+    async fn find_tag_by_name(&self, user_id: i32, name: &str) -> Result<BookmarkTag, sqlx::Error> {
+        let row = sqlx::query_as::<_, BookmarkTag>(
+            "SELECT id, name FROM bookmark_tag WHERE name = ? AND user_id = ?",
+        )
+        .bind(name)
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await?;
         Ok(row)
     }
 }
@@ -426,6 +552,8 @@ mod tests {
     use super::*;
     use sqlx::SqlitePool;
 
+    const USER: i32 = 1;
+
     async fn setup_db() -> BookmarkRepo {
         let pool = SqlitePool::connect("sqlite::memory:")
             .await
@@ -437,8 +565,11 @@ mod tests {
                 title TEXT NOT NULL,
                 url TEXT NOT NULL,
                 description TEXT NOT NULL DEFAULT '',
+                user_id INTEGER,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                deleted_at TEXT
             )",
         )
         .execute(&pool)
@@ -447,8 +578,10 @@ mod tests {
         sqlx::query(
             "CREATE TABLE bookmark_tag (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE,
-                created_at TEXT NOT NULL DEFAULT ''
+                name TEXT NOT NULL,
+                user_id INTEGER,
+                created_at TEXT NOT NULL DEFAULT '',
+                UNIQUE(name, user_id)
             )",
         )
         .execute(&pool)
@@ -481,6 +614,7 @@ mod tests {
         let repo = setup_db().await;
         let bm = repo
             .create(
+                USER,
                 "Rust 官网",
                 "https://www.rust-lang.org/",
                 "学习",
@@ -490,28 +624,30 @@ mod tests {
             .unwrap();
         assert_eq!(bm.tags, str_vec(&["rust", "编程"])); // 按名称排序
 
-        let found = repo.find_by_id(bm.id).await.unwrap().expect("应找到");
+        let found = repo.find_by_id(USER, bm.id).await.unwrap().expect("应找到");
         assert_eq!(found.tags, str_vec(&["rust", "编程"]));
     }
 
     #[tokio::test]
     async fn find_by_id_not_found() {
         let repo = setup_db().await;
-        assert!(repo.find_by_id(999).await.unwrap().is_none());
+        assert!(repo.find_by_id(USER, 999).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn find_all_paginated_with_tags() {
         let repo = setup_db().await;
-        repo.create("A", "https://a.com", "", &str_vec(&["x"]))
+        repo.create(USER, "A", "https://a.com", "", &str_vec(&["x"]))
             .await
             .unwrap();
-        repo.create("B", "https://b.com", "", &[]).await.unwrap();
-        repo.create("C", "https://c.com", "", &str_vec(&["y", "x"]))
+        repo.create(USER, "B", "https://b.com", "", &[])
+            .await
+            .unwrap();
+        repo.create(USER, "C", "https://c.com", "", &str_vec(&["y", "x"]))
             .await
             .unwrap();
 
-        let (items, total) = repo.find_all_paginated(10, 0, None).await.unwrap();
+        let (items, total) = repo.find_all_paginated(USER, 10, 0, None).await.unwrap();
         assert_eq!(total, 3);
         assert_eq!(items[0].title, "C");
         assert_eq!(items[0].tags, str_vec(&["x", "y"]));
@@ -521,20 +657,25 @@ mod tests {
     #[tokio::test]
     async fn find_all_filtered_by_tag() {
         let repo = setup_db().await;
-        repo.create("A", "https://a.com", "", &str_vec(&["编程"]))
+        repo.create(USER, "A", "https://a.com", "", &str_vec(&["编程"]))
             .await
             .unwrap();
-        repo.create("B", "https://b.com", "", &[]).await.unwrap();
-        repo.create("C", "https://c.com", "", &str_vec(&["编程", "rust"]))
+        repo.create(USER, "B", "https://b.com", "", &[])
+            .await
+            .unwrap();
+        repo.create(USER, "C", "https://c.com", "", &str_vec(&["编程", "rust"]))
             .await
             .unwrap();
 
-        let (items, total) = repo.find_all_paginated(10, 0, Some("编程")).await.unwrap();
+        let (items, total) = repo
+            .find_all_paginated(USER, 10, 0, Some("编程"))
+            .await
+            .unwrap();
         assert_eq!(total, 2);
         assert!(items.iter().all(|b| b.tags.contains(&"编程".to_string())));
 
         let (items, total) = repo
-            .find_all_paginated(10, 0, Some("不存在的"))
+            .find_all_paginated(USER, 10, 0, Some("不存在的"))
             .await
             .unwrap();
         assert_eq!(total, 0);
@@ -545,11 +686,11 @@ mod tests {
     async fn find_all_paginated_respects_limit_offset() {
         let repo = setup_db().await;
         for i in 0..10 {
-            repo.create(&format!("bm{i}"), "https://x.com", "", &[])
+            repo.create(USER, &format!("bm{i}"), "https://x.com", "", &[])
                 .await
                 .unwrap();
         }
-        let (items, total) = repo.find_all_paginated(3, 2, None).await.unwrap();
+        let (items, total) = repo.find_all_paginated(USER, 3, 2, None).await.unwrap();
         assert_eq!(total, 10);
         assert_eq!(items.len(), 3);
         assert_eq!(items[0].title, "bm7");
@@ -559,15 +700,23 @@ mod tests {
     async fn update_partial_fields() {
         let repo = setup_db().await;
         let bm = repo
-            .create("旧标题", "https://old.com", "旧描述", &str_vec(&["a"]))
+            .create(
+                USER,
+                "旧标题",
+                "https://old.com",
+                "旧描述",
+                &str_vec(&["a"]),
+            )
             .await
             .unwrap();
 
         // 只更新标题
         let updated = repo
-            .update(bm.id, Some("新标题"), None, None)
+            .update(USER, bm.id, Some("新标题"), None, None, None)
             .await
-            .unwrap();
+            .unwrap()
+            .unwrap()
+            .row;
         assert_eq!(updated.title, "新标题");
         assert_eq!(updated.url, "https://old.com");
         assert_eq!(updated.description, "旧描述");
@@ -577,37 +726,110 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn update_nonexistent_fails() {
+    async fn update_nonexistent_returns_none() {
         let repo = setup_db().await;
-        assert!(repo.update(999, Some("x"), None, None).await.is_err());
+        let res = repo.update(USER, 999, Some("x"), None, None, None).await;
+        assert!(res.unwrap().is_none());
     }
 
     #[tokio::test]
-    async fn delete_existing_cascades_tag_rels() {
+    async fn update_checks_version() {
         let repo = setup_db().await;
         let bm = repo
-            .create("x", "https://x.com", "", &str_vec(&["编程"]))
+            .create(USER, "t", "https://e.com", "", &[])
             .await
             .unwrap();
-        assert_eq!(repo.delete(bm.id).await.unwrap(), 1);
-        assert!(repo.find_by_id(bm.id).await.unwrap().is_none());
-        // 标签本身保留，关联清除
-        let tags = repo.search_tags(None).await.unwrap();
-        assert_eq!(tags.len(), 1);
+        let v = repo
+            .find_versioned(USER, bm.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .version;
+
+        let stale = repo
+            .update(USER, bm.id, Some("x"), None, None, Some(&[v + 1]))
+            .await
+            .unwrap();
+        assert!(stale.is_none());
+        assert_eq!(
+            repo.find_by_id(USER, bm.id).await.unwrap().unwrap().title,
+            "t"
+        );
+
+        let saved = repo
+            .update(USER, bm.id, Some("x"), None, None, Some(&[v]))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.version, v + 1);
+        assert_eq!(saved.row.title, "x");
+    }
+
+    #[tokio::test]
+    async fn delete_moves_to_trash_and_restore() {
+        let repo = setup_db().await;
+        let bm = repo
+            .create(USER, "x", "https://x.com", "", &str_vec(&["编程"]))
+            .await
+            .unwrap();
+        assert_eq!(repo.delete(USER, bm.id).await.unwrap(), 1);
+        assert!(repo.find_by_id(USER, bm.id).await.unwrap().is_none());
+        assert!(
+            repo.find_by_url(USER, "https://x.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            repo.find_all_paginated(USER, 10, 0, None).await.unwrap().1,
+            0
+        );
+        // 回收站中的书签不计入标签使用次数
+        let tags = repo.search_tags(USER, None).await.unwrap();
         assert_eq!(tags[0].count, 0);
+        // 重复删除无效
+        assert_eq!(repo.delete(USER, bm.id).await.unwrap(), 0);
+
+        assert_eq!(repo.restore(USER, bm.id).await.unwrap(), 1);
+        let got = repo.find_by_id(USER, bm.id).await.unwrap().unwrap();
+        assert_eq!(got.tags, vec!["编程"]);
+        assert_eq!(repo.restore(USER, bm.id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn purge_only_trashed_and_cascades_tag_rels() {
+        let repo = setup_db().await;
+        let bm = repo
+            .create(USER, "x", "https://x.com", "", &str_vec(&["编程"]))
+            .await
+            .unwrap();
+        // 未删除的书签不能直接彻底删除
+        assert_eq!(repo.purge(USER, bm.id).await.unwrap(), 0);
+        repo.delete(USER, bm.id).await.unwrap();
+        assert_eq!(repo.purge(USER, bm.id).await.unwrap(), 1);
+        assert_eq!(repo.restore(USER, bm.id).await.unwrap(), 0);
+        // 标签本身保留，关联清除
+        let rels: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM bookmark_tag_rel")
+            .fetch_one(&*repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(rels, 0);
+        assert_eq!(repo.search_tags(USER, None).await.unwrap().len(), 1);
     }
 
     #[tokio::test]
     async fn delete_nonexistent_returns_zero() {
         let repo = setup_db().await;
-        assert_eq!(repo.delete(999).await.unwrap(), 0);
+        assert_eq!(repo.delete(USER, 999).await.unwrap(), 0);
     }
 
     #[tokio::test]
     async fn search_empty_query_falls_back() {
         let repo = setup_db().await;
-        repo.create("A", "https://a.com", "", &[]).await.unwrap();
-        let (items, total) = repo.search_paginated("", None, 10, 0).await.unwrap();
+        repo.create(USER, "A", "https://a.com", "", &[])
+            .await
+            .unwrap();
+        let (items, total) = repo.search_paginated(USER, "", None, 10, 0).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items.len(), 1);
     }
@@ -615,23 +837,38 @@ mod tests {
     #[tokio::test]
     async fn search_matches_title_url_description() {
         let repo = setup_db().await;
-        repo.create("Rust 教程", "https://rust.example.com", "入门指南", &[])
-            .await
-            .unwrap();
-        repo.create("Go 官网", "https://go.dev", "", &[])
+        repo.create(
+            USER,
+            "Rust 教程",
+            "https://rust.example.com",
+            "入门指南",
+            &[],
+        )
+        .await
+        .unwrap();
+        repo.create(USER, "Go 官网", "https://go.dev", "", &[])
             .await
             .unwrap();
-        repo.create("其它", "https://other.example.com", "提到 rust 语言", &[])
+        repo.create(
+            USER,
+            "其它",
+            "https://other.example.com",
+            "提到 rust 语言",
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let (items, total) = repo
+            .search_paginated(USER, "rust", None, 10, 0)
             .await
             .unwrap();
-
-        let (items, total) = repo.search_paginated("rust", None, 10, 0).await.unwrap();
         assert_eq!(total, 2);
         assert!(items[0].title.contains("Rust") || items[0].description.contains("rust"));
 
         // 多关键词命中得分排序
         let (items, total) = repo
-            .search_paginated("rust 教程", None, 10, 0)
+            .search_paginated(USER, "rust 教程", None, 10, 0)
             .await
             .unwrap();
         assert_eq!(total, 2);
@@ -642,15 +879,15 @@ mod tests {
     #[tokio::test]
     async fn search_combined_with_tag_filter() {
         let repo = setup_db().await;
-        repo.create("Rust 教程", "https://a.com", "", &str_vec(&["编程"]))
+        repo.create(USER, "Rust 教程", "https://a.com", "", &str_vec(&["编程"]))
             .await
             .unwrap();
-        repo.create("Rust 新闻", "https://b.com", "", &[])
+        repo.create(USER, "Rust 新闻", "https://b.com", "", &[])
             .await
             .unwrap();
 
         let (items, total) = repo
-            .search_paginated("rust", Some("编程"), 10, 0)
+            .search_paginated(USER, "rust", Some("编程"), 10, 0)
             .await
             .unwrap();
         assert_eq!(total, 1);
@@ -660,8 +897,13 @@ mod tests {
     #[tokio::test]
     async fn search_no_match() {
         let repo = setup_db().await;
-        repo.create("x", "https://x.com", "", &[]).await.unwrap();
-        let (items, total) = repo.search_paginated("不存在", None, 10, 0).await.unwrap();
+        repo.create(USER, "x", "https://x.com", "", &[])
+            .await
+            .unwrap();
+        let (items, total) = repo
+            .search_paginated(USER, "不存在", None, 10, 0)
+            .await
+            .unwrap();
         assert_eq!(total, 0);
         assert!(items.is_empty());
     }
@@ -671,8 +913,8 @@ mod tests {
     #[tokio::test]
     async fn create_tag_idempotent() {
         let repo = setup_db().await;
-        let t1 = repo.create_tag("编程").await.unwrap();
-        let t2 = repo.create_tag("编程").await.unwrap();
+        let t1 = repo.create_tag(USER, "编程").await.unwrap();
+        let t2 = repo.create_tag(USER, "编程").await.unwrap();
         assert_eq!(t1.id, t2.id);
         assert_eq!(t1.name, "编程");
     }
@@ -680,21 +922,21 @@ mod tests {
     #[tokio::test]
     async fn search_tags_with_counts() {
         let repo = setup_db().await;
-        repo.create("A", "https://a.com", "", &str_vec(&["编程", "rust"]))
+        repo.create(USER, "A", "https://a.com", "", &str_vec(&["编程", "rust"]))
             .await
             .unwrap();
-        repo.create("B", "https://b.com", "", &str_vec(&["编程"]))
+        repo.create(USER, "B", "https://b.com", "", &str_vec(&["编程"]))
             .await
             .unwrap();
 
-        let tags = repo.search_tags(None).await.unwrap();
+        let tags = repo.search_tags(USER, None).await.unwrap();
         // 按 count DESC：编程(2) 在前
         assert_eq!(tags[0].name, "编程");
         assert_eq!(tags[0].count, 2);
         assert_eq!(tags[1].name, "rust");
         assert_eq!(tags[1].count, 1);
 
-        let matched = repo.search_tags(Some("rus")).await.unwrap();
+        let matched = repo.search_tags(USER, Some("rus")).await.unwrap();
         assert_eq!(matched.len(), 1);
         assert_eq!(matched[0].name, "rust");
     }
@@ -702,31 +944,39 @@ mod tests {
     #[tokio::test]
     async fn get_and_set_bookmark_tags() {
         let repo = setup_db().await;
-        let bm = repo.create("A", "https://a.com", "", &[]).await.unwrap();
+        let bm = repo
+            .create(USER, "A", "https://a.com", "", &[])
+            .await
+            .unwrap();
 
         let tags = repo
-            .set_bookmark_tags(bm.id, &str_vec(&["编程", "rust"]))
+            .set_bookmark_tags(USER, bm.id, &str_vec(&["编程", "rust"]))
             .await
             .unwrap();
         assert_eq!(tags.len(), 2);
 
-        let got = repo.get_bookmark_tags(bm.id).await.unwrap();
+        let got = repo.get_bookmark_tags(USER, bm.id).await.unwrap();
         assert_eq!(got.len(), 2);
 
         // 整体替换：清空并设置新标签
         let tags = repo
-            .set_bookmark_tags(bm.id, &str_vec(&["rust"]))
+            .set_bookmark_tags(USER, bm.id, &str_vec(&["rust"]))
             .await
             .unwrap();
         assert_eq!(tags.len(), 1);
         assert_eq!(tags[0].name, "rust");
         // 空的也清空
-        let tags = repo.set_bookmark_tags(bm.id, &[]).await.unwrap();
+        let tags = repo.set_bookmark_tags(USER, bm.id, &[]).await.unwrap();
         assert!(tags.is_empty());
-        assert!(repo.get_bookmark_tags(bm.id).await.unwrap().is_empty());
+        assert!(
+            repo.get_bookmark_tags(USER, bm.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
         // 标签名去重
         let tags = repo
-            .set_bookmark_tags(bm.id, &str_vec(&["a", "a"]))
+            .set_bookmark_tags(USER, bm.id, &str_vec(&["a", "a"]))
             .await
             .unwrap();
         assert_eq!(tags.len(), 1);
@@ -735,9 +985,12 @@ mod tests {
     #[tokio::test]
     async fn set_tags_ignores_blank_names() {
         let repo = setup_db().await;
-        let bm = repo.create("A", "https://a.com", "", &[]).await.unwrap();
+        let bm = repo
+            .create(USER, "A", "https://a.com", "", &[])
+            .await
+            .unwrap();
         let tags = repo
-            .set_bookmark_tags(bm.id, &str_vec(&["", "  ", "有效"]))
+            .set_bookmark_tags(USER, bm.id, &str_vec(&["", "  ", "有效"]))
             .await
             .unwrap();
         assert_eq!(tags.len(), 1);
@@ -748,16 +1001,20 @@ mod tests {
     async fn delete_tag_cascades_rels() {
         let repo = setup_db().await;
         let bm = repo
-            .create("A", "https://a.com", "", &str_vec(&["编程", "rust"]))
+            .create(USER, "A", "https://a.com", "", &str_vec(&["编程", "rust"]))
             .await
             .unwrap();
-        let tag = repo.search_tags(Some("编程")).await.unwrap().remove(0);
+        let tag = repo
+            .search_tags(USER, Some("编程"))
+            .await
+            .unwrap()
+            .remove(0);
 
-        assert_eq!(repo.delete_tag(tag.id).await.unwrap(), 1);
-        let got = repo.get_bookmark_tags(bm.id).await.unwrap();
+        assert_eq!(repo.delete_tag(USER, tag.id).await.unwrap(), 1);
+        let got = repo.get_bookmark_tags(USER, bm.id).await.unwrap();
         assert_eq!(got.len(), 1);
         assert_eq!(got[0].name, "rust");
         // 删除不存在的标签返回 0
-        assert_eq!(repo.delete_tag(999).await.unwrap(), 0);
+        assert_eq!(repo.delete_tag(USER, 999).await.unwrap(), 0);
     }
 }
@@ -1,79 +1,170 @@
 use std::sync::Arc;
+use utoipa::ToSchema;
 
+use super::handler::BookmarkResponse;
 use super::model::{Bookmark, BookmarkTag, BookmarkTagWithCount};
 use super::repository::BookmarkRepo;
 use crate::error::ServiceError;
+use crate::etag::{self, IfMatch, Versioned};
+use crate::modules::activity::{AuditLog, Entity};
+use crate::modules::events::ChangeFeed;
+use crate::modules::webhook::{WebhookEvent, Webhooks};
+use crate::pagination::{Cursor, Window};
 
 #[derive(Clone)]
 pub struct BookmarkService {
     repo: BookmarkRepo,
+    audit: AuditLog,
+    hooks: Webhooks,
 }
 
 impl BookmarkService {
-    pub fn new(db: Arc<sqlx::SqlitePool>) -> Self {
+    pub fn new(db: Arc<sqlx::SqlitePool>, hooks: Webhooks, changes: ChangeFeed) -> Self {
         Self {
-            repo: BookmarkRepo::new(db),
+            repo: BookmarkRepo::new(db.clone()),
+            audit: AuditLog::new(db, changes),
+            hooks,
         }
     }
 
     pub async fn list(
         &self,
-        limit: i64,
-        offset: i64,
+        user_id: i32,
+        window: &Window,
         tag: Option<&str>,
-    ) -> Result<(Vec<Bookmark>, i64), ServiceError> {
+    ) -> Result<(Vec<Bookmark>, i64, Option<Cursor>), ServiceError> {
+        self.repo
+            .find_page(user_id, window, tag)
+            .await
+            .map_err(ServiceError::Db)
+    }
+
+    pub async fn by_id(&self, user_id: i32, id: i32) -> Result<Option<Bookmark>, ServiceError> {
         self.repo
-            .find_all_paginated(limit, offset, tag)
+            .find_by_id(user_id, id)
             .await
             .map_err(ServiceError::Db)
     }
 
-    pub async fn by_id(&self, id: i32) -> Result<Option<Bookmark>, ServiceError> {
-        self.repo.find_by_id(id).await.map_err(ServiceError::Db)
+    /// 连同行版本号获取书签，供 ETag 使用
+    pub async fn versioned(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<Versioned<Bookmark>>, ServiceError> {
+        self.repo
+            .find_versioned(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 
     pub async fn create(
         &self,
+        user_id: i32,
         title: &str,
         url: &str,
         description: &str,
         tags: &[String],
     ) -> Result<Bookmark, ServiceError> {
-        self.repo
-            .create(title, url, description, tags)
+        let bookmark = self
+            .repo
+            .create(user_id, title, url, description, tags)
             .await
-            .map_err(ServiceError::Db)
+            .map_err(ServiceError::Db)?;
+        self.audit
+            .created(user_id, Entity::Bookmark, bookmark.id, &bookmark)
+            .await;
+        self.hooks
+            .emit(user_id, WebhookEvent::BookmarkCreated, &bookmark)
+            .await;
+        Ok(bookmark)
     }
 
-    pub async fn update(
+    /// 按 `If-Match` 条件更新：版本校验与写入在同一条 UPDATE 中完成，
+    /// 版本不符时返回带最新表示的 [`ServiceError::PreconditionFailed`]
+    pub async fn update_if(
         &self,
+        user_id: i32,
         id: i32,
         title: Option<&str>,
         url: Option<&str>,
         description: Option<&str>,
-    ) -> Result<Bookmark, ServiceError> {
-        self.repo
-            .update(id, title, url, description)
+        if_match: &IfMatch,
+    ) -> Result<Versioned<Bookmark>, ServiceError> {
+        let before = self.by_id(user_id, id).await?;
+        let versions = if_match.versions();
+        let Some(bookmark) = self
+            .repo
+            .update(user_id, id, title, url, description, versions.as_deref())
             .await
-            .map_err(|e| match e {
-                sqlx::Error::RowNotFound => ServiceError::NotFound("书签不存在".into()),
-                other => ServiceError::Db(other),
-            })
+            .map_err(ServiceError::Db)?
+        else {
+            return match self.versioned(user_id, id).await? {
+                Some(current) => Err(ServiceError::PreconditionFailed {
+                    etag: etag::version(current.version),
+                    current: serde_json::to_value(BookmarkResponse::from(current.row))
+                        .unwrap_or_default(),
+                }),
+                None => Err(ServiceError::NotFound("书签不存在".into())),
+            };
+        };
+        if let Some(before) = before {
+            self.audit
+                .updated(user_id, Entity::Bookmark, id, &before, &bookmark.row)
+                .await;
+        }
+        Ok(bookmark)
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        let before = self.by_id(user_id, id).await?;
+        let rows = self
+            .repo
+            .delete(user_id, id)
+            .await
+            .map_err(ServiceError::Db)?;
+        if let Some(before) = before.filter(|_| rows > 0) {
+            self.audit
+                .deleted(user_id, Entity::Bookmark, id, &before)
+                .await;
+        }
+        Ok(rows)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete(id).await.map_err(ServiceError::Db)
+    pub async fn restore(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self
+            .repo
+            .restore(user_id, id)
+            .await
+            .map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.restored(user_id, Entity::Bookmark, id).await;
+        }
+        Ok(rows)
+    }
+
+    pub async fn purge(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self
+            .repo
+            .purge(user_id, id)
+            .await
+            .map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.purged(user_id, Entity::Bookmark, id).await;
+        }
+        Ok(rows)
     }
 
     pub async fn search(
         &self,
+        user_id: i32,
         query: &str,
         tag: Option<&str>,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<Bookmark>, i64), ServiceError> {
         self.repo
-            .search_paginated(query, tag, limit, offset)
+            .search_paginated(user_id, query, tag, limit, offset)
             .await
             .map_err(ServiceError::Db)
     }
@@ -82,36 +173,51 @@ impl BookmarkService {
 
     pub async fn search_tags(
         &self,
+        user_id: i32,
         q: Option<&str>,
     ) -> Result<Vec<BookmarkTagWithCount>, ServiceError> {
-        self.repo.search_tags(q).await.map_err(ServiceError::Db)
+        self.repo
+            .search_tags(user_id, q)
+            .await
+            .map_err(ServiceError::Db)
     }
 
-    pub async fn create_tag(&self, name: &str) -> Result<BookmarkTag, ServiceError> {
-        self.repo.create_tag(name).await.map_err(ServiceError::Db)
+    pub async fn create_tag(&self, user_id: i32, name: &str) -> Result<BookmarkTag, ServiceError> {
+        self.repo
+            .create_tag(user_id, name)
+            .await
+            .map_err(ServiceError::Db)
     }
 
-    pub async fn delete_tag(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete_tag(id).await.map_err(ServiceError::Db)
+    pub async fn delete_tag(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        self.repo
+            .delete_tag(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 
     pub async fn get_bookmark_tags(
         &self,
+        user_id: i32,
         bookmark_id: i32,
     ) -> Result<Vec<BookmarkTag>, ServiceError> {
+        if self.repo.find_by_id(user_id, bookmark_id).await?.is_none() {
+            return Err(ServiceError::NotFound("书签不存在".into()));
+        }
         self.repo
-            .get_bookmark_tags(bookmark_id)
+            .get_bookmark_tags(user_id, bookmark_id)
             .await
             .map_err(ServiceError::Db)
     }
 
     pub async fn set_bookmark_tags(
         &self,
+        user_id: i32,
         bookmark_id: i32,
         names: &[String],
     ) -> Result<Vec<BookmarkTag>, ServiceError> {
         self.repo
-            .set_bookmark_tags(bookmark_id, names)
+            .set_bookmark_tags(user_id, bookmark_id, names)
             .await
             .map_err(|e| match e {
                 sqlx::Error::RowNotFound => ServiceError::NotFound("书签不存在".into()),
@@ -125,7 +231,11 @@ impl BookmarkService {
     ///
     /// 按 URL 去重：已存在的书签合并文件夹标签；不存在的创建。
     /// 返回统计结果。
-    pub async fn import_netscape_html(&self, html: &str) -> Result<ImportResult, ServiceError> {
+    pub async fn import_netscape_html(
+        &self,
+        user_id: i32,
+        html: &str,
+    ) -> Result<ImportResult, ServiceError> {
         let parsed = super::import_html::parse_netscape_html(html);
 
         let mut created = 0u64;
@@ -139,7 +249,7 @@ impl BookmarkService {
                 .filter(|s| !s.is_empty())
                 .collect();
 
-            if let Some(existing) = self.repo.find_by_url(&item.url).await? {
+            if let Some(existing) = self.repo.find_by_url(user_id, &item.url).await? {
                 // 合并：旧标签 ∪ 新标签
                 let mut union = existing.tags.clone();
                 for t in &tags {
@@ -147,7 +257,9 @@ impl BookmarkService {
                         union.push(t.clone());
                     }
                 }
-                self.repo.set_bookmark_tags(existing.id, &union).await?;
+                self.repo
+                    .set_bookmark_tags(user_id, existing.id, &union)
+                    .await?;
                 merged += 1;
             } else {
                 let title = if item.title.trim().is_empty() {
@@ -155,7 +267,9 @@ impl BookmarkService {
                 } else {
                     item.title
                 };
-                self.repo.create(&title, &item.url, "", &tags).await?;
+                self.repo
+                    .create(user_id, &title, &item.url, "", &tags)
+                    .await?;
                 created += 1;
             }
         }
@@ -169,7 +283,7 @@ impl BookmarkService {
 }
 
 /// Firefox 书签导入统计
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, ToSchema)]
 pub struct ImportResult {
     /// 处理总数（含无效条目占位，便于前端提示）
     pub total: u64,
@@ -183,8 +297,11 @@ pub struct ImportResult {
 mod tests {
     #![allow(clippy::unwrap_used)]
     use super::*;
+    use crate::modules::job::JobService;
     use sqlx::SqlitePool;
 
+    const USER: i32 = 1;
+
     async fn setup() -> BookmarkService {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
         sqlx::query(
@@ -193,8 +310,11 @@ mod tests {
                 title TEXT NOT NULL,
                 url TEXT NOT NULL,
                 description TEXT NOT NULL DEFAULT '',
+                user_id INTEGER,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                deleted_at TEXT
             )",
         )
         .execute(&*pool)
@@ -203,8 +323,10 @@ mod tests {
         sqlx::query(
             "CREATE TABLE bookmark_tag (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL UNIQUE,
-                created_at TEXT NOT NULL DEFAULT ''
+                name TEXT NOT NULL,
+                user_id INTEGER,
+                created_at TEXT NOT NULL DEFAULT '',
+                UNIQUE(name, user_id)
             )",
         )
         .execute(&*pool)
@@ -222,24 +344,36 @@ mod tests {
         .execute(&*pool)
         .await
         .unwrap();
-        BookmarkService::new(pool)
+        let hooks = Webhooks::new(pool.clone(), JobService::new(pool.clone()));
+        BookmarkService::new(pool, hooks, ChangeFeed::default())
     }
 
     fn str_vec(v: &[&str]) -> Vec<String> {
         v.iter().map(|s| s.to_string()).collect()
     }
 
+    const FIRST_PAGE: Window = Window::Offset {
+        limit: 10,
+        offset: 0,
+    };
+
     #[tokio::test]
     async fn create_with_tags_and_list() {
         let svc = setup().await;
         let bm = svc
-            .create("标题", "https://example.com", "备注", &str_vec(&["编程"]))
+            .create(
+                USER,
+                "标题",
+                "https://example.com",
+                "备注",
+                &str_vec(&["编程"]),
+            )
             .await
             .unwrap();
         assert!(bm.id > 0);
         assert_eq!(bm.tags, str_vec(&["编程"]));
 
-        let (items, total) = svc.list(10, 0, None).await.unwrap();
+        let (items, total, _) = svc.list(USER, &FIRST_PAGE, None).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].url, "https://example.com");
         assert_eq!(items[0].tags, str_vec(&["编程"]));
@@ -248,56 +382,127 @@ mod tests {
     #[tokio::test]
     async fn list_filtered_by_tag() {
         let svc = setup().await;
-        svc.create("A", "https://a.com", "", &str_vec(&["编程"]))
+        svc.create(USER, "A", "https://a.com", "", &str_vec(&["编程"]))
+            .await
+            .unwrap();
+        svc.create(USER, "B", "https://b.com", "", &[])
             .await
             .unwrap();
-        svc.create("B", "https://b.com", "", &[]).await.unwrap();
 
-        let (items, total) = svc.list(10, 0, Some("编程")).await.unwrap();
+        let (items, total, _) = svc.list(USER, &FIRST_PAGE, Some("编程")).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].title, "A");
     }
 
+    #[tokio::test]
+    async fn cursor_pages_are_stable_under_inserts() {
+        let svc = setup().await;
+        for i in 0..3 {
+            svc.create(USER, &format!("t{i}"), &format!("https://{i}.com"), "", &[])
+                .await
+                .unwrap();
+        }
+
+        let first = Window::Keyset {
+            limit: 2,
+            after: None,
+        };
+        let (page1, _, next) = svc.list(USER, &first, None).await.unwrap();
+        let titles: Vec<_> = page1.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, ["t2", "t1"]);
+
+        // 翻页之间新增书签，不应挤出或重复已翻过的行
+        svc.create(USER, "new", "https://new.com", "", &[])
+            .await
+            .unwrap();
+        let second = Window::Keyset {
+            limit: 2,
+            after: next,
+        };
+        let (page2, total, next) = svc.list(USER, &second, None).await.unwrap();
+        let titles: Vec<_> = page2.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, ["t0"]);
+        assert_eq!(total, 4);
+        assert!(next.is_none());
+    }
+
     #[tokio::test]
     async fn by_id() {
         let svc = setup().await;
-        let bm = svc.create("t", "https://e.com", "", &[]).await.unwrap();
-        assert!(svc.by_id(bm.id).await.unwrap().is_some());
-        assert!(svc.by_id(999).await.unwrap().is_none());
+        let bm = svc
+            .create(USER, "t", "https://e.com", "", &[])
+            .await
+            .unwrap();
+        assert!(svc.by_id(USER, bm.id).await.unwrap().is_some());
+        assert!(svc.by_id(USER, 999).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn update_and_delete() {
         let svc = setup().await;
         let bm = svc
-            .create("t", "https://e.com", "", &str_vec(&["a"]))
+            .create(USER, "t", "https://e.com", "", &str_vec(&["a"]))
             .await
             .unwrap();
-        let updated = svc.update(bm.id, Some("新标题"), None, None).await.unwrap();
+        let updated = svc
+            .update_if(USER, bm.id, Some("新标题"), None, None, &IfMatch::Absent)
+            .await
+            .unwrap()
+            .row;
         assert_eq!(updated.title, "新标题");
         assert_eq!(updated.tags, str_vec(&["a"]));
 
-        assert_eq!(svc.delete(bm.id).await.unwrap(), 1);
-        assert!(svc.by_id(bm.id).await.unwrap().is_none());
+        assert_eq!(svc.delete(USER, bm.id).await.unwrap(), 1);
+        assert!(svc.by_id(USER, bm.id).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn update_not_found_returns_notfound() {
         let svc = setup().await;
-        let err = svc.update(999, Some("x"), None, None).await.unwrap_err();
+        let err = svc
+            .update_if(USER, 999, Some("x"), None, None, &IfMatch::Absent)
+            .await
+            .unwrap_err();
         assert!(matches!(err, ServiceError::NotFound(_)));
     }
 
+    #[tokio::test]
+    async fn concurrent_updates_with_same_etag_one_fails() {
+        let svc = setup().await;
+        let bm = svc
+            .create(USER, "t", "https://e.com", "", &[])
+            .await
+            .unwrap();
+        let tag = svc.versioned(USER, bm.id).await.unwrap().unwrap().version;
+        let if_match = IfMatch::Tags(vec![etag::version(tag)]);
+
+        let (a, b) = tokio::join!(
+            svc.update_if(USER, bm.id, Some("a"), None, None, &if_match),
+            svc.update_if(USER, bm.id, Some("b"), None, None, &if_match),
+        );
+        let (ok, stale): (Vec<_>, Vec<_>) = [a, b].into_iter().partition(Result::is_ok);
+        assert_eq!(ok.len(), 1);
+        let winner = ok.into_iter().next().unwrap().unwrap();
+        assert_eq!(winner.version, tag + 1);
+        match stale.into_iter().next().unwrap().unwrap_err() {
+            ServiceError::PreconditionFailed { etag, current } => {
+                assert_eq!(etag, etag::version(winner.version));
+                assert_eq!(current["title"], winner.row.title.as_str());
+            }
+            other => panic!("expected 412, got {other:?}"),
+        }
+    }
+
     #[tokio::test]
     async fn search_by_keyword() {
         let svc = setup().await;
-        svc.create("Rust 官网", "https://rust-lang.org", "", &[])
+        svc.create(USER, "Rust 官网", "https://rust-lang.org", "", &[])
             .await
             .unwrap();
-        svc.create("Go 官网", "https://go.dev", "", &[])
+        svc.create(USER, "Go 官网", "https://go.dev", "", &[])
             .await
             .unwrap();
-        let (items, total) = svc.search("rust", None, 10, 0).await.unwrap();
+        let (items, total) = svc.search(USER, "rust", None, 10, 0).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].url, "https://rust-lang.org");
     }
@@ -306,6 +511,7 @@ mod tests {
     async fn search_with_tag_filter() {
         let svc = setup().await;
         svc.create(
+            USER,
             "Rust 官网",
             "https://rust-lang.org",
             "",
@@ -313,14 +519,17 @@ mod tests {
         )
         .await
         .unwrap();
-        svc.create("Go 官网", "https://go.dev", "", &[])
+        svc.create(USER, "Go 官网", "https://go.dev", "", &[])
             .await
             .unwrap();
-        let (items, total) = svc.search("rust", Some("编程"), 10, 0).await.unwrap();
+        let (items, total) = svc.search(USER, "rust", Some("编程"), 10, 0).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].url, "https://rust-lang.org");
 
-        let (items, total) = svc.search("rust", Some("不存在"), 10, 0).await.unwrap();
+        let (items, total) = svc
+            .search(USER, "rust", Some("不存在"), 10, 0)
+            .await
+            .unwrap();
         assert_eq!(total, 0);
         assert!(items.is_empty());
     }
@@ -328,32 +537,35 @@ mod tests {
     #[tokio::test]
     async fn tags_roundtrip() {
         let svc = setup().await;
-        let bm = svc.create("t", "https://e.com", "", &[]).await.unwrap();
+        let bm = svc
+            .create(USER, "t", "https://e.com", "", &[])
+            .await
+            .unwrap();
 
         let tags = svc
-            .set_bookmark_tags(bm.id, &str_vec(&["编程", "rust"]))
+            .set_bookmark_tags(USER, bm.id, &str_vec(&["编程", "rust"]))
             .await
             .unwrap();
         assert_eq!(tags.len(), 2);
 
-        let got = svc.get_bookmark_tags(bm.id).await.unwrap();
+        let got = svc.get_bookmark_tags(USER, bm.id).await.unwrap();
         assert_eq!(got.len(), 2);
 
-        let all = svc.search_tags(None).await.unwrap();
+        let all = svc.search_tags(USER, None).await.unwrap();
         assert_eq!(all.len(), 2);
 
-        let matched = svc.search_tags(Some("rus")).await.unwrap();
+        let matched = svc.search_tags(USER, Some("rus")).await.unwrap();
         assert_eq!(matched.len(), 1);
         assert_eq!(matched[0].name, "rust");
 
         // 幂等创建
-        let t = svc.create_tag("编程").await.unwrap();
+        let t = svc.create_tag(USER, "编程").await.unwrap();
         assert_eq!(t.name, "编程");
 
         // 删除标签
-        assert_eq!(svc.delete_tag(t.id).await.unwrap(), 1);
+        assert_eq!(svc.delete_tag(USER, t.id).await.unwrap(), 1);
         assert!(
-            svc.get_bookmark_tags(bm.id)
+            svc.get_bookmark_tags(USER, bm.id)
                 .await
                 .unwrap()
                 .iter()
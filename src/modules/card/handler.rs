@@ -1,24 +1,27 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
+use crate::auth::Claims;
 use crate::error;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCardRequest {
     pub content: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCardRequest {
     pub content: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CardResponse {
     pub id: i32,
     pub content: String,
@@ -37,18 +40,33 @@ impl From<super::model::Card> for CardResponse {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建卡片",
+    request_body = CreateCardRequest,
+    responses((status = 201, description = "已创建", body = CardResponse))
+)]
 pub async fn create_card_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateCardRequest>,
 ) -> impl IntoResponse {
     let result = state
         .card
-        .create(payload.content)
+        .create(claims.sub, payload.content)
         .await
         .map(CardResponse::from);
     error::created_or(result, "创建卡片")
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "卡片列表",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<CardResponse>))
+)]
 pub async fn get_cards_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
@@ -64,6 +82,12 @@ pub async fn get_cards_handler(
     error::ok_or(result, "获取卡片列表")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取卡片",
+    responses((status = 200, description = "成功", body = CardResponse))
+)]
 pub async fn get_card_handler(
     State(state): State<AppState>,
     Path(id): Path<i32>,
@@ -76,27 +100,43 @@ pub async fn get_card_handler(
     error::found_or(result, "获取卡片")
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "更新卡片",
+    request_body = UpdateCardRequest,
+    responses((status = 200, description = "成功", body = CardResponse))
+)]
 pub async fn update_card_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
     Json(payload): Json<UpdateCardRequest>,
 ) -> impl IntoResponse {
     let result = state
         .card
-        .update(id, payload.content)
+        .update(claims.sub, id, payload.content)
         .await
         .map(CardResponse::from);
     error::ok_or(result, "更新卡片")
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除卡片",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_card_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    error::deleted_or(state.card.delete(id).await, "删除卡片")
+    error::deleted_or(state.card.delete(claims.sub, id).await, "删除卡片")
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchCardsQuery {
     pub q: String,
     pub page: Option<i64>,
@@ -108,10 +148,18 @@ impl SearchCardsQuery {
         Pagination {
             page: self.page.unwrap_or(1),
             page_size: self.page_size.unwrap_or(20),
+            cursor: None,
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/search",
+    summary = "搜索卡片",
+    params(SearchCardsQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<CardResponse>))
+)]
 pub async fn search_cards_handler(
     Query(params): Query<SearchCardsQuery>,
     State(state): State<AppState>,
@@ -4,23 +4,21 @@ pub mod repository;
 pub mod service;
 
 pub use service::CardService;
-
-pub use handler::{
-    create_card_handler, delete_card_handler, get_card_handler, get_cards_handler,
-    search_cards_handler, update_card_handler,
-};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{Router, routing::get};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(get_cards_handler).post(create_card_handler))
-        .route(
-            "/{id}",
-            get(get_card_handler)
-                .patch(update_card_handler)
-                .delete(delete_card_handler),
-        )
-        .route("/search", get(search_cards_handler))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::get_cards_handler,
+            handler::create_card_handler
+        ))
+        .routes(routes!(
+            handler::get_card_handler,
+            handler::update_card_handler,
+            handler::delete_card_handler
+        ))
+        .routes(routes!(handler::search_cards_handler))
 }
@@ -22,12 +22,12 @@ impl CardRepository {
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<Card>, i64), sqlx::Error> {
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM card")
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM card WHERE deleted_at IS NULL")
             .fetch_one(&*self.db)
             .await?;
 
         let items = sqlx::query_as::<_, Card>(
-            "SELECT id, content, created_at, updated_at FROM card ORDER BY updated_at DESC LIMIT ? OFFSET ?",
+            "SELECT id, content, created_at, updated_at FROM card WHERE deleted_at IS NULL ORDER BY updated_at DESC LIMIT ? OFFSET ?",
         )
         .bind(limit)
         .bind(offset)
@@ -40,7 +40,7 @@ impl CardRepository {
     /// 根据ID获取卡片
     pub async fn find_by_id(&self, id: i32) -> Result<Option<Card>, sqlx::Error> {
         sqlx::query_as::<_, Card>(
-            "SELECT id, content, created_at, updated_at FROM card WHERE id = ?",
+            "SELECT id, content, created_at, updated_at FROM card WHERE id = ? AND deleted_at IS NULL",
         )
         .bind(id)
         .fetch_optional(&*self.db)
@@ -82,7 +82,7 @@ impl CardRepository {
         builder.push_bind(now);
         builder.push(" WHERE id = ");
         builder.push_bind(id);
-        builder.push(" RETURNING id, content, created_at, updated_at");
+        builder.push(" AND deleted_at IS NULL RETURNING id, content, created_at, updated_at");
 
         let result = builder.build().fetch_one(&*self.db).await?;
 
@@ -94,9 +94,34 @@ impl CardRepository {
         })
     }
 
-    /// 删除卡片
+    /// 删除卡片（移入回收站）
     pub async fn delete(&self, id: i32) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query("DELETE FROM card WHERE id = ?")
+        let result = sqlx::query(
+            "UPDATE card SET deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now') \
+             WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .execute(&*self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 从回收站恢复卡片
+    pub async fn restore(&self, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE card SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&*self.db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 彻底删除回收站中的卡片
+    pub async fn purge(&self, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM card WHERE id = ? AND deleted_at IS NOT NULL")
             .bind(id)
             .execute(&*self.db)
             .await?;
@@ -120,7 +145,8 @@ impl CardRepository {
         // 用 QueryBuilder 动态拼 OR 子句。注意：每个 push + push_bind 算一次
         // 分隔插入，所以这里不用 Separated（Separated 的 push_bind 会把 "content LIKE"
         // 和 "?" 当成两个独立项）。改为直接在 QueryBuilder 上 push，手动控制 OR。
-        let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM card WHERE ");
+        let mut count_builder =
+            QueryBuilder::new("SELECT COUNT(*) FROM card WHERE deleted_at IS NULL AND (");
         for (i, kw) in keywords.iter().enumerate() {
             if i > 0 {
                 count_builder.push(" OR ");
@@ -128,14 +154,16 @@ impl CardRepository {
             count_builder.push("content LIKE ");
             count_builder.push_bind(format!("%{}%", kw));
         }
+        count_builder.push(")");
         let total: i64 = count_builder
             .build_query_scalar()
             .fetch_one(&*self.db)
             .await?;
 
         // ── Fetch ──
-        let mut fetch_builder =
-            QueryBuilder::new("SELECT id, content, created_at, updated_at FROM card WHERE ");
+        let mut fetch_builder = QueryBuilder::new(
+            "SELECT id, content, created_at, updated_at FROM card WHERE deleted_at IS NULL AND (",
+        );
         for (i, kw) in keywords.iter().enumerate() {
             if i > 0 {
                 fetch_builder.push(" OR ");
@@ -143,6 +171,7 @@ impl CardRepository {
             fetch_builder.push("content LIKE ");
             fetch_builder.push_bind(format!("%{}%", kw));
         }
+        fetch_builder.push(")");
         fetch_builder.push(" ORDER BY (");
         // 评分：每个关键词命中的加 1
         for (i, kw) in keywords.iter().enumerate() {
@@ -185,7 +214,8 @@ mod tests {
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 content TEXT,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                deleted_at TEXT
             )",
         )
         .execute(&pool)
@@ -301,8 +331,35 @@ mod tests {
         let affected = repo.delete(card.id).await.unwrap();
         assert_eq!(affected, 1);
 
-        // 验证已删除
+        // 验证已移入回收站，不再出现在列表和搜索中
         assert!(repo.find_by_id(card.id).await.unwrap().is_none());
+        assert_eq!(repo.find_all_paginated(10, 0).await.unwrap().1, 0);
+        assert_eq!(
+            repo.search_by_content_paginated("待删除", 10, 0)
+                .await
+                .unwrap()
+                .1,
+            0
+        );
+        assert_eq!(repo.delete(card.id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn restore_and_purge_trashed_card() {
+        let repo = setup_db().await;
+        let card = repo.create("回收站".to_string()).await.unwrap();
+
+        // 未删除的卡片既不能恢复也不能彻底删除
+        assert_eq!(repo.restore(card.id).await.unwrap(), 0);
+        assert_eq!(repo.purge(card.id).await.unwrap(), 0);
+
+        repo.delete(card.id).await.unwrap();
+        assert_eq!(repo.restore(card.id).await.unwrap(), 1);
+        assert!(repo.find_by_id(card.id).await.unwrap().is_some());
+
+        repo.delete(card.id).await.unwrap();
+        assert_eq!(repo.purge(card.id).await.unwrap(), 1);
+        assert_eq!(repo.restore(card.id).await.unwrap(), 0);
     }
 
     #[tokio::test]
@@ -3,16 +3,20 @@ use std::sync::Arc;
 use super::model::Card;
 use super::repository::CardRepository;
 use crate::error::ServiceError;
+use crate::modules::activity::{AuditLog, Entity};
+use crate::modules::events::ChangeFeed;
 
 #[derive(Clone)]
 pub struct CardService {
     repo: CardRepository,
+    audit: AuditLog,
 }
 
 impl CardService {
-    pub fn new(db: Arc<sqlx::SqlitePool>) -> Self {
+    pub fn new(db: Arc<sqlx::SqlitePool>, changes: ChangeFeed) -> Self {
         Self {
-            repo: CardRepository::new(db),
+            repo: CardRepository::new(db.clone()),
+            audit: AuditLog::new(db, changes),
         }
     }
 
@@ -27,19 +31,56 @@ impl CardService {
         self.repo.find_by_id(id).await.map_err(ServiceError::Db)
     }
 
-    pub async fn create(&self, content: String) -> Result<Card, ServiceError> {
-        self.repo.create(content).await.map_err(ServiceError::Db)
+    pub async fn create(&self, actor: i32, content: String) -> Result<Card, ServiceError> {
+        let card = self.repo.create(content).await.map_err(ServiceError::Db)?;
+        self.audit
+            .created(actor, Entity::Card, card.id, &card)
+            .await;
+        Ok(card)
     }
 
-    pub async fn update(&self, id: i32, content: Option<String>) -> Result<Card, ServiceError> {
-        self.repo.update(id, content).await.map_err(|e| match e {
+    pub async fn update(
+        &self,
+        actor: i32,
+        id: i32,
+        content: Option<String>,
+    ) -> Result<Card, ServiceError> {
+        let before = self.by_id(id).await?;
+        let card = self.repo.update(id, content).await.map_err(|e| match e {
             sqlx::Error::RowNotFound => ServiceError::NotFound("卡片不存在".into()),
             other => ServiceError::Db(other),
-        })
+        })?;
+        if let Some(before) = before {
+            self.audit
+                .updated(actor, Entity::Card, id, &before, &card)
+                .await;
+        }
+        Ok(card)
+    }
+
+    pub async fn delete(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let before = self.by_id(id).await?;
+        let rows = self.repo.delete(id).await.map_err(ServiceError::Db)?;
+        if let Some(before) = before.filter(|_| rows > 0) {
+            self.audit.deleted(actor, Entity::Card, id, &before).await;
+        }
+        Ok(rows)
+    }
+
+    pub async fn restore(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self.repo.restore(id).await.map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.restored(actor, Entity::Card, id).await;
+        }
+        Ok(rows)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete(id).await.map_err(ServiceError::Db)
+    pub async fn purge(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self.repo.purge(id).await.map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.purged(actor, Entity::Card, id).await;
+        }
+        Ok(rows)
     }
 
     pub async fn search(
@@ -62,16 +103,19 @@ mod tests {
     use sqlx::SqlitePool;
 
     async fn setup() -> CardService {
+        setup_with_pool().await.0
+    }
+
+    async fn setup_with_pool() -> (CardService, Arc<SqlitePool>) {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE card (id INTEGER PRIMARY KEY AUTOINCREMENT, content TEXT, created_at TEXT NOT NULL, updated_at TEXT NOT NULL)")
-            .execute(&*pool).await.unwrap();
-        CardService::new(pool)
+        crate::db::migrate::run(&pool).await.unwrap();
+        (CardService::new(pool.clone(), ChangeFeed::default()), pool)
     }
 
     #[tokio::test]
     async fn create_and_list() {
         let svc = setup().await;
-        let card = svc.create("hello".into()).await.unwrap();
+        let card = svc.create(1, "hello".into()).await.unwrap();
         assert!(card.id > 0);
 
         let (items, total) = svc.list(10, 0).await.unwrap();
@@ -82,7 +126,7 @@ mod tests {
     #[tokio::test]
     async fn by_id() {
         let svc = setup().await;
-        let card = svc.create("test".into()).await.unwrap();
+        let card = svc.create(1, "test".into()).await.unwrap();
         assert!(svc.by_id(card.id).await.unwrap().is_some());
         assert!(svc.by_id(999).await.unwrap().is_none());
     }
@@ -90,24 +134,43 @@ mod tests {
     #[tokio::test]
     async fn update_content() {
         let svc = setup().await;
-        let card = svc.create("old".into()).await.unwrap();
-        let updated = svc.update(card.id, Some("new".into())).await.unwrap();
+        let card = svc.create(1, "old".into()).await.unwrap();
+        let updated = svc.update(1, card.id, Some("new".into())).await.unwrap();
         assert_eq!(updated.content, "new");
     }
 
     #[tokio::test]
     async fn delete() {
         let svc = setup().await;
-        let card = svc.create("x".into()).await.unwrap();
-        assert_eq!(svc.delete(card.id).await.unwrap(), 1);
+        let card = svc.create(1, "x".into()).await.unwrap();
+        assert_eq!(svc.delete(1, card.id).await.unwrap(), 1);
         assert!(svc.by_id(card.id).await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn mutations_are_audited() {
+        let (svc, pool) = setup_with_pool().await;
+        let card = svc.create(7, "old".into()).await.unwrap();
+        svc.update(7, card.id, Some("new".into())).await.unwrap();
+        svc.delete(7, card.id).await.unwrap();
+
+        let events: Vec<(String, String)> = sqlx::query_as(
+            "SELECT action, diff FROM audit_event WHERE actor_id = 7 AND entity_type = 'card' ORDER BY id",
+        )
+        .fetch_all(&*pool)
+        .await
+        .unwrap();
+        let actions: Vec<&str> = events.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(actions, ["create", "update", "delete"]);
+        let deleted: serde_json::Value = serde_json::from_str(&events[2].1).unwrap();
+        assert_eq!(deleted["content"], serde_json::json!(["new", null]));
+    }
+
     #[tokio::test]
     async fn search_by_keyword() {
         let svc = setup().await;
-        svc.create("rust language".into()).await.unwrap();
-        svc.create("go language".into()).await.unwrap();
+        svc.create(1, "rust language".into()).await.unwrap();
+        svc.create(1, "go language".into()).await.unwrap();
         let (items, total) = svc.search("rust", 10, 0).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].content, "rust language");
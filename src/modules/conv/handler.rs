@@ -1,26 +1,36 @@
 use std::collections::HashMap;
 
 use axum::{
-    Json, Router,
+    Json,
     extract::{Path, Query, State},
     response::IntoResponse,
-    routing::get,
 };
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::error;
 use crate::state::AppState;
 
-use super::model::{ArticleItem, ConvDetail, QaPair, SearchParams};
+use super::model::{
+    ArticleItem, ConvArticle, ConvDetail, ConvQa, QaPair, SearchParams, SearchResponse,
+};
 use super::service::search_conv;
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/search", get(search_handler))
-        .route("/{id}", get(conv_detail_handler))
-        .route("/qa/{id}", get(conv_qa_handler))
-        .route("/concept/{id}", get(conv_concept_handler))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(search_handler))
+        .routes(routes!(conv_detail_handler))
+        .routes(routes!(conv_qa_handler))
+        .routes(routes!(conv_concept_handler))
 }
 
+#[utoipa::path(
+    get,
+    path = "/search",
+    summary = "搜索对话",
+    params(SearchParams),
+    responses((status = 200, description = "成功", body = SearchResponse))
+)]
 pub async fn search_handler(
     State(state): State<AppState>,
     Query(params): Query<SearchParams>,
@@ -40,6 +50,13 @@ pub async fn search_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "对话详情",
+    params(("mode" = Option<String>, Query)),
+    responses((status = 200, description = "成功", body = ConvDetail))
+)]
 pub async fn conv_detail_handler(
     State(state): State<AppState>,
     Path(id): Path<i64>,
@@ -103,6 +120,12 @@ pub async fn conv_detail_handler(
     .into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/qa/{id}",
+    summary = "对话问答",
+    responses((status = 200, description = "成功", body = ConvQa))
+)]
 pub async fn conv_qa_handler(
     State(state): State<AppState>,
     Path(id): Path<i64>,
@@ -130,18 +153,30 @@ pub async fn conv_qa_handler(
     .await
     .unwrap_or_default();
 
-    Json(serde_json::json!({
-        "conv_id": id,
-        "title": title,
-        "conv_type": conv_type,
-        "created_at": created_at,
-        "qa_pairs": qa_pairs.into_iter().map(|(id, q, a)| serde_json::json!({
-            "qa_id": id, "question": q, "answer": a
-        })).collect::<Vec<_>>(),
-    }))
+    Json(ConvQa {
+        conv_id: id,
+        title,
+        conv_type,
+        created_at,
+        qa_pairs: qa_pairs
+            .into_iter()
+            .map(|(id, q, a)| QaPair {
+                qa_id: id,
+                question: q,
+                answer: a,
+            })
+            .collect(),
+    })
     .into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/concept/{id}",
+    summary = "对话中的概念文章",
+    params(("article" = String, Query)),
+    responses((status = 200, description = "成功", body = ConvArticle))
+)]
 pub async fn conv_concept_handler(
     State(state): State<AppState>,
     Path(id): Path<i64>,
@@ -164,12 +199,12 @@ pub async fn conv_concept_handler(
     .unwrap_or(None);
 
     match article {
-        Some((atype, title, content)) => Json(serde_json::json!({
-            "conv_id": id,
-            "article_type": atype,
-            "title": title,
-            "content": content,
-        }))
+        Some((article_type, title, content)) => Json(ConvArticle {
+            conv_id: id,
+            article_type,
+            title,
+            content,
+        })
         .into_response(),
         None => error::not_found("文章不存在"),
     }
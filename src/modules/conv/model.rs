@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchParams {
     pub q: Option<String>,
     pub limit: Option<i64>,
@@ -9,7 +11,7 @@ pub struct SearchParams {
     pub search_type: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ConvHit {
     pub conv_id: i64,
     pub title: String,
@@ -23,20 +25,20 @@ pub struct ConvHit {
     pub article_title: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SearchResponse {
     pub hits: Vec<ConvHit>,
     pub total: i64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct QaPair {
     pub qa_id: i32,
     pub question: String,
     pub answer: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ConvDetail {
     pub conv_id: i64,
     pub title: String,
@@ -46,7 +48,26 @@ pub struct ConvDetail {
     pub articles: Vec<ArticleItem>,
 }
 
-#[derive(Serialize)]
+/// 只含问答的对话
+#[derive(Serialize, ToSchema)]
+pub struct ConvQa {
+    pub conv_id: i64,
+    pub title: String,
+    pub conv_type: String,
+    pub created_at: String,
+    pub qa_pairs: Vec<QaPair>,
+}
+
+/// 对话中的单篇概念文章
+#[derive(Serialize, ToSchema)]
+pub struct ConvArticle {
+    pub conv_id: i64,
+    pub article_type: String,
+    pub title: String,
+    pub content: String,
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct ArticleItem {
     pub article_type: String,
     pub title: String,
@@ -3,27 +3,44 @@ use axum::{
     response::IntoResponse,
 };
 use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::error;
-use crate::pagination::Pagination;
+use crate::pagination::{Cursor, Pagination};
 use crate::state::AppState;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ColumnInfo {
     pub name: String,
     pub col_type: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TableData {
     pub header: Vec<ColumnInfo>,
     pub rows: Vec<Vec<serde_json::Value>>,
+    /// 游标模式下的下一页游标
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Cursor>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "表名列表",
+    responses((status = 200, description = "成功", body = Vec<String>))
+)]
 pub async fn get_table_names(State(state): State<AppState>) -> impl IntoResponse {
     error::ok_or(state.db_viewer.get_table_names().await, "获取表名")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{table_name}",
+    summary = "分页查看表数据",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = TableData))
+)]
 pub async fn get_table_data(
     Path(table_name): Path<String>,
     Query(pagination): Query<Pagination>,
@@ -31,8 +48,12 @@ pub async fn get_table_data(
 ) -> impl IntoResponse {
     let result = state
         .db_viewer
-        .get_table_data(&table_name, pagination.limit(), pagination.offset())
+        .get_table_data(&table_name, &pagination.window())
         .await
-        .map(|(header, rows)| TableData { header, rows });
+        .map(|(header, rows, next_cursor)| TableData {
+            header,
+            rows,
+            next_cursor,
+        });
     error::ok_or(result, "获取表数据")
 }
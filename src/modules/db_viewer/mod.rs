@@ -4,12 +4,13 @@ mod repository;
 pub mod service;
 
 pub use service::DbViewerService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{Router, routing::get};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(handler::get_table_names))
-        .route("/{table_name}", get(handler::get_table_data))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::get_table_names))
+        .routes(routes!(handler::get_table_data))
 }
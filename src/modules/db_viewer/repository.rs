@@ -5,6 +5,10 @@ use std::sync::Arc;
 use super::handler::ColumnInfo;
 use super::model::TableName;
 use crate::db::query::sanitize_table_name;
+use crate::pagination::{Cursor, Window};
+
+/// 游标模式下附加的 rowid 列，不出现在返回数据里
+const ROWID_COL: &str = "__cursor_rowid";
 
 #[derive(Clone)]
 pub struct DBRepo {
@@ -23,13 +27,12 @@ impl DBRepo {
         Ok(rows.iter().map(|r| r.name.clone()).collect())
     }
 
-    /// 返回 ColumnInfo + 数据行
+    /// 返回 ColumnInfo + 数据行；游标模式按 rowid 翻页（不支持 WITHOUT ROWID 表）
     pub async fn get_table_data(
         &self,
         table_name: &str,
-        limit: i64,
-        offset: i64,
-    ) -> Result<(Vec<ColumnInfo>, Vec<Vec<Value>>), sqlx::Error> {
+        window: &Window,
+    ) -> Result<(Vec<ColumnInfo>, Vec<Vec<Value>>, Option<Cursor>), sqlx::Error> {
         // 先校验表名合法，SQLite 不支持参数化表名
         let safe_name = sanitize_table_name(table_name)?;
 
@@ -50,20 +53,27 @@ impl DBRepo {
             .collect();
 
         // 查数据
-        let rows = sqlx::query(
-            // SAFETY: sanitize_table_name 确保 safe_name 只含 [a-zA-Z0-9_]
-            sqlx::AssertSqlSafe(format!("SELECT * FROM {} LIMIT $1 OFFSET $2", safe_name)),
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&*self.pool)
-        .await?;
+        // SAFETY: sanitize_table_name 确保 safe_name 只含 [a-zA-Z0-9_]
+        let mut qb = if window.is_keyset() {
+            let mut qb = sqlx::QueryBuilder::new(format!(
+                "SELECT rowid AS {ROWID_COL}, * FROM {safe_name} WHERE 1 = 1"
+            ));
+            window.push_after(&mut qb, "rowid", "rowid", false);
+            qb.push(" ORDER BY rowid");
+            qb
+        } else {
+            sqlx::QueryBuilder::new(format!("SELECT * FROM {safe_name}"))
+        };
+        window.push_limit(&mut qb);
+        let rows = qb.build().fetch_all(&*self.pool).await?;
+        let (rows, next) = window.finish(rows, ROWID_COL, ROWID_COL)?;
 
         let data: Vec<Vec<Value>> = rows
             .iter()
             .map(|row| {
                 row.columns()
                     .iter()
+                    .filter(|col| col.name() != ROWID_COL)
                     .map(|col| {
                         let name = col.name();
                         match col.type_info().name() {
@@ -89,7 +99,7 @@ impl DBRepo {
             })
             .collect();
 
-        Ok((columns, data))
+        Ok((columns, data, next))
     }
 }
 
@@ -119,25 +129,56 @@ mod tests {
         assert!(names.contains(&"test_table".to_string()));
     }
 
+    fn offset(limit: i64, offset: i64) -> Window {
+        Window::Offset { limit, offset }
+    }
+
     #[tokio::test]
     async fn get_table_data() {
         let repo = setup().await;
-        let (header, rows) = repo.get_table_data("test_table", 10, 0).await.unwrap();
+        let (header, rows, next) = repo
+            .get_table_data("test_table", &offset(10, 0))
+            .await
+            .unwrap();
         assert_eq!(header.len(), 2);
         assert_eq!(rows.len(), 2);
+        assert!(next.is_none());
     }
 
     #[tokio::test]
     async fn get_table_data_paginated() {
         let repo = setup().await;
-        let (_, rows) = repo.get_table_data("test_table", 1, 0).await.unwrap();
+        let (_, rows, _) = repo
+            .get_table_data("test_table", &offset(1, 0))
+            .await
+            .unwrap();
         assert_eq!(rows.len(), 1);
     }
 
+    #[tokio::test]
+    async fn get_table_data_by_cursor() {
+        let repo = setup().await;
+        let first = Window::Keyset {
+            limit: 1,
+            after: None,
+        };
+        let (header, rows, next) = repo.get_table_data("test_table", &first).await.unwrap();
+        assert_eq!(header.len(), 2);
+        assert_eq!(rows, vec![vec![Value::from(1), Value::from("alice")]]);
+
+        let second = Window::Keyset {
+            limit: 1,
+            after: next,
+        };
+        let (_, rows, next) = repo.get_table_data("test_table", &second).await.unwrap();
+        assert_eq!(rows, vec![vec![Value::from(2), Value::from("bob")]]);
+        assert!(next.is_none());
+    }
+
     #[tokio::test]
     async fn table_not_found() {
         let repo = setup().await;
-        let result = repo.get_table_data("nonexistent", 10, 0).await;
+        let result = repo.get_table_data("nonexistent", &offset(10, 0)).await;
         assert!(result.is_err());
     }
 }
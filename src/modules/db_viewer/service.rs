@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::error::ServiceError;
+use crate::pagination::{Cursor, Window};
 
 use super::handler::ColumnInfo;
 use super::repository::DBRepo;
@@ -23,11 +24,10 @@ impl DbViewerService {
     pub async fn get_table_data(
         &self,
         table_name: &str,
-        limit: i64,
-        offset: i64,
-    ) -> Result<(Vec<ColumnInfo>, Vec<Vec<serde_json::Value>>), ServiceError> {
+        window: &Window,
+    ) -> Result<(Vec<ColumnInfo>, Vec<Vec<serde_json::Value>>, Option<Cursor>), ServiceError> {
         let repo = DBRepo::new(self.pool.clone());
-        repo.get_table_data(table_name, limit, offset)
+        repo.get_table_data(table_name, window)
             .await
             .map_err(ServiceError::Db)
     }
@@ -62,7 +62,11 @@ mod tests {
     #[tokio::test]
     async fn read_table() {
         let svc = setup().await;
-        let (header, rows) = svc.get_table_data("test_t", 10, 0).await.unwrap();
+        let window = Window::Offset {
+            limit: 10,
+            offset: 0,
+        };
+        let (header, rows, _) = svc.get_table_data("test_t", &window).await.unwrap();
         assert_eq!(header.len(), 2);
         assert_eq!(rows.len(), 1);
     }
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+
+use super::model::{ChangeEvent, ChangeKind};
+use crate::modules::activity::Entity;
+
+/// 保留在内存中、供断线重连补发的最近事件数（所有用户共用）
+const HISTORY: usize = 1024;
+/// 广播缓冲；订阅端落后超过此数时收到 `resync`
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 一条已发布的变更，`seq` 在进程内单调递增
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub seq: u64,
+    pub user_id: i32,
+    pub entity: Entity,
+    pub id: String,
+    pub kind: ChangeKind,
+    pub at: String,
+}
+
+impl Envelope {
+    pub fn event(&self) -> ChangeEvent {
+        ChangeEvent {
+            entity: self.entity.as_str().to_string(),
+            id: self.id.clone(),
+            kind: self.kind,
+            at: self.at.clone(),
+        }
+    }
+}
+
+/// 订阅结果：先补发 `missed`，再转发 `live`
+pub struct Subscription {
+    /// 重连时漏掉的本用户事件；`None` 表示无法补全，客户端须全量刷新
+    pub missed: Option<Vec<Envelope>>,
+    pub live: broadcast::Receiver<Envelope>,
+}
+
+struct History {
+    next_seq: u64,
+    events: VecDeque<Envelope>,
+}
+
+impl History {
+    /// `last` 之后属于 `user_id` 的事件；`last` 已被淘汰或不属于本序列时返回 `None`
+    fn since(&self, user_id: i32, last: u64) -> Option<Vec<Envelope>> {
+        let oldest = self.events.front().map_or(self.next_seq, |e| e.seq);
+        if last >= self.next_seq || last + 1 < oldest {
+            return None;
+        }
+        Some(
+            self.events
+                .iter()
+                .filter(|e| e.seq > last && e.user_id == user_id)
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// 进程内的变更广播。
+///
+/// 在 `AppState::new` 中创建一份，由各 service 与 `/api/events` 共用。
+/// 审计日志写入后自动发布，不入审计的状态变化（复习、挂起等）由 service 直接调用
+/// [`publish`](Self::publish)。与审计日志一样尽力而为：没有订阅者时直接丢弃。
+#[derive(Clone)]
+pub struct ChangeFeed {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// 进程启动时间（毫秒），作为事件 id 前缀区分重启前后的序列
+    epoch: i64,
+    tx: broadcast::Sender<Envelope>,
+    history: Mutex<History>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeFeed {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            inner: Arc::new(Inner {
+                epoch: chrono::Utc::now().timestamp_millis(),
+                tx,
+                history: Mutex::new(History {
+                    next_seq: 1,
+                    events: VecDeque::with_capacity(HISTORY),
+                }),
+            }),
+        }
+    }
+
+    pub fn publish(&self, user_id: i32, entity: Entity, id: impl Display, kind: ChangeKind) {
+        let Ok(mut history) = self.inner.history.lock() else {
+            return;
+        };
+        let envelope = Envelope {
+            seq: history.next_seq,
+            user_id,
+            entity,
+            id: id.to_string(),
+            kind,
+            at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        };
+        history.next_seq += 1;
+        if history.events.len() == HISTORY {
+            history.events.pop_front();
+        }
+        history.events.push_back(envelope.clone());
+        // 持锁发送，保证与 subscribe 之间既不漏也不重
+        let _ = self.inner.tx.send(envelope);
+    }
+
+    /// 订阅 `user_id` 的变更。`last_event_id` 为客户端 `Last-Event-ID` 头
+    pub fn subscribe(&self, user_id: i32, last_event_id: Option<&str>) -> Subscription {
+        let history = match self.inner.history.lock() {
+            Ok(h) => h,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let missed = match last_event_id {
+            None => Some(Vec::new()),
+            Some(id) => self
+                .parse_id(id)
+                .and_then(|last| history.since(user_id, last)),
+        };
+        Subscription {
+            missed,
+            live: self.inner.tx.subscribe(),
+        }
+    }
+
+    /// SSE `id` 字段：`<epoch>-<seq>`
+    pub fn event_id(&self, seq: u64) -> String {
+        format!("{}-{seq}", self.inner.epoch)
+    }
+
+    /// 最近一条已发布事件的 id（尚无事件时 seq 为 0）
+    pub fn latest_id(&self) -> String {
+        let seq = self
+            .inner
+            .history
+            .lock()
+            .map_or(0, |h| h.next_seq.saturating_sub(1));
+        self.event_id(seq)
+    }
+
+    fn parse_id(&self, id: &str) -> Option<u64> {
+        let (epoch, seq) = id.trim().split_once('-')?;
+        if epoch.parse::<i64>().ok()? != self.inner.epoch {
+            return None;
+        }
+        seq.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[tokio::test]
+    async fn live_events_reach_subscribers() {
+        let feed = ChangeFeed::new();
+        let mut sub = feed.subscribe(1, None);
+        assert_eq!(sub.missed.unwrap().len(), 0);
+
+        feed.publish(1, Entity::Mem, 7, ChangeKind::Reviewed);
+        let e = sub.live.recv().await.unwrap();
+        assert_eq!(
+            (e.user_id, e.id.as_str(), e.kind),
+            (1, "7", ChangeKind::Reviewed)
+        );
+        assert_eq!(feed.latest_id(), feed.event_id(e.seq));
+    }
+
+    #[test]
+    fn resume_replays_only_own_events_after_last_id() {
+        let feed = ChangeFeed::new();
+        feed.publish(1, Entity::Task, 1, ChangeKind::Created);
+        let last = feed.latest_id();
+        feed.publish(2, Entity::Task, 2, ChangeKind::Created);
+        feed.publish(1, Entity::Bookmark, 3, ChangeKind::Updated);
+
+        let missed = feed.subscribe(1, Some(&last)).missed.unwrap();
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].event().entity, "bookmark");
+
+        // 已是最新：无需补发
+        let latest = feed.latest_id();
+        assert!(feed.subscribe(1, Some(&latest)).missed.unwrap().is_empty());
+    }
+
+    #[test]
+    fn unknown_or_expired_id_requires_resync() {
+        let feed = ChangeFeed::new();
+        assert!(feed.subscribe(1, Some("garbage")).missed.is_none());
+        // 其他进程（重启前）的 id
+        assert!(feed.subscribe(1, Some("1-1")).missed.is_none());
+
+        // 第 1 条已被淘汰：尚未收到它的客户端无法补全，收到过的仍可续传
+        let before = feed.latest_id();
+        for i in 0..=HISTORY {
+            feed.publish(1, Entity::Card, i, ChangeKind::Created);
+        }
+        assert!(feed.subscribe(1, Some(&before)).missed.is_none());
+        let first = feed.event_id(1);
+        assert_eq!(
+            feed.subscribe(1, Some(&first)).missed.unwrap().len(),
+            HISTORY
+        );
+        let recent = feed.event_id(HISTORY as u64);
+        assert_eq!(feed.subscribe(1, Some(&recent)).missed.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use std::future::pending;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    Extension,
+    extract::State,
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+
+use super::feed::{ChangeFeed, Envelope};
+use super::model::ChangeEvent;
+use crate::auth::{self, Claims};
+use crate::modules::activity::Entity;
+use crate::modules::api_token::model::scope_allows;
+use crate::state::AppState;
+
+/// 心跳间隔：保持连接穿过代理的空闲超时
+const HEARTBEAT: Duration = Duration::from_secs(15);
+/// 复核凭证的间隔：会话注销、API 令牌吊销、账号停用后最迟这么久断开
+const RECHECK: Duration = Duration::from_secs(30);
+
+/// GET /api/events
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "实时变更流（SSE：change / resync 事件，支持 Last-Event-ID 续传）",
+    params(("Last-Event-ID" = Option<String>, Header, description = "断线前收到的最后一个事件 id，用于补发")),
+    responses((status = 200, description = "事件流", body = ChangeEvent, content_type = "text/event-stream"))
+)]
+pub async fn events_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let feed = state.changes.clone();
+    let token = auth::bearer_token(&headers).unwrap_or_default();
+    let closed = revoked(state, token, claims.exp, RECHECK);
+    let last_event_id = headers.get("last-event-id").and_then(|v| v.to_str().ok());
+    let sub = feed.subscribe(claims.sub, last_event_id);
+    let (user_id, scopes) = (claims.sub, claims.scopes);
+
+    let head: Vec<_> = match sub.missed {
+        Some(missed) => missed
+            .iter()
+            .filter(|e| visible(e, scopes.as_deref()))
+            .map(|e| change(&feed, e))
+            .collect(),
+        None => vec![Ok(resync().id(feed.latest_id()))],
+    };
+
+    let live = stream::unfold((sub.live, scopes), move |(mut rx, scopes)| {
+        let feed = feed.clone();
+        async move {
+            loop {
+                match rx.recv().await {
+                    Ok(e) if e.user_id == user_id && visible(&e, scopes.as_deref()) => {
+                        return Some((change(&feed, &e), (rx, scopes)));
+                    }
+                    Ok(_) => {}
+                    // 落后太多，中间的事件已被覆盖
+                    Err(RecvError::Lagged(_)) => return Some((Ok(resync()), (rx, scopes))),
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+
+    Sse::new(stream::iter(head).chain(live).take_until(closed))
+        .keep_alive(KeepAlive::new().interval(HEARTBEAT).text("heartbeat"))
+}
+
+/// 连接建立时的凭证失效后完成：access token 到期（`exp`，API 令牌为 0 表示不过期），
+/// 或每隔 `every` 复核时发现会话已注销、令牌已吊销、账号已删除或停用
+async fn revoked(state: AppState, token: String, exp: usize, every: Duration) {
+    let expiry = async {
+        if exp == 0 {
+            return pending().await;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        tokio::time::sleep(Duration::from_secs((exp as u64).saturating_sub(now))).await;
+    };
+    let recheck = async {
+        let mut ticks = tokio::time::interval(every);
+        // 首个 tick 立即完成，连接刚通过认证，跳过
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            if !auth::still_authenticated(&state, &token).await {
+                return;
+            }
+        }
+    };
+    tokio::select! {
+        () = expiry => {}
+        () = recheck => {}
+    }
+}
+
+fn change(feed: &ChangeFeed, e: &Envelope) -> Result<Event, axum::Error> {
+    Event::default()
+        .event("change")
+        .id(feed.event_id(e.seq))
+        .json_data(e.event())
+}
+
+/// 无法补全漏掉的事件，客户端应全量刷新
+fn resync() -> Event {
+    Event::default().event("resync").data("{}")
+}
+
+/// API 令牌只收到其作用域可读的实体变更
+fn visible(e: &Envelope, scopes: Option<&[String]>) -> bool {
+    let resource = match e.entity {
+        Entity::Mem => "mem",
+        Entity::Task => "tasks",
+        Entity::Bookmark => "bookmarks",
+        Entity::Card => "cards",
+        Entity::Media => "media",
+    };
+    scopes.is_none_or(|s| scope_allows(s, resource, false))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::config::Config;
+    use crate::storage::LocalStorage;
+    use sqlx::SqlitePool;
+    use std::sync::Arc;
+
+    const EVERY: Duration = Duration::from_millis(10);
+
+    async fn setup() -> (AppState, i32) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let uid = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('u', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        let storage = Arc::new(LocalStorage::new(std::env::temp_dir()));
+        (AppState::new(pool, storage, &Config::from_env()), uid)
+    }
+
+    /// `revoked` 在 `within` 内是否完成
+    async fn closes_within(state: &AppState, token: &str, exp: usize, within: Duration) -> bool {
+        let closed = revoked(state.clone(), token.to_string(), exp, EVERY);
+        tokio::time::timeout(within, closed).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn stream_stays_open_while_credentials_hold() {
+        let (state, uid) = setup().await;
+        let (token, claims) = auth::create_token(uid, "user", "s1", &state.jwt_secret);
+        assert!(!closes_within(&state, &token, claims.exp, EVERY * 10).await);
+    }
+
+    #[tokio::test]
+    async fn stream_closes_at_token_expiry() {
+        let (state, uid) = setup().await;
+        let (token, _) = auth::create_token(uid, "user", "s1", &state.jwt_secret);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize;
+        assert!(closes_within(&state, &token, now, EVERY * 10).await);
+    }
+
+    #[tokio::test]
+    async fn stream_closes_after_logout_or_disable() {
+        let (state, uid) = setup().await;
+        let (token, claims) = auth::create_token(uid, "user", "s1", &state.jwt_secret);
+        sqlx::query("INSERT INTO revoked_jti (jti, exp) VALUES (?, ?)")
+            .bind(&claims.jti)
+            .bind(claims.exp as i64)
+            .execute(&*state.db)
+            .await
+            .unwrap();
+        assert!(closes_within(&state, &token, claims.exp, EVERY * 10).await);
+
+        let (token, claims) = auth::create_token(uid, "user", "s2", &state.jwt_secret);
+        sqlx::query("UPDATE user SET disabled = 1 WHERE id = ?")
+            .bind(uid)
+            .execute(&*state.db)
+            .await
+            .unwrap();
+        assert!(closes_within(&state, &token, claims.exp, EVERY * 10).await);
+    }
+}
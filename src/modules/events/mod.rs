@@ -0,0 +1,26 @@
+//! 实时变更流（Server-Sent Events）。
+//!
+//! 各模块 service 增删改后经 [`ChangeFeed`] 广播 `{entity, id, kind}`，
+//! `/api/events` 把当前用户的变更推给已连接的客户端，客户端据此刷新计数与列表。
+//! 审计日志写入时自动发布；复习、挂起等不入审计的状态变化由 service 直接发布。
+//!
+//! 事件 id 形如 `<epoch>-<seq>`，epoch 为进程启动时间。重连时带上 `Last-Event-ID`，
+//! 服务端从内存中保留的最近事件里补发；超出保留范围或服务端已重启则先发一条
+//! `resync`，客户端应全量刷新。空闲时每 15 秒发送注释行心跳。
+//!
+//! 连接只在建立时认证一次：令牌到期时服务端结束流，期间每 30 秒复查会话是否被注销、
+//! 用户是否被禁用，一旦失效同样结束，客户端需带新令牌重连。
+
+mod feed;
+mod handler;
+pub mod model;
+
+use crate::state::AppState;
+pub use feed::ChangeFeed;
+pub use model::ChangeKind;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::events_handler))
+}
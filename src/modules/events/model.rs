@@ -0,0 +1,41 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::modules::activity::model::Action;
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    /// 移入回收站
+    Deleted,
+    Restored,
+    Purged,
+    /// 记忆项复习后调度状态变化
+    Reviewed,
+}
+
+impl From<Action> for ChangeKind {
+    fn from(action: Action) -> Self {
+        match action {
+            Action::Create => Self::Created,
+            Action::Update => Self::Updated,
+            Action::Delete => Self::Deleted,
+            Action::Restore => Self::Restored,
+            Action::Purge => Self::Purged,
+        }
+    }
+}
+
+/// `change` 事件的 data：只说明哪个实体变了，客户端按需重新拉取
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChangeEvent {
+    /// `mem` / `task` / `bookmark` / `card` / `media`
+    pub entity: String,
+    pub id: String,
+    pub kind: ChangeKind,
+    /// RFC 3339
+    pub at: String,
+}
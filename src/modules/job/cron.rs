@@ -0,0 +1,212 @@
+//! 五段式 cron 表达式（分 时 日 月 周，UTC）。
+//!
+//! 每段支持 `*`、数字、`a-b` 区间、`,` 列表与 `/n` 步长；周日可写 0 或 7。
+//! 日与周同时受限时满足其一即可（与 Vixie cron 一致）。
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_any: bool,
+    weekdays_any: bool,
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("cron 表达式须为 5 段: {expr}"));
+        };
+        let mut weekdays = field(weekday, 0, 7)?;
+        // 7 与 0 都表示周日
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: field(minute, 0, 59)?,
+            hours: field(hour, 0, 23)?,
+            days: field(day, 1, 31)?,
+            months: field(month, 1, 12)?,
+            weekdays,
+            days_any: day.starts_with('*'),
+            weekdays_any: weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, t: &DateTime<Utc>) -> bool {
+        let day = bit(self.days, t.day());
+        let weekday = bit(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.days_any, self.weekdays_any) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// 严格晚于 `after` 的下一个触发时刻（精确到分钟）；四年内无匹配时返回 None
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(4 * 366);
+        while t <= limit {
+            if !bit(self.months, t.month()) {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = Utc.with_ymd_and_hms(y, m, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(&t) {
+                t = (t + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+            } else if !bit(self.hours, t.hour()) {
+                t = (t + Duration::hours(1)).with_minute(0)?;
+            } else if !bit(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+fn bit(set: u64, n: u32) -> bool {
+    set & (1 << n) != 0
+}
+
+/// 解析一段为位集
+fn field(spec: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || format!("无效的 cron 字段: {spec}");
+    let num = |s: &str| -> Result<u32, String> {
+        let n: u32 = s.parse().map_err(|_| invalid())?;
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(format!("cron 字段 {spec} 超出范围 {min}-{max}"))
+        }
+    };
+
+    let mut set = 0u64;
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => (
+                r,
+                s.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (num(a)?, num(b)?)
+        } else {
+            let a = num(range)?;
+            // `a/n` 表示从 a 起每 n 个
+            (a, if step > 1 { max } else { a })
+        };
+        if lo > hi {
+            return Err(invalid());
+        }
+        for n in (lo..=hi).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> String {
+        Cron::parse(expr)
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn parses_fields() {
+        assert!(Cron::parse("* * * * *").is_ok());
+        assert!(Cron::parse("*/15 0-6,22 1 */2 1-5").is_ok());
+        assert!(Cron::parse("* * * *").is_err());
+        assert!(Cron::parse("60 * * * *").is_err());
+        assert!(Cron::parse("5-1 * * * *").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("a * * * *").is_err());
+    }
+
+    #[test]
+    fn next_minute_and_hour() {
+        assert_eq!(
+            next("* * * * *", "2024-01-01T10:00:30Z"),
+            "2024-01-01T10:01:00+00:00"
+        );
+        assert_eq!(
+            next("0 * * * *", "2024-01-01T10:00:00Z"),
+            "2024-01-01T11:00:00+00:00"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01T10:16:00Z"),
+            "2024-01-01T10:30:00+00:00"
+        );
+        assert_eq!(
+            next("30 3 * * *", "2024-01-01T04:00:00Z"),
+            "2024-01-02T03:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn next_crosses_month_and_year() {
+        assert_eq!(
+            next("0 0 1 * *", "2024-01-15T00:00:00Z"),
+            "2024-02-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z"),
+            "2028-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 12 * 12 *", "2024-12-31T13:00:00Z"),
+            "2025-12-01T12:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn weekday_rules() {
+        // 2024-01-01 是周一
+        assert_eq!(
+            next("0 9 * * 0", "2024-01-01T00:00:00Z"),
+            "2024-01-07T09:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 9 * * 7", "2024-01-01T00:00:00Z"),
+            "2024-01-07T09:00:00+00:00"
+        );
+        // 日与周同时指定：满足其一
+        assert_eq!(
+            next("0 0 15 * 3", "2024-01-01T00:00:00Z"),
+            "2024-01-03T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn impossible_date_yields_none() {
+        let cron = Cron::parse("0 0 31 2 *").unwrap();
+        assert!(cron.next_after(at("2024-01-01T00:00:00Z")).is_none());
+    }
+}
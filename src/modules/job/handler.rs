@@ -0,0 +1,27 @@
+use axum::{
+    Extension,
+    extract::{Path, State},
+    response::{IntoResponse, Json},
+};
+
+use super::model::JobResponse;
+use crate::auth::Claims;
+use crate::state::AppState;
+
+/// GET /api/jobs/{id}
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "任务状态（普通用户仅自己提交的任务）",
+    responses((status = 200, description = "成功", body = JobResponse))
+)]
+pub async fn get_job_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
+    match state.jobs.get(id, claims.sub, claims.role == "admin").await {
+        Ok(job) => Json(job).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,24 @@
+//! 持久化后台任务。
+//!
+//! 任务写入 `job` 表，由 [`runner`] 启动的 worker 取出执行；失败按指数退避重试，
+//! 进程重启后中断的任务重新排队。周期任务按 cron 表达式定时入队，
+//! `/api/jobs/{id}` 查询任务状态与结果。
+
+mod cron;
+mod handler;
+pub mod model;
+mod repository;
+pub mod runner;
+pub mod service;
+mod tasks;
+
+pub use model::{JobKind, JobResponse, NewJob};
+pub use service::JobService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::get_job_handler))
+}
@@ -0,0 +1,151 @@
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// 训练 FSRS 参数并写回配置
+    FsrsOptimize,
+    /// 修剪过多的 revlog
+    RevlogPrune,
+    /// 删除上传目录中没有数据库记录的文件
+    MediaCleanup,
+    /// 抓取并缓存网站图标，payload `{"host": …}`
+    FaviconFetch,
+    /// 导入 Firefox 书签 HTML，payload `{"html": …}`
+    BookmarkImport,
+    /// 投递一次 webhook，payload `{"delivery_id": …}`
+    WebhookDeliver,
+    /// 彻底删除回收站中超过保留期的记录
+    TrashPurge,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 7] = [
+        JobKind::FsrsOptimize,
+        JobKind::RevlogPrune,
+        JobKind::MediaCleanup,
+        JobKind::FaviconFetch,
+        JobKind::BookmarkImport,
+        JobKind::WebhookDeliver,
+        JobKind::TrashPurge,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::FsrsOptimize => "fsrs_optimize",
+            JobKind::RevlogPrune => "revlog_prune",
+            JobKind::MediaCleanup => "media_cleanup",
+            JobKind::FaviconFetch => "favicon_fetch",
+            JobKind::BookmarkImport => "bookmark_import",
+            JobKind::WebhookDeliver => "webhook_deliver",
+            JobKind::TrashPurge => "trash_purge",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+}
+
+/// 任务是否已结束。状态流转：`queued` → `running` → `succeeded` / `failed`，
+/// 失败且还有重试次数时回到 `queued`
+pub fn is_finished(status: &str) -> bool {
+    matches!(status, "succeeded" | "failed")
+}
+
+/// 入队参数
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub kind: JobKind,
+    pub payload: Value,
+    pub user_id: Option<i32>,
+    pub max_attempts: i32,
+    pub dedupe_key: Option<String>,
+}
+
+impl NewJob {
+    pub fn new(kind: JobKind) -> Self {
+        Self {
+            kind,
+            payload: Value::Object(Default::default()),
+            user_id: None,
+            max_attempts: 3,
+            dedupe_key: None,
+        }
+    }
+
+    pub fn payload(mut self, payload: Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub fn user(mut self, user_id: i32) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    pub fn max_attempts(mut self, n: i32) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+
+    /// 已有相同 key 的任务在排队或运行时不再重复入队，直接返回那一个
+    pub fn dedupe(mut self, key: impl Into<String>) -> Self {
+        self.dedupe_key = Some(key.into());
+        self
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct JobRow {
+    pub id: i64,
+    pub kind: String,
+    pub payload: String,
+    pub user_id: Option<i32>,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: String,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+/// 任务状态查询响应（不含 payload）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobResponse {
+    pub id: i64,
+    pub kind: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: String,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+impl From<JobRow> for JobResponse {
+    fn from(r: JobRow) -> Self {
+        Self {
+            id: r.id,
+            kind: r.kind,
+            status: r.status,
+            attempts: r.attempts,
+            max_attempts: r.max_attempts,
+            run_at: r.run_at,
+            result: r.result.and_then(|s| serde_json::from_str(&s).ok()),
+            error: r.error,
+            created_at: r.created_at,
+            started_at: r.started_at,
+            finished_at: r.finished_at,
+        }
+    }
+}
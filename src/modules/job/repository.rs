@@ -0,0 +1,172 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::model::{JobRow, NewJob};
+
+const COLUMNS: &str = "id, kind, payload, user_id, status, attempts, max_attempts, run_at, \
+                       result, error, created_at, started_at, finished_at";
+
+#[derive(Clone)]
+pub struct JobRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl JobRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    /// 入队；带 dedupe_key 且已有同 key 任务在排队 / 运行时返回已有任务
+    pub async fn insert(&self, job: &NewJob) -> Result<JobRow, sqlx::Error> {
+        let inserted = sqlx::query_as::<_, JobRow>(sqlx::AssertSqlSafe(format!(
+            "INSERT OR IGNORE INTO job (kind, payload, user_id, max_attempts, dedupe_key)
+             VALUES (?, ?, ?, ?, ?)
+             RETURNING {COLUMNS}"
+        )))
+        .bind(job.kind.as_str())
+        .bind(job.payload.to_string())
+        .bind(job.user_id)
+        .bind(job.max_attempts)
+        .bind(job.dedupe_key.as_deref())
+        .fetch_optional(&*self.pool)
+        .await?;
+        if let Some(row) = inserted {
+            return Ok(row);
+        }
+        sqlx::query_as::<_, JobRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {COLUMNS} FROM job
+             WHERE dedupe_key = ? AND status IN ('queued', 'running')"
+        )))
+        .bind(job.dedupe_key.as_deref())
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn find_by_id(&self, id: i64) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as::<_, JobRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {COLUMNS} FROM job WHERE id = ?"
+        )))
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// 原子地取出一个到期的排队任务并标记为 running（attempts + 1）
+    pub async fn claim_next(&self, now: &str) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as::<_, JobRow>(sqlx::AssertSqlSafe(format!(
+            "UPDATE job SET status = 'running', attempts = attempts + 1, started_at = ?1
+             WHERE id = (SELECT id FROM job WHERE status = 'queued' AND run_at <= ?1
+                         ORDER BY run_at, id LIMIT 1)
+               AND status = 'queued'
+             RETURNING {COLUMNS}"
+        )))
+        .bind(now)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn succeed(&self, id: i64, result: &str, now: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE job SET status = 'succeeded', result = ?, error = NULL, finished_at = ?
+             WHERE id = ?",
+        )
+        .bind(result)
+        .bind(now)
+        .bind(id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 失败后重新排队，`run_at` 之后才会再被取出
+    pub async fn retry(&self, id: i64, error: &str, run_at: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE job SET status = 'queued', error = ?, run_at = ? WHERE id = ?")
+            .bind(error)
+            .bind(run_at)
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn fail(&self, id: i64, error: &str, now: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE job SET status = 'failed', error = ?, finished_at = ? WHERE id = ?")
+            .bind(error)
+            .bind(now)
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 启动时处理上次进程遗留的 running 任务：还有重试次数的放回队列，否则标记失败
+    pub async fn requeue_running(&self, now: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE job SET
+                 status = CASE WHEN attempts >= max_attempts THEN 'failed' ELSE 'queued' END,
+                 finished_at = CASE WHEN attempts >= max_attempts THEN ?1 END,
+                 error = '进程退出时任务中断'
+             WHERE status = 'running'",
+        )
+        .bind(now)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除 `before` 之前结束的任务
+    pub async fn purge_finished(&self, before: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM job WHERE status IN ('succeeded', 'failed') AND finished_at < ?",
+        )
+        .bind(before)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // ── 周期任务 ──
+
+    pub async fn schedule_next_run(&self, name: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT next_run_at FROM job_schedule WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&*self.pool)
+            .await
+    }
+
+    pub async fn init_schedule(&self, name: &str, next_run_at: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR IGNORE INTO job_schedule (name, next_run_at) VALUES (?, ?)")
+            .bind(name)
+            .bind(next_run_at)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 仅当下次触发时间仍为 `expected` 时推进（多实例时只有一个能抢到本次触发）
+    pub async fn advance_schedule(
+        &self,
+        name: &str,
+        expected: &str,
+        next_run_at: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE job_schedule SET next_run_at = ? WHERE name = ? AND next_run_at = ?",
+        )
+        .bind(next_run_at)
+        .bind(name)
+        .bind(expected)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn set_schedule_job(&self, name: &str, job_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE job_schedule SET last_job_id = ? WHERE name = ?")
+            .bind(job_id)
+            .bind(name)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+}
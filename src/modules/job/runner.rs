@@ -0,0 +1,280 @@
+//! worker 与周期调度。
+//!
+//! 每个 worker 循环取出到期任务执行；入队时通过 [`JobService`] 的通知立即唤醒，
+//! 否则每 [`POLL_INTERVAL`] 轮询一次（退避到期的重试靠轮询取出）。
+//! 调度循环每 [`SCHEDULE_TICK`] 检查 [`SCHEDULES`]，到期则入队并推进下次触发时间。
+
+use std::future::Future;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use super::cron::Cron;
+use super::model::{JobKind, JobRow, NewJob};
+use super::service::{JobService, timestamp};
+use super::tasks;
+use crate::error::ServiceError;
+use crate::state::AppState;
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+pub const SCHEDULE_TICK: Duration = Duration::from_secs(30);
+/// 已结束任务的保留天数
+const RETENTION_DAYS: i64 = 7;
+
+/// 周期任务
+pub struct Schedule {
+    pub name: &'static str,
+    /// 5 段 cron 表达式（UTC）
+    pub cron: &'static str,
+    pub kind: JobKind,
+}
+
+pub const SCHEDULES: &[Schedule] = &[
+    Schedule {
+        name: "revlog_prune",
+        cron: "0 * * * *",
+        kind: JobKind::RevlogPrune,
+    },
+    Schedule {
+        name: "media_cleanup",
+        cron: "30 3 * * *",
+        kind: JobKind::MediaCleanup,
+    },
+    Schedule {
+        name: "trash_purge",
+        cron: "15 4 * * *",
+        kind: JobKind::TrashPurge,
+    },
+];
+
+/// 恢复中断的任务，启动 `workers` 个 worker 与调度循环
+pub fn spawn(state: AppState, workers: usize) {
+    let jobs = state.jobs.clone();
+    tokio::spawn(async move {
+        match jobs.recover().await {
+            Ok(0) => {}
+            Ok(n) => info!("恢复 {n} 个中断的后台任务"),
+            Err(e) => warn!("恢复中断任务失败: {e}"),
+        }
+
+        for _ in 0..workers.max(1) {
+            let state = state.clone();
+            tokio::spawn(worker(state.jobs.clone(), move |job| {
+                tasks::execute(state.clone(), job)
+            }));
+        }
+        info!("后台任务 worker 已启动: {} 个", workers.max(1));
+
+        scheduler(jobs).await;
+    });
+}
+
+async fn worker<F, Fut>(jobs: JobService, exec: F)
+where
+    F: Fn(JobRow) -> Fut,
+    Fut: Future<Output = Result<Value, String>> + Send + 'static,
+{
+    loop {
+        match run_next(&jobs, &exec).await {
+            Ok(true) => {}
+            Ok(false) => jobs.idle(POLL_INTERVAL).await,
+            Err(e) => {
+                warn!("后台任务队列读写失败: {e}");
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// 取出并执行一个到期任务；队列为空时返回 false
+async fn run_next<F, Fut>(jobs: &JobService, exec: &F) -> Result<bool, sqlx::Error>
+where
+    F: Fn(JobRow) -> Fut,
+    Fut: Future<Output = Result<Value, String>> + Send + 'static,
+{
+    let Some(job) = jobs.claim_next().await? else {
+        return Ok(false);
+    };
+
+    // 在独立 task 中执行：panic 记为失败，不会拖垮 worker
+    let outcome = match tokio::spawn(exec(job.clone())).await {
+        Ok(outcome) => outcome,
+        Err(e) => Err(format!("任务异常退出: {e}")),
+    };
+    match outcome {
+        Ok(result) => {
+            info!("任务 #{} {} 完成", job.id, job.kind);
+            jobs.succeed(&job, &result).await?;
+        }
+        Err(e) => {
+            warn!(
+                "任务 #{} {} 第 {} 次执行失败: {e}",
+                job.id, job.kind, job.attempts
+            );
+            jobs.fail(&job, &e).await?;
+        }
+    }
+    Ok(true)
+}
+
+async fn scheduler(jobs: JobService) {
+    let schedules: Vec<(&Schedule, Cron)> = SCHEDULES
+        .iter()
+        .filter_map(|s| match Cron::parse(s.cron) {
+            Ok(cron) => Some((s, cron)),
+            Err(e) => {
+                warn!("周期任务 {} 的 cron 表达式无效: {e}", s.name);
+                None
+            }
+        })
+        .collect();
+
+    let mut ticker = tokio::time::interval(SCHEDULE_TICK);
+    loop {
+        ticker.tick().await;
+        let now = Utc::now();
+        for (schedule, cron) in &schedules {
+            if let Err(e) = tick_schedule(&jobs, schedule, cron, now).await {
+                warn!("周期任务 {} 入队失败: {e}", schedule.name);
+            }
+        }
+        if let Err(e) = jobs
+            .purge_finished(now - chrono::Duration::days(RETENTION_DAYS))
+            .await
+        {
+            warn!("清理过期任务记录失败: {e}");
+        }
+    }
+}
+
+/// 到期则入队一次并推进到 `now` 之后的下一次触发；返回入队的任务 id。
+///
+/// 停机期间错过的多次触发只补跑一次；推进用比较交换，多实例共享数据库时只有一个会入队。
+async fn tick_schedule(
+    jobs: &JobService,
+    schedule: &Schedule,
+    cron: &Cron,
+    now: DateTime<Utc>,
+) -> Result<Option<i64>, ServiceError> {
+    let repo = jobs.repo();
+    let Some(next_run_at) = repo.schedule_next_run(schedule.name).await? else {
+        // 首次见到该任务：从现在起算，不立即执行
+        if let Some(first) = cron.next_after(now) {
+            repo.init_schedule(schedule.name, &timestamp(first)).await?;
+        }
+        return Ok(None);
+    };
+    if next_run_at > timestamp(now) {
+        return Ok(None);
+    }
+
+    let Some(following) = cron.next_after(now) else {
+        return Ok(None);
+    };
+    if !repo
+        .advance_schedule(schedule.name, &next_run_at, &timestamp(following))
+        .await?
+    {
+        return Ok(None);
+    }
+
+    let job = jobs
+        .enqueue(NewJob::new(schedule.kind).dedupe(format!("schedule:{}", schedule.name)))
+        .await?;
+    repo.set_schedule_job(schedule.name, job.id).await?;
+    Ok(Some(job.id))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use std::sync::Arc;
+
+    async fn setup() -> JobService {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        JobService::new(pool)
+    }
+
+    #[test]
+    fn builtin_schedules_parse() {
+        for s in SCHEDULES {
+            assert!(Cron::parse(s.cron).is_ok(), "{}", s.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn run_next_records_outcome() {
+        let jobs = setup().await;
+        let ok = jobs
+            .enqueue(NewJob::new(JobKind::RevlogPrune))
+            .await
+            .unwrap();
+        let bad = jobs
+            .enqueue(NewJob::new(JobKind::MediaCleanup).max_attempts(1))
+            .await
+            .unwrap();
+        let panics = jobs
+            .enqueue(NewJob::new(JobKind::FsrsOptimize).max_attempts(1))
+            .await
+            .unwrap();
+
+        let exec = |job: JobRow| async move {
+            match JobKind::parse(&job.kind) {
+                Some(JobKind::RevlogPrune) => Ok(json!({ "n": 3 })),
+                Some(JobKind::MediaCleanup) => Err("disk full".to_string()),
+                _ => panic!("boom"),
+            }
+        };
+        for _ in 0..3 {
+            assert!(run_next(&jobs, &exec).await.unwrap());
+        }
+        assert!(!run_next(&jobs, &exec).await.unwrap());
+
+        let ok = jobs.get(ok.id, 0, true).await.unwrap();
+        assert_eq!(ok.status, "succeeded");
+        assert_eq!(ok.result, Some(json!({ "n": 3 })));
+        let bad = jobs.get(bad.id, 0, true).await.unwrap();
+        assert_eq!(bad.status, "failed");
+        assert_eq!(bad.error.as_deref(), Some("disk full"));
+        let panics = jobs.get(panics.id, 0, true).await.unwrap();
+        assert_eq!(panics.status, "failed");
+        assert!(panics.error.unwrap().contains("异常退出"));
+    }
+
+    #[tokio::test]
+    async fn schedule_fires_once_per_slot() {
+        let jobs = setup().await;
+        let schedule = Schedule {
+            name: "test",
+            cron: "0 * * * *",
+            kind: JobKind::RevlogPrune,
+        };
+        let cron = Cron::parse(schedule.cron).unwrap();
+        let t = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // 首次只登记下次触发时间
+        let first = tick_schedule(&jobs, &schedule, &cron, t("2024-01-01T10:15:00Z"));
+        assert_eq!(first.await.unwrap(), None);
+        let next = jobs.repo().schedule_next_run("test").await.unwrap();
+        assert_eq!(next.as_deref(), Some("2024-01-01T11:00:00.000Z"));
+
+        let early = tick_schedule(&jobs, &schedule, &cron, t("2024-01-01T10:59:00Z"));
+        assert_eq!(early.await.unwrap(), None);
+
+        // 停机错过多个整点，只补跑一次
+        let fired = tick_schedule(&jobs, &schedule, &cron, t("2024-01-01T13:30:00Z"))
+            .await
+            .unwrap();
+        assert!(fired.is_some());
+        let next = jobs.repo().schedule_next_run("test").await.unwrap();
+        assert_eq!(next.as_deref(), Some("2024-01-01T14:00:00.000Z"));
+        let again = tick_schedule(&jobs, &schedule, &cron, t("2024-01-01T13:31:00Z"));
+        assert_eq!(again.await.unwrap(), None);
+    }
+}
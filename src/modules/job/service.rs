@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use super::model::{JobResponse, JobRow, NewJob, is_finished};
+use super::repository::JobRepo;
+use crate::error::ServiceError;
+
+/// 与 SQLite `strftime('%Y-%m-%dT%H:%M:%fZ')` 相同的格式，可按字典序比较
+pub fn timestamp(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// 第 `attempts` 次失败后的重试间隔：10s、20s、40s…… 最长 1 小时
+pub fn backoff(attempts: i32) -> Duration {
+    let exp = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::from_secs(10u64.saturating_mul(1 << exp).min(3600))
+}
+
+/// 任务队列：入队、状态查询，以及供 [`super::runner`] 使用的取出 / 完成接口
+#[derive(Clone)]
+pub struct JobService {
+    repo: JobRepo,
+    /// 入队时唤醒一个空闲 worker
+    notify: Arc<Notify>,
+}
+
+impl JobService {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self {
+            repo: JobRepo::new(db),
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub async fn enqueue(&self, job: NewJob) -> Result<JobRow, ServiceError> {
+        let row = self.repo.insert(&job).await?;
+        self.notify.notify_one();
+        Ok(row)
+    }
+
+    /// 查询任务状态：普通用户只能看自己提交的任务，管理员可看全部
+    pub async fn get(
+        &self,
+        id: i64,
+        viewer: i32,
+        is_admin: bool,
+    ) -> Result<JobResponse, ServiceError> {
+        self.repo
+            .find_by_id(id)
+            .await?
+            .filter(|job| is_admin || job.user_id == Some(viewer))
+            .map(JobResponse::from)
+            .ok_or_else(|| ServiceError::NotFound("任务不存在".into()))
+    }
+
+    /// 等待任务结束，超时返回 None
+    pub async fn wait(&self, id: i64, timeout: Duration) -> Result<Option<JobRow>, ServiceError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Some(job) = self.repo.find_by_id(id).await?
+                && is_finished(&job.status)
+            {
+                return Ok(Some(job));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    // ── worker 接口 ──
+
+    pub(super) async fn claim_next(&self) -> Result<Option<JobRow>, sqlx::Error> {
+        self.repo.claim_next(&timestamp(Utc::now())).await
+    }
+
+    /// 等待入队通知或超时
+    pub(super) async fn idle(&self, timeout: Duration) {
+        let _ = tokio::time::timeout(timeout, self.notify.notified()).await;
+    }
+
+    pub(super) async fn succeed(&self, job: &JobRow, result: &Value) -> Result<(), sqlx::Error> {
+        self.repo
+            .succeed(job.id, &result.to_string(), &timestamp(Utc::now()))
+            .await
+    }
+
+    /// 记录失败；还有次数时按退避重新排队
+    pub(super) async fn fail(&self, job: &JobRow, error: &str) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        if job.attempts < job.max_attempts {
+            let delay = chrono::Duration::from_std(backoff(job.attempts)).unwrap_or_default();
+            self.repo
+                .retry(job.id, error, &timestamp(now + delay))
+                .await?;
+            // 退避到期后由 worker 的轮询取出
+        } else {
+            self.repo.fail(job.id, error, &timestamp(now)).await?;
+        }
+        Ok(())
+    }
+
+    pub(super) async fn recover(&self) -> Result<u64, sqlx::Error> {
+        self.repo.requeue_running(&timestamp(Utc::now())).await
+    }
+
+    pub(super) async fn purge_finished(&self, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+        self.repo.purge_finished(&timestamp(before)).await
+    }
+
+    pub(super) fn repo(&self) -> &JobRepo {
+        &self.repo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::modules::job::model::JobKind;
+    use serde_json::json;
+
+    async fn setup() -> JobService {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        JobService::new(pool)
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        assert_eq!(backoff(1), Duration::from_secs(10));
+        assert_eq!(backoff(2), Duration::from_secs(20));
+        assert_eq!(backoff(4), Duration::from_secs(80));
+        assert_eq!(backoff(20), Duration::from_secs(3600));
+    }
+
+    #[tokio::test]
+    async fn dedupe_returns_pending_job() {
+        let jobs = setup().await;
+        let a = jobs
+            .enqueue(NewJob::new(JobKind::FsrsOptimize).dedupe("opt"))
+            .await
+            .unwrap();
+        let b = jobs
+            .enqueue(NewJob::new(JobKind::FsrsOptimize).dedupe("opt"))
+            .await
+            .unwrap();
+        assert_eq!(a.id, b.id);
+
+        // 结束后可以再次入队
+        let claimed = jobs.claim_next().await.unwrap().unwrap();
+        jobs.succeed(&claimed, &json!({})).await.unwrap();
+        let c = jobs
+            .enqueue(NewJob::new(JobKind::FsrsOptimize).dedupe("opt"))
+            .await
+            .unwrap();
+        assert_ne!(a.id, c.id);
+    }
+
+    #[tokio::test]
+    async fn failure_retries_with_backoff_then_fails() {
+        let jobs = setup().await;
+        jobs.enqueue(NewJob::new(JobKind::RevlogPrune).max_attempts(2))
+            .await
+            .unwrap();
+
+        let job = jobs.claim_next().await.unwrap().unwrap();
+        assert_eq!((job.status.as_str(), job.attempts), ("running", 1));
+        jobs.fail(&job, "boom").await.unwrap();
+
+        // 退避期间不会被取出
+        assert!(jobs.claim_next().await.unwrap().is_none());
+        let queued = jobs.repo.find_by_id(job.id).await.unwrap().unwrap();
+        assert_eq!(queued.status, "queued");
+        assert!(queued.run_at > timestamp(Utc::now()));
+
+        // 模拟退避到期
+        jobs.repo
+            .retry(job.id, "boom", &timestamp(Utc::now()))
+            .await
+            .unwrap();
+        let job = jobs.claim_next().await.unwrap().unwrap();
+        assert_eq!(job.attempts, 2);
+        jobs.fail(&job, "boom again").await.unwrap();
+
+        let failed = jobs.repo.find_by_id(job.id).await.unwrap().unwrap();
+        assert_eq!(failed.status, "failed");
+        assert_eq!(failed.error.as_deref(), Some("boom again"));
+        assert!(failed.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn visibility_and_recovery() {
+        let jobs = setup().await;
+        let own = jobs
+            .enqueue(NewJob::new(JobKind::BookmarkImport).user(1).max_attempts(1))
+            .await
+            .unwrap();
+        let system = jobs
+            .enqueue(NewJob::new(JobKind::MediaCleanup))
+            .await
+            .unwrap();
+
+        assert!(jobs.get(own.id, 1, false).await.is_ok());
+        assert!(jobs.get(own.id, 2, false).await.is_err());
+        assert!(jobs.get(system.id, 1, false).await.is_err());
+        assert!(jobs.get(system.id, 2, true).await.is_ok());
+
+        // 两个任务都在运行时进程退出
+        jobs.claim_next().await.unwrap().unwrap();
+        jobs.claim_next().await.unwrap().unwrap();
+        assert_eq!(jobs.recover().await.unwrap(), 2);
+        // 只允许一次的任务直接失败，其余重新排队
+        assert_eq!(jobs.get(own.id, 1, false).await.unwrap().status, "failed");
+        assert_eq!(jobs.get(system.id, 1, true).await.unwrap().status, "queued");
+    }
+}
@@ -0,0 +1,87 @@
+//! 各类任务的执行逻辑，结果写入 `job.result`。
+
+use serde_json::{Value, json};
+
+use super::model::{JobKind, JobRow};
+use crate::metrics::OptimizerOutcome;
+use crate::modules::bookmark::fetch_and_cache;
+use crate::state::AppState;
+
+pub async fn execute(state: AppState, job: JobRow) -> Result<Value, String> {
+    let kind = JobKind::parse(&job.kind).ok_or_else(|| format!("未知任务类型: {}", job.kind))?;
+    let payload: Value =
+        serde_json::from_str(&job.payload).map_err(|e| format!("payload 不是有效 JSON: {e}"))?;
+
+    match kind {
+        JobKind::FsrsOptimize => {
+            let user_id = job.user_id.ok_or("优化任务缺少用户")?;
+            let result = state.mem.optimize_params(user_id).await;
+            state.metrics.optimizer_run(match &result {
+                Ok(Some(_)) => OptimizerOutcome::Ok,
+                Ok(None) => OptimizerOutcome::Insufficient,
+                Err(_) => OptimizerOutcome::Error,
+            });
+            match result? {
+                Some(params) => Ok(json!({
+                    "ok": true,
+                    "message": format!("优化完成，得到 {} 个参数", params.len()),
+                    "params": params,
+                })),
+                None => Ok(json!({
+                    "ok": false,
+                    "message": "数据不足，至少需要 10 条复习记录",
+                })),
+            }
+        }
+        JobKind::RevlogPrune => {
+            state.mem.prune_revlogs().await.map_err(|e| e.to_string())?;
+            Ok(json!({ "ok": true }))
+        }
+        JobKind::MediaCleanup => {
+            let removed = state
+                .media
+                .cleanup_orphans()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(json!({ "removed": removed }))
+        }
+        JobKind::FaviconFetch => {
+            let host = str_field(&payload, "host")?;
+            fetch_and_cache(state.storage.as_ref(), host).await?;
+            Ok(json!({ "host": host }))
+        }
+        JobKind::BookmarkImport => {
+            let user_id = job.user_id.ok_or("导入任务缺少用户")?;
+            let html = str_field(&payload, "html")?;
+            let result = state
+                .bookmark
+                .import_netscape_html(user_id, html)
+                .await
+                .map_err(|e| e.to_string())?;
+            serde_json::to_value(result).map_err(|e| e.to_string())
+        }
+        JobKind::WebhookDeliver => {
+            let delivery_id = payload
+                .get("delivery_id")
+                .and_then(Value::as_i64)
+                .ok_or("payload 缺少 delivery_id")?;
+            let last_attempt = job.attempts >= job.max_attempts;
+            state.webhook.deliver(delivery_id, last_attempt).await
+        }
+        JobKind::TrashPurge => {
+            let purged = state
+                .trash
+                .purge_expired()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(json!({ "purged": purged }))
+        }
+    }
+}
+
+fn str_field<'a>(payload: &'a Value, key: &str) -> Result<&'a str, String> {
+    payload
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("payload 缺少 {key}"))
+}
@@ -1,4 +1,5 @@
 use axum::{
+    Extension,
     body::Body,
     extract::{Multipart, Path, Query, State},
     http::{StatusCode, header},
@@ -7,26 +8,28 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 /// 删除媒体查询参数
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct DeleteQuery {
     /// 跳过引用检查强制删除
     #[serde(default)]
     pub force: Option<bool>,
 }
-use tokio_util::io::ReaderStream;
-
-use super::service::MediaService;
+use crate::auth::Claims;
 use crate::error;
 use crate::error::ServiceError;
-use crate::pagination::Pagination;
+use crate::openapi::{Binary, FileUpload};
+use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
+use utoipa::{IntoParams, ToSchema};
 
 // ── 响应 ──
 
-#[derive(Serialize)]
-struct MediaResponse {
+#[derive(Serialize, ToSchema)]
+pub(crate) struct MediaResponse {
     stored_id: String,
     url: String,
+    thumb_url: String,
     original_name: String,
     media_type: String,
     mime_type: String,
@@ -41,6 +44,7 @@ fn to_response(m: &super::model::Media) -> MediaResponse {
     MediaResponse {
         stored_id: m.stored_id.clone(),
         url: format!("/api/media/{}/file", m.stored_id),
+        thumb_url: format!("/api/media/{}/thumb", m.stored_id),
         original_name: m.original_name.clone(),
         media_type: m.media_type.as_str().to_string(),
         mime_type: m.mime_type.clone(),
@@ -54,8 +58,16 @@ fn to_response(m: &super::model::Media) -> MediaResponse {
 
 // ── 上传 ──
 
+#[utoipa::path(
+    post,
+    path = "/upload",
+    summary = "上传媒体文件",
+    request_body(content = FileUpload, content_type = "multipart/form-data"),
+    responses((status = 201, description = "已创建", body = MediaResponse))
+)]
 pub async fn upload_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
     let service = &state.media;
@@ -78,7 +90,7 @@ pub async fn upload_handler(
         };
 
         match service
-            .upload(&data, &original_name, &content_type, None)
+            .upload(&data, &original_name, &content_type, claims.sub)
             .await
         {
             Ok(media) => {
@@ -93,14 +105,23 @@ pub async fn upload_handler(
 
 // ── 列表 ──
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListQuery {
     #[serde(default)]
     pub media_type: Option<String>,
     #[serde(flatten)]
+    #[param(ignore)]
     pub pagination: Pagination,
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "媒体列表",
+    params(ListQuery, Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<MediaResponse>))
+)]
 pub async fn list_handler(
     Query(q): Query<ListQuery>,
     State(state): State<AppState>,
@@ -111,13 +132,10 @@ pub async fn list_handler(
     match service.list(&q.pagination, mt).await {
         Ok(response) => {
             let items: Vec<MediaResponse> = response.items.iter().map(to_response).collect();
-            Json(serde_json::json!({
-                "items": items,
-                "total": response.total,
-                "page": response.page,
-                "page_size": response.page_size,
-                "total_pages": response.total_pages,
-            }))
+            Json(
+                PaginatedResponse::new(items, response.total, &q.pagination)
+                    .with_cursor(response.next_cursor),
+            )
             .into_response()
         }
         Err(e) => e.into_response(),
@@ -126,6 +144,12 @@ pub async fn list_handler(
 
 // ── 详情 ──
 
+#[utoipa::path(
+    get,
+    path = "/{stored_id}",
+    summary = "媒体元数据",
+    responses((status = 200, description = "成功", body = MediaResponse))
+)]
 pub async fn get_handler(
     State(state): State<AppState>,
     Path(stored_id): Path<String>,
@@ -140,6 +164,12 @@ pub async fn get_handler(
 
 // ── 文件服务 ──
 
+#[utoipa::path(
+    get,
+    path = "/{stored_id}/file",
+    summary = "读取媒体文件",
+    responses((status = 200, description = "成功", body = Binary, content_type = "application/octet-stream"))
+)]
 pub async fn file_handler(
     State(state): State<AppState>,
     Path(stored_id): Path<String>,
@@ -151,11 +181,10 @@ pub async fn file_handler(
         Err(e) => return e.into_response(),
     };
 
-    let path = MediaService::file_path(media.media_type.as_str(), &stored_id);
-
-    let file = match tokio::fs::File::open(&path).await {
-        Ok(f) => f,
-        Err(_) => return ServiceError::NotFound("文件不存在".into()).into_response(),
+    let body = match service.open(&media).await {
+        Ok(Some(body)) => body,
+        Ok(None) => return ServiceError::NotFound("文件不存在".into()).into_response(),
+        Err(e) => return e.into_response(),
     };
 
     let ct = if media.media_type.as_str() == "image" && media.mime_type != "image/svg+xml" {
@@ -164,9 +193,6 @@ pub async fn file_handler(
         "application/octet-stream".to_string()
     };
 
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
     let mut resp = Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, &ct)
@@ -184,15 +210,55 @@ pub async fn file_handler(
         .expect("ResponseBuilder 使用已知合法的状态码和头，不会失败")
 }
 
+// ── 缩略图 ──
+
+/// 图片缩略图；SVG、非图片等无法生成缩略图时返回原文件
+#[utoipa::path(
+    get,
+    path = "/{stored_id}/thumb",
+    summary = "读取图片缩略图（PNG）",
+    responses((status = 200, description = "成功", body = Binary, content_type = "image/png"))
+)]
+pub async fn thumb_handler(
+    State(state): State<AppState>,
+    Path(stored_id): Path<String>,
+) -> Response {
+    let media = match state.media.get_by_stored_id(&stored_id).await {
+        Ok(Some(m)) => m,
+        Ok(None) => return ServiceError::NotFound("媒体不存在".into()).into_response(),
+        Err(e) => return e.into_response(),
+    };
+    match state.media.thumbnail(&media).await {
+        Ok(Some(thumb)) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            Body::from(thumb),
+        )
+            .into_response(),
+        Ok(None) => file_handler(State(state), Path(stored_id)).await,
+        Err(e) => e.into_response(),
+    }
+}
+
 // ── 重命名 ──
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct RenameRequest {
     pub original_name: String,
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{stored_id}",
+    summary = "重命名",
+    request_body = RenameRequest,
+    responses((status = 200, description = "成功", body = MediaResponse))
+)]
 pub async fn rename_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(stored_id): Path<String>,
     Json(payload): Json<RenameRequest>,
 ) -> impl IntoResponse {
@@ -201,7 +267,7 @@ pub async fn rename_handler(
     }
     let service = &state.media;
     match service
-        .rename(&stored_id, payload.original_name.trim())
+        .rename(claims.sub, &stored_id, payload.original_name.trim())
         .await
     {
         Ok(media) => Json(to_response(&media)).into_response(),
@@ -211,13 +277,24 @@ pub async fn rename_handler(
 
 // ── 删除 ──
 
+#[utoipa::path(
+    delete,
+    path = "/{stored_id}",
+    summary = "删除媒体",
+    params(DeleteQuery),
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(stored_id): Path<String>,
     Query(query): Query<DeleteQuery>,
 ) -> impl IntoResponse {
     let service = &state.media;
-    match service.delete(&stored_id, query.force.unwrap_or(false)).await {
+    match service
+        .delete(claims.sub, &stored_id, query.force.unwrap_or(false))
+        .await
+    {
         Ok(()) => StatusCode::NO_CONTENT.into_response(),
         Err(e) => e.into_response(),
     }
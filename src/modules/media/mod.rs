@@ -3,23 +3,25 @@ pub mod model;
 pub mod repository;
 pub mod service;
 
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
 use crate::state::AppState;
-use axum::Router;
-use axum::routing::{get, post};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/upload", post(handler::upload_handler))
-        .route("/", get(handler::list_handler))
-        .route(
-            "/{stored_id}",
-            get(handler::get_handler)
-                .patch(handler::rename_handler)
-                .delete(handler::delete_handler),
-        )
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::upload_handler))
+        .routes(routes!(handler::list_handler))
+        .routes(routes!(
+            handler::get_handler,
+            handler::rename_handler,
+            handler::delete_handler
+        ))
 }
 
 /// 公开路由：文件服务（markdown 内嵌图片等），无需认证
-pub fn public_file_route() -> Router<AppState> {
-    Router::new().route("/{stored_id}/file", get(handler::file_handler))
+pub fn public_file_route() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::file_handler))
+        .routes(routes!(handler::thumb_handler))
 }
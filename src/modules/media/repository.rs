@@ -1,7 +1,9 @@
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use super::model::{Media, NewMedia};
+use crate::pagination::{Cursor, Window};
 
 #[derive(Debug, FromRow)]
 struct MediaRow {
@@ -92,57 +94,52 @@ impl MediaRepository {
     pub async fn count(&self, media_type: Option<&str>) -> Result<i64, sqlx::Error> {
         match media_type {
             Some(mt) => {
-                sqlx::query_scalar("SELECT COUNT(*) FROM media WHERE media_type = ?")
-                    .bind(mt)
-                    .fetch_one(&*self.db)
-                    .await
+                sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM media WHERE media_type = ? AND deleted_at IS NULL",
+                )
+                .bind(mt)
+                .fetch_one(&*self.db)
+                .await
             }
             None => {
-                sqlx::query_scalar("SELECT COUNT(*) FROM media")
+                sqlx::query_scalar("SELECT COUNT(*) FROM media WHERE deleted_at IS NULL")
                     .fetch_one(&*self.db)
                     .await
             }
         }
     }
 
+    /// 按 `(created_at, id)` 倒序取一页，游标模式附下一页游标
     pub async fn find_all(
         &self,
-        limit: i64,
-        offset: i64,
+        window: &Window,
         media_type: Option<&str>,
-    ) -> Result<Vec<Media>, sqlx::Error> {
-        let rows = match media_type {
-            Some(mt) => {
-                sqlx::query_as::<_, MediaRow>(
-                    "SELECT id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at
-                     FROM media WHERE media_type = ?
-                     ORDER BY created_at DESC LIMIT ? OFFSET ?",
-                )
-                .bind(mt)
-                .bind(limit)
-                .bind(offset)
-                .fetch_all(&*self.db)
-                .await?
-            }
-            None => {
-                sqlx::query_as::<_, MediaRow>(
-                    "SELECT id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at
-                     FROM media
-                     ORDER BY created_at DESC LIMIT ? OFFSET ?",
-                )
-                .bind(limit)
-                .bind(offset)
-                .fetch_all(&*self.db)
-                .await?
-            }
-        };
-        Ok(rows.into_iter().map(Into::into).collect())
+    ) -> Result<(Vec<Media>, Option<Cursor>), sqlx::Error> {
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+            "SELECT id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at
+             FROM media WHERE deleted_at IS NULL",
+        );
+        if let Some(mt) = media_type {
+            qb.push(" AND media_type = ");
+            qb.push_bind(mt);
+        }
+        window.push_after(&mut qb, "created_at", "id", true);
+        qb.push(" ORDER BY created_at DESC, id DESC");
+        window.push_limit(&mut qb);
+
+        let rows = qb.build().fetch_all(&*self.db).await?;
+        let (rows, next) = window.finish(rows, "created_at", "id")?;
+        let items = rows
+            .iter()
+            .map(|r| MediaRow::from_row(r).map(Into::into))
+            .collect::<Result<_, _>>()?;
+        Ok((items, next))
     }
 
     pub async fn find_by_stored_id(&self, stored_id: &str) -> Result<Option<Media>, sqlx::Error> {
         let row = sqlx::query_as::<_, MediaRow>(
             "SELECT id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at
-             FROM media WHERE stored_id = ?",
+             FROM media WHERE stored_id = ? AND deleted_at IS NULL",
         )
         .bind(stored_id)
         .fetch_optional(&*self.db)
@@ -157,7 +154,7 @@ impl MediaRepository {
         new_name: &str,
     ) -> Result<Option<Media>, sqlx::Error> {
         let row = sqlx::query_as::<_, MediaRow>(
-            "UPDATE media SET original_name = ? WHERE stored_id = ?
+            "UPDATE media SET original_name = ? WHERE stored_id = ? AND deleted_at IS NULL
              RETURNING id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at",
         )
         .bind(new_name)
@@ -168,13 +165,48 @@ impl MediaRepository {
         Ok(row.map(Into::into))
     }
 
+    /// 移入回收站（文件保留），返回被删除的记录
     pub async fn delete(&self, stored_id: &str) -> Result<Option<Media>, sqlx::Error> {
-        let existing = self.find_by_stored_id(stored_id).await?;
-        sqlx::query("DELETE FROM media WHERE stored_id = ?")
-            .bind(stored_id)
-            .execute(&*self.db)
+        let row = sqlx::query_as::<_, MediaRow>(
+            "UPDATE media SET deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE stored_id = ? AND deleted_at IS NULL
+             RETURNING id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at",
+        )
+        .bind(stored_id)
+        .fetch_optional(&*self.db)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// 从回收站恢复
+    pub async fn restore(&self, stored_id: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE media SET deleted_at = NULL WHERE stored_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(stored_id)
+        .execute(&*self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 彻底删除回收站中的记录，返回被删除的记录（文件由调用方删除）
+    pub async fn purge(&self, stored_id: &str) -> Result<Option<Media>, sqlx::Error> {
+        let row = sqlx::query_as::<_, MediaRow>(
+            "DELETE FROM media WHERE stored_id = ? AND deleted_at IS NOT NULL
+             RETURNING id, stored_id, original_name, media_type, mime_type, size_bytes, width, height, duration_ms, user_id, created_at",
+        )
+        .bind(stored_id)
+        .fetch_optional(&*self.db)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    /// 全部已登记文件的 stored_id（含回收站中的，其文件在彻底删除前保留）
+    pub async fn all_stored_ids(&self) -> Result<HashSet<String>, sqlx::Error> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT stored_id FROM media")
+            .fetch_all(&*self.db)
             .await?;
-        Ok(existing)
+        Ok(ids.into_iter().collect())
     }
 }
 
@@ -1,14 +1,23 @@
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
+use axum::body::Body;
+use chrono::Utc;
 use sqlx::SqlitePool;
 use tracing::warn;
 
 use super::model::{Media, NewMedia};
 use super::repository::MediaRepository;
 use crate::error::ServiceError;
+use crate::modules::activity::{AuditLog, Entity};
+use crate::modules::events::ChangeFeed;
+use crate::modules::webhook::{WebhookEvent, Webhooks};
 use crate::pagination::{PaginatedResponse, Pagination};
+use crate::storage::Storage;
 
-pub(crate) const UPLOAD_DIR: &str = "uploads";
+/// 缩略图最长边（像素）
+const THUMB_SIZE: u32 = 256;
 
 const ALLOWED_MIMES: &[(&str, &str, u64)] = &[
     // (MIME, media_type, max_size_bytes)
@@ -57,7 +66,7 @@ fn sanitize_name(name: &str) -> String {
 }
 
 /// 获取扩展名对应的目录名
-fn dir_for_type(media_type: &str) -> &str {
+pub(crate) fn dir_for_type(media_type: &str) -> &str {
     match media_type {
         "video" => "video",
         "audio" => "audio",
@@ -65,36 +74,77 @@ fn dir_for_type(media_type: &str) -> &str {
     }
 }
 
+fn thumb_key(stored_id: &str) -> String {
+    format!("thumbs/{stored_id}.png")
+}
+
+/// 等比缩小到 [`THUMB_SIZE`] 以内并编码为 PNG；无法解码时返回 None
+fn make_thumbnail(data: &[u8]) -> Option<Vec<u8>> {
+    let img = image::load_from_memory(data).ok()?;
+    let img = if img.width() > THUMB_SIZE || img.height() > THUMB_SIZE {
+        img.thumbnail(THUMB_SIZE, THUMB_SIZE)
+    } else {
+        img
+    };
+    let mut out = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut out, image::ImageFormat::Png).ok()?;
+    Some(out.into_inner())
+}
+
+/// 未登记文件的宽限期：上传先写文件再插库，刚写入的文件不能当作孤儿
+const ORPHAN_GRACE: Duration = Duration::from_secs(3600);
+
+/// 删除存储中不在 `known` 中、且早于宽限期的媒体文件与缩略图
+async fn remove_orphans(
+    storage: &dyn Storage,
+    known: &HashSet<String>,
+    grace: Duration,
+) -> std::io::Result<usize> {
+    let mut removed = 0;
+    for prefix in ["image/", "video/", "audio/", "thumbs/"] {
+        for object in storage.list(prefix).await? {
+            let name = object.key.rsplit('/').next().unwrap_or_default();
+            let stored_id = name.strip_suffix(".png").filter(|_| prefix == "thumbs/");
+            if known.contains(stored_id.unwrap_or(name)) {
+                continue;
+            }
+            // 取不到修改时间（或时间在未来）时按新文件处理
+            let fresh = object
+                .modified
+                .and_then(|t| (Utc::now() - t).to_std().ok())
+                .is_none_or(|age| age < grace);
+            if fresh {
+                continue;
+            }
+            match storage.delete(&object.key).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("删除孤儿文件失败 {}: {}", object.key, e),
+            }
+        }
+    }
+    Ok(removed)
+}
+
 #[derive(Clone)]
 pub struct MediaService {
     repo: MediaRepository,
+    storage: Arc<dyn Storage>,
+    audit: AuditLog,
+    hooks: Webhooks,
 }
 
 impl MediaService {
-    pub fn new(db: Arc<SqlitePool>) -> Self {
-        // 确保上传子目录存在
-        for d in &["image", "video", "audio"] {
-            std::fs::create_dir_all(format!("{}/{}", UPLOAD_DIR, d)).ok();
-        }
-        // 清理孤儿临时文件
-        Self::cleanup_temp_files();
+    pub fn new(
+        db: Arc<SqlitePool>,
+        storage: Arc<dyn Storage>,
+        hooks: Webhooks,
+        changes: ChangeFeed,
+    ) -> Self {
         Self {
-            repo: MediaRepository::new(db),
-        }
-    }
-
-    fn cleanup_temp_files() {
-        let dirs = ["image", "video", "audio"];
-        for d in &dirs {
-            let path = format!("{}/{}", UPLOAD_DIR, d);
-            if let Ok(entries) = std::fs::read_dir(&path) {
-                for entry in entries.flatten() {
-                    let name = entry.file_name().to_string_lossy().to_string();
-                    if name.starts_with("tmp_") && name.ends_with(".tmp") {
-                        let _ = std::fs::remove_file(entry.path());
-                    }
-                }
-            }
+            repo: MediaRepository::new(db.clone()),
+            storage,
+            audit: AuditLog::new(db, changes),
+            hooks,
         }
     }
 
@@ -103,13 +153,13 @@ impl MediaService {
         infer::get(data).map(|t| t.mime_type().to_string())
     }
 
-    /// 上传：校验 → 写临时文件 → 插库 → 原子 rename → 解析元数据
+    /// 上传：校验 → 写入存储 → 插库 → 解析元数据。上传者即所有者
     pub async fn upload(
         &self,
         data: &[u8],
         original_name: &str,
         client_mime: &str,
-        user_id: Option<i64>,
+        actor: i32,
     ) -> Result<Media, ServiceError> {
         // 1. MIME 真实校验
         let real_mime = Self::detect_mime(data)
@@ -137,12 +187,11 @@ impl MediaService {
 
         let safe_name = sanitize_name(original_name);
         let stored_id = generate_stored_id();
-        let dir = dir_for_type(media_type_str);
-        let tmp_path = format!("{}/{}/tmp_{}.tmp", UPLOAD_DIR, dir, stored_id);
-        let final_path = format!("{}/{}/{}", UPLOAD_DIR, dir, stored_id);
+        let key = Self::key(media_type_str, &stored_id);
 
-        // 3. 写临时文件
-        tokio::fs::write(&tmp_path, data)
+        // 3. 写入存储（插库失败时删除；进程中断留下的文件由孤儿清理回收）
+        self.storage
+            .put(&key, data)
             .await
             .map_err(|e| ServiceError::Internal(format!("写入文件失败: {}", e)))?;
 
@@ -158,23 +207,18 @@ impl MediaService {
                 width: None,
                 height: None,
                 duration_ms: None,
-                user_id,
+                user_id: Some(actor as i64),
             })
             .await
         {
             Ok(m) => m,
             Err(e) => {
-                let _ = tokio::fs::remove_file(&tmp_path).await;
+                let _ = self.storage.delete(&key).await;
                 return Err(ServiceError::Db(e));
             }
         };
 
-        // 5. 原子 rename
-        if let Err(e) = std::fs::rename(&tmp_path, &final_path) {
-            warn!("rename 失败 stored_id={}: {}", stored_id, e);
-        }
-
-        // 6. 元数据解析（非阻塞）
+        // 5. 元数据解析（非阻塞）
         let (width, height, duration_ms) = Self::extract_metadata(media_type_str, &real_mime, data);
         if width.is_some() || height.is_some() || duration_ms.is_some() {
             let _ = self
@@ -183,12 +227,19 @@ impl MediaService {
                 .await;
         }
 
-        Ok(Media {
+        let media = Media {
             width,
             height,
             duration_ms,
             ..media
-        })
+        };
+        self.audit
+            .created(actor, Entity::Media, &media.stored_id, &media)
+            .await;
+        self.hooks
+            .emit(actor, WebhookEvent::MediaUploaded, &media)
+            .await;
+        Ok(media)
     }
 
     fn extract_metadata(
@@ -222,12 +273,12 @@ impl MediaService {
             .count(media_type)
             .await
             .map_err(ServiceError::Db)?;
-        let items = self
+        let (items, next) = self
             .repo
-            .find_all(pagination.limit(), pagination.offset(), media_type)
+            .find_all(&pagination.window(), media_type)
             .await
             .map_err(ServiceError::Db)?;
-        Ok(PaginatedResponse::new(items, total, pagination))
+        Ok(PaginatedResponse::new(items, total, pagination).with_cursor(next))
     }
 
     pub async fn get_by_stored_id(&self, stored_id: &str) -> Result<Option<Media>, ServiceError> {
@@ -237,16 +288,34 @@ impl MediaService {
             .map_err(ServiceError::Db)
     }
 
-    pub async fn rename(&self, stored_id: &str, new_name: &str) -> Result<Media, ServiceError> {
+    pub async fn rename(
+        &self,
+        actor: i32,
+        stored_id: &str,
+        new_name: &str,
+    ) -> Result<Media, ServiceError> {
         let safe = sanitize_name(new_name);
-        self.repo
+        let before = self.get_by_stored_id(stored_id).await?;
+        let media = self
+            .repo
             .update_name(stored_id, &safe)
             .await
             .map_err(ServiceError::Db)?
-            .ok_or_else(|| ServiceError::NotFound("媒体不存在".into()))
+            .ok_or_else(|| ServiceError::NotFound("媒体不存在".into()))?;
+        if let Some(before) = before {
+            self.audit
+                .updated(actor, Entity::Media, stored_id, &before, &media)
+                .await;
+        }
+        Ok(media)
     }
 
-    pub async fn delete(&self, stored_id: &str, force: bool) -> Result<(), ServiceError> {
+    pub async fn delete(
+        &self,
+        actor: i32,
+        stored_id: &str,
+        force: bool,
+    ) -> Result<(), ServiceError> {
         // 删除前检查引用：图片仍被内容使用时拒绝（除非 force）
         let refs = self
             .repo
@@ -259,23 +328,97 @@ impl MediaService {
             )));
         }
 
+        // 移入回收站：文件保留到彻底删除
         let media = self
             .repo
             .delete(stored_id)
             .await
             .map_err(ServiceError::Db)?
             .ok_or_else(|| ServiceError::NotFound("媒体不存在".into()))?;
-        let dir = dir_for_type(media.media_type.as_str());
-        let path = format!("{}/{}/{}", UPLOAD_DIR, dir, stored_id);
-        if let Err(e) = std::fs::remove_file(&path) {
-            warn!("删除文件失败 stored_id={}: {}", stored_id, e);
-        }
+        self.audit
+            .deleted(actor, Entity::Media, stored_id, &media)
+            .await;
         Ok(())
     }
 
-    /// 文件路径
-    pub fn file_path(media_type: &str, stored_id: &str) -> String {
-        format!("{}/{}/{}", UPLOAD_DIR, dir_for_type(media_type), stored_id)
+    /// 从回收站恢复，返回是否恢复了记录
+    pub async fn restore(&self, actor: i32, stored_id: &str) -> Result<bool, ServiceError> {
+        let rows = self
+            .repo
+            .restore(stored_id)
+            .await
+            .map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.restored(actor, Entity::Media, stored_id).await;
+        }
+        Ok(rows > 0)
+    }
+
+    /// 彻底删除回收站中的媒体及其文件、缩略图，返回是否删除了记录
+    pub async fn purge(&self, actor: i32, stored_id: &str) -> Result<bool, ServiceError> {
+        let Some(media) = self.repo.purge(stored_id).await.map_err(ServiceError::Db)? else {
+            return Ok(false);
+        };
+        let key = Self::key(media.media_type.as_str(), stored_id);
+        for key in [key, thumb_key(stored_id)] {
+            if let Err(e) = self.storage.delete(&key).await {
+                warn!("删除文件失败 {}: {}", key, e);
+            }
+        }
+        self.audit.purged(actor, Entity::Media, stored_id).await;
+        Ok(true)
+    }
+
+    /// 删除存储中没有数据库记录的文件（含中断写入留下的临时文件），返回删除个数
+    pub async fn cleanup_orphans(&self) -> Result<usize, ServiceError> {
+        let known = self.repo.all_stored_ids().await?;
+        remove_orphans(self.storage.as_ref(), &known, ORPHAN_GRACE)
+            .await
+            .map_err(|e| ServiceError::Internal(format!("清理孤儿文件失败: {e}")))
+    }
+
+    /// 以流的形式读取文件；存储中不存在时返回 None
+    pub async fn open(&self, media: &Media) -> Result<Option<Body>, ServiceError> {
+        self.storage
+            .stream(&Self::key(media.media_type.as_str(), &media.stored_id))
+            .await
+            .map_err(|e| ServiceError::Internal(format!("读取文件失败: {e}")))
+    }
+
+    /// 图片缩略图（PNG），首次请求时生成并写入存储。
+    /// SVG、非图片或无法解码的图片返回 None，由调用方回退为原文件
+    pub async fn thumbnail(&self, media: &Media) -> Result<Option<Vec<u8>>, ServiceError> {
+        if media.media_type.as_str() != "image" || media.mime_type == "image/svg+xml" {
+            return Ok(None);
+        }
+        let read_err = |e: std::io::Error| ServiceError::Internal(format!("读取文件失败: {e}"));
+        let key = thumb_key(&media.stored_id);
+        if let Some(thumb) = self.storage.get(&key).await.map_err(read_err)? {
+            return Ok(Some(thumb));
+        }
+
+        let Some(original) = self
+            .storage
+            .get(&Self::key("image", &media.stored_id))
+            .await
+            .map_err(read_err)?
+        else {
+            return Ok(None);
+        };
+        let thumb = tokio::task::spawn_blocking(move || make_thumbnail(&original))
+            .await
+            .map_err(|e| ServiceError::Internal(format!("生成缩略图失败: {e}")))?;
+        if let Some(thumb) = &thumb
+            && let Err(e) = self.storage.put(&key, thumb).await
+        {
+            warn!("缓存缩略图失败 {}: {}", key, e);
+        }
+        Ok(thumb)
+    }
+
+    /// 存储 key：`{image,video,audio}/{stored_id}`
+    pub fn key(media_type: &str, stored_id: &str) -> String {
+        format!("{}/{}", dir_for_type(media_type), stored_id)
     }
 }
 
@@ -414,23 +557,145 @@ mod tests {
         assert_eq!(ids.len(), 100);
     }
 
-    // ── MediaService::file_path (静态方法) ──
+    // ── MediaService::key (静态方法) ──
 
     #[test]
-    fn file_path_image() {
-        let path = MediaService::file_path("image", "abc123");
-        assert_eq!(path, "uploads/image/abc123");
+    fn key_image() {
+        assert_eq!(MediaService::key("image", "abc123"), "image/abc123");
     }
 
     #[test]
-    fn file_path_video() {
-        let path = MediaService::file_path("video", "vid456");
-        assert_eq!(path, "uploads/video/vid456");
+    fn key_video() {
+        assert_eq!(MediaService::key("video", "vid456"), "video/vid456");
     }
 
     #[test]
-    fn file_path_audio() {
-        let path = MediaService::file_path("audio", "aud789");
-        assert_eq!(path, "uploads/audio/aud789");
+    fn key_audio() {
+        assert_eq!(MediaService::key("audio", "aud789"), "audio/aud789");
+    }
+
+    // ── remove_orphans ──
+
+    #[tokio::test]
+    async fn remove_orphans_keeps_known_and_fresh_files() {
+        let root = std::env::temp_dir().join(format!("brainbow-media-{}", uuid::Uuid::new_v4()));
+        let storage = crate::storage::LocalStorage::new(&root);
+        for key in [
+            "image/known",
+            "image/orphan",
+            "thumbs/known.png",
+            "thumbs/orphan.png",
+        ] {
+            storage.put(key, b"x").await.unwrap();
+        }
+        // 写入中断留下的临时文件
+        std::fs::create_dir_all(root.join("audio")).unwrap();
+        std::fs::write(root.join("audio/tmp_abc.tmp"), b"x").unwrap();
+        let known: HashSet<String> = ["known".to_string()].into();
+
+        // 宽限期内一个都不删
+        let grace = Duration::from_secs(3600);
+        assert_eq!(remove_orphans(&storage, &known, grace).await.unwrap(), 0);
+        assert_eq!(
+            remove_orphans(&storage, &known, Duration::ZERO)
+                .await
+                .unwrap(),
+            3
+        );
+        assert!(root.join("image/known").exists());
+        assert!(root.join("thumbs/known.png").exists());
+        assert!(!root.join("image/orphan").exists());
+        assert!(!root.join("thumbs/orphan.png").exists());
+        assert!(!root.join("audio/tmp_abc.tmp").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_keeps_file_until_purge() {
+        let root = std::env::temp_dir().join(format!("brainbow-media-{}", uuid::Uuid::new_v4()));
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (id, name, password_hash) VALUES (1, 'u', 'x')")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        let hooks = Webhooks::new(
+            pool.clone(),
+            crate::modules::job::JobService::new(pool.clone()),
+        );
+        let storage = Arc::new(crate::storage::LocalStorage::new(&root));
+        let svc = MediaService::new(pool, storage, hooks, ChangeFeed::default());
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(4, 4)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let media = svc
+            .upload(png.get_ref(), "a.png", "image/png", 1)
+            .await
+            .unwrap();
+        let file = root.join("image").join(&media.stored_id);
+
+        // 未删除的媒体不能恢复或彻底删除
+        assert!(!svc.restore(1, &media.stored_id).await.unwrap());
+        assert!(!svc.purge(1, &media.stored_id).await.unwrap());
+
+        svc.delete(1, &media.stored_id, false).await.unwrap();
+        assert!(
+            svc.get_by_stored_id(&media.stored_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(file.exists());
+        // 回收站中的文件不算孤儿
+        assert!(
+            svc.repo
+                .all_stored_ids()
+                .await
+                .unwrap()
+                .contains(&media.stored_id)
+        );
+
+        assert!(svc.restore(1, &media.stored_id).await.unwrap());
+        assert!(
+            svc.get_by_stored_id(&media.stored_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        svc.delete(1, &media.stored_id, false).await.unwrap();
+        assert!(svc.purge(1, &media.stored_id).await.unwrap());
+        assert!(!file.exists());
+        assert!(!svc.restore(1, &media.stored_id).await.unwrap());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    // ── make_thumbnail ──
+
+    #[test]
+    fn thumbnail_fits_within_bounds() {
+        let img = image::DynamicImage::new_rgb8(1024, 512);
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).unwrap();
+
+        let thumb = make_thumbnail(png.get_ref()).unwrap();
+        let thumb = image::load_from_memory(&thumb).unwrap();
+        assert_eq!(
+            (thumb.width(), thumb.height()),
+            (THUMB_SIZE, THUMB_SIZE / 2)
+        );
+
+        // 小图不放大
+        let small = image::DynamicImage::new_rgb8(10, 20);
+        let mut png = std::io::Cursor::new(Vec::new());
+        small.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        let thumb = image::load_from_memory(&make_thumbnail(png.get_ref()).unwrap()).unwrap();
+        assert_eq!((thumb.width(), thumb.height()), (10, 20));
+
+        assert!(make_thumbnail(b"not an image").is_none());
     }
 }
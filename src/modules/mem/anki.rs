@@ -0,0 +1,1395 @@
+//! Anki `.apkg` / `.colpkg` 导入。
+//!
+//! 包是一个 zip，媒体文件以编号为条目名存放，`meta` 条目（protobuf）标明格式版本：
+//! - 旧格式：`collection.anki21`（更早的版本为 `collection.anki2`）是 SQLite 数据库，
+//!   `media` 是「条目名 → 文件名」的 JSON；
+//! - 新格式（新版 Anki 的默认导出）：`collection.anki21b`、`media` 清单与媒体文件都经 zstd 压缩，
+//!   清单是 protobuf 的 `MediaEntries`，第 i 项对应条目 `i`。集合为 schema 18，
+//!   笔记类型、字段、模板与卡组存放在各自的表中，配置同样是 protobuf。
+//!
+//! 每张卡片成为一个 mem：问题模板渲染为 cue，答案模板去掉 `{{FrontSide}}` 后渲染为 target，
+//! HTML 转为 markdown。卡组全名与笔记标签成为 tag；调度状态换算为 FSRS 记忆状态，
+//! 复习记录写入 revlog，可直接用于参数优化。
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use utoipa::ToSchema;
+
+use super::fsrs;
+use super::model::{RevlogEntry, ScheduledMem};
+use crate::archive;
+use crate::error::ServiceError;
+
+/// Anki 新建集合时自带的「Default」卡组，不作为标签导入
+const DEFAULT_DECK_ID: i64 = 1;
+
+/// 单个媒体文件或 media 清单解压后的大小上限
+const MAX_ENTRY_SIZE: u64 = 1 << 30;
+
+/// 导入结果
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct AnkiImportReport {
+    /// 导入的卡片数
+    pub imported: usize,
+    /// 导入的复习记录条数
+    pub reviews: usize,
+    /// 上传的媒体文件数
+    pub media_files: usize,
+    /// 跳过的卡片与媒体
+    pub errors: Vec<String>,
+}
+
+/// 媒体文件导入后的访问地址
+pub fn media_url(stored_id: &str) -> String {
+    format!("/api/media/{stored_id}/file")
+}
+
+#[derive(Deserialize)]
+struct Model {
+    name: String,
+    /// 0 = 标准，1 = 填空
+    #[serde(rename = "type", default)]
+    kind: i64,
+    flds: Vec<Field>,
+    tmpls: Vec<Template>,
+}
+
+#[derive(Deserialize)]
+struct Field {
+    name: String,
+    #[serde(default)]
+    ord: usize,
+}
+
+#[derive(Deserialize)]
+struct Template {
+    name: String,
+    #[serde(default)]
+    ord: usize,
+    qfmt: String,
+    afmt: String,
+}
+
+#[derive(Deserialize)]
+struct Deck {
+    name: String,
+}
+
+struct Note {
+    model_id: i64,
+    tags: Vec<String>,
+    fields: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct Card {
+    id: i64,
+    nid: i64,
+    did: i64,
+    ord: i64,
+    /// 0 新卡，1 学习，2 复习，3 重学
+    #[sqlx(rename = "type")]
+    kind: i64,
+    /// -3 / -2 搁置，-1 暂停，其余同 type
+    queue: i64,
+    due: i64,
+    ivl: i64,
+    factor: i64,
+    lapses: i64,
+    /// 在筛选卡组中时为原卡组与原到期
+    odid: i64,
+    odue: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct Review {
+    /// 复习时刻（毫秒时间戳）
+    id: i64,
+    cid: i64,
+    /// 1–4，与本程序的评分一致；0 为手动调整
+    ease: i64,
+    /// 0 学习，1 复习，2 重学，3 筛选卡组，4 手动调整
+    #[sqlx(rename = "type")]
+    kind: i64,
+}
+
+// ── 新格式中的 protobuf 消息，只声明用到的字段，其余字段解码时跳过 ──
+
+/// `meta` 条目
+#[derive(Clone, PartialEq, Message)]
+struct PackageMetadata {
+    #[prost(int32, tag = "1")]
+    version: i32,
+}
+
+/// `media` 条目
+#[derive(Clone, PartialEq, Message)]
+struct MediaEntries {
+    #[prost(message, repeated, tag = "1")]
+    entries: Vec<MediaEntry>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MediaEntry {
+    #[prost(string, tag = "1")]
+    name: String,
+    /// 从旧格式转换来的包沿用原来的条目名
+    #[prost(uint32, optional, tag = "255")]
+    legacy_zip_filename: Option<u32>,
+}
+
+/// `notetypes.config`
+#[derive(Clone, PartialEq, Message)]
+struct NotetypeConfig {
+    /// 0 = 标准，1 = 填空
+    #[prost(int32, tag = "1")]
+    kind: i32,
+}
+
+/// `templates.config`
+#[derive(Clone, PartialEq, Message)]
+struct TemplateConfig {
+    #[prost(string, tag = "1")]
+    q_format: String,
+    #[prost(string, tag = "2")]
+    a_format: String,
+}
+
+/// 包格式版本，决定集合的条目名以及各条目是否经 zstd 压缩
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Version {
+    Legacy1,
+    Legacy2,
+    Latest,
+}
+
+impl Version {
+    fn collection(self) -> &'static str {
+        match self {
+            Self::Legacy1 => "collection.anki2",
+            Self::Legacy2 => "collection.anki21",
+            Self::Latest => "collection.anki21b",
+        }
+    }
+
+    fn zstd(self) -> bool {
+        self == Self::Latest
+    }
+}
+
+/// 包中的媒体文件，按需读出
+struct Media {
+    zip: Arc<Mutex<archive::Reader>>,
+    /// 文件名 → 包内条目名
+    entries: HashMap<String, String>,
+    zstd: bool,
+}
+
+/// 读出的 Anki 集合与媒体
+pub struct Package {
+    /// 集合创建时刻（秒），复习卡的到期日以此为第 0 天
+    crt: i64,
+    models: HashMap<i64, Model>,
+    decks: HashMap<i64, String>,
+    notes: HashMap<i64, Note>,
+    cards: Vec<Card>,
+    /// 卡片 id → 按时间排序的复习记录
+    reviews: HashMap<i64, Vec<Review>>,
+    media: Media,
+}
+
+impl Package {
+    /// 读取包文件。集合数据库解压到临时文件供 SQLite 打开，离开作用域时删除
+    pub async fn read(file: File) -> Result<Self, ServiceError> {
+        let (collection, media) = tokio::task::spawn_blocking(move || unpack(file))
+            .await
+            .map_err(|e| ServiceError::Internal(format!("解析任务异常: {e}")))??;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(collection.path())
+                    .read_only(true),
+            )
+            .await
+            .map_err(|e| ServiceError::InvalidInput(format!("无法打开 Anki 集合: {e}")))?;
+        let result = Self::load(&pool, media).await;
+        pool.close().await;
+        result.map_err(|e| ServiceError::InvalidInput(format!("读取 Anki 集合失败: {e}")))
+    }
+
+    async fn load(
+        pool: &sqlx::SqlitePool,
+        media: Media,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let crt: i64 = sqlx::query_scalar("SELECT crt FROM col")
+            .fetch_one(pool)
+            .await?;
+        let split: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'notetypes')",
+        )
+        .fetch_one(pool)
+        .await?;
+        let (models, decks) = if split {
+            Self::load_tables(pool).await?
+        } else {
+            Self::load_json(pool).await?
+        };
+
+        let notes = sqlx::query_as::<_, (i64, i64, String, String)>(
+            "SELECT id, mid, tags, flds FROM notes",
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(id, model_id, tags, flds)| {
+            let note = Note {
+                model_id,
+                tags: tags.split_whitespace().map(String::from).collect(),
+                fields: flds.split('\x1f').map(String::from).collect(),
+            };
+            (id, note)
+        })
+        .collect();
+
+        let cards = sqlx::query_as::<_, Card>(
+            "SELECT id, nid, did, ord, type, queue, due, ivl, factor, lapses, odid, odue
+             FROM cards ORDER BY nid, ord",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut reviews: HashMap<i64, Vec<Review>> = HashMap::new();
+        for r in sqlx::query_as::<_, Review>("SELECT id, cid, ease, type FROM revlog ORDER BY id")
+            .fetch_all(pool)
+            .await?
+        {
+            reviews.entry(r.cid).or_default().push(r);
+        }
+
+        Ok(Self {
+            crt,
+            models,
+            decks,
+            notes,
+            cards,
+            reviews,
+            media,
+        })
+    }
+
+    /// schema 18 之前：笔记类型与卡组以 JSON 存放在 col 表中
+    async fn load_json(
+        pool: &sqlx::SqlitePool,
+    ) -> Result<(HashMap<i64, Model>, HashMap<i64, String>), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let (models, decks): (String, String) = sqlx::query_as("SELECT models, decks FROM col")
+            .fetch_one(pool)
+            .await?;
+        let models = serde_json::from_str::<HashMap<String, Model>>(&models)?
+            .into_iter()
+            .filter_map(|(id, m)| Some((id.parse().ok()?, m)))
+            .collect();
+        let decks = serde_json::from_str::<HashMap<String, Deck>>(&decks)?
+            .into_iter()
+            .filter_map(|(id, d)| Some((id.parse().ok()?, d.name)))
+            .collect();
+        Ok((models, decks))
+    }
+
+    /// schema 18：笔记类型、字段、模板与卡组各有一张表，卡组全名以 `\x1f` 分隔层级
+    async fn load_tables(
+        pool: &sqlx::SqlitePool,
+    ) -> Result<(HashMap<i64, Model>, HashMap<i64, String>), Box<dyn std::error::Error + Send + Sync>>
+    {
+        let mut models = HashMap::new();
+        for (id, name, config) in
+            sqlx::query_as::<_, (i64, String, Vec<u8>)>("SELECT id, name, config FROM notetypes")
+                .fetch_all(pool)
+                .await?
+        {
+            let config = NotetypeConfig::decode(config.as_slice())?;
+            let model = Model {
+                name,
+                kind: config.kind.into(),
+                flds: Vec::new(),
+                tmpls: Vec::new(),
+            };
+            models.insert(id, model);
+        }
+        for (ntid, ord, name) in sqlx::query_as::<_, (i64, i64, String)>(
+            "SELECT ntid, ord, name FROM fields ORDER BY ntid, ord",
+        )
+        .fetch_all(pool)
+        .await?
+        {
+            if let Some(model) = models.get_mut(&ntid) {
+                let ord = usize::try_from(ord)?;
+                model.flds.push(Field { name, ord });
+            }
+        }
+        for (ntid, ord, name, config) in sqlx::query_as::<_, (i64, i64, String, Vec<u8>)>(
+            "SELECT ntid, ord, name, config FROM templates ORDER BY ntid, ord",
+        )
+        .fetch_all(pool)
+        .await?
+        {
+            if let Some(model) = models.get_mut(&ntid) {
+                let config = TemplateConfig::decode(config.as_slice())?;
+                model.tmpls.push(Template {
+                    name,
+                    ord: usize::try_from(ord)?,
+                    qfmt: config.q_format,
+                    afmt: config.a_format,
+                });
+            }
+        }
+
+        let decks = sqlx::query_as::<_, (i64, String)>("SELECT id, name FROM decks")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|(id, name)| (id, name.replace('\x1f', "::")))
+            .collect();
+        Ok((models, decks))
+    }
+
+    /// 卡片用到、且包中存在的媒体文件名（排序）
+    pub fn referenced_media(&self) -> Vec<&str> {
+        let mut names = HashSet::new();
+        let mut collect = |html: &str| {
+            html_to_markdown(html, &mut |name| {
+                names.insert(name.to_string());
+                None
+            });
+        };
+        for note in self.notes.values() {
+            note.fields.iter().for_each(|f| collect(f));
+        }
+        for t in self.models.values().flat_map(|m| &m.tmpls) {
+            collect(&t.qfmt);
+            collect(&t.afmt);
+        }
+        let mut media: Vec<_> = self
+            .media
+            .entries
+            .keys()
+            .filter(|name| names.contains(name.as_str()))
+            .map(String::as_str)
+            .collect();
+        media.sort_unstable();
+        media
+    }
+
+    /// 读出一个媒体文件的内容
+    pub async fn media(&self, name: &str) -> Result<Vec<u8>, ServiceError> {
+        let entry = self
+            .media
+            .entries
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ServiceError::NotFound(format!("媒体 {name} 不存在")))?;
+        let (zip, zstd) = (self.media.zip.clone(), self.media.zstd);
+        tokio::task::spawn_blocking(move || {
+            let mut zip = zip
+                .lock()
+                .map_err(|_| ServiceError::Internal("归档锁已损坏".into()))?;
+            read_entry(&mut zip, &entry, zstd)
+                .map_err(|e| ServiceError::InvalidInput(format!("读取 {entry} 失败: {e}")))?
+                .ok_or_else(|| ServiceError::InvalidInput(format!("缺少条目 {entry}")))
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(format!("读取任务异常: {e}")))?
+    }
+
+    /// 换算为待导入的 mem。`urls` 为媒体文件名 → 导入后的地址，
+    /// `params` 为该用户的 FSRS 参数；返回值第二项为跳过的卡片
+    pub fn to_mems(
+        &self,
+        urls: &HashMap<String, String>,
+        params: &[f32],
+        now: DateTime<Utc>,
+    ) -> (Vec<ScheduledMem>, Vec<String>) {
+        let mut mems = Vec::with_capacity(self.cards.len());
+        let mut errors = Vec::new();
+        for card in &self.cards {
+            let Some(note) = self.notes.get(&card.nid) else {
+                errors.push(format!("卡片 {}: 笔记不存在", card.id));
+                continue;
+            };
+            let Some(model) = self.models.get(&note.model_id) else {
+                errors.push(format!("卡片 {}: 笔记类型不存在", card.id));
+                continue;
+            };
+            let deck_id = if card.odid != 0 { card.odid } else { card.did };
+            let deck = self.decks.get(&deck_id).map_or("", String::as_str);
+            let Some((question, answer)) = render_card(model, note, card.ord, deck) else {
+                errors.push(format!("卡片 {}: 找不到卡片模板", card.id));
+                continue;
+            };
+            let mut url = |name: &str| urls.get(name).cloned();
+            let cue = html_to_markdown(&question, &mut url);
+            let target = html_to_markdown(&answer, &mut url);
+            if cue.is_empty() || target.is_empty() {
+                errors.push(format!("卡片 {}: 问题或答案为空", card.id));
+                continue;
+            }
+
+            let mut tags = Vec::new();
+            if deck_id != DEFAULT_DECK_ID && !deck.is_empty() {
+                tags.push(deck.to_string());
+            }
+            for t in &note.tags {
+                if !tags.contains(t) {
+                    tags.push(t.clone());
+                }
+            }
+            let leeched = note.tags.iter().any(|t| t.eq_ignore_ascii_case("leech"));
+            let reviews = self.reviews.get(&card.id).map_or(&[][..], Vec::as_slice);
+            mems.push(ScheduledMem {
+                cue,
+                target,
+                tags,
+                leeched,
+                ..self.schedule(card, reviews, params, now)
+            });
+        }
+        (mems, errors)
+    }
+
+    /// 卡片的调度状态与复习记录（cue / target / tags 留空）
+    fn schedule(
+        &self,
+        card: &Card,
+        reviews: &[Review],
+        params: &[f32],
+        now: DateTime<Utc>,
+    ) -> ScheduledMem {
+        let state = match card.kind {
+            1 => "learning",
+            2 => "review",
+            3 => "relearning",
+            _ => "new",
+        };
+        let due = if card.odid != 0 { card.odue } else { card.due };
+        let day = |n: i64| DateTime::from_timestamp(self.crt, 0).unwrap_or(now) + Duration::days(n);
+        let due_at = match state {
+            "new" => now,
+            // 学习中的卡 due 为秒级时间戳，跨天的学习步进为天数
+            "learning" | "relearning" if due > 1_000_000_000 => {
+                DateTime::from_timestamp(due, 0).unwrap_or(now)
+            }
+            _ => day(due),
+        };
+
+        // 手动调整（改期、重置）不是真实的回忆
+        let kept: Vec<&Review> = reviews
+            .iter()
+            .filter(|r| (1..=4).contains(&r.ease) && r.kind != 4)
+            .collect();
+        let times: Vec<DateTime<Utc>> = kept
+            .iter()
+            .map(|r| DateTime::from_timestamp_millis(r.id).unwrap_or(now))
+            .collect();
+        let deltas: Vec<u32> = times
+            .iter()
+            .enumerate()
+            .map(|(i, t)| match i {
+                0 => 0,
+                _ => ((*t - times[i - 1]).num_seconds().max(0) / 86400) as u32,
+            })
+            .collect();
+        // 有从首次学习开始的完整历史时按历史重放，否则由 SM-2 的间隔与难度系数近似
+        let history = kept.first().filter(|r| r.kind == 0).and_then(|_| {
+            let items: Vec<(u8, u32)> = kept
+                .iter()
+                .zip(&deltas)
+                .map(|(r, &d)| (r.ease as u8, d))
+                .collect();
+            fsrs::replay_history(params, &items)
+        });
+        let (stability, difficulty) = match (&history, state) {
+            (_, "new") => (0.0, 0.0),
+            (Some(states), _) => states.last().copied().unwrap_or((0.0, 0.0)),
+            (None, "review" | "relearning") => {
+                let ease = if card.factor > 0 {
+                    card.factor as f64 / 1000.0
+                } else {
+                    2.5
+                };
+                fsrs::memory_from_sm2(params, ease, card.ivl.max(1) as f64).unwrap_or((0.0, 0.0))
+            }
+            (None, _) => (0.0, 0.0),
+        };
+
+        let review_state = |r: &Review, first: bool| match r.kind {
+            0 if first => "new",
+            0 => "learning",
+            2 => "relearning",
+            _ => "review",
+        };
+        let revlog = kept
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let memory = |j: usize| history.as_ref().and_then(|h| h.get(j)).copied();
+                let before = i.checked_sub(1).and_then(memory);
+                let after = memory(i);
+                let state_after = match kept.get(i + 1) {
+                    Some(next) => review_state(next, false),
+                    None => state,
+                };
+                RevlogEntry {
+                    review_time: rfc3339(times[i]),
+                    rating: r.ease as u8,
+                    delta_t: deltas[i] as i32,
+                    stability_before: before.map(|m| m.0),
+                    difficulty_before: before.map(|m| m.1),
+                    state_before: Some(review_state(r, i == 0).to_string()),
+                    stability_after: after.map(|m| m.0),
+                    difficulty_after: after.map(|m| m.1),
+                    state_after: Some(state_after.to_string()),
+                }
+            })
+            .collect();
+
+        let last_review_at = match times.last() {
+            Some(t) => Some(*t),
+            None if state == "review" => Some(due_at - Duration::days(card.ivl.max(0))),
+            None => None,
+        };
+        ScheduledMem {
+            id: 0,
+            cue: String::new(),
+            target: String::new(),
+            state: if card.queue == -1 { "suspended" } else { state }.to_string(),
+            stability,
+            difficulty,
+            step_index: matches!(state, "learning" | "relearning").then_some(0),
+            buried: matches!(card.queue, -2 | -3),
+            lapses: card.lapses as i32,
+            leeched: false,
+            due_at: rfc3339(due_at),
+            last_review_at: last_review_at.map(rfc3339),
+            tags: Vec::new(),
+            mnemonic: None,
+            prerequisites: Vec::new(),
+            revlog,
+        }
+    }
+}
+
+fn rfc3339(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// 把集合数据库解压到临时文件，并读出媒体清单（只保留包中确实存在的条目）
+fn unpack(file: File) -> Result<(tempfile::NamedTempFile, Media), ServiceError> {
+    let bad = |msg: String| ServiceError::InvalidInput(msg);
+    let mut zip = archive::open(file).map_err(|e| bad(format!("无法读取 Anki 包: {e}")))?;
+    let has = |zip: &archive::Reader, name: &str| zip.index_for_name(name).is_some();
+
+    // 新格式的包里另有一个只含提示卡片的 collection.anki2，版本以 meta 为准
+    let version = match archive::read(&mut zip, "meta", MAX_ENTRY_SIZE)
+        .map_err(|e| bad(format!("读取 meta 失败: {e}")))?
+    {
+        Some(data) => {
+            let meta = PackageMetadata::decode(data.as_slice())
+                .map_err(|e| bad(format!("meta 无效: {e}")))?;
+            match meta.version {
+                1 => Version::Legacy1,
+                2 => Version::Legacy2,
+                3 => Version::Latest,
+                v => return Err(bad(format!("不支持的 Anki 包版本 {v}，请升级程序"))),
+            }
+        }
+        None if has(&zip, "collection.anki21") => Version::Legacy2,
+        None => Version::Legacy1,
+    };
+    let zstd = version.zstd();
+
+    let name = version.collection();
+    if !has(&zip, name) {
+        return Err(bad(format!("不是 Anki 包：缺少 {name}")));
+    }
+    let mut collection = tempfile::NamedTempFile::new()
+        .map_err(|e| ServiceError::Internal(format!("创建临时文件失败: {e}")))?;
+    let copied = if zstd {
+        zip.by_name(name)
+            .map_err(io::Error::from)
+            .and_then(|entry| zstd::stream::copy_decode(entry, &mut collection))
+    } else {
+        archive::copy(&mut zip, name, &mut collection).map(|_| ())
+    };
+    copied
+        .and_then(|_| io::Write::flush(&mut collection))
+        .map_err(|e| bad(format!("读取 {name} 失败: {e}")))?;
+
+    let index =
+        read_entry(&mut zip, "media", zstd).map_err(|e| bad(format!("读取 media 失败: {e}")))?;
+    let mut entries: HashMap<String, String> = match index {
+        None => HashMap::new(),
+        Some(data) if zstd => MediaEntries::decode(data.as_slice())
+            .map_err(|e| bad(format!("media 清单无效: {e}")))?
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let entry = e
+                    .legacy_zip_filename
+                    .map_or(i.to_string(), |n| n.to_string());
+                (e.name, entry)
+            })
+            .collect(),
+        Some(data) if data.is_empty() => HashMap::new(),
+        Some(data) => serde_json::from_slice::<HashMap<String, String>>(&data)
+            .map_err(|e| bad(format!("media 清单无效: {e}")))?
+            .into_iter()
+            .map(|(entry, name)| (name, entry))
+            .collect(),
+    };
+    entries.retain(|_, entry| has(&zip, entry));
+
+    let media = Media {
+        zip: Arc::new(Mutex::new(zip)),
+        entries,
+        zstd,
+    };
+    Ok((collection, media))
+}
+
+/// 读取整个条目，新格式下先经 zstd 解压；解压后超过上限视为无效（防止 zip 炸弹）
+fn read_entry(zip: &mut archive::Reader, name: &str, zstd: bool) -> io::Result<Option<Vec<u8>>> {
+    let Some(data) = archive::read(zip, name, MAX_ENTRY_SIZE)? else {
+        return Ok(None);
+    };
+    if !zstd {
+        return Ok(Some(data));
+    }
+    let mut out = Vec::new();
+    zstd::stream::read::Decoder::new(data.as_slice())?
+        .take(MAX_ENTRY_SIZE + 1)
+        .read_to_end(&mut out)?;
+    if out.len() as u64 > MAX_ENTRY_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{name} 过大"),
+        ));
+    }
+    Ok(Some(out))
+}
+
+// ── 模板渲染 ──
+
+/// 渲染问题与答案的 HTML；`ord` 为卡片在笔记中的序号
+fn render_card(model: &Model, note: &Note, ord: i64, deck: &str) -> Option<(String, String)> {
+    let cloze = model.kind == 1;
+    let template = if cloze {
+        model.tmpls.first()?
+    } else {
+        model
+            .tmpls
+            .iter()
+            .find(|t| t.ord as i64 == ord)
+            .or_else(|| model.tmpls.get(usize::try_from(ord).ok()?))?
+    };
+    let fields = model
+        .flds
+        .iter()
+        .map(|f| {
+            let value = note.fields.get(f.ord).map_or("", String::as_str);
+            (f.name.as_str(), value)
+        })
+        .collect();
+    let tags = note.tags.join(" ");
+    let mut ctx = Context {
+        fields,
+        tags: &tags,
+        deck,
+        card: &template.name,
+        model: &model.name,
+        cloze: cloze.then_some(ord + 1),
+        answer: false,
+    };
+    let question = render(&template.qfmt, &ctx);
+    ctx.answer = true;
+    let answer = render(&template.afmt, &ctx);
+    // {{FrontSide}} 已渲染为空，去掉紧随其后的分隔线
+    let answer = answer
+        .replacen("<hr id=answer>", "", 1)
+        .replacen("<hr id=\"answer\">", "", 1);
+    Some((question, answer))
+}
+
+struct Context<'a> {
+    fields: HashMap<&'a str, &'a str>,
+    tags: &'a str,
+    deck: &'a str,
+    card: &'a str,
+    model: &'a str,
+    /// 填空卡的序号（c1 为 1）
+    cloze: Option<i64>,
+    answer: bool,
+}
+
+impl Context<'_> {
+    fn value(&self, name: &str) -> &str {
+        match name {
+            "Tags" => self.tags,
+            "Deck" => self.deck,
+            "Subdeck" => self.deck.rsplit("::").next().unwrap_or(self.deck),
+            "Card" => self.card,
+            "Type" => self.model,
+            _ => self.fields.get(name).copied().unwrap_or(""),
+        }
+    }
+
+    /// `{{filter:...:Field}}` 的替换结果
+    fn substitute(&self, tag: &str) -> String {
+        let mut parts: Vec<&str> = tag.split(':').map(str::trim).collect();
+        let name = parts.pop().unwrap_or("");
+        // 答案中不重复问题；输入框在这里没有意义
+        if name == "FrontSide" || parts.contains(&"type") {
+            return String::new();
+        }
+        let value = self.value(name);
+        match self.cloze {
+            Some(n) if parts.contains(&"cloze") => render_cloze(value, n, self.answer),
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// 处理 `{{Field}}` 替换与 `{{#Field}}` / `{{^Field}}` 条件段
+fn render(template: &str, ctx: &Context) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = after[..end].trim();
+        rest = &after[end + 2..];
+        if let Some(name) = tag.strip_prefix(['#', '^']) {
+            let close = format!("{{{{/{}}}}}", name.trim());
+            let (inner, tail) = match rest.find(&close) {
+                Some(i) => (&rest[..i], &rest[i + close.len()..]),
+                None => (rest, ""),
+            };
+            rest = tail;
+            let present = !html_to_markdown(ctx.value(name.trim()), &mut |_| None).is_empty();
+            if present == tag.starts_with('#') {
+                out.push_str(&render(inner, ctx));
+            }
+        } else if !tag.starts_with('/') {
+            out.push_str(&ctx.substitute(tag));
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 展开 `{{cN::答案::提示}}`：问题中第 `active` 个挖空显示为 `[...]`（或提示），
+/// 答案中加粗显示，其余挖空直接显示答案
+fn render_cloze(text: &str, active: i64, answer: bool) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{c") {
+        let after = &rest[start + 3..];
+        let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let body = &after[digits..];
+        let (Ok(n), Some(body)) = (after[..digits].parse::<i64>(), body.strip_prefix("::")) else {
+            out.push_str(&rest[..start + 3]);
+            rest = after;
+            continue;
+        };
+        let Some(end) = body.find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let (content, hint) = match body[..end].split_once("::") {
+            Some((c, h)) => (c, Some(h)),
+            None => (&body[..end], None),
+        };
+        match (n == active, answer) {
+            (true, false) => {
+                out.push('[');
+                out.push_str(hint.unwrap_or("..."));
+                out.push(']');
+            }
+            (true, true) => {
+                out.push_str("<b>");
+                out.push_str(content);
+                out.push_str("</b>");
+            }
+            (false, _) => out.push_str(content),
+        }
+        rest = &body[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ── HTML → markdown ──
+
+/// 把卡片 HTML 转为 markdown：块级标签换行，图片与 `[sound:...]` 改为链接，
+/// 其余标签去掉。`media` 把 Anki 的文件名映射为地址，返回 None 时保留原名
+fn html_to_markdown(html: &str, media: &mut dyn FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&decode_entities(&rest[..start]));
+        let Some(end) = rest[start..].find('>') else {
+            out.push_str(&decode_entities(&rest[start..]));
+            rest = "";
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        // 块级标签：另起一行
+        let block_start = !out.is_empty() && !out.ends_with('\n');
+        match name.as_str() {
+            "br" => out.push('\n'),
+            "div" | "p" | "tr" | "ul" | "ol" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                if block_start =>
+            {
+                out.push('\n')
+            }
+            "li" if !closing => {
+                if block_start {
+                    out.push('\n');
+                }
+                out.push_str("- ");
+            }
+            "b" | "strong" => out.push_str("**"),
+            "i" | "em" => out.push('*'),
+            "img" if !closing => {
+                if let Some(src) = attribute(tag, "src") {
+                    let src = decode_entities(src);
+                    let url = media(&src).unwrap_or(src);
+                    out.push_str(&format!("![]({url})"));
+                }
+            }
+            // 样式与脚本的内容不是卡片文字
+            "style" | "script" if !closing => {
+                let close = format!("</{name}");
+                rest = match rest.to_ascii_lowercase().find(&close) {
+                    Some(i) => rest[i..].find('>').map_or("", |j| &rest[i + j + 1..]),
+                    None => "",
+                };
+            }
+            _ => {}
+        }
+    }
+    out.push_str(&decode_entities(rest));
+    let out = replace_sounds(&out, media);
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in out.lines().map(str::trim_end) {
+        // 最多保留一个空行
+        if line.trim().is_empty() && lines.last().is_none_or(|l| l.trim().is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// `[sound:文件名]` → `[文件名](地址)`
+fn replace_sounds(text: &str, media: &mut dyn FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[sound:") {
+        let Some(end) = rest[start..].find(']') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 7..start + end];
+        let url = media(name).unwrap_or_else(|| name.to_string());
+        out.push_str(&format!("[{name}]({url})"));
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 取标签的属性值（支持双引号、单引号与不加引号）
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(name) {
+        let at = from + i;
+        from = at + name.len();
+        let boundary = at > 0 && lower.as_bytes()[at - 1].is_ascii_whitespace();
+        let value = tag[from..].trim_start();
+        let Some(value) = value.strip_prefix('=').filter(|_| boundary) else {
+            continue;
+        };
+        let value = value.trim_start();
+        return Some(match value.chars().next() {
+            Some(q @ ('"' | '\'')) => value[1..].split(q).next().unwrap_or(""),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace() || c == '/')
+                .next()
+                .unwrap_or(""),
+        });
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let decoded = after.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &after[..end];
+            let c = match entity {
+                "nbsp" => ' ',
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = entity.strip_prefix('#')?;
+                    let n = match code.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => code.parse().ok()?,
+                    };
+                    char::from_u32(n)?
+                }
+            };
+            Some((c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::modules::mem::MemRepo;
+    use std::io::Write;
+
+    #[test]
+    fn html_becomes_markdown() {
+        let mut media = |name: &str| (name == "a b.png").then(|| "/x".to_string());
+        assert_eq!(
+            html_to_markdown(
+                "<div>猫 &amp; 狗</div><div><b>cat</b><br/><img src=\"a&#32;b.png\"></div>\
+                 <style>.card{}</style>[sound:c.mp3]&nbsp;",
+                &mut media
+            ),
+            "猫 & 狗\n**cat**\n![](/x)\n[c.mp3](c.mp3)"
+        );
+        assert_eq!(attribute("img class=x src='y.jpg'", "src"), Some("y.jpg"));
+        assert_eq!(attribute("img data-src=z src=y.jpg", "src"), Some("y.jpg"));
+    }
+
+    #[test]
+    fn templates_and_clozes_render() {
+        let ctx = Context {
+            fields: HashMap::from([("Front", "apple"), ("Extra", ""), ("Text", "")]),
+            tags: "",
+            deck: "英语::水果",
+            card: "Card 1",
+            model: "Basic",
+            cloze: None,
+            answer: true,
+        };
+        assert_eq!(
+            render(
+                "{{FrontSide}}{{Front}}{{#Extra}}<br>{{Extra}}{{/Extra}}{{^Extra}}!{{/Extra}} {{Subdeck}}{{type:Front}}",
+                &ctx
+            ),
+            "apple! 水果"
+        );
+        let text = "{{c1::東京::city}} is in {{c2::日本}}";
+        assert_eq!(render_cloze(text, 1, false), "[city] is in 日本");
+        assert_eq!(render_cloze(text, 2, false), "東京 is in [...]");
+        assert_eq!(render_cloze(text, 2, true), "東京 is in <b>日本</b>");
+    }
+
+    /// 建一个最小的 Anki 集合：一个标准笔记（两张卡）与一个填空笔记。
+    /// `version` 为 `Latest` 时按新格式打包：schema 18 的表、zstd 压缩与 protobuf 清单
+    async fn build_package(version: Version) -> File {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection");
+        let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display()))
+            .await
+            .unwrap();
+        for sql in [
+            "CREATE TABLE col (crt INTEGER, models TEXT, decks TEXT)",
+            "CREATE TABLE notes (id INTEGER, mid INTEGER, tags TEXT, flds TEXT)",
+            "CREATE TABLE cards (id INTEGER, nid INTEGER, did INTEGER, ord INTEGER, type INTEGER,
+                queue INTEGER, due INTEGER, ivl INTEGER, factor INTEGER, lapses INTEGER,
+                odid INTEGER, odue INTEGER)",
+            "CREATE TABLE revlog (id INTEGER, cid INTEGER, ease INTEGER, type INTEGER)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        let models = serde_json::json!({
+            "10": {
+                "name": "Basic (and reversed)", "type": 0,
+                "flds": [{"name": "Front", "ord": 0}, {"name": "Back", "ord": 1}],
+                "tmpls": [
+                    {"name": "Card 1", "ord": 0, "qfmt": "{{Front}}",
+                     "afmt": "{{FrontSide}}<hr id=answer>{{Back}}"},
+                    {"name": "Card 2", "ord": 1, "qfmt": "{{Back}}",
+                     "afmt": "{{FrontSide}}<hr id=answer>{{Front}}"}
+                ]
+            },
+            "20": {
+                "name": "Cloze", "type": 1,
+                "flds": [{"name": "Text", "ord": 0}],
+                "tmpls": [{"name": "Cloze", "ord": 0, "qfmt": "{{cloze:Text}}",
+                           "afmt": "{{cloze:Text}}"}]
+            }
+        });
+        let decks = serde_json::json!({
+            "1": {"name": "Default"},
+            "2": {"name": "日语::N3"}
+        });
+        // 2024-01-01T00:00:00Z
+        let crt = 1_704_067_200;
+        if version.zstd() {
+            insert_tables(&pool, &models, &decks).await;
+            // 新格式不再使用 col 中的这两列
+            sqlx::query("INSERT INTO col VALUES (?, '', '')")
+                .bind(crt)
+                .execute(&pool)
+                .await
+                .unwrap();
+        } else {
+            sqlx::query("INSERT INTO col VALUES (?, ?, ?)")
+                .bind(crt)
+                .bind(models.to_string())
+                .bind(decks.to_string())
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO notes VALUES (1, 10, ' 动词 leech ', ?), (2, 20, '', ?)")
+            .bind("食べる<img src=\"eat.png\">\x1f吃")
+            .bind("{{c1::東京}}は{{c2::日本}}の首都")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO cards VALUES
+                (100, 1, 2, 0, 2, 2, 40, 10, 2500, 1, 0, 0),
+                (101, 1, 2, 1, 0, -1, 5, 0, 0, 0, 0, 0),
+                (200, 2, 1, 0, 2, 2, 30, 5, 2300, 0, 0, 0),
+                (201, 2, 1, 1, 0, 0, 6, 0, 0, 0, 0, 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let ms = |day: i64| (crt + day * 86400) * 1000;
+        for (id, cid, ease, kind) in [
+            (ms(20), 100, 3, 0),
+            (ms(20) + 600_000, 100, 3, 0),
+            (ms(23), 100, 1, 1),
+            (ms(23) + 600_000, 100, 3, 2),
+            (ms(30), 100, 3, 1),
+            (ms(31), 100, 0, 4),
+            // 没有学习阶段的记录：调度状态由 SM-2 近似
+            (ms(25), 200, 3, 1),
+        ] {
+            sqlx::query("INSERT INTO revlog VALUES (?, ?, ?, ?)")
+                .bind(id)
+                .bind(cid)
+                .bind(ease)
+                .bind(kind)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        pool.close().await;
+
+        let collection = std::fs::read(&path).unwrap();
+        if !version.zstd() {
+            return zip_of(&[
+                (version.collection(), &collection),
+                (
+                    "media",
+                    br#"{"0": "eat.png", "1": "unused.png", "2": "lost.png"}"#,
+                ),
+                ("0", b"png-bytes"),
+                ("1", b"other"),
+            ]);
+        }
+        let meta = PackageMetadata { version: 3 }.encode_to_vec();
+        let media = MediaEntries {
+            entries: ["eat.png", "unused.png", "lost.png"]
+                .map(|name| MediaEntry {
+                    name: name.into(),
+                    legacy_zip_filename: None,
+                })
+                .to_vec(),
+        };
+        let zstd = |data: &[u8]| zstd::stream::encode_all(data, 0).unwrap();
+        zip_of(&[
+            ("meta", &meta),
+            // 新版 Anki 附带的旧格式集合只有一张提示升级的卡片，不应被读取
+            ("collection.anki2", b"dummy"),
+            ("collection.anki21b", &zstd(&collection)),
+            ("media", &zstd(&media.encode_to_vec())),
+            ("0", &zstd(b"png-bytes")),
+            ("1", &zstd(b"other")),
+        ])
+    }
+
+    /// 把 JSON 形式的笔记类型与卡组写入 schema 18 的表
+    async fn insert_tables(
+        pool: &sqlx::SqlitePool,
+        models: &serde_json::Value,
+        decks: &serde_json::Value,
+    ) {
+        for sql in [
+            "CREATE TABLE notetypes (id INTEGER, name TEXT, config BLOB)",
+            "CREATE TABLE fields (ntid INTEGER, ord INTEGER, name TEXT, config BLOB)",
+            "CREATE TABLE templates (ntid INTEGER, ord INTEGER, name TEXT, config BLOB)",
+            "CREATE TABLE decks (id INTEGER, name TEXT, common BLOB, kind BLOB)",
+        ] {
+            sqlx::query(sql).execute(pool).await.unwrap();
+        }
+        for (id, model) in models.as_object().unwrap() {
+            let id: i64 = id.parse().unwrap();
+            let config = NotetypeConfig {
+                kind: model["type"].as_i64().unwrap() as i32,
+            };
+            sqlx::query("INSERT INTO notetypes VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(model["name"].as_str())
+                .bind(config.encode_to_vec())
+                .execute(pool)
+                .await
+                .unwrap();
+            for f in model["flds"].as_array().unwrap() {
+                sqlx::query("INSERT INTO fields VALUES (?, ?, ?, x'')")
+                    .bind(id)
+                    .bind(f["ord"].as_i64())
+                    .bind(f["name"].as_str())
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+            for t in model["tmpls"].as_array().unwrap() {
+                let config = TemplateConfig {
+                    q_format: t["qfmt"].as_str().unwrap().into(),
+                    a_format: t["afmt"].as_str().unwrap().into(),
+                };
+                sqlx::query("INSERT INTO templates VALUES (?, ?, ?, ?)")
+                    .bind(id)
+                    .bind(t["ord"].as_i64())
+                    .bind(t["name"].as_str())
+                    .bind(config.encode_to_vec())
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+        }
+        for (id, deck) in decks.as_object().unwrap() {
+            sqlx::query("INSERT INTO decks VALUES (?, ?, x'', x'')")
+                .bind(id.parse::<i64>().unwrap())
+                .bind(deck["name"].as_str().unwrap().replace("::", "\x1f"))
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    fn zip_of(entries: &[(&str, &[u8])]) -> File {
+        let mut zip = archive::writer().unwrap();
+        for (name, data) in entries {
+            archive::start_deflated(&mut zip, name).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        archive::finish(zip).unwrap()
+    }
+
+    #[tokio::test]
+    async fn package_converts_to_scheduled_mems() {
+        let package = Package::read(build_package(Version::Legacy1).await)
+            .await
+            .unwrap();
+
+        assert_eq!(package.referenced_media(), vec!["eat.png"]);
+        assert_eq!(package.media("eat.png").await.unwrap(), b"png-bytes");
+        let urls = HashMap::from([("eat.png".to_string(), media_url("m1"))]);
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (mems, errors) = package.to_mems(&urls, &[], now);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(mems.len(), 4);
+
+        let review = &mems[0];
+        assert_eq!(review.cue, "食べる![](/api/media/m1/file)");
+        assert_eq!(review.target, "吃");
+        assert_eq!(review.tags, vec!["日语::N3", "动词", "leech"]);
+        assert!(review.leeched);
+        assert_eq!(review.state, "review");
+        assert_eq!(review.due_at, "2024-02-10T00:00:00Z");
+        assert_eq!(review.lapses, 1);
+        assert!(review.stability > 0.0 && review.difficulty > 0.0);
+        // 手动调整的记录被丢弃
+        assert_eq!(review.revlog.len(), 5);
+        let states: Vec<_> = review
+            .revlog
+            .iter()
+            .map(|r| r.state_before.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            states,
+            ["new", "learning", "review", "relearning", "review"]
+        );
+        assert_eq!(
+            review.revlog.iter().map(|r| r.delta_t).collect::<Vec<_>>(),
+            [0, 0, 2, 0, 6]
+        );
+        assert_eq!(review.revlog[4].stability_after, Some(review.stability));
+        assert_eq!(
+            review.last_review_at.as_deref(),
+            Some("2024-01-31T00:00:00Z")
+        );
+
+        let suspended = &mems[1];
+        assert_eq!(
+            (suspended.cue.as_str(), suspended.target.as_str()),
+            ("吃", "食べる![](/api/media/m1/file)")
+        );
+        assert_eq!(suspended.state, "suspended");
+        assert_eq!(suspended.stability, 0.0);
+
+        let cloze = &mems[2];
+        assert_eq!(cloze.cue, "[...]は日本の首都");
+        assert_eq!(cloze.target, "**東京**は日本の首都");
+        // Default 卡组不作为标签
+        assert!(cloze.tags.is_empty());
+        assert!(cloze.stability > 0.0);
+        assert_eq!(cloze.revlog[0].stability_after, None);
+        assert_eq!(mems[3].state, "new");
+        assert_eq!(mems[3].due_at, "2024-03-01T00:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn anki21b_packages_read_like_legacy_ones() {
+        let urls = HashMap::from([("eat.png".to_string(), media_url("m1"))]);
+        let now = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let legacy = Package::read(build_package(Version::Legacy2).await)
+            .await
+            .unwrap();
+        let latest = Package::read(build_package(Version::Latest).await)
+            .await
+            .unwrap();
+        assert_eq!(latest.decks[&2], "日语::N3");
+        assert_eq!(latest.referenced_media(), vec!["eat.png"]);
+        assert_eq!(latest.media("eat.png").await.unwrap(), b"png-bytes");
+
+        let (mems, errors) = latest.to_mems(&urls, &[], now);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(mems.len(), 4);
+        assert_eq!(mems, legacy.to_mems(&urls, &[], now).0);
+    }
+
+    #[test]
+    fn unknown_package_versions_are_rejected() {
+        let meta = PackageMetadata { version: 4 }.encode_to_vec();
+        let zip = zip_of(&[("meta", &meta), ("collection.anki21b", b"zstd")]);
+        let err = unpack(zip).err().unwrap();
+        assert!(err.to_string().contains("请升级程序"));
+        let mut garbage = tempfile::tempfile().unwrap();
+        garbage.write_all(b"not a zip").unwrap();
+        assert!(unpack(garbage).is_err());
+    }
+
+    #[tokio::test]
+    async fn import_writes_mems_tags_and_revlog() {
+        let pool = Arc::new(sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        let repo = MemRepo::new(pool.clone());
+        let entry = RevlogEntry {
+            review_time: "2024-01-01T00:00:00Z".into(),
+            rating: 3,
+            delta_t: 0,
+            stability_before: None,
+            difficulty_before: None,
+            state_before: Some("new".into()),
+            stability_after: Some(2.3),
+            difficulty_after: Some(5.0),
+            state_after: Some("review".into()),
+        };
+        let mem = |cue: &str, tags: &[&str]| ScheduledMem {
+            id: 0,
+            cue: cue.into(),
+            target: "a".into(),
+            state: "review".into(),
+            stability: 2.3,
+            difficulty: 5.0,
+            step_index: None,
+            buried: false,
+            lapses: 0,
+            leeched: false,
+            due_at: "2024-01-03T00:00:00Z".into(),
+            last_review_at: Some("2024-01-01T00:00:00Z".into()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            mnemonic: None,
+            prerequisites: Vec::new(),
+            revlog: vec![entry.clone()],
+        };
+        let ids = repo
+            .import_scheduled(1, &[mem("q1", &["日语", "N3"]), mem("q2", &["日语"])])
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 2);
+        let row = repo.get_mem(1, ids[1]).await.unwrap().unwrap();
+        assert_eq!((row.state.as_str(), row.stability), ("review", 2.3));
+        assert_eq!(row.due_at, "2024-01-03T00:00:00Z");
+        assert_eq!(repo.get_mem_tags(1, ids[1]).await.unwrap().len(), 1);
+        assert_eq!(repo.list_tags(1).await.unwrap().len(), 2);
+        assert_eq!(repo.count_revlogs(1).await.unwrap(), 2);
+    }
+}
@@ -1,15 +1,18 @@
-//! 记忆模块配置：FSRS 参数 + 调度器配置的持久化。
+//! 记忆模块配置：FSRS 参数 + 调度器配置。
 //!
-//! 配置文件路径：`mem_config.json`（工作目录）
+//! 每个用户一份，以 JSON 存于 `mem_config` 表；没有记录的用户使用默认值。
+//! 早期版本使用单个全局的 `mem_config.json`，启动时由 [`import_legacy_file`]
+//! 迁入数据库。
 
 use serde::{Deserialize, Serialize};
-use std::fs;
+use sqlx::SqlitePool;
 use std::path::Path;
+use utoipa::ToSchema;
 
-const CONFIG_PATH: &str = "mem_config.json";
+use super::fsrs::{self, SchedulerConfig};
 
-/// 持久化配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 用户的记忆配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 pub struct MemConfig {
     /// FSRS 参数（19 个浮点数），空 = 用默认值
     #[serde(default)]
@@ -30,20 +33,50 @@ pub struct MemConfig {
     /// 期望回忆率
     #[serde(default = "default_desired_retention")]
     pub desired_retention: f64,
+
+    /// 每天最多引入的新卡数
+    #[serde(default = "default_new_per_day")]
+    pub new_per_day: i64,
+
+    /// 每天最多复习的到期卡数（学习 / 重学步进不计）
+    #[serde(default = "default_reviews_per_day")]
+    pub reviews_per_day: i64,
+
+    /// 一天从本地时间几点开始（0–23），此前的复习算作前一天
+    #[serde(default = "default_day_rollover_hour")]
+    pub day_rollover_hour: u32,
+
+    /// 用户所在的 IANA 时区（如 `Europe/Berlin`），按该时区计算每日开始时刻，含夏令时
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// 相对 UTC 的固定偏移（分钟，东八区为 480）；仅在未设置 `timezone` 时使用，
+    /// 保留给设置时区之前保存的配置
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
 }
 
-fn default_learning_steps() -> Vec<i64> {
+pub(super) fn default_learning_steps() -> Vec<i64> {
     vec![60, 600]
 }
-fn default_relearn_steps() -> Vec<i64> {
+pub(super) fn default_relearn_steps() -> Vec<i64> {
     vec![600]
 }
-fn default_graduating_interval() -> i64 {
+pub(super) fn default_graduating_interval() -> i64 {
     7200
 }
-fn default_desired_retention() -> f64 {
+pub(super) fn default_desired_retention() -> f64 {
     0.9
 }
+fn default_new_per_day() -> i64 {
+    20
+}
+fn default_reviews_per_day() -> i64 {
+    200
+}
+fn default_day_rollover_hour() -> u32 {
+    4
+}
 
 impl Default for MemConfig {
     fn default() -> Self {
@@ -53,70 +86,187 @@ impl Default for MemConfig {
             relearn_steps: default_relearn_steps(),
             graduating_interval_secs: default_graduating_interval(),
             desired_retention: default_desired_retention(),
+            new_per_day: default_new_per_day(),
+            reviews_per_day: default_reviews_per_day(),
+            day_rollover_hour: default_day_rollover_hour(),
+            timezone: None,
+            utc_offset_minutes: 0,
         }
     }
 }
 
 impl MemConfig {
-    /// 从默认路径加载；不存在则返回默认值
-    pub fn load() -> Self {
-        Self::load_from(Path::new(CONFIG_PATH))
+    /// 校验用户提交的配置，返回面向用户的错误信息
+    pub fn validate(&self) -> Result<(), String> {
+        if self.learning_steps.is_empty() || self.relearn_steps.is_empty() {
+            return Err("学习步进与重学步进至少各有一步".into());
+        }
+        if self
+            .learning_steps
+            .iter()
+            .chain(&self.relearn_steps)
+            .any(|&s| s <= 0)
+        {
+            return Err("步进须为正的秒数".into());
+        }
+        if self.graduating_interval_secs <= 0 {
+            return Err("毕业间隔须为正的秒数".into());
+        }
+        if !(self.desired_retention > 0.0 && self.desired_retention < 1.0) {
+            return Err("期望回忆率须在 0 与 1 之间".into());
+        }
+        if self.new_per_day < 0 || self.reviews_per_day < 0 {
+            return Err("每日上限不能为负数".into());
+        }
+        if self.day_rollover_hour > 23 {
+            return Err("每日开始时刻须在 0 到 23 点之间".into());
+        }
+        if !(-12 * 60..=14 * 60).contains(&self.utc_offset_minutes) {
+            return Err("时区偏移须在 UTC-12 到 UTC+14 之间".into());
+        }
+        if let Some(tz) = &self.timezone
+            && tz.parse::<chrono_tz::Tz>().is_err()
+        {
+            return Err(format!("未知时区: {tz}"));
+        }
+        if !fsrs::params_valid(&self.fsrs_params) {
+            return Err("FSRS 参数无效".into());
+        }
+        Ok(())
     }
 
-    /// 从指定路径加载；不存在则返回默认值
-    pub fn load_from(path: &Path) -> Self {
-        if path.exists() {
-            match fs::read_to_string(path) {
-                Ok(content) => match serde_json::from_str::<MemConfig>(&content) {
-                    Ok(cfg) => {
-                        tracing::info!("已加载记忆配置，FSRS 参数数: {}", cfg.fsrs_params.len());
-                        return cfg;
-                    }
-                    Err(e) => {
-                        tracing::warn!("解析 {} 失败 ({}), 使用默认配置", path.display(), e);
-                    }
-                },
-                Err(e) => {
-                    tracing::warn!("读取 {} 失败 ({}), 使用默认配置", path.display(), e);
-                }
-            }
-        } else {
-            tracing::info!("{} 不存在，创建默认配置文件", path.display());
-            let cfg = MemConfig::default();
-            if let Err(e) = cfg.save_to(path) {
-                tracing::warn!("创建 {} 失败: {}", path.display(), e);
-            }
-            return cfg;
+    /// 调度器使用的配置
+    pub fn scheduler(&self) -> SchedulerConfig {
+        SchedulerConfig {
+            fsrs_params: self.fsrs_params.clone(),
+            learning_steps: self.learning_steps.clone(),
+            relearn_steps: self.relearn_steps.clone(),
+            graduating_interval_secs: self.graduating_interval_secs,
+            desired_retention: self.desired_retention,
         }
-        MemConfig::default()
     }
+}
 
-    /// 保存到默认路径
-    pub fn save(&self) -> Result<(), String> {
-        self.save_to(Path::new(CONFIG_PATH))
+/// 把旧版全局配置文件迁入数据库：已有用户中尚无配置的各得一份副本，
+/// 之后文件改名为 `*.imported`，不再读取。文件不存在时什么也不做。
+pub async fn import_legacy_file(pool: &SqlitePool, path: &Path) -> Result<u64, String> {
+    if !path.exists() {
+        return Ok(0);
     }
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let config: MemConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析 {} 失败: {e}", path.display()))?;
+    if let Err(e) = config.validate() {
+        return Err(format!("{} 无效: {e}", path.display()));
+    }
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    let imported =
+        sqlx::query("INSERT OR IGNORE INTO mem_config (user_id, config) SELECT id, ? FROM user")
+            .bind(&json)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
 
-    /// 保存到指定路径
-    fn save_to(&self, path: &Path) -> Result<(), String> {
-        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
-        fs::write(path, &content).map_err(|e| e.to_string())?;
-        tracing::info!("已保存记忆配置到 {}", path.display());
-        Ok(())
+    let mut renamed = path.as_os_str().to_owned();
+    renamed.push(".imported");
+    std::fs::rename(path, &renamed).map_err(|e| e.to_string())?;
+    tracing::info!(
+        "已将 {} 迁入数据库（{} 个用户），原文件改名为 {}",
+        path.display(),
+        imported,
+        Path::new(&renamed).display()
+    );
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let cfg: MemConfig = serde_json::from_str(r#"{"desired_retention":0.85}"#).unwrap();
+        assert_eq!(cfg.learning_steps, vec![60, 600]);
+        assert_eq!(cfg.desired_retention, 0.85);
+        assert!(cfg.validate().is_ok());
     }
 
-    /// 更新 FSRS 参数并保存
-    pub fn update_fsrs_params(&mut self, params: Vec<f32>) -> Result<(), String> {
-        self.fsrs_params = params;
-        self.save()
+    #[test]
+    fn validate_rejects_bad_values() {
+        let bad = [
+            MemConfig {
+                learning_steps: vec![],
+                ..MemConfig::default()
+            },
+            MemConfig {
+                relearn_steps: vec![600, 0],
+                ..MemConfig::default()
+            },
+            MemConfig {
+                desired_retention: 1.0,
+                ..MemConfig::default()
+            },
+            MemConfig {
+                fsrs_params: vec![1.0, 2.0],
+                ..MemConfig::default()
+            },
+            MemConfig {
+                new_per_day: -1,
+                ..MemConfig::default()
+            },
+            MemConfig {
+                day_rollover_hour: 24,
+                ..MemConfig::default()
+            },
+            MemConfig {
+                utc_offset_minutes: 15 * 60,
+                ..MemConfig::default()
+            },
+            MemConfig {
+                timezone: Some("Mars/Olympus".into()),
+                ..MemConfig::default()
+            },
+        ];
+        for cfg in bad {
+            assert!(cfg.validate().is_err(), "{cfg:?}");
+        }
     }
-}
 
-/// 加载配置并初始化全局 FSRS 参数
-///
-/// `config_path`：配置文件的路径，默认 `mem_config.json`。
-pub fn load_and_init_mem_config(config_path: Option<&Path>) -> MemConfig {
-    let path = config_path.unwrap_or(Path::new(CONFIG_PATH));
-    let cfg = MemConfig::load_from(path);
-    crate::modules::mem::fsrs::init_global_params(cfg.fsrs_params.clone());
-    cfg
+    #[tokio::test]
+    async fn legacy_file_is_copied_to_every_user_once() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        for name in ["a", "b"] {
+            sqlx::query("INSERT INTO user (name, password_hash) VALUES (?, 'x')")
+                .bind(name)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        // b 已有自己的配置，不被覆盖
+        sqlx::query("INSERT INTO mem_config (user_id, config) VALUES (2, '{}')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("brainbow-memcfg-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mem_config.json");
+        std::fs::write(&path, r#"{"desired_retention":0.8}"#).unwrap();
+
+        assert_eq!(import_legacy_file(&pool, &path).await.unwrap(), 1);
+        assert!(!path.exists());
+        assert!(dir.join("mem_config.json.imported").exists());
+        assert_eq!(import_legacy_file(&pool, &path).await.unwrap(), 0);
+
+        let stored: String = sqlx::query_scalar("SELECT config FROM mem_config WHERE user_id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let cfg: MemConfig = serde_json::from_str(&stored).unwrap();
+        assert_eq!(cfg.desired_retention, 0.8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
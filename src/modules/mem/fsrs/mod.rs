@@ -5,44 +5,18 @@
 //! Again 始终走 FSRS 降 stability
 
 use crate::modules::mem::model::CardState;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use fsrs::{FSRS, MemoryState};
-use std::sync::RwLock;
-
-/// 全局 FSRS 参数，启动时由 `init_global_params` 设置，
-/// 优化后可运行时更新（不重启即生效）。
-static GLOBAL_FSRS_PARAMS: RwLock<Vec<f32>> = RwLock::new(Vec::new());
-
-/// 设置全局 FSRS 参数（启动时调用）
-pub fn init_global_params(params: Vec<f32>) {
-    if let Ok(mut p) = GLOBAL_FSRS_PARAMS.write() {
-        *p = params;
-    }
-}
-
-/// 运行时更新全局 FSRS 参数（优化后调用）
-pub fn set_global_params(params: Vec<f32>) {
-    let count = params.len();
-    if let Ok(mut p) = GLOBAL_FSRS_PARAMS.write() {
-        *p = params;
-    }
-    tracing::info!("FSRS 参数已运行时更新 ({count} 个)");
-}
-
-/// 获取当前 FSRS 参数（返回空 Vec 表示用默认值）
-pub fn get_global_params() -> Vec<f32> {
-    GLOBAL_FSRS_PARAMS
-        .read()
-        .ok()
-        .map(|p| p.clone())
-        .unwrap_or_default()
-}
 
 // ── 可配置参数 ──
 
-/// 调度器配置：步进、毕业间隔、期望 retention
+/// 调度器配置：FSRS 参数、步进、毕业间隔、期望 retention。
+///
+/// 每个用户一份，由调用方从该用户的 `MemConfig` 构造后传入
 #[derive(Debug, Clone)]
 pub struct SchedulerConfig {
+    /// FSRS 参数，空 = 用默认值
+    pub fsrs_params: Vec<f32>,
     /// 学习步进（秒）
     pub learning_steps: Vec<i64>,
     /// 重学步进（秒）
@@ -56,6 +30,7 @@ pub struct SchedulerConfig {
 impl Default for SchedulerConfig {
     fn default() -> Self {
         Self {
+            fsrs_params: Vec::new(),
             learning_steps: vec![60, 600],
             relearn_steps: vec![600],
             graduating_interval_secs: 7200,
@@ -66,15 +41,23 @@ impl Default for SchedulerConfig {
 
 // ── 内部函数 ──
 
-fn due_in_secs(secs: i64) -> String {
-    (Utc::now() + Duration::seconds(secs))
+fn due_in_secs(now: DateTime<Utc>, secs: i64) -> String {
+    (now + Duration::seconds(secs))
         .format("%Y-%m-%dT%H:%M:%SZ")
         .to_string()
 }
 
-fn make_fsrs() -> FSRS {
-    let params = get_global_params();
-    FSRS::new(&params).expect("FSRS 参数来自全局配置或默认值，构造不会失败")
+/// 参数不被 FSRS 接受时退回默认参数（保存前已校验，这里只是兜底）
+fn make_fsrs(params: &[f32]) -> FSRS {
+    FSRS::new(params).unwrap_or_else(|e| {
+        tracing::warn!("FSRS 参数无效 ({e:?})，使用默认参数");
+        FSRS::new(&[]).expect("默认参数构造不会失败")
+    })
+}
+
+/// 参数能否用于构造调度器（空 = 默认参数）
+pub fn params_valid(params: &[f32]) -> bool {
+    FSRS::new(params).is_ok()
 }
 
 /// 除非有真实的记忆参数，否则传 None（避免 stability=0 / difficulty=0 传给 FSRS）
@@ -93,11 +76,11 @@ fn compute_next(
     mem: Option<MemoryState>,
     rating: u8,
     days_elapsed: u32,
-    desired_retention: f64,
+    config: &SchedulerConfig,
 ) -> f64 {
-    let fsrs = make_fsrs();
+    let fsrs = make_fsrs(&config.fsrs_params);
     let next = fsrs
-        .next_states(mem, desired_retention as f32, days_elapsed)
+        .next_states(mem, config.desired_retention as f32, days_elapsed)
         .expect("FSRS next_states 接收合法参数，不会失败");
     let chosen = match rating {
         1 => &next.again,
@@ -112,11 +95,11 @@ fn compute_next_with_state(
     mem: Option<MemoryState>,
     rating: u8,
     days_elapsed: u32,
-    desired_retention: f64,
+    config: &SchedulerConfig,
 ) -> (f64, f64, f64) {
-    let fsrs = make_fsrs();
+    let fsrs = make_fsrs(&config.fsrs_params);
     let next = fsrs
-        .next_states(mem, desired_retention as f32, days_elapsed)
+        .next_states(mem, config.desired_retention as f32, days_elapsed)
         .expect("FSRS next_states 接收合法参数，不会失败");
     let chosen = match rating {
         1 => &next.again,
@@ -160,7 +143,12 @@ pub struct ScheduleInput {
     pub cumulative_step_days: u32,
 }
 
-pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome {
+/// 以 `now` 为复习时刻调度，`due_at` 相对它计算；离线同步时按客户端记录的时间重放。
+pub fn schedule(
+    input: ScheduleInput,
+    config: &SchedulerConfig,
+    now: DateTime<Utc>,
+) -> ReviewOutcome {
     use CardState::*;
     let ScheduleInput {
         s_old,
@@ -178,7 +166,7 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
             state: Suspended,
             stability: s_old,
             difficulty: d_old,
-            due_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            due_at: now.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
         };
     }
 
@@ -192,6 +180,7 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
             days_elapsed,
             cumulative_step_days,
             config,
+            now,
         );
     }
 
@@ -209,13 +198,12 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
         return match rating {
             1 => {
                 // Again：用 FSRS 更新状态，回到 step 0
-                let (s, d, _) =
-                    compute_next_with_state(mem, 1, days_elapsed, config.desired_retention);
+                let (s, d, _) = compute_next_with_state(mem, 1, days_elapsed, config);
                 ReviewOutcome {
                     state: Learning,
                     stability: s,
                     difficulty: d,
-                    due_at: due_in_secs(steps[0]),
+                    due_at: due_in_secs(now, steps[0]),
                 }
             }
             2 => {
@@ -225,25 +213,21 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
                     state: Learning,
                     stability: s_old,
                     difficulty: d_old,
-                    due_at: due_in_secs(secs),
+                    due_at: due_in_secs(now, secs),
                 }
             }
             _ => {
                 let next = step + 1;
                 if next >= total_steps {
                     // 毕业 → Review：使用累积时间
-                    let (s, d, secs) = compute_next_with_state(
-                        mem,
-                        rating,
-                        cumulative_step_days,
-                        config.desired_retention,
-                    );
+                    let (s, d, secs) =
+                        compute_next_with_state(mem, rating, cumulative_step_days, config);
                     let secs = secs.max(config.graduating_interval_secs as f64);
                     ReviewOutcome {
                         state: Review,
                         stability: s,
                         difficulty: d,
-                        due_at: due_in_secs(secs as i64),
+                        due_at: due_in_secs(now, secs as i64),
                     }
                 } else {
                     // 推进到下一步：不调 FSRS，S/D 保持不变（毕业时用累积时间算）
@@ -251,7 +235,7 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
                         state: Learning,
                         stability: s_old,
                         difficulty: d_old,
-                        due_at: due_in_secs(steps[next]),
+                        due_at: due_in_secs(now, steps[next]),
                     }
                 }
             }
@@ -262,24 +246,25 @@ pub fn schedule(input: ScheduleInput, config: &SchedulerConfig) -> ReviewOutcome
     let mem = to_memory_state(s_old, d_old);
 
     if rating == 1 {
-        let (s, d, _) = compute_next_with_state(mem, 1, days_elapsed, config.desired_retention);
+        let (s, d, _) = compute_next_with_state(mem, 1, days_elapsed, config);
         return ReviewOutcome {
             state: Relearning,
             stability: s,
             difficulty: d,
-            due_at: due_in_secs(config.relearn_steps[0]),
+            due_at: due_in_secs(now, config.relearn_steps[0]),
         };
     }
 
-    let (s, d, secs) = compute_next_with_state(mem, rating, days_elapsed, config.desired_retention);
+    let (s, d, secs) = compute_next_with_state(mem, rating, days_elapsed, config);
     ReviewOutcome {
         state: Review,
         stability: s,
         difficulty: d,
-        due_at: due_in_secs(secs as i64),
+        due_at: due_in_secs(now, secs as i64),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn relearn(
     s_old: f64,
     d_old: f64,
@@ -288,6 +273,7 @@ fn relearn(
     days_elapsed: u32,
     cumulative_step_days: u32,
     config: &SchedulerConfig,
+    now: DateTime<Utc>,
 ) -> ReviewOutcome {
     let mem = to_memory_state(s_old, d_old);
     let steps = &config.relearn_steps;
@@ -296,12 +282,12 @@ fn relearn(
 
     match rating {
         1 => {
-            let (s, d, _) = compute_next_with_state(mem, 1, days_elapsed, config.desired_retention);
+            let (s, d, _) = compute_next_with_state(mem, 1, days_elapsed, config);
             ReviewOutcome {
                 state: Relearning,
                 stability: s,
                 difficulty: d,
-                due_at: due_in_secs(steps[0]),
+                due_at: due_in_secs(now, steps[0]),
             }
         }
         2 => {
@@ -310,30 +296,26 @@ fn relearn(
                 state: Relearning,
                 stability: s_old,
                 difficulty: d_old,
-                due_at: due_in_secs(secs),
+                due_at: due_in_secs(now, secs),
             }
         }
         _ => {
             let next = step + 1;
             if next >= total_steps {
-                let (s, d, secs) = compute_next_with_state(
-                    mem,
-                    rating,
-                    cumulative_step_days,
-                    config.desired_retention,
-                );
+                let (s, d, secs) =
+                    compute_next_with_state(mem, rating, cumulative_step_days, config);
                 ReviewOutcome {
                     state: Review,
                     stability: s,
                     difficulty: d,
-                    due_at: due_in_secs(secs as i64),
+                    due_at: due_in_secs(now, secs as i64),
                 }
             } else {
                 ReviewOutcome {
                     state: Relearning,
                     stability: s_old,
                     difficulty: d_old,
-                    due_at: due_in_secs(steps[next]),
+                    due_at: due_in_secs(now, steps[next]),
                 }
             }
         }
@@ -363,12 +345,12 @@ pub fn preview(
             steps[0] as f64,
             steps[step.min(steps.len() - 1)] as f64,
             if step + 1 >= steps.len() {
-                compute_next(mem, 3, days_elapsed, config.desired_retention)
+                compute_next(mem, 3, days_elapsed, config)
             } else {
                 steps[step + 1] as f64
             },
             if step + 1 >= steps.len() {
-                compute_next(mem, 4, days_elapsed, config.desired_retention)
+                compute_next(mem, 4, days_elapsed, config)
             } else {
                 steps[step + 1] as f64
             },
@@ -387,8 +369,8 @@ pub fn preview(
         let next = step + 1;
         let (good, easy) = if next >= steps.len() {
             (
-                compute_next(mem, 3, days_elapsed, config.desired_retention),
-                compute_next(mem, 4, days_elapsed, config.desired_retention),
+                compute_next(mem, 3, days_elapsed, config),
+                compute_next(mem, 4, days_elapsed, config),
             )
         } else {
             (steps[next] as f64, steps[next] as f64)
@@ -398,11 +380,46 @@ pub fn preview(
 
     [
         config.learning_steps[0] as f64,
-        compute_next(mem, 2, days_elapsed, config.desired_retention),
-        compute_next(mem, 3, days_elapsed, config.desired_retention),
-        compute_next(mem, 4, days_elapsed, config.desired_retention),
+        compute_next(mem, 2, days_elapsed, config),
+        compute_next(mem, 3, days_elapsed, config),
+        compute_next(mem, 4, days_elapsed, config),
     ]
 }
 
+// ── 导入换算 ──
+
+/// 按复习历史 `(评分, 距上次复习天数)` 重放，得到每次复习后的 (stability, difficulty)。
+///
+/// 用于导入其他软件的复习记录；历史无法计算时返回 None
+pub fn replay_history(params: &[f32], reviews: &[(u8, u32)]) -> Option<Vec<(f64, f64)>> {
+    let item = fsrs::FSRSItem {
+        reviews: reviews
+            .iter()
+            .map(|&(rating, delta_t)| fsrs::FSRSReview {
+                rating: rating.into(),
+                delta_t,
+            })
+            .collect(),
+    };
+    let states = make_fsrs(params)
+        .historical_memory_states(item, None)
+        .ok()?;
+    Some(
+        states
+            .into_iter()
+            .map(|m| (m.stability as f64, m.difficulty as f64))
+            .collect(),
+    )
+}
+
+/// 由 SM-2 的难度系数（如 2.5）与当前间隔（天）近似 (stability, difficulty)，
+/// 用于缺少完整学习历史的复习卡
+pub fn memory_from_sm2(params: &[f32], ease_factor: f64, interval_days: f64) -> Option<(f64, f64)> {
+    make_fsrs(params)
+        .memory_state_from_sm2(ease_factor as f32, interval_days as f32, 0.9)
+        .ok()
+        .map(|m| (m.stability as f64, m.difficulty as f64))
+}
+
 #[cfg(test)]
 mod tests;
@@ -32,6 +32,7 @@ fn schedule_secs(
             cumulative_step_days: 0,
         },
         &config,
+        Utc::now(),
     );
     let due = chrono::DateTime::parse_from_rfc3339(&outcome.due_at)
         .unwrap()
@@ -124,6 +125,7 @@ fn good_grows_faster_than_hard() {
                     cumulative_step_days: 0,
                 },
                 &config,
+                Utc::now(),
             );
             s = o.stability;
             d = o.difficulty;
@@ -161,6 +163,7 @@ fn forget_in_review_triggers_relearning() {
             cumulative_step_days: 0,
         },
         &config,
+        Utc::now(),
     );
     assert_eq!(outcome.state, CardState::Relearning);
     let secs = schedule_secs(5.0, 5.0, CardState::Review, None, 1, 5);
@@ -181,6 +184,7 @@ fn relearn_then_recover() {
             cumulative_step_days: 0,
         },
         &config,
+        Utc::now(),
     );
     assert_eq!(o1.state, CardState::Relearning);
     let o2 = schedule(
@@ -194,6 +198,7 @@ fn relearn_then_recover() {
             cumulative_step_days: 1,
         },
         &config,
+        Utc::now(),
     );
     assert_eq!(o2.state, CardState::Review);
 }
@@ -239,6 +244,7 @@ fn long_term_growth_trajectory() {
             cumulative_step_days: 0,
         },
         &config,
+        Utc::now(),
     );
     (s, d, state) = (o1.stability, o1.difficulty, o1.state);
     // Step 1 → 毕业（用 cumulative_step_days=1）
@@ -253,6 +259,7 @@ fn long_term_growth_trajectory() {
             cumulative_step_days: 1,
         },
         &config,
+        Utc::now(),
     );
     (s, d, state) = (o2.stability, o2.difficulty, o2.state);
     assert_eq!(state, CardState::Review, "应毕业到 Review");
@@ -270,6 +277,7 @@ fn long_term_growth_trajectory() {
                 cumulative_step_days: 0,
             },
             &config,
+            Utc::now(),
         );
         s = o.stability;
         d = o.difficulty;
@@ -386,6 +394,7 @@ impl TrueMemSim {
                 cumulative_step_days,
             },
             &self.config,
+            Utc::now(),
         );
         self.sys_s = outcome.stability;
         self.sys_d = outcome.difficulty;
@@ -557,6 +566,7 @@ fn custom_config_produces_different_intervals() {
         relearn_steps: vec![60, 300],
         graduating_interval_secs: 43200,
         desired_retention: 0.85,
+        ..SchedulerConfig::default()
     };
     let now = Utc::now();
 
@@ -572,6 +582,7 @@ fn custom_config_produces_different_intervals() {
             cumulative_step_days: 0,
         },
         &default,
+        Utc::now(),
     );
     let f1 = schedule(
         ScheduleInput {
@@ -584,6 +595,7 @@ fn custom_config_produces_different_intervals() {
             cumulative_step_days: 0,
         },
         &fast,
+        Utc::now(),
     );
     // 默认 [60, 600]，第一个 step 后 due 在 STEPS[1]=600s
     // 快速 [30, 120]，第一个 step 后 due 在 120s
@@ -602,14 +614,7 @@ fn custom_config_produces_different_intervals() {
     eprintln!("默认步进: {d_step}s, 快速步进: {f_step}s");
 }
 
-// ── 全局参数运行时更新 ──
-//
-// 这些测试会短暂修改 `GLOBAL_FSRS_PARAMS`，然后立即恢复为空。
-// 窗口极小（set → assert → restore 在同一线程连续执行），
-// 并行测试几乎不可能在窗口内读到脏数据。
-//
-// 使用的参数值必须能被 FSRS 接受，避免 InvalidParameters 错误
-// 污染并行的其他测试。这里使用默认 FSRS 参数（19 个 f32）。
+// ── 按用户的 FSRS 参数 ──
 
 /// 默认 FSRS 参数（从实际优化结果提取，保证 FSRS 能接受）
 fn default_fsrs_params() -> Vec<f32> {
@@ -619,46 +624,61 @@ fn default_fsrs_params() -> Vec<f32> {
     ]
 }
 
-#[test]
-fn global_params_can_be_set_after_init() {
-    init_global_params(vec![]);
-
-    assert!(get_global_params().is_empty());
-
-    let params = default_fsrs_params();
-    set_global_params(params.clone());
-
-    assert_eq!(get_global_params(), params);
-
-    init_global_params(vec![]);
+fn review_interval(config: &SchedulerConfig) -> f64 {
+    preview(10.0, 5.0, CardState::Review, None, 10, config)[2]
 }
 
 #[test]
-fn global_params_overwrites_previous() {
-    init_global_params(vec![]);
-
-    let first = vec![1.0, 2.0, 3.0];
-    init_global_params(first.clone());
-    let p1 = get_global_params();
-    assert_eq!(p1, first);
-
-    let second = default_fsrs_params();
-    set_global_params(second.clone());
-    let p2 = get_global_params();
-    assert_eq!(p2, second);
-    assert_ne!(p1, p2, "set_global_params 后参数应变化");
+fn params_validation() {
+    assert!(params_valid(&[]));
+    assert!(params_valid(&default_fsrs_params()));
+    assert!(!params_valid(&[1.0, 2.0, 3.0]));
+}
 
-    init_global_params(vec![]);
+#[test]
+fn custom_params_change_intervals() {
+    let mut params = default_fsrs_params();
+    // w[8] 控制成功回忆后稳定性的增长幅度
+    params[8] = 0.5;
+    let custom = SchedulerConfig {
+        fsrs_params: params,
+        ..SchedulerConfig::default()
+    };
+    assert_ne!(
+        review_interval(&SchedulerConfig::default()),
+        review_interval(&custom)
+    );
 }
 
 #[test]
-fn make_fsrs_uses_current_global_params() {
-    init_global_params(vec![]);
-    let _fsrs = make_fsrs();
+fn invalid_params_fall_back_to_default() {
+    let broken = SchedulerConfig {
+        fsrs_params: vec![1.0, 2.0, 3.0],
+        ..SchedulerConfig::default()
+    };
+    assert_eq!(
+        review_interval(&broken),
+        review_interval(&SchedulerConfig::default())
+    );
+}
 
-    let params = default_fsrs_params();
-    set_global_params(params);
-    let _fsrs = make_fsrs();
+#[test]
+fn replay_history_tracks_each_review() {
+    let states = replay_history(&[], &[(3, 0), (3, 0), (3, 3), (1, 10), (3, 1)]).unwrap();
+    assert_eq!(states.len(), 5);
+    assert!(states[2].0 > states[0].0);
+    // 遗忘后稳定性下降、难度上升
+    assert!(states[3].0 < states[2].0);
+    assert!(states[3].1 > states[2].1);
+}
 
-    init_global_params(vec![]);
+#[test]
+fn sm2_interval_maps_to_stability() {
+    let (short, _) = memory_from_sm2(&[], 2.5, 3.0).unwrap();
+    let (long, _) = memory_from_sm2(&[], 2.5, 30.0).unwrap();
+    assert!(long > short);
+    let (_, easy) = memory_from_sm2(&[], 3.0, 10.0).unwrap();
+    let (_, hard) = memory_from_sm2(&[], 1.3, 10.0).unwrap();
+    assert!((1.0..=10.0).contains(&easy) && (1.0..=10.0).contains(&hard));
+    assert!(hard > easy);
 }
@@ -1,19 +1,28 @@
 use axum::{
     Json,
-    extract::{Extension, Path, Query, State},
+    extract::{Extension, Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use serde::Deserialize;
+use utoipa::ToSchema;
 
 use crate::auth::Claims;
 use std::collections::HashMap;
 
+use crate::archive;
 use crate::batch::{BatchDataResponse, BatchRequest, BatchResponse};
 use crate::error;
+use crate::etag::{self, IfMatch};
 use crate::guard_empty_batch;
+use crate::modules::job::{JobKind, JobResponse, NewJob};
+use crate::modules::mem::anki::AnkiImportReport;
 use crate::modules::mem::config::MemConfig;
 use crate::modules::mem::model::*;
-use crate::modules::mem::optimizer;
+use crate::modules::mem::preset::{MemPreset, PresetRequest, SetTagPresetRequest};
+use crate::modules::mem::service::optimize_dedupe_key;
+use crate::openapi::{Binary, FileUpload, OkFlag, PreconditionFailed};
+use crate::pagination::PaginatedResponse;
 use crate::state::AppState;
 
 fn ok() -> axum::response::Response {
@@ -27,33 +36,65 @@ fn err(e: impl std::fmt::Display, op: &str) -> axum::response::Response {
 //  读操作（通过 MemQueryService，无副作用）
 // ═══════════════════════════════════════════════════════════════
 
+#[utoipa::path(
+    get,
+    path = "/all",
+    summary = "分页查询卡片",
+    params(MemQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<MemWithChunks>))
+)]
 pub async fn get_all(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Query(p): Query<MemQuery>,
 ) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.get_all(&p).await {
+    match svc.get_all(claims.sub, &p).await {
         Ok(res) => Json(res).into_response(),
         Err(e) => err(e, "获取全部"),
     }
 }
 
-pub async fn get_session_estimate(State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/session-estimate",
+    summary = "本轮复习量估计",
+    responses((status = 200, description = "成功", body = SessionEstimate))
+)]
+pub async fn get_session_estimate(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.get_session_estimate().await {
+    match svc.get_session_estimate(claims.sub).await {
         Ok(est) => Json(est).into_response(),
         Err(e) => err(e, "获取学习预估"),
     }
 }
 
-pub async fn get_counts(State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/counts",
+    summary = "各状态卡片数量",
+    responses((status = 200, description = "成功", body = MemCounts))
+)]
+pub async fn get_counts(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.get_counts().await {
+    match svc.get_counts(claims.sub).await {
         Ok(counts) => Json(counts).into_response(),
         Err(e) => err(e, "获取统计"),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/tag/list",
+    summary = "标签列表",
+    responses((status = 200, description = "成功", body = Vec<TagInfo>))
+)]
 pub async fn list_tags(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -65,6 +106,13 @@ pub async fn list_tags(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/tag/search",
+    summary = "搜索标签",
+    params(("q" = Option<String>, Query)),
+    responses((status = 200, description = "成功", body = Vec<TagInfo>))
+)]
 pub async fn search_tags(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -78,28 +126,53 @@ pub async fn search_tags(
     }
 }
 
-pub async fn get_mem_tags(State(state): State<AppState>, Path(id): Path<i32>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/tag/mem/{id}",
+    summary = "卡片的标签",
+    responses((status = 200, description = "成功", body = Vec<TagInfo>))
+)]
+pub async fn get_mem_tags(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.get_mem_tags(id).await {
+    match svc.get_mem_tags(claims.sub, id).await {
         Ok(tags) => Json(tags).into_response(),
-        Err(e) => err(e, "获取记忆标签"),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/batch-by-ids",
+    summary = "批量查询卡片标签",
+    request_body = BatchRequest<i32>,
+    responses((status = 200, description = "成功", body = BatchDataResponse<MemTagRow>))
+)]
 pub async fn batch_get_mems_tags(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchRequest<i32>>,
 ) -> Json<BatchDataResponse<MemTagRow>> {
     if payload.items.is_empty() {
         return Json(BatchDataResponse::empty());
     }
     let svc = &state.mem_query;
-    Json(svc.get_mems_tags_batch(&payload.items).await)
+    Json(svc.get_mems_tags_batch(claims.sub, &payload.items).await)
 }
 
+#[utoipa::path(
+    get,
+    path = "/export/csv",
+    summary = "导出 PSV",
+    params(("tag_ids" = Option<String>, Query)),
+    responses((status = 200, description = "成功", body = Binary, content_type = "text/tab-separated-values"))
+)]
 pub async fn export_csv(
     State(state): State<AppState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
     let tag_ids: Vec<i32> = params
@@ -107,7 +180,7 @@ pub async fn export_csv(
         .map(|v| v.split(',').filter_map(|s| s.trim().parse().ok()).collect())
         .unwrap_or_default();
     let svc = &state.mem_query;
-    match svc.export_csv(&tag_ids).await {
+    match svc.export_csv(claims.sub, &tag_ids).await {
         Ok(psv) => (
             [
                 ("Content-Type", "text/tab-separated-values; charset=utf-8"),
@@ -120,26 +193,97 @@ pub async fn export_csv(
     }
 }
 
-pub async fn preview_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/export/full",
+    summary = "完整导出（含调度状态、助记、前置依赖与复习记录）",
+    params(("tag_ids" = Option<String>, Query)),
+    responses((status = 200, description = "成功", body = MemExport))
+)]
+pub async fn export_full(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let tag_ids: Vec<i32> = params
+        .get("tag_ids")
+        .map(|v| v.split(',').filter_map(|s| s.trim().parse().ok()).collect())
+        .unwrap_or_default();
+    match state.mem_query.export_full(claims.sub, &tag_ids).await {
+        Ok(export) => (
+            [("Content-Disposition", "attachment; filename=\"mems.json\"")],
+            Json(export),
+        )
+            .into_response(),
+        Err(e) => err(e, "完整导出"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "卡片内容",
+    responses((status = 200, description = "成功", body = MemContent, headers(("ETag" = String, description = "资源当前版本"))))
+)]
+pub async fn get_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    match state.mem_query.content(claims.sub, id).await {
+        Ok(content) => etag::with(&etag::version(content.version), Json(content.row)),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}/preview",
+    summary = "预览四档评分的间隔（秒）",
+    responses((status = 200, description = "成功", body = IntervalPreview))
+)]
+pub async fn preview_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.preview(id).await {
-        Ok(secs) => Json(serde_json::json!({ "intervals": secs })).into_response(),
+    match svc.preview(claims.sub, id).await {
+        Ok(intervals) => Json(IntervalPreview { intervals }).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
-pub async fn get_mnemonic(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/{id}/mnemonic",
+    summary = "获取助记",
+    responses((status = 200, description = "成功", body = Mnemonic))
+)]
+pub async fn get_mnemonic(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.get_mnemonic(id).await {
-        Ok(Some(content)) => Json(serde_json::json!({ "content": content })).into_response(),
-        Ok(None) => Json(serde_json::json!({ "content": null })).into_response(),
+    match svc.get_mnemonic(claims.sub, id).await {
+        Ok(content) => Json(Mnemonic { content }).into_response(),
         Err(e) => err(e, "查询助记"),
     }
 }
 
-pub async fn upcoming_counts(State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/upcoming-counts",
+    summary = "即将到期数量",
+    responses((status = 200, description = "成功", body = UpcomingCounts))
+)]
+pub async fn upcoming_counts(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem_query;
-    match svc.upcoming_counts().await {
+    match svc.upcoming_counts(claims.sub).await {
         Ok(v) => Json(v).into_response(),
         Err(e) => err(e, "查询 upcoming 数量"),
     }
@@ -149,33 +293,64 @@ pub async fn upcoming_counts(State(state): State<AppState>) -> impl IntoResponse
 //  写操作（通过 MemService，含副作用和不变量检查）
 // ═══════════════════════════════════════════════════════════════
 
+#[utoipa::path(
+    post,
+    path = "/batch-bury",
+    summary = "批量搁置",
+    request_body = BatchRequest<i32>,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_bury(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchRequest<i32>>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
-    Json(svc.batch_bury(&payload.items).await)
+    Json(svc.batch_bury(claims.sub, &payload.items).await)
 }
 
+#[utoipa::path(
+    post,
+    path = "/batch-delete",
+    summary = "批量删除",
+    request_body = BatchRequest<i32>,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_delete(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchRequest<i32>>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
-    Json(svc.batch_delete(&payload.items).await)
+    Json(svc.batch_delete(claims.sub, &payload.items).await)
 }
 
+#[utoipa::path(
+    post,
+    path = "/batch-reset",
+    summary = "批量重置",
+    request_body = BatchRequest<i32>,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_reset(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchRequest<i32>>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
-    Json(svc.batch_reset(&payload.items).await)
+    Json(svc.batch_reset(claims.sub, &payload.items).await)
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/create",
+    summary = "创建标签",
+    request_body = CreateTagRequest,
+    responses((status = 200, description = "成功", body = TagInfo))
+)]
 pub async fn create_tag(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -191,113 +366,180 @@ pub async fn create_tag(
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/tag/delete/{id}",
+    summary = "删除标签",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn delete_tag(
     State(state): State<AppState>,
-    Extension(_claims): Extension<Claims>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.delete_tag(id).await {
+    match svc.delete_tag(claims.sub, id).await {
         Ok(()) => ok(),
         Err(e) => err(e, "删除标签"),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/mem/add",
+    summary = "为卡片添加标签",
+    request_body = TagMemRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn add_mem_tag(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<TagMemRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.add_tag_to_mem(payload.mem_id, payload.tag_id).await {
+    match svc
+        .add_tag_to_mem(claims.sub, payload.mem_id, payload.tag_id)
+        .await
+    {
         Ok(()) => ok(),
-        Err(e) => err(e, "添加标签"),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/mem/remove",
+    summary = "移除卡片标签",
+    request_body = TagMemRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn remove_mem_tag(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<TagMemRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
     match svc
-        .remove_tag_from_mem(payload.mem_id, payload.tag_id)
+        .remove_tag_from_mem(claims.sub, payload.mem_id, payload.tag_id)
         .await
     {
         Ok(()) => ok(),
-        Err(e) => err(e, "移除标签"),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/mem/set",
+    summary = "设置卡片标签",
+    request_body = SetTagsRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn set_mem_tags(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<SetTagsRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.set_mem_tags(payload.mem_id, &payload.tag_ids).await {
+    match svc
+        .set_mem_tags(claims.sub, payload.mem_id, &payload.tag_ids)
+        .await
+    {
         Ok(()) => ok(),
-        Err(e) => err(e, "设置标签"),
+        Err(e) => e.into_response(),
     }
 }
 
 // ── 批量标签请求结构体 ──
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BatchTagRequest {
     pub items: Vec<i32>,
     pub tag_id: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BatchSetTagsRequest {
     pub items: Vec<i32>,
     pub tag_ids: Vec<i32>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/batch-add",
+    summary = "批量添加标签",
+    request_body = BatchTagRequest,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_add_tag(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchTagRequest>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
     Json(
-        svc.batch_add_tag_to_mems(&payload.items, payload.tag_id)
+        svc.batch_add_tag_to_mems(claims.sub, &payload.items, payload.tag_id)
             .await,
     )
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/batch-remove",
+    summary = "批量移除标签",
+    request_body = BatchTagRequest,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_remove_tag(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchTagRequest>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
     Json(
-        svc.batch_remove_tag_from_mems(&payload.items, payload.tag_id)
+        svc.batch_remove_tag_from_mems(claims.sub, &payload.items, payload.tag_id)
             .await,
     )
 }
 
+#[utoipa::path(
+    post,
+    path = "/tag/batch-set",
+    summary = "批量设置标签",
+    request_body = BatchSetTagsRequest,
+    responses((status = 200, description = "成功", body = BatchResponse))
+)]
 pub async fn batch_set_tags(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<BatchSetTagsRequest>,
 ) -> Json<BatchResponse> {
     guard_empty_batch!(payload.items);
     let svc = &state.mem;
     Json(
-        svc.batch_set_tags_for_mems(&payload.items, &payload.tag_ids)
+        svc.batch_set_tags_for_mems(claims.sub, &payload.items, &payload.tag_ids)
             .await,
     )
 }
 
 // ── CSV/JSON 导入类 ──
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct ImportCsvPayload {
     pub csv: String,
     #[serde(default)]
     pub default_tags: Vec<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/import/csv",
+    summary = "导入 CSV",
+    request_body = ImportCsvPayload,
+    responses((status = 200, description = "成功", body = ImportSummary))
+)]
 pub async fn import_csv(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -308,15 +550,18 @@ pub async fn import_csv(
         .import_csv(&payload.csv, claims.sub, &payload.default_tags)
         .await
     {
-        Ok((count, errors)) => Json(serde_json::json!({
-            "imported": count,
-            "errors": errors,
-        }))
-        .into_response(),
+        Ok((imported, errors)) => Json(ImportSummary { imported, errors }).into_response(),
         Err(e) => err(e, "导入 CSV"),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/import/psv",
+    summary = "导入 PSV",
+    request_body = ImportCsvPayload,
+    responses((status = 200, description = "成功", body = ImportSummary))
+)]
 pub async fn import_psv(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -327,22 +572,25 @@ pub async fn import_psv(
         .import_psv(&payload.csv, claims.sub, &payload.default_tags)
         .await
     {
-        Ok((count, errors)) => Json(serde_json::json!({
-            "imported": count,
-            "errors": errors,
-        }))
-        .into_response(),
+        Ok((imported, errors)) => Json(ImportSummary { imported, errors }).into_response(),
         Err(e) => err(e, "导入 PSV"),
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct ImportJsonPayload {
     pub mems: Vec<JsonMemItem>,
     #[serde(default)]
     pub default_tags: Vec<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/import/json",
+    summary = "导入 JSON",
+    request_body = ImportJsonPayload,
+    responses((status = 200, description = "成功", body = ImportSummary))
+)]
 pub async fn import_json(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -353,19 +601,102 @@ pub async fn import_json(
         .import_json(&payload.mems, claims.sub, &payload.default_tags)
         .await
     {
-        Ok((count, errors)) => Json(serde_json::json!({
-            "imported": count,
-            "errors": errors,
-        }))
-        .into_response(),
+        Ok((imported, errors)) => Json(ImportSummary { imported, errors }).into_response(),
         Err(e) => err(e, "导入 JSON"),
     }
 }
 
+#[derive(Deserialize)]
+pub struct ImportTagsQuery {
+    /// 逗号分隔，附加到每张卡片的标签
+    #[serde(default)]
+    pub tags: Option<String>,
+}
+
+impl ImportTagsQuery {
+    fn tags(&self) -> Vec<String> {
+        self.tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect()
+    }
+}
+
+/// 上传 `.apkg` / `.colpkg`（multipart 字段 `file`）
+#[utoipa::path(
+    post,
+    path = "/import/anki",
+    summary = "导入 Anki 包（含调度状态、复习记录与媒体）",
+    params(("tags" = Option<String>, Query)),
+    request_body(content = FileUpload, content_type = "multipart/form-data"),
+    responses((status = 200, description = "成功", body = AnkiImportReport))
+)]
+pub async fn import_anki(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(q): Query<ImportTagsQuery>,
+    mut multipart: Multipart,
+) -> impl IntoResponse {
+    let mut package = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        if field.name() != Some("file") {
+            continue;
+        }
+        match archive::spool(field).await {
+            Ok(file) => package = Some(file),
+            Err(e) => return error::bad_request(format!("读取文件失败: {}", e)),
+        }
+    }
+    let Some(package) = package else {
+        return error::bad_request("缺少 'file' 字段");
+    };
+    match state
+        .mem
+        .import_anki(&state.media, claims.sub, package, &q.tags())
+        .await
+    {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 请求体即 GET /export/full 的下载内容
+#[utoipa::path(
+    post,
+    path = "/import/full",
+    summary = "导入完整导出文件",
+    params(("tags" = Option<String>, Query)),
+    request_body = MemExport,
+    responses((status = 200, description = "成功", body = ImportSummary))
+)]
+pub async fn import_full(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(q): Query<ImportTagsQuery>,
+    Json(payload): Json<MemExport>,
+) -> impl IntoResponse {
+    match state.mem.import_full(claims.sub, &payload, &q.tags()).await {
+        Ok((imported, errors)) => Json(ImportSummary { imported, errors }).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
 // ── get_due（含侧面写操作：新卡标注 learning）──
 
+#[utoipa::path(
+    get,
+    path = "/due",
+    summary = "获取待复习卡片",
+    params(("limit" = Option<i64>, Query), ("tag_ids" = Option<String>, Query), ("exclude_tag_ids" = Option<String>, Query)),
+    responses((status = 200, description = "成功", body = DueResponse))
+)]
 pub async fn get_due(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
     let limit = params
@@ -381,7 +712,10 @@ pub async fn get_due(
         .map(|v| v.split(',').filter_map(|s| s.trim().parse().ok()).collect())
         .unwrap_or_default();
     let svc = &state.mem;
-    match svc.get_due(limit, &tag_ids, &exclude_tag_ids).await {
+    match svc
+        .get_due(claims.sub, limit, &tag_ids, &exclude_tag_ids)
+        .await
+    {
         Ok(res) => Json(res).into_response(),
         Err(e) => err(e, "获取待复习"),
     }
@@ -389,142 +723,368 @@ pub async fn get_due(
 
 // ── 纯写操作 ──
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建记忆卡片",
+    request_body = CreateMemRequest,
+    responses((status = 200, description = "成功", body = CreatedMem))
+)]
 pub async fn create_mem(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(body): Json<CreateMemRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.create(body).await {
-        Ok(id) => Json(serde_json::json!({ "id": id })).into_response(),
+    match svc.create(claims.sub, body).await {
+        Ok(id) => Json(CreatedMem { id }).into_response(),
         Err(e) => err(e, "创建记忆项"),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/review",
+    summary = "提交复习评分",
+    request_body = ReviewRequest,
+    responses((status = 200, description = "成功", body = ReviewResponse))
+)]
 pub async fn review_mem(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(body): Json<ReviewRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.review(id, body.rating).await {
+    match svc.review(claims.sub, id, body.rating).await {
         Ok(res) => Json(res).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/undo",
+    summary = "撤销复习",
+    request_body = UndoRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn undo_review(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(body): Json<UndoRequest>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.undo(id, body).await {
+    match svc.undo(claims.sub, id, body).await {
         Ok(()) => ok(),
-        Err(e) => err(e, "撤销"),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/{id}/edit",
+    summary = "编辑卡片内容",
+    params(IfMatch),
+    request_body = EditMemRequest,
+    responses((status = 200, description = "成功", body = OkFlag, headers(("ETag" = String, description = "资源当前版本"))), PreconditionFailed)
+)]
 pub async fn edit_mem(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     Json(body): Json<EditMemRequest>,
 ) -> impl IntoResponse {
-    let svc = &state.mem;
-    match svc.edit(id, body).await {
-        Ok(()) => ok(),
+    let if_match = IfMatch::from_headers(&headers);
+    match state.mem.edit_if(claims.sub, id, body, &if_match).await {
+        Ok(version) => etag::with(&etag::version(version), ok()),
         Err(e) => e.into_response(),
     }
 }
 
-pub async fn bury_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    post,
+    path = "/{id}/bury",
+    summary = "搁置",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn bury_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.bury(id).await {
+    match svc.bury(claims.sub, id).await {
         Ok(()) => ok(),
-        Err(e) => err(e, "跳过"),
+        Err(e) => e.into_response(),
     }
 }
 
-pub async fn unbury_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    post,
+    path = "/{id}/unbury",
+    summary = "取消搁置",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn unbury_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.unbury(id).await {
+    match svc.unbury(claims.sub, id).await {
         Ok(()) => ok(),
-        Err(e) => err(e, "取消跳过"),
+        Err(e) => e.into_response(),
     }
 }
 
-pub async fn suspend_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    post,
+    path = "/{id}/suspend",
+    summary = "暂停",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn suspend_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.suspend(id).await {
+    match svc.suspend(claims.sub, id).await {
         Ok(()) => ok(),
         Err(e) => err(e, "挂起"),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/unsuspend",
+    summary = "取消暂停",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn unsuspend_mem(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.unsuspend(id).await {
+    match svc.unsuspend(claims.sub, id).await {
         Ok(()) => ok(),
         Err(e) => err(e, "恢复"),
     }
 }
 
-pub async fn reset_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    post,
+    path = "/{id}/reset",
+    summary = "重置为新卡",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn reset_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.reset(id).await {
+    match svc.reset(claims.sub, id).await {
         Ok(()) => ok(),
-        Err(e) => err(e, "重置"),
+        Err(e) => e.into_response(),
     }
 }
 
-pub async fn delete_mem(Path(id): Path<i32>, State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除卡片",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn delete_mem(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let svc = &state.mem;
-    match svc.delete(id).await {
+    match svc.delete(claims.sub, id).await {
         Ok(()) => ok(),
-        Err(e) => err(e, "删除"),
+        Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/{id}/mnemonic",
+    summary = "保存助记",
+    request_body = serde_json::Value,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn set_mnemonic(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(body): Json<serde_json::Value>,
 ) -> impl IntoResponse {
     match body.get("content").and_then(|v| v.as_str()) {
         Some(content) => {
             let svc = &state.mem;
-            match svc.set_mnemonic(id, content).await {
+            match svc.set_mnemonic(claims.sub, id, content).await {
                 Ok(()) => ok(),
-                Err(e) => err(e, "保存助记"),
+                Err(e) => e.into_response(),
             }
         }
         None => err("缺少 content 字段", "保存助记"),
     }
 }
 
-/// 优化 FSRS 参数（直接使用 state.db，不属于任一服务）
-pub async fn optimize_params(State(state): State<AppState>) -> impl IntoResponse {
-    let config = MemConfig::load();
-    match optimizer::optimize_fsrs_params(&state.db, &config).await {
-        Ok(Some(params)) => {
-            tracing::info!("FSRS 参数优化完成，共 {} 个参数", params.len());
-            let mut cfg = config;
-            cfg.update_fsrs_params(params.clone()).ok();
-            crate::modules::mem::fsrs::set_global_params(params);
-            Json(serde_json::json!({
-                "ok": true,
-                "params": cfg.fsrs_params,
-                "message": format!("优化完成，得到 {} 个参数", cfg.fsrs_params.len()),
-            }))
-            .into_response()
-        }
-        Ok(None) => Json(serde_json::json!({
-            "ok": false,
-            "message": "数据不足，至少需要 10 条复习记录"
-        }))
-        .into_response(),
-        Err(e) => err(e, "优化"),
+/// 用当前用户的复习记录排队一次 FSRS 参数优化，返回 202 与任务；
+/// 该用户已有排队中的优化时返回同一个任务
+#[utoipa::path(
+    post,
+    path = "/optimize",
+    summary = "用自己的复习记录优化 FSRS 参数（后台任务）",
+    responses((status = 202, description = "已排队", body = JobResponse))
+)]
+pub async fn optimize_params(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let job = NewJob::new(JobKind::FsrsOptimize)
+        .user(claims.sub)
+        .dedupe(optimize_dedupe_key(claims.sub));
+    match state.jobs.enqueue(job).await {
+        Ok(job) => (StatusCode::ACCEPTED, Json(JobResponse::from(job))).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+// ── 调度配置 ──
+
+/// 当前用户的 FSRS 参数与调度配置，从未保存过时为默认值
+#[utoipa::path(
+    get,
+    path = "/config",
+    summary = "FSRS 参数与调度配置",
+    responses((status = 200, description = "成功", body = MemConfig))
+)]
+pub async fn get_config(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    match state.mem.config(claims.sub).await {
+        Ok(config) => Json(config).into_response(),
+        Err(e) => err(e, "获取记忆配置"),
+    }
+}
+
+/// 整体替换当前用户的配置，省略的字段取默认值；只影响此后的调度
+#[utoipa::path(
+    put,
+    path = "/config",
+    summary = "保存 FSRS 参数与调度配置",
+    request_body = MemConfig,
+    responses((status = 200, description = "成功", body = MemConfig))
+)]
+pub async fn update_config(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(config): Json<MemConfig>,
+) -> impl IntoResponse {
+    if let Err(msg) = config.validate() {
+        return error::bad_request(msg);
+    }
+    match state.mem.update_config(claims.sub, &config).await {
+        Ok(()) => Json(config).into_response(),
+        Err(e) => err(e, "保存记忆配置"),
+    }
+}
+
+// ── 卡组预设 ──
+
+#[utoipa::path(
+    get,
+    path = "/preset",
+    summary = "卡组预设列表",
+    responses((status = 200, description = "成功", body = Vec<MemPreset>))
+)]
+pub async fn list_presets(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    error::ok_or(state.mem.presets(claims.sub).await, "获取预设")
+}
+
+#[utoipa::path(
+    post,
+    path = "/preset",
+    summary = "创建卡组预设",
+    request_body = PresetRequest,
+    responses((status = 201, description = "已创建", body = MemPreset))
+)]
+pub async fn create_preset(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(body): Json<PresetRequest>,
+) -> impl IntoResponse {
+    match state.mem.create_preset(claims.sub, &body).await {
+        Ok(preset) => (StatusCode::CREATED, Json(preset)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/preset/{id}",
+    summary = "替换卡组预设",
+    request_body = PresetRequest,
+    responses((status = 200, description = "成功", body = MemPreset))
+)]
+pub async fn update_preset(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(body): Json<PresetRequest>,
+) -> impl IntoResponse {
+    match state.mem.update_preset(claims.sub, id, &body).await {
+        Ok(preset) => Json(preset).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 删除预设，指定了它的标签改为不指定
+#[utoipa::path(
+    delete,
+    path = "/preset/{id}",
+    summary = "删除卡组预设",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn delete_preset(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    error::deleted_or(state.mem.delete_preset(claims.sub, id).await, "删除预设")
+}
+
+#[utoipa::path(
+    put,
+    path = "/tag/{id}/preset",
+    summary = "为标签指定卡组预设（null 取消）",
+    request_body = SetTagPresetRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn set_tag_preset(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(body): Json<SetTagPresetRequest>,
+) -> impl IntoResponse {
+    match state
+        .mem
+        .set_tag_preset(claims.sub, id, body.preset_id)
+        .await
+    {
+        Ok(()) => ok(),
+        Err(e) => e.into_response(),
     }
 }
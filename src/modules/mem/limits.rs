@@ -0,0 +1,390 @@
+//! 每日新卡与复习上限。
+//!
+//! 学习日从用户本地时间（`timezone`，未设置时用固定的 `utc_offset_minutes`）的
+//! `day_rollover_hour` 点开始，已用额度从 revlog 统计：
+//! 当天首次复习的新卡（以及已排入学习队列、尚未复习的卡）计入新卡，
+//! 复习阶段卡片的每次评分计入复习；学习 / 重学步进不受限制。
+//! 用户配置的上限对全部卡片生效，卡组预设的上限另外限制该预设下的卡片。
+
+use chrono::{DateTime, Duration, FixedOffset, Offset, TimeZone, Utc};
+use std::collections::HashMap;
+
+use super::config::MemConfig;
+use super::port::MemRepository;
+use super::preset::{self, MemPreset};
+
+/// 包含 `now` 的学习日的开始时刻
+pub fn day_start(config: &MemConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+    let hour = config.day_rollover_hour.min(23);
+    if let Some(tz) = config
+        .timezone
+        .as_deref()
+        .and_then(|tz| tz.parse::<chrono_tz::Tz>().ok())
+    {
+        return rollover_in(&tz, hour, now);
+    }
+    let offset = FixedOffset::east_opt(config.utc_offset_minutes * 60).unwrap_or(Utc.fix());
+    rollover_in(&offset, hour, now)
+}
+
+/// `starts_at` 所在学习日的下一个学习日的开始时刻。夏令时切换当天学习日为 23 或
+/// 25 小时，不能直接加一天；学习日最长 25 小时，加满后必然落在下一个学习日内
+pub fn day_end(config: &MemConfig, starts_at: DateTime<Utc>) -> DateTime<Utc> {
+    day_start(config, starts_at + Duration::hours(25))
+}
+
+/// 在 `tz` 的本地时间里找 `now` 之前最近的 `hour` 点。该时刻因夏令时被跳过时
+/// 顺延到之后第一个存在的整点，重复出现时取较早的一次
+fn rollover_in<Tz: TimeZone>(tz: &Tz, hour: u32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let local = now.with_timezone(tz).naive_local();
+    let rollover = local.date().and_time(Default::default()) + Duration::hours(hour.into());
+    let start = if local < rollover {
+        rollover - Duration::days(1)
+    } else {
+        rollover
+    };
+    (0..=2)
+        .find_map(|h| {
+            tz.from_local_datetime(&(start + Duration::hours(h)))
+                .earliest()
+        })
+        .map_or(now, |t| t.with_timezone(&Utc))
+}
+
+/// 当天的剩余额度；`take_*` 在给出一张卡时扣减
+#[derive(Debug)]
+pub struct Today {
+    pub starts_at: DateTime<Utc>,
+    new_left: i64,
+    reviews_left: i64,
+    /// 各预设当天已引入的新卡数
+    preset_new: HashMap<i32, i64>,
+    /// 各预设当天已复习的次数
+    preset_reviews: HashMap<i32, i64>,
+}
+
+impl Today {
+    pub async fn load(
+        repo: &dyn MemRepository,
+        user_id: i32,
+        config: &MemConfig,
+        now: DateTime<Utc>,
+    ) -> Result<Self, sqlx::Error> {
+        let starts_at = day_start(config, now);
+        let since = starts_at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let introduced = repo.introduced_since(user_id, &since).await?;
+        let reviewed = repo.reviewed_since(user_id, &since).await?;
+
+        let mut ids: Vec<i32> = introduced.iter().chain(&reviewed).copied().collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let presets = preset::resolve(repo, user_id, &ids).await?;
+        let tally = |mems: &[i32]| {
+            let mut counts = HashMap::new();
+            for p in mems.iter().filter_map(|id| presets.get(id)) {
+                *counts.entry(p.id).or_insert(0) += 1;
+            }
+            counts
+        };
+
+        Ok(Self {
+            starts_at,
+            new_left: (config.new_per_day - introduced.len() as i64).max(0),
+            reviews_left: (config.reviews_per_day - reviewed.len() as i64).max(0),
+            preset_new: tally(&introduced),
+            preset_reviews: tally(&reviewed),
+        })
+    }
+
+    /// 下一个学习日的开始时刻
+    pub fn ends_at(&self, config: &MemConfig) -> DateTime<Utc> {
+        day_end(config, self.starts_at)
+    }
+
+    /// 还可引入的新卡数（不考虑预设上限）
+    pub fn new_left(&self) -> i64 {
+        self.new_left
+    }
+
+    /// 还可复习的次数（不考虑预设上限）
+    pub fn reviews_left(&self) -> i64 {
+        self.reviews_left
+    }
+
+    /// 额度允许时扣减并返回 true；`preset` 为该卡生效的预设
+    pub fn take_new(&mut self, preset: Option<&MemPreset>) -> bool {
+        let cap = preset.and_then(|p| p.options.new_per_day);
+        take(&mut self.new_left, &mut self.preset_new, preset, cap)
+    }
+
+    pub fn take_review(&mut self, preset: Option<&MemPreset>) -> bool {
+        let cap = preset.and_then(|p| p.options.reviews_per_day);
+        take(
+            &mut self.reviews_left,
+            &mut self.preset_reviews,
+            preset,
+            cap,
+        )
+    }
+}
+
+fn take(
+    left: &mut i64,
+    used: &mut HashMap<i32, i64>,
+    preset: Option<&MemPreset>,
+    cap: Option<i64>,
+) -> bool {
+    if *left <= 0 {
+        return false;
+    }
+    if let Some(p) = preset {
+        let n = used.entry(p.id).or_insert(0);
+        if cap.is_some_and(|cap| *n >= cap) {
+            return false;
+        }
+        *n += 1;
+    }
+    *left -= 1;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::modules::mem::MemRepo;
+    use crate::modules::mem::preset::PresetOptions;
+    use std::sync::Arc;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn day_starts_at_local_rollover_hour() {
+        let utc8 = MemConfig {
+            utc_offset_minutes: 480,
+            ..MemConfig::default()
+        };
+        // 本地 2024-05-02 10:00 → 当天 04:00（UTC 前一天 20:00）
+        assert_eq!(
+            day_start(&utc8, at("2024-05-02T02:00:00Z")),
+            at("2024-05-01T20:00:00Z")
+        );
+        // 本地 2024-05-02 03:00 仍属于 5 月 1 日
+        assert_eq!(
+            day_start(&utc8, at("2024-05-01T19:00:00Z")),
+            at("2024-04-30T20:00:00Z")
+        );
+        let west = MemConfig {
+            utc_offset_minutes: -300,
+            day_rollover_hour: 0,
+            ..MemConfig::default()
+        };
+        assert_eq!(
+            day_start(&west, at("2024-05-02T03:00:00Z")),
+            at("2024-05-01T05:00:00Z")
+        );
+    }
+
+    #[test]
+    fn day_start_follows_dst_in_named_timezone() {
+        let berlin = MemConfig {
+            timezone: Some("Europe/Berlin".into()),
+            // 设置了时区时不再使用固定偏移
+            utc_offset_minutes: 480,
+            ..MemConfig::default()
+        };
+        // 2024-03-31 02:00 起改用夏令时（UTC+1 → UTC+2）：前一天 04:00 是 03:00Z，当天是 02:00Z
+        assert_eq!(
+            day_start(&berlin, at("2024-03-30T12:00:00Z")),
+            at("2024-03-30T03:00:00Z")
+        );
+        assert_eq!(
+            day_start(&berlin, at("2024-03-31T12:00:00Z")),
+            at("2024-03-31T02:00:00Z")
+        );
+        // 10-27 03:00 恢复冬令时
+        assert_eq!(
+            day_start(&berlin, at("2024-10-28T12:00:00Z")),
+            at("2024-10-28T03:00:00Z")
+        );
+
+        // 每日开始于 02:00 时，3 月 31 日的 02:00 不存在，顺延到 03:00（01:00Z）
+        let gap = MemConfig {
+            day_rollover_hour: 2,
+            ..berlin
+        };
+        assert_eq!(
+            day_start(&gap, at("2024-03-31T12:00:00Z")),
+            at("2024-03-31T01:00:00Z")
+        );
+    }
+
+    #[test]
+    fn day_end_is_next_rollover_across_dst() {
+        let new_york = MemConfig {
+            timezone: Some("America/New_York".into()),
+            day_rollover_hour: 4,
+            ..MemConfig::default()
+        };
+        // 2024-03-10 02:00 起改用夏令时：3 月 9 日的学习日只有 23 小时
+        let start = day_start(&new_york, at("2024-03-09T12:00:00Z"));
+        assert_eq!(start, at("2024-03-09T09:00:00Z"));
+        assert_eq!(day_end(&new_york, start), at("2024-03-10T08:00:00Z"));
+        // 11-03 02:00 恢复冬令时：11 月 2 日的学习日有 25 小时
+        let start = day_start(&new_york, at("2024-11-02T12:00:00Z"));
+        assert_eq!(start, at("2024-11-02T08:00:00Z"));
+        assert_eq!(day_end(&new_york, start), at("2024-11-03T09:00:00Z"));
+        // 平常日子仍是 24 小时
+        assert_eq!(
+            day_end(&new_york, at("2024-05-01T08:00:00Z")),
+            at("2024-05-02T08:00:00Z")
+        );
+
+        // 固定偏移没有夏令时
+        let utc8 = MemConfig {
+            utc_offset_minutes: 480,
+            ..MemConfig::default()
+        };
+        assert_eq!(
+            day_end(&utc8, at("2024-05-01T20:00:00Z")),
+            at("2024-05-02T20:00:00Z")
+        );
+    }
+
+    #[test]
+    fn preset_caps_apply_within_user_cap() {
+        let capped = MemPreset {
+            id: 1,
+            name: "词汇".into(),
+            priority: 0,
+            options: PresetOptions {
+                new_per_day: Some(1),
+                ..PresetOptions::default()
+            },
+            tag_ids: Vec::new(),
+        };
+        let mut today = Today {
+            starts_at: Utc::now(),
+            new_left: 3,
+            reviews_left: 0,
+            preset_new: HashMap::new(),
+            preset_reviews: HashMap::new(),
+        };
+        assert!(today.take_new(Some(&capped)));
+        assert!(!today.take_new(Some(&capped)));
+        assert!(today.take_new(None));
+        assert!(today.take_new(None));
+        assert!(!today.take_new(None));
+        assert!(!today.take_review(None));
+        assert_eq!(today.new_left(), 0);
+    }
+
+    #[tokio::test]
+    async fn usage_is_counted_from_revlog() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = MemRepo::new(Arc::new(pool.clone()));
+        let mut mems = Vec::new();
+        for _ in 0..5 {
+            let cue = repo.create_chunk(1, "q").await.unwrap();
+            let target = repo.create_chunk(1, "a").await.unwrap();
+            mems.push(repo.create_mem(1, cue, target, &[]).await.unwrap());
+        }
+        let log = |mem: i32, time: &'static str, state: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO revlog (mem_id, review_time, rating, delta_t, state_before)
+                     VALUES (?, ?, 3, 0, ?)",
+                )
+                .bind(mem)
+                .bind(time)
+                .bind(state)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        };
+        // mems[0]：昨天已学过，今天的学习步进不算新卡
+        log(mems[0], "2024-05-01T10:00:00Z", "new").await;
+        log(mems[0], "2024-05-02T05:00:00Z", "learning").await;
+        // mems[1]：今天首次学习，两次评分只算一张
+        log(mems[1], "2024-05-02T05:00:00Z", "new").await;
+        log(mems[1], "2024-05-02T05:10:00Z", "learning").await;
+        // mems[2]：今天复习两次
+        log(mems[2], "2024-05-02T06:00:00Z", "review").await;
+        log(mems[2], "2024-05-02T07:00:00Z", "review").await;
+        // mems[3]：已排入学习队列，尚未复习
+        repo.set_state(1, mems[3], "learning", Some(0))
+            .await
+            .unwrap();
+        // mems[4]：昨天排入学习队列、一直没复习，不占今天的额度
+        sqlx::query(
+            "UPDATE mem SET state = 'learning', queued_at = '2024-05-01T10:00:00Z' WHERE id = ?",
+        )
+        .bind(mems[4])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let config = MemConfig {
+            new_per_day: 5,
+            reviews_per_day: 3,
+            ..MemConfig::default()
+        };
+        let today = Today::load(&repo, 1, &config, at("2024-05-02T12:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(today.starts_at, at("2024-05-02T04:00:00Z"));
+        assert_eq!(today.new_left(), 3);
+        assert_eq!(today.reviews_left(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_due_stops_at_daily_new_limit() {
+        use crate::modules::job::JobService;
+        use crate::modules::mem::service::MemService;
+        use crate::modules::webhook::Webhooks;
+
+        let pool = Arc::new(sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        let repo = Arc::new(MemRepo::new(pool.clone()));
+        for _ in 0..5 {
+            let cue = repo.create_chunk(1, "q").await.unwrap();
+            let target = repo.create_chunk(1, "a").await.unwrap();
+            repo.create_mem(1, cue, target, &[]).await.unwrap();
+        }
+        let config = MemConfig {
+            new_per_day: 2,
+            ..MemConfig::default()
+        };
+        repo.save_config(1, &config).await.unwrap();
+        let jobs = JobService::new(pool.clone());
+        let hooks = Webhooks::new(pool.clone(), jobs.clone());
+        let mem = MemService::new(
+            repo,
+            pool,
+            jobs,
+            hooks,
+            crate::modules::events::ChangeFeed::default(),
+        );
+
+        let due = mem.get_due(1, 10, &[], &[]).await.unwrap();
+        assert_eq!(due.due_count, 2);
+        assert_eq!(due.new_remaining, 0);
+        assert_eq!(due.reviews_remaining, config.reviews_per_day);
+        // 已排入学习队列的卡仍会给出，但不再引入新卡
+        let again = mem.get_due(1, 10, &[], &[]).await.unwrap();
+        assert_eq!(again.due_count, 2);
+    }
+}
@@ -1,62 +1,72 @@
+pub mod anki;
 pub mod config;
 pub mod fsrs;
 pub mod handler;
+pub mod limits;
 pub mod model;
 pub mod optimizer;
 pub mod port;
+pub mod preset;
 pub(crate) mod repository;
 pub(crate) use repository::MemRepo;
 pub mod query;
 pub mod service;
 
+use utoipa_axum::router::{OpenApiRouter, UtoipaMethodRouterExt};
+use utoipa_axum::routes;
+
 use crate::state::AppState;
-use axum::{
-    Router,
-    routing::{delete, get, post, put},
-};
+use axum::extract::DefaultBodyLimit;
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/", post(handler::create_mem))
-        .route("/{id}/edit", put(handler::edit_mem))
-        .route("/all", get(handler::get_all))
-        .route("/due", get(handler::get_due))
-        .route("/counts", get(handler::get_counts))
-        .route("/session-estimate", get(handler::get_session_estimate))
-        .route("/upcoming-counts", get(handler::upcoming_counts))
-        .route("/batch-bury", post(handler::batch_bury))
-        .route("/batch-delete", post(handler::batch_delete))
-        .route("/batch-reset", post(handler::batch_reset))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::create_mem))
+        .routes(routes!(handler::edit_mem))
+        .routes(routes!(handler::get_all))
+        .routes(routes!(handler::get_due))
+        .routes(routes!(handler::get_counts))
+        .routes(routes!(handler::get_session_estimate))
+        .routes(routes!(handler::upcoming_counts))
+        .routes(routes!(handler::batch_bury))
+        .routes(routes!(handler::batch_delete))
+        .routes(routes!(handler::batch_reset))
         // ── 标签 ──
-        .route("/tag/create", post(handler::create_tag))
-        .route("/tag/delete/{id}", delete(handler::delete_tag))
-        .route("/tag/list", get(handler::list_tags))
-        .route("/tag/search", get(handler::search_tags))
-        .route("/tag/mem/{id}", get(handler::get_mem_tags))
-        .route("/tag/mem/add", post(handler::add_mem_tag))
-        .route("/tag/mem/remove", post(handler::remove_mem_tag))
-        .route("/tag/mem/set", post(handler::set_mem_tags))
-        .route("/tag/batch-add", post(handler::batch_add_tag))
-        .route("/tag/batch-remove", post(handler::batch_remove_tag))
-        .route("/tag/batch-set", post(handler::batch_set_tags))
-        .route("/tag/batch-by-ids", post(handler::batch_get_mems_tags))
+        .routes(routes!(handler::create_tag))
+        .routes(routes!(handler::delete_tag))
+        .routes(routes!(handler::list_tags))
+        .routes(routes!(handler::search_tags))
+        .routes(routes!(handler::get_mem_tags))
+        .routes(routes!(handler::add_mem_tag))
+        .routes(routes!(handler::remove_mem_tag))
+        .routes(routes!(handler::set_mem_tags))
+        .routes(routes!(handler::batch_add_tag))
+        .routes(routes!(handler::batch_remove_tag))
+        .routes(routes!(handler::batch_set_tags))
+        .routes(routes!(handler::batch_get_mems_tags))
         // ── CSV / PSV 导入导出 ──
-        .route("/export/csv", get(handler::export_csv))
-        .route("/import/csv", post(handler::import_csv))
-        .route("/import/psv", post(handler::import_psv))
-        .route("/import/json", post(handler::import_json))
-        .route("/{id}/review", post(handler::review_mem))
-        .route("/{id}/undo", post(handler::undo_review))
-        .route("/{id}/preview", get(handler::preview_mem))
-        .route("/{id}/bury", post(handler::bury_mem))
-        .route("/{id}/unbury", post(handler::unbury_mem))
-        .route("/{id}/suspend", post(handler::suspend_mem))
-        .route("/{id}/unsuspend", post(handler::unsuspend_mem))
-        .route("/{id}/reset", post(handler::reset_mem))
-        .route(
-            "/{id}/mnemonic",
-            get(handler::get_mnemonic).put(handler::set_mnemonic),
-        )
-        .route("/{id}", delete(handler::delete_mem))
-        .route("/optimize", post(handler::optimize_params))
+        .routes(routes!(handler::export_csv))
+        .routes(routes!(handler::import_csv))
+        .routes(routes!(handler::import_psv))
+        .routes(routes!(handler::import_json))
+        // ── 完整导入导出（含调度状态与复习记录）──
+        .routes(routes!(handler::export_full))
+        .routes(routes!(handler::import_full).layer(DefaultBodyLimit::max(512 * 1024 * 1024)))
+        // 包逐块写入临时文件，不受 body 大小限制
+        .routes(routes!(handler::import_anki).layer(DefaultBodyLimit::disable()))
+        .routes(routes!(handler::review_mem))
+        .routes(routes!(handler::undo_review))
+        .routes(routes!(handler::preview_mem))
+        .routes(routes!(handler::bury_mem))
+        .routes(routes!(handler::unbury_mem))
+        .routes(routes!(handler::suspend_mem))
+        .routes(routes!(handler::unsuspend_mem))
+        .routes(routes!(handler::reset_mem))
+        .routes(routes!(handler::get_mnemonic, handler::set_mnemonic))
+        .routes(routes!(handler::get_mem, handler::delete_mem))
+        .routes(routes!(handler::optimize_params))
+        .routes(routes!(handler::get_config, handler::update_config))
+        // ── 卡组预设 ──
+        .routes(routes!(handler::list_presets, handler::create_preset))
+        .routes(routes!(handler::update_preset, handler::delete_preset))
+        .routes(routes!(handler::set_tag_preset))
 }
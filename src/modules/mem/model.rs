@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::pagination::CursorParam;
 
 // ── 卡片状态枚举 ──
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CardState {
     New,
@@ -59,7 +62,7 @@ impl FromStr for CardState {
 // ── 数据模型 ──
 
 /// 知识块：Markdown 内容
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Chunk {
     pub id: i32,
     pub content: String,
@@ -67,7 +70,7 @@ pub struct Chunk {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MemWithChunks {
     pub id: i32,
     pub cue: Chunk,
@@ -79,27 +82,38 @@ pub struct MemWithChunks {
     pub lapses: i32,
     pub leeched: bool,
     pub mnemonic: Option<String>,
+    /// 经由标签生效的卡组预设，没有则使用用户配置
+    #[serde(default)]
+    pub preset_id: Option<i32>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct CreateMemRequest {
     pub cue_content: String,
     pub target_content: String,
     pub prerequisites: Vec<i32>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct ReviewRequest {
     pub rating: u8,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct EditMemRequest {
     pub cue_content: String,
     pub target_content: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// 卡片正反面内容；其 ETag（内容版本号）用作 `PUT /mem/{id}/edit` 的 `If-Match`
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct MemContent {
+    pub id: i32,
+    pub cue_content: String,
+    pub target_content: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct UndoRequest {
     pub state: String,
     pub stability: f64,
@@ -110,13 +124,13 @@ pub struct UndoRequest {
     pub due_at: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ReviewResponse {
     pub state: String,
     pub due_at: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct DueResponse {
     pub items: Vec<MemWithChunks>,
     pub due_count: usize,
@@ -125,10 +139,16 @@ pub struct DueResponse {
     pub upcoming_count: usize,
     /// 所有卡的下次复习都在 24h 之后
     pub all_far: bool,
+    /// 除本批外今天还可引入的新卡数
+    pub new_remaining: i64,
+    /// 除本批外今天还可复习的到期卡数
+    pub reviews_remaining: i64,
+    /// 下一个学习日开始、额度重置的时刻
+    pub day_ends_at: String,
 }
 
 /// 各状态计数（与 Anki 底部统计类似）
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct MemCounts {
     pub new: usize,
     pub learning: usize,
@@ -138,7 +158,8 @@ pub struct MemCounts {
 }
 
 /// 管理页查询参数
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct MemQuery {
     pub q: Option<String>,
     pub state: Option<String>,
@@ -150,10 +171,13 @@ pub struct MemQuery {
     pub exclude_tag_ids: Option<String>,
     pub page: Option<i64>,
     pub page_size: Option<i64>,
+    /// 游标分页，见 [`crate::pagination::Pagination::cursor`]
+    #[param(value_type = Option<String>)]
+    pub cursor: Option<CursorParam>,
 }
 
 /// 标签
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TagInfo {
     pub id: i32,
     pub name: String,
@@ -161,7 +185,7 @@ pub struct TagInfo {
 }
 
 /// 标签 + mem_id 联合查询结果（供 get_mems_tags_batch 使用）
-#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
 pub struct MemTagRow {
     pub mem_id: i32,
     pub id: i32,
@@ -170,27 +194,27 @@ pub struct MemTagRow {
 }
 
 /// 创建标签请求
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct CreateTagRequest {
     pub name: String,
 }
 
 /// 给 mem 打标签请求
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct TagMemRequest {
     pub mem_id: i32,
     pub tag_id: i32,
 }
 
 /// 批量设置标签请求
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct SetTagsRequest {
     pub mem_id: i32,
     pub tag_ids: Vec<i32>,
 }
 
 /// JSON 导入的单条记忆
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct JsonMemItem {
     pub cue: String,
     pub target: String,
@@ -198,6 +222,121 @@ pub struct JsonMemItem {
     pub tags: Vec<String>,
 }
 
+/// 带调度状态与复习记录的记忆：完整导出的一项，也用于从 Anki 导入。时间均为 RFC 3339
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScheduledMem {
+    /// 导出时的 id，只用于在同一份导出中引用前置依赖；导入时重新分配
+    #[serde(default)]
+    pub id: i32,
+    pub cue: String,
+    pub target: String,
+    pub state: String,
+    #[serde(default)]
+    pub stability: f64,
+    #[serde(default)]
+    pub difficulty: f64,
+    #[serde(default)]
+    pub step_index: Option<i32>,
+    #[serde(default)]
+    pub buried: bool,
+    #[serde(default)]
+    pub lapses: i32,
+    #[serde(default)]
+    pub leeched: bool,
+    pub due_at: String,
+    #[serde(default)]
+    pub last_review_at: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub mnemonic: Option<String>,
+    /// 须先掌握的 mem（同一份导出中的 id）
+    #[serde(default)]
+    pub prerequisites: Vec<i32>,
+    #[serde(default)]
+    pub revlog: Vec<RevlogEntry>,
+}
+
+impl ScheduledMem {
+    /// 校验导入的一项，返回面向用户的错误信息
+    pub fn validate(&self) -> Result<(), String> {
+        let time = |s: &str| chrono::DateTime::parse_from_rfc3339(s).map(|_| ());
+        if self.cue.trim().is_empty() || self.target.trim().is_empty() {
+            return Err("线索或答案为空".into());
+        }
+        CardState::from_str(&self.state)?;
+        if ![self.stability, self.difficulty]
+            .iter()
+            .all(|v| v.is_finite() && *v >= 0.0)
+        {
+            return Err("stability / difficulty 须为非负数".into());
+        }
+        time(&self.due_at).map_err(|_| format!("due_at 无效: {}", self.due_at))?;
+        if let Some(t) = &self.last_review_at {
+            time(t).map_err(|_| format!("last_review_at 无效: {t}"))?;
+        }
+        for r in &self.revlog {
+            time(&r.review_time).map_err(|_| format!("复习时间无效: {}", r.review_time))?;
+            if !(1..=4).contains(&r.rating) {
+                return Err(format!("评分须为 1–4: {}", r.rating));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 一条复习记录；来源没有的记忆状态为 None
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RevlogEntry {
+    pub review_time: String,
+    pub rating: u8,
+    #[serde(default)]
+    pub delta_t: i32,
+    #[serde(default)]
+    pub stability_before: Option<f64>,
+    #[serde(default)]
+    pub difficulty_before: Option<f64>,
+    #[serde(default)]
+    pub state_before: Option<String>,
+    #[serde(default)]
+    pub stability_after: Option<f64>,
+    #[serde(default)]
+    pub difficulty_after: Option<f64>,
+    #[serde(default)]
+    pub state_after: Option<String>,
+}
+
+pub const MEM_EXPORT_FORMAT: &str = "brainbow-mems";
+pub const MEM_EXPORT_VERSION: u32 = 1;
+
+/// GET /api/mem/export/full 的内容，也是 POST /api/mem/import/full 的请求体
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MemExport {
+    /// 固定为 [`MEM_EXPORT_FORMAT`]
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub mems: Vec<ScheduledMem>,
+}
+
+impl MemExport {
+    /// 检查 id 引用：非 0 的 id 不能重复，前置依赖须指向同一份导出中的 id
+    pub fn check_ids(&self) -> Result<(), String> {
+        let mut ids = std::collections::HashSet::new();
+        for (i, m) in self.mems.iter().enumerate() {
+            if m.id != 0 && !ids.insert(m.id) {
+                return Err(format!("项 {}: id {} 重复", i + 1, m.id));
+            }
+        }
+        for (i, m) in self.mems.iter().enumerate() {
+            if let Some(p) = m.prerequisites.iter().find(|p| !ids.contains(p)) {
+                return Err(format!("项 {}: 前置依赖 {p} 不在导出文件中", i + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// MemWithTags — 供列表用
 #[allow(dead_code)]
 #[derive(Debug, Clone, Serialize)]
@@ -244,13 +383,21 @@ pub struct InsertRevlogParams {
 /// 已复习过的卡即使不到 1 天也返回至少 1，
 /// 确保 FSRS 收到非零 days_elapsed 从而正确更新 stability。
 pub(crate) fn days_elapsed_since(last_review_at: &Option<String>) -> u32 {
+    days_elapsed_between(last_review_at, chrono::Utc::now())
+}
+
+/// 自上次复习到 `now` 的整天数（`now` 更早时为 0）
+pub(crate) fn days_elapsed_between(
+    last_review_at: &Option<String>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> u32 {
     match last_review_at {
         None => 0,
         Some(s) => {
             if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
                 let t_utc = t.with_timezone(&chrono::Utc);
-                let elapsed = chrono::Utc::now() - t_utc;
-                (elapsed.num_seconds() / 86400) as u32
+                let elapsed = now - t_utc;
+                (elapsed.num_seconds().max(0) / 86400) as u32
             } else {
                 0
             }
@@ -267,10 +414,45 @@ pub struct FsrsUpdate {
     pub lapses: i32,
     pub leeched: bool,
     pub due_at: String,
+    /// 本次复习时刻（RFC 3339）
+    pub last_review_at: String,
+}
+
+/// 新建卡片的 id
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CreatedMem {
+    pub id: i32,
+}
+
+/// 即将到期的复习卡数（不含 learning）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UpcomingCounts {
+    pub within_8h: i64,
+    pub within_24h: i64,
+}
+
+/// 四档评分（Again / Hard / Good / Easy）的间隔（秒）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct IntervalPreview {
+    pub intervals: [f64; 4],
+}
+
+/// 助记，未设置时为 null
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Mnemonic {
+    pub content: Option<String>,
+}
+
+/// CSV / PSV / JSON / 完整导入的结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// 跳过的行及原因
+    pub errors: Vec<String>,
 }
 
 /// 本次学习预估
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct SessionEstimate {
     /// 当前到期的总卡数
     pub due_count: usize,
@@ -284,6 +466,11 @@ pub struct SessionEstimate {
 #[derive(Debug)]
 pub enum AppError {
     NotFound,
+    /// `If-Match` 与当前内容版本不符（412），附最新 ETag 与表示
+    PreconditionFailed {
+        etag: String,
+        current: serde_json::Value,
+    },
     Db(sqlx::Error),
 }
 
@@ -297,6 +484,7 @@ impl std::fmt::Display for AppError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AppError::NotFound => write!(f, "not found"),
+            AppError::PreconditionFailed { .. } => write!(f, "precondition failed"),
             AppError::Db(e) => write!(f, "db: {e}"),
         }
     }
@@ -306,6 +494,9 @@ impl AppError {
     pub fn into_response(self) -> axum::response::Response {
         match self {
             AppError::NotFound => crate::error::not_found("记忆项不存在"),
+            AppError::PreconditionFailed { etag, current } => {
+                crate::etag::precondition_failed(&etag, current)
+            }
             AppError::Db(e) => crate::error::internal(e, "数据库操作"),
         }
     }
@@ -313,6 +504,7 @@ impl AppError {
 
 #[cfg(test)]
 mod tests {
+    #![allow(clippy::unwrap_used)]
     use super::*;
 
     #[test]
@@ -380,7 +572,84 @@ mod tests {
             serde_json::to_value(CardState::Relearning).unwrap(),
             serde_json::Value::String("relearning".into())
         );
-        let v: CardState = serde_json::from_value(serde_json::Value::String("suspended".into())).unwrap();
+        let v: CardState =
+            serde_json::from_value(serde_json::Value::String("suspended".into())).unwrap();
         assert_eq!(v, CardState::Suspended);
     }
+
+    #[test]
+    fn scheduled_mem_validate_checks_state_times_and_ratings() {
+        let item: ScheduledMem = serde_json::from_value(serde_json::json!({
+            "cue": "q",
+            "target": "a",
+            "state": "review",
+            "stability": 3.0,
+            "due_at": "2024-01-03T00:00:00Z",
+            "revlog": [{ "review_time": "2024-01-01T00:00:00Z", "rating": 3 }],
+        }))
+        .unwrap();
+        assert!(item.validate().is_ok());
+
+        let mut bad_rating = item.clone();
+        bad_rating.revlog[0].rating = 0;
+        let bad = [
+            ScheduledMem {
+                cue: " ".into(),
+                ..item.clone()
+            },
+            ScheduledMem {
+                state: "reviewed".into(),
+                ..item.clone()
+            },
+            ScheduledMem {
+                stability: f64::NAN,
+                ..item.clone()
+            },
+            ScheduledMem {
+                due_at: "明天".into(),
+                ..item.clone()
+            },
+            bad_rating,
+        ];
+        for m in bad {
+            assert!(m.validate().is_err(), "{m:?}");
+        }
+    }
+
+    #[test]
+    fn mem_export_check_ids_rejects_duplicates_and_dangling_prerequisites() {
+        let mem = |id: i32, prerequisites: Vec<i32>| -> ScheduledMem {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "cue": "q",
+                "target": "a",
+                "state": "new",
+                "due_at": "2024-01-01T00:00:00Z",
+                "prerequisites": prerequisites,
+            }))
+            .unwrap()
+        };
+        let export = |mems: Vec<ScheduledMem>| MemExport {
+            format: MEM_EXPORT_FORMAT.into(),
+            version: MEM_EXPORT_VERSION,
+            exported_at: "2024-01-01T00:00:00Z".into(),
+            mems,
+        };
+        // 没有 id 的项可以有多个，只是不能被引用
+        let ok = export(vec![
+            mem(1, vec![2]),
+            mem(2, vec![]),
+            mem(0, vec![1]),
+            mem(0, vec![]),
+        ]);
+        assert!(ok.check_ids().is_ok());
+
+        let duplicate = export(vec![mem(1, vec![]), mem(1, vec![])]).check_ids();
+        assert!(duplicate.unwrap_err().contains("重复"));
+        let dangling = export(vec![mem(1, vec![]), mem(2, vec![3])]).check_ids();
+        assert!(dangling.unwrap_err().contains("前置依赖 3"));
+        // id 为 0 的项不可引用
+        let unnamed = export(vec![mem(0, vec![]), mem(1, vec![0])]).check_ids();
+        assert!(unnamed.is_err());
+    }
 }
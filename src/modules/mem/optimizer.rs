@@ -1,7 +1,7 @@
 //! FSRS 参数优化器。
 //!
-//! 从 revlog 读取复习记录，调用 fsrs crate 的 `compute_parameters`，
-//! 将优化后的参数写回 MemConfig。
+//! 从 revlog 读取某个用户的复习记录，调用 fsrs crate 的 `compute_parameters`，
+//! 由调用方将优化后的参数写回该用户的 MemConfig。
 
 use std::sync::Arc;
 
@@ -10,16 +10,18 @@ use sqlx::SqlitePool;
 
 use super::config::MemConfig;
 
-/// 从 DB 读取所有复习记录，分组为 FSRSItem 列表
-async fn load_fsrs_items(pool: &SqlitePool) -> Result<Vec<FSRSItem>, sqlx::Error> {
+/// 从 DB 读取用户的全部复习记录，分组为 FSRSItem 列表
+async fn load_fsrs_items(pool: &SqlitePool, user_id: i32) -> Result<Vec<FSRSItem>, sqlx::Error> {
     // 按 mem_id 分组读取
     let rows: Vec<(i32, i32, i32)> = sqlx::query_as(
         r#"
         SELECT mem_id, delta_t, rating
         FROM revlog
+        WHERE mem_id IN (SELECT id FROM mem WHERE user_id = ?)
         ORDER BY mem_id, review_time ASC
         "#,
     )
+    .bind(user_id)
     .fetch_all(pool)
     .await?;
 
@@ -52,14 +54,15 @@ async fn load_fsrs_items(pool: &SqlitePool) -> Result<Vec<FSRSItem>, sqlx::Error
     Ok(items)
 }
 
-/// 执行 FSRS 参数优化。
+/// 用 `user_id` 自己的复习记录执行 FSRS 参数优化。
 ///
 /// 返回优化后的 19 个参数。如果数据不足则返回 Ok(None)。
 pub async fn optimize_fsrs_params(
     pool: &Arc<SqlitePool>,
+    user_id: i32,
     config: &MemConfig,
 ) -> Result<Option<Vec<f32>>, String> {
-    let items = load_fsrs_items(pool)
+    let items = load_fsrs_items(pool, user_id)
         .await
         .map_err(|e| format!("读取复习记录失败: {e}"))?;
 
@@ -82,7 +85,8 @@ pub async fn optimize_fsrs_params(
     let params = fsrs::compute_parameters(input).map_err(|e| format!("优化失败: {e}"))?;
 
     tracing::info!(
-        "FSRS 参数优化完成: 共 {} 条复习记录, 得到 {} 个参数",
+        "用户 {} 的 FSRS 参数优化完成: 共 {} 条复习记录, 得到 {} 个参数",
+        user_id,
         total_reviews,
         params.len()
     );
@@ -1,6 +1,13 @@
 use async_trait::async_trait;
 
-use super::model::{Chunk, FsrsUpdate, InsertRevlogParams, MemQuery, MemRow, MemTagRow, TagInfo};
+use super::config::MemConfig;
+use super::model::{
+    Chunk, FsrsUpdate, InsertRevlogParams, MemContent, MemQuery, MemRow, MemTagRow, ScheduledMem,
+    TagInfo,
+};
+use super::preset::{MemPreset, PresetRequest};
+use crate::etag::Versioned;
+use crate::pagination::{Cursor, Window};
 
 /// Repository interface for the `mem` module.
 ///
@@ -9,105 +16,229 @@ use super::model::{Chunk, FsrsUpdate, InsertRevlogParams, MemQuery, MemRow, MemT
 /// `SqliteMemRepo`) implement it so that the service never touches SQL or
 /// connection pools directly.
 ///
+/// Every query is scoped to one owner: methods take the caller's `user_id`
+/// (`Claims.sub`) and behave as if rows belonging to other users do not exist.
+///
 /// All methods are fallible with `sqlx::Error` for now.  A future phase may
 /// introduce a domain error type to fully decouple the port from sqlx.
 #[async_trait]
 pub trait MemRepository: Send + Sync {
     // ── Chunks ──
 
-    async fn create_chunk(&self, content: &str) -> Result<i32, sqlx::Error>;
-    async fn get_chunk(&self, id: i32) -> Result<Option<Chunk>, sqlx::Error>;
-    async fn update_chunk(&self, id: i32, content: &str) -> Result<(), sqlx::Error>;
+    async fn create_chunk(&self, user_id: i32, content: &str) -> Result<i32, sqlx::Error>;
+    async fn get_chunk(&self, user_id: i32, id: i32) -> Result<Option<Chunk>, sqlx::Error>;
+
+    // ── 正反面内容 ──
+
+    /// 正反面内容及内容版本号
+    async fn get_content(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<Versioned<MemContent>>, sqlx::Error>;
+    /// 改写正反面内容；`expected` 为可接受的内容版本号，不符或 mem 不存在时返回 `None`
+    async fn edit_content(
+        &self,
+        user_id: i32,
+        id: i32,
+        cue: &str,
+        target: &str,
+        expected: Option<&[i64]>,
+    ) -> Result<Option<i64>, sqlx::Error>;
 
     // ── Mem CRUD ──
 
     async fn create_mem(
         &self,
+        user_id: i32,
         cue_id: i32,
         target_id: i32,
         prerequisites: &[i32],
     ) -> Result<i32, sqlx::Error>;
-    async fn get_mem(&self, id: i32) -> Result<Option<MemRow>, sqlx::Error>;
-    async fn delete_mem(&self, id: i32) -> Result<(), sqlx::Error>;
+    async fn get_mem(&self, user_id: i32, id: i32) -> Result<Option<MemRow>, sqlx::Error>;
+    /// 移入回收站
+    async fn delete_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    /// 从回收站恢复，返回是否恢复了记录
+    async fn restore_mem(&self, user_id: i32, id: i32) -> Result<bool, sqlx::Error>;
+    /// 彻底删除回收站中的 mem
+    async fn purge_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    /// 管理列表的一页 mem id，游标模式附下一页游标
     async fn get_all_mems(
         &self,
-        limit: i64,
-        offset: i64,
+        user_id: i32,
+        window: &Window,
         query: &MemQuery,
-    ) -> Result<Vec<i32>, sqlx::Error>;
-    async fn count_all_mems(&self, query: &MemQuery) -> Result<i64, sqlx::Error>;
+    ) -> Result<(Vec<i32>, Option<Cursor>), sqlx::Error>;
+    async fn count_all_mems(&self, user_id: i32, query: &MemQuery) -> Result<i64, sqlx::Error>;
 
     // ── Learning pool ──
 
     async fn get_learning_mems(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error>;
     async fn get_due_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error>;
     async fn get_new_cards(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error>;
     async fn get_upcoming_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error>;
-    async fn count_upcoming(&self) -> Result<i64, sqlx::Error>;
-    async fn count_upcoming_within_hours(&self, hours: i64) -> Result<i64, sqlx::Error>;
-    async fn get_counts(&self) -> Result<(i64, i64, i64, i64, i64), sqlx::Error>;
-    async fn get_next_mem(&self) -> Result<Option<i32>, sqlx::Error>;
+    async fn count_upcoming(&self, user_id: i32) -> Result<i64, sqlx::Error>;
+    async fn count_upcoming_within_hours(
+        &self,
+        user_id: i32,
+        hours: i64,
+    ) -> Result<i64, sqlx::Error>;
+    async fn get_counts(&self, user_id: i32) -> Result<(i64, i64, i64, i64, i64), sqlx::Error>;
+    async fn get_next_mem(&self, user_id: i32) -> Result<Option<i32>, sqlx::Error>;
 
     // ── State updates ──
 
     async fn set_state(
         &self,
+        user_id: i32,
         id: i32,
         state: &str,
         step_index: Option<i32>,
     ) -> Result<(), sqlx::Error>;
-    async fn update_mem_fsrs(&self, id: i32, params: &FsrsUpdate) -> Result<(), sqlx::Error>;
-    async fn bury_mem(&self, id: i32) -> Result<(), sqlx::Error>;
-    async fn unbury_mem(&self, id: i32) -> Result<(), sqlx::Error>;
-    async fn suspend_mem(&self, id: i32) -> Result<(), sqlx::Error>;
-    async fn unsuspend_mem(&self, id: i32) -> Result<(), sqlx::Error>;
-    async fn reset_mem(&self, id: i32) -> Result<(), sqlx::Error>;
-    async fn get_recent_retention(&self, limit: i64) -> Result<f64, sqlx::Error>;
+    async fn update_mem_fsrs(
+        &self,
+        user_id: i32,
+        id: i32,
+        params: &FsrsUpdate,
+    ) -> Result<(), sqlx::Error>;
+    async fn bury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    async fn unbury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    async fn suspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    async fn unsuspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    async fn reset_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
+    async fn get_recent_retention(&self, user_id: i32, limit: i64) -> Result<f64, sqlx::Error>;
 
     // ── Tags ──
 
     async fn create_tag(&self, name: &str, user_id: i32) -> Result<TagInfo, sqlx::Error>;
-    async fn delete_tag(&self, id: i32) -> Result<(), sqlx::Error>;
+    async fn delete_tag(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error>;
     async fn list_tags(&self, user_id: i32) -> Result<Vec<TagInfo>, sqlx::Error>;
     async fn search_tags(&self, user_id: i32, q: &str) -> Result<Vec<TagInfo>, sqlx::Error>;
-    async fn get_mem_tags(&self, mem_id: i32) -> Result<Vec<TagInfo>, sqlx::Error>;
-    async fn add_tag_to_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error>;
-    async fn remove_tag_from_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error>;
-    async fn set_mem_tags(&self, mem_id: i32, tag_ids: &[i32]) -> Result<(), sqlx::Error>;
-    async fn get_mems_tags_batch(&self, mem_ids: &[i32]) -> Result<Vec<MemTagRow>, sqlx::Error>;
+    async fn get_mem_tags(&self, user_id: i32, mem_id: i32) -> Result<Vec<TagInfo>, sqlx::Error>;
+    async fn add_tag_to_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn remove_tag_from_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error>;
+    async fn set_mem_tags(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<(), sqlx::Error>;
+    async fn get_mems_tags_batch(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> Result<Vec<MemTagRow>, sqlx::Error>;
     async fn export_all_mems(
         &self,
+        user_id: i32,
         tag_ids: &[i32],
     ) -> Result<Vec<(String, String, String)>, sqlx::Error>;
 
     // ── Mnemonic ──
 
-    async fn get_mnemonic(&self, mem_id: i32) -> Result<Option<String>, sqlx::Error>;
-    async fn upsert_mnemonic(&self, mem_id: i32, content: &str) -> Result<(), sqlx::Error>;
+    async fn get_mnemonic(&self, user_id: i32, mem_id: i32) -> Result<Option<String>, sqlx::Error>;
+    async fn upsert_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        content: &str,
+    ) -> Result<(), sqlx::Error>;
 
     // ── Revlog (previously direct SQL in service) ──
 
     async fn insert_revlog(&self, params: &InsertRevlogParams) -> Result<(), sqlx::Error>;
-    async fn count_revlogs(&self) -> Result<i64, sqlx::Error>;
+    async fn count_revlogs(&self, user_id: i32) -> Result<i64, sqlx::Error>;
     async fn prune_revlogs(&self) -> Result<(), sqlx::Error>;
-    async fn count_relearning(&self) -> Result<i64, sqlx::Error>;
+    async fn count_relearning(&self, user_id: i32) -> Result<i64, sqlx::Error>;
+
+    // ── Import ──
+
+    /// Inserts mems together with their scheduling state, tags (created on
+    /// demand), mnemonics, prerequisites (by `ScheduledMem::id` within the
+    /// batch) and review history in one transaction; returns the new ids.
+    async fn import_scheduled(
+        &self,
+        user_id: i32,
+        mems: &[ScheduledMem],
+    ) -> Result<Vec<i32>, sqlx::Error>;
+    /// Live mems (optionally limited to `tag_ids`) with everything
+    /// `import_scheduled` needs to restore them.
+    async fn export_scheduled(
+        &self,
+        user_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<Vec<ScheduledMem>, sqlx::Error>;
+
+    // ── Config ──
+
+    /// The user's scheduler config; defaults when none has been saved.
+    async fn get_config(&self, user_id: i32) -> Result<MemConfig, sqlx::Error>;
+    async fn save_config(&self, user_id: i32, config: &MemConfig) -> Result<(), sqlx::Error>;
+
+    // ── Daily limits ──
+
+    /// Mems first reviewed at or after `since`, plus mems queued for learning
+    /// that have never been reviewed.
+    async fn introduced_since(&self, user_id: i32, since: &str) -> Result<Vec<i32>, sqlx::Error>;
+    /// One entry per review of a review-state mem at or after `since`.
+    async fn reviewed_since(&self, user_id: i32, since: &str) -> Result<Vec<i32>, sqlx::Error>;
+
+    // ── Presets ──
+
+    async fn list_presets(&self, user_id: i32) -> Result<Vec<MemPreset>, sqlx::Error>;
+    async fn create_preset(&self, user_id: i32, preset: &PresetRequest)
+    -> Result<i32, sqlx::Error>;
+    /// Returns the number of rows updated (0 when the preset is not the user's).
+    async fn update_preset(
+        &self,
+        user_id: i32,
+        id: i32,
+        preset: &PresetRequest,
+    ) -> Result<u64, sqlx::Error>;
+    async fn delete_preset(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error>;
+    async fn set_tag_preset(
+        &self,
+        user_id: i32,
+        tag_id: i32,
+        preset_id: Option<i32>,
+    ) -> Result<u64, sqlx::Error>;
+    /// Every `(mem_id, preset_id)` pair reachable through the mems' tags.
+    async fn mem_preset_ids(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> Result<Vec<(i32, i32)>, sqlx::Error>;
 }
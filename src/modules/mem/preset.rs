@@ -0,0 +1,302 @@
+//! 卡组预设：按标签指定不同的调度选项与每日上限。
+//!
+//! 预设属于用户，标签可指定一个预设，mem 经由标签得到生效的预设。
+//! 一个 mem 的多个标签指向不同预设时，`priority` 大的优先，相同时取 id 小（先创建）的；
+//! 没有任何标签指定预设的 mem 使用用户自己的配置。
+//! FSRS 参数按用户训练，预设只覆盖步进、毕业间隔与期望回忆率。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use super::config::{self, MemConfig};
+use super::fsrs::SchedulerConfig;
+use super::port::MemRepository;
+
+/// 预设的调度选项与每日上限
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PresetOptions {
+    /// 学习步进（秒）
+    #[serde(default = "config::default_learning_steps")]
+    pub learning_steps: Vec<i64>,
+    /// 重学步进（秒）
+    #[serde(default = "config::default_relearn_steps")]
+    pub relearn_steps: Vec<i64>,
+    /// 毕业最小间隔（秒）
+    #[serde(default = "config::default_graduating_interval")]
+    pub graduating_interval_secs: i64,
+    /// 期望回忆率
+    #[serde(default = "config::default_desired_retention")]
+    pub desired_retention: f64,
+    /// 该预设下每天最多引入的新卡数，None 为不单独限制
+    #[serde(default)]
+    pub new_per_day: Option<i64>,
+    /// 该预设下每天最多复习的到期卡数，None 为不单独限制
+    #[serde(default)]
+    pub reviews_per_day: Option<i64>,
+}
+
+impl Default for PresetOptions {
+    fn default() -> Self {
+        Self {
+            learning_steps: config::default_learning_steps(),
+            relearn_steps: config::default_relearn_steps(),
+            graduating_interval_secs: config::default_graduating_interval(),
+            desired_retention: config::default_desired_retention(),
+            new_per_day: None,
+            reviews_per_day: None,
+        }
+    }
+}
+
+impl PresetOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        MemConfig {
+            fsrs_params: Vec::new(),
+            ..self.apply(&MemConfig::default())
+        }
+        .validate()?;
+        if self.new_per_day.is_some_and(|n| n < 0) || self.reviews_per_day.is_some_and(|n| n < 0) {
+            return Err("每日上限不能为负数".into());
+        }
+        Ok(())
+    }
+
+    /// 以用户配置为底，覆盖预设中的选项（FSRS 参数沿用用户的）
+    pub fn apply(&self, base: &MemConfig) -> MemConfig {
+        MemConfig {
+            learning_steps: self.learning_steps.clone(),
+            relearn_steps: self.relearn_steps.clone(),
+            graduating_interval_secs: self.graduating_interval_secs,
+            desired_retention: self.desired_retention,
+            ..base.clone()
+        }
+    }
+
+    /// 在用户配置基础上调度该预设下的 mem
+    pub fn scheduler(&self, base: &MemConfig) -> SchedulerConfig {
+        self.apply(base).scheduler()
+    }
+}
+
+/// 预设及指定了它的标签
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct MemPreset {
+    pub id: i32,
+    pub name: String,
+    /// mem 的多个标签指向不同预设时，数值大的优先
+    pub priority: i32,
+    pub options: PresetOptions,
+    pub tag_ids: Vec<i32>,
+}
+
+/// POST / PUT /api/mem/preset 请求体；PUT 整体替换
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PresetRequest {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub options: PresetOptions,
+}
+
+impl PresetRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("预设名不能为空".into());
+        }
+        self.options.validate()
+    }
+}
+
+/// PUT /api/mem/tag/{id}/preset 请求体，`preset_id` 为 null 时取消指定
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SetTagPresetRequest {
+    pub preset_id: Option<i32>,
+}
+
+/// 从 mem 的标签所指向的预设中选出生效的一个
+pub fn effective<'a>(candidates: impl IntoIterator<Item = &'a MemPreset>) -> Option<&'a MemPreset> {
+    candidates
+        .into_iter()
+        .max_by_key(|p| (p.priority, std::cmp::Reverse(p.id)))
+}
+
+/// 各 mem 生效的预设；没有预设的 mem 不在结果中
+pub async fn resolve(
+    repo: &dyn MemRepository,
+    user_id: i32,
+    mem_ids: &[i32],
+) -> Result<HashMap<i32, MemPreset>, sqlx::Error> {
+    let pairs = repo.mem_preset_ids(user_id, mem_ids).await?;
+    if pairs.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let presets: HashMap<i32, MemPreset> = repo
+        .list_presets(user_id)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    let mut candidates: HashMap<i32, Vec<&MemPreset>> = HashMap::new();
+    for (mem_id, preset_id) in &pairs {
+        if let Some(p) = presets.get(preset_id) {
+            candidates.entry(*mem_id).or_default().push(p);
+        }
+    }
+    Ok(candidates
+        .into_iter()
+        .filter_map(|(mem_id, list)| effective(list).map(|p| (mem_id, p.clone())))
+        .collect())
+}
+
+/// 调度某个 mem 使用的配置：用户配置叠加其生效的预设
+pub async fn scheduler_for(
+    repo: &dyn MemRepository,
+    user_id: i32,
+    mem_id: i32,
+) -> Result<SchedulerConfig, sqlx::Error> {
+    let base = repo.get_config(user_id).await?;
+    Ok(
+        match resolve(repo, user_id, &[mem_id]).await?.remove(&mem_id) {
+            Some(preset) => preset.options.scheduler(&base),
+            None => base.scheduler(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn preset(id: i32, priority: i32) -> MemPreset {
+        MemPreset {
+            id,
+            name: format!("p{id}"),
+            priority,
+            options: PresetOptions::default(),
+            tag_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn higher_priority_then_older_preset_wins() {
+        let (a, b, c) = (preset(1, 0), preset(2, 5), preset(3, 5));
+        assert_eq!(effective([&a, &b, &c]).unwrap().id, 2);
+        assert_eq!(effective([&c, &a]).unwrap().id, 3);
+        assert_eq!(effective([&a, &preset(4, 0)]).unwrap().id, 1);
+        assert!(effective([]).is_none());
+    }
+
+    #[test]
+    fn options_override_steps_but_keep_user_params() {
+        let base = MemConfig {
+            fsrs_params: vec![0.5; 19],
+            ..MemConfig::default()
+        };
+        let options = PresetOptions {
+            learning_steps: vec![30],
+            desired_retention: 0.8,
+            ..PresetOptions::default()
+        };
+        let cfg = options.scheduler(&base);
+        assert_eq!(cfg.learning_steps, vec![30]);
+        assert_eq!(cfg.desired_retention, 0.8);
+        assert_eq!(cfg.fsrs_params, base.fsrs_params);
+    }
+
+    #[test]
+    fn validate_rejects_bad_options() {
+        let ok = PresetRequest {
+            name: "词汇".into(),
+            priority: 0,
+            options: PresetOptions::default(),
+        };
+        assert!(ok.validate().is_ok());
+        let blank = PresetRequest {
+            name: " ".into(),
+            ..ok.clone()
+        };
+        assert!(blank.validate().is_err());
+        let negative = PresetRequest {
+            options: PresetOptions {
+                new_per_day: Some(-1),
+                ..PresetOptions::default()
+            },
+            ..ok.clone()
+        };
+        assert!(negative.validate().is_err());
+        let no_steps = PresetRequest {
+            options: PresetOptions {
+                relearn_steps: vec![],
+                ..PresetOptions::default()
+            },
+            ..ok
+        };
+        assert!(no_steps.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn mems_resolve_presets_through_tags() {
+        use crate::modules::mem::MemRepo;
+        use std::sync::Arc;
+
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = MemRepo::new(Arc::new(pool));
+        let user = 1;
+        let mut mems = Vec::new();
+        for _ in 0..3 {
+            let cue = repo.create_chunk(user, "q").await.unwrap();
+            let target = repo.create_chunk(user, "a").await.unwrap();
+            mems.push(repo.create_mem(user, cue, target, &[]).await.unwrap());
+        }
+        let vocab = repo.create_tag("词汇", user).await.unwrap().id;
+        let dense = repo.create_tag("技术", user).await.unwrap().id;
+        let request = |name: &str, priority, steps: Vec<i64>| PresetRequest {
+            name: name.into(),
+            priority,
+            options: PresetOptions {
+                learning_steps: steps,
+                ..PresetOptions::default()
+            },
+        };
+        let fast = repo
+            .create_preset(user, &request("快", 0, vec![30]))
+            .await
+            .unwrap();
+        let slow = repo
+            .create_preset(user, &request("慢", 1, vec![600, 3600]))
+            .await
+            .unwrap();
+        repo.set_tag_preset(user, vocab, Some(fast)).await.unwrap();
+        repo.set_tag_preset(user, dense, Some(slow)).await.unwrap();
+        repo.add_tag_to_mem(user, mems[0], vocab).await.unwrap();
+        repo.add_tag_to_mem(user, mems[1], vocab).await.unwrap();
+        repo.add_tag_to_mem(user, mems[1], dense).await.unwrap();
+
+        let resolved = resolve(&repo, user, &mems).await.unwrap();
+        assert_eq!(resolved[&mems[0]].id, fast);
+        // 两个标签都有预设时 priority 大的生效
+        assert_eq!(resolved[&mems[1]].id, slow);
+        assert!(!resolved.contains_key(&mems[2]));
+
+        let cfg = scheduler_for(&repo, user, mems[0]).await.unwrap();
+        assert_eq!(cfg.learning_steps, vec![30]);
+        let cfg = scheduler_for(&repo, user, mems[2]).await.unwrap();
+        assert_eq!(cfg.learning_steps, MemConfig::default().learning_steps);
+
+        // 删除预设后标签不再指定它
+        assert_eq!(repo.delete_preset(user, slow).await.unwrap(), 1);
+        let resolved = resolve(&repo, user, &mems).await.unwrap();
+        assert_eq!(resolved[&mems[1]].id, fast);
+        let presets = repo.list_presets(user).await.unwrap();
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].tag_ids, vec![vocab]);
+    }
+}
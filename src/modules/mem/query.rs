@@ -1,9 +1,11 @@
 use std::sync::Arc;
 
 use crate::batch::BatchDataResponse;
+use crate::etag::Versioned;
 use crate::modules::mem::fsrs;
 use crate::modules::mem::model::*;
 use crate::modules::mem::port::MemRepository;
+use crate::modules::mem::preset;
 use crate::pagination::{PaginatedResponse, Pagination};
 
 /// 查询侧服务——纯读取，无副作用。
@@ -24,26 +26,28 @@ impl MemQueryService {
 
     pub async fn get_all(
         &self,
+        user_id: i32,
         query: &MemQuery,
     ) -> Result<PaginatedResponse<MemWithChunks>, sqlx::Error> {
         let pagination = Pagination {
             page: query.page.unwrap_or(1),
             page_size: query.page_size.unwrap_or(50),
+            cursor: query.cursor.clone(),
         };
-        let (page, page_size) = pagination.clamp();
-        let offset = (page - 1) * page_size;
-        let ids = self.repo.get_all_mems(page_size, offset, query).await?;
-        let items = self.build_items(&ids).await;
-        let total = self.repo.count_all_mems(query).await?;
-        let pagination_ref = &pagination;
-        Ok(PaginatedResponse::new(items, total, pagination_ref))
+        let (ids, next) = self
+            .repo
+            .get_all_mems(user_id, &pagination.window(), query)
+            .await?;
+        let items = self.build_items(user_id, &ids).await;
+        let total = self.repo.count_all_mems(user_id, query).await?;
+        Ok(PaginatedResponse::new(items, total, &pagination).with_cursor(next))
     }
 
     // ── 统计 ──
 
-    pub async fn get_counts(&self) -> Result<MemCounts, sqlx::Error> {
+    pub async fn get_counts(&self, user_id: i32) -> Result<MemCounts, sqlx::Error> {
         let (new_count, learning_count, due_count, buried_count, suspended_count) =
-            self.repo.get_counts().await?;
+            self.repo.get_counts(user_id).await?;
         Ok(MemCounts {
             new: new_count as usize,
             learning: learning_count as usize,
@@ -53,14 +57,14 @@ impl MemQueryService {
         })
     }
 
-    pub async fn get_session_estimate(&self) -> Result<SessionEstimate, sqlx::Error> {
-        let (new_count, learning_count, due_count, _, _) = self.repo.get_counts().await?;
-        let relearning_count = self.repo.count_relearning().await?;
+    pub async fn get_session_estimate(&self, user_id: i32) -> Result<SessionEstimate, sqlx::Error> {
+        let (new_count, learning_count, due_count, _, _) = self.repo.get_counts(user_id).await?;
+        let relearning_count = self.repo.count_relearning(user_id).await?;
         let pure_learning = learning_count - relearning_count;
 
-        let retention = self.repo.get_recent_retention(100).await?;
+        let retention = self.repo.get_recent_retention(user_id, 100).await?;
 
-        let config = crate::modules::mem::config::MemConfig::load();
+        let config = self.repo.get_config(user_id).await?;
         let step_count_learning = config.learning_steps.len();
         let step_count_relearning = config.relearn_steps.len();
 
@@ -100,11 +104,15 @@ impl MemQueryService {
 
     // ── 预览 ──
 
-    pub async fn preview(&self, id: i32) -> Result<[f64; 4], AppError> {
-        let row = self.repo.get_mem(id).await?.ok_or(AppError::NotFound)?;
+    pub async fn preview(&self, user_id: i32, id: i32) -> Result<[f64; 4], AppError> {
+        let row = self
+            .repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         let state: CardState = row.state.parse().unwrap_or(CardState::New);
         let days_elapsed = days_elapsed_since(&row.last_review_at);
-        let config = fsrs::SchedulerConfig::default();
+        let config = preset::scheduler_for(self.repo.as_ref(), user_id, id).await?;
         Ok(fsrs::preview(
             row.stability,
             row.difficulty,
@@ -115,6 +123,16 @@ impl MemQueryService {
         ))
     }
 
+    // ── 内容（编辑前读取，带 ETag） ──
+
+    /// 正反面内容及内容版本号
+    pub async fn content(&self, user_id: i32, id: i32) -> Result<Versioned<MemContent>, AppError> {
+        self.repo
+            .get_content(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)
+    }
+
     // ── 标签查询 ──
 
     pub async fn list_tags(&self, user_id: i32) -> Result<Vec<TagInfo>, AppError> {
@@ -128,12 +146,27 @@ impl MemQueryService {
             .map_err(AppError::Db)
     }
 
-    pub async fn get_mem_tags(&self, mem_id: i32) -> Result<Vec<TagInfo>, AppError> {
-        self.repo.get_mem_tags(mem_id).await.map_err(AppError::Db)
+    pub async fn get_mem_tags(&self, user_id: i32, mem_id: i32) -> Result<Vec<TagInfo>, AppError> {
+        self.repo
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .get_mem_tags(user_id, mem_id)
+            .await
+            .map_err(AppError::Db)
     }
 
-    pub async fn get_mems_tags_batch(&self, mem_ids: &[i32]) -> BatchDataResponse<MemTagRow> {
-        match self.repo.get_mems_tags_batch(mem_ids).await {
+    pub async fn get_mems_tags_batch(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> BatchDataResponse<MemTagRow> {
+        match self.repo.get_mems_tags_batch(user_id, mem_ids).await {
             Ok(items) => BatchDataResponse::all_ok(items),
             Err(e) => BatchDataResponse::from_results(
                 vec![],
@@ -149,10 +182,10 @@ impl MemQueryService {
 
     // ── CSV/PSV 导出 ──
 
-    pub async fn export_csv(&self, tag_ids: &[i32]) -> Result<String, AppError> {
+    pub async fn export_csv(&self, user_id: i32, tag_ids: &[i32]) -> Result<String, AppError> {
         let rows = self
             .repo
-            .export_all_mems(tag_ids)
+            .export_all_mems(user_id, tag_ids)
             .await
             .map_err(AppError::Db)?;
         let mut wtr = csv::WriterBuilder::new()
@@ -174,32 +207,56 @@ impl MemQueryService {
         String::from_utf8(data).map_err(|e| AppError::Db(sqlx::Error::Protocol(e.to_string())))
     }
 
+    /// 完整导出：除内容与标签外还带调度状态、助记、前置依赖与复习记录，
+    /// 可由 `MemService::import_full` 原样恢复
+    pub async fn export_full(&self, user_id: i32, tag_ids: &[i32]) -> Result<MemExport, AppError> {
+        let mems = self
+            .repo
+            .export_scheduled(user_id, tag_ids)
+            .await
+            .map_err(AppError::Db)?;
+        Ok(MemExport {
+            format: MEM_EXPORT_FORMAT.to_string(),
+            version: MEM_EXPORT_VERSION,
+            exported_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            mems,
+        })
+    }
+
     // ── 助记 ──
 
-    pub async fn get_mnemonic(&self, mem_id: i32) -> Result<Option<String>, sqlx::Error> {
-        self.repo.get_mnemonic(mem_id).await
+    pub async fn get_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+    ) -> Result<Option<String>, sqlx::Error> {
+        self.repo.get_mnemonic(user_id, mem_id).await
     }
 
     // ── upcoming ──
 
-    pub async fn upcoming_counts(&self) -> Result<serde_json::Value, sqlx::Error> {
-        let h8 = self.repo.count_upcoming_within_hours(8).await?;
-        let h24 = self.repo.count_upcoming_within_hours(24).await?;
-        Ok(serde_json::json!({"within_8h": h8, "within_24h": h24}))
+    pub async fn upcoming_counts(&self, user_id: i32) -> Result<UpcomingCounts, sqlx::Error> {
+        Ok(UpcomingCounts {
+            within_8h: self.repo.count_upcoming_within_hours(user_id, 8).await?,
+            within_24h: self.repo.count_upcoming_within_hours(user_id, 24).await?,
+        })
     }
 
     // ── 内部辅助 ──
 
-    async fn build_items(&self, ids: &[i32]) -> Vec<MemWithChunks> {
+    async fn build_items(&self, user_id: i32, ids: &[i32]) -> Vec<MemWithChunks> {
+        let presets = preset::resolve(self.repo.as_ref(), user_id, ids)
+            .await
+            .unwrap_or_default();
         let mut items = Vec::new();
         for &id in ids {
-            if let Ok(Some(row)) = self.repo.get_mem(id).await
+            if let Ok(Some(row)) = self.repo.get_mem(user_id, id).await
                 && let (Ok(Some(cue)), Ok(Some(target))) = (
-                    self.repo.get_chunk(row.cue_chunk_id).await,
-                    self.repo.get_chunk(row.target_chunk_id).await,
+                    self.repo.get_chunk(user_id, row.cue_chunk_id).await,
+                    self.repo.get_chunk(user_id, row.target_chunk_id).await,
                 )
             {
-                let mnemonic = self.repo.get_mnemonic(id).await.unwrap_or(None);
+                let mnemonic = self.repo.get_mnemonic(user_id, id).await.unwrap_or(None);
                 items.push(MemWithChunks {
                     id: row.id,
                     cue,
@@ -211,6 +268,7 @@ impl MemQueryService {
                     lapses: row.lapses,
                     leeched: row.leeched,
                     mnemonic,
+                    preset_id: presets.get(&id).map(|p| p.id),
                 });
             }
         }
@@ -1,8 +1,15 @@
-use sqlx::{QueryBuilder, SqlitePool};
+use sqlx::{QueryBuilder, Row, SqlitePool};
 use std::sync::Arc;
 
-use super::model::{Chunk, FsrsUpdate, InsertRevlogParams, MemQuery, MemRow, MemTagRow, TagInfo};
+use super::config::MemConfig;
+use super::model::{
+    Chunk, FsrsUpdate, InsertRevlogParams, MemContent, MemQuery, MemRow, MemTagRow, RevlogEntry,
+    ScheduledMem, TagInfo,
+};
 use super::port::MemRepository;
+use super::preset::{MemPreset, PresetRequest};
+use crate::etag::{self, Versioned};
+use crate::pagination::{Cursor, Window};
 use async_trait::async_trait;
 
 #[derive(Debug, sqlx::FromRow)]
@@ -24,18 +31,22 @@ impl MemRepo {
 
     // ── Chunk ──
 
-    pub async fn create_chunk(&self, content: &str) -> Result<i32, sqlx::Error> {
-        sqlx::query_scalar::<_, i32>("INSERT INTO chunk (content) VALUES (?) RETURNING id")
-            .bind(content)
-            .fetch_one(&*self.pool)
-            .await
+    pub async fn create_chunk(&self, user_id: i32, content: &str) -> Result<i32, sqlx::Error> {
+        sqlx::query_scalar::<_, i32>(
+            "INSERT INTO chunk (content, user_id) VALUES (?, ?) RETURNING id",
+        )
+        .bind(content)
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await
     }
 
-    pub async fn get_chunk(&self, id: i32) -> Result<Option<Chunk>, sqlx::Error> {
+    pub async fn get_chunk(&self, user_id: i32, id: i32) -> Result<Option<Chunk>, sqlx::Error> {
         sqlx::query_as::<_, (i32, String, String, String)>(
-            "SELECT id, content, created_at, updated_at FROM chunk WHERE id = ?",
+            "SELECT id, content, created_at, updated_at FROM chunk WHERE id = ? AND user_id = ?",
         )
         .bind(id)
+        .bind(user_id)
         .fetch_optional(&*self.pool)
         .await
         .map(|r| {
@@ -48,54 +59,129 @@ impl MemRepo {
         })
     }
 
-    pub async fn update_chunk(&self, id: i32, content: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE chunk SET content=?, updated_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=?")
-            .bind(content).bind(id).execute(&*self.pool).await?;
-        Ok(())
+    // ── 正反面内容 ──
+
+    /// 正反面内容及内容版本号
+    pub async fn get_content(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<Versioned<MemContent>>, sqlx::Error> {
+        sqlx::query_as::<_, Versioned<MemContent>>(
+            "SELECT m.id, COALESCE(cc.content, '') AS cue_content, COALESCE(ct.content, '') AS target_content, m.version
+             FROM mem m
+             LEFT JOIN chunk cc ON cc.id = m.cue_chunk_id AND cc.user_id = m.user_id
+             LEFT JOIN chunk ct ON ct.id = m.target_chunk_id AND ct.user_id = m.user_id
+             WHERE m.id = ? AND m.user_id = ? AND m.deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// 改写正反面内容，返回新的内容版本号。
+    ///
+    /// 先以带版本条件的 UPDATE 占住 mem 行，版本不符（或 mem 不存在）时不写入并返回 `None`
+    pub async fn edit_content(
+        &self,
+        user_id: i32,
+        id: i32,
+        cue: &str,
+        target: &str,
+        expected: Option<&[i64]>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut qb: QueryBuilder<sqlx::Sqlite> =
+            QueryBuilder::new("UPDATE mem SET version = version + 1 WHERE id = ");
+        qb.push_bind(id)
+            .push(" AND user_id = ")
+            .push_bind(user_id)
+            .push(" AND deleted_at IS NULL");
+        etag::push_guard(&mut qb, expected);
+        qb.push(" RETURNING cue_chunk_id, target_chunk_id");
+        let Some((cue_id, target_id)) = qb
+            .build_query_as::<(i32, i32)>()
+            .fetch_optional(&mut *tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        for (chunk_id, content) in [(cue_id, cue), (target_id, target)] {
+            sqlx::query("UPDATE chunk SET content=?, updated_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=? AND user_id=?")
+                .bind(content)
+                .bind(chunk_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let version = sqlx::query_scalar::<_, i64>("SELECT version FROM mem WHERE id = ?")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(version))
     }
 
     // ── Mem CRUD ──
 
     pub async fn create_mem(
         &self,
+        user_id: i32,
         cue_id: i32,
         target_id: i32,
         prerequisites: &[i32],
     ) -> Result<i32, sqlx::Error> {
         let mem_id = sqlx::query_scalar::<_, i32>(
-            "INSERT INTO mem (cue_chunk_id, target_chunk_id) VALUES (?, ?) RETURNING id",
+            "INSERT INTO mem (cue_chunk_id, target_chunk_id, user_id) VALUES (?, ?, ?) RETURNING id",
         )
         .bind(cue_id)
         .bind(target_id)
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await?;
+        // 只允许依赖同一用户的 mem
         for &req_id in prerequisites {
             sqlx::query(
-                "INSERT OR IGNORE INTO mem_prerequisite (mem_id, requires_mem_id) VALUES (?, ?)",
+                "INSERT OR IGNORE INTO mem_prerequisite (mem_id, requires_mem_id) SELECT ?, id FROM mem WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
             )
             .bind(mem_id)
             .bind(req_id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         }
         Ok(mem_id)
     }
 
-    pub async fn get_mem(&self, id: i32) -> Result<Option<MemRow>, sqlx::Error> {
+    pub async fn get_mem(&self, user_id: i32, id: i32) -> Result<Option<MemRow>, sqlx::Error> {
         sqlx::query_as::<_, MemRow>(
-            "SELECT id, cue_chunk_id, target_chunk_id, state, stability, difficulty, step_index, buried, lapses, leeched, due_at, last_review_at FROM mem WHERE id = ?",
-        ).bind(id).fetch_optional(&*self.pool).await
+            "SELECT id, cue_chunk_id, target_chunk_id, state, stability, difficulty, step_index, buried, lapses, leeched, due_at, last_review_at FROM mem WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ).bind(id).bind(user_id).fetch_optional(&*self.pool).await
     }
 
     pub async fn get_all_mems(
         &self,
-        limit: i64,
-        offset: i64,
+        user_id: i32,
+        window: &Window,
         query: &MemQuery,
-    ) -> Result<Vec<i32>, sqlx::Error> {
-        let mut qb: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new(
-            "SELECT m.id FROM mem m LEFT JOIN chunk cc ON m.cue_chunk_id = cc.id LEFT JOIN chunk ct ON m.target_chunk_id = ct.id WHERE 1=1",
-        );
+    ) -> Result<(Vec<i32>, Option<Cursor>), sqlx::Error> {
+        // 排序键；id 作为次序键保证翻页稳定
+        let sort_key = match query.sort.as_deref() {
+            Some("difficulty") => "m.difficulty",
+            Some("cue.created_at") => "cc.created_at",
+            Some("state") => "m.state",
+            _ => "m.due_at",
+        };
+        let desc = query.order.as_deref() == Some("desc");
+
+        let mut qb: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new(format!(
+            "SELECT m.id, {sort_key} AS sort_key FROM mem m LEFT JOIN chunk cc ON m.cue_chunk_id = cc.id LEFT JOIN chunk ct ON m.target_chunk_id = ct.id WHERE m.deleted_at IS NULL AND m.user_id = ",
+        ));
+        qb.push_bind(user_id);
 
         if let Some(ref state) = query.state {
             if state == "buried" {
@@ -158,38 +244,25 @@ impl MemRepo {
         }
 
         // 排序
-        match query.sort.as_deref() {
-            Some("difficulty") => {
-                qb.push(" ORDER BY m.difficulty");
-            }
-            Some("cue.created_at") => {
-                qb.push(" ORDER BY cc.created_at");
-            }
-            Some("state") => {
-                qb.push(" ORDER BY m.state");
-            }
-            _ => {
-                qb.push(" ORDER BY m.due_at");
-            }
-        }
-        if query.order.as_deref() == Some("desc") {
-            qb.push(" DESC");
-        } else {
-            qb.push(" ASC");
-        }
-
-        qb.push(" LIMIT ");
-        qb.push_bind(limit);
-        qb.push(" OFFSET ");
-        qb.push_bind(offset);
+        window.push_after(&mut qb, sort_key, "m.id", desc);
+        let dir = if desc { "DESC" } else { "ASC" };
+        qb.push(format!(" ORDER BY {sort_key} {dir}, m.id {dir}"));
+        window.push_limit(&mut qb);
 
-        qb.build_query_scalar().fetch_all(&*self.pool).await
+        let rows = qb.build().fetch_all(&*self.pool).await?;
+        let (rows, next) = window.finish(rows, "sort_key", "id")?;
+        let ids = rows
+            .iter()
+            .map(|r| r.try_get("id"))
+            .collect::<Result<_, _>>()?;
+        Ok((ids, next))
     }
 
-    pub async fn count_all_mems(&self, query: &MemQuery) -> Result<i64, sqlx::Error> {
+    pub async fn count_all_mems(&self, user_id: i32, query: &MemQuery) -> Result<i64, sqlx::Error> {
         let mut qb: QueryBuilder<sqlx::Sqlite> = QueryBuilder::new(
-            "SELECT COUNT(*) FROM mem m LEFT JOIN chunk cc ON m.cue_chunk_id = cc.id LEFT JOIN chunk ct ON m.target_chunk_id = ct.id WHERE 1=1",
+            "SELECT COUNT(*) FROM mem m LEFT JOIN chunk cc ON m.cue_chunk_id = cc.id LEFT JOIN chunk ct ON m.target_chunk_id = ct.id WHERE m.deleted_at IS NULL AND m.user_id = ",
         );
+        qb.push_bind(user_id);
 
         if let Some(ref state) = query.state {
             if state == "buried" {
@@ -254,16 +327,49 @@ impl MemRepo {
         qb.build_query_scalar().fetch_one(&*self.pool).await
     }
 
-    pub async fn delete_mem(&self, id: i32) -> Result<(), sqlx::Error> {
+    /// 移入回收站：只设置 deleted_at，复习记录、标签与前置关系保留以便恢复
+    pub async fn delete_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE mem SET deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        Ok(())
+    }
+
+    /// 从回收站恢复，返回是否恢复了记录
+    pub async fn restore_mem(&self, user_id: i32, id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE mem SET deleted_at = NULL
+             WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 彻底删除回收站中的 mem，连同复习记录、前置关系、孤儿标签与孤儿 chunk
+    pub async fn purge_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
         let mut tx = self.pool.begin().await?;
 
         // 先查出关联的 chunk id，删除 mem 后清理孤儿 chunk
-        let (cue_id, target_id): (i32, i32) =
-            sqlx::query_as("SELECT cue_chunk_id, target_chunk_id FROM mem WHERE id = ?")
-                .bind(id)
-                .fetch_optional(&mut *tx)
-                .await?
-                .ok_or(sqlx::Error::RowNotFound)?;
+        let (cue_id, target_id): (i32, i32) = sqlx::query_as(
+            "SELECT cue_chunk_id, target_chunk_id FROM mem
+             WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
 
         // 级联删除关联数据
         sqlx::query("DELETE FROM revlog WHERE mem_id = ?")
@@ -324,20 +430,22 @@ impl MemRepo {
 
     // ── 学习池 ──
 
-    pub async fn suspend_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE mem SET state='suspended' WHERE id=?")
+    pub async fn suspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE mem SET state='suspended' WHERE id=? AND user_id=?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn unsuspend_mem(&self, id: i32) -> Result<(), sqlx::Error> {
+    pub async fn unsuspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
         // 恢复到新卡状态，保留内容
         sqlx::query(
-            "UPDATE mem SET state='new', stability=0, difficulty=0, step_index=NULL, lapses=0, leeched=0, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=?"
+            "UPDATE mem SET state='new', stability=0, difficulty=0, step_index=NULL, lapses=0, leeched=0, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=? AND user_id=?"
         )
         .bind(id)
+        .bind(user_id)
         .execute(&*self.pool)
         .await?;
         Ok(())
@@ -370,14 +478,16 @@ impl MemRepo {
 
     pub async fn get_learning_mems(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
         let mut qb = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
             r#"SELECT m.id FROM mem m WHERE m.state IN ('learning', 'relearning') AND m.buried = 0 AND m.state != 'suspended'
-              AND m.due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now')"#,
+              AND m.due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now') AND m.deleted_at IS NULL AND m.user_id = "#,
         );
+        qb.push_bind(user_id);
         Self::tag_filter_sql(&mut qb, tag_ids);
         Self::exclude_tag_filter_sql(&mut qb, exclude_tag_ids);
         qb.push(" ORDER BY due_at LIMIT ");
@@ -388,6 +498,7 @@ impl MemRepo {
     /// 获取到期复习卡（保持 review 状态，不转为 learning）
     pub async fn get_due_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
@@ -396,8 +507,10 @@ impl MemRepo {
             r#"SELECT m.id FROM mem m
             WHERE m.state = 'review' AND m.buried = 0 AND m.state != 'suspended'
               AND m.due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
-              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new')"#,
+              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new' AND pm.deleted_at IS NULL)
+              AND m.deleted_at IS NULL AND m.user_id = "#,
         );
+        qb.push_bind(user_id);
         Self::tag_filter_sql(&mut qb, tag_ids);
         Self::exclude_tag_filter_sql(&mut qb, exclude_tag_ids);
         qb.push(" ORDER BY m.due_at LIMIT ");
@@ -408,6 +521,7 @@ impl MemRepo {
     /// 获取新卡（随后由 service 转为 learning 状态）
     pub async fn get_new_cards(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
@@ -415,8 +529,10 @@ impl MemRepo {
         let mut qb = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
             r#"SELECT m.id FROM mem m
             WHERE m.state = 'new' AND m.buried = 0 AND m.state != 'suspended'
-              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new')"#,
+              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new' AND pm.deleted_at IS NULL)
+              AND m.deleted_at IS NULL AND m.user_id = "#,
         );
+        qb.push_bind(user_id);
         Self::tag_filter_sql(&mut qb, tag_ids);
         Self::exclude_tag_filter_sql(&mut qb, exclude_tag_ids);
         qb.push(" ORDER BY RANDOM() LIMIT ");
@@ -427,6 +543,7 @@ impl MemRepo {
     /// 获取将来 review 卡（保持 review 状态，不转为 learning）
     pub async fn get_upcoming_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
@@ -434,60 +551,77 @@ impl MemRepo {
             r#"SELECT m.id FROM mem m
             WHERE m.state = 'review' AND m.buried = 0 AND m.state != 'suspended'
               AND m.due_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
-              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new')"#,
+              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new' AND pm.deleted_at IS NULL)
+              AND m.deleted_at IS NULL AND m.user_id = "#,
         );
+        qb.push_bind(user_id);
         Self::tag_filter_sql(&mut qb, tag_ids);
         qb.push(" ORDER BY m.due_at LIMIT ");
         qb.push_bind(limit);
         qb.build_query_scalar().fetch_all(&*self.pool).await
     }
 
-    pub async fn count_upcoming(&self) -> Result<i64, sqlx::Error> {
+    pub async fn count_upcoming(&self, user_id: i32) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar::<_, i64>(
-            r#"SELECT COUNT(*) FROM mem WHERE state = 'review' AND buried = 0 AND state != 'suspended'"#,
+            r#"SELECT COUNT(*) FROM mem WHERE state = 'review' AND buried = 0 AND state != 'suspended' AND deleted_at IS NULL AND user_id = ?"#,
         )
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await
     }
 
     /// 统计在 N 小时内到期的 review 卡数量（不含 learning）
-    pub async fn count_upcoming_within_hours(&self, hours: i64) -> Result<i64, sqlx::Error> {
+    pub async fn count_upcoming_within_hours(
+        &self,
+        user_id: i32,
+        hours: i64,
+    ) -> Result<i64, sqlx::Error> {
         sqlx::query_scalar::<_, i64>(
             r#"SELECT COUNT(*) FROM mem m
             WHERE m.state IN ('review') AND m.buried = 0
               AND m.due_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
               AND m.due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now', '+' || ? || ' hours')
-              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new')"#,
+              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new' AND pm.deleted_at IS NULL)
+              AND m.deleted_at IS NULL AND m.user_id = ?"#,
         )
         .bind(hours)
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await
     }
 
-    pub async fn get_counts(&self) -> Result<(i64, i64, i64, i64, i64), sqlx::Error> {
+    pub async fn get_counts(&self, user_id: i32) -> Result<(i64, i64, i64, i64, i64), sqlx::Error> {
         let new_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM mem WHERE state = 'new' AND buried = 0 AND state != 'suspended'",
+            "SELECT COUNT(*) FROM mem WHERE state = 'new' AND buried = 0 AND state != 'suspended' AND deleted_at IS NULL AND user_id = ?",
         )
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await?;
         let learning_count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM mem WHERE state IN ('learning', 'relearning') AND buried = 0 AND state != 'suspended'",
+            "SELECT COUNT(*) FROM mem WHERE state IN ('learning', 'relearning') AND buried = 0 AND state != 'suspended' AND deleted_at IS NULL AND user_id = ?",
         )
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await?;
         let due_count: i64 = sqlx::query_scalar(
             r#"SELECT COUNT(*) FROM mem WHERE state = 'review' AND buried = 0 AND state != 'suspended'
-               AND due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now')"#,
+               AND due_at <= strftime('%Y-%m-%dT%H:%M:%SZ', 'now') AND deleted_at IS NULL AND user_id = ?"#,
         )
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await?;
+        let buried_count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mem WHERE buried = 1 AND deleted_at IS NULL AND user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await?;
+        let suspended_count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mem WHERE state = 'suspended' AND deleted_at IS NULL AND user_id = ?",
+        )
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await?;
-        let buried_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mem WHERE buried = 1")
-            .fetch_one(&*self.pool)
-            .await?;
-        let suspended_count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM mem WHERE state = 'suspended'")
-                .fetch_one(&*self.pool)
-                .await?;
         Ok((
             new_count,
             learning_count,
@@ -497,32 +631,49 @@ impl MemRepo {
         ))
     }
 
-    pub async fn get_next_mem(&self) -> Result<Option<i32>, sqlx::Error> {
+    pub async fn get_next_mem(&self, user_id: i32) -> Result<Option<i32>, sqlx::Error> {
         sqlx::query_scalar::<_, i32>(
             r#"SELECT m.id FROM mem m
             WHERE m.state = 'review' AND m.due_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
-              AND m.buried = 0 AND m.state != 'suspended'
-              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new')
+              AND m.buried = 0 AND m.state != 'suspended' AND m.deleted_at IS NULL AND m.user_id = ?
+              AND NOT EXISTS (SELECT 1 FROM mem_prerequisite mp JOIN mem pm ON mp.requires_mem_id=pm.id WHERE mp.mem_id=m.id AND pm.state='new' AND pm.deleted_at IS NULL)
             ORDER BY m.due_at LIMIT 1"#
-        ).fetch_optional(&*self.pool).await
+        ).bind(user_id).fetch_optional(&*self.pool).await
     }
 
     // ── 更新 ──
 
     pub async fn set_state(
         &self,
+        user_id: i32,
         id: i32,
         state: &str,
         step_index: Option<i32>,
     ) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE mem SET state=?, step_index=?, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=?")
-            .bind(state).bind(step_index).bind(id).execute(&*self.pool).await?;
+        // 新卡排入学习队列时记下时刻，供每日新卡额度统计
+        sqlx::query(
+            "UPDATE mem SET state=?1, step_index=?2, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now'),
+                queued_at = CASE WHEN state = 'new' AND ?1 = 'learning'
+                    THEN strftime('%Y-%m-%dT%H:%M:%SZ', 'now') ELSE queued_at END
+             WHERE id=?3 AND user_id=?4",
+        )
+        .bind(state)
+        .bind(step_index)
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
         Ok(())
     }
 
-    pub async fn update_mem_fsrs(&self, id: i32, params: &FsrsUpdate) -> Result<(), sqlx::Error> {
+    pub async fn update_mem_fsrs(
+        &self,
+        user_id: i32,
+        id: i32,
+        params: &FsrsUpdate,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
-            "UPDATE mem SET state=?, stability=?, difficulty=?, step_index=?, lapses=?, leeched=?, due_at=?, last_review_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=?",
+            "UPDATE mem SET state=?, stability=?, difficulty=?, step_index=?, lapses=?, leeched=?, due_at=?, last_review_at=? WHERE id=? AND user_id=?",
         )
         .bind(&params.state)
         .bind(params.stability)
@@ -531,30 +682,36 @@ impl MemRepo {
         .bind(params.lapses)
         .bind(params.leeched)
         .bind(&params.due_at)
+        .bind(&params.last_review_at)
         .bind(id)
+        .bind(user_id)
         .execute(&*self.pool).await?;
         Ok(())
     }
 
-    pub async fn bury_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE mem SET buried = 1 WHERE id = ?")
+    pub async fn bury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE mem SET buried = 1 WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn unbury_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE mem SET buried = 0 WHERE id = ?")
+    pub async fn unbury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE mem SET buried = 0 WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn get_recent_retention(&self, limit: i64) -> Result<f64, sqlx::Error> {
-        let ratings: Vec<i64> =
-            sqlx::query_scalar("SELECT rating FROM revlog ORDER BY review_time DESC LIMIT ?")
+    pub async fn get_recent_retention(&self, user_id: i32, limit: i64) -> Result<f64, sqlx::Error> {
+        let ratings: Vec<i64> = sqlx::query_scalar(
+            "SELECT r.rating FROM revlog r JOIN mem m ON m.id = r.mem_id WHERE m.user_id = ? ORDER BY r.review_time DESC LIMIT ?",
+        )
+                .bind(user_id)
                 .bind(limit)
                 .fetch_all(&*self.pool)
                 .await?;
@@ -585,9 +742,10 @@ impl MemRepo {
         })
     }
 
-    pub async fn delete_tag(&self, id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM tag WHERE id = ?")
+    pub async fn delete_tag(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM tag WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.pool)
             .await?;
         Ok(())
@@ -597,7 +755,8 @@ impl MemRepo {
         let rows = sqlx::query_as::<_, TagRow>(
             "SELECT t.id, t.name, t.created_at FROM tag t
              WHERE t.user_id = ?
-               AND EXISTS (SELECT 1 FROM mem_tag WHERE tag_id = t.id)
+               AND EXISTS (SELECT 1 FROM mem_tag mt JOIN mem m ON m.id = mt.mem_id
+                           WHERE mt.tag_id = t.id AND m.deleted_at IS NULL)
              ORDER BY t.name",
         )
         .bind(user_id)
@@ -634,15 +793,21 @@ impl MemRepo {
             .collect())
     }
 
-    pub async fn get_mem_tags(&self, mem_id: i32) -> Result<Vec<TagInfo>, sqlx::Error> {
+    pub async fn get_mem_tags(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+    ) -> Result<Vec<TagInfo>, sqlx::Error> {
         let rows = sqlx::query_as::<_, TagRow>(
             "SELECT t.id, t.name, t.created_at
              FROM tag t
              JOIN mem_tag mt ON mt.tag_id = t.id
-             WHERE mt.mem_id = ?
+             JOIN mem m ON m.id = mt.mem_id
+             WHERE mt.mem_id = ? AND m.user_id = ?
              ORDER BY t.name",
         )
         .bind(mem_id)
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await?;
         Ok(rows
@@ -655,12 +820,24 @@ impl MemRepo {
             .collect())
     }
 
-    pub async fn add_tag_to_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("INSERT OR IGNORE INTO mem_tag (mem_id, tag_id) VALUES (?, ?)")
-            .bind(mem_id)
-            .bind(tag_id)
-            .execute(&*self.pool)
-            .await?;
+    /// mem 与 tag 都必须属于该用户，否则不做任何事
+    pub async fn add_tag_to_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO mem_tag (mem_id, tag_id)
+             SELECT m.id, t.id FROM mem m, tag t
+             WHERE m.id = ? AND m.user_id = ? AND t.id = ? AND t.user_id = ?",
+        )
+        .bind(mem_id)
+        .bind(user_id)
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
         Ok(())
     }
 
@@ -676,18 +853,38 @@ impl MemRepo {
         Ok(())
     }
 
-    pub async fn remove_tag_from_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error> {
-        sqlx::query("DELETE FROM mem_tag WHERE mem_id = ? AND tag_id = ?")
-            .bind(mem_id)
-            .bind(tag_id)
-            .execute(&*self.pool)
-            .await?;
+    pub async fn remove_tag_from_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM mem_tag WHERE mem_id = ? AND tag_id = ?
+               AND mem_id IN (SELECT id FROM mem WHERE user_id = ?)",
+        )
+        .bind(mem_id)
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
         self.delete_orphan_tag(tag_id).await?;
         Ok(())
     }
 
-    pub async fn set_mem_tags(&self, mem_id: i32, tag_ids: &[i32]) -> Result<(), sqlx::Error> {
+    /// mem 不存在或属于其他用户时返回 `RowNotFound`；其他用户的 tag 被忽略
+    pub async fn set_mem_tags(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<(), sqlx::Error> {
         let mut tx = self.pool.begin().await?;
+        sqlx::query_scalar::<_, i32>("SELECT id FROM mem WHERE id = ? AND user_id = ?")
+            .bind(mem_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
         // 记录移除前的旧标签
         let old_tag_ids: Vec<i32> =
             sqlx::query_scalar("SELECT tag_id FROM mem_tag WHERE mem_id = ?")
@@ -701,11 +898,14 @@ impl MemRepo {
             .await?;
         // 插入新的
         for &tag_id in tag_ids {
-            sqlx::query("INSERT OR IGNORE INTO mem_tag (mem_id, tag_id) VALUES (?, ?)")
-                .bind(mem_id)
-                .bind(tag_id)
-                .execute(&mut *tx)
-                .await?;
+            sqlx::query(
+                "INSERT OR IGNORE INTO mem_tag (mem_id, tag_id) SELECT ?, id FROM tag WHERE id = ? AND user_id = ?",
+            )
+            .bind(mem_id)
+            .bind(tag_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
         }
         tx.commit().await?;
         // 清理孤儿标签
@@ -719,6 +919,7 @@ impl MemRepo {
 
     pub async fn get_mems_tags_batch(
         &self,
+        user_id: i32,
         mem_ids: &[i32],
     ) -> Result<Vec<MemTagRow>, sqlx::Error> {
         if mem_ids.is_empty() {
@@ -728,8 +929,11 @@ impl MemRepo {
             "SELECT mt.mem_id, t.id, t.name, t.created_at
              FROM mem_tag mt
              JOIN tag t ON t.id = mt.tag_id
-             WHERE mt.mem_id IN (",
+             JOIN mem m ON m.id = mt.mem_id
+             WHERE m.user_id = ",
         );
+        qb.push_bind(user_id);
+        qb.push(" AND mt.mem_id IN (");
         let mut separated = qb.separated(", ");
         for &id in mem_ids {
             separated.push_bind(id);
@@ -743,6 +947,7 @@ impl MemRepo {
 
     pub async fn export_all_mems(
         &self,
+        user_id: i32,
         tag_ids: &[i32],
     ) -> Result<Vec<(String, String, String)>, sqlx::Error> {
         let mut qb = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
@@ -750,11 +955,13 @@ impl MemRepo {
                 COALESCE((SELECT GROUP_CONCAT(t.name, '; ') FROM mem_tag mt JOIN tag t ON t.id = mt.tag_id WHERE mt.mem_id = m.id), '') AS tags
              FROM mem m
              JOIN chunk cc ON cc.id = m.cue_chunk_id
-             JOIN chunk ct ON ct.id = m.target_chunk_id"
+             JOIN chunk ct ON ct.id = m.target_chunk_id
+             WHERE m.deleted_at IS NULL AND m.user_id = "
         );
+        qb.push_bind(user_id);
 
         if !tag_ids.is_empty() {
-            qb.push(" WHERE m.id IN (SELECT mem_id FROM mem_tag WHERE tag_id IN (");
+            qb.push(" AND m.id IN (SELECT mem_id FROM mem_tag WHERE tag_id IN (");
             let mut sep = qb.separated(", ");
             for &tid in tag_ids {
                 sep.push_bind(tid);
@@ -768,32 +975,45 @@ impl MemRepo {
             .await
     }
 
-    pub async fn get_mnemonic(&self, mem_id: i32) -> Result<Option<String>, sqlx::Error> {
-        sqlx::query_scalar::<_, String>("SELECT content FROM mem_mnemonic WHERE mem_id = ?")
+    pub async fn get_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT mn.content FROM mem_mnemonic mn JOIN mem m ON m.id = mn.mem_id WHERE mn.mem_id = ? AND m.user_id = ?",
+        )
             .bind(mem_id)
+            .bind(user_id)
             .fetch_optional(&*self.pool)
             .await
     }
 
-    pub async fn upsert_mnemonic(&self, mem_id: i32, content: &str) -> Result<(), sqlx::Error> {
+    pub async fn upsert_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        content: &str,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             INSERT INTO mem_mnemonic (mem_id, content)
-            VALUES (?, ?)
+            SELECT id, ? FROM mem WHERE id = ? AND user_id = ?
             ON CONFLICT(mem_id) DO UPDATE SET content = excluded.content
             "#,
         )
-        .bind(mem_id)
         .bind(content)
+        .bind(mem_id)
+        .bind(user_id)
         .execute(&*self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn reset_mem(&self, id: i32) -> Result<(), sqlx::Error> {
+    pub async fn reset_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
         sqlx::query(
-            "UPDATE mem SET state='new', stability=0, difficulty=0, step_index=NULL, lapses=0, leeched=0, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=?"
-        ).bind(id).execute(&*self.pool).await?;
+            "UPDATE mem SET state='new', stability=0, difficulty=0, step_index=NULL, lapses=0, leeched=0, due_at=strftime('%Y-%m-%dT%H:%M:%SZ', 'now') WHERE id=? AND user_id=?"
+        ).bind(id).bind(user_id).execute(&*self.pool).await?;
         Ok(())
     }
 
@@ -823,10 +1043,13 @@ impl MemRepo {
         Ok(())
     }
 
-    pub async fn count_revlogs(&self) -> Result<i64, sqlx::Error> {
-        sqlx::query_scalar("SELECT COUNT(*) FROM revlog")
-            .fetch_one(&*self.pool)
-            .await
+    pub async fn count_revlogs(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM revlog WHERE mem_id IN (SELECT id FROM mem WHERE user_id = ?)",
+        )
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await
     }
 
     pub async fn prune_revlogs(&self) -> Result<(), sqlx::Error> {
@@ -857,10 +1080,434 @@ impl MemRepo {
         Ok(())
     }
 
-    pub async fn count_relearning(&self) -> Result<i64, sqlx::Error> {
-        sqlx::query_scalar("SELECT COUNT(*) FROM mem WHERE state = 'relearning' AND buried = 0")
-            .fetch_one(&*self.pool)
-            .await
+    pub async fn count_relearning(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mem WHERE state = 'relearning' AND buried = 0 AND deleted_at IS NULL AND user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    // ── Import ──
+
+    pub async fn import_scheduled(
+        &self,
+        user_id: i32,
+        mems: &[ScheduledMem],
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut tag_ids: std::collections::HashMap<&str, i32> = std::collections::HashMap::new();
+        let mut ids = Vec::with_capacity(mems.len());
+        for m in mems {
+            let chunk = |content: &str| {
+                sqlx::query_scalar::<_, i32>(
+                    "INSERT INTO chunk (content, user_id) VALUES (?, ?) RETURNING id",
+                )
+                .bind(content.to_string())
+                .bind(user_id)
+            };
+            let cue_id = chunk(&m.cue).fetch_one(&mut *tx).await?;
+            let target_id = chunk(&m.target).fetch_one(&mut *tx).await?;
+            let mem_id = sqlx::query_scalar::<_, i32>(
+                "INSERT INTO mem (cue_chunk_id, target_chunk_id, user_id, state, stability, difficulty,
+                    step_index, buried, lapses, leeched, due_at, last_review_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
+            )
+            .bind(cue_id)
+            .bind(target_id)
+            .bind(user_id)
+            .bind(&m.state)
+            .bind(m.stability)
+            .bind(m.difficulty)
+            .bind(m.step_index)
+            .bind(m.buried)
+            .bind(m.lapses)
+            .bind(m.leeched)
+            .bind(&m.due_at)
+            .bind(&m.last_review_at)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            for name in &m.tags {
+                let tag_id = match tag_ids.get(name.as_str()) {
+                    Some(&id) => id,
+                    None => {
+                        sqlx::query("INSERT OR IGNORE INTO tag (name, user_id) VALUES (?, ?)")
+                            .bind(name)
+                            .bind(user_id)
+                            .execute(&mut *tx)
+                            .await?;
+                        let id: i32 =
+                            sqlx::query_scalar("SELECT id FROM tag WHERE name = ? AND user_id = ?")
+                                .bind(name)
+                                .bind(user_id)
+                                .fetch_one(&mut *tx)
+                                .await?;
+                        tag_ids.insert(name, id);
+                        id
+                    }
+                };
+                sqlx::query("INSERT OR IGNORE INTO mem_tag (mem_id, tag_id) VALUES (?, ?)")
+                    .bind(mem_id)
+                    .bind(tag_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            for r in &m.revlog {
+                sqlx::query(
+                    "INSERT INTO revlog (mem_id, review_time, rating, delta_t,
+                        stability_before, difficulty_before, state_before,
+                        stability_after, difficulty_after, state_after)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(mem_id)
+                .bind(&r.review_time)
+                .bind(r.rating as i32)
+                .bind(r.delta_t)
+                .bind(r.stability_before)
+                .bind(r.difficulty_before)
+                .bind(&r.state_before)
+                .bind(r.stability_after)
+                .bind(r.difficulty_after)
+                .bind(&r.state_after)
+                .execute(&mut *tx)
+                .await?;
+            }
+            if let Some(content) = &m.mnemonic {
+                sqlx::query("INSERT INTO mem_mnemonic (mem_id, content) VALUES (?, ?)")
+                    .bind(mem_id)
+                    .bind(content)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            ids.push(mem_id);
+        }
+
+        // 前置依赖引用同一批中的 id，全部插入后再按新 id 写入
+        let new_ids: std::collections::HashMap<i32, i32> = mems
+            .iter()
+            .map(|m| m.id)
+            .zip(ids.iter().copied())
+            .filter(|&(old, _)| old != 0)
+            .collect();
+        for (m, &mem_id) in mems.iter().zip(&ids) {
+            for required in m.prerequisites.iter().filter_map(|old| new_ids.get(old)) {
+                sqlx::query(
+                    "INSERT OR IGNORE INTO mem_prerequisite (mem_id, requires_mem_id)
+                     VALUES (?, ?)",
+                )
+                .bind(mem_id)
+                .bind(required)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    /// 完整导出：未删除的 mem 及其调度状态、标签、助记、前置依赖与复习记录。
+    /// `tag_ids` 非空时只导出带这些标签之一的 mem，前置依赖只保留导出范围内的
+    pub async fn export_scheduled(
+        &self,
+        user_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<Vec<ScheduledMem>, sqlx::Error> {
+        let mut qb = QueryBuilder::<sqlx::Sqlite>::new(
+            "SELECT m.id, cc.content AS cue, ct.content AS target, m.state, m.stability,
+                m.difficulty, m.step_index, m.buried, m.lapses, m.leeched, m.due_at,
+                m.last_review_at, mn.content AS mnemonic
+             FROM mem m
+             JOIN chunk cc ON cc.id = m.cue_chunk_id
+             JOIN chunk ct ON ct.id = m.target_chunk_id
+             LEFT JOIN mem_mnemonic mn ON mn.mem_id = m.id
+             WHERE m.deleted_at IS NULL AND m.user_id = ",
+        );
+        qb.push_bind(user_id);
+        if !tag_ids.is_empty() {
+            qb.push(" AND m.id IN (SELECT mem_id FROM mem_tag WHERE tag_id IN (");
+            let mut sep = qb.separated(", ");
+            for &tid in tag_ids {
+                sep.push_bind(tid);
+            }
+            qb.push("))");
+        }
+        qb.push(" ORDER BY m.id");
+        let rows = qb.build().fetch_all(&*self.pool).await?;
+
+        let mut mems = Vec::with_capacity(rows.len());
+        let mut index = std::collections::HashMap::new();
+        for row in rows {
+            index.insert(row.try_get::<i32, _>("id")?, mems.len());
+            mems.push(ScheduledMem {
+                id: row.try_get("id")?,
+                cue: row.try_get("cue")?,
+                target: row.try_get("target")?,
+                state: row.try_get("state")?,
+                stability: row.try_get::<Option<f64>, _>("stability")?.unwrap_or(0.0),
+                difficulty: row.try_get::<Option<f64>, _>("difficulty")?.unwrap_or(0.0),
+                step_index: row.try_get("step_index")?,
+                buried: row.try_get("buried")?,
+                lapses: row.try_get("lapses")?,
+                leeched: row.try_get("leeched")?,
+                due_at: row.try_get("due_at")?,
+                last_review_at: row.try_get("last_review_at")?,
+                tags: Vec::new(),
+                mnemonic: row.try_get("mnemonic")?,
+                prerequisites: Vec::new(),
+                revlog: Vec::new(),
+            });
+        }
+
+        let tags: Vec<(i32, String)> = sqlx::query_as(
+            "SELECT mt.mem_id, t.name FROM mem_tag mt
+             JOIN tag t ON t.id = mt.tag_id
+             JOIN mem m ON m.id = mt.mem_id
+             WHERE m.user_id = ? ORDER BY t.name",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        for (mem_id, name) in tags {
+            if let Some(&i) = index.get(&mem_id) {
+                mems[i].tags.push(name);
+            }
+        }
+
+        let prerequisites: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT mem_id, requires_mem_id FROM mem_prerequisite
+             WHERE mem_id IN (SELECT id FROM mem WHERE user_id = ?)
+             ORDER BY mem_id, requires_mem_id",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        for (mem_id, required) in prerequisites {
+            if let Some(&i) = index.get(&mem_id)
+                && index.contains_key(&required)
+            {
+                mems[i].prerequisites.push(required);
+            }
+        }
+
+        let revlog = sqlx::query(
+            "SELECT mem_id, review_time, rating, delta_t, stability_before, difficulty_before,
+                state_before, stability_after, difficulty_after, state_after
+             FROM revlog WHERE mem_id IN (SELECT id FROM mem WHERE user_id = ?)
+             ORDER BY mem_id, review_time, id",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        for row in revlog {
+            let Some(&i) = index.get(&row.try_get::<i32, _>("mem_id")?) else {
+                continue;
+            };
+            mems[i].revlog.push(RevlogEntry {
+                review_time: row.try_get("review_time")?,
+                rating: row.try_get::<i32, _>("rating")? as u8,
+                delta_t: row.try_get("delta_t")?,
+                stability_before: row.try_get("stability_before")?,
+                difficulty_before: row.try_get("difficulty_before")?,
+                state_before: row.try_get("state_before")?,
+                stability_after: row.try_get("stability_after")?,
+                difficulty_after: row.try_get("difficulty_after")?,
+                state_after: row.try_get("state_after")?,
+            });
+        }
+        Ok(mems)
+    }
+
+    // ── Config ──
+
+    pub async fn get_config(&self, user_id: i32) -> Result<MemConfig, sqlx::Error> {
+        let stored: Option<String> =
+            sqlx::query_scalar("SELECT config FROM mem_config WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&*self.pool)
+                .await?;
+        match stored {
+            Some(json) => serde_json::from_str(&json).map_err(|e| sqlx::Error::Decode(e.into())),
+            None => Ok(MemConfig::default()),
+        }
+    }
+
+    pub async fn save_config(&self, user_id: i32, config: &MemConfig) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(config).map_err(|e| sqlx::Error::Encode(e.into()))?;
+        sqlx::query(
+            "INSERT INTO mem_config (user_id, config) VALUES (?, ?)
+             ON CONFLICT(user_id) DO UPDATE SET config = excluded.config,
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')",
+        )
+        .bind(user_id)
+        .bind(json)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // ── 每日上限 ──
+
+    /// `since` 之后首次复习的新卡，加上 `since` 之后排入学习队列、尚未复习过的卡
+    pub async fn introduced_since(
+        &self,
+        user_id: i32,
+        since: &str,
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT DISTINCT r.mem_id FROM revlog r JOIN mem m ON m.id = r.mem_id
+             WHERE m.user_id = ?1 AND r.review_time >= ?2 AND r.state_before IN ('new', 'learning')
+               AND NOT EXISTS (SELECT 1 FROM revlog p WHERE p.mem_id = r.mem_id AND p.review_time < ?2)
+             UNION
+             SELECT id FROM mem
+             WHERE user_id = ?1 AND state = 'learning' AND deleted_at IS NULL AND queued_at >= ?2
+               AND NOT EXISTS (SELECT 1 FROM revlog WHERE revlog.mem_id = mem.id)",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// `since` 之后每次复习（不含学习 / 重学步进）对应的 mem，同一张卡可出现多次
+    pub async fn reviewed_since(&self, user_id: i32, since: &str) -> Result<Vec<i32>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT r.mem_id FROM revlog r JOIN mem m ON m.id = r.mem_id
+             WHERE m.user_id = ? AND r.review_time >= ? AND r.state_before = 'review'",
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    // ── Presets ──
+
+    pub async fn list_presets(&self, user_id: i32) -> Result<Vec<MemPreset>, sqlx::Error> {
+        let rows: Vec<(i32, String, i32, String)> = sqlx::query_as(
+            "SELECT id, name, priority, options FROM mem_preset WHERE user_id = ? ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        let tags: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT preset_id, id FROM tag WHERE user_id = ? AND preset_id IS NOT NULL ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|(id, name, priority, options)| {
+                Ok(MemPreset {
+                    id,
+                    name,
+                    priority,
+                    options: serde_json::from_str(&options)
+                        .map_err(|e| sqlx::Error::Decode(e.into()))?,
+                    tag_ids: tags
+                        .iter()
+                        .filter(|(p, _)| *p == id)
+                        .map(|(_, t)| *t)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn create_preset(
+        &self,
+        user_id: i32,
+        preset: &PresetRequest,
+    ) -> Result<i32, sqlx::Error> {
+        let options =
+            serde_json::to_string(&preset.options).map_err(|e| sqlx::Error::Encode(e.into()))?;
+        sqlx::query_scalar(
+            "INSERT INTO mem_preset (user_id, name, priority, options) VALUES (?, ?, ?, ?) RETURNING id",
+        )
+        .bind(user_id)
+        .bind(preset.name.trim())
+        .bind(preset.priority)
+        .bind(options)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn update_preset(
+        &self,
+        user_id: i32,
+        id: i32,
+        preset: &PresetRequest,
+    ) -> Result<u64, sqlx::Error> {
+        let options =
+            serde_json::to_string(&preset.options).map_err(|e| sqlx::Error::Encode(e.into()))?;
+        let result = sqlx::query(
+            "UPDATE mem_preset SET name = ?, priority = ?, options = ? WHERE id = ? AND user_id = ?",
+        )
+        .bind(preset.name.trim())
+        .bind(preset.priority)
+        .bind(options)
+        .bind(id)
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除预设，指定了它的标签改回不指定
+    pub async fn delete_preset(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE tag SET preset_id = NULL WHERE preset_id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM mem_preset WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 为标签指定预设；`preset_id` 须属于同一用户（由调用方检查）
+    pub async fn set_tag_preset(
+        &self,
+        user_id: i32,
+        tag_id: i32,
+        preset_id: Option<i32>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("UPDATE tag SET preset_id = ? WHERE id = ? AND user_id = ?")
+            .bind(preset_id)
+            .bind(tag_id)
+            .bind(user_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// mem 经由标签关联到的全部预设 `(mem_id, preset_id)`，未去重、未排序
+    pub async fn mem_preset_ids(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> Result<Vec<(i32, i32)>, sqlx::Error> {
+        if mem_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::new(
+            "SELECT mt.mem_id, t.preset_id FROM mem_tag mt JOIN tag t ON t.id = mt.tag_id
+             WHERE t.preset_id IS NOT NULL AND t.user_id = ",
+        );
+        qb.push_bind(user_id).push(" AND mt.mem_id IN (");
+        let mut list = qb.separated(", ");
+        for id in mem_ids {
+            list.push_bind(*id);
+        }
+        qb.push(")");
+        qb.build_query_as().fetch_all(&*self.pool).await
     }
 }
 
@@ -868,118 +1515,156 @@ impl MemRepo {
 
 #[async_trait]
 impl MemRepository for MemRepo {
-    async fn create_chunk(&self, content: &str) -> Result<i32, sqlx::Error> {
-        self.create_chunk(content).await
+    async fn create_chunk(&self, user_id: i32, content: &str) -> Result<i32, sqlx::Error> {
+        self.create_chunk(user_id, content).await
     }
-    async fn get_chunk(&self, id: i32) -> Result<Option<Chunk>, sqlx::Error> {
-        self.get_chunk(id).await
+    async fn get_chunk(&self, user_id: i32, id: i32) -> Result<Option<Chunk>, sqlx::Error> {
+        self.get_chunk(user_id, id).await
     }
-    async fn update_chunk(&self, id: i32, content: &str) -> Result<(), sqlx::Error> {
-        self.update_chunk(id, content).await
+    async fn get_content(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<Versioned<MemContent>>, sqlx::Error> {
+        self.get_content(user_id, id).await
+    }
+    async fn edit_content(
+        &self,
+        user_id: i32,
+        id: i32,
+        cue: &str,
+        target: &str,
+        expected: Option<&[i64]>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        self.edit_content(user_id, id, cue, target, expected).await
     }
     async fn create_mem(
         &self,
+        user_id: i32,
         cue_id: i32,
         target_id: i32,
         prerequisites: &[i32],
     ) -> Result<i32, sqlx::Error> {
-        self.create_mem(cue_id, target_id, prerequisites).await
+        self.create_mem(user_id, cue_id, target_id, prerequisites)
+            .await
     }
-    async fn get_mem(&self, id: i32) -> Result<Option<MemRow>, sqlx::Error> {
-        self.get_mem(id).await
+    async fn get_mem(&self, user_id: i32, id: i32) -> Result<Option<MemRow>, sqlx::Error> {
+        self.get_mem(user_id, id).await
     }
-    async fn delete_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.delete_mem(id).await
+    async fn delete_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.delete_mem(user_id, id).await
+    }
+    async fn restore_mem(&self, user_id: i32, id: i32) -> Result<bool, sqlx::Error> {
+        self.restore_mem(user_id, id).await
+    }
+    async fn purge_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.purge_mem(user_id, id).await
     }
     async fn get_all_mems(
         &self,
-        limit: i64,
-        offset: i64,
+        user_id: i32,
+        window: &Window,
         query: &MemQuery,
-    ) -> Result<Vec<i32>, sqlx::Error> {
-        self.get_all_mems(limit, offset, query).await
+    ) -> Result<(Vec<i32>, Option<Cursor>), sqlx::Error> {
+        self.get_all_mems(user_id, window, query).await
     }
-    async fn count_all_mems(&self, query: &MemQuery) -> Result<i64, sqlx::Error> {
-        self.count_all_mems(query).await
+    async fn count_all_mems(&self, user_id: i32, query: &MemQuery) -> Result<i64, sqlx::Error> {
+        self.count_all_mems(user_id, query).await
     }
     async fn get_learning_mems(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
-        self.get_learning_mems(limit, tag_ids, exclude_tag_ids)
+        self.get_learning_mems(user_id, limit, tag_ids, exclude_tag_ids)
             .await
     }
     async fn get_due_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
-        self.get_due_reviews(limit, tag_ids, exclude_tag_ids).await
+        self.get_due_reviews(user_id, limit, tag_ids, exclude_tag_ids)
+            .await
     }
     async fn get_new_cards(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
-        self.get_new_cards(limit, tag_ids, exclude_tag_ids).await
+        self.get_new_cards(user_id, limit, tag_ids, exclude_tag_ids)
+            .await
     }
     async fn get_upcoming_reviews(
         &self,
+        user_id: i32,
         limit: i64,
         tag_ids: &[i32],
     ) -> Result<Vec<i32>, sqlx::Error> {
-        self.get_upcoming_reviews(limit, tag_ids).await
+        self.get_upcoming_reviews(user_id, limit, tag_ids).await
     }
-    async fn count_upcoming(&self) -> Result<i64, sqlx::Error> {
-        self.count_upcoming().await
+    async fn count_upcoming(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        self.count_upcoming(user_id).await
     }
-    async fn count_upcoming_within_hours(&self, hours: i64) -> Result<i64, sqlx::Error> {
-        self.count_upcoming_within_hours(hours).await
+    async fn count_upcoming_within_hours(
+        &self,
+        user_id: i32,
+        hours: i64,
+    ) -> Result<i64, sqlx::Error> {
+        self.count_upcoming_within_hours(user_id, hours).await
     }
-    async fn get_counts(&self) -> Result<(i64, i64, i64, i64, i64), sqlx::Error> {
-        self.get_counts().await
+    async fn get_counts(&self, user_id: i32) -> Result<(i64, i64, i64, i64, i64), sqlx::Error> {
+        self.get_counts(user_id).await
     }
-    async fn get_next_mem(&self) -> Result<Option<i32>, sqlx::Error> {
-        self.get_next_mem().await
+    async fn get_next_mem(&self, user_id: i32) -> Result<Option<i32>, sqlx::Error> {
+        self.get_next_mem(user_id).await
     }
     async fn set_state(
         &self,
+        user_id: i32,
         id: i32,
         state: &str,
         step_index: Option<i32>,
     ) -> Result<(), sqlx::Error> {
-        self.set_state(id, state, step_index).await
+        self.set_state(user_id, id, state, step_index).await
     }
-    async fn update_mem_fsrs(&self, id: i32, params: &FsrsUpdate) -> Result<(), sqlx::Error> {
-        self.update_mem_fsrs(id, params).await
+    async fn update_mem_fsrs(
+        &self,
+        user_id: i32,
+        id: i32,
+        params: &FsrsUpdate,
+    ) -> Result<(), sqlx::Error> {
+        self.update_mem_fsrs(user_id, id, params).await
     }
-    async fn bury_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.bury_mem(id).await
+    async fn bury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.bury_mem(user_id, id).await
     }
-    async fn unbury_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.unbury_mem(id).await
+    async fn unbury_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.unbury_mem(user_id, id).await
     }
-    async fn suspend_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.suspend_mem(id).await
+    async fn suspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.suspend_mem(user_id, id).await
     }
-    async fn unsuspend_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.unsuspend_mem(id).await
+    async fn unsuspend_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.unsuspend_mem(user_id, id).await
     }
-    async fn reset_mem(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.reset_mem(id).await
+    async fn reset_mem(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.reset_mem(user_id, id).await
     }
-    async fn get_recent_retention(&self, limit: i64) -> Result<f64, sqlx::Error> {
-        self.get_recent_retention(limit).await
+    async fn get_recent_retention(&self, user_id: i32, limit: i64) -> Result<f64, sqlx::Error> {
+        self.get_recent_retention(user_id, limit).await
     }
     async fn create_tag(&self, name: &str, user_id: i32) -> Result<TagInfo, sqlx::Error> {
         self.create_tag(name, user_id).await
     }
-    async fn delete_tag(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.delete_tag(id).await
+    async fn delete_tag(&self, user_id: i32, id: i32) -> Result<(), sqlx::Error> {
+        self.delete_tag(user_id, id).await
     }
     async fn list_tags(&self, user_id: i32) -> Result<Vec<TagInfo>, sqlx::Error> {
         self.list_tags(user_id).await
@@ -987,44 +1672,131 @@ impl MemRepository for MemRepo {
     async fn search_tags(&self, user_id: i32, q: &str) -> Result<Vec<TagInfo>, sqlx::Error> {
         self.search_tags(user_id, q).await
     }
-    async fn get_mem_tags(&self, mem_id: i32) -> Result<Vec<TagInfo>, sqlx::Error> {
-        self.get_mem_tags(mem_id).await
+    async fn get_mem_tags(&self, user_id: i32, mem_id: i32) -> Result<Vec<TagInfo>, sqlx::Error> {
+        self.get_mem_tags(user_id, mem_id).await
     }
-    async fn add_tag_to_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error> {
-        self.add_tag_to_mem(mem_id, tag_id).await
+    async fn add_tag_to_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        self.add_tag_to_mem(user_id, mem_id, tag_id).await
     }
-    async fn remove_tag_from_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), sqlx::Error> {
-        self.remove_tag_from_mem(mem_id, tag_id).await
+    async fn remove_tag_from_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        self.remove_tag_from_mem(user_id, mem_id, tag_id).await
     }
-    async fn set_mem_tags(&self, mem_id: i32, tag_ids: &[i32]) -> Result<(), sqlx::Error> {
-        self.set_mem_tags(mem_id, tag_ids).await
+    async fn set_mem_tags(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<(), sqlx::Error> {
+        self.set_mem_tags(user_id, mem_id, tag_ids).await
     }
-    async fn get_mems_tags_batch(&self, mem_ids: &[i32]) -> Result<Vec<MemTagRow>, sqlx::Error> {
-        self.get_mems_tags_batch(mem_ids).await
+    async fn get_mems_tags_batch(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> Result<Vec<MemTagRow>, sqlx::Error> {
+        self.get_mems_tags_batch(user_id, mem_ids).await
     }
     async fn export_all_mems(
         &self,
+        user_id: i32,
         tag_ids: &[i32],
     ) -> Result<Vec<(String, String, String)>, sqlx::Error> {
-        self.export_all_mems(tag_ids).await
+        self.export_all_mems(user_id, tag_ids).await
     }
-    async fn get_mnemonic(&self, mem_id: i32) -> Result<Option<String>, sqlx::Error> {
-        self.get_mnemonic(mem_id).await
+    async fn get_mnemonic(&self, user_id: i32, mem_id: i32) -> Result<Option<String>, sqlx::Error> {
+        self.get_mnemonic(user_id, mem_id).await
     }
-    async fn upsert_mnemonic(&self, mem_id: i32, content: &str) -> Result<(), sqlx::Error> {
-        self.upsert_mnemonic(mem_id, content).await
+    async fn upsert_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        content: &str,
+    ) -> Result<(), sqlx::Error> {
+        self.upsert_mnemonic(user_id, mem_id, content).await
     }
     async fn insert_revlog(&self, params: &InsertRevlogParams) -> Result<(), sqlx::Error> {
         self.insert_revlog(params).await
     }
-    async fn count_revlogs(&self) -> Result<i64, sqlx::Error> {
-        self.count_revlogs().await
+    async fn count_revlogs(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        self.count_revlogs(user_id).await
     }
     async fn prune_revlogs(&self) -> Result<(), sqlx::Error> {
         self.prune_revlogs().await
     }
-    async fn count_relearning(&self) -> Result<i64, sqlx::Error> {
-        self.count_relearning().await
+    async fn count_relearning(&self, user_id: i32) -> Result<i64, sqlx::Error> {
+        self.count_relearning(user_id).await
+    }
+    async fn import_scheduled(
+        &self,
+        user_id: i32,
+        mems: &[ScheduledMem],
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        self.import_scheduled(user_id, mems).await
+    }
+    async fn export_scheduled(
+        &self,
+        user_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<Vec<ScheduledMem>, sqlx::Error> {
+        self.export_scheduled(user_id, tag_ids).await
+    }
+    async fn get_config(&self, user_id: i32) -> Result<MemConfig, sqlx::Error> {
+        self.get_config(user_id).await
+    }
+    async fn save_config(&self, user_id: i32, config: &MemConfig) -> Result<(), sqlx::Error> {
+        self.save_config(user_id, config).await
+    }
+    async fn introduced_since(&self, user_id: i32, since: &str) -> Result<Vec<i32>, sqlx::Error> {
+        self.introduced_since(user_id, since).await
+    }
+    async fn reviewed_since(&self, user_id: i32, since: &str) -> Result<Vec<i32>, sqlx::Error> {
+        self.reviewed_since(user_id, since).await
+    }
+    async fn list_presets(&self, user_id: i32) -> Result<Vec<MemPreset>, sqlx::Error> {
+        self.list_presets(user_id).await
+    }
+    async fn create_preset(
+        &self,
+        user_id: i32,
+        preset: &PresetRequest,
+    ) -> Result<i32, sqlx::Error> {
+        self.create_preset(user_id, preset).await
+    }
+    async fn update_preset(
+        &self,
+        user_id: i32,
+        id: i32,
+        preset: &PresetRequest,
+    ) -> Result<u64, sqlx::Error> {
+        self.update_preset(user_id, id, preset).await
+    }
+    async fn delete_preset(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        self.delete_preset(user_id, id).await
+    }
+    async fn set_tag_preset(
+        &self,
+        user_id: i32,
+        tag_id: i32,
+        preset_id: Option<i32>,
+    ) -> Result<u64, sqlx::Error> {
+        self.set_tag_preset(user_id, tag_id, preset_id).await
+    }
+    async fn mem_preset_ids(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+    ) -> Result<Vec<(i32, i32)>, sqlx::Error> {
+        self.mem_preset_ids(user_id, mem_ids).await
     }
 }
 
@@ -1056,6 +1828,7 @@ mod tests {
         sqlx::query(
             "CREATE TABLE chunk (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER,
                 content TEXT NOT NULL DEFAULT '',
                 created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
                 updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
@@ -1068,6 +1841,7 @@ mod tests {
         sqlx::query(
             "CREATE TABLE mem (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER,
                 cue_chunk_id INTEGER NOT NULL,
                 target_chunk_id INTEGER NOT NULL,
                 state TEXT NOT NULL DEFAULT 'new',
@@ -1079,7 +1853,10 @@ mod tests {
                 leeched INTEGER NOT NULL DEFAULT 0,
                 due_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
                 last_review_at TEXT,
+                queued_at TEXT,
                 created_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+                deleted_at TEXT,
+                version INTEGER NOT NULL DEFAULT 1,
                 FOREIGN KEY (cue_chunk_id) REFERENCES chunk(id),
                 FOREIGN KEY (target_chunk_id) REFERENCES chunk(id)
             )",
@@ -1134,6 +1911,7 @@ mod tests {
                 name TEXT NOT NULL,
                 user_id INTEGER NOT NULL,
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                preset_id INTEGER,
                 FOREIGN KEY (user_id) REFERENCES user(id),
                 UNIQUE(name, user_id)
             )",
@@ -1155,6 +1933,31 @@ mod tests {
         .await
         .unwrap();
 
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS mem_preset (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                options TEXT NOT NULL,
+                UNIQUE (user_id, name)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS mem_config (
+                user_id INTEGER PRIMARY KEY,
+                config TEXT NOT NULL,
+                updated_at TEXT DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
         // 启用外键约束（SQLite 默认不启用）
         sqlx::query("PRAGMA foreign_keys = ON")
             .execute(&pool)
@@ -1166,35 +1969,72 @@ mod tests {
         }
     }
 
+    /// 测试默认所属用户
+    const USER: i32 = 1;
+
     /// 创建一条测试 mem 记录，返回 (mem_id, cue_chunk_id, target_chunk_id)
     async fn create_test_mem(repo: &MemRepo, cue: &str, target: &str) -> (i32, i32, i32) {
-        let cue_id = repo.create_chunk(cue).await.unwrap();
-        let target_id = repo.create_chunk(target).await.unwrap();
-        let mem_id = repo.create_mem(cue_id, target_id, &[]).await.unwrap();
+        create_test_mem_for(repo, USER, cue, target).await
+    }
+
+    /// 为指定用户创建一条测试 mem 记录
+    async fn create_test_mem_for(
+        repo: &MemRepo,
+        user_id: i32,
+        cue: &str,
+        target: &str,
+    ) -> (i32, i32, i32) {
+        let cue_id = repo.create_chunk(user_id, cue).await.unwrap();
+        let target_id = repo.create_chunk(user_id, target).await.unwrap();
+        let mem_id = repo
+            .create_mem(user_id, cue_id, target_id, &[])
+            .await
+            .unwrap();
         (mem_id, cue_id, target_id)
     }
 
+    /// 移入回收站后彻底删除
+    async fn purge(repo: &MemRepo, user_id: i32, id: i32) {
+        repo.delete_mem(user_id, id).await.unwrap();
+        repo.purge_mem(user_id, id).await.unwrap();
+    }
+
     #[tokio::test]
     async fn delete_mem_basic() {
         let repo = setup_db().await;
         let (mem_id, cue_id, target_id) = create_test_mem(&repo, "cue", "target").await;
 
         // 验证 mem 存在
-        assert!(repo.get_mem(mem_id).await.unwrap().is_some());
+        assert!(repo.get_mem(USER, mem_id).await.unwrap().is_some());
 
-        // 删除
-        repo.delete_mem(mem_id).await.unwrap();
+        // 移入回收站：不可见，但 chunk 保留
+        repo.delete_mem(USER, mem_id).await.unwrap();
+        assert!(repo.get_mem(USER, mem_id).await.unwrap().is_none());
+        assert_eq!(
+            repo.count_all_mems(USER, &MemQuery::default())
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(repo.get_chunk(USER, cue_id).await.unwrap().is_some());
+        // 重复删除视为不存在
+        assert!(repo.delete_mem(USER, mem_id).await.is_err());
 
-        // 验证 mem 已被删除
-        assert!(repo.get_mem(mem_id).await.unwrap().is_none());
+        // 恢复
+        assert!(repo.restore_mem(USER, mem_id).await.unwrap());
+        assert!(repo.get_mem(USER, mem_id).await.unwrap().is_some());
+        assert!(!repo.restore_mem(USER, mem_id).await.unwrap());
+        // 不在回收站中的 mem 不能彻底删除
+        assert!(repo.purge_mem(USER, mem_id).await.is_err());
 
-        // 验证 chunk 已被清理
-        assert!(repo.get_chunk(cue_id).await.unwrap().is_none());
-        assert!(repo.get_chunk(target_id).await.unwrap().is_none());
+        // 彻底删除后 chunk 一并清理
+        purge(&repo, USER, mem_id).await;
+        assert!(repo.get_chunk(USER, cue_id).await.unwrap().is_none());
+        assert!(repo.get_chunk(USER, target_id).await.unwrap().is_none());
     }
 
     #[tokio::test]
-    async fn delete_mem_with_revlog() {
+    async fn purge_mem_with_revlog() {
         let repo = setup_db().await;
         let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
 
@@ -1211,10 +2051,10 @@ mod tests {
         .unwrap();
 
         // 删除——之前因 FK 约束会失败
-        repo.delete_mem(mem_id).await.unwrap();
+        purge(&repo, USER, mem_id).await;
 
         // 验证 mem 已删
-        assert!(repo.get_mem(mem_id).await.unwrap().is_none());
+        assert!(repo.get_mem(USER, mem_id).await.unwrap().is_none());
 
         // 验证 revlog 也被级联删除
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM revlog WHERE mem_id = ?")
@@ -1226,7 +2066,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_mem_with_prerequisite() {
+    async fn purge_mem_with_prerequisite() {
         let repo = setup_db().await;
         let (mem_id, ..) = create_test_mem(&repo, "main", "main-target").await;
         let (dep_id, ..) = create_test_mem(&repo, "dep", "dep-target").await;
@@ -1240,10 +2080,10 @@ mod tests {
             .unwrap();
 
         // 删除依赖的 mem (dep)
-        repo.delete_mem(dep_id).await.unwrap();
+        purge(&repo, USER, dep_id).await;
 
         // 验证 dep 已删
-        assert!(repo.get_mem(dep_id).await.unwrap().is_none());
+        assert!(repo.get_mem(USER, dep_id).await.unwrap().is_none());
 
         // 验证前提约束也被清理
         let count: i64 = sqlx::query_scalar(
@@ -1258,33 +2098,52 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_mem_preserves_shared_chunk() {
+    async fn purge_mem_preserves_shared_chunk() {
         let repo = setup_db().await;
-        let cue_id = repo.create_chunk("shared-cue").await.unwrap();
+        let cue_id = repo.create_chunk(USER, "shared-cue").await.unwrap();
 
         // 两个 mem 共用同一个 cue chunk
-        let target1 = repo.create_chunk("target1").await.unwrap();
-        let target2 = repo.create_chunk("target2").await.unwrap();
-        let mem1 = repo.create_mem(cue_id, target1, &[]).await.unwrap();
-        let mem2 = repo.create_mem(cue_id, target2, &[]).await.unwrap();
+        let target1 = repo.create_chunk(USER, "target1").await.unwrap();
+        let target2 = repo.create_chunk(USER, "target2").await.unwrap();
+        let mem1 = repo.create_mem(USER, cue_id, target1, &[]).await.unwrap();
+        let mem2 = repo.create_mem(USER, cue_id, target2, &[]).await.unwrap();
 
         // 删除第一个 mem
-        repo.delete_mem(mem1).await.unwrap();
+        purge(&repo, USER, mem1).await;
 
         // 验证 mem1 已删
-        assert!(repo.get_mem(mem1).await.unwrap().is_none());
+        assert!(repo.get_mem(USER, mem1).await.unwrap().is_none());
 
         // 验证共享的 cue chunk 仍存在（因为 mem2 还在引用）
-        assert!(repo.get_chunk(cue_id).await.unwrap().is_some());
+        assert!(repo.get_chunk(USER, cue_id).await.unwrap().is_some());
 
         // 验证 mem2 正常
-        assert!(repo.get_mem(mem2).await.unwrap().is_some());
+        assert!(repo.get_mem(USER, mem2).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn other_users_mems_are_invisible() {
+        let repo = setup_db().await;
+        let (mem_id, cue_id, _) = create_test_mem_for(&repo, 2, "cue", "target").await;
+
+        assert!(repo.get_mem(USER, mem_id).await.unwrap().is_none());
+        assert!(repo.get_chunk(USER, cue_id).await.unwrap().is_none());
+        assert_eq!(
+            repo.count_all_mems(USER, &MemQuery::default())
+                .await
+                .unwrap(),
+            0
+        );
+        assert!(repo.delete_mem(USER, mem_id).await.is_err());
+
+        // 所有者仍可正常访问
+        assert!(repo.get_mem(2, mem_id).await.unwrap().is_some());
     }
 
     #[tokio::test]
     async fn delete_nonexistent_mem_returns_error() {
         let repo = setup_db().await;
-        let result = repo.delete_mem(999).await;
+        let result = repo.delete_mem(USER, 999).await;
         assert!(result.is_err());
     }
 
@@ -1293,7 +2152,7 @@ mod tests {
     #[tokio::test]
     async fn get_recent_retention_empty() {
         let repo = setup_db().await;
-        assert_eq!(repo.get_recent_retention(100).await.unwrap(), 0.0);
+        assert_eq!(repo.get_recent_retention(USER, 100).await.unwrap(), 0.0);
     }
 
     #[tokio::test]
@@ -1314,7 +2173,7 @@ mod tests {
             .unwrap();
         }
 
-        assert_eq!(repo.get_recent_retention(100).await.unwrap(), 1.0);
+        assert_eq!(repo.get_recent_retention(USER, 100).await.unwrap(), 1.0);
     }
 
     #[tokio::test]
@@ -1337,7 +2196,7 @@ mod tests {
             .unwrap();
         }
 
-        let retention = repo.get_recent_retention(100).await.unwrap();
+        let retention = repo.get_recent_retention(USER, 100).await.unwrap();
         assert!((retention - 0.6).abs() < 1e-10);
     }
 
@@ -1360,7 +2219,7 @@ mod tests {
         }
 
         // limit=5 只取前 5 个（都是 4）→ 1.0
-        assert_eq!(repo.get_recent_retention(5).await.unwrap(), 1.0);
+        assert_eq!(repo.get_recent_retention(USER, 5).await.unwrap(), 1.0);
     }
 
     // ── 标签 ──
@@ -1399,14 +2258,14 @@ mod tests {
         assert!(repo.list_tags(uid).await.unwrap().is_empty());
 
         // 给一个 mem 打上标签后，才会出现
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
-        repo.add_tag_to_mem(mem_id, t1.id).await.unwrap();
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
+        repo.add_tag_to_mem(uid, mem_id, t1.id).await.unwrap();
         let tags = repo.list_tags(uid).await.unwrap();
         assert_eq!(tags.len(), 1);
         assert_eq!(tags[0].name, "rust");
 
         // 再打一个
-        repo.add_tag_to_mem(mem_id, t2.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t2.id).await.unwrap();
         let tags = repo.list_tags(uid).await.unwrap();
         assert_eq!(tags.len(), 2);
         assert_eq!(tags[0].name, "rust");
@@ -1471,21 +2330,21 @@ mod tests {
         let uid = create_user(&repo).await;
 
         let tag = repo.create_tag("移除", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
-        repo.add_tag_to_mem(mem_id, tag.id).await.unwrap();
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
+        repo.add_tag_to_mem(uid, mem_id, tag.id).await.unwrap();
 
         // 验证关联存在
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert_eq!(tags.len(), 1);
 
         // 删除标签
-        repo.delete_tag(tag.id).await.unwrap();
+        repo.delete_tag(uid, tag.id).await.unwrap();
 
         // 标签已删除
         assert!(repo.list_tags(uid).await.unwrap().is_empty());
 
         // mem_tag 被级联删除
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert!(tags.is_empty());
     }
 
@@ -1496,16 +2355,16 @@ mod tests {
 
         let t1 = repo.create_tag("标签A", uid).await.unwrap();
         let t2 = repo.create_tag("标签B", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
 
         // 初始无标签
-        assert!(repo.get_mem_tags(mem_id).await.unwrap().is_empty());
+        assert!(repo.get_mem_tags(uid, mem_id).await.unwrap().is_empty());
 
         // 添加两个标签
-        repo.add_tag_to_mem(mem_id, t1.id).await.unwrap();
-        repo.add_tag_to_mem(mem_id, t2.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t1.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t2.id).await.unwrap();
 
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert_eq!(tags.len(), 2);
     }
 
@@ -1515,12 +2374,12 @@ mod tests {
         let uid = create_user(&repo).await;
 
         let tag = repo.create_tag("幂等", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
 
-        repo.add_tag_to_mem(mem_id, tag.id).await.unwrap();
-        repo.add_tag_to_mem(mem_id, tag.id).await.unwrap(); // 第二次不应报错
+        repo.add_tag_to_mem(uid, mem_id, tag.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, tag.id).await.unwrap(); // 第二次不应报错
 
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert_eq!(tags.len(), 1);
     }
 
@@ -1531,15 +2390,15 @@ mod tests {
 
         let t1 = repo.create_tag("保留", uid).await.unwrap();
         let t2 = repo.create_tag("移除", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
 
-        repo.add_tag_to_mem(mem_id, t1.id).await.unwrap();
-        repo.add_tag_to_mem(mem_id, t2.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t1.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t2.id).await.unwrap();
 
         // 移除一个标签
-        repo.remove_tag_from_mem(mem_id, t2.id).await.unwrap();
+        repo.remove_tag_from_mem(uid, mem_id, t2.id).await.unwrap();
 
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert_eq!(tags.len(), 1);
         assert_eq!(tags[0].name, "保留");
     }
@@ -1552,14 +2411,16 @@ mod tests {
         let t1 = repo.create_tag("旧标签", uid).await.unwrap();
         let t2 = repo.create_tag("新标签A", uid).await.unwrap();
         let t3 = repo.create_tag("新标签B", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
 
-        repo.add_tag_to_mem(mem_id, t1.id).await.unwrap();
+        repo.add_tag_to_mem(uid, mem_id, t1.id).await.unwrap();
 
         // 批量覆盖：只保留 t2, t3
-        repo.set_mem_tags(mem_id, &[t2.id, t3.id]).await.unwrap();
+        repo.set_mem_tags(uid, mem_id, &[t2.id, t3.id])
+            .await
+            .unwrap();
 
-        let tags = repo.get_mem_tags(mem_id).await.unwrap();
+        let tags = repo.get_mem_tags(uid, mem_id).await.unwrap();
         assert_eq!(tags.len(), 2);
         assert!(tags.iter().all(|t| t.name.starts_with("新标签")));
     }
@@ -1570,11 +2431,11 @@ mod tests {
         let uid = create_user(&repo).await;
 
         let tag = repo.create_tag("清空", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
-        repo.add_tag_to_mem(mem_id, tag.id).await.unwrap();
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
+        repo.add_tag_to_mem(uid, mem_id, tag.id).await.unwrap();
 
-        repo.set_mem_tags(mem_id, &[]).await.unwrap();
-        assert!(repo.get_mem_tags(mem_id).await.unwrap().is_empty());
+        repo.set_mem_tags(uid, mem_id, &[]).await.unwrap();
+        assert!(repo.get_mem_tags(uid, mem_id).await.unwrap().is_empty());
     }
 
     #[tokio::test]
@@ -1583,26 +2444,26 @@ mod tests {
         let uid = create_user(&repo).await;
 
         let tag = repo.create_tag("共享", uid).await.unwrap();
-        let (m1, ..) = create_test_mem(&repo, "a", "a-target").await;
-        let (m2, ..) = create_test_mem(&repo, "b", "b-target").await;
+        let (m1, ..) = create_test_mem_for(&repo, uid, "a", "a-target").await;
+        let (m2, ..) = create_test_mem_for(&repo, uid, "b", "b-target").await;
 
-        repo.add_tag_to_mem(m1, tag.id).await.unwrap();
+        repo.add_tag_to_mem(uid, m1, tag.id).await.unwrap();
 
-        assert_eq!(repo.get_mem_tags(m1).await.unwrap().len(), 1);
-        assert!(repo.get_mem_tags(m2).await.unwrap().is_empty());
+        assert_eq!(repo.get_mem_tags(uid, m1).await.unwrap().len(), 1);
+        assert!(repo.get_mem_tags(uid, m2).await.unwrap().is_empty());
     }
 
     #[tokio::test]
-    async fn delete_mem_cleans_orphan_tag() {
+    async fn purge_mem_cleans_orphan_tag() {
         let repo = setup_db().await;
         let uid = create_user(&repo).await;
 
         let tag = repo.create_tag("孤儿", uid).await.unwrap();
-        let (mem_id, ..) = create_test_mem(&repo, "cue", "target").await;
-        repo.add_tag_to_mem(mem_id, tag.id).await.unwrap();
+        let (mem_id, ..) = create_test_mem_for(&repo, uid, "cue", "target").await;
+        repo.add_tag_to_mem(uid, mem_id, tag.id).await.unwrap();
 
         // 删除 mem → mem_tag 级联删除 → 标签无 mem 关联 → 自动清理
-        repo.delete_mem(mem_id).await.unwrap();
+        purge(&repo, uid, mem_id).await;
 
         // 标签已被自动删除
         let tags = repo.list_tags(uid).await.unwrap();
@@ -1613,11 +2474,12 @@ mod tests {
 
     /// 插入一条 mem（仅基本字段），返回 id
     async fn insert_session_mem(repo: &MemRepo, state: &str, buried: i32, due_at: &str) -> i32 {
-        let cue_id = repo.create_chunk("cue").await.unwrap();
-        let target_id = repo.create_chunk("target").await.unwrap();
+        let cue_id = repo.create_chunk(USER, "cue").await.unwrap();
+        let target_id = repo.create_chunk(USER, "target").await.unwrap();
         sqlx::query_scalar::<_, i32>(
-            "INSERT INTO mem (cue_chunk_id, target_chunk_id, state, buried, due_at) VALUES (?, ?, ?, ?, ?) RETURNING id"
+            "INSERT INTO mem (user_id, cue_chunk_id, target_chunk_id, state, buried, due_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING id"
         )
+        .bind(USER)
         .bind(cue_id)
         .bind(target_id)
         .bind(state)
@@ -1632,7 +2494,7 @@ mod tests {
         let repo_arc: Arc<dyn crate::modules::mem::port::MemRepository> =
             Arc::new(MemRepo::new(repo.pool.clone()));
         let svc = crate::modules::mem::query::MemQueryService::new(repo_arc);
-        svc.get_session_estimate().await.unwrap()
+        svc.get_session_estimate(USER).await.unwrap()
     }
 
     #[tokio::test]
@@ -1751,8 +2613,12 @@ mod tests {
         insert_session_mem(&repo, "new", 1, "2099-01-01T00:00:00Z").await;
 
         let query = MemQuery::default();
-        let ids = repo.get_all_mems(100, 0, &query).await.unwrap();
-        let count = repo.count_all_mems(&query).await.unwrap();
+        let window = Window::Offset {
+            limit: 100,
+            offset: 0,
+        };
+        let (ids, _) = repo.get_all_mems(USER, &window, &query).await.unwrap();
+        let count = repo.count_all_mems(USER, &query).await.unwrap();
 
         assert_eq!(ids.len(), 1, "默认应排除已埋葬卡");
         assert_eq!(count, 1);
@@ -1766,7 +2632,7 @@ mod tests {
         insert_session_mem(&repo, "review", 0, "2020-01-01T00:00:00Z").await;
 
         let query = MemQuery::default();
-        let count = repo.count_all_mems(&query).await.unwrap();
+        let count = repo.count_all_mems(USER, &query).await.unwrap();
         assert_eq!(count, 2, "2 张正常卡，1 张已埋葬");
     }
 
@@ -1782,10 +2648,14 @@ mod tests {
             state: Some("buried".into()),
             ..MemQuery::default()
         };
-        let ids = repo.get_all_mems(100, 0, &query).await.unwrap();
+        let window = Window::Offset {
+            limit: 100,
+            offset: 0,
+        };
+        let (ids, _) = repo.get_all_mems(USER, &window, &query).await.unwrap();
         assert_eq!(ids.len(), 2, "2 张已埋葬卡");
 
-        let count = repo.count_all_mems(&query).await.unwrap();
+        let count = repo.count_all_mems(USER, &query).await.unwrap();
         assert_eq!(count, 2);
     }
 
@@ -1800,7 +2670,11 @@ mod tests {
             state: Some("review".into()),
             ..MemQuery::default()
         };
-        let ids = repo.get_all_mems(100, 0, &query).await.unwrap();
+        let window = Window::Offset {
+            limit: 100,
+            offset: 0,
+        };
+        let (ids, _) = repo.get_all_mems(USER, &window, &query).await.unwrap();
         assert_eq!(ids.len(), 1, "只有 1 张未埋葬的 review 卡");
     }
 
@@ -1811,22 +2685,28 @@ mod tests {
 
         // 创建 20 张新卡
         for i in 0..20 {
-            let cue_id = repo.create_chunk(&format!("cue_{}", i)).await.unwrap();
-            let target_id = repo.create_chunk(&format!("target_{}", i)).await.unwrap();
-            repo.create_mem(cue_id, target_id, &[]).await.unwrap();
+            let cue_id = repo
+                .create_chunk(USER, &format!("cue_{}", i))
+                .await
+                .unwrap();
+            let target_id = repo
+                .create_chunk(USER, &format!("target_{}", i))
+                .await
+                .unwrap();
+            repo.create_mem(USER, cue_id, target_id, &[]).await.unwrap();
         }
 
         // 创建 5 张 review 卡（未来的 due_at，本不应出现在本轮）
         for i in 0..5 {
             let cue_id = repo
-                .create_chunk(&format!("upcoming_cue_{}", i))
+                .create_chunk(USER, &format!("upcoming_cue_{}", i))
                 .await
                 .unwrap();
             let target_id = repo
-                .create_chunk(&format!("upcoming_target_{}", i))
+                .create_chunk(USER, &format!("upcoming_target_{}", i))
                 .await
                 .unwrap();
-            let id = repo.create_mem(cue_id, target_id, &[]).await.unwrap();
+            let id = repo.create_mem(USER, cue_id, target_id, &[]).await.unwrap();
             // 设为 review 状态，due_at 在 1 分钟后（使用 TZ 格式，与真实代码一致）
             // 1 分钟 = 60 秒
             let future = (chrono::Utc::now() + chrono::Duration::seconds(60))
@@ -1841,7 +2721,7 @@ mod tests {
         }
 
         // 验证新卡有 20 张
-        let (n, _l, _d, _b, _s) = repo.get_counts().await.unwrap();
+        let (n, _l, _d, _b, _s) = repo.get_counts(USER).await.unwrap();
         assert_eq!(n, 20, "应有 20 张新卡");
 
         // 模拟 get_due 逻辑（简化版）：先取 learning，再取 due_reviews，再取 new_cards
@@ -1851,7 +2731,7 @@ mod tests {
 
         // 1. learning
         let mut ids = repo
-            .get_learning_mems(limit, tag_ids, exclude_tag_ids)
+            .get_learning_mems(USER, limit, tag_ids, exclude_tag_ids)
             .await
             .unwrap();
         assert_eq!(ids.len(), 0, "没有 learning 卡");
@@ -1860,7 +2740,7 @@ mod tests {
         if ids.len() < limit as usize {
             let needed = limit as usize - ids.len();
             let due = repo
-                .get_due_reviews(needed as i64, tag_ids, exclude_tag_ids)
+                .get_due_reviews(USER, needed as i64, tag_ids, exclude_tag_ids)
                 .await
                 .unwrap();
             assert!(due.is_empty(), "没有到期的 review 卡");
@@ -1871,7 +2751,7 @@ mod tests {
         if ids.len() < limit as usize {
             let needed = limit as usize - ids.len();
             let new_cards = repo
-                .get_new_cards(needed as i64, tag_ids, exclude_tag_ids)
+                .get_new_cards(USER, needed as i64, tag_ids, exclude_tag_ids)
                 .await
                 .unwrap();
             // 关键断言：应该拿到足够的卡填满队列
@@ -1885,7 +2765,7 @@ mod tests {
         if ids.len() < limit as usize {
             let needed = limit as usize - ids.len();
             let upcoming = repo
-                .get_upcoming_reviews(needed as i64, tag_ids)
+                .get_upcoming_reviews(USER, needed as i64, tag_ids)
                 .await
                 .unwrap();
             // 不应走到这里！
@@ -1893,4 +2773,202 @@ mod tests {
             panic!("不应拉取 upcoming！新卡足够填满队列");
         }
     }
+
+    // ── 配置 ──
+
+    #[tokio::test]
+    async fn config_is_per_user_with_defaults() {
+        let repo = setup_db().await;
+        assert_eq!(repo.get_config(USER).await.unwrap(), MemConfig::default());
+
+        let custom = MemConfig {
+            learning_steps: vec![30],
+            desired_retention: 0.85,
+            ..MemConfig::default()
+        };
+        repo.save_config(USER, &custom).await.unwrap();
+        assert_eq!(repo.get_config(USER).await.unwrap(), custom);
+        assert_eq!(repo.get_config(2).await.unwrap(), MemConfig::default());
+
+        // 再次保存覆盖
+        repo.save_config(USER, &MemConfig::default()).await.unwrap();
+        assert_eq!(repo.get_config(USER).await.unwrap(), MemConfig::default());
+    }
+
+    #[tokio::test]
+    async fn count_revlogs_is_per_user() {
+        let repo = setup_db().await;
+        let (mine, _, _) = create_test_mem(&repo, "a", "b").await;
+        let (theirs, _, _) = create_test_mem_for(&repo, 2, "c", "d").await;
+        for mem_id in [mine, mine, theirs] {
+            sqlx::query(
+                "INSERT INTO revlog (mem_id, review_time, rating, delta_t)
+                 VALUES (?, '2024-01-01T00:00:00Z', 3, 0)",
+            )
+            .bind(mem_id)
+            .execute(&*repo.pool)
+            .await
+            .unwrap();
+        }
+        assert_eq!(repo.count_revlogs(USER).await.unwrap(), 2);
+        assert_eq!(repo.count_revlogs(2).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn full_export_round_trips_into_another_user() {
+        // 需要完整的 revlog / 助记 / 前置依赖表，用正式迁移建库
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        for name in ["a", "b"] {
+            sqlx::query("INSERT INTO user (name, password_hash) VALUES (?, 'x')")
+                .bind(name)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let repo = MemRepo::new(Arc::new(pool));
+        let review = RevlogEntry {
+            review_time: "2024-01-01T00:00:00Z".into(),
+            rating: 3,
+            delta_t: 0,
+            stability_before: None,
+            difficulty_before: None,
+            state_before: Some("new".into()),
+            stability_after: Some(2.3),
+            difficulty_after: Some(5.1),
+            state_after: Some("review".into()),
+        };
+        let source = vec![
+            ScheduledMem {
+                id: 7,
+                cue: "q1".into(),
+                target: "a1".into(),
+                state: "relearning".into(),
+                stability: 1.25,
+                difficulty: 7.5,
+                step_index: Some(0),
+                buried: true,
+                lapses: 3,
+                leeched: true,
+                due_at: "2024-01-05T00:00:00Z".into(),
+                last_review_at: Some("2024-01-04T00:00:00Z".into()),
+                tags: vec!["N3".into(), "日语".into()],
+                mnemonic: Some("谐音".into()),
+                prerequisites: vec![9],
+                revlog: vec![
+                    review.clone(),
+                    RevlogEntry {
+                        review_time: "2024-01-04T00:00:00Z".into(),
+                        rating: 1,
+                        delta_t: 3,
+                        state_before: Some("review".into()),
+                        state_after: Some("relearning".into()),
+                        ..review.clone()
+                    },
+                ],
+            },
+            ScheduledMem {
+                id: 9,
+                cue: "q2".into(),
+                target: "a2".into(),
+                state: "new".into(),
+                stability: 0.0,
+                difficulty: 0.0,
+                step_index: None,
+                buried: false,
+                lapses: 0,
+                leeched: false,
+                due_at: "2024-01-01T00:00:00Z".into(),
+                last_review_at: None,
+                tags: vec!["日语".into()],
+                mnemonic: None,
+                // 导出范围外的依赖被忽略
+                prerequisites: vec![42],
+                revlog: Vec::new(),
+            },
+        ];
+        repo.import_scheduled(1, &source).await.unwrap();
+
+        let exported = repo.export_scheduled(1, &[]).await.unwrap();
+        repo.import_scheduled(2, &exported).await.unwrap();
+        let restored = repo.export_scheduled(2, &[]).await.unwrap();
+
+        // 除 id 与前置依赖外与原始数据一致；前置依赖按新 id 指向同一张卡
+        let strip = |mems: &[ScheduledMem]| -> Vec<ScheduledMem> {
+            mems.iter()
+                .map(|m| ScheduledMem {
+                    id: 0,
+                    prerequisites: Vec::new(),
+                    ..m.clone()
+                })
+                .collect()
+        };
+        assert_eq!(strip(&restored), strip(&source));
+        assert_eq!(restored[0].prerequisites, vec![restored[1].id]);
+        assert!(restored[1].prerequisites.is_empty());
+
+        // 按标签筛选时不带出范围外的依赖
+        let n3 = repo
+            .list_tags(2)
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|t| t.name == "N3")
+            .unwrap();
+        let filtered = repo.export_scheduled(2, &[n3.id]).await.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered[0].prerequisites.is_empty());
+        assert_eq!(filtered[0].revlog.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn edit_content_checks_version() {
+        // 版本递增依赖迁移中的触发器，用正式迁移建库
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::db::migrate::run(&pool).await.unwrap();
+        sqlx::query("INSERT INTO user (name, password_hash) VALUES ('a', 'x')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = MemRepo::new(Arc::new(pool));
+        let (id, cue_id, _) = create_test_mem(&repo, "q", "a").await;
+        let v = repo.get_content(USER, id).await.unwrap().unwrap().version;
+
+        // 同一版本的两次并发改写只有一次生效
+        let expected = [v];
+        let (a, b) = tokio::join!(
+            repo.edit_content(USER, id, "q1", "a1", Some(&expected)),
+            repo.edit_content(USER, id, "q2", "a2", Some(&expected)),
+        );
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert_eq!(a.is_some() as u8 + b.is_some() as u8, 1);
+        let saved = repo.get_content(USER, id).await.unwrap().unwrap();
+        assert_eq!(Some(saved.version), a.or(b));
+        assert!(saved.version > v);
+        assert!(["q1", "q2"].contains(&saved.row.cue_content.as_str()));
+
+        // 埋藏等调度变更不改变内容版本；经其他路径改写块内容则使旧版本失效
+        repo.bury_mem(USER, id).await.unwrap();
+        let v = repo.get_content(USER, id).await.unwrap().unwrap().version;
+        assert_eq!(v, saved.version);
+        sqlx::query("UPDATE chunk SET content = 'q3' WHERE id = ?")
+            .bind(cue_id)
+            .execute(&*repo.pool)
+            .await
+            .unwrap();
+        let stale = repo.edit_content(USER, id, "q4", "a4", Some(&[v])).await;
+        assert!(stale.unwrap().is_none());
+        assert!(
+            repo.edit_content(USER, id, "q4", "a4", None)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            repo.edit_content(USER, 999, "q", "a", None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
 }
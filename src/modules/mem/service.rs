@@ -2,27 +2,101 @@ use sqlx::SqlitePool;
 use std::sync::Arc;
 
 use crate::batch::{BatchResponse, batch_execute, batch_execute_with_code};
+use crate::error::ServiceError;
+use crate::etag::{self, IfMatch};
+use crate::modules::activity::{AuditLog, Entity};
+use crate::modules::events::{ChangeFeed, ChangeKind};
+use crate::modules::job::{JobKind, JobService, NewJob};
+use crate::modules::media::service::MediaService;
+use crate::modules::mem::anki::{self, AnkiImportReport};
 use crate::modules::mem::config::MemConfig;
 use crate::modules::mem::fsrs::{self, ReviewOutcome};
+use crate::modules::mem::limits;
 use crate::modules::mem::model::*;
+use crate::modules::mem::optimizer;
 use crate::modules::mem::port::MemRepository;
+use crate::modules::mem::preset::{self, MemPreset, PresetRequest};
+use crate::modules::webhook::{WebhookEvent, Webhooks};
+
+/// 同名预设违反唯一约束时返回 409
+fn preset_conflict(e: sqlx::Error) -> ServiceError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            ServiceError::AlreadyExists("同名预设已存在".into())
+        }
+        _ => e.into(),
+    }
+}
+
+/// 同一用户同一时间只排一个优化任务，不同用户互不影响
+pub fn optimize_dedupe_key(user_id: i32) -> String {
+    format!("{}:{user_id}", JobKind::FsrsOptimize.as_str())
+}
 
 #[derive(Clone)]
 pub struct MemService {
     repo: Arc<dyn MemRepository>,
     /// 数据库连接池（临时保留，供 optimizer 使用。TODO: Phase 2 — 让 optimizer 也通过 Repository trait 访问）
     db: Arc<SqlitePool>,
+    audit: AuditLog,
+    /// 不入审计的状态变化（复习、挂起、标签等）直接推送到变更流
+    changes: ChangeFeed,
+    jobs: JobService,
+    hooks: Webhooks,
 }
 
 impl MemService {
-    pub fn new(repo: Arc<dyn MemRepository>, db: Arc<SqlitePool>) -> Self {
-        Self { repo, db }
+    pub fn new(
+        repo: Arc<dyn MemRepository>,
+        db: Arc<SqlitePool>,
+        jobs: JobService,
+        hooks: Webhooks,
+        changes: ChangeFeed,
+    ) -> Self {
+        let audit = AuditLog::new(db.clone(), changes.clone());
+        Self {
+            repo,
+            db,
+            audit,
+            changes,
+            jobs,
+            hooks,
+        }
+    }
+
+    fn changed(&self, user_id: i32, id: i32, kind: ChangeKind) {
+        self.changes.publish(user_id, Entity::Mem, id, kind);
+    }
+
+    /// 审计用快照：正反面内容与调度状态
+    async fn snapshot(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<serde_json::Value>, sqlx::Error> {
+        let Some(row) = self.repo.get_mem(user_id, id).await? else {
+            return Ok(None);
+        };
+        let cue = self.repo.get_chunk(user_id, row.cue_chunk_id).await?;
+        let target = self.repo.get_chunk(user_id, row.target_chunk_id).await?;
+        Ok(Some(serde_json::json!({
+            "cue": cue.map(|c| c.content),
+            "target": target.map(|c| c.content),
+            "state": row.state,
+            "stability": row.stability,
+            "difficulty": row.difficulty,
+            "step_index": row.step_index,
+            "lapses": row.lapses,
+            "leeched": row.leeched,
+            "due_at": row.due_at,
+        })))
     }
 
     // ── 获取学习池（含侧面：新卡标注 learning 状态） ──
 
     pub async fn get_due(
         &self,
+        user_id: i32,
         max_learning: i64,
         tag_ids: &[i32],
         exclude_tag_ids: &[i32],
@@ -33,7 +107,7 @@ impl MemService {
         // 1. 学习卡优先：learning + relearning（按 due_at 排序）
         let learning = self
             .repo
-            .get_learning_mems(max_learning, tag_ids, exclude_tag_ids)
+            .get_learning_mems(user_id, max_learning, tag_ids, exclude_tag_ids)
             .await?;
         for id in &learning {
             if ids.len() < cap {
@@ -42,50 +116,79 @@ impl MemService {
         }
         let more_to_learn = learning.len() > ids.len();
 
+        // 学习 / 重学步进不受每日上限限制，到期复习与新卡按当天剩余额度给出。
+        // 候选按用户剩余额度取，再逐张检查其预设的上限
+        let config = self.repo.get_config(user_id).await?;
+        let mut today =
+            limits::Today::load(self.repo.as_ref(), user_id, &config, chrono::Utc::now()).await?;
+
         // 2. 到期 review 填空
-        let review_quota = cap.saturating_sub(ids.len());
-        if review_quota > 0 {
+        if ids.len() < cap && today.reviews_left() > 0 {
             let due = self
                 .repo
-                .get_due_reviews(review_quota as i64, tag_ids, exclude_tag_ids)
+                .get_due_reviews(user_id, today.reviews_left(), tag_ids, exclude_tag_ids)
                 .await?;
-            ids.extend(due);
+            let presets = preset::resolve(self.repo.as_ref(), user_id, &due).await?;
+            for id in due {
+                if ids.len() >= cap {
+                    break;
+                }
+                if today.take_review(presets.get(&id)) {
+                    ids.push(id);
+                }
+            }
         }
 
         // 3. 新卡填空（标注 learning 状态——这是写操作）
-        let new_quota = cap.saturating_sub(ids.len());
-        if new_quota > 0 {
+        if ids.len() < cap && today.new_left() > 0 {
             let new_cards = self
                 .repo
-                .get_new_cards(new_quota as i64, tag_ids, exclude_tag_ids)
+                .get_new_cards(user_id, today.new_left(), tag_ids, exclude_tag_ids)
                 .await?;
-            for id in &new_cards {
-                self.repo.set_state(*id, "learning", Some(0)).await?;
+            let presets = preset::resolve(self.repo.as_ref(), user_id, &new_cards).await?;
+            for id in new_cards {
+                if ids.len() >= cap {
+                    break;
+                }
+                if today.take_new(presets.get(&id)) {
+                    self.repo
+                        .set_state(user_id, id, "learning", Some(0))
+                        .await?;
+                    ids.push(id);
+                }
             }
-            ids.extend(new_cards);
         }
 
-        // 4. 提前复习 (upcoming) 填空
-        let upcoming_quota = cap.saturating_sub(ids.len());
-        if upcoming_quota > 0 {
+        // 4. 提前复习 (upcoming) 填空，计入复习额度
+        if ids.len() < cap && today.reviews_left() > 0 {
             let upcoming = self
                 .repo
-                .get_upcoming_reviews(upcoming_quota as i64, tag_ids)
+                .get_upcoming_reviews(user_id, today.reviews_left(), tag_ids)
                 .await?;
-            ids.extend(upcoming);
+            let presets = preset::resolve(self.repo.as_ref(), user_id, &upcoming).await?;
+            for id in upcoming {
+                if ids.len() >= cap {
+                    break;
+                }
+                if today.take_review(presets.get(&id)) {
+                    ids.push(id);
+                }
+            }
         }
 
-        // 5. 实在没卡了，随便给一张
+        // 5. 实在没卡了，随便给一张（当天额度用完时不给）
         if ids.is_empty()
-            && let Ok(Some(id)) = self.repo.get_next_mem().await
+            && today.new_left() > 0
+            && today.reviews_left() > 0
+            && let Ok(Some(id)) = self.repo.get_next_mem(user_id).await
         {
             ids.push(id);
         }
 
-        let items = self.build_items(&ids).await;
+        let items = self.build_items(user_id, &ids).await;
         let has_more = more_to_learn || ids.len() >= cap;
         let upcoming_count = if ids.is_empty() {
-            self.repo.count_upcoming().await.unwrap_or(0) as usize
+            self.repo.count_upcoming(user_id).await.unwrap_or(0) as usize
         } else {
             0
         };
@@ -98,19 +201,48 @@ impl MemService {
             });
 
         Ok(DueResponse {
-            items: items.into(),
+            items,
             due_count: ids.len(),
             has_more,
             upcoming_count,
             all_far,
+            new_remaining: today.new_left(),
+            reviews_remaining: today.reviews_left(),
+            day_ends_at: today
+                .ends_at(&config)
+                .format("%Y-%m-%dT%H:%M:%SZ")
+                .to_string(),
         })
     }
 
     // ── 复习 ──
 
-    pub async fn review(&self, id: i32, rating: u8) -> Result<ReviewResponse, AppError> {
-        let row = self.repo.get_mem(id).await?.ok_or(AppError::NotFound)?;
-        let outcome = self.apply_review(&row, rating);
+    pub async fn review(
+        &self,
+        user_id: i32,
+        id: i32,
+        rating: u8,
+    ) -> Result<ReviewResponse, AppError> {
+        self.review_at(user_id, id, rating, chrono::Utc::now())
+            .await
+    }
+
+    /// 以 `reviewed_at` 为复习时刻调度并写 revlog（离线同步按客户端时间重放）
+    pub async fn review_at(
+        &self,
+        user_id: i32,
+        id: i32,
+        rating: u8,
+        reviewed_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ReviewResponse, AppError> {
+        let row = self
+            .repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        let config = preset::scheduler_for(self.repo.as_ref(), user_id, id).await?;
+        let outcome = self.apply_review(&row, rating, &config, reviewed_at);
+        let review_time = reviewed_at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
 
         let new_step = if outcome.state.has_steps() {
             let old = row.step_index.map(|i| i as usize);
@@ -136,6 +268,7 @@ impl MemService {
 
         self.repo
             .update_mem_fsrs(
+                user_id,
                 id,
                 &FsrsUpdate {
                     state: new_state.to_string(),
@@ -145,17 +278,17 @@ impl MemService {
                     lapses,
                     leeched,
                     due_at: outcome.due_at.clone(),
+                    last_review_at: review_time.clone(),
                 },
             )
             .await?;
 
         // 写 revlog（通过 Repository trait）
-        let delta_t = days_elapsed_since(&row.last_review_at) as i32;
-        let now_str = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let delta_t = days_elapsed_between(&row.last_review_at, reviewed_at) as i32;
         self.repo
             .insert_revlog(&InsertRevlogParams {
                 mem_id: id,
-                review_time: now_str,
+                review_time,
                 rating,
                 delta_t,
                 stability_before: row.stability,
@@ -168,12 +301,21 @@ impl MemService {
             .await
             .map_err(AppError::Db)?;
 
+        // 本次复习使其首次成为 leech
+        if leeched
+            && !row.leeched
+            && let Ok(Some(mut mem)) = self.snapshot(user_id, id).await
+        {
+            mem["id"] = id.into();
+            self.hooks
+                .emit(user_id, WebhookEvent::MemLeeched, &mem)
+                .await;
+        }
+
+        self.changed(user_id, id, ChangeKind::Reviewed);
+
         // 每 20 次复习自动触发一次参数优化
-        let repo = self.repo.clone();
-        let db = self.db.clone();
-        tokio::spawn(async move {
-            maybe_auto_optimize(repo, db, 20).await;
-        });
+        self.queue_auto_optimize(user_id, 20).await;
 
         Ok(ReviewResponse {
             state: new_state.to_string(),
@@ -181,15 +323,20 @@ impl MemService {
         })
     }
 
-    fn apply_review(&self, row: &MemRow, rating: u8) -> ReviewOutcome {
+    fn apply_review(
+        &self,
+        row: &MemRow,
+        rating: u8,
+        config: &fsrs::SchedulerConfig,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> ReviewOutcome {
         let state: CardState = row.state.parse().unwrap_or(CardState::New);
         let step = if state == CardState::New {
             Some(0)
         } else {
             row.step_index.map(|i| i as usize)
         };
-        let days_elapsed = days_elapsed_since(&row.last_review_at);
-        let config = fsrs::SchedulerConfig::default();
+        let days_elapsed = days_elapsed_between(&row.last_review_at, now);
         let cumulative_step_days = days_elapsed;
         fsrs::schedule(
             fsrs::ScheduleInput {
@@ -201,22 +348,26 @@ impl MemService {
                 days_elapsed,
                 cumulative_step_days,
             },
-            &config,
+            config,
+            now,
         )
     }
 
     // ── 内部辅助 ──
 
-    async fn build_items(&self, ids: &[i32]) -> Vec<MemWithChunks> {
+    async fn build_items(&self, user_id: i32, ids: &[i32]) -> Vec<MemWithChunks> {
+        let presets = preset::resolve(self.repo.as_ref(), user_id, ids)
+            .await
+            .unwrap_or_default();
         let mut items = Vec::new();
         for &id in ids {
-            if let Ok(Some(row)) = self.repo.get_mem(id).await
+            if let Ok(Some(row)) = self.repo.get_mem(user_id, id).await
                 && let (Ok(Some(cue)), Ok(Some(target))) = (
-                    self.repo.get_chunk(row.cue_chunk_id).await,
-                    self.repo.get_chunk(row.target_chunk_id).await,
+                    self.repo.get_chunk(user_id, row.cue_chunk_id).await,
+                    self.repo.get_chunk(user_id, row.target_chunk_id).await,
                 )
             {
-                let mnemonic = self.repo.get_mnemonic(id).await.unwrap_or(None);
+                let mnemonic = self.repo.get_mnemonic(user_id, id).await.unwrap_or(None);
                 items.push(MemWithChunks {
                     id: row.id,
                     cue,
@@ -228,47 +379,216 @@ impl MemService {
                     lapses: row.lapses,
                     leeched: row.leeched,
                     mnemonic,
+                    preset_id: presets.get(&id).map(|p| p.id),
                 });
             }
         }
         items
     }
 
+    // ── 后台任务 ──
+
+    /// 若用户的 revlog 条数达到 `every` 的整数倍，为其排队一次 FSRS 参数优化
+    /// （每个用户同一时间只排一个）
+    async fn queue_auto_optimize(&self, user_id: i32, every: i64) {
+        let count = match self.repo.count_revlogs(user_id).await {
+            Ok(n) => n,
+            Err(_) => return,
+        };
+        if count < 10 || count % every != 0 {
+            return;
+        }
+
+        tracing::info!("触发自动优化: 用户 {} 的 revlog 共 {} 条", user_id, count);
+        let job = NewJob::new(JobKind::FsrsOptimize)
+            .user(user_id)
+            .dedupe(optimize_dedupe_key(user_id));
+        if let Err(e) = self.jobs.enqueue(job).await {
+            tracing::warn!("自动优化排队失败: {e}");
+        }
+    }
+
+    /// 用该用户的复习记录训练 FSRS 参数并写回其配置；复习记录不足时返回 None
+    pub async fn optimize_params(&self, user_id: i32) -> Result<Option<Vec<f32>>, String> {
+        let mut config = self
+            .repo
+            .get_config(user_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(params) = optimizer::optimize_fsrs_params(&self.db, user_id, &config).await?
+        else {
+            return Ok(None);
+        };
+        if !fsrs::params_valid(&params) {
+            return Err("优化得到的参数无效".into());
+        }
+        // 训练期间用户可能改过配置：只替换参数，其余以最新的为准
+        config = self
+            .repo
+            .get_config(user_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        config.fsrs_params = params.clone();
+        self.repo
+            .save_config(user_id, &config)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Some(params))
+    }
+
+    // ── 配置 ──
+
+    pub async fn config(&self, user_id: i32) -> Result<MemConfig, AppError> {
+        Ok(self.repo.get_config(user_id).await?)
+    }
+
+    /// 保存前由调用方校验（[`MemConfig::validate`]）
+    pub async fn update_config(&self, user_id: i32, config: &MemConfig) -> Result<(), AppError> {
+        self.repo.save_config(user_id, config).await?;
+        Ok(())
+    }
+
+    // ── 预设 ──
+
+    pub async fn presets(&self, user_id: i32) -> Result<Vec<MemPreset>, ServiceError> {
+        Ok(self.repo.list_presets(user_id).await?)
+    }
+
+    async fn preset(&self, user_id: i32, id: i32) -> Result<MemPreset, ServiceError> {
+        self.presets(user_id)
+            .await?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| ServiceError::NotFound("预设不存在".into()))
+    }
+
+    pub async fn create_preset(
+        &self,
+        user_id: i32,
+        req: &PresetRequest,
+    ) -> Result<MemPreset, ServiceError> {
+        req.validate().map_err(ServiceError::InvalidInput)?;
+        let id = self
+            .repo
+            .create_preset(user_id, req)
+            .await
+            .map_err(preset_conflict)?;
+        self.preset(user_id, id).await
+    }
+
+    pub async fn update_preset(
+        &self,
+        user_id: i32,
+        id: i32,
+        req: &PresetRequest,
+    ) -> Result<MemPreset, ServiceError> {
+        req.validate().map_err(ServiceError::InvalidInput)?;
+        let updated = self
+            .repo
+            .update_preset(user_id, id, req)
+            .await
+            .map_err(preset_conflict)?;
+        if updated == 0 {
+            return Err(ServiceError::NotFound("预设不存在".into()));
+        }
+        self.preset(user_id, id).await
+    }
+
+    pub async fn delete_preset(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        Ok(self.repo.delete_preset(user_id, id).await?)
+    }
+
+    /// 为标签指定预设，`None` 取消指定
+    pub async fn set_tag_preset(
+        &self,
+        user_id: i32,
+        tag_id: i32,
+        preset_id: Option<i32>,
+    ) -> Result<(), ServiceError> {
+        if let Some(id) = preset_id {
+            self.preset(user_id, id).await?;
+        }
+        if self.repo.set_tag_preset(user_id, tag_id, preset_id).await? == 0 {
+            return Err(ServiceError::NotFound("标签不存在".into()));
+        }
+        Ok(())
+    }
+
+    /// 修剪过旧的 revlog
+    pub async fn prune_revlogs(&self) -> Result<(), sqlx::Error> {
+        self.repo.prune_revlogs().await
+    }
+
     // ── 挂起 / 恢复 ──
 
-    pub async fn suspend(&self, id: i32) -> Result<(), AppError> {
-        self.repo.get_mem(id).await?.ok_or(AppError::NotFound)?;
-        self.repo.suspend_mem(id).await.map_err(AppError::Db)?;
+    pub async fn suspend(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .suspend_mem(user_id, id)
+            .await
+            .map_err(AppError::Db)?;
+        self.changed(user_id, id, ChangeKind::Updated);
         Ok(())
     }
 
-    pub async fn unsuspend(&self, id: i32) -> Result<(), AppError> {
-        self.repo.get_mem(id).await?.ok_or(AppError::NotFound)?;
-        self.repo.unsuspend_mem(id).await.map_err(AppError::Db)?;
+    pub async fn unsuspend(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .unsuspend_mem(user_id, id)
+            .await
+            .map_err(AppError::Db)?;
+        self.changed(user_id, id, ChangeKind::Updated);
         Ok(())
     }
 
     // ── 批量操作 ──
 
-    pub async fn batch_delete(&self, ids: &[i32]) -> BatchResponse {
+    pub async fn batch_delete(&self, user_id: i32, ids: &[i32]) -> BatchResponse {
         let (_, errors) = batch_execute(ids.iter().copied(), |id| async move {
-            self.repo.delete_mem(id).await.map_err(|e| format!("{e}"))
+            let before = self
+                .snapshot(user_id, id)
+                .await
+                .map_err(|e| format!("{e}"))?;
+            self.repo
+                .delete_mem(user_id, id)
+                .await
+                .map_err(|e| format!("{e}"))?;
+            if let Some(before) = before {
+                self.audit.deleted(user_id, Entity::Mem, id, &before).await;
+            }
+            Ok::<_, String>(())
         })
         .await;
         BatchResponse::from_results(errors, ids.len())
     }
 
-    pub async fn batch_bury(&self, ids: &[i32]) -> BatchResponse {
+    pub async fn batch_bury(&self, user_id: i32, ids: &[i32]) -> BatchResponse {
         let (_, errors) = batch_execute(ids.iter().copied(), |id| async move {
-            self.repo.bury_mem(id).await.map_err(|e| format!("{e}"))
+            self.repo
+                .bury_mem(user_id, id)
+                .await
+                .map_err(|e| format!("{e}"))?;
+            self.changed(user_id, id, ChangeKind::Updated);
+            Ok::<_, String>(())
         })
         .await;
         BatchResponse::from_results(errors, ids.len())
     }
 
-    pub async fn batch_reset(&self, ids: &[i32]) -> BatchResponse {
+    pub async fn batch_reset(&self, user_id: i32, ids: &[i32]) -> BatchResponse {
         let (_, errors) = batch_execute(ids.iter().copied(), |id| async move {
-            self.repo.reset_mem(id).await.map_err(|e| format!("{e}"))
+            self.repo
+                .reset_mem(user_id, id)
+                .await
+                .map_err(|e| format!("{e}"))?;
+            self.changed(user_id, id, ChangeKind::Updated);
+            Ok::<_, String>(())
         })
         .await;
         BatchResponse::from_results(errors, ids.len())
@@ -276,17 +596,27 @@ impl MemService {
 
     // ── CRUD ──
 
-    pub async fn create(&self, req: CreateMemRequest) -> Result<i32, sqlx::Error> {
-        let cue_id = self.repo.create_chunk(&req.cue_content).await?;
-        let target_id = self.repo.create_chunk(&req.target_content).await?;
-        self.repo
-            .create_mem(cue_id, target_id, &req.prerequisites)
-            .await
+    pub async fn create(&self, user_id: i32, req: CreateMemRequest) -> Result<i32, sqlx::Error> {
+        let cue_id = self.repo.create_chunk(user_id, &req.cue_content).await?;
+        let target_id = self.repo.create_chunk(user_id, &req.target_content).await?;
+        let id = self
+            .repo
+            .create_mem(user_id, cue_id, target_id, &req.prerequisites)
+            .await?;
+        if let Some(after) = self.snapshot(user_id, id).await? {
+            self.audit.created(user_id, Entity::Mem, id, &after).await;
+        }
+        Ok(id)
     }
 
-    pub async fn undo(&self, id: i32, req: UndoRequest) -> Result<(), sqlx::Error> {
+    pub async fn undo(&self, user_id: i32, id: i32, req: UndoRequest) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         self.repo
             .update_mem_fsrs(
+                user_id,
                 id,
                 &FsrsUpdate {
                     state: req.state.clone(),
@@ -296,35 +626,135 @@ impl MemService {
                     lapses: req.lapses,
                     leeched: req.leeched,
                     due_at: req.due_at.clone(),
+                    last_review_at: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
                 },
             )
             .await
+            .map_err(AppError::Db)?;
+        self.changed(user_id, id, ChangeKind::Updated);
+        Ok(())
     }
 
-    pub async fn edit(&self, id: i32, req: EditMemRequest) -> Result<(), AppError> {
-        let row = self.repo.get_mem(id).await?.ok_or(AppError::NotFound)?;
+    pub async fn edit(&self, user_id: i32, id: i32, req: EditMemRequest) -> Result<(), AppError> {
+        self.edit_if(user_id, id, req, &IfMatch::Absent)
+            .await
+            .map(|_| ())
+    }
+
+    /// 按 `If-Match` 条件改写正反面，返回新的内容版本号。
+    /// 版本校验与写入在同一事务的同一条 UPDATE 中完成，不符时返回带最新内容的 412
+    pub async fn edit_if(
+        &self,
+        user_id: i32,
+        id: i32,
+        req: EditMemRequest,
+        if_match: &IfMatch,
+    ) -> Result<i64, AppError> {
+        let before = self.snapshot(user_id, id).await?;
+        let versions = if_match.versions();
+        let Some(version) = self
+            .repo
+            .edit_content(
+                user_id,
+                id,
+                &req.cue_content,
+                &req.target_content,
+                versions.as_deref(),
+            )
+            .await?
+        else {
+            return match self.repo.get_content(user_id, id).await? {
+                Some(current) => Err(AppError::PreconditionFailed {
+                    etag: etag::version(current.version),
+                    current: serde_json::to_value(current.row).unwrap_or_default(),
+                }),
+                None => Err(AppError::NotFound),
+            };
+        };
+        self.audit_update(user_id, id, before).await?;
+        Ok(version)
+    }
+
+    pub async fn bury(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         self.repo
-            .update_chunk(row.cue_chunk_id, &req.cue_content)
+            .bury_mem(user_id, id)
             .await
             .map_err(AppError::Db)?;
+        self.changed(user_id, id, ChangeKind::Updated);
+        Ok(())
+    }
+    pub async fn unbury(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         self.repo
-            .update_chunk(row.target_chunk_id, &req.target_content)
+            .unbury_mem(user_id, id)
             .await
             .map_err(AppError::Db)?;
+        self.changed(user_id, id, ChangeKind::Updated);
+        Ok(())
+    }
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        let before = self
+            .snapshot(user_id, id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .delete_mem(user_id, id)
+            .await
+            .map_err(AppError::Db)?;
+        self.audit.deleted(user_id, Entity::Mem, id, &before).await;
         Ok(())
     }
 
-    pub async fn bury(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.repo.bury_mem(id).await
+    /// 从回收站恢复记忆项
+    pub async fn restore(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        if !self.repo.restore_mem(user_id, id).await? {
+            return Err(AppError::NotFound);
+        }
+        self.audit.restored(user_id, Entity::Mem, id).await;
+        Ok(())
     }
-    pub async fn unbury(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.repo.unbury_mem(id).await
+
+    /// 彻底删除回收站中的记忆项
+    pub async fn purge(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        match self.repo.purge_mem(user_id, id).await {
+            Err(sqlx::Error::RowNotFound) => return Err(AppError::NotFound),
+            other => other?,
+        }
+        self.audit.purged(user_id, Entity::Mem, id).await;
+        Ok(())
     }
-    pub async fn delete(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.repo.delete_mem(id).await
+    pub async fn reset(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        let before = self.snapshot(user_id, id).await?;
+        if before.is_none() {
+            return Err(AppError::NotFound);
+        }
+        self.repo
+            .reset_mem(user_id, id)
+            .await
+            .map_err(AppError::Db)?;
+        self.audit_update(user_id, id, before).await?;
+        Ok(())
     }
-    pub async fn reset(&self, id: i32) -> Result<(), sqlx::Error> {
-        self.repo.reset_mem(id).await
+
+    async fn audit_update(
+        &self,
+        user_id: i32,
+        id: i32,
+        before: Option<serde_json::Value>,
+    ) -> Result<(), sqlx::Error> {
+        if let (Some(before), Some(after)) = (before, self.snapshot(user_id, id).await?) {
+            self.audit
+                .updated(user_id, Entity::Mem, id, &before, &after)
+                .await;
+        }
+        Ok(())
     }
 
     // ── 标签 ──
@@ -336,70 +766,122 @@ impl MemService {
             .map_err(AppError::Db)
     }
 
-    pub async fn delete_tag(&self, id: i32) -> Result<(), AppError> {
-        self.repo.delete_tag(id).await.map_err(AppError::Db)?;
+    pub async fn delete_tag(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.repo
+            .delete_tag(user_id, id)
+            .await
+            .map_err(AppError::Db)?;
         Ok(())
     }
 
-    pub async fn add_tag_to_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), AppError> {
-        self.repo.get_mem(mem_id).await?.ok_or(AppError::NotFound)?;
+    pub async fn add_tag_to_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         self.repo
-            .add_tag_to_mem(mem_id, tag_id)
+            .add_tag_to_mem(user_id, mem_id, tag_id)
             .await
             .map_err(AppError::Db)?;
+        self.changed(user_id, mem_id, ChangeKind::Updated);
         Ok(())
     }
 
-    pub async fn remove_tag_from_mem(&self, mem_id: i32, tag_id: i32) -> Result<(), AppError> {
+    pub async fn remove_tag_from_mem(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_id: i32,
+    ) -> Result<(), AppError> {
         self.repo
-            .remove_tag_from_mem(mem_id, tag_id)
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .remove_tag_from_mem(user_id, mem_id, tag_id)
             .await
             .map_err(AppError::Db)?;
+        self.changed(user_id, mem_id, ChangeKind::Updated);
         Ok(())
     }
 
-    pub async fn set_mem_tags(&self, mem_id: i32, tag_ids: &[i32]) -> Result<(), AppError> {
-        self.repo.get_mem(mem_id).await?.ok_or(AppError::NotFound)?;
+    pub async fn set_mem_tags(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        tag_ids: &[i32],
+    ) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
         self.repo
-            .set_mem_tags(mem_id, tag_ids)
+            .set_mem_tags(user_id, mem_id, tag_ids)
             .await
             .map_err(AppError::Db)?;
+        self.changed(user_id, mem_id, ChangeKind::Updated);
         Ok(())
     }
 
     // ── 批量标签 ──
 
-    pub async fn batch_add_tag_to_mems(&self, mem_ids: &[i32], tag_id: i32) -> BatchResponse {
+    pub async fn batch_add_tag_to_mems(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+        tag_id: i32,
+    ) -> BatchResponse {
         let (_, errors) = batch_execute_with_code(mem_ids.iter().copied(), |mem_id| async move {
             self.repo
-                .add_tag_to_mem(mem_id, tag_id)
+                .add_tag_to_mem(user_id, mem_id, tag_id)
                 .await
-                .map_err(|e| ("DB_ERROR", format!("{e}")))
+                .map_err(|e| ("DB_ERROR", format!("{e}")))?;
+            self.changed(user_id, mem_id, ChangeKind::Updated);
+            Ok(())
         })
         .await;
         BatchResponse::from_results(errors, mem_ids.len())
     }
 
-    pub async fn batch_remove_tag_from_mems(&self, mem_ids: &[i32], tag_id: i32) -> BatchResponse {
+    pub async fn batch_remove_tag_from_mems(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+        tag_id: i32,
+    ) -> BatchResponse {
         let (_, errors) = batch_execute_with_code(mem_ids.iter().copied(), |mem_id| async move {
             self.repo
-                .remove_tag_from_mem(mem_id, tag_id)
+                .remove_tag_from_mem(user_id, mem_id, tag_id)
                 .await
-                .map_err(|e| ("DB_ERROR", format!("{e}")))
+                .map_err(|e| ("DB_ERROR", format!("{e}")))?;
+            self.changed(user_id, mem_id, ChangeKind::Updated);
+            Ok(())
         })
         .await;
         BatchResponse::from_results(errors, mem_ids.len())
     }
 
-    pub async fn batch_set_tags_for_mems(&self, mem_ids: &[i32], tag_ids: &[i32]) -> BatchResponse {
+    pub async fn batch_set_tags_for_mems(
+        &self,
+        user_id: i32,
+        mem_ids: &[i32],
+        tag_ids: &[i32],
+    ) -> BatchResponse {
         let tag_ids = tag_ids.to_vec();
         let (_, errors) = batch_execute_with_code(mem_ids.iter().copied(), |mem_id| {
             let tag_ids = tag_ids.clone();
             async move {
                 self.repo
-                    .set_mem_tags(mem_id, &tag_ids)
+                    .set_mem_tags(user_id, mem_id, &tag_ids)
                     .await
-                    .map_err(|e| ("DB_ERROR", format!("{e}")))
+                    .map_err(|e| ("DB_ERROR", format!("{e}")))?;
+                self.changed(user_id, mem_id, ChangeKind::Updated);
+                Ok(())
             }
         })
         .await;
@@ -461,16 +943,25 @@ impl MemService {
                         continue;
                     }
 
-                    let cue_id = self.repo.create_chunk(cue).await.map_err(AppError::Db)?;
-                    let target_id = self.repo.create_chunk(target).await.map_err(AppError::Db)?;
+                    let cue_id = self
+                        .repo
+                        .create_chunk(user_id, cue)
+                        .await
+                        .map_err(AppError::Db)?;
+                    let target_id = self
+                        .repo
+                        .create_chunk(user_id, target)
+                        .await
+                        .map_err(AppError::Db)?;
                     let mem_id = self
                         .repo
-                        .create_mem(cue_id, target_id, &[])
+                        .create_mem(user_id, cue_id, target_id, &[])
                         .await
                         .map_err(AppError::Db)?;
 
                     self.apply_tags_to_mem(mem_id, tags_str, default_tags, user_id)
                         .await?;
+                    self.changed(user_id, mem_id, ChangeKind::Created);
 
                     count += 1;
                 }
@@ -517,7 +1008,7 @@ impl MemService {
                     .map_err(AppError::Db)?,
             };
             self.repo
-                .add_tag_to_mem(mem_id, tag.id)
+                .add_tag_to_mem(user_id, mem_id, tag.id)
                 .await
                 .map_err(AppError::Db)?;
         }
@@ -543,17 +1034,26 @@ impl MemService {
                 continue;
             }
 
-            let cue_id = self.repo.create_chunk(cue).await.map_err(AppError::Db)?;
-            let target_id = self.repo.create_chunk(target).await.map_err(AppError::Db)?;
+            let cue_id = self
+                .repo
+                .create_chunk(user_id, cue)
+                .await
+                .map_err(AppError::Db)?;
+            let target_id = self
+                .repo
+                .create_chunk(user_id, target)
+                .await
+                .map_err(AppError::Db)?;
             let mem_id = self
                 .repo
-                .create_mem(cue_id, target_id, &[])
+                .create_mem(user_id, cue_id, target_id, &[])
                 .await
                 .map_err(AppError::Db)?;
 
             let tags_str = item.tags.join("; ");
             self.apply_tags_to_mem(mem_id, &tags_str, default_tags, user_id)
                 .await?;
+            self.changed(user_id, mem_id, ChangeKind::Created);
 
             count += 1;
         }
@@ -561,43 +1061,147 @@ impl MemService {
         Ok((count, errors))
     }
 
-    // ── 助记 ──
+    /// 导入 Anki 包：卡片用到的媒体经 `media` 上传并改写引用，
+    /// 卡片连同调度状态、复习记录在一个事务内写入。有复习记录时排一次参数优化
+    pub async fn import_anki(
+        &self,
+        media: &MediaService,
+        user_id: i32,
+        file: std::fs::File,
+        default_tags: &[String],
+    ) -> Result<AnkiImportReport, ServiceError> {
+        let package = anki::Package::read(file).await?;
+        let mut report = AnkiImportReport::default();
+
+        let mut urls = std::collections::HashMap::new();
+        for name in package.referenced_media() {
+            let uploaded = match package.media(name).await {
+                Ok(data) => match MediaService::detect_mime(&data) {
+                    Some(mime) => media.upload(&data, name, &mime, user_id).await,
+                    None => Err(ServiceError::InvalidInput("无法识别文件类型".into())),
+                },
+                Err(e) => Err(e),
+            };
+            match uploaded {
+                Ok(m) => {
+                    urls.insert(name.to_string(), anki::media_url(&m.stored_id));
+                    report.media_files += 1;
+                }
+                Err(e) => report.errors.push(format!("媒体 {name}: {e}")),
+            }
+        }
 
-    pub async fn set_mnemonic(&self, mem_id: i32, content: &str) -> Result<(), sqlx::Error> {
-        self.repo.upsert_mnemonic(mem_id, content).await
+        let config = self.repo.get_config(user_id).await?;
+        let (mut mems, errors) = package.to_mems(&urls, &config.fsrs_params, chrono::Utc::now());
+        report.errors.extend(errors);
+        for m in &mut mems {
+            for tag in default_tags {
+                if !m.tags.contains(tag) {
+                    m.tags.push(tag.clone());
+                }
+            }
+        }
+        let ids = self.repo.import_scheduled(user_id, &mems).await?;
+        for &id in &ids {
+            self.changed(user_id, id, ChangeKind::Created);
+        }
+        report.imported = ids.len();
+        report.reviews = mems.iter().map(|m| m.revlog.len()).sum();
+
+        if report.reviews > 0 {
+            let job = NewJob::new(JobKind::FsrsOptimize)
+                .user(user_id)
+                .dedupe(optimize_dedupe_key(user_id));
+            if let Err(e) = self.jobs.enqueue(job).await {
+                tracing::warn!("导入后排队参数优化失败: {e}");
+            }
+        }
+        Ok(report)
     }
-}
 
-/// 如果 revlog 条数达到 `every` 的整数倍，自动触发 FSRS 参数优化。
-async fn maybe_auto_optimize(repo: Arc<dyn MemRepository>, db: Arc<SqlitePool>, every: i64) {
-    let count = match repo.count_revlogs().await {
-        Ok(n) => n,
-        Err(_) => return,
-    };
-    if count < 10 || count % every != 0 {
-        return;
-    }
-
-    tracing::info!("触发自动优化: revlog 共 {} 条", count);
-    let config = MemConfig::load();
-    match crate::modules::mem::optimizer::optimize_fsrs_params(&db, &config).await {
-        Ok(Some(params)) => {
-            let mut cfg = config;
-            let ok = cfg.update_fsrs_params(params.clone()).is_ok();
-            crate::modules::mem::fsrs::set_global_params(params);
-            if ok {
-                tracing::info!("自动优化完成, 参数已更新 (文件 + 运行时)");
-            } else {
-                tracing::warn!("自动优化完成但保存文件失败, 仅运行时生效");
+    /// 导入 `export_full` 的结果：调度状态与复习记录原样写入，不重新计算。
+    /// 格式或版本不符时整体拒绝；单项无效时跳过并记入错误
+    pub async fn import_full(
+        &self,
+        user_id: i32,
+        export: &MemExport,
+        default_tags: &[String],
+    ) -> Result<(usize, Vec<String>), ServiceError> {
+        if export.format != MEM_EXPORT_FORMAT {
+            return Err(ServiceError::InvalidInput(format!(
+                "不是 BRainbow 完整导出文件（format 须为 {MEM_EXPORT_FORMAT}）"
+            )));
+        }
+        if export.version != MEM_EXPORT_VERSION {
+            return Err(ServiceError::InvalidInput(format!(
+                "不支持的导出版本 {}",
+                export.version
+            )));
+        }
+        export.check_ids().map_err(ServiceError::InvalidInput)?;
+
+        let mut errors = Vec::new();
+        let (valid, invalid): (Vec<_>, Vec<_>) = export
+            .mems
+            .iter()
+            .enumerate()
+            .partition(|(_, item)| item.validate().is_ok());
+        let mut skipped = std::collections::HashSet::new();
+        for (i, item) in invalid {
+            if let Err(e) = item.validate() {
+                errors.push(format!("项 {}: {e}", i + 1));
+            }
+            skipped.insert(item.id);
+        }
+        let mut mems = Vec::with_capacity(valid.len());
+        for (i, item) in valid {
+            let mut m = item.clone();
+            // 依赖的项无效被跳过时，只丢弃这条依赖并报告
+            m.prerequisites.retain(|p| {
+                let keep = !skipped.contains(p);
+                if !keep {
+                    errors.push(format!("项 {}: 前置依赖 {p} 未导入，已忽略", i + 1));
+                }
+                keep
+            });
+            for tag in default_tags {
+                if !m.tags.contains(tag) {
+                    m.tags.push(tag.clone());
+                }
             }
+            mems.push(m);
+        }
+
+        let ids = self.repo.import_scheduled(user_id, &mems).await?;
+        for &id in &ids {
+            self.changed(user_id, id, ChangeKind::Created);
         }
-        Ok(None) => {}
-        Err(e) => {
-            tracing::warn!("自动优化失败: {e}");
+        if mems.iter().any(|m| !m.revlog.is_empty()) {
+            let job = NewJob::new(JobKind::FsrsOptimize)
+                .user(user_id)
+                .dedupe(optimize_dedupe_key(user_id));
+            if let Err(e) = self.jobs.enqueue(job).await {
+                tracing::warn!("导入后排队参数优化失败: {e}");
+            }
         }
+        Ok((ids.len(), errors))
     }
 
-    if let Err(e) = repo.prune_revlogs().await {
-        tracing::warn!("revlog 修剪失败: {e}");
+    // ── 助记 ──
+
+    pub async fn set_mnemonic(
+        &self,
+        user_id: i32,
+        mem_id: i32,
+        content: &str,
+    ) -> Result<(), AppError> {
+        self.repo
+            .get_mem(user_id, mem_id)
+            .await?
+            .ok_or(AppError::NotFound)?;
+        self.repo
+            .upsert_mnemonic(user_id, mem_id, content)
+            .await
+            .map_err(AppError::Db)
     }
 }
@@ -1,13 +1,23 @@
+pub mod account;
+pub mod activity;
+pub mod api_token;
 pub mod bookmark;
 pub mod card;
 pub mod conv;
 pub mod db_viewer;
+pub mod events;
+pub mod job;
 pub mod media;
 pub mod mem;
 pub mod onto;
 pub mod reading;
+pub mod search;
+pub mod session;
 pub mod sign;
+pub mod sync;
 pub mod task;
 pub mod text;
 pub mod time_window;
+pub mod trash;
 pub mod user;
+pub mod webhook;
@@ -1,26 +1,29 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::auth::Claims;
 use crate::error;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateOntoRequest {
     pub name: String,
     pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateOntoRequest {
     pub name: Option<String>,
     pub description: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OntoResponse {
     pub id: i32,
     pub name: String,
@@ -37,25 +40,41 @@ impl From<super::model::Onto> for OntoResponse {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建本体",
+    request_body = CreateOntoRequest,
+    responses((status = 201, description = "已创建", body = OntoResponse))
+)]
 pub async fn create_onto_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateOntoRequest>,
 ) -> impl IntoResponse {
     let result = state
         .onto
-        .create(payload.name, payload.description)
+        .create(claims.sub, payload.name, payload.description)
         .await
         .map(OntoResponse::from);
     error::created_or(result, "创建本体")
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "本体列表",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<OntoResponse>))
+)]
 pub async fn get_ontos_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let result = state
         .onto
-        .list(pagination.limit(), pagination.offset())
+        .list(claims.sub, pagination.limit(), pagination.offset())
         .await
         .map(|(items, total)| {
             let items: Vec<OntoResponse> = items.into_iter().map(OntoResponse::from).collect();
@@ -64,34 +83,60 @@ pub async fn get_ontos_handler(
     error::ok_or(result, "获取本体列表")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取本体",
+    responses((status = 200, description = "成功", body = OntoResponse))
+)]
 pub async fn get_onto_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
     let result = state
         .onto
-        .by_id(id)
+        .by_id(claims.sub, id)
         .await
         .map(|opt| opt.map(OntoResponse::from));
     error::found_or(result, "获取本体")
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "更新本体",
+    request_body = UpdateOntoRequest,
+    responses((status = 200, description = "成功", body = OntoResponse))
+)]
 pub async fn update_onto_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
     Json(payload): Json<UpdateOntoRequest>,
 ) -> impl IntoResponse {
     let result = state
         .onto
-        .update(id, payload.name, payload.description)
+        .update(claims.sub, id, payload.name, payload.description)
         .await
         .map(OntoResponse::from);
-    error::ok_or(result, "更新本体")
+    // 不存在或属于其他用户 → 404
+    match result {
+        Ok(onto) => Json(onto).into_response(),
+        Err(e) => e.into_response(),
+    }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除本体",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_onto_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    error::deleted_or(state.onto.delete(id).await, "删除本体")
+    error::deleted_or(state.onto.delete(claims.sub, id).await, "删除本体")
 }
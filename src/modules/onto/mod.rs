@@ -4,22 +4,20 @@ mod repository;
 pub mod service;
 
 pub use service::OntoService;
-
-pub use handler::{
-    create_onto_handler, delete_onto_handler, get_onto_handler, get_ontos_handler,
-    update_onto_handler,
-};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{Router, routing::get};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(get_ontos_handler).post(create_onto_handler))
-        .route(
-            "/{id}",
-            get(get_onto_handler)
-                .patch(update_onto_handler)
-                .delete(delete_onto_handler),
-        )
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::get_ontos_handler,
+            handler::create_onto_handler
+        ))
+        .routes(routes!(
+            handler::get_onto_handler,
+            handler::update_onto_handler,
+            handler::delete_onto_handler
+        ))
 }
@@ -17,15 +17,18 @@ impl OntoRepository {
 
     pub async fn find_all_paginated(
         &self,
+        user_id: i32,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<Onto>, i64), sqlx::Error> {
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM onto")
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM onto WHERE user_id = ?")
+            .bind(user_id)
             .fetch_one(&*self.db)
             .await?;
         let items = sqlx::query_as::<_, Onto>(
-            "SELECT id, name, description FROM onto ORDER BY id LIMIT ? OFFSET ?",
+            "SELECT id, name, description FROM onto WHERE user_id = ? ORDER BY id LIMIT ? OFFSET ?",
         )
+        .bind(user_id)
         .bind(limit)
         .bind(offset)
         .fetch_all(&*self.db)
@@ -34,24 +37,29 @@ impl OntoRepository {
     }
 
     /// 根据ID获取本体
-    pub async fn find_by_id(&self, id: i32) -> Result<Option<Onto>, sqlx::Error> {
-        sqlx::query_as::<_, Onto>("SELECT id, name, description FROM onto WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&*self.db)
-            .await
+    pub async fn find_by_id(&self, user_id: i32, id: i32) -> Result<Option<Onto>, sqlx::Error> {
+        sqlx::query_as::<_, Onto>(
+            "SELECT id, name, description FROM onto WHERE id = ? AND user_id = ?",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.db)
+        .await
     }
 
     /// 创建本体
     pub async fn create(
         &self,
+        user_id: i32,
         name: String,
         description: Option<String>,
     ) -> Result<Onto, sqlx::Error> {
         let result = sqlx::query(
-            "INSERT INTO onto (name, description) VALUES (?, ?) RETURNING id, name, description",
+            "INSERT INTO onto (name, description, user_id) VALUES (?, ?, ?) RETURNING id, name, description",
         )
         .bind(&name)
         .bind(&description)
+        .bind(user_id)
         .fetch_one(&*self.db)
         .await?;
 
@@ -63,9 +71,10 @@ impl OntoRepository {
     }
 
     /// 删除本体
-    pub async fn delete(&self, id: i32) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query("DELETE FROM onto WHERE id = ?")
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM onto WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.db)
             .await?;
 
@@ -75,6 +84,7 @@ impl OntoRepository {
     /// 更新本体
     pub async fn update(
         &self,
+        user_id: i32,
         id: i32,
         name: Option<String>,
         description: Option<String>,
@@ -102,13 +112,15 @@ impl OntoRepository {
 
         if !has_updates {
             return self
-                .find_by_id(id)
+                .find_by_id(user_id, id)
                 .await?
                 .ok_or_else(|| sqlx::Error::RowNotFound);
         }
 
         builder.push(" WHERE id = ");
         builder.push_bind(id);
+        builder.push(" AND user_id = ");
+        builder.push_bind(user_id);
         builder.push(" RETURNING id, name, description");
 
         let result = builder.build().fetch_one(&*self.db).await?;
@@ -129,7 +141,7 @@ mod tests {
 
     async fn setup_db() -> OntoRepository {
         let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-        sqlx::query("CREATE TABLE onto (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT)")
+        sqlx::query("CREATE TABLE onto (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT, user_id INTEGER NOT NULL)")
             .execute(&pool).await.unwrap();
         OntoRepository::new(Arc::new(pool))
     }
@@ -138,23 +150,23 @@ mod tests {
     async fn create_and_find() {
         let repo = setup_db().await;
         let onto = repo
-            .create("test-name".into(), Some("desc".into()))
+            .create(1, "test-name".into(), Some("desc".into()))
             .await
             .unwrap();
         assert!(onto.id > 0);
         assert_eq!(onto.name, "test-name");
         assert_eq!(onto.description, Some("desc".into()));
 
-        let found = repo.find_by_id(onto.id).await.unwrap().unwrap();
+        let found = repo.find_by_id(1, onto.id).await.unwrap().unwrap();
         assert_eq!(found.name, "test-name");
     }
 
     #[tokio::test]
     async fn find_all_paginated() {
         let repo = setup_db().await;
-        repo.create("A".into(), None).await.unwrap();
-        repo.create("B".into(), None).await.unwrap();
-        let (items, total) = repo.find_all_paginated(10, 0).await.unwrap();
+        repo.create(1, "A".into(), None).await.unwrap();
+        repo.create(1, "B".into(), None).await.unwrap();
+        let (items, total) = repo.find_all_paginated(1, 10, 0).await.unwrap();
         assert_eq!(total, 2);
         assert_eq!(items.len(), 2);
     }
@@ -162,18 +174,18 @@ mod tests {
     #[tokio::test]
     async fn find_by_id_not_found() {
         let repo = setup_db().await;
-        assert!(repo.find_by_id(999).await.unwrap().is_none());
+        assert!(repo.find_by_id(1, 999).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn update_name_and_description() {
         let repo = setup_db().await;
         let onto = repo
-            .create("old".into(), Some("old-desc".into()))
+            .create(1, "old".into(), Some("old-desc".into()))
             .await
             .unwrap();
         let updated = repo
-            .update(onto.id, Some("new".into()), Some("new-desc".into()))
+            .update(1, onto.id, Some("new".into()), Some("new-desc".into()))
             .await
             .unwrap();
         assert_eq!(updated.name, "new");
@@ -183,20 +195,35 @@ mod tests {
     #[tokio::test]
     async fn update_nonexistent_fails() {
         let repo = setup_db().await;
-        assert!(repo.update(999, Some("x".into()), None).await.is_err());
+        assert!(repo.update(1, 999, Some("x".into()), None).await.is_err());
     }
 
     #[tokio::test]
     async fn delete_existing() {
         let repo = setup_db().await;
-        let onto = repo.create("x".into(), None).await.unwrap();
-        assert_eq!(repo.delete(onto.id).await.unwrap(), 1);
-        assert!(repo.find_by_id(onto.id).await.unwrap().is_none());
+        let onto = repo.create(1, "x".into(), None).await.unwrap();
+        assert_eq!(repo.delete(1, onto.id).await.unwrap(), 1);
+        assert!(repo.find_by_id(1, onto.id).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn delete_nonexistent() {
         let repo = setup_db().await;
-        assert_eq!(repo.delete(999).await.unwrap(), 0);
+        assert_eq!(repo.delete(1, 999).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn other_users_onto_is_invisible() {
+        let repo = setup_db().await;
+        let onto = repo.create(1, "mine".into(), None).await.unwrap();
+        assert!(repo.find_by_id(2, onto.id).await.unwrap().is_none());
+        assert!(
+            repo.update(2, onto.id, Some("x".into()), None)
+                .await
+                .is_err()
+        );
+        assert_eq!(repo.delete(2, onto.id).await.unwrap(), 0);
+        let (_, total) = repo.find_all_paginated(2, 10, 0).await.unwrap();
+        assert_eq!(total, 0);
     }
 }
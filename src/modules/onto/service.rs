@@ -16,19 +16,28 @@ impl OntoService {
         }
     }
 
-    pub async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<Onto>, i64), ServiceError> {
+    pub async fn list(
+        &self,
+        user_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Onto>, i64), ServiceError> {
         self.repo
-            .find_all_paginated(limit, offset)
+            .find_all_paginated(user_id, limit, offset)
             .await
             .map_err(ServiceError::Db)
     }
 
-    pub async fn by_id(&self, id: i32) -> Result<Option<Onto>, ServiceError> {
-        self.repo.find_by_id(id).await.map_err(ServiceError::Db)
+    pub async fn by_id(&self, user_id: i32, id: i32) -> Result<Option<Onto>, ServiceError> {
+        self.repo
+            .find_by_id(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 
     pub async fn create(
         &self,
+        user_id: i32,
         name: String,
         description: Option<String>,
     ) -> Result<Onto, ServiceError> {
@@ -36,19 +45,20 @@ impl OntoService {
             return Err(ServiceError::InvalidInput("本体名称不能为空".into()));
         }
         self.repo
-            .create(name, description)
+            .create(user_id, name, description)
             .await
             .map_err(ServiceError::Db)
     }
 
     pub async fn update(
         &self,
+        user_id: i32,
         id: i32,
         name: Option<String>,
         description: Option<String>,
     ) -> Result<Onto, ServiceError> {
         self.repo
-            .update(id, name, description)
+            .update(user_id, id, name, description)
             .await
             .map_err(|e| match e {
                 sqlx::Error::RowNotFound => ServiceError::NotFound("本体不存在".into()),
@@ -56,8 +66,11 @@ impl OntoService {
             })
     }
 
-    pub async fn delete(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete(id).await.map_err(ServiceError::Db)
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        self.repo
+            .delete(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 }
 
@@ -69,7 +82,7 @@ mod tests {
 
     async fn real_service() -> (OntoService, Arc<SqlitePool>) {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE onto (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT)")
+        sqlx::query("CREATE TABLE onto (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, description TEXT, user_id INTEGER NOT NULL)")
             .execute(&*pool).await.unwrap();
         (OntoService::new(pool.clone()), pool)
     }
@@ -78,7 +91,7 @@ mod tests {
     async fn create_valid() {
         let (svc, _) = real_service().await;
         let onto = svc
-            .create("onto-a".into(), Some("desc".into()))
+            .create(1, "onto-a".into(), Some("desc".into()))
             .await
             .unwrap();
         assert_eq!(onto.name, "onto-a");
@@ -87,29 +100,31 @@ mod tests {
     #[tokio::test]
     async fn create_empty_name_rejected() {
         let (svc, _) = real_service().await;
-        let err = svc.create("  ".into(), None).await.unwrap_err();
+        let err = svc.create(1, "  ".into(), None).await.unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
     }
 
     #[tokio::test]
     async fn list_and_by_id() {
         let (svc, _) = real_service().await;
-        svc.create("a".into(), None).await.unwrap();
-        let (items, total) = svc.list(10, 0).await.unwrap();
+        svc.create(1, "a".into(), None).await.unwrap();
+        let (items, total) = svc.list(1, 10, 0).await.unwrap();
         assert_eq!(total, 1);
         assert_eq!(items[0].name, "a");
-        assert!(svc.by_id(items[0].id).await.unwrap().is_some());
-        assert!(svc.by_id(999).await.unwrap().is_none());
+        assert!(svc.by_id(1, items[0].id).await.unwrap().is_some());
+        assert!(svc.by_id(1, 999).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn update_and_delete() {
         let (svc, _) = real_service().await;
-        let onto = svc.create("x".into(), None).await.unwrap();
-        svc.update(onto.id, Some("y".into()), None).await.unwrap();
-        let u = svc.by_id(onto.id).await.unwrap().unwrap();
+        let onto = svc.create(1, "x".into(), None).await.unwrap();
+        svc.update(1, onto.id, Some("y".into()), None)
+            .await
+            .unwrap();
+        let u = svc.by_id(1, onto.id).await.unwrap().unwrap();
         assert_eq!(u.name, "y");
-        svc.delete(onto.id).await.unwrap();
-        assert!(svc.by_id(onto.id).await.unwrap().is_none());
+        svc.delete(1, onto.id).await.unwrap();
+        assert!(svc.by_id(1, onto.id).await.unwrap().is_none());
     }
 }
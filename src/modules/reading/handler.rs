@@ -1,42 +1,80 @@
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, State},
     response::IntoResponse,
 };
 use serde_json::json;
 
+use crate::auth::Claims;
 use crate::error;
+use crate::openapi::OkFlag;
 use crate::state::AppState;
 
-use super::model::{ArticleDetail, MarkWordRequest, UploadArticleRequest};
+use super::model::{
+    ArticleDetail, ArticleList, ArticleNotes, ArticleWords, MarkWordRequest, Recommendation,
+    UnknownWords, UploadArticleRequest, UploadedArticle,
+};
 use super::repository;
 
 /// 文章列表（含认识率）
-pub async fn list_articles(State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "文章列表",
+    responses((status = 200, description = "成功", body = ArticleList))
+)]
+pub async fn list_articles(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.get_all_article_summaries().await {
-        Ok(summaries) => Json(json!({"articles": summaries})).into_response(),
+    match repo.get_all_article_summaries(claims.sub).await {
+        Ok(summaries) => Json(ArticleList {
+            articles: summaries,
+        })
+        .into_response(),
         Err(e) => error::internal(e, "获取文章列表"),
     }
 }
 
 /// 上传文章
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "上传文章",
+    request_body = UploadArticleRequest,
+    responses((status = 200, description = "成功", body = UploadedArticle))
+)]
 pub async fn upload_article(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(body): Json<UploadArticleRequest>,
 ) -> impl IntoResponse {
     let svc = &state.reading;
-    match svc.upload_article(&body.title, &body.content).await {
-        Ok(article) => Json(json!({"article": article})).into_response(),
+    match svc
+        .upload_article(claims.sub, &body.title, &body.content)
+        .await
+    {
+        Ok(article) => Json(UploadedArticle { article }).into_response(),
         Err(e) => error::internal(e, "上传文章"),
     }
 }
 
 /// 获取单篇文章详情（含词状态 + notes）
-pub async fn get_article(State(state): State<AppState>, Path(id): Path<i64>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "文章详情（含词汇状态）",
+    responses((status = 200, description = "成功", body = ArticleDetail))
+)]
+pub async fn get_article(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.get_article(id).await {
-        Ok(Some(article)) => match repo.get_article_word_statuses(id).await {
+    match repo.get_article(claims.sub, id).await {
+        Ok(Some(article)) => match repo.get_article_word_statuses(claims.sub, id).await {
             Ok(words) => Json(ArticleDetail { article, words }).into_response(),
             Err(e) => error::internal(e, "获取文章词状态"),
         },
@@ -46,71 +84,127 @@ pub async fn get_article(State(state): State<AppState>, Path(id): Path<i64>) ->
 }
 
 /// 获取文章中的所有词
+#[utoipa::path(
+    get,
+    path = "/{id}/words",
+    summary = "文章词汇状态",
+    responses((status = 200, description = "成功", body = ArticleWords))
+)]
 pub async fn get_article_words(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.get_article_words(id).await {
-        Ok(words) => Json(json!({"words": words})).into_response(),
+    match repo.get_article_words(claims.sub, id).await {
+        Ok(words) => Json(ArticleWords { words }).into_response(),
         Err(e) => error::internal(e, "获取文章词表"),
     }
 }
 
 /// 标记单词
+#[utoipa::path(
+    post,
+    path = "/word/{word}",
+    summary = "标记单词",
+    request_body = MarkWordRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn mark_word(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(word): Path<String>,
     Json(body): Json<MarkWordRequest>,
 ) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.upsert_user_word(&word, &body.status).await {
+    match repo.upsert_user_word(claims.sub, &word, &body.status).await {
         Ok(()) => Json(json!({"ok": true})).into_response(),
         Err(e) => error::internal(e, "标记单词"),
     }
 }
 
 /// 获取所有不认识词
-pub async fn list_unknown_words(State(state): State<AppState>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/unknown",
+    summary = "生词列表",
+    responses((status = 200, description = "成功", body = UnknownWords))
+)]
+pub async fn list_unknown_words(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.get_unknown_words().await {
-        Ok(words) => Json(json!({"words": words})).into_response(),
+    match repo.get_unknown_words(claims.sub).await {
+        Ok(words) => Json(UnknownWords { words }).into_response(),
         Err(e) => error::internal(e, "获取不认识词列表"),
     }
 }
 
 /// 推荐下一篇（认识率最接近 90%）
+#[utoipa::path(
+    get,
+    path = "/{id}/recommend",
+    summary = "推荐下一篇",
+    responses((status = 200, description = "成功", body = Recommendation))
+)]
 pub async fn recommend_next(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.recommend_article(id, 0.9).await {
-        Ok(article) => Json(json!({"recommended": article})).into_response(),
+    match repo.recommend_article(claims.sub, id, 0.9).await {
+        Ok(article) => Json(Recommendation {
+            recommended: article,
+        })
+        .into_response(),
         Err(e) => error::internal(e, "推荐下一篇"),
     }
 }
 
 /// 获取文章笔记
-pub async fn get_notes(State(state): State<AppState>, Path(id): Path<i64>) -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/{id}/notes",
+    summary = "获取笔记",
+    responses((status = 200, description = "成功", body = ArticleNotes))
+)]
+pub async fn get_notes(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i64>,
+) -> impl IntoResponse {
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.get_article(id).await {
-        Ok(Some(article)) => Json(json!({"notes": article.notes})).into_response(),
+    match repo.get_article(claims.sub, id).await {
+        Ok(Some(article)) => Json(ArticleNotes {
+            notes: article.notes,
+        })
+        .into_response(),
         Ok(None) => error::not_found("文章未找到"),
         Err(e) => error::internal(e, "获取笔记"),
     }
 }
 
 /// 更新文章笔记
+#[utoipa::path(
+    put,
+    path = "/{id}/notes",
+    summary = "保存笔记",
+    request_body = serde_json::Value,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn update_notes(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i64>,
     Json(body): Json<serde_json::Value>,
 ) -> impl IntoResponse {
     let notes = body.get("notes").and_then(|v| v.as_str()).unwrap_or("");
     let repo = repository::ReadingRepo::new(state.db);
-    match repo.update_article_notes(id, notes).await {
-        Ok(()) => Json(json!({"ok": true})).into_response(),
+    match repo.update_article_notes(claims.sub, id, notes).await {
+        Ok(0) => error::not_found("文章未找到"),
+        Ok(_) => Json(json!({"ok": true})).into_response(),
         Err(e) => error::internal(e, "更新笔记"),
     }
 }
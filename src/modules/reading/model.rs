@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// 文章
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Article {
     pub id: i64,
     pub title: String,
@@ -12,7 +13,7 @@ pub struct Article {
 }
 
 /// 文章列表项（含认识率）
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ArticleSummary {
     pub id: i64,
     pub title: String,
@@ -23,21 +24,21 @@ pub struct ArticleSummary {
 }
 
 /// 文章详情（含词状态）
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ArticleDetail {
     pub article: Article,
     pub words: Vec<ArticleWordStatus>,
 }
 
 /// 文章中每个词的认识状态
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ArticleWordStatus {
     pub word: String,
     pub status: String, // "known" | "unknown" | "ignored"
 }
 
 /// 不认识词条目
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UnknownWord {
     pub word: String,
     pub unknown_count: i64,
@@ -45,15 +46,51 @@ pub struct UnknownWord {
     pub first_seen_at: String,
 }
 
+/// 文章列表
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArticleList {
+    pub articles: Vec<ArticleSummary>,
+}
+
+/// 上传后的文章
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadedArticle {
+    pub article: Article,
+}
+
+/// 文章中出现的词
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArticleWords {
+    pub words: Vec<String>,
+}
+
+/// 不认识词列表
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UnknownWords {
+    pub words: Vec<UnknownWord>,
+}
+
+/// 推荐的下一篇，没有合适的为 null
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Recommendation {
+    pub recommended: Option<ArticleSummary>,
+}
+
+/// 文章笔记
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArticleNotes {
+    pub notes: String,
+}
+
 /// 上传文章请求
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UploadArticleRequest {
     pub title: String,
     pub content: String,
 }
 
 /// 标记单词认识/不认识请求（word 从 URL 路径取）
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MarkWordRequest {
     pub status: String, // "known" | "unknown"
 }
@@ -16,16 +16,18 @@ impl ReadingRepo {
 
     pub async fn insert_article(
         &self,
+        user_id: i32,
         title: &str,
         content: &str,
         word_count: i64,
     ) -> Result<i64, sqlx::Error> {
         let result = sqlx::query(
-            "INSERT INTO reading_article (title, content, word_count) VALUES (?, ?, ?)",
+            "INSERT INTO reading_article (title, content, word_count, user_id) VALUES (?, ?, ?, ?)",
         )
         .bind(title)
         .bind(content)
         .bind(word_count)
+        .bind(user_id)
         .execute(&*self.pool)
         .await?;
         Ok(result.last_insert_rowid())
@@ -50,11 +52,12 @@ impl ReadingRepo {
         Ok(())
     }
 
-    pub async fn get_article(&self, id: i64) -> Result<Option<Article>, sqlx::Error> {
+    pub async fn get_article(&self, user_id: i32, id: i64) -> Result<Option<Article>, sqlx::Error> {
         sqlx::query_as::<_, (i64, String, String, i64, String, String)>(
-            "SELECT id, title, content, word_count, notes, created_at FROM reading_article WHERE id = ?",
+            "SELECT id, title, content, word_count, notes, created_at FROM reading_article WHERE id = ? AND user_id = ?",
         )
         .bind(id)
+        .bind(user_id)
         .fetch_optional(&*self.pool)
         .await
         .map(|row| {
@@ -69,10 +72,11 @@ impl ReadingRepo {
         })
     }
 
-    pub async fn get_all_articles(&self) -> Result<Vec<Article>, sqlx::Error> {
+    pub async fn get_all_articles(&self, user_id: i32) -> Result<Vec<Article>, sqlx::Error> {
         sqlx::query_as::<_, (i64, String, String, i64, String, String)>(
-            "SELECT id, title, content, word_count, notes, created_at FROM reading_article ORDER BY id DESC",
+            "SELECT id, title, content, word_count, notes, created_at FROM reading_article WHERE user_id = ? ORDER BY id DESC",
         )
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await
         .map(|rows| {
@@ -91,11 +95,22 @@ impl ReadingRepo {
 
     // ── 文章词表 ──
 
-    pub async fn get_article_words(&self, article_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    pub async fn get_article_words(
+        &self,
+        user_id: i32,
+        article_id: i64,
+    ) -> Result<Vec<String>, sqlx::Error> {
         sqlx::query_as::<_, (String,)>(
-            "SELECT word FROM reading_article_word WHERE article_id = ? ORDER BY id",
+            r#"
+            SELECT w.word
+            FROM reading_article_word w
+            JOIN reading_article a ON a.id = w.article_id
+            WHERE w.article_id = ? AND a.user_id = ?
+            ORDER BY w.id
+            "#,
         )
         .bind(article_id)
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await
         .map(|rows| rows.into_iter().map(|(w,)| w).collect())
@@ -104,18 +119,21 @@ impl ReadingRepo {
     /// 获取文章每词的认识状态
     pub async fn get_article_word_statuses(
         &self,
+        user_id: i32,
         article_id: i64,
     ) -> Result<Vec<ArticleWordStatus>, sqlx::Error> {
         let rows = sqlx::query_as::<_, (String, String)>(
             r#"
             SELECT w.word, COALESCE(uw.status, 'unknown') AS status
             FROM reading_article_word w
-            LEFT JOIN reading_user_word uw ON uw.word = w.word
-            WHERE w.article_id = ?
+            JOIN reading_article a ON a.id = w.article_id
+            LEFT JOIN reading_user_word uw ON uw.word = w.word AND uw.user_id = a.user_id
+            WHERE w.article_id = ? AND a.user_id = ?
             ORDER BY w.id
             "#,
         )
         .bind(article_id)
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await?;
 
@@ -126,19 +144,25 @@ impl ReadingRepo {
     }
 
     /// 计算文章认识率（已知词 / (总不同词数 - 忽略词)）
-    pub async fn get_article_known_ratio(&self, article_id: i64) -> Result<f64, sqlx::Error> {
+    pub async fn get_article_known_ratio(
+        &self,
+        user_id: i32,
+        article_id: i64,
+    ) -> Result<f64, sqlx::Error> {
         let row = sqlx::query_as::<_, (i64, i64)>(
             r#"
             SELECT
                 COUNT(*) AS total,
                 COALESCE(SUM(CASE WHEN uw.status = 'known' THEN 1 ELSE 0 END), 0) AS known
             FROM reading_article_word w
-            LEFT JOIN reading_user_word uw ON uw.word = w.word
-            WHERE w.article_id = ?
+            JOIN reading_article a ON a.id = w.article_id
+            LEFT JOIN reading_user_word uw ON uw.word = w.word AND uw.user_id = a.user_id
+            WHERE w.article_id = ? AND a.user_id = ?
               AND (uw.status IS NULL OR uw.status != 'ignored')
             "#,
         )
         .bind(article_id)
+        .bind(user_id)
         .fetch_one(&*self.pool)
         .await?;
 
@@ -152,13 +176,16 @@ impl ReadingRepo {
     }
 
     /// 获取所有文章的认识率摘要
-    pub async fn get_all_article_summaries(&self) -> Result<Vec<ArticleSummary>, sqlx::Error> {
-        let articles = self.get_all_articles().await?;
+    pub async fn get_all_article_summaries(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<ArticleSummary>, sqlx::Error> {
+        let articles = self.get_all_articles(user_id).await?;
         let mut summaries = Vec::with_capacity(articles.len());
 
         for article in articles {
             let known_ratio = self
-                .get_article_known_ratio(article.id)
+                .get_article_known_ratio(user_id, article.id)
                 .await
                 .unwrap_or(0.0);
 
@@ -166,11 +193,12 @@ impl ReadingRepo {
                 r#"
                 SELECT COUNT(*)
                 FROM reading_article_word w
-                LEFT JOIN reading_user_word uw ON uw.word = w.word
+                LEFT JOIN reading_user_word uw ON uw.word = w.word AND uw.user_id = ?
                 WHERE w.article_id = ?
                   AND (uw.status IS NULL OR uw.status = 'unknown')
             "#,
             )
+            .bind(user_id)
             .bind(article.id)
             .fetch_one(&*self.pool)
             .await
@@ -192,60 +220,76 @@ impl ReadingRepo {
 
     // ── 笔记 ──
 
-    pub async fn update_article_notes(&self, id: i64, notes: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE reading_article SET notes = ? WHERE id = ?")
-            .bind(notes)
-            .bind(id)
-            .execute(&*self.pool)
-            .await?;
-        Ok(())
+    /// 返回受影响行数（0 = 不存在或不属于该用户）
+    pub async fn update_article_notes(
+        &self,
+        user_id: i32,
+        id: i64,
+        notes: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result =
+            sqlx::query("UPDATE reading_article SET notes = ? WHERE id = ? AND user_id = ?")
+                .bind(notes)
+                .bind(id)
+                .bind(user_id)
+                .execute(&*self.pool)
+                .await?;
+        Ok(result.rows_affected())
     }
 
     // ── 用户词库 ──
 
-    pub async fn upsert_user_word(&self, word: &str, status: &str) -> Result<(), sqlx::Error> {
+    pub async fn upsert_user_word(
+        &self,
+        user_id: i32,
+        word: &str,
+        status: &str,
+    ) -> Result<(), sqlx::Error> {
         match status {
             "known" => {
                 sqlx::query(
                     r#"
-                    INSERT INTO reading_user_word (word, status, known_count, unknown_count, updated_at)
-                    VALUES (?, 'known', 1, 0, datetime('now'))
-                    ON CONFLICT(word) DO UPDATE SET
+                    INSERT INTO reading_user_word (word, user_id, status, known_count, unknown_count, updated_at)
+                    VALUES (?, ?, 'known', 1, 0, datetime('now'))
+                    ON CONFLICT(word, user_id) DO UPDATE SET
                         status = 'known',
                         known_count = known_count + 1,
                         updated_at = datetime('now')
                     "#,
                 )
                 .bind(word)
+                .bind(user_id)
                 .execute(&*self.pool)
                 .await?;
             }
             "ignored" => {
                 sqlx::query(
                     r#"
-                    INSERT INTO reading_user_word (word, status, known_count, unknown_count, updated_at)
-                    VALUES (?, 'ignored', 0, 0, datetime('now'))
-                    ON CONFLICT(word) DO UPDATE SET
+                    INSERT INTO reading_user_word (word, user_id, status, known_count, unknown_count, updated_at)
+                    VALUES (?, ?, 'ignored', 0, 0, datetime('now'))
+                    ON CONFLICT(word, user_id) DO UPDATE SET
                         status = 'ignored',
                         updated_at = datetime('now')
                     "#,
                 )
                 .bind(word)
+                .bind(user_id)
                 .execute(&*self.pool)
                 .await?;
             }
             _ => {
                 sqlx::query(
                     r#"
-                    INSERT INTO reading_user_word (word, status, unknown_count, known_count, updated_at)
-                    VALUES (?, 'unknown', 1, 0, datetime('now'))
-                    ON CONFLICT(word) DO UPDATE SET
+                    INSERT INTO reading_user_word (word, user_id, status, unknown_count, known_count, updated_at)
+                    VALUES (?, ?, 'unknown', 1, 0, datetime('now'))
+                    ON CONFLICT(word, user_id) DO UPDATE SET
                         status = 'unknown',
                         unknown_count = unknown_count + 1,
                         updated_at = datetime('now')
                     "#,
                 )
                 .bind(word)
+                .bind(user_id)
                 .execute(&*self.pool)
                 .await?;
             }
@@ -253,15 +297,16 @@ impl ReadingRepo {
         Ok(())
     }
 
-    pub async fn get_unknown_words(&self) -> Result<Vec<UnknownWord>, sqlx::Error> {
+    pub async fn get_unknown_words(&self, user_id: i32) -> Result<Vec<UnknownWord>, sqlx::Error> {
         sqlx::query_as::<_, (String, i64, i64, String)>(
             r#"
             SELECT word, unknown_count, known_count, first_seen_at
             FROM reading_user_word
-            WHERE status = 'unknown'
+            WHERE status = 'unknown' AND user_id = ?
             ORDER BY unknown_count DESC, word ASC
             "#,
         )
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await
         .map(|rows| {
@@ -283,10 +328,11 @@ impl ReadingRepo {
     /// 推荐认识率最接近 target_ratio 的文章（排除指定 ID）
     pub async fn recommend_article(
         &self,
+        user_id: i32,
         exclude_id: i64,
         target_ratio: f64,
     ) -> Result<Option<ArticleSummary>, sqlx::Error> {
-        let all = self.get_all_article_summaries().await?;
+        let all = self.get_all_article_summaries(user_id).await?;
         let best = all
             .into_iter()
             .filter(|a| a.id != exclude_id)
@@ -304,6 +350,8 @@ mod tests {
     #![allow(clippy::unwrap_used)]
     use super::*;
 
+    const USER: i32 = 1;
+
     async fn setup_db() -> ReadingRepo {
         let pool = SqlitePool::connect("sqlite::memory:")
             .await
@@ -316,6 +364,7 @@ mod tests {
                 content TEXT NOT NULL,
                 word_count INTEGER DEFAULT 0,
                 notes TEXT NOT NULL DEFAULT '',
+                user_id INTEGER,
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )",
         )
@@ -339,12 +388,14 @@ mod tests {
         sqlx::query(
             "CREATE TABLE reading_user_word (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                word TEXT NOT NULL UNIQUE,
+                word TEXT NOT NULL,
+                user_id INTEGER,
                 status TEXT NOT NULL DEFAULT 'unknown',
                 unknown_count INTEGER NOT NULL DEFAULT 0,
                 known_count INTEGER NOT NULL DEFAULT 0,
                 first_seen_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(word, user_id)
             )",
         )
         .execute(&pool)
@@ -355,6 +406,15 @@ mod tests {
     }
 
     async fn insert_article(repo: &ReadingRepo, title: &str, content: &str) -> i64 {
+        insert_article_for(repo, USER, title, content).await
+    }
+
+    async fn insert_article_for(
+        repo: &ReadingRepo,
+        user_id: i32,
+        title: &str,
+        content: &str,
+    ) -> i64 {
         let words: Vec<String> = content
             .split(|c: char| !c.is_ascii_alphabetic() && c != '\'')
             .filter(|s| !s.is_empty())
@@ -370,7 +430,7 @@ mod tests {
         };
 
         let id = repo
-            .insert_article(title, content, word_count)
+            .insert_article(user_id, title, content, word_count)
             .await
             .unwrap();
         repo.insert_article_words(id, &unique).await.unwrap();
@@ -385,7 +445,7 @@ mod tests {
         let id = insert_article(&repo, "Test Title", "hello world").await;
 
         let article = repo
-            .get_article(id)
+            .get_article(USER, id)
             .await
             .unwrap()
             .expect("article should exist");
@@ -397,13 +457,13 @@ mod tests {
     #[tokio::test]
     async fn test_get_nonexistent_article() {
         let repo = setup_db().await;
-        assert!(repo.get_article(999).await.unwrap().is_none());
+        assert!(repo.get_article(USER, 999).await.unwrap().is_none());
     }
 
     #[tokio::test]
     async fn test_get_all_articles_empty() {
         let repo = setup_db().await;
-        let articles = repo.get_all_articles().await.unwrap();
+        let articles = repo.get_all_articles(USER).await.unwrap();
         assert!(articles.is_empty());
     }
 
@@ -413,7 +473,7 @@ mod tests {
         let id1 = insert_article(&repo, "A", "alpha bravo").await;
         let id2 = insert_article(&repo, "B", "charlie delta").await;
 
-        let articles = repo.get_all_articles().await.unwrap();
+        let articles = repo.get_all_articles(USER).await.unwrap();
         assert_eq!(articles.len(), 2);
         assert_eq!(articles[0].id, id2, "newest first");
         assert_eq!(articles[1].id, id1);
@@ -426,7 +486,7 @@ mod tests {
         let repo = setup_db().await;
         let id = insert_article(&repo, "Test", "the quick brown fox").await;
 
-        let words = repo.get_article_words(id).await.unwrap();
+        let words = repo.get_article_words(USER, id).await.unwrap();
         assert_eq!(words.len(), 4);
     }
 
@@ -435,7 +495,7 @@ mod tests {
         let repo = setup_db().await;
         let id = insert_article(&repo, "Test", "hello world").await;
 
-        let statuses = repo.get_article_word_statuses(id).await.unwrap();
+        let statuses = repo.get_article_word_statuses(USER, id).await.unwrap();
         assert_eq!(statuses.len(), 2);
         assert_eq!(statuses[0].status, "unknown");
         assert_eq!(statuses[1].status, "unknown");
@@ -446,9 +506,9 @@ mod tests {
         let repo = setup_db().await;
         let id = insert_article(&repo, "Test", "hello world").await;
 
-        repo.upsert_user_word("world", "known").await.unwrap();
+        repo.upsert_user_word(USER, "world", "known").await.unwrap();
 
-        let statuses = repo.get_article_word_statuses(id).await.unwrap();
+        let statuses = repo.get_article_word_statuses(USER, id).await.unwrap();
         let hello = statuses.iter().find(|w| w.word == "hello").unwrap();
         let world = statuses.iter().find(|w| w.word == "world").unwrap();
         assert_eq!(hello.status, "unknown");
@@ -461,7 +521,7 @@ mod tests {
     async fn test_known_ratio_all_unknown() {
         let repo = setup_db().await;
         let id = insert_article(&repo, "Test", "hello world foo").await;
-        let ratio = repo.get_article_known_ratio(id).await.unwrap();
+        let ratio = repo.get_article_known_ratio(USER, id).await.unwrap();
         assert!((ratio - 0.0).abs() < f64::EPSILON);
     }
 
@@ -470,10 +530,10 @@ mod tests {
         let repo = setup_db().await;
         let id = insert_article(&repo, "Test", "hello world foo").await;
 
-        repo.upsert_user_word("hello", "known").await.unwrap();
-        repo.upsert_user_word("foo", "known").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "known").await.unwrap();
+        repo.upsert_user_word(USER, "foo", "known").await.unwrap();
 
-        let ratio = repo.get_article_known_ratio(id).await.unwrap();
+        let ratio = repo.get_article_known_ratio(USER, id).await.unwrap();
         assert!((ratio - 2.0 / 3.0).abs() < 0.001);
     }
 
@@ -482,9 +542,11 @@ mod tests {
     #[tokio::test]
     async fn test_upsert_user_word_new_unknown() {
         let repo = setup_db().await;
-        repo.upsert_user_word("hello", "unknown").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "unknown")
+            .await
+            .unwrap();
 
-        let words = repo.get_unknown_words().await.unwrap();
+        let words = repo.get_unknown_words(USER).await.unwrap();
         assert_eq!(words.len(), 1);
         assert_eq!(words[0].word, "hello");
         assert_eq!(words[0].unknown_count, 1);
@@ -494,10 +556,14 @@ mod tests {
     #[tokio::test]
     async fn test_upsert_user_word_twice_increments_count() {
         let repo = setup_db().await;
-        repo.upsert_user_word("hello", "unknown").await.unwrap();
-        repo.upsert_user_word("hello", "unknown").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "unknown")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "hello", "unknown")
+            .await
+            .unwrap();
 
-        let words = repo.get_unknown_words().await.unwrap();
+        let words = repo.get_unknown_words(USER).await.unwrap();
         assert_eq!(words.len(), 1);
         assert_eq!(words[0].unknown_count, 2);
     }
@@ -505,10 +571,12 @@ mod tests {
     #[tokio::test]
     async fn test_upsert_user_word_switch_to_known() {
         let repo = setup_db().await;
-        repo.upsert_user_word("hello", "unknown").await.unwrap();
-        repo.upsert_user_word("hello", "known").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "unknown")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "hello", "known").await.unwrap();
 
-        let words = repo.get_unknown_words().await.unwrap();
+        let words = repo.get_unknown_words(USER).await.unwrap();
         assert!(
             words.is_empty(),
             "switched to known, should not appear in unknown"
@@ -529,12 +597,20 @@ mod tests {
     #[tokio::test]
     async fn test_get_unknown_words_ordered_by_count() {
         let repo = setup_db().await;
-        repo.upsert_user_word("rare", "unknown").await.unwrap();
-        repo.upsert_user_word("common", "unknown").await.unwrap();
-        repo.upsert_user_word("common", "unknown").await.unwrap();
-        repo.upsert_user_word("common", "unknown").await.unwrap();
+        repo.upsert_user_word(USER, "rare", "unknown")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "common", "unknown")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "common", "unknown")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "common", "unknown")
+            .await
+            .unwrap();
 
-        let words = repo.get_unknown_words().await.unwrap();
+        let words = repo.get_unknown_words(USER).await.unwrap();
         assert_eq!(words[0].word, "common");
         assert_eq!(words[0].unknown_count, 3);
         assert_eq!(words[1].word, "rare");
@@ -549,24 +625,26 @@ mod tests {
         let id = insert_article(&repo, "Test", "alice bob charlie dave").await;
 
         // 标记 alice 为 ignored，bob 为 known
-        repo.upsert_user_word("alice", "ignored").await.unwrap();
-        repo.upsert_user_word("bob", "known").await.unwrap();
+        repo.upsert_user_word(USER, "alice", "ignored")
+            .await
+            .unwrap();
+        repo.upsert_user_word(USER, "bob", "known").await.unwrap();
 
         // ratio: only bob counts as known, alice excluded, charlie and dave unknown
         // total after exclusion: 3 (bob, charlie, dave), known: 1 (bob)
-        let ratio = repo.get_article_known_ratio(id).await.unwrap();
+        let ratio = repo.get_article_known_ratio(USER, id).await.unwrap();
         assert!(
             (ratio - 1.0 / 3.0).abs() < 0.001,
             "ignored should be excluded from total"
         );
 
         // status should reflect 'ignored'
-        let statuses = repo.get_article_word_statuses(id).await.unwrap();
+        let statuses = repo.get_article_word_statuses(USER, id).await.unwrap();
         let alice = statuses.iter().find(|w| w.word == "alice").unwrap();
         assert_eq!(alice.status, "ignored");
 
         // unknown_count should not include alice
-        let summaries = repo.get_all_article_summaries().await.unwrap();
+        let summaries = repo.get_all_article_summaries(USER).await.unwrap();
         assert_eq!(summaries.len(), 1);
         assert_eq!(summaries[0].unknown_word_count, 2, "charlie + dave");
     }
@@ -578,9 +656,9 @@ mod tests {
         let repo = setup_db().await;
         let _id = insert_article(&repo, "Test", "hello world foo").await;
 
-        repo.upsert_user_word("hello", "known").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "known").await.unwrap();
 
-        let summaries = repo.get_all_article_summaries().await.unwrap();
+        let summaries = repo.get_all_article_summaries(USER).await.unwrap();
         assert_eq!(summaries.len(), 1);
         let s = &summaries[0];
         assert!((s.known_ratio - 1.0 / 3.0).abs() < 0.001);
@@ -592,7 +670,7 @@ mod tests {
     #[tokio::test]
     async fn test_recommend_no_articles() {
         let repo = setup_db().await;
-        let result = repo.recommend_article(0, 0.9).await.unwrap();
+        let result = repo.recommend_article(USER, 0, 0.9).await.unwrap();
         assert!(result.is_none());
     }
 
@@ -604,18 +682,54 @@ mod tests {
         let id3 = insert_article(&repo, "C", "one two three").await;
 
         // article B: 4/5 = 80%
-        repo.upsert_user_word("hello", "known").await.unwrap();
-        repo.upsert_user_word("world", "known").await.unwrap();
-        repo.upsert_user_word("alpha", "known").await.unwrap();
-        repo.upsert_user_word("beta", "known").await.unwrap();
+        repo.upsert_user_word(USER, "hello", "known").await.unwrap();
+        repo.upsert_user_word(USER, "world", "known").await.unwrap();
+        repo.upsert_user_word(USER, "alpha", "known").await.unwrap();
+        repo.upsert_user_word(USER, "beta", "known").await.unwrap();
 
         // article C: 3/3 = 100%
-        repo.upsert_user_word("one", "known").await.unwrap();
-        repo.upsert_user_word("two", "known").await.unwrap();
-        repo.upsert_user_word("three", "known").await.unwrap();
+        repo.upsert_user_word(USER, "one", "known").await.unwrap();
+        repo.upsert_user_word(USER, "two", "known").await.unwrap();
+        repo.upsert_user_word(USER, "three", "known").await.unwrap();
 
-        let rec = repo.recommend_article(id1, 0.9).await.unwrap().unwrap();
+        let rec = repo
+            .recommend_article(USER, id1, 0.9)
+            .await
+            .unwrap()
+            .unwrap();
         // both B (80%, diff=0.1) and C (100%, diff=0.1) are equally close
         assert!(rec.id == id2 || rec.id == id3);
     }
+
+    // ── 用户隔离 ──
+
+    #[tokio::test]
+    async fn test_articles_and_words_are_scoped_to_user() {
+        let repo = setup_db().await;
+        let mine = insert_article(&repo, "Mine", "hello world").await;
+        let theirs = insert_article_for(&repo, 2, "Theirs", "hello there").await;
+
+        repo.upsert_user_word(2, "hello", "known").await.unwrap();
+
+        // 看不到别人的文章
+        assert!(repo.get_article(USER, theirs).await.unwrap().is_none());
+        assert!(
+            repo.get_article_words(USER, theirs)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            repo.update_article_notes(USER, theirs, "x").await.unwrap(),
+            0
+        );
+        let all = repo.get_all_articles(USER).await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, mine);
+
+        // 别人的词库不影响自己的认识率
+        let ratio = repo.get_article_known_ratio(USER, mine).await.unwrap();
+        assert!((ratio - 0.0).abs() < f64::EPSILON);
+        assert!(repo.get_unknown_words(USER).await.unwrap().is_empty());
+    }
 }
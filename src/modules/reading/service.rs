@@ -29,7 +29,12 @@ impl ReadingService {
         Self { pool }
     }
 
-    pub async fn upload_article(&self, title: &str, content: &str) -> Result<Article, sqlx::Error> {
+    pub async fn upload_article(
+        &self,
+        user_id: i32,
+        title: &str,
+        content: &str,
+    ) -> Result<Article, sqlx::Error> {
         let repo = repository::ReadingRepo::new(self.pool.clone());
 
         // 切词
@@ -38,13 +43,15 @@ impl ReadingService {
         let unique = unique_words(all_words);
 
         // 插入文章
-        let article_id = repo.insert_article(title, content, word_count).await?;
+        let article_id = repo
+            .insert_article(user_id, title, content, word_count)
+            .await?;
 
         // 插入文章词
         repo.insert_article_words(article_id, &unique).await?;
 
         // 返回
-        repo.get_article(article_id)
+        repo.get_article(user_id, article_id)
             .await
             .map(|a| a.expect("刚插入的文章必须存在"))
     }
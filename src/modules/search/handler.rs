@@ -0,0 +1,49 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+
+use super::model::{SearchParams, SearchResponse};
+use super::query::SearchQuery;
+use super::service::SearchService;
+use crate::auth::Claims;
+use crate::error;
+use crate::state::AppState;
+
+/// 每个模块默认返回的命中数
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 50;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "全文搜索（按模块分组，bm25 排序，命中处高亮）",
+    params(super::model::SearchParams),
+    responses((status = 200, description = "成功", body = super::model::SearchResponse))
+)]
+pub async fn search_handler(
+    Query(params): Query<SearchParams>,
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let Some(query) = SearchQuery::parse(&params.q) else {
+        return error::bad_request("搜索关键词不能为空");
+    };
+    let modules =
+        match SearchService::resolve_modules(params.modules.as_deref(), claims.scopes.as_deref()) {
+            Ok(modules) => modules,
+            Err(e) => return e.into_response(),
+        };
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let result = state
+        .search
+        .search(claims.sub, &query, &modules, limit)
+        .await
+        .map(|groups| SearchResponse {
+            query: params.q.trim().to_string(),
+            groups,
+        });
+    error::ok_or(result, "全文搜索")
+}
@@ -0,0 +1,19 @@
+//! 跨模块全文检索。
+//!
+//! 索引由迁移 `0006_search_fts` 建立的 FTS5 表提供，源表增删改通过触发器同步，
+//! 本模块只负责解析检索式、逐模块查询并高亮。
+
+mod handler;
+pub mod model;
+pub mod query;
+pub mod service;
+
+pub use service::SearchService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::search_handler))
+}
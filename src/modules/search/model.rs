@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// `GET /search` 查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// 检索式，语法见 [`super::query`]
+    pub q: String,
+    /// 限定模块，逗号分隔，如 `cards,mem`；缺省为全部
+    pub modules: Option<String>,
+    /// 每个模块最多返回的条数
+    pub limit: Option<i64>,
+}
+
+/// 单条命中。`title` / `snippet` 为 HTML，命中处以 `<mark>` 标出
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchHit {
+    /// 对应模块中的记录 id（mem 为卡片 id，conv 为对话 id）
+    pub id: i64,
+    pub title: String,
+    pub snippet: String,
+    /// bm25 相关度，越大越相关；检索词都过短、未走索引时为 0
+    pub score: f64,
+}
+
+/// 一个模块的命中
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchGroup {
+    pub module: String,
+    /// 该模块命中总数（不受 limit 限制）
+    pub total: i64,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchResponse {
+    pub query: String,
+    /// 只包含有命中的模块
+    pub groups: Vec<SearchGroup>,
+}
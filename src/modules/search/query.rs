@@ -0,0 +1,340 @@
+//! 检索式解析与结果高亮。
+//!
+//! 语法（多个词之间为 AND）：
+//! - `rust 所有权`：普通词，按子串匹配
+//! - `"machine learning"`：短语，整体按子串匹配
+//! - `prog*` / `"deep lea"*`：前缀，须从词首开始匹配（CJK 文本没有词边界，等同子串）
+//!
+//! 满 3 个字符的词交给 FTS5 trigram 索引（MATCH + bm25 排序）；
+//! 更短的词 trigram 无法命中，回退为 LIKE。前缀词另加 GLOB 条件限定词首。
+
+use sqlx::{QueryBuilder, Sqlite};
+
+/// trigram 分词能命中的最短词长（字符数）
+const TRIGRAM_MIN_CHARS: usize = 3;
+/// 单次检索最多使用的词数，超出部分忽略
+const MAX_TERMS: usize = 8;
+
+/// 检索式中的一个词或短语
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    text: String,
+    prefix: bool,
+}
+
+impl Term {
+    /// 是否足够长、能走 FTS 索引
+    fn indexed(&self) -> bool {
+        self.text.chars().count() >= TRIGRAM_MIN_CHARS
+    }
+}
+
+/// 解析后的检索式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    terms: Vec<Term>,
+}
+
+impl SearchQuery {
+    /// 解析检索式；没有任何有效词时返回 None
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut terms = Vec::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            let (raw, prefix) = if c == '"' {
+                chars.next();
+                let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                let prefix = chars.next_if_eq(&'*').is_some();
+                (phrase, prefix)
+            } else {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '"') {
+                    word.push(c);
+                }
+                let prefix = word.ends_with('*');
+                (word.trim_matches('*').to_string(), prefix)
+            };
+
+            let text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                terms.push(Term { text, prefix });
+            }
+        }
+
+        terms.truncate(MAX_TERMS);
+        (!terms.is_empty()).then_some(Self { terms })
+    }
+
+    /// 是否有词走了 FTS 索引（决定能否按 bm25 排序）
+    pub fn ranked(&self) -> bool {
+        self.terms.iter().any(Term::indexed)
+    }
+
+    /// 能走索引的词组成的 FTS5 MATCH 表达式，每个词都作为短语引用
+    fn match_expr(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .terms
+            .iter()
+            .filter(|t| t.indexed())
+            .map(|t| format!("\"{}\"", t.text.replace('"', "\"\"")))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// 追加过滤条件（AND 连接，不含前导 WHERE / AND）。
+    ///
+    /// `columns` 为 FTS 表中参与检索的列名。
+    pub fn push_filter(&self, qb: &mut QueryBuilder<Sqlite>, fts: &str, columns: &[&str]) {
+        let mut first = true;
+        let mut and = |qb: &mut QueryBuilder<Sqlite>| {
+            if !std::mem::take(&mut first) {
+                qb.push(" AND ");
+            }
+        };
+
+        if let Some(expr) = self.match_expr() {
+            and(qb);
+            qb.push(format!("{fts} MATCH ")).push_bind(expr);
+        }
+
+        for term in &self.terms {
+            if term.prefix {
+                // 行首补一个空格，让「词首」统一表现为「前一个字符不是字母数字」
+                let pattern = format!(
+                    "*[^a-z0-9]{}*",
+                    escape_glob(&term.text.to_ascii_lowercase())
+                );
+                and(qb);
+                push_any(qb, fts, columns, |qb, col| {
+                    qb.push(format!("(' ' || lower(COALESCE({col}, ''))) GLOB "))
+                        .push_bind(pattern.clone());
+                });
+            } else if !term.indexed() {
+                let pattern = format!("%{}%", escape_like(&term.text));
+                and(qb);
+                push_any(qb, fts, columns, |qb, col| {
+                    qb.push(format!("{col} LIKE "))
+                        .push_bind(pattern.clone())
+                        .push(" ESCAPE '\\'");
+                });
+            }
+        }
+    }
+
+    /// 标出 `text` 中全部命中（`<mark>`），截取首个命中附近约 `max_chars` 个字符。
+    ///
+    /// 输出已做 HTML 转义，换行折叠为空格；没有命中时返回开头一段。
+    pub fn highlight(&self, text: &str, max_chars: usize) -> String {
+        let ranges = self.match_ranges(text);
+
+        let first = ranges.first().map_or(0, |r| r.0);
+        let start = text[..first]
+            .char_indices()
+            .rev()
+            .take(max_chars / 4)
+            .last()
+            .map_or(first, |(i, _)| i);
+        let end = text[start..]
+            .char_indices()
+            .nth(max_chars)
+            .map_or(text.len(), |(i, _)| start + i);
+
+        let mut out = String::new();
+        if start > 0 {
+            out.push('…');
+        }
+        let mut pos = start;
+        for &(s, e) in &ranges {
+            let (s, e) = (s.max(pos), e.min(end));
+            if s >= e {
+                continue;
+            }
+            push_escaped(&mut out, &text[pos..s]);
+            out.push_str("<mark>");
+            push_escaped(&mut out, &text[s..e]);
+            out.push_str("</mark>");
+            pos = e;
+        }
+        push_escaped(&mut out, &text[pos..end]);
+        if end < text.len() {
+            out.push('…');
+        }
+        out
+    }
+
+    /// 全部命中的字节区间，已排序并合并重叠
+    fn match_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        // 只折叠 ASCII 大小写，字节偏移与原文一致
+        let lower = text.to_ascii_lowercase();
+        let mut ranges: Vec<(usize, usize)> = self
+            .terms
+            .iter()
+            .flat_map(|term| {
+                let needle = term.text.to_ascii_lowercase();
+                lower
+                    .match_indices(&needle)
+                    .filter(|(i, _)| {
+                        !term.prefix
+                            || !lower[..*i]
+                                .chars()
+                                .next_back()
+                                .is_some_and(|c| c.is_ascii_alphanumeric())
+                    })
+                    .map(|(i, m)| (i, i + m.len()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (s, e) in ranges {
+            match merged.last_mut() {
+                Some(last) if s <= last.1 => last.1 = last.1.max(e),
+                _ => merged.push((s, e)),
+            }
+        }
+        merged
+    }
+}
+
+/// `(c1 cond OR c2 cond ...)`
+fn push_any(
+    qb: &mut QueryBuilder<Sqlite>,
+    fts: &str,
+    columns: &[&str],
+    mut cond: impl FnMut(&mut QueryBuilder<Sqlite>, &str),
+) {
+    qb.push("(");
+    for (i, col) in columns.iter().enumerate() {
+        if i > 0 {
+            qb.push(" OR ");
+        }
+        cond(qb, &format!("{fts}.{col}"));
+    }
+    qb.push(")");
+}
+
+fn escape_like(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn escape_glob(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '*' | '?' | '[' => {
+                out.push('[');
+                out.push(c);
+                out.push(']');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' | '\r' | '\t' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn term(text: &str, prefix: bool) -> Term {
+        Term {
+            text: text.into(),
+            prefix,
+        }
+    }
+
+    #[test]
+    fn parse_words_phrases_and_prefixes() {
+        let q =
+            SearchQuery::parse(r#"  rust "machine   learning" prog* "deep le"* 学习 "#).unwrap();
+        assert_eq!(
+            q.terms,
+            [
+                term("rust", false),
+                term("machine learning", false),
+                term("prog", true),
+                term("deep le", true),
+                term("学习", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rejects_empty() {
+        assert!(SearchQuery::parse("").is_none());
+        assert!(SearchQuery::parse("  \"\" * ").is_none());
+    }
+
+    #[test]
+    fn match_expr_quotes_only_indexable_terms() {
+        let q = SearchQuery::parse(r#"go rust say"hi"#).unwrap();
+        assert!(q.ranked());
+        assert_eq!(q.match_expr().unwrap(), r#""rust" "say""#);
+
+        let short = SearchQuery::parse("go 学习").unwrap();
+        assert!(!short.ranked());
+        assert!(short.match_expr().is_none());
+    }
+
+    #[test]
+    fn highlight_marks_and_escapes() {
+        let q = SearchQuery::parse("rust").unwrap();
+        assert_eq!(
+            q.highlight("Learn <Rust> & rust", 100),
+            "Learn &lt;<mark>Rust</mark>&gt; &amp; <mark>rust</mark>"
+        );
+    }
+
+    #[test]
+    fn highlight_prefix_requires_word_start() {
+        let q = SearchQuery::parse("gram*").unwrap();
+        assert_eq!(
+            q.highlight("programming grammar", 100),
+            "programming <mark>gram</mark>mar"
+        );
+    }
+
+    #[test]
+    fn highlight_windows_around_first_hit() {
+        let q = SearchQuery::parse("所有权").unwrap();
+        let text = format!("{}所有权{}", "前".repeat(50), "后".repeat(50));
+        let snippet = q.highlight(&text, 20);
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("前前前前前<mark>所有权</mark>后"));
+        assert_eq!(snippet.chars().filter(|c| *c != '…').count(), 20 + 13);
+    }
+
+    #[test]
+    fn highlight_without_hit_returns_head() {
+        let q = SearchQuery::parse("zzz").unwrap();
+        assert_eq!(q.highlight("abcdef", 3), "abc…");
+    }
+}
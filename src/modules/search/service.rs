@@ -0,0 +1,499 @@
+use std::sync::Arc;
+
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool};
+
+use super::model::{SearchGroup, SearchHit};
+use super::query::SearchQuery;
+use crate::error::ServiceError;
+use crate::modules::api_token::model::scope_allows;
+
+/// 标题高亮保留的最大字符数
+const TITLE_CHARS: usize = 80;
+/// 摘要高亮保留的最大字符数
+const SNIPPET_CHARS: usize = 120;
+
+/// 一个可检索模块：FTS 表与源表的对应关系（均为常量 SQL 片段）
+struct Source {
+    /// 分组名
+    module: &'static str,
+    /// API 令牌访问所需的作用域资源；None 表示公开数据
+    scope: Option<&'static str>,
+    fts: &'static str,
+    /// FROM 子句，FTS 表须以原名出现
+    from: &'static str,
+    /// 参与检索的 FTS 列
+    columns: &'static [&'static str],
+    /// bm25 列权重，与 `columns` 一一对应
+    weights: &'static str,
+    id: &'static str,
+    title: &'static str,
+    body: &'static str,
+    /// 归属用户列。cards / tasks / conv 与各自模块现有接口一致，不按用户过滤
+    owner: Option<&'static str>,
+}
+
+const SOURCES: &[Source] = &[
+    Source {
+        module: "cards",
+        scope: Some("cards"),
+        fts: "card_fts",
+        from: "card_fts JOIN card b ON b.id = card_fts.rowid AND b.deleted_at IS NULL",
+        columns: &["content"],
+        weights: "1.0",
+        id: "b.id",
+        title: "''",
+        body: "b.content",
+        owner: None,
+    },
+    Source {
+        module: "mem",
+        scope: Some("mem"),
+        fts: "chunk_fts",
+        from: "chunk_fts
+               JOIN mem m ON chunk_fts.rowid IN (m.cue_chunk_id, m.target_chunk_id)
+                 AND m.deleted_at IS NULL
+               JOIN chunk cue ON cue.id = m.cue_chunk_id
+               JOIN chunk tgt ON tgt.id = m.target_chunk_id",
+        columns: &["content"],
+        weights: "1.0",
+        id: "m.id",
+        title: "cue.content",
+        body: "tgt.content",
+        owner: Some("m.user_id"),
+    },
+    Source {
+        module: "bookmarks",
+        scope: Some("bookmarks"),
+        fts: "bookmark_fts",
+        from: "bookmark_fts JOIN bookmark b ON b.id = bookmark_fts.rowid AND b.deleted_at IS NULL",
+        columns: &["title", "url", "description"],
+        weights: "10.0, 2.0, 1.0",
+        id: "b.id",
+        title: "b.title",
+        body: "b.description || char(10) || b.url",
+        owner: Some("b.user_id"),
+    },
+    Source {
+        module: "tasks",
+        scope: Some("tasks"),
+        fts: "task_fts",
+        from: "task_fts JOIN task b ON b.id = task_fts.rowid AND b.deleted_at IS NULL",
+        columns: &["title", "description"],
+        weights: "10.0, 1.0",
+        id: "b.id",
+        title: "b.title",
+        body: "b.description",
+        owner: None,
+    },
+    Source {
+        module: "text",
+        scope: Some("text"),
+        fts: "text_note_fts",
+        from: "text_note_fts JOIN text_note b ON b.id = text_note_fts.rowid",
+        columns: &["name", "content"],
+        weights: "10.0, 1.0",
+        id: "b.id",
+        title: "b.name",
+        body: "b.content",
+        owner: Some("b.user_id"),
+    },
+    Source {
+        module: "reading",
+        scope: Some("reading"),
+        fts: "reading_article_fts",
+        from: "reading_article_fts JOIN reading_article b ON b.id = reading_article_fts.rowid",
+        columns: &["title", "content", "notes"],
+        weights: "10.0, 1.0, 2.0",
+        id: "b.id",
+        title: "b.title",
+        body: "b.content || char(10) || b.notes",
+        owner: Some("b.user_id"),
+    },
+    Source {
+        module: "conv",
+        scope: None,
+        fts: "conv_fts",
+        from: "conv_fts",
+        columns: &["title", "content"],
+        weights: "5.0, 1.0",
+        id: "conv_fts.conv_id",
+        title: "conv_fts.title",
+        body: "conv_fts.content",
+        owner: None,
+    },
+];
+
+#[derive(FromRow)]
+struct HitRow {
+    id: i64,
+    title: String,
+    body: String,
+    score: f64,
+}
+
+#[derive(Clone)]
+pub struct SearchService {
+    pool: Arc<SqlitePool>,
+}
+
+impl SearchService {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    /// 确定要检索的模块：`requested` 为逗号分隔的模块名（None 为全部），
+    /// API 令牌（`scopes` 为 Some）只能检索有读权限的模块
+    pub fn resolve_modules(
+        requested: Option<&str>,
+        scopes: Option<&[String]>,
+    ) -> Result<Vec<&'static str>, ServiceError> {
+        let readable = |s: &Source| match (s.scope, scopes) {
+            (Some(resource), Some(scopes)) => scope_allows(scopes, resource, false),
+            _ => true,
+        };
+
+        let Some(requested) = requested.map(str::trim).filter(|r| !r.is_empty()) else {
+            return Ok(SOURCES
+                .iter()
+                .filter(|s| readable(s))
+                .map(|s| s.module)
+                .collect());
+        };
+
+        let mut modules = Vec::new();
+        for name in requested
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+        {
+            let source = SOURCES
+                .iter()
+                .find(|s| s.module == name)
+                .ok_or_else(|| ServiceError::InvalidInput(format!("未知的搜索模块: {name}")))?;
+            if !readable(source) {
+                return Err(ServiceError::InvalidInput(format!(
+                    "API 令牌缺少 {name}:read 作用域"
+                )));
+            }
+            if !modules.contains(&source.module) {
+                modules.push(source.module);
+            }
+        }
+        Ok(modules)
+    }
+
+    /// 在各模块中检索，返回有命中的分组（按 `modules` 顺序），每组最多 `limit` 条
+    pub async fn search(
+        &self,
+        user_id: i32,
+        query: &SearchQuery,
+        modules: &[&str],
+        limit: i64,
+    ) -> Result<Vec<SearchGroup>, sqlx::Error> {
+        let mut groups = Vec::new();
+        for module in modules {
+            let Some(source) = SOURCES.iter().find(|s| s.module == *module) else {
+                continue;
+            };
+            let total = self.count(source, user_id, query).await?;
+            if total == 0 {
+                continue;
+            }
+            let hits = self
+                .hits(source, user_id, query, limit)
+                .await?
+                .into_iter()
+                .map(|row| to_hit(row, query))
+                .collect();
+            groups.push(SearchGroup {
+                module: source.module.to_string(),
+                total,
+                hits,
+            });
+        }
+        Ok(groups)
+    }
+
+    async fn count(
+        &self,
+        source: &Source,
+        user_id: i32,
+        query: &SearchQuery,
+    ) -> Result<i64, sqlx::Error> {
+        let mut qb = QueryBuilder::new(format!(
+            "SELECT COUNT(DISTINCT {}) FROM {}",
+            source.id, source.from
+        ));
+        push_where(&mut qb, source, user_id, query);
+        qb.build_query_scalar().fetch_one(&*self.pool).await
+    }
+
+    async fn hits(
+        &self,
+        source: &Source,
+        user_id: i32,
+        query: &SearchQuery,
+        limit: i64,
+    ) -> Result<Vec<HitRow>, sqlx::Error> {
+        // bm25 越小越相关，取反后作为 score。
+        // 同一记录可能有多行命中（mem 的正反面、conv 的多段问答），取最相关的一行；
+        // bm25 不能直接放进聚合，先在物化的 CTE 里逐行算好
+        let score = if query.ranked() {
+            format!("-bm25({}, {})", source.fts, source.weights)
+        } else {
+            "0.0".to_string()
+        };
+        let mut qb = QueryBuilder::new(format!(
+            "WITH hit AS MATERIALIZED (
+                 SELECT {id} AS id, COALESCE({title}, '') AS title,
+                        COALESCE({body}, '') AS body, {score} AS score
+                 FROM {from}",
+            id = source.id,
+            title = source.title,
+            body = source.body,
+            from = source.from,
+        ));
+        push_where(&mut qb, source, user_id, query);
+        qb.push(
+            ")
+             SELECT id, title, body, MAX(score) AS score FROM hit
+             GROUP BY id ORDER BY score DESC, id DESC LIMIT ",
+        )
+        .push_bind(limit);
+        qb.build_query_as().fetch_all(&*self.pool).await
+    }
+}
+
+fn push_where(qb: &mut QueryBuilder<Sqlite>, source: &Source, user_id: i32, query: &SearchQuery) {
+    qb.push(" WHERE ");
+    query.push_filter(qb, source.fts, source.columns);
+    if let Some(owner) = source.owner {
+        qb.push(format!(" AND {owner} = ")).push_bind(user_id);
+    }
+}
+
+fn to_hit(row: HitRow, query: &SearchQuery) -> SearchHit {
+    // 无标题的模块取正文首行作标题
+    let title = if row.title.is_empty() {
+        row.body.lines().next().unwrap_or_default()
+    } else {
+        &row.title
+    };
+    SearchHit {
+        id: row.id,
+        title: query.highlight(title, TITLE_CHARS),
+        snippet: query.highlight(&row.body, SNIPPET_CHARS),
+        score: row.score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    async fn setup() -> (SearchService, Arc<SqlitePool>, i32, i32) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let alice: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('alice', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        let bob: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('bob', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        (SearchService::new(pool.clone()), pool, alice, bob)
+    }
+
+    async fn exec(pool: &SqlitePool, sql: &str) {
+        sqlx::raw_sql(sqlx::AssertSqlSafe(sql.to_string()))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn search(svc: &SearchService, user_id: i32, q: &str) -> Vec<SearchGroup> {
+        let modules: Vec<_> = SOURCES.iter().map(|s| s.module).collect();
+        svc.search(user_id, &SearchQuery::parse(q).unwrap(), &modules, 10)
+            .await
+            .unwrap()
+    }
+
+    fn group<'a>(groups: &'a [SearchGroup], module: &str) -> Option<&'a SearchGroup> {
+        groups.iter().find(|g| g.module == module)
+    }
+
+    #[tokio::test]
+    async fn finds_hits_in_every_module() {
+        let (svc, pool, alice, _) = setup().await;
+        exec(
+            &pool,
+            &format!(
+                "INSERT INTO card (content) VALUES ('Rust 所有权笔记');
+                 INSERT INTO chunk (id, content, user_id) VALUES (1, '什么是所有权', {alice}), (2, 'Rust 的内存管理', {alice});
+                 INSERT INTO mem (cue_chunk_id, target_chunk_id, user_id) VALUES (1, 2, {alice});
+                 INSERT INTO bookmark (title, url, user_id) VALUES ('Rust Book', 'https://doc.rust-lang.org', {alice});
+                 INSERT INTO task (title, description) VALUES ('学习 Rust', '所有权与借用');
+                 INSERT INTO text_note (name, content, user_id) VALUES ('草稿', '关于所有权的思考', {alice});
+                 INSERT INTO reading_article (title, content, user_id) VALUES ('Ownership', '所有权是 Rust 的核心', {alice});
+                 INSERT INTO conv_titles (conv_id, title, conv_type) VALUES (7, 'Rust所有权系统', 'concept');"
+            ),
+        )
+        .await;
+
+        let groups = search(&svc, alice, "所有权").await;
+        let modules: Vec<_> = groups.iter().map(|g| g.module.as_str()).collect();
+        assert_eq!(
+            modules,
+            ["cards", "mem", "tasks", "text", "reading", "conv"]
+        );
+
+        let mem = &group(&groups, "mem").unwrap().hits[0];
+        assert_eq!(mem.title, "什么是<mark>所有权</mark>");
+        assert!(mem.score > 0.0);
+        assert_eq!(group(&groups, "conv").unwrap().hits[0].id, 7);
+        assert_eq!(
+            group(&groups, "cards").unwrap().hits[0].title,
+            "Rust <mark>所有权</mark>笔记"
+        );
+    }
+
+    #[tokio::test]
+    async fn results_are_scoped_to_owner() {
+        let (svc, pool, alice, bob) = setup().await;
+        exec(
+            &pool,
+            &format!(
+                "INSERT INTO bookmark (title, url, user_id) VALUES ('alice 的书签', 'https://a.example', {alice});
+                 INSERT INTO bookmark (title, url, user_id) VALUES ('bob 的书签', 'https://b.example', {bob});"
+            ),
+        )
+        .await;
+
+        let groups = search(&svc, bob, "example").await;
+        let bookmarks = group(&groups, "bookmarks").unwrap();
+        assert_eq!(bookmarks.total, 1);
+        assert!(bookmarks.hits[0].title.starts_with("bob"));
+    }
+
+    #[tokio::test]
+    async fn triggers_follow_updates_and_deletes() {
+        let (svc, pool, _, _) = setup().await;
+        exec(
+            &pool,
+            "INSERT INTO task (id, title) VALUES (1, 'write report')",
+        )
+        .await;
+        assert!(group(&search(&svc, 0, "report").await, "tasks").is_some());
+
+        exec(
+            &pool,
+            "UPDATE task SET title = 'write summary' WHERE id = 1",
+        )
+        .await;
+        assert!(group(&search(&svc, 0, "report").await, "tasks").is_none());
+        assert!(group(&search(&svc, 0, "summary").await, "tasks").is_some());
+
+        // 回收站中的条目不参与检索
+        exec(
+            &pool,
+            "UPDATE task SET deleted_at = '2026-01-01T00:00:00Z' WHERE id = 1",
+        )
+        .await;
+        assert!(search(&svc, 0, "summary").await.is_empty());
+        exec(&pool, "UPDATE task SET deleted_at = NULL WHERE id = 1").await;
+        assert!(group(&search(&svc, 0, "summary").await, "tasks").is_some());
+
+        exec(&pool, "DELETE FROM task WHERE id = 1").await;
+        assert!(search(&svc, 0, "summary").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ranks_by_bm25_with_title_weight() {
+        let (svc, pool, _, _) = setup().await;
+        exec(
+            &pool,
+            "INSERT INTO task (id, title, description) VALUES (1, 'misc', 'some notes on tokio runtime');
+             INSERT INTO task (id, title, description) VALUES (2, 'tokio', 'async runtime');",
+        )
+        .await;
+
+        let groups = search(&svc, 0, "tokio").await;
+        let ids: Vec<_> = groups[0].hits.iter().map(|h| h.id).collect();
+        assert_eq!(ids, [2, 1]);
+        assert!(groups[0].hits[0].score > groups[0].hits[1].score);
+    }
+
+    #[tokio::test]
+    async fn phrase_prefix_and_short_terms() {
+        let (svc, pool, _, _) = setup().await;
+        exec(
+            &pool,
+            "INSERT INTO card (id, content) VALUES (1, 'machine learning basics');
+             INSERT INTO card (id, content) VALUES (2, 'learning about machines');
+             INSERT INTO card (id, content) VALUES (3, 'reprogramming habits');
+             INSERT INTO card (id, content) VALUES (4, 'programming 学习');",
+        )
+        .await;
+        let ids = |groups: Vec<SearchGroup>| -> Vec<i64> {
+            groups
+                .first()
+                .map(|g| g.hits.iter().map(|h| h.id).collect())
+                .unwrap_or_default()
+        };
+
+        // 短语：整体匹配
+        assert_eq!(ids(search(&svc, 0, "\"machine learning\"").await), [1]);
+        // 前缀：从词首匹配，不命中词中间
+        assert_eq!(ids(search(&svc, 0, "program*").await), [4]);
+        // 不足 3 字的 CJK 词回退为 LIKE
+        let groups = search(&svc, 0, "学习").await;
+        assert_eq!(ids(groups.clone()), [4]);
+        assert_eq!(groups[0].hits[0].snippet, "programming <mark>学习</mark>");
+        assert_eq!(groups[0].hits[0].score, 0.0);
+        // 长短混合：MATCH 与 LIKE 同时生效
+        assert_eq!(ids(search(&svc, 0, "programming 学").await), [4]);
+    }
+
+    #[tokio::test]
+    async fn limit_caps_hits_but_not_total() {
+        let (svc, pool, _, _) = setup().await;
+        for i in 0..5 {
+            sqlx::query("INSERT INTO card (content) VALUES (?)")
+                .bind(format!("note {i} about sqlite"))
+                .execute(&*pool)
+                .await
+                .unwrap();
+        }
+        let groups = svc
+            .search(0, &SearchQuery::parse("sqlite").unwrap(), &["cards"], 2)
+            .await
+            .unwrap();
+        assert_eq!(groups[0].total, 5);
+        assert_eq!(groups[0].hits.len(), 2);
+    }
+
+    #[test]
+    fn resolve_modules_checks_names_and_scopes() {
+        let all = SearchService::resolve_modules(None, None).unwrap();
+        assert_eq!(all.len(), SOURCES.len());
+
+        assert_eq!(
+            SearchService::resolve_modules(Some("mem, cards,mem"), None).unwrap(),
+            ["mem", "cards"]
+        );
+        assert!(SearchService::resolve_modules(Some("nope"), None).is_err());
+
+        let scopes = vec!["tasks:read".to_string()];
+        assert_eq!(
+            SearchService::resolve_modules(None, Some(&scopes)).unwrap(),
+            ["tasks", "conv"]
+        );
+        assert!(SearchService::resolve_modules(Some("mem"), Some(&scopes)).is_err());
+    }
+}
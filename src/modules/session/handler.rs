@@ -0,0 +1,65 @@
+use axum::{
+    Extension,
+    extract::{Path, State},
+    response::{IntoResponse, Json},
+};
+
+use super::model::{RefreshRequest, SessionInfo, TokenPair};
+use crate::auth::Claims;
+use crate::error::{deleted_or, ok_or};
+use crate::state::AppState;
+
+/// POST /api/user/refresh — 用 refresh token 换新的一对 token
+#[utoipa::path(
+    post,
+    path = "/user/refresh",
+    summary = "用 refresh token 换新 token",
+    request_body = RefreshRequest,
+    responses((status = 200, description = "成功", body = TokenPair))
+)]
+pub async fn refresh_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshRequest>,
+) -> impl IntoResponse {
+    match state
+        .session
+        .refresh(&payload.refresh_token, &state.jwt_secret)
+        .await
+    {
+        Ok(pair) => Json(pair).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/user/sessions — 当前用户的有效会话
+#[utoipa::path(
+    get,
+    path = "/user/sessions",
+    summary = "有效会话列表",
+    responses((status = 200, description = "成功", body = Vec<SessionInfo>))
+)]
+pub async fn list_sessions_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    ok_or(
+        state.session.list(claims.sub, &claims.sid).await,
+        "获取会话列表",
+    )
+}
+
+/// DELETE /api/user/sessions/{id} — 吊销指定会话
+#[utoipa::path(
+    delete,
+    path = "/user/sessions/{id}",
+    summary = "注销会话",
+    params(("id" = String, Path)),
+    responses((status = 204, description = "成功"))
+)]
+pub async fn revoke_session_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    deleted_or(state.session.revoke(claims.sub, &id).await, "注销会话")
+}
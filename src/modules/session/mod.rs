@@ -0,0 +1,23 @@
+mod handler;
+mod model;
+mod repository;
+pub mod service;
+
+pub use model::TokenPair;
+pub use service::SessionService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+/// 刷新 token，公开路由
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::refresh_handler))
+}
+
+/// 会话管理
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::list_sessions_handler))
+        .routes(routes!(handler::revoke_session_handler))
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// 登录 / 刷新后下发给客户端的一对 token
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TokenPair {
+    /// 短期 access token（JWT）
+    pub access_token: String,
+    /// 一次性 refresh token，用于换取新的一对 token
+    pub refresh_token: String,
+    /// access token 剩余有效秒数
+    pub expires_in: usize,
+}
+
+/// 会话列表项（不含任何 token）
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SessionInfo {
+    pub id: String,
+    pub user_agent: String,
+    pub created_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub expires_at: String,
+    /// 是否为发起本次请求的会话
+    #[sqlx(skip)]
+    pub current: bool,
+}
+
+/// 按 refresh token 查到的有效会话
+#[derive(Debug, Clone, FromRow)]
+pub struct ActiveSession {
+    pub id: String,
+    pub user_id: i32,
+    pub role: String,
+    pub access_jti: String,
+    pub access_exp: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
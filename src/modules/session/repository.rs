@@ -0,0 +1,184 @@
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::sync::Arc;
+
+use super::model::{ActiveSession, SessionInfo};
+
+/// 新建会话所需字段
+pub struct NewSession<'a> {
+    pub id: &'a str,
+    pub user_id: i32,
+    pub refresh_token_hash: &'a str,
+    pub access_jti: &'a str,
+    pub access_exp: i64,
+    pub user_agent: &'a str,
+    pub expires_at: &'a str,
+}
+
+#[derive(Clone)]
+pub struct SessionRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl SessionRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, s: &NewSession<'_>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO session (id, user_id, refresh_token_hash, access_jti, access_exp, user_agent, expires_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(s.id)
+        .bind(s.user_id)
+        .bind(s.refresh_token_hash)
+        .bind(s.access_jti)
+        .bind(s.access_exp)
+        .bind(s.user_agent)
+        .bind(s.expires_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// 按当前 refresh token 查未吊销、未过期的会话
+    pub async fn find_active_by_token(
+        &self,
+        refresh_token_hash: &str,
+    ) -> Result<Option<ActiveSession>, sqlx::Error> {
+        sqlx::query_as::<_, ActiveSession>(
+            "SELECT s.id, s.user_id, u.role, s.access_jti, s.access_exp
+             FROM session s JOIN user u ON u.id = s.user_id
+             WHERE s.refresh_token_hash = ?
+               AND s.revoked_at IS NULL
+               AND s.expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now')",
+        )
+        .bind(refresh_token_hash)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// 按已轮换掉的上一个 refresh token 查会话（用于重放检测）
+    pub async fn find_by_prev_token(
+        &self,
+        prev_token_hash: &str,
+    ) -> Result<Option<(String, i32)>, sqlx::Error> {
+        sqlx::query_as::<_, (String, i32)>(
+            "SELECT id, user_id FROM session WHERE prev_token_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(prev_token_hash)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// 轮换 refresh token 并登记新的 access token，旧 access token 进入黑名单。
+    ///
+    /// 以旧 token hash 作为条件，并发刷新时只有一个请求能成功，返回受影响行数。
+    pub async fn rotate(
+        &self,
+        old: &ActiveSession,
+        old_token_hash: &str,
+        new_token_hash: &str,
+        access_jti: &str,
+        access_exp: i64,
+        expires_at: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(
+            "UPDATE session
+             SET refresh_token_hash = ?, prev_token_hash = ?, access_jti = ?, access_exp = ?,
+                 expires_at = ?, last_used_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE id = ? AND refresh_token_hash = ? AND revoked_at IS NULL",
+        )
+        .bind(new_token_hash)
+        .bind(old_token_hash)
+        .bind(access_jti)
+        .bind(access_exp)
+        .bind(expires_at)
+        .bind(&old.id)
+        .bind(old_token_hash)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if rows > 0 {
+            deny_in_tx(&mut tx, &old.access_jti, old.access_exp).await?;
+        }
+        tx.commit().await?;
+        Ok(rows)
+    }
+
+    pub async fn list_active(&self, user_id: i32) -> Result<Vec<SessionInfo>, sqlx::Error> {
+        sqlx::query_as::<_, SessionInfo>(
+            "SELECT id, user_agent, created_at, last_used_at, expires_at
+             FROM session
+             WHERE user_id = ?
+               AND revoked_at IS NULL
+               AND expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             ORDER BY last_used_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// 吊销单个会话，其当前 access token 同时进入黑名单。返回受影响行数。
+    pub async fn revoke(&self, user_id: i32, id: &str) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let revoked = sqlx::query_as::<_, (String, i64)>(
+            "UPDATE session SET revoked_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE id = ? AND user_id = ? AND revoked_at IS NULL
+             RETURNING access_jti, access_exp",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (jti, exp) in &revoked {
+            deny_in_tx(&mut tx, jti, *exp).await?;
+        }
+        tx.commit().await?;
+        Ok(revoked.len() as u64)
+    }
+
+    /// 吊销用户的全部会话。返回受影响行数。
+    pub async fn revoke_all(&self, user_id: i32) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let revoked = sqlx::query_as::<_, (String, i64)>(
+            "UPDATE session SET revoked_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE user_id = ? AND revoked_at IS NULL
+             RETURNING access_jti, access_exp",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (jti, exp) in &revoked {
+            deny_in_tx(&mut tx, jti, *exp).await?;
+        }
+        tx.commit().await?;
+        Ok(revoked.len() as u64)
+    }
+
+    pub async fn is_denied(&self, jti: &str) -> Result<bool, sqlx::Error> {
+        let found: Option<i64> = sqlx::query_scalar("SELECT 1 FROM revoked_jti WHERE jti = ?")
+            .bind(jti)
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(found.is_some())
+    }
+}
+
+/// 写入黑名单，并顺手清理已自然过期的条目
+async fn deny_in_tx(
+    tx: &mut Transaction<'_, Sqlite>,
+    jti: &str,
+    exp: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO revoked_jti (jti, exp) VALUES (?, ?)")
+        .bind(jti)
+        .bind(exp)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query("DELETE FROM revoked_jti WHERE exp < CAST(strftime('%s', 'now') AS INTEGER)")
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+
+use super::model::{SessionInfo, TokenPair};
+use super::repository::{NewSession, SessionRepo};
+use crate::auth::{ACCESS_TOKEN_TTL, create_token, hash_token};
+use crate::error::ServiceError;
+
+/// refresh token 有效期（天）。每次轮换顺延。
+const REFRESH_TOKEN_DAYS: i64 = 30;
+
+#[derive(Clone)]
+pub struct SessionService {
+    repo: SessionRepo,
+}
+
+impl SessionService {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self {
+            repo: SessionRepo::new(db),
+        }
+    }
+
+    /// 登录成功后开启新会话，签发第一对 token
+    pub async fn issue(
+        &self,
+        user_id: i32,
+        role: &str,
+        user_agent: &str,
+        jwt_secret: &str,
+    ) -> Result<TokenPair, sqlx::Error> {
+        let sid = uuid::Uuid::new_v4().to_string();
+        let refresh_token = new_refresh_token();
+        let (access_token, claims) = create_token(user_id, role, &sid, jwt_secret);
+
+        self.repo
+            .create(&NewSession {
+                id: &sid,
+                user_id,
+                refresh_token_hash: &hash_token(&refresh_token),
+                access_jti: &claims.jti,
+                access_exp: claims.exp as i64,
+                user_agent,
+                expires_at: &refresh_expiry(),
+            })
+            .await?;
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token,
+            expires_in: ACCESS_TOKEN_TTL,
+        })
+    }
+
+    /// 用 refresh token 换新的一对 token（旧 refresh token 立即失效）。
+    ///
+    /// 已轮换掉的旧 token 再次出现说明可能泄露，直接吊销整个会话。
+    pub async fn refresh(
+        &self,
+        refresh_token: &str,
+        jwt_secret: &str,
+    ) -> Result<TokenPair, ServiceError> {
+        let old_hash = hash_token(refresh_token);
+
+        let Some(session) = self.repo.find_active_by_token(&old_hash).await? else {
+            if let Some((sid, user_id)) = self.repo.find_by_prev_token(&old_hash).await? {
+                tracing::warn!("refresh token 被重复使用，吊销会话 {sid}（用户 {user_id}）");
+                self.repo.revoke(user_id, &sid).await?;
+            }
+            return Err(ServiceError::Unauthorized("登录已过期，请重新登录".into()));
+        };
+
+        let new_token = new_refresh_token();
+        let (access_token, claims) =
+            create_token(session.user_id, &session.role, &session.id, jwt_secret);
+        let rows = self
+            .repo
+            .rotate(
+                &session,
+                &old_hash,
+                &hash_token(&new_token),
+                &claims.jti,
+                claims.exp as i64,
+                &refresh_expiry(),
+            )
+            .await?;
+        if rows == 0 {
+            // 并发刷新中落败的一方
+            return Err(ServiceError::Unauthorized("登录已过期，请重新登录".into()));
+        }
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token: new_token,
+            expires_in: ACCESS_TOKEN_TTL,
+        })
+    }
+
+    /// 列出用户的有效会话，`current_sid` 对应的一项标记为当前会话
+    pub async fn list(
+        &self,
+        user_id: i32,
+        current_sid: &str,
+    ) -> Result<Vec<SessionInfo>, sqlx::Error> {
+        let mut sessions = self.repo.list_active(user_id).await?;
+        for s in &mut sessions {
+            s.current = s.id == current_sid;
+        }
+        Ok(sessions)
+    }
+
+    /// 吊销单个会话，返回受影响行数（0 = 不存在或不属于该用户）
+    pub async fn revoke(&self, user_id: i32, sid: &str) -> Result<u64, sqlx::Error> {
+        self.repo.revoke(user_id, sid).await
+    }
+
+    /// 吊销用户的全部会话（改密码等场景）
+    pub async fn revoke_all(&self, user_id: i32) -> Result<u64, sqlx::Error> {
+        self.repo.revoke_all(user_id).await
+    }
+
+    /// access token 是否已被吊销
+    pub async fn is_denied(&self, jti: &str) -> Result<bool, sqlx::Error> {
+        self.repo.is_denied(jti).await
+    }
+}
+
+fn new_refresh_token() -> String {
+    nanoid::nanoid!(43)
+}
+
+fn refresh_expiry() -> String {
+    (chrono::Utc::now() + chrono::Duration::days(REFRESH_TOKEN_DAYS))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const TEST_SECRET: &str = "test-jwt-secret";
+
+    async fn setup() -> (SessionService, i32) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let uid: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('alice', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        (SessionService::new(pool), uid)
+    }
+
+    fn claims_of(token: &str) -> crate::auth::Claims {
+        use jsonwebtoken::{DecodingKey, Validation, decode};
+        decode::<crate::auth::Claims>(
+            token,
+            &DecodingKey::from_secret(TEST_SECRET.as_bytes()),
+            &Validation::default(),
+        )
+        .unwrap()
+        .claims
+    }
+
+    fn jti_of(token: &str) -> String {
+        claims_of(token).jti
+    }
+
+    #[tokio::test]
+    async fn refresh_rotates_token() {
+        let (svc, uid) = setup().await;
+        let first = svc.issue(uid, "user", "ua", TEST_SECRET).await.unwrap();
+        let second = svc
+            .refresh(&first.refresh_token, TEST_SECRET)
+            .await
+            .unwrap();
+        assert_ne!(first.refresh_token, second.refresh_token);
+
+        // 旧 access token 进入黑名单，新的可用
+        assert!(svc.is_denied(&jti_of(&first.access_token)).await.unwrap());
+        assert!(!svc.is_denied(&jti_of(&second.access_token)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reused_refresh_token_revokes_session() {
+        let (svc, uid) = setup().await;
+        let first = svc.issue(uid, "user", "ua", TEST_SECRET).await.unwrap();
+        let second = svc
+            .refresh(&first.refresh_token, TEST_SECRET)
+            .await
+            .unwrap();
+
+        let err = svc
+            .refresh(&first.refresh_token, TEST_SECRET)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Unauthorized(_)));
+
+        // 整个会话被吊销：新 token 也不能再用
+        assert!(svc.is_denied(&jti_of(&second.access_token)).await.unwrap());
+        assert!(
+            svc.refresh(&second.refresh_token, TEST_SECRET)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_refresh_token_is_rejected() {
+        let (svc, _) = setup().await;
+        let err = svc.refresh("nope", TEST_SECRET).await.unwrap_err();
+        assert!(matches!(err, ServiceError::Unauthorized(_)));
+    }
+
+    #[tokio::test]
+    async fn list_marks_current_session() {
+        let (svc, uid) = setup().await;
+        let a = svc.issue(uid, "user", "phone", TEST_SECRET).await.unwrap();
+        svc.issue(uid, "user", "laptop", TEST_SECRET).await.unwrap();
+
+        let sessions = svc
+            .list(uid, &claims_of(&a.access_token).sid)
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+        let current: Vec<_> = sessions.iter().filter(|s| s.current).collect();
+        assert_eq!(current.len(), 1);
+        assert_eq!(current[0].user_agent, "phone");
+    }
+
+    #[tokio::test]
+    async fn revoke_only_own_session() {
+        let (svc, uid) = setup().await;
+        let pair = svc.issue(uid, "user", "ua", TEST_SECRET).await.unwrap();
+        let sid = svc.list(uid, "").await.unwrap()[0].id.clone();
+
+        assert_eq!(svc.revoke(uid + 1, &sid).await.unwrap(), 0);
+        assert_eq!(svc.revoke(uid, &sid).await.unwrap(), 1);
+        assert!(svc.is_denied(&jti_of(&pair.access_token)).await.unwrap());
+        assert!(svc.list(uid, "").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn revoke_all_denies_every_session() {
+        let (svc, uid) = setup().await;
+        let a = svc.issue(uid, "user", "a", TEST_SECRET).await.unwrap();
+        let b = svc.issue(uid, "user", "b", TEST_SECRET).await.unwrap();
+
+        assert_eq!(svc.revoke_all(uid).await.unwrap(), 2);
+        assert!(svc.is_denied(&jti_of(&a.access_token)).await.unwrap());
+        assert!(svc.is_denied(&jti_of(&b.access_token)).await.unwrap());
+        assert!(svc.refresh(&a.refresh_token, TEST_SECRET).await.is_err());
+    }
+}
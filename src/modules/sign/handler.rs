@@ -1,14 +1,17 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::auth::Claims;
 use crate::error;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateSignRequest {
     pub signifier: String,
     pub signified: String,
@@ -17,7 +20,7 @@ pub struct CreateSignRequest {
     pub relation_type: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SignResponse {
     pub id: i32,
     pub signifier: String,
@@ -42,13 +45,22 @@ impl From<super::model::SignifierSignified> for SignResponse {
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建符号关系",
+    request_body = CreateSignRequest,
+    responses((status = 201, description = "已创建", body = SignResponse))
+)]
 pub async fn create_sign_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateSignRequest>,
 ) -> impl IntoResponse {
     let result = state
         .sign
         .create(
+            claims.sub,
             payload.signifier,
             payload.signified,
             payload.onto_id,
@@ -60,13 +72,21 @@ pub async fn create_sign_handler(
     error::created_or(result, "创建符号关系")
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "符号关系列表",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<SignResponse>))
+)]
 pub async fn get_signs_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let result = state
         .sign
-        .list(pagination.limit(), pagination.offset())
+        .list(claims.sub, pagination.limit(), pagination.offset())
         .await
         .map(|(items, total)| {
             let items: Vec<SignResponse> = items.into_iter().map(SignResponse::from).collect();
@@ -75,33 +95,60 @@ pub async fn get_signs_handler(
     error::ok_or(result, "获取符号关系列表")
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取符号关系",
+    responses((status = 200, description = "成功", body = SignResponse))
+)]
 pub async fn get_sign_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
     let result = state
         .sign
-        .by_id(id)
+        .by_id(claims.sub, id)
         .await
         .map(|opt| opt.map(SignResponse::from));
     error::found_or(result, "获取符号关系")
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除符号关系",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_sign_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<i32>,
 ) -> impl IntoResponse {
-    error::deleted_or(state.sign.delete(id).await, "删除符号关系")
+    error::deleted_or(state.sign.delete(claims.sub, id).await, "删除符号关系")
 }
 
+#[utoipa::path(
+    get,
+    path = "/signifier/{signifier}",
+    summary = "按能指查询",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<SignResponse>))
+)]
 pub async fn get_signs_by_signifier_handler(
     Path(signifier): Path<String>,
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let result = state
         .sign
-        .by_signifier(&signifier, pagination.limit(), pagination.offset())
+        .by_signifier(
+            claims.sub,
+            &signifier,
+            pagination.limit(),
+            pagination.offset(),
+        )
         .await
         .map(|(items, total)| {
             let items: Vec<SignResponse> = items.into_iter().map(SignResponse::from).collect();
@@ -110,14 +157,27 @@ pub async fn get_signs_by_signifier_handler(
     error::ok_or(result, "按能指查询")
 }
 
+#[utoipa::path(
+    get,
+    path = "/signified/{signified}",
+    summary = "按所指查询",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<SignResponse>))
+)]
 pub async fn get_signs_by_signified_handler(
     Path(signified): Path<String>,
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
     let result = state
         .sign
-        .by_signified(&signified, pagination.limit(), pagination.offset())
+        .by_signified(
+            claims.sub,
+            &signified,
+            pagination.limit(),
+            pagination.offset(),
+        )
         .await
         .map(|(items, total)| {
             let items: Vec<SignResponse> = items.into_iter().map(SignResponse::from).collect();
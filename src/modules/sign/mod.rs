@@ -4,25 +4,21 @@ mod repository;
 pub mod service;
 
 pub use service::SignService;
-
-pub use handler::{
-    create_sign_handler, delete_sign_handler, get_sign_handler, get_signs_by_signified_handler,
-    get_signs_by_signifier_handler, get_signs_handler,
-};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{Router, routing::get};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/", get(get_signs_handler).post(create_sign_handler))
-        .route("/{id}", get(get_sign_handler).delete(delete_sign_handler))
-        .route(
-            "/signifier/{signifier}",
-            get(get_signs_by_signifier_handler),
-        )
-        .route(
-            "/signified/{signified}",
-            get(get_signs_by_signified_handler),
-        )
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::get_signs_handler,
+            handler::create_sign_handler
+        ))
+        .routes(routes!(
+            handler::get_sign_handler,
+            handler::delete_sign_handler
+        ))
+        .routes(routes!(handler::get_signs_by_signifier_handler))
+        .routes(routes!(handler::get_signs_by_signified_handler))
 }
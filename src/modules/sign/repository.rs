@@ -16,16 +16,32 @@ impl SignRepository {
     }
 
     /// 根据ID获取能指所指关系
-    pub async fn find_by_id(&self, id: i32) -> Result<Option<SignifierSignified>, sqlx::Error> {
-        sqlx::query_as::<_, SignifierSignified>("SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE id = ?")
+    pub async fn find_by_id(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<SignifierSignified>, sqlx::Error> {
+        sqlx::query_as::<_, SignifierSignified>("SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .fetch_optional(&*self.db)
             .await
     }
 
+    /// 本体是否存在且属于该用户
+    pub async fn onto_owned(&self, user_id: i32, onto_id: i32) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query("SELECT 1 FROM onto WHERE id = ? AND user_id = ?")
+            .bind(onto_id)
+            .bind(user_id)
+            .fetch_optional(&*self.db)
+            .await?;
+        Ok(row.is_some())
+    }
+
     /// 创建能指所指关系
     pub async fn create(
         &self,
+        user_id: i32,
         signifier: String,
         signified: String,
         onto_id: Option<i32>,
@@ -36,7 +52,7 @@ impl SignRepository {
         let now = Utc::now();
 
         let result = sqlx::query(
-            "INSERT INTO signifier_signified (signifier, signified, onto_id, weight, relation_type, created_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING id, signifier, signified, onto_id, weight, relation_type, created_at"
+            "INSERT INTO signifier_signified (signifier, signified, onto_id, weight, relation_type, created_at, user_id) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id, signifier, signified, onto_id, weight, relation_type, created_at"
         )
             .bind(&signifier)
             .bind(&signified)
@@ -44,6 +60,7 @@ impl SignRepository {
             .bind(weight)
             .bind(&relation_type)
             .bind(now)
+            .bind(user_id)
             .fetch_one(&*self.db)
             .await?;
 
@@ -59,9 +76,10 @@ impl SignRepository {
     }
 
     /// 删除能指所指关系
-    pub async fn delete(&self, id: i32) -> Result<u64, sqlx::Error> {
-        let result = sqlx::query("DELETE FROM signifier_signified WHERE id = ?")
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM signifier_signified WHERE id = ? AND user_id = ?")
             .bind(id)
+            .bind(user_id)
             .execute(&*self.db)
             .await?;
 
@@ -71,15 +89,19 @@ impl SignRepository {
     /// 获取所有能指所指关系（分页）
     pub async fn find_all_paginated(
         &self,
+        user_id: i32,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), sqlx::Error> {
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM signifier_signified")
-            .fetch_one(&*self.db)
-            .await?;
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM signifier_signified WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&*self.db)
+                .await?;
         let items = sqlx::query_as::<_, SignifierSignified>(
-            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified ORDER BY id LIMIT ? OFFSET ?",
+            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE user_id = ? ORDER BY id LIMIT ? OFFSET ?",
         )
+        .bind(user_id)
         .bind(limit)
         .bind(offset)
         .fetch_all(&*self.db)
@@ -90,19 +112,23 @@ impl SignRepository {
     /// 根据能指查找关系（分页）
     pub async fn find_by_signifier_paginated(
         &self,
+        user_id: i32,
         signifier: &str,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), sqlx::Error> {
-        let total: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM signifier_signified WHERE signifier = ?")
-                .bind(signifier)
-                .fetch_one(&*self.db)
-                .await?;
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM signifier_signified WHERE signifier = ? AND user_id = ?",
+        )
+        .bind(signifier)
+        .bind(user_id)
+        .fetch_one(&*self.db)
+        .await?;
         let items = sqlx::query_as::<_, SignifierSignified>(
-            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE signifier = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE signifier = ? AND user_id = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
         )
         .bind(signifier)
+        .bind(user_id)
         .bind(limit)
         .bind(offset)
         .fetch_all(&*self.db)
@@ -113,19 +139,23 @@ impl SignRepository {
     /// 根据所指查找关系（分页）
     pub async fn find_by_signified_paginated(
         &self,
+        user_id: i32,
         signified: &str,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), sqlx::Error> {
-        let total: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM signifier_signified WHERE signified = ?")
-                .bind(signified)
-                .fetch_one(&*self.db)
-                .await?;
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM signifier_signified WHERE signified = ? AND user_id = ?",
+        )
+        .bind(signified)
+        .bind(user_id)
+        .fetch_one(&*self.db)
+        .await?;
         let items = sqlx::query_as::<_, SignifierSignified>(
-            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE signified = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            "SELECT id, signifier, signified, onto_id, weight, relation_type, created_at FROM signifier_signified WHERE signified = ? AND user_id = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
         )
         .bind(signified)
+        .bind(user_id)
         .bind(limit)
         .bind(offset)
         .fetch_all(&*self.db)
@@ -142,7 +172,7 @@ mod tests {
 
     async fn setup() -> SignRepository {
         let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
-        sqlx::query("CREATE TABLE signifier_signified (id INTEGER PRIMARY KEY AUTOINCREMENT, signifier TEXT NOT NULL, signified TEXT NOT NULL, onto_id INTEGER, weight REAL, relation_type TEXT, created_at TEXT NOT NULL)")
+        sqlx::query("CREATE TABLE signifier_signified (id INTEGER PRIMARY KEY AUTOINCREMENT, signifier TEXT NOT NULL, signified TEXT NOT NULL, onto_id INTEGER, weight REAL, relation_type TEXT, created_at TEXT NOT NULL, user_id INTEGER NOT NULL)")
             .execute(&pool).await.unwrap();
         SignRepository::new(Arc::new(pool))
     }
@@ -151,26 +181,29 @@ mod tests {
     async fn create_and_find_by_id() {
         let repo = setup().await;
         let s = repo
-            .create("猫".into(), "cat".into(), None, None, None)
+            .create(1, "猫".into(), "cat".into(), None, None, None)
             .await
             .unwrap();
         assert!(s.id > 0);
         assert_eq!(s.signifier, "猫");
 
-        let found = repo.find_by_id(s.id).await.unwrap().unwrap();
+        let found = repo.find_by_id(1, s.id).await.unwrap().unwrap();
         assert_eq!(found.signified, "cat");
     }
 
     #[tokio::test]
     async fn find_by_signifier() {
         let repo = setup().await;
-        repo.create("狗".into(), "dog".into(), None, None, None)
+        repo.create(1, "狗".into(), "dog".into(), None, None, None)
             .await
             .unwrap();
-        repo.create("狗".into(), "chien".into(), None, None, None)
+        repo.create(1, "狗".into(), "chien".into(), None, None, None)
+            .await
+            .unwrap();
+        let (items, total) = repo
+            .find_by_signifier_paginated(1, "狗", 10, 0)
             .await
             .unwrap();
-        let (items, total) = repo.find_by_signifier_paginated("狗", 10, 0).await.unwrap();
         assert_eq!(total, 2);
         assert_eq!(items.len(), 2);
     }
@@ -178,11 +211,11 @@ mod tests {
     #[tokio::test]
     async fn find_by_signified() {
         let repo = setup().await;
-        repo.create("书".into(), "book".into(), None, None, None)
+        repo.create(1, "书".into(), "book".into(), None, None, None)
             .await
             .unwrap();
         let (_, total) = repo
-            .find_by_signified_paginated("book", 10, 0)
+            .find_by_signified_paginated(1, "book", 10, 0)
             .await
             .unwrap();
         assert_eq!(total, 1);
@@ -192,10 +225,26 @@ mod tests {
     async fn delete_cascade() {
         let repo = setup().await;
         let s = repo
-            .create("x".into(), "y".into(), None, None, None)
+            .create(1, "x".into(), "y".into(), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(repo.delete(1, s.id).await.unwrap(), 1);
+        assert!(repo.find_by_id(1, s.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn other_users_signs_are_invisible() {
+        let repo = setup().await;
+        let s = repo
+            .create(1, "猫".into(), "cat".into(), None, None, None)
+            .await
+            .unwrap();
+        assert!(repo.find_by_id(2, s.id).await.unwrap().is_none());
+        assert_eq!(repo.delete(2, s.id).await.unwrap(), 0);
+        let (_, total) = repo
+            .find_by_signifier_paginated(2, "猫", 10, 0)
             .await
             .unwrap();
-        assert_eq!(repo.delete(s.id).await.unwrap(), 1);
-        assert!(repo.find_by_id(s.id).await.unwrap().is_none());
+        assert_eq!(total, 0);
     }
 }
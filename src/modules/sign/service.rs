@@ -18,21 +18,30 @@ impl SignService {
 
     pub async fn list(
         &self,
+        user_id: i32,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), ServiceError> {
         self.repo
-            .find_all_paginated(limit, offset)
+            .find_all_paginated(user_id, limit, offset)
             .await
             .map_err(ServiceError::Db)
     }
 
-    pub async fn by_id(&self, id: i32) -> Result<Option<SignifierSignified>, ServiceError> {
-        self.repo.find_by_id(id).await.map_err(ServiceError::Db)
+    pub async fn by_id(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<SignifierSignified>, ServiceError> {
+        self.repo
+            .find_by_id(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 
     pub async fn create(
         &self,
+        user_id: i32,
         signifier: String,
         signified: String,
         onto_id: Option<i32>,
@@ -45,36 +54,58 @@ impl SignService {
         if signified.trim().is_empty() {
             return Err(ServiceError::InvalidInput("所指不能为空".into()));
         }
+        // 只能挂到自己的本体上
+        if let Some(onto_id) = onto_id
+            && !self
+                .repo
+                .onto_owned(user_id, onto_id)
+                .await
+                .map_err(ServiceError::Db)?
+        {
+            return Err(ServiceError::NotFound("本体不存在".into()));
+        }
         self.repo
-            .create(signifier, signified, onto_id, weight, relation_type)
+            .create(
+                user_id,
+                signifier,
+                signified,
+                onto_id,
+                weight,
+                relation_type,
+            )
             .await
             .map_err(ServiceError::Db)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete(id).await.map_err(ServiceError::Db)
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, ServiceError> {
+        self.repo
+            .delete(user_id, id)
+            .await
+            .map_err(ServiceError::Db)
     }
 
     pub async fn by_signifier(
         &self,
+        user_id: i32,
         signifier: &str,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), ServiceError> {
         self.repo
-            .find_by_signifier_paginated(signifier, limit, offset)
+            .find_by_signifier_paginated(user_id, signifier, limit, offset)
             .await
             .map_err(ServiceError::Db)
     }
 
     pub async fn by_signified(
         &self,
+        user_id: i32,
         signified: &str,
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<SignifierSignified>, i64), ServiceError> {
         self.repo
-            .find_by_signified_paginated(signified, limit, offset)
+            .find_by_signified_paginated(user_id, signified, limit, offset)
             .await
             .map_err(ServiceError::Db)
     }
@@ -88,8 +119,18 @@ mod tests {
 
     async fn setup() -> SignService {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE signifier_signified (id INTEGER PRIMARY KEY AUTOINCREMENT, signifier TEXT NOT NULL, signified TEXT NOT NULL, onto_id INTEGER, weight REAL, relation_type TEXT, created_at TEXT NOT NULL)")
+        sqlx::query("CREATE TABLE signifier_signified (id INTEGER PRIMARY KEY AUTOINCREMENT, signifier TEXT NOT NULL, signified TEXT NOT NULL, onto_id INTEGER, weight REAL, relation_type TEXT, created_at TEXT NOT NULL, user_id INTEGER NOT NULL)")
             .execute(&*pool).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE onto (id INTEGER PRIMARY KEY AUTOINCREMENT, user_id INTEGER NOT NULL)",
+        )
+        .execute(&*pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO onto (id, user_id) VALUES (1, 1), (2, 2)")
+            .execute(&*pool)
+            .await
+            .unwrap();
         SignService::new(pool)
     }
 
@@ -97,7 +138,7 @@ mod tests {
     async fn create_valid() {
         let svc = setup().await;
         let s = svc
-            .create("日".into(), "sun".into(), None, None, None)
+            .create(1, "日".into(), "sun".into(), None, None, None)
             .await
             .unwrap();
         assert_eq!(s.signifier, "日");
@@ -107,7 +148,7 @@ mod tests {
     async fn create_empty_signifier_rejected() {
         let svc = setup().await;
         let err = svc
-            .create("".into(), "sun".into(), None, None, None)
+            .create(1, "".into(), "sun".into(), None, None, None)
             .await
             .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
@@ -117,22 +158,46 @@ mod tests {
     async fn create_empty_signified_rejected() {
         let svc = setup().await;
         let err = svc
-            .create("日".into(), "  ".into(), None, None, None)
+            .create(1, "日".into(), "  ".into(), None, None, None)
             .await
             .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
     }
 
+    #[tokio::test]
+    async fn create_with_own_onto() {
+        let svc = setup().await;
+        let s = svc
+            .create(1, "日".into(), "sun".into(), Some(1), None, None)
+            .await
+            .unwrap();
+        assert_eq!(s.onto_id, Some(1));
+    }
+
+    #[tokio::test]
+    async fn create_with_foreign_or_missing_onto_rejected() {
+        let svc = setup().await;
+        for onto_id in [2, 99] {
+            let err = svc
+                .create(1, "日".into(), "sun".into(), Some(onto_id), None, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ServiceError::NotFound(_)));
+        }
+        let (_, total) = svc.list(1, 10, 0).await.unwrap();
+        assert_eq!(total, 0);
+    }
+
     #[tokio::test]
     async fn list_paginated() {
         let svc = setup().await;
-        svc.create("a".into(), "1".into(), None, None, None)
+        svc.create(1, "a".into(), "1".into(), None, None, None)
             .await
             .unwrap();
-        svc.create("b".into(), "2".into(), None, None, None)
+        svc.create(1, "b".into(), "2".into(), None, None, None)
             .await
             .unwrap();
-        let (items, total) = svc.list(1, 0).await.unwrap();
+        let (items, total) = svc.list(1, 1, 0).await.unwrap();
         assert_eq!(total, 2);
         assert_eq!(items.len(), 1);
     }
@@ -140,10 +205,10 @@ mod tests {
     #[tokio::test]
     async fn by_signifier_query() {
         let svc = setup().await;
-        svc.create("月".into(), "moon".into(), None, None, None)
+        svc.create(1, "月".into(), "moon".into(), None, None, None)
             .await
             .unwrap();
-        let (items, _) = svc.by_signifier("月", 10, 0).await.unwrap();
+        let (items, _) = svc.by_signifier(1, "月", 10, 0).await.unwrap();
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].signified, "moon");
     }
@@ -152,10 +217,10 @@ mod tests {
     async fn delete_sign() {
         let svc = setup().await;
         let s = svc
-            .create("x".into(), "y".into(), None, None, None)
+            .create(1, "x".into(), "y".into(), None, None, None)
             .await
             .unwrap();
-        assert_eq!(svc.delete(s.id).await.unwrap(), 1);
-        assert!(svc.by_id(s.id).await.unwrap().is_none());
+        assert_eq!(svc.delete(1, s.id).await.unwrap(), 1);
+        assert!(svc.by_id(1, s.id).await.unwrap().is_none());
     }
 }
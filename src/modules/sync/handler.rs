@@ -0,0 +1,55 @@
+use axum::{
+    Extension,
+    extract::{Query, State},
+    response::{IntoResponse, Json},
+};
+
+use super::model::{PullQuery, PullResponse, PushRequest, PushResponse};
+use crate::auth::Claims;
+use crate::state::AppState;
+
+/// GET /api/sync
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "拉取令牌之后的变更（含墓碑）",
+    params(PullQuery),
+    responses((status = 200, description = "成功", body = PullResponse))
+)]
+pub async fn pull_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PullQuery>,
+) -> impl IntoResponse {
+    match state
+        .sync
+        .pull(claims.sub, claims.scopes.as_deref(), &query)
+        .await
+    {
+        Ok(resp) => Json(resp).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/sync
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "推送离线变更，逐条返回 applied / conflict / rejected",
+    request_body = PushRequest,
+    responses((status = 200, description = "成功", body = PushResponse))
+)]
+pub async fn push_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(req): Json<PushRequest>,
+) -> impl IntoResponse {
+    match state
+        .sync
+        .push(claims.sub, claims.scopes.as_deref(), &req)
+        .await
+    {
+        Ok(resp) => Json(resp).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,24 @@
+//! 离线客户端的增量同步。
+//!
+//! mem（含正反面 chunk）、标签、任务、时间窗口与书签的每次增删改由数据库触发器
+//! 记入 `sync_change`，序号即同步令牌。`GET /api/sync?since=` 分页返回令牌之后
+//! 变化过的实体的最新状态，已删除的以墓碑 `{entity, id}` 下发；首次同步省略 `since`。
+//!
+//! 客户端离线时记录的操作经 `POST /api/sync` 一次推送：复习带客户端时间，
+//! 按时间顺序通过 FSRS 重放；与服务端并发修改冲突时以服务端为准，返回 `conflict`。
+//! 推送后应从原令牌继续拉取，以取回自身与其他设备的变更。
+
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use service::SyncService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::pull_handler, handler::push_handler))
+}
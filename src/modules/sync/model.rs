@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::modules::bookmark::BookmarkResponse;
+use crate::modules::task::{CreateTaskRequest, Task, TimeWindow, UpdateTaskRequest};
+
+/// GET /api/sync 查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PullQuery {
+    /// 上次拉取得到的令牌；省略则从头全量同步
+    pub since: Option<String>,
+    /// 每页最多返回的变更数，默认 500
+    pub limit: Option<i64>,
+}
+
+/// mem 的调度状态，正反面内容见 `chunks`
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SyncMem {
+    pub id: i32,
+    pub cue_chunk_id: i32,
+    pub target_chunk_id: i32,
+    pub state: String,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub step_index: Option<i32>,
+    pub buried: bool,
+    pub lapses: i32,
+    pub leeched: bool,
+    pub due_at: Option<String>,
+    pub last_review_at: Option<String>,
+    pub created_at: Option<String>,
+    #[sqlx(skip)]
+    pub tag_ids: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SyncChunk {
+    pub id: i32,
+    pub content: String,
+    pub updated_at: Option<String>,
+}
+
+/// mem 标签
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct SyncTag {
+    pub id: i32,
+    pub name: String,
+}
+
+/// 已删除（含移入回收站）的实体，客户端应在本地删除
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Tombstone {
+    /// `mem` / `chunk` / `tag` / `task` / `time_window` / `bookmark`
+    pub entity: String,
+    pub id: i32,
+}
+
+/// 一页增量变更。`has_more` 为真时用 `token` 继续拉取
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct PullResponse {
+    pub token: String,
+    pub has_more: bool,
+    pub mems: Vec<SyncMem>,
+    pub chunks: Vec<SyncChunk>,
+    pub tags: Vec<SyncTag>,
+    pub tasks: Vec<Task>,
+    pub time_windows: Vec<TimeWindow>,
+    pub bookmarks: Vec<BookmarkResponse>,
+    pub tombstones: Vec<Tombstone>,
+}
+
+/// 变更引用的实体：整数为服务端 id，字符串为同一批次中创建操作的 `client_id`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum EntityRef {
+    Id(i32),
+    Client(String),
+}
+
+/// 客户端离线期间的一次操作
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Mutation {
+    /// `reviewed_at` 为客户端记录的复习时刻
+    Review {
+        mem: EntityRef,
+        rating: u8,
+        reviewed_at: DateTime<Utc>,
+    },
+    CreateMem {
+        client_id: String,
+        cue_content: String,
+        target_content: String,
+    },
+    EditMem {
+        mem: EntityRef,
+        cue_content: String,
+        target_content: String,
+    },
+    DeleteMem {
+        mem: EntityRef,
+    },
+    CreateTask {
+        client_id: String,
+        task: CreateTaskRequest,
+    },
+    UpdateTask {
+        task: EntityRef,
+        patch: UpdateTaskRequest,
+    },
+    DeleteTask {
+        task: EntityRef,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct PushMutation {
+    /// 客户端生成、在该用户下唯一；重发同一操作时据此去重
+    pub mutation_id: String,
+    #[serde(flatten)]
+    pub op: Mutation,
+}
+
+/// POST /api/sync 请求体
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushRequest {
+    /// 客户端本地数据对应的令牌，用于检测服务端在此之后的并发修改
+    pub since: Option<String>,
+    pub mutations: Vec<PushMutation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationStatus {
+    Applied,
+    /// 服务端在 `since` 之后已修改该实体，以服务端为准
+    Conflict,
+    /// 校验失败、实体不存在或无权限
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MutationResult {
+    pub mutation_id: String,
+    pub status: MutationStatus,
+    /// 创建操作得到的服务端 id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 与请求中 `mutations` 一一对应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushResponse {
+    pub results: Vec<MutationResult>,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_tagged_mutations() {
+        let m: PushMutation = serde_json::from_value(json!({
+            "mutation_id": "m1",
+            "op": "review",
+            "mem": "local-1",
+            "rating": 3,
+            "reviewed_at": "2024-05-01T08:00:00Z",
+        }))
+        .unwrap();
+        assert_eq!(m.mutation_id, "m1");
+        assert!(matches!(
+            m.op,
+            Mutation::Review { mem: EntityRef::Client(ref c), rating: 3, .. } if c == "local-1"
+        ));
+
+        let m: PushMutation = serde_json::from_value(json!({
+            "mutation_id": "m2",
+            "op": "update_task",
+            "task": 7,
+            "patch": { "title": "新标题" },
+        }))
+        .unwrap();
+        assert!(matches!(
+            m.op,
+            Mutation::UpdateTask {
+                task: EntityRef::Id(7),
+                ..
+            }
+        ));
+
+        let bad =
+            serde_json::from_value::<PushMutation>(json!({ "mutation_id": "m3", "op": "nope" }));
+        assert!(bad.is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use std::sync::Arc;
+
+use super::model::{SyncChunk, SyncMem, SyncTag};
+use crate::modules::task::{Task, TimeWindow};
+
+/// `sync_change` 中的一条变更
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChangeRow {
+    pub seq: i64,
+    pub entity: String,
+    pub entity_id: i32,
+    pub deleted: bool,
+}
+
+#[derive(Clone)]
+pub struct SyncRepo {
+    db: Arc<SqlitePool>,
+}
+
+impl SyncRepo {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+
+    /// 当前最新的变更序号
+    pub async fn latest_seq(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COALESCE(MAX(seq), 0) FROM sync_change")
+            .fetch_one(&*self.db)
+            .await
+    }
+
+    /// `(since, upto]` 之间用户可见的变更，按序号升序，最多 `limit` 条。
+    /// `entities` 为空时返回空
+    pub async fn changes(
+        &self,
+        user_id: i32,
+        since: i64,
+        upto: i64,
+        entities: &[&str],
+        limit: i64,
+    ) -> Result<Vec<ChangeRow>, sqlx::Error> {
+        if entities.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT seq, entity, entity_id, deleted FROM sync_change WHERE seq > ",
+        );
+        qb.push_bind(since)
+            .push(" AND seq <= ")
+            .push_bind(upto)
+            .push(" AND (user_id = ")
+            .push_bind(user_id)
+            .push(" OR user_id IS NULL) AND entity IN (");
+        let mut list = qb.separated(", ");
+        for entity in entities {
+            list.push_bind(entity.to_string());
+        }
+        qb.push(") ORDER BY seq LIMIT ").push_bind(limit);
+        qb.build_query_as().fetch_all(&*self.db).await
+    }
+
+    /// 实体最近一次变更的序号，从未记录过为 0
+    pub async fn seq_of(&self, entity: &str, id: i32) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(MAX(seq), 0) FROM sync_change WHERE entity = ? AND entity_id = ?",
+        )
+        .bind(entity)
+        .bind(id)
+        .fetch_one(&*self.db)
+        .await
+    }
+
+    /// 未删除 mem 的 `(cue_chunk_id, target_chunk_id, last_review_at)`
+    pub async fn mem_head(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> Result<Option<(i32, i32, Option<String>)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT cue_chunk_id, target_chunk_id, last_review_at FROM mem
+             WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.db)
+        .await
+    }
+
+    pub async fn mems(&self, user_id: i32, ids: &[i32]) -> Result<Vec<SyncMem>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT id, cue_chunk_id, target_chunk_id, state, stability, difficulty, step_index,
+                buried, lapses, leeched, due_at, last_review_at, created_at
+             FROM mem WHERE deleted_at IS NULL AND user_id = ",
+        );
+        qb.push_bind(user_id);
+        push_ids(&mut qb, ids);
+        let mut mems: Vec<SyncMem> = qb.build_query_as().fetch_all(&*self.db).await?;
+
+        let mut qb = QueryBuilder::<Sqlite>::new("SELECT mem_id, tag_id FROM mem_tag WHERE 1 = 1");
+        push_ids_as(&mut qb, "mem_id", ids);
+        qb.push(" ORDER BY tag_id");
+        let pairs: Vec<(i32, i32)> = qb.build_query_as().fetch_all(&*self.db).await?;
+        for mem in &mut mems {
+            mem.tag_ids = pairs
+                .iter()
+                .filter(|(m, _)| *m == mem.id)
+                .map(|(_, t)| *t)
+                .collect();
+        }
+        Ok(mems)
+    }
+
+    pub async fn chunks(&self, user_id: i32, ids: &[i32]) -> Result<Vec<SyncChunk>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT id, content, updated_at FROM chunk WHERE user_id = ",
+        );
+        qb.push_bind(user_id);
+        push_ids(&mut qb, ids);
+        qb.build_query_as().fetch_all(&*self.db).await
+    }
+
+    pub async fn tags(&self, user_id: i32, ids: &[i32]) -> Result<Vec<SyncTag>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new("SELECT id, name FROM tag WHERE user_id = ");
+        qb.push_bind(user_id);
+        push_ids(&mut qb, ids);
+        qb.build_query_as().fetch_all(&*self.db).await
+    }
+
+    pub async fn tasks(&self, ids: &[i32]) -> Result<Vec<Task>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT id, title, description, parent_task_id, status, completed_at,
+                effort_estimate_minutes, created_at, updated_at
+             FROM task WHERE deleted_at IS NULL",
+        );
+        push_ids(&mut qb, ids);
+        qb.build_query_as().fetch_all(&*self.db).await
+    }
+
+    pub async fn time_windows(&self, ids: &[i32]) -> Result<Vec<TimeWindow>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut qb = QueryBuilder::<Sqlite>::new(
+            "SELECT id, start_time, end_time, type, task_id, user_id,
+                recurrence_freq, recurrence_interval, recurrence_until, recurrence_by_weekdays
+             FROM time_window WHERE 1 = 1",
+        );
+        push_ids(&mut qb, ids);
+        qb.build_query_as().fetch_all(&*self.db).await
+    }
+
+    /// 此前推送过的同一变更的结果（JSON）
+    pub async fn find_mutation(
+        &self,
+        user_id: i32,
+        mutation_id: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT result FROM sync_mutation WHERE user_id = ? AND mutation_id = ?")
+            .bind(user_id)
+            .bind(mutation_id)
+            .fetch_optional(&*self.db)
+            .await
+    }
+
+    pub async fn save_mutation(
+        &self,
+        user_id: i32,
+        mutation_id: &str,
+        result: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO sync_mutation (user_id, mutation_id, result) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(mutation_id)
+        .bind(result)
+        .execute(&*self.db)
+        .await?;
+        Ok(())
+    }
+}
+
+fn push_ids(qb: &mut QueryBuilder<Sqlite>, ids: &[i32]) {
+    push_ids_as(qb, "id", ids);
+}
+
+fn push_ids_as(qb: &mut QueryBuilder<Sqlite>, column: &'static str, ids: &[i32]) {
+    qb.push(" AND ").push(column).push(" IN (");
+    let mut list = qb.separated(", ");
+    for id in ids {
+        list.push_bind(*id);
+    }
+    qb.push(")");
+}
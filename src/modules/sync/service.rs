@@ -0,0 +1,781 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use super::model::{
+    EntityRef, Mutation, MutationResult, MutationStatus, PullQuery, PullResponse, PushMutation,
+    PushRequest, PushResponse, Tombstone,
+};
+use super::repository::{ChangeRow, SyncRepo};
+use crate::error::ServiceError;
+use crate::modules::api_token::model::scope_allows;
+use crate::modules::bookmark::{BookmarkResponse, BookmarkService};
+use crate::modules::mem::model::{AppError, CreateMemRequest, EditMemRequest};
+use crate::modules::mem::service::MemService;
+use crate::modules::task::TaskService;
+
+const DEFAULT_LIMIT: i64 = 500;
+const MAX_LIMIT: i64 = 1000;
+/// 单次推送的最大变更数
+const MAX_MUTATIONS: usize = 500;
+/// 允许客户端时钟超前服务端的幅度
+const CLOCK_SKEW_SECS: i64 = 300;
+
+/// 参与同步的实体及其对应的作用域资源
+const ENTITIES: [(&str, &str); 6] = [
+    ("mem", "mem"),
+    ("chunk", "mem"),
+    ("tag", "mem"),
+    ("task", "tasks"),
+    ("time_window", "time-windows"),
+    ("bookmark", "bookmarks"),
+];
+
+/// 增量同步。
+///
+/// 拉取按 `sync_change` 的序号分页返回令牌之后的变更；推送的写操作委托给各模块
+/// service，与在线接口走同一套校验、审计与推送。
+///
+/// 权限也与在线接口一致：mem 只能改自己的；任务不分归属，与 `/api/tasks`
+/// 一样任何登录用户（或带 `tasks:write` 的令牌）都可修改、删除。
+#[derive(Clone)]
+pub struct SyncService {
+    repo: SyncRepo,
+    mem: MemService,
+    task: TaskService,
+    bookmark: BookmarkService,
+}
+
+impl SyncService {
+    pub fn new(
+        db: Arc<SqlitePool>,
+        mem: MemService,
+        task: TaskService,
+        bookmark: BookmarkService,
+    ) -> Self {
+        Self {
+            repo: SyncRepo::new(db),
+            mem,
+            task,
+            bookmark,
+        }
+    }
+
+    /// 令牌之后的变更。`scopes` 为 API 令牌的作用域，只返回其可读的实体
+    pub async fn pull(
+        &self,
+        user_id: i32,
+        scopes: Option<&[String]>,
+        query: &PullQuery,
+    ) -> Result<PullResponse, ServiceError> {
+        let since = parse_token(query.since.as_deref())?;
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let upto = self.repo.latest_seq().await?;
+        if since > upto {
+            return Err(invalid_token());
+        }
+        let entities: Vec<&str> = ENTITIES
+            .iter()
+            .filter(|(_, resource)| scopes.is_none_or(|s| scope_allows(s, resource, false)))
+            .map(|(entity, _)| *entity)
+            .collect();
+
+        let mut rows = self
+            .repo
+            .changes(user_id, since, upto, &entities, limit + 1)
+            .await?;
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+        // 未取完时停在本页最后一条，否则直接推进到读取时的最新序号
+        let token = match rows.last() {
+            Some(last) if has_more => last.seq,
+            _ => upto,
+        };
+
+        let mut resp = PullResponse {
+            token: token.to_string(),
+            has_more,
+            ..Default::default()
+        };
+        let ids = |entity: &str| -> Vec<i32> {
+            rows.iter()
+                .filter(|r| r.entity == entity && !r.deleted)
+                .map(|r| r.entity_id)
+                .collect()
+        };
+        resp.mems = self.repo.mems(user_id, &ids("mem")).await?;
+        resp.chunks = self.repo.chunks(user_id, &ids("chunk")).await?;
+        resp.tags = self.repo.tags(user_id, &ids("tag")).await?;
+        resp.tasks = self.repo.tasks(&ids("task")).await?;
+        resp.time_windows = self.repo.time_windows(&ids("time_window")).await?;
+        for id in ids("bookmark") {
+            if let Some(b) = self.bookmark.by_id(user_id, id).await? {
+                resp.bookmarks.push(BookmarkResponse::from(b));
+            }
+        }
+
+        // 物理删除的行，以及读取时已不存在（软删除）的行都下发为墓碑
+        let present: HashSet<(&str, i32)> = resp
+            .mems
+            .iter()
+            .map(|m| ("mem", m.id))
+            .chain(resp.chunks.iter().map(|c| ("chunk", c.id)))
+            .chain(resp.tags.iter().map(|t| ("tag", t.id)))
+            .chain(resp.tasks.iter().map(|t| ("task", t.id)))
+            .chain(resp.time_windows.iter().map(|w| ("time_window", w.id)))
+            .chain(resp.bookmarks.iter().map(|b| ("bookmark", b.id)))
+            .collect();
+        resp.tombstones = rows
+            .iter()
+            .filter(|r| !present.contains(&(r.entity.as_str(), r.entity_id)))
+            .map(|r: &ChangeRow| Tombstone {
+                entity: r.entity.clone(),
+                id: r.entity_id,
+            })
+            .collect();
+        Ok(resp)
+    }
+
+    /// 应用客户端离线期间的变更。
+    ///
+    /// 非复习操作按提交顺序执行；复习随后按 `reviewed_at`（相同时按提交顺序）重放，
+    /// 保证同一 mem 的调度与客户端记录的时间线一致。冲突一律以服务端为准：
+    /// `since` 之后服务端已改动的实体不被覆盖，更早于服务端最近一次复习的复习被跳过。
+    /// 同一 `mutation_id` 重复推送时直接返回首次的结果。
+    pub async fn push(
+        &self,
+        user_id: i32,
+        scopes: Option<&[String]>,
+        req: &PushRequest,
+    ) -> Result<PushResponse, ServiceError> {
+        let since = parse_token(req.since.as_deref())?;
+        if req.mutations.len() > MAX_MUTATIONS {
+            return Err(ServiceError::InvalidInput(format!(
+                "单次最多推送 {MAX_MUTATIONS} 个变更"
+            )));
+        }
+
+        let (mut reviews, others): (Vec<usize>, Vec<usize>) = (0..req.mutations.len())
+            .partition(|&i| matches!(req.mutations[i].op, Mutation::Review { .. }));
+        reviews.sort_by_key(|&i| match req.mutations[i].op {
+            Mutation::Review { reviewed_at, .. } => reviewed_at,
+            _ => DateTime::<Utc>::MIN_UTC,
+        });
+
+        let mut batch = Batch {
+            since,
+            created: HashMap::new(),
+            touched: HashSet::new(),
+        };
+        let mut results = vec![None; req.mutations.len()];
+        for i in others.into_iter().chain(reviews) {
+            let m = &req.mutations[i];
+            results[i] = Some(self.apply_once(user_id, scopes, &mut batch, m).await?);
+        }
+        Ok(PushResponse {
+            results: results.into_iter().flatten().collect(),
+        })
+    }
+
+    async fn apply_once(
+        &self,
+        user_id: i32,
+        scopes: Option<&[String]>,
+        batch: &mut Batch,
+        m: &PushMutation,
+    ) -> Result<MutationResult, ServiceError> {
+        if m.mutation_id.trim().is_empty() || m.mutation_id.len() > 128 {
+            return Ok(result(m, rejected("mutation_id 不能为空且不超过 128 字节")));
+        }
+        if let Some(saved) = self.repo.find_mutation(user_id, &m.mutation_id).await?
+            && let Ok(saved) = serde_json::from_str::<MutationResult>(&saved)
+        {
+            batch.remember(&m.op, &saved);
+            return Ok(saved);
+        }
+
+        let resource = match m.op {
+            Mutation::CreateTask { .. }
+            | Mutation::UpdateTask { .. }
+            | Mutation::DeleteTask { .. } => "tasks",
+            _ => "mem",
+        };
+        let outcome = if scopes.is_some_and(|s| !scope_allows(s, resource, true)) {
+            rejected(format!("令牌缺少 {resource}:write 作用域"))
+        } else {
+            self.apply(user_id, batch, &m.op).await?
+        };
+        let res = result(m, outcome);
+        batch.remember(&m.op, &res);
+        let json =
+            serde_json::to_string(&res).map_err(|e| ServiceError::Internal(e.to_string()))?;
+        self.repo
+            .save_mutation(user_id, &m.mutation_id, &json)
+            .await?;
+        Ok(res)
+    }
+
+    /// 执行单个变更。业务上的失败以 `rejected` / `conflict` 返回，只有数据库等内部错误返回 `Err`
+    async fn apply(
+        &self,
+        user_id: i32,
+        batch: &Batch,
+        op: &Mutation,
+    ) -> Result<Outcome, ServiceError> {
+        match op {
+            Mutation::Review {
+                mem,
+                rating,
+                reviewed_at,
+            } => {
+                let Some(id) = batch.resolve(mem) else {
+                    return Ok(unknown_ref());
+                };
+                if !(1..=4).contains(rating) {
+                    return Ok(rejected("rating 必须为 1-4"));
+                }
+                if *reviewed_at > Utc::now() + Duration::seconds(CLOCK_SKEW_SECS) {
+                    return Ok(rejected("复习时间晚于服务器时间"));
+                }
+                let Some((_, _, last)) = self.repo.mem_head(user_id, id).await? else {
+                    return Ok(rejected("mem 不存在"));
+                };
+                let last = last
+                    .as_deref()
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+                if last.is_some_and(|last| *reviewed_at < last) {
+                    return Ok(conflict("服务端已有更晚的复习记录"));
+                }
+                mem_outcome(
+                    self.mem.review_at(user_id, id, *rating, *reviewed_at).await,
+                    id,
+                )
+            }
+            Mutation::CreateMem {
+                client_id,
+                cue_content,
+                target_content,
+            } => {
+                if batch.created.contains_key(client_id) {
+                    return Ok(rejected("client_id 在本批次中重复"));
+                }
+                let req = CreateMemRequest {
+                    cue_content: cue_content.clone(),
+                    target_content: target_content.clone(),
+                    prerequisites: Vec::new(),
+                };
+                let id = self.mem.create(user_id, req).await?;
+                Ok(applied(id))
+            }
+            Mutation::EditMem {
+                mem,
+                cue_content,
+                target_content,
+            } => {
+                let Some(id) = batch.resolve(mem) else {
+                    return Ok(unknown_ref());
+                };
+                let Some((cue, target, _)) = self.repo.mem_head(user_id, id).await? else {
+                    return Ok(rejected("mem 不存在"));
+                };
+                if !batch.touched.contains(&("mem", id))
+                    && (self.changed_since(batch, "chunk", cue).await?
+                        || self.changed_since(batch, "chunk", target).await?)
+                {
+                    return Ok(conflict("服务端在此之后修改过该 mem 的内容"));
+                }
+                let req = EditMemRequest {
+                    cue_content: cue_content.clone(),
+                    target_content: target_content.clone(),
+                };
+                mem_outcome(self.mem.edit(user_id, id, req).await, id)
+            }
+            Mutation::DeleteMem { mem } => {
+                let Some(id) = batch.resolve(mem) else {
+                    return Ok(unknown_ref());
+                };
+                if self.repo.mem_head(user_id, id).await?.is_none() {
+                    return Ok(rejected("mem 不存在"));
+                }
+                if self.changed_since(batch, "mem", id).await? {
+                    return Ok(conflict("服务端在此之后修改过该 mem"));
+                }
+                mem_outcome(self.mem.delete(user_id, id).await, id)
+            }
+            Mutation::CreateTask { client_id, task } => {
+                if batch.created.contains_key(client_id) {
+                    return Ok(rejected("client_id 在本批次中重复"));
+                }
+                task_outcome(self.task.create(user_id, task.clone()).await.map(|t| t.id))
+            }
+            // 任务为共享数据，与在线接口一样不按归属过滤
+            Mutation::UpdateTask { task, patch } => {
+                let Some(id) = batch.resolve(task) else {
+                    return Ok(unknown_ref());
+                };
+                if self.task.by_id(id).await?.is_none() {
+                    return Ok(rejected("任务不存在"));
+                }
+                if self.changed_since(batch, "task", id).await? {
+                    return Ok(conflict("服务端在此之后修改过该任务"));
+                }
+                task_outcome(
+                    self.task
+                        .update(user_id, id, patch.clone())
+                        .await
+                        .map(|t| t.id),
+                )
+            }
+            Mutation::DeleteTask { task } => {
+                let Some(id) = batch.resolve(task) else {
+                    return Ok(unknown_ref());
+                };
+                if self.task.by_id(id).await?.is_none() {
+                    return Ok(rejected("任务不存在"));
+                }
+                if self.changed_since(batch, "task", id).await? {
+                    return Ok(conflict("服务端在此之后修改过该任务"));
+                }
+                task_outcome(self.task.delete(user_id, id).await.map(|_| id))
+            }
+        }
+    }
+
+    /// 实体在 `since` 之后是否被改动过（本批次自己的改动不算）
+    async fn changed_since(
+        &self,
+        batch: &Batch,
+        entity: &'static str,
+        id: i32,
+    ) -> Result<bool, ServiceError> {
+        if batch.touched.contains(&(entity, id)) {
+            return Ok(false);
+        }
+        Ok(self.repo.seq_of(entity, id).await? > batch.since)
+    }
+}
+
+/// 一次推送中的状态：客户端 id 映射与本批次已改动的实体
+struct Batch {
+    since: i64,
+    created: HashMap<String, i32>,
+    touched: HashSet<(&'static str, i32)>,
+}
+
+impl Batch {
+    fn resolve(&self, r: &EntityRef) -> Option<i32> {
+        match r {
+            EntityRef::Id(id) => Some(*id),
+            EntityRef::Client(client_id) => self.created.get(client_id).copied(),
+        }
+    }
+
+    fn remember(&mut self, op: &Mutation, res: &MutationResult) {
+        let (Some(id), MutationStatus::Applied) = (res.id, res.status) else {
+            return;
+        };
+        match op {
+            Mutation::CreateMem { client_id, .. } => {
+                self.created.insert(client_id.clone(), id);
+                self.touched.insert(("mem", id));
+            }
+            Mutation::CreateTask { client_id, .. } => {
+                self.created.insert(client_id.clone(), id);
+                self.touched.insert(("task", id));
+            }
+            Mutation::EditMem { .. } | Mutation::DeleteMem { .. } => {
+                self.touched.insert(("mem", id));
+            }
+            Mutation::UpdateTask { .. } | Mutation::DeleteTask { .. } => {
+                self.touched.insert(("task", id));
+            }
+            Mutation::Review { .. } => {}
+        }
+    }
+}
+
+struct Outcome {
+    status: MutationStatus,
+    id: Option<i32>,
+    message: Option<String>,
+}
+
+fn applied(id: i32) -> Outcome {
+    Outcome {
+        status: MutationStatus::Applied,
+        id: Some(id),
+        message: None,
+    }
+}
+
+fn conflict(message: impl Into<String>) -> Outcome {
+    Outcome {
+        status: MutationStatus::Conflict,
+        id: None,
+        message: Some(message.into()),
+    }
+}
+
+fn rejected(message: impl Into<String>) -> Outcome {
+    Outcome {
+        status: MutationStatus::Rejected,
+        id: None,
+        message: Some(message.into()),
+    }
+}
+
+fn unknown_ref() -> Outcome {
+    rejected("引用了本批次中不存在或未成功创建的 client_id")
+}
+
+fn result(m: &PushMutation, outcome: Outcome) -> MutationResult {
+    MutationResult {
+        mutation_id: m.mutation_id.clone(),
+        status: outcome.status,
+        id: outcome.id,
+        message: outcome.message,
+    }
+}
+
+fn mem_outcome<T>(r: Result<T, AppError>, id: i32) -> Result<Outcome, ServiceError> {
+    match r {
+        Ok(_) => Ok(applied(id)),
+        Err(AppError::NotFound) => Ok(rejected("mem 不存在")),
+        Err(e @ AppError::PreconditionFailed { .. }) => Ok(rejected(e.to_string())),
+        Err(AppError::Db(e)) => Err(e.into()),
+    }
+}
+
+fn task_outcome(r: Result<i32, ServiceError>) -> Result<Outcome, ServiceError> {
+    match r {
+        Ok(id) => Ok(applied(id)),
+        Err(e @ (ServiceError::Db(_) | ServiceError::Internal(_))) => Err(e),
+        Err(e) => Ok(rejected(e.to_string())),
+    }
+}
+
+/// 令牌为十进制的变更序号，空表示从头同步
+fn parse_token(token: Option<&str>) -> Result<i64, ServiceError> {
+    match token.map(str::trim).filter(|t| !t.is_empty()) {
+        None => Ok(0),
+        Some(t) => t
+            .parse::<i64>()
+            .ok()
+            .filter(|seq| *seq >= 0)
+            .ok_or_else(invalid_token),
+    }
+}
+
+fn invalid_token() -> ServiceError {
+    ServiceError::InvalidInput("无效的同步令牌，请清空本地数据后全量同步".into())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::modules::job::JobService;
+    use crate::modules::mem::MemRepo;
+    use crate::modules::webhook::Webhooks;
+    use serde_json::json;
+
+    struct Fixture {
+        sync: SyncService,
+        mem: MemService,
+        task: TaskService,
+        pool: Arc<SqlitePool>,
+        alice: i32,
+        bob: i32,
+    }
+
+    async fn setup() -> Fixture {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let mut users = Vec::new();
+        for name in ["alice", "bob"] {
+            let id: i32 = sqlx::query_scalar(
+                "INSERT INTO user (name, password_hash) VALUES (?, 'x') RETURNING id",
+            )
+            .bind(name)
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+            users.push(id);
+        }
+        let jobs = JobService::new(pool.clone());
+        let hooks = Webhooks::new(pool.clone(), jobs.clone());
+        let changes = crate::modules::events::ChangeFeed::default();
+        let mem = MemService::new(
+            Arc::new(MemRepo::new(pool.clone())),
+            pool.clone(),
+            jobs,
+            hooks.clone(),
+            changes.clone(),
+        );
+        let task = TaskService::new(pool.clone(), hooks.clone(), changes.clone());
+        let bookmark = BookmarkService::new(pool.clone(), hooks, changes);
+        Fixture {
+            sync: SyncService::new(pool.clone(), mem.clone(), task.clone(), bookmark),
+            mem,
+            task,
+            pool,
+            alice: users[0],
+            bob: users[1],
+        }
+    }
+
+    fn since(token: &str) -> PullQuery {
+        PullQuery {
+            since: Some(token.to_string()),
+            limit: None,
+        }
+    }
+
+    fn push_req(since: &str, mutations: serde_json::Value) -> PushRequest {
+        PushRequest {
+            since: Some(since.to_string()),
+            mutations: serde_json::from_value(mutations).unwrap(),
+        }
+    }
+
+    async fn create_mem(f: &Fixture, user_id: i32, cue: &str) -> i32 {
+        let req = CreateMemRequest {
+            cue_content: cue.into(),
+            target_content: "答".into(),
+            prerequisites: Vec::new(),
+        };
+        f.mem.create(user_id, req).await.unwrap()
+    }
+
+    fn statuses(resp: &PushResponse) -> Vec<MutationStatus> {
+        resp.results.iter().map(|r| r.status).collect()
+    }
+
+    #[tokio::test]
+    async fn pull_returns_changes_after_token_with_tombstones() {
+        let f = setup().await;
+        let id = create_mem(&f, f.alice, "问").await;
+        create_mem(&f, f.bob, "别人的").await;
+
+        let full = f.sync.pull(f.alice, None, &since("")).await.unwrap();
+        assert_eq!(full.mems.len(), 1);
+        assert_eq!(full.mems[0].id, id);
+        assert_eq!(full.chunks.len(), 2);
+        assert!(!full.has_more && full.tombstones.is_empty());
+
+        // 无变化时令牌不动、内容为空
+        let same = f
+            .sync
+            .pull(f.alice, None, &since(&full.token))
+            .await
+            .unwrap();
+        assert_eq!(same.token, full.token);
+        assert!(same.mems.is_empty() && same.chunks.is_empty());
+
+        f.mem.delete(f.alice, id).await.unwrap();
+        let delta = f
+            .sync
+            .pull(f.alice, None, &since(&full.token))
+            .await
+            .unwrap();
+        assert!(delta.mems.is_empty());
+        assert_eq!(
+            delta.tombstones,
+            vec![Tombstone {
+                entity: "mem".into(),
+                id
+            }]
+        );
+
+        // 令牌只能读到作用域内的实体
+        let scopes = vec!["tasks:read".to_string()];
+        let scoped = f
+            .sync
+            .pull(f.alice, Some(&scopes), &since(""))
+            .await
+            .unwrap();
+        assert!(scoped.mems.is_empty() && scoped.tombstones.is_empty());
+
+        assert!(matches!(
+            f.sync.pull(f.alice, None, &since("abc")).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn pull_pages_until_caught_up() {
+        let f = setup().await;
+        for i in 0..3 {
+            create_mem(&f, f.alice, &format!("问{i}")).await;
+        }
+        // 3 个 mem 共 9 条变更（各含两个 chunk）
+        let mut token = String::new();
+        let mut pages = 0;
+        let mut mems = 0;
+        loop {
+            let query = PullQuery {
+                since: Some(token.clone()),
+                limit: Some(4),
+            };
+            let page = f.sync.pull(f.alice, None, &query).await.unwrap();
+            pages += 1;
+            mems += page.mems.len();
+            token = page.token;
+            if !page.has_more {
+                break;
+            }
+        }
+        assert_eq!((pages, mems), (3, 3));
+    }
+
+    #[tokio::test]
+    async fn push_replays_reviews_in_client_time_order() {
+        let f = setup().await;
+        let token = f.sync.pull(f.alice, None, &since("")).await.unwrap().token;
+        let req = push_req(
+            &token,
+            json!([
+                { "mutation_id": "r2", "op": "review", "mem": "c1", "rating": 3,
+                  "reviewed_at": "2024-05-03T08:00:00Z" },
+                { "mutation_id": "r1", "op": "review", "mem": "c1", "rating": 1,
+                  "reviewed_at": "2024-05-01T08:00:00Z" },
+                { "mutation_id": "m1", "op": "create_mem", "client_id": "c1",
+                  "cue_content": "问", "target_content": "答" },
+            ]),
+        );
+        let resp = f.sync.push(f.alice, None, &req).await.unwrap();
+        assert_eq!(statuses(&resp), vec![MutationStatus::Applied; 3]);
+        let id = resp.results[2].id.unwrap();
+        assert_eq!(resp.results[0].id, Some(id));
+
+        let log: Vec<(String, i32)> =
+            sqlx::query_as("SELECT review_time, rating FROM revlog WHERE mem_id = ? ORDER BY id")
+                .bind(id)
+                .fetch_all(&*f.pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            log,
+            vec![
+                ("2024-05-01T08:00:00Z".to_string(), 1),
+                ("2024-05-03T08:00:00Z".to_string(), 3)
+            ]
+        );
+
+        // 早于服务端最近一次复习的记录被跳过；超前太多的时间被拒绝
+        let req = push_req(
+            &token,
+            json!([
+                { "mutation_id": "r3", "op": "review", "mem": id, "rating": 4,
+                  "reviewed_at": "2024-05-02T08:00:00Z" },
+                { "mutation_id": "r4", "op": "review", "mem": id, "rating": 4,
+                  "reviewed_at": "2999-01-01T00:00:00Z" },
+            ]),
+        );
+        let resp = f.sync.push(f.alice, None, &req).await.unwrap();
+        assert_eq!(
+            statuses(&resp),
+            vec![MutationStatus::Conflict, MutationStatus::Rejected]
+        );
+    }
+
+    #[tokio::test]
+    async fn push_detects_conflicts_and_deduplicates() {
+        let f = setup().await;
+        let id = create_mem(&f, f.alice, "问").await;
+        let task = f
+            .task
+            .create(
+                f.alice,
+                serde_json::from_value(json!({ "title": "任务" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        let token = f.sync.pull(f.alice, None, &since("")).await.unwrap().token;
+
+        // 另一设备在令牌之后改了内容
+        let edit = EditMemRequest {
+            cue_content: "服务端".into(),
+            target_content: "答".into(),
+        };
+        f.mem.edit(f.alice, id, edit).await.unwrap();
+
+        let req = push_req(
+            &token,
+            json!([
+                { "mutation_id": "e1", "op": "edit_mem", "mem": id,
+                  "cue_content": "客户端", "target_content": "答" },
+                { "mutation_id": "t1", "op": "update_task", "task": task.id,
+                  "patch": { "title": "改名" } },
+                { "mutation_id": "t2", "op": "delete_task", "task": task.id },
+                { "mutation_id": "x1", "op": "delete_mem", "mem": "nope" },
+            ]),
+        );
+        let resp = f.sync.push(f.alice, None, &req).await.unwrap();
+        assert_eq!(
+            statuses(&resp),
+            vec![
+                MutationStatus::Conflict,
+                MutationStatus::Applied,
+                // 本批次自己的修改不算冲突
+                MutationStatus::Applied,
+                MutationStatus::Rejected,
+            ]
+        );
+        // 与在线接口一致：未完成的任务删除时先归档
+        let archived = f.task.by_id(task.id).await.unwrap().unwrap();
+        assert_eq!(archived.status, crate::modules::task::TaskStatus::Archived);
+
+        // 重发返回首次结果，不再执行
+        let again = f.sync.push(f.alice, None, &req).await.unwrap();
+        assert_eq!(again.results, resp.results);
+
+        // 缺少写作用域
+        let scopes = vec!["mem:read".to_string()];
+        let req = push_req(
+            &token,
+            json!([{ "mutation_id": "m9", "op": "create_mem", "client_id": "c",
+                     "cue_content": "问", "target_content": "答" }]),
+        );
+        let resp = f.sync.push(f.alice, Some(&scopes), &req).await.unwrap();
+        assert_eq!(statuses(&resp), vec![MutationStatus::Rejected]);
+    }
+
+    #[tokio::test]
+    async fn push_writes_shared_tasks_like_the_rest_api() {
+        let f = setup().await;
+        let create = |title: &str| serde_json::from_value(json!({ "title": title })).unwrap();
+        let kept = f.task.create(f.alice, create("保留")).await.unwrap();
+        let gone = f.task.create(f.alice, create("删除")).await.unwrap();
+        let token = f.sync.pull(f.bob, None, &since("")).await.unwrap().token;
+
+        // 只读令牌不能改任务
+        let scopes = vec!["tasks:read".to_string()];
+        let req = push_req(
+            &token,
+            json!([{ "mutation_id": "r1", "op": "delete_task", "task": gone.id }]),
+        );
+        let resp = f.sync.push(f.bob, Some(&scopes), &req).await.unwrap();
+        assert_eq!(statuses(&resp), vec![MutationStatus::Rejected]);
+
+        // 他人创建的任务同样可改可删
+        let req = push_req(
+            &token,
+            json!([
+                { "mutation_id": "b1", "op": "update_task", "task": kept.id,
+                  "patch": { "title": "bob 改名" } },
+                { "mutation_id": "b2", "op": "delete_task", "task": gone.id },
+            ]),
+        );
+        let resp = f.sync.push(f.bob, None, &req).await.unwrap();
+        assert_eq!(
+            statuses(&resp),
+            vec![MutationStatus::Applied, MutationStatus::Applied]
+        );
+        let kept = f.task.by_id(kept.id).await.unwrap().unwrap();
+        assert_eq!(kept.title, "bob 改名");
+        let gone = f.task.by_id(gone.id).await.unwrap().unwrap();
+        assert_eq!(gone.status, crate::modules::task::TaskStatus::Archived);
+    }
+}
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::model::{Task, TaskStatus, TimeWindow};
 
 /// 任务创建请求体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateTaskRequest {
     /// 任务标题（必需）
     pub title: String,
@@ -22,14 +23,14 @@ pub struct CreateTaskRequest {
 }
 
 /// 快速创建任务请求体（仅标题）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct QuickCreateTaskRequest {
     /// 任务标题（必需）
     pub title: String,
 }
 
 /// 任务更新请求体（部分更新）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateTaskRequest {
     /// 任务标题（可选）
     #[serde(default)]
@@ -53,7 +54,7 @@ pub struct UpdateTaskRequest {
 }
 
 /// 任务详情响应（包含依赖和时间窗口信息）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TaskDetailResponse {
     /// 任务基本信息
     pub task: Task,
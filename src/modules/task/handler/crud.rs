@@ -1,60 +1,89 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Json},
 };
 
-use super::super::dto::{CreateTaskRequest, QuickCreateTaskRequest, UpdateTaskRequest};
+use super::super::dto::{
+    CreateTaskRequest, QuickCreateTaskRequest, TaskDetailResponse, UpdateTaskRequest,
+};
 use super::super::response::TaskResponse;
+use crate::auth::Claims;
 use crate::error;
+use crate::etag::{self, IfMatch};
+use crate::openapi::PreconditionFailed;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "任务列表（未归档）",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    match state
-        .task
-        .list(pagination.limit(), pagination.offset())
-        .await
-    {
-        Ok((tasks, total)) => {
+    match state.task.list(&pagination.window()).await {
+        Ok((tasks, total, next)) => {
             let items: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
-            Json(PaginatedResponse::new(items, total, &pagination)).into_response()
+            Json(PaginatedResponse::new(items, total, &pagination).with_cursor(next))
+                .into_response()
         }
         Err(e) => error::internal(e, "获取任务列表"),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/all",
+    summary = "全部任务",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_all_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    match state
-        .task
-        .list_all(pagination.limit(), pagination.offset())
-        .await
-    {
-        Ok((tasks, total)) => {
+    match state.task.list_all(&pagination.window()).await {
+        Ok((tasks, total, next)) => {
             let items: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
-            Json(PaginatedResponse::new(items, total, &pagination)).into_response()
+            Json(PaginatedResponse::new(items, total, &pagination).with_cursor(next))
+                .into_response()
         }
         Err(e) => error::internal(e, "获取全部任务"),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取任务",
+    responses((status = 200, description = "成功", body = TaskResponse, headers(("ETag" = String, description = "资源当前版本"))))
+)]
 pub async fn get_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    match state.task.by_id(id).await {
-        Ok(Some(task)) => Json(TaskResponse::from(task)).into_response(),
+    match state.task.versioned(id).await {
+        Ok(Some(task)) => etag::with(
+            &etag::version(task.version),
+            Json(TaskResponse::from(task.row)),
+        ),
         Ok(None) => error::not_found("任务不存在"),
         Err(e) => error::internal(e, "获取任务"),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/{id}/detail",
+    summary = "任务详情",
+    responses((status = 200, description = "成功", body = TaskDetailResponse))
+)]
 pub async fn get_task_detail_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
@@ -66,42 +95,83 @@ pub async fn get_task_detail_handler(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建任务",
+    request_body = CreateTaskRequest,
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn create_task_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<CreateTaskRequest>,
 ) -> impl IntoResponse {
-    match state.task.create(payload).await {
+    match state.task.create(claims.sub, payload).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/quick",
+    summary = "快速创建任务",
+    request_body = QuickCreateTaskRequest,
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn quick_create_task_handler(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<QuickCreateTaskRequest>,
 ) -> impl IntoResponse {
-    match state.task.quick_create(payload).await {
+    match state.task.quick_create(claims.sub, payload).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "更新任务",
+    params(IfMatch),
+    request_body = UpdateTaskRequest,
+    responses((status = 200, description = "成功", body = TaskResponse, headers(("ETag" = String, description = "资源当前版本"))), PreconditionFailed)
+)]
 pub async fn update_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateTaskRequest>,
 ) -> impl IntoResponse {
-    match state.task.update(id, payload).await {
-        Ok(task) => Json(TaskResponse::from(task)).into_response(),
+    let if_match = IfMatch::from_headers(&headers);
+    match state
+        .task
+        .update_if(claims.sub, id, payload, &if_match)
+        .await
+    {
+        Ok(task) => etag::with(
+            &etag::version(task.version),
+            Json(TaskResponse::from(task.row)),
+        ),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除任务",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    match state.task.delete(id).await {
+    match state.task.delete(claims.sub, id).await {
         Ok(rows) if rows > 0 => StatusCode::NO_CONTENT.into_response(),
         Ok(_) => error::not_found("任务不存在"),
         Err(e) => e.into_response(),
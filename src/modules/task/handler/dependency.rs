@@ -5,18 +5,21 @@ use axum::{
 };
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 use super::super::model::TaskStatus;
 use super::super::response::MessageResponse;
 use crate::error;
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TreeQuery {
     pub status: Option<TaskStatus>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)]
 pub struct CalendarQuery {
     pub start: Option<DateTime<Utc>>,
@@ -24,18 +27,26 @@ pub struct CalendarQuery {
     pub status: Option<TaskStatus>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)]
 pub struct DagQuery {
     pub task_id: Option<i32>,
     pub depth: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DependencyRequest {
     pub depends_on_task_id: i32,
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/dependencies",
+    summary = "添加依赖",
+    request_body = DependencyRequest,
+    responses((status = 200, description = "成功", body = MessageResponse))
+)]
 pub async fn add_dependency_handler(
     Path(task_id): Path<i32>,
     State(state): State<AppState>,
@@ -55,6 +66,12 @@ pub async fn add_dependency_handler(
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/{id}/dependencies/{depends_on_task_id}",
+    summary = "移除依赖",
+    responses((status = 200, description = "成功", body = MessageResponse))
+)]
 pub async fn remove_dependency_handler(
     Path((task_id, depends_on_task_id)): Path<(i32, i32)>,
     State(state): State<AppState>,
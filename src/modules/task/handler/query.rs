@@ -6,13 +6,20 @@ use std::collections::HashMap;
 use std::pin::Pin;
 
 use super::super::model::Task;
-use super::super::response::{CalendarEvent, StatsResponse, TaskResponse, TreeNode};
+use super::super::response::{CalendarEvent, DagView, StatsResponse, TaskResponse, TreeNode};
 use super::super::service::TaskService;
 use super::dependency::{CalendarQuery, DagQuery, TreeQuery};
 use crate::error;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
+#[utoipa::path(
+    get,
+    path = "/tree",
+    summary = "任务树",
+    params(TreeQuery),
+    responses((status = 200, description = "成功", body = Vec<TreeNode>))
+)]
 pub async fn get_tree_handler(
     Query(query): Query<TreeQuery>,
     State(state): State<AppState>,
@@ -35,7 +42,7 @@ pub async fn get_tree_handler(
 
     let mut nodes = Vec::new();
     for task in filtered {
-        if let Some(node) = build_tree_node(&svc, task).await {
+        if let Some(node) = build_tree_node(svc, task).await {
             nodes.push(node);
         }
     }
@@ -65,6 +72,13 @@ fn build_tree_node<'a>(
     })
 }
 
+#[utoipa::path(
+    get,
+    path = "/calendar",
+    summary = "日历视图",
+    params(CalendarQuery),
+    responses((status = 200, description = "成功", body = Vec<CalendarEvent>))
+)]
 pub async fn get_calendar_handler(
     Query(query): Query<CalendarQuery>,
     State(state): State<AppState>,
@@ -90,6 +104,13 @@ pub async fn get_calendar_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/dag",
+    summary = "依赖图",
+    params(DagQuery),
+    responses((status = 200, description = "成功", body = DagView))
+)]
 pub async fn get_dag_handler(
     Query(query): Query<DagQuery>,
     State(state): State<AppState>,
@@ -102,6 +123,12 @@ pub async fn get_dag_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats",
+    summary = "各状态任务数",
+    responses((status = 200, description = "成功", body = StatsResponse))
+)]
 pub async fn get_stats_handler(State(state): State<AppState>) -> impl IntoResponse {
     let svc = &state.task;
     match svc.stats().await {
@@ -116,6 +143,13 @@ pub async fn get_stats_handler(State(state): State<AppState>) -> impl IntoRespon
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/search",
+    summary = "搜索任务",
+    params(("q" = String, Query), Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn search_tasks_handler(
     Query(mut params): Query<HashMap<String, String>>,
     State(state): State<AppState>,
@@ -133,6 +167,7 @@ pub async fn search_tasks_handler(
             .get("page_size")
             .and_then(|s| s.parse().ok())
             .unwrap_or(20),
+        cursor: None,
     };
 
     let svc = &state.task;
@@ -1,54 +1,91 @@
 use axum::{
+    Extension,
     extract::{Path, Query, State},
     response::{IntoResponse, Json},
 };
 
 use super::super::model::TaskStatus;
 use super::super::response::TaskResponse;
+use crate::auth::Claims;
 use crate::error;
 use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
+#[utoipa::path(
+    post,
+    path = "/{id}/complete",
+    summary = "完成任务",
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn complete_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    match state.task.complete(id).await {
+    match state.task.complete(claims.sub, id).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/activate",
+    summary = "激活任务",
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn activate_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    match state.task.activate(id).await {
+    match state.task.activate(claims.sub, id).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/archive",
+    summary = "归档任务",
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn archive_task_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    match state.task.archive(id).await {
+    match state.task.archive(claims.sub, id).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/{id}/move-to-backlog",
+    summary = "移回待办",
+    responses((status = 200, description = "成功", body = TaskResponse))
+)]
 pub async fn move_to_backlog_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
 ) -> impl IntoResponse {
-    match state.task.move_to_backlog(id).await {
+    match state.task.move_to_backlog(claims.sub, id).await {
         Ok(task) => Json(TaskResponse::from(task)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/backlog",
+    summary = "待办任务",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_backlog_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
@@ -66,6 +103,13 @@ pub async fn get_backlog_tasks_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/active",
+    summary = "进行中任务",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_active_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
@@ -83,6 +127,13 @@ pub async fn get_active_tasks_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/completed",
+    summary = "已完成任务",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_completed_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
@@ -104,6 +155,13 @@ pub async fn get_completed_tasks_handler(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/status/archived",
+    summary = "已归档任务",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TaskResponse>))
+)]
 pub async fn get_archived_tasks_handler(
     Query(pagination): Query<Pagination>,
     State(state): State<AppState>,
@@ -2,9 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Decode, FromRow, Sqlite, Type, sqlite::SqliteValueRef};
 use std::str::FromStr;
+use utoipa::ToSchema;
 
 /// 任务状态枚举
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
     Backlog,
@@ -87,7 +88,7 @@ impl Type<Sqlite> for TaskStatus {
 }
 
 /// 任务实体 - 根据new_task.md设计
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Task {
     /// 任务ID
     pub id: i32,
@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
-use sqlx::Row;
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
 
 use super::super::dto::{CreateTaskRequest, QuickCreateTaskRequest, UpdateTaskRequest};
 use super::super::model::{Task, TaskStatus};
 use super::TaskRepository;
+use crate::etag::{self, Versioned};
+use crate::pagination::{Cursor, Window};
 
 impl TaskRepository {
     pub async fn find_all_paginated(
@@ -11,39 +13,41 @@ impl TaskRepository {
         limit: i64,
         offset: i64,
     ) -> Result<(Vec<Task>, i64), sqlx::Error> {
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task")
-            .fetch_one(&*self.db)
-            .await?;
-        let items = sqlx::query_as::<_, Task>(
-            "SELECT id, title, description, parent_task_id, status, completed_at,
-            effort_estimate_minutes, created_at, updated_at
-            FROM task ORDER BY created_at DESC LIMIT ? OFFSET ?",
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&*self.db)
-        .await?;
+        let window = Window::Offset { limit, offset };
+        let (items, total, _) = self.find_page(&window, true).await?;
         Ok((items, total))
     }
 
-    pub async fn find_all_excluding_archived_paginated(
+    /// 按 `(created_at, id)` 倒序取一页任务，游标模式附下一页游标
+    pub async fn find_page(
         &self,
-        limit: i64,
-        offset: i64,
-    ) -> Result<(Vec<Task>, i64), sqlx::Error> {
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status != 'archived'")
-            .fetch_one(&*self.db)
-            .await?;
-        let items = sqlx::query_as::<_, Task>(
+        window: &Window,
+        include_archived: bool,
+    ) -> Result<(Vec<Task>, i64, Option<Cursor>), sqlx::Error> {
+        let filter = if include_archived {
+            "deleted_at IS NULL"
+        } else {
+            "status != 'archived' AND deleted_at IS NULL"
+        };
+        let total: i64 = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+            "SELECT COUNT(*) FROM task WHERE {filter}"
+        )))
+        .fetch_one(&*self.db)
+        .await?;
+
+        let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
             "SELECT id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at
-            FROM task WHERE status != 'archived' ORDER BY created_at DESC LIMIT ? OFFSET ?",
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&*self.db)
-        .await?;
-        Ok((items, total))
+            FROM task WHERE {filter}"
+        ));
+        window.push_after(&mut qb, "created_at", "id", true);
+        qb.push(" ORDER BY created_at DESC, id DESC");
+        window.push_limit(&mut qb);
+
+        let rows = qb.build().fetch_all(&*self.db).await?;
+        let (rows, next) = window.finish(rows, "created_at", "id")?;
+        let items = rows.iter().map(Task::from_row).collect::<Result<_, _>>()?;
+        Ok((items, total, next))
     }
 
     pub async fn find_by_id(&self, id: i32) -> Result<Option<Task>, sqlx::Error> {
@@ -51,7 +55,20 @@ impl TaskRepository {
             "SELECT id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at
             FROM task
-            WHERE id = ?",
+            WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&*self.db)
+        .await
+    }
+
+    /// 连同行版本号读取，供 ETag 使用
+    pub async fn find_versioned(&self, id: i32) -> Result<Option<Versioned<Task>>, sqlx::Error> {
+        sqlx::query_as::<_, Versioned<Task>>(
+            "SELECT id, title, description, parent_task_id, status, completed_at,
+            effort_estimate_minutes, created_at, updated_at, version
+            FROM task
+            WHERE id = ? AND deleted_at IS NULL",
         )
         .bind(id)
         .fetch_optional(&*self.db)
@@ -124,7 +141,13 @@ impl TaskRepository {
         })
     }
 
-    pub async fn update(&self, id: i32, request: UpdateTaskRequest) -> Result<Task, sqlx::Error> {
+    /// 更新任务；`expected` 为可接受的行版本号，版本不符时不写入并返回 `None`
+    pub async fn update(
+        &self,
+        id: i32,
+        request: UpdateTaskRequest,
+        expected: Option<&[i64]>,
+    ) -> Result<Option<Versioned<Task>>, sqlx::Error> {
         let current_task = match self.find_by_id(id).await? {
             Some(task) => task,
             None => return Err(sqlx::Error::RowNotFound),
@@ -221,29 +244,33 @@ impl TaskRepository {
         }
         qb.push("updated_at = ");
         qb.push_bind(Utc::now());
+        qb.push(", version = version + 1");
 
-        let result = qb
-            .push(" WHERE id = ")
+        qb.push(" WHERE id = ")
             .push_bind(id)
-            .push(" RETURNING id, title, description, parent_task_id, status, completed_at, effort_estimate_minutes, created_at, updated_at")
-            .build_query_as::<Task>()
-            .fetch_one(&*self.db)
-            .await?;
-
-        Ok(result)
+            .push(" AND deleted_at IS NULL");
+        etag::push_guard(&mut qb, expected);
+        qb.push(" RETURNING id, title, description, parent_task_id, status, completed_at, effort_estimate_minutes, created_at, updated_at, version")
+            .build_query_as::<Versioned<Task>>()
+            .fetch_optional(&*self.db)
+            .await
     }
 
+    /// 删除任务：已完成或已归档的移入回收站，其余先归档
     pub async fn delete(&self, id: i32) -> Result<u64, sqlx::Error> {
         let task = match self.find_by_id(id).await? {
             Some(task) => task,
             None => return Ok(0),
         };
 
-        if task.is_completed() {
-            let result = sqlx::query("DELETE FROM task WHERE id = ?")
-                .bind(id)
-                .execute(&*self.db)
-                .await?;
+        if task.is_completed() || task.status == TaskStatus::Archived {
+            let result = sqlx::query(
+                "UPDATE task SET deleted_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+                WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(id)
+            .execute(&*self.db)
+            .await?;
             Ok(result.rows_affected())
         } else {
             let result =
@@ -255,4 +282,61 @@ impl TaskRepository {
             Ok(result.rows_affected())
         }
     }
+
+    /// 从回收站恢复任务
+    pub async fn restore(&self, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE task SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&*self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 彻底删除回收站中的任务，连同依赖、分解关系与时间窗口；子任务提升为顶层任务
+    pub async fn purge(&self, id: i32) -> Result<u64, sqlx::Error> {
+        let mut tx = self.db.begin().await?;
+        let trashed: Option<i32> =
+            sqlx::query_scalar("SELECT id FROM task WHERE id = ? AND deleted_at IS NOT NULL")
+                .bind(id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if trashed.is_none() {
+            return Ok(0);
+        }
+
+        sqlx::query("DELETE FROM task_dependency WHERE task_id = ? OR depends_on_task_id = ?")
+            .bind(id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM task_decomposition WHERE parent_task_id = ? OR child_task_id = ?")
+            .bind(id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "DELETE FROM task_time_allocation
+            WHERE task_id = ? OR time_window_id IN (SELECT id FROM time_window WHERE task_id = ?)",
+        )
+        .bind(id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM time_window WHERE task_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE task SET parent_task_id = NULL WHERE parent_task_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM task WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
 }
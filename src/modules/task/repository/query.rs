@@ -12,7 +12,7 @@ impl TaskRepository {
                 SELECT id, title, description, parent_task_id, status, completed_at,
                        effort_estimate_minutes, created_at, updated_at
                 FROM task
-                WHERE ",
+                WHERE deleted_at IS NULL AND ",
         );
 
         if let Some(root_id) = root_task_id {
@@ -29,6 +29,7 @@ impl TaskRepository {
                    t.effort_estimate_minutes, t.created_at, t.updated_at
             FROM task t
             INNER JOIN task_tree tt ON t.parent_task_id = tt.id
+            WHERE t.deleted_at IS NULL
         )
         SELECT * FROM task_tree ORDER BY created_at",
         );
@@ -55,7 +56,7 @@ impl TaskRepository {
             "SELECT id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at
             FROM task
-            WHERE parent_task_id = ?
+            WHERE parent_task_id = ? AND deleted_at IS NULL
             ORDER BY created_at",
         )
         .bind(id)
@@ -102,14 +103,16 @@ impl TaskRepository {
         offset: i64,
     ) -> Result<(Vec<Task>, i64), sqlx::Error> {
         let pattern = format!("%{}%", query);
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE title LIKE ?")
-            .bind(&pattern)
-            .fetch_one(&*self.db)
-            .await?;
+        let total: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task WHERE title LIKE ? AND deleted_at IS NULL",
+        )
+        .bind(&pattern)
+        .fetch_one(&*self.db)
+        .await?;
         let items = sqlx::query_as::<_, Task>(
             "SELECT id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at
-            FROM task WHERE title LIKE ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            FROM task WHERE title LIKE ? AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ? OFFSET ?",
         )
         .bind(&pattern)
         .bind(limit)
@@ -141,7 +144,7 @@ impl TaskRepository {
                         tw.recurrence_until, tw.recurrence_by_weekdays
                  FROM task t
                  INNER JOIN time_window tw ON t.id = tw.task_id
-                 WHERE t.status = ?
+                 WHERE t.status = ? AND t.deleted_at IS NULL
                    AND tw.start_time < ?
                    AND tw.end_time > ?
                  ORDER BY tw.start_time"
@@ -160,7 +163,7 @@ impl TaskRepository {
                         tw.recurrence_until, tw.recurrence_by_weekdays
                  FROM task t
                  INNER JOIN time_window tw ON t.id = tw.task_id
-                 WHERE t.status != 'archived'
+                 WHERE t.status != 'archived' AND t.deleted_at IS NULL
                    AND tw.start_time < ?
                    AND tw.end_time > ?
                  ORDER BY tw.start_time"
@@ -242,23 +245,29 @@ impl TaskRepository {
     }
 
     pub async fn get_stats(&self) -> Result<(i64, i64, i64, i64), sqlx::Error> {
-        let backlog: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = 'backlog'")
-            .fetch_one(&*self.db)
-            .await?;
+        let backlog: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task WHERE status = 'backlog' AND deleted_at IS NULL",
+        )
+        .fetch_one(&*self.db)
+        .await?;
 
-        let active: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = 'active'")
-            .fetch_one(&*self.db)
-            .await?;
+        let active: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task WHERE status = 'active' AND deleted_at IS NULL",
+        )
+        .fetch_one(&*self.db)
+        .await?;
 
-        let completed: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = 'completed'")
-                .fetch_one(&*self.db)
-                .await?;
+        let completed: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task WHERE status = 'completed' AND deleted_at IS NULL",
+        )
+        .fetch_one(&*self.db)
+        .await?;
 
-        let archived: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = 'archived'")
-                .fetch_one(&*self.db)
-                .await?;
+        let archived: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM task WHERE status = 'archived' AND deleted_at IS NULL",
+        )
+        .fetch_one(&*self.db)
+        .await?;
 
         Ok((backlog, active, completed, archived))
     }
@@ -11,14 +11,15 @@ impl TaskRepository {
         offset: i64,
     ) -> Result<(Vec<Task>, i64), sqlx::Error> {
         let status_str = status.as_str();
-        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = ?")
-            .bind(status_str)
-            .fetch_one(&*self.db)
-            .await?;
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM task WHERE status = ? AND deleted_at IS NULL")
+                .bind(status_str)
+                .fetch_one(&*self.db)
+                .await?;
         let items = sqlx::query_as::<_, Task>(
             "SELECT id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at
-            FROM task WHERE status = ? ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            FROM task WHERE status = ? AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ? OFFSET ?",
         )
         .bind(status_str)
         .bind(limit)
@@ -32,7 +33,7 @@ impl TaskRepository {
         let now = Utc::now();
         let result = sqlx::query_as::<_, Task>(
             "UPDATE task SET status = 'completed', completed_at = ?, updated_at = ?
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             RETURNING id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at",
         )
@@ -49,7 +50,7 @@ impl TaskRepository {
         let now = Utc::now();
         let result = sqlx::query_as::<_, Task>(
             "UPDATE task SET status = 'active', updated_at = ?
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             RETURNING id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at",
         )
@@ -65,7 +66,7 @@ impl TaskRepository {
         let now = Utc::now();
         let result = sqlx::query_as::<_, Task>(
             "UPDATE task SET status = 'archived', updated_at = ?
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             RETURNING id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at",
         )
@@ -81,7 +82,7 @@ impl TaskRepository {
         let now = Utc::now();
         let result = sqlx::query_as::<_, Task>(
             "UPDATE task SET status = 'backlog', updated_at = ?
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             RETURNING id, title, description, parent_task_id, status, completed_at,
             effort_estimate_minutes, created_at, updated_at",
         )
@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::model::{Task, TaskStatus};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TaskResponse {
     pub id: i32,
     pub title: String,
@@ -32,13 +33,14 @@ impl From<Task> for TaskResponse {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TreeNode {
     pub task: TaskResponse,
+    #[schema(no_recursion)]
     pub children: Vec<TreeNode>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CalendarEvent {
     pub task_id: i32,
     pub title: String,
@@ -48,26 +50,26 @@ pub struct CalendarEvent {
     pub status: TaskStatus,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DagView {
     pub nodes: Vec<DagNode>,
     pub edges: Vec<DagEdge>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DagNode {
     pub id: i32,
     pub title: String,
     pub status: TaskStatus,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DagEdge {
     pub from: i32,
     pub to: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StatsResponse {
     pub backlog: i64,
     pub active: i64,
@@ -75,7 +77,7 @@ pub struct StatsResponse {
     pub archived: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MessageResponse {
     pub message: String,
 }
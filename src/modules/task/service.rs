@@ -4,33 +4,53 @@ use std::sync::Arc;
 use super::dto::{CreateTaskRequest, QuickCreateTaskRequest, UpdateTaskRequest};
 use super::model::{Task, TaskStatus, TimeWindow, TimeWindowType};
 use super::repository::TaskRepository;
+use super::response::TaskResponse;
+use crate::etag::{self, IfMatch, Versioned};
+use crate::modules::activity::{AuditLog, Entity};
+use crate::modules::events::ChangeFeed;
+use crate::modules::webhook::{WebhookEvent, Webhooks};
+use crate::pagination::{Cursor, Window};
 
 #[derive(Clone)]
 pub struct TaskService {
     repo: TaskRepository,
+    audit: AuditLog,
+    hooks: Webhooks,
 }
 
 impl TaskService {
-    pub fn new(db: Arc<sqlx::SqlitePool>) -> Self {
+    pub fn new(db: Arc<sqlx::SqlitePool>, hooks: Webhooks, changes: ChangeFeed) -> Self {
         Self {
-            repo: TaskRepository::new(db),
+            repo: TaskRepository::new(db.clone()),
+            audit: AuditLog::new(db, changes),
+            hooks,
         }
     }
 
-    pub async fn list(&self, limit: i64, offset: i64) -> Result<(Vec<Task>, i64), sqlx::Error> {
-        self.repo
-            .find_all_excluding_archived_paginated(limit, offset)
-            .await
+    /// 未归档任务
+    pub async fn list(
+        &self,
+        window: &Window,
+    ) -> Result<(Vec<Task>, i64, Option<Cursor>), sqlx::Error> {
+        self.repo.find_page(window, false).await
     }
 
-    pub async fn list_all(&self, limit: i64, offset: i64) -> Result<(Vec<Task>, i64), sqlx::Error> {
-        self.repo.find_all_paginated(limit, offset).await
+    pub async fn list_all(
+        &self,
+        window: &Window,
+    ) -> Result<(Vec<Task>, i64, Option<Cursor>), sqlx::Error> {
+        self.repo.find_page(window, true).await
     }
 
     pub async fn by_id(&self, id: i32) -> Result<Option<Task>, sqlx::Error> {
         self.repo.find_by_id(id).await
     }
 
+    /// 连同行版本号读取，供 ETag 使用
+    pub async fn versioned(&self, id: i32) -> Result<Option<Versioned<Task>>, sqlx::Error> {
+        self.repo.find_versioned(id).await
+    }
+
     pub async fn detail(
         &self,
         id: i32,
@@ -68,21 +88,56 @@ impl TaskService {
             .await
     }
 
-    pub async fn create(&self, req: CreateTaskRequest) -> Result<Task, ServiceError> {
+    pub async fn create(&self, actor: i32, req: CreateTaskRequest) -> Result<Task, ServiceError> {
         validate_title(&req.title)?;
         validate_effort(req.effort_estimate_minutes)?;
         if let Some(parent_id) = req.parent_task_id {
             check_circular_parent(&self.repo, 0, parent_id).await?;
         }
-        self.repo.create(req).await.map_err(ServiceError::Db)
+        let task = self.repo.create(req).await.map_err(ServiceError::Db)?;
+        self.audit
+            .created(actor, Entity::Task, task.id, &task)
+            .await;
+        Ok(task)
     }
 
-    pub async fn quick_create(&self, req: QuickCreateTaskRequest) -> Result<Task, ServiceError> {
+    pub async fn quick_create(
+        &self,
+        actor: i32,
+        req: QuickCreateTaskRequest,
+    ) -> Result<Task, ServiceError> {
         validate_title(&req.title)?;
-        self.repo.quick_create(req).await.map_err(ServiceError::Db)
+        let task = self
+            .repo
+            .quick_create(req)
+            .await
+            .map_err(ServiceError::Db)?;
+        self.audit
+            .created(actor, Entity::Task, task.id, &task)
+            .await;
+        Ok(task)
+    }
+
+    pub async fn update(
+        &self,
+        actor: i32,
+        id: i32,
+        req: UpdateTaskRequest,
+    ) -> Result<Task, ServiceError> {
+        self.update_if(actor, id, req, &IfMatch::Absent)
+            .await
+            .map(|task| task.row)
     }
 
-    pub async fn update(&self, id: i32, req: UpdateTaskRequest) -> Result<Task, ServiceError> {
+    /// 按 `If-Match` 条件更新：版本校验与写入在同一条 UPDATE 中完成，
+    /// 版本不符时返回带最新表示的 [`ServiceError::PreconditionFailed`]
+    pub async fn update_if(
+        &self,
+        actor: i32,
+        id: i32,
+        req: UpdateTaskRequest,
+        if_match: &IfMatch,
+    ) -> Result<Versioned<Task>, ServiceError> {
         if let Some(ref title) = req.title {
             validate_title(title)?;
         }
@@ -95,42 +150,94 @@ impl TaskService {
             }
             check_circular_parent(&self.repo, id, parent_id).await?;
         }
-        self.repo.update(id, req).await.map_err(|e| match e {
-            sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
-            other => ServiceError::from(other),
-        })
+        let before = self.repo.find_by_id(id).await?;
+        let versions = if_match.versions();
+        let Some(task) = self
+            .repo
+            .update(id, req, versions.as_deref())
+            .await
+            .map_err(not_found)?
+        else {
+            return match self.repo.find_versioned(id).await? {
+                Some(current) => Err(ServiceError::PreconditionFailed {
+                    etag: etag::version(current.version),
+                    current: serde_json::to_value(TaskResponse::from(current.row))
+                        .unwrap_or_default(),
+                }),
+                None => Err(ServiceError::NotFound("任务不存在".into())),
+            };
+        };
+        self.audit_update(actor, before, &task.row).await;
+        Ok(task)
+    }
+
+    pub async fn complete(&self, actor: i32, id: i32) -> Result<Task, ServiceError> {
+        let before = self.repo.find_by_id(id).await?;
+        let was_completed = before
+            .as_ref()
+            .is_some_and(|t| t.status == TaskStatus::Completed);
+        let task = self.repo.complete(id).await.map_err(not_found)?;
+        self.audit_update(actor, before, &task).await;
+        if !was_completed {
+            self.hooks
+                .emit(actor, WebhookEvent::TaskCompleted, &task)
+                .await;
+        }
+        Ok(task)
     }
 
-    pub async fn complete(&self, id: i32) -> Result<Task, ServiceError> {
-        self.repo.complete(id).await.map_err(|e| match e {
-            sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
-            other => ServiceError::from(other),
-        })
+    pub async fn activate(&self, actor: i32, id: i32) -> Result<Task, ServiceError> {
+        let before = self.repo.find_by_id(id).await?;
+        let task = self.repo.activate(id).await.map_err(not_found)?;
+        self.audit_update(actor, before, &task).await;
+        Ok(task)
     }
 
-    pub async fn activate(&self, id: i32) -> Result<Task, ServiceError> {
-        self.repo.activate(id).await.map_err(|e| match e {
-            sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
-            other => ServiceError::from(other),
-        })
+    pub async fn archive(&self, actor: i32, id: i32) -> Result<Task, ServiceError> {
+        let before = self.repo.find_by_id(id).await?;
+        let task = self.repo.archive(id).await.map_err(not_found)?;
+        self.audit_update(actor, before, &task).await;
+        Ok(task)
     }
 
-    pub async fn archive(&self, id: i32) -> Result<Task, ServiceError> {
-        self.repo.archive(id).await.map_err(|e| match e {
-            sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
-            other => ServiceError::from(other),
-        })
+    pub async fn move_to_backlog(&self, actor: i32, id: i32) -> Result<Task, ServiceError> {
+        let before = self.repo.find_by_id(id).await?;
+        let task = self.repo.move_to_backlog(id).await.map_err(not_found)?;
+        self.audit_update(actor, before, &task).await;
+        Ok(task)
     }
 
-    pub async fn move_to_backlog(&self, id: i32) -> Result<Task, ServiceError> {
-        self.repo.move_to_backlog(id).await.map_err(|e| match e {
-            sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
-            other => ServiceError::from(other),
-        })
+    pub async fn delete(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let before = self.repo.find_by_id(id).await?;
+        let rows = self.repo.delete(id).await.map_err(ServiceError::Db)?;
+        if let Some(before) = before.filter(|_| rows > 0) {
+            self.audit.deleted(actor, Entity::Task, id, &before).await;
+        }
+        Ok(rows)
+    }
+
+    pub async fn restore(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self.repo.restore(id).await.map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.restored(actor, Entity::Task, id).await;
+        }
+        Ok(rows)
     }
 
-    pub async fn delete(&self, id: i32) -> Result<u64, ServiceError> {
-        self.repo.delete(id).await.map_err(ServiceError::Db)
+    pub async fn purge(&self, actor: i32, id: i32) -> Result<u64, ServiceError> {
+        let rows = self.repo.purge(id).await.map_err(ServiceError::Db)?;
+        if rows > 0 {
+            self.audit.purged(actor, Entity::Task, id).await;
+        }
+        Ok(rows)
+    }
+
+    async fn audit_update(&self, actor: i32, before: Option<Task>, after: &Task) {
+        if let Some(before) = before {
+            self.audit
+                .updated(actor, Entity::Task, after.id, &before, after)
+                .await;
+        }
     }
 
     pub async fn add_dependency(&self, task_id: i32, depends_on: i32) -> Result<(), ServiceError> {
@@ -325,6 +432,13 @@ impl TaskService {
     }
 }
 
+fn not_found(e: sqlx::Error) -> ServiceError {
+    match e {
+        sqlx::Error::RowNotFound => ServiceError::NotFound("任务不存在".into()),
+        other => ServiceError::from(other),
+    }
+}
+
 fn validate_title(title: &str) -> Result<(), ServiceError> {
     if title.is_empty() || title.len() > 255 {
         return Err(ServiceError::InvalidInput(
@@ -493,4 +607,125 @@ mod tests {
             let _ = e.into_response();
         }
     }
+
+    // ── 回收站 ──
+
+    #[tokio::test]
+    async fn delete_archives_first_then_trashes() {
+        let pool = Arc::new(sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let jobs = crate::modules::job::JobService::new(pool.clone());
+        let svc = TaskService::new(
+            pool.clone(),
+            Webhooks::new(pool.clone(), jobs),
+            ChangeFeed::default(),
+        );
+        let parent = svc
+            .quick_create(1, QuickCreateTaskRequest { title: "p".into() })
+            .await
+            .unwrap();
+        let child = svc
+            .quick_create(1, QuickCreateTaskRequest { title: "c".into() })
+            .await
+            .unwrap();
+        sqlx::query("UPDATE task SET parent_task_id = ? WHERE id = ?")
+            .bind(parent.id)
+            .bind(child.id)
+            .execute(&*pool)
+            .await
+            .unwrap();
+        svc.add_dependency(child.id, parent.id).await.unwrap();
+
+        // 未完成的任务先归档，仍可见
+        assert_eq!(svc.delete(1, parent.id).await.unwrap(), 1);
+        let archived = svc.by_id(parent.id).await.unwrap().unwrap();
+        assert_eq!(archived.status, TaskStatus::Archived);
+        // 再次删除移入回收站
+        assert_eq!(svc.delete(1, parent.id).await.unwrap(), 1);
+        assert!(svc.by_id(parent.id).await.unwrap().is_none());
+        let window = Window::Offset {
+            limit: 10,
+            offset: 0,
+        };
+        assert_eq!(svc.list_all(&window).await.unwrap().1, 1);
+        assert!(matches!(
+            svc.complete(1, parent.id).await,
+            Err(ServiceError::NotFound(_))
+        ));
+
+        assert_eq!(svc.restore(1, parent.id).await.unwrap(), 1);
+        assert!(svc.by_id(parent.id).await.unwrap().is_some());
+        assert_eq!(svc.restore(1, parent.id).await.unwrap(), 0);
+
+        // 只能彻底删除回收站中的任务；依赖清理，子任务提升为顶层
+        assert_eq!(svc.purge(1, parent.id).await.unwrap(), 0);
+        svc.delete(1, parent.id).await.unwrap();
+        assert_eq!(svc.purge(1, parent.id).await.unwrap(), 1);
+        let child = svc.by_id(child.id).await.unwrap().unwrap();
+        assert_eq!(child.parent_task_id, None);
+        let deps: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task_dependency")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(deps, 0);
+    }
+
+    // ── If-Match ──
+
+    fn retitle(title: &str) -> UpdateTaskRequest {
+        UpdateTaskRequest {
+            title: Some(title.into()),
+            description: None,
+            parent_task_id: None,
+            status: None,
+            effort_estimate_minutes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_updates_with_same_etag_one_fails() {
+        let pool = Arc::new(sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let jobs = crate::modules::job::JobService::new(pool.clone());
+        let svc = TaskService::new(
+            pool.clone(),
+            Webhooks::new(pool.clone(), jobs),
+            ChangeFeed::default(),
+        );
+        let task = svc
+            .quick_create(1, QuickCreateTaskRequest { title: "t".into() })
+            .await
+            .unwrap();
+        let v = svc.versioned(task.id).await.unwrap().unwrap().version;
+        let if_match = IfMatch::Tags(vec![etag::version(v)]);
+
+        let (a, b) = tokio::join!(
+            svc.update_if(1, task.id, retitle("a"), &if_match),
+            svc.update_if(1, task.id, retitle("b"), &if_match),
+        );
+        let (ok, stale): (Vec<_>, Vec<_>) = [a, b].into_iter().partition(Result::is_ok);
+        assert_eq!(ok.len(), 1);
+        assert_eq!(stale.len(), 1);
+        let winner = ok.into_iter().next().unwrap().unwrap();
+        match stale.into_iter().next().unwrap().unwrap_err() {
+            ServiceError::PreconditionFailed { etag, current } => {
+                assert_eq!(etag, etag::version(winner.version));
+                assert_eq!(current["title"], winner.row.title.as_str());
+            }
+            other => panic!("expected 412, got {other:?}"),
+        }
+
+        // 其他写入路径（完成任务）同样使旧 ETag 失效
+        let if_match = IfMatch::Tags(vec![etag::version(winner.version)]);
+        svc.complete(1, task.id).await.unwrap();
+        assert!(matches!(
+            svc.update_if(1, task.id, retitle("c"), &if_match).await,
+            Err(ServiceError::PreconditionFailed { .. })
+        ));
+        assert!(
+            svc.update_if(1, task.id, retitle("c"), &IfMatch::Any)
+                .await
+                .is_ok()
+        );
+    }
 }
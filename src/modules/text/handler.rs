@@ -1,37 +1,51 @@
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{Extension, Json, extract::State, http::HeaderMap, response::IntoResponse};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::auth::Claims;
+use crate::etag::{self, IfMatch};
+use crate::openapi::{OkFlag, PreconditionFailed};
 use crate::state::AppState;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TabItem {
     pub name: String,
     pub content: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TextResponse {
     pub tabs: Vec<TabItem>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TabItemInput {
     pub name: String,
     pub content: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SaveRequest {
     pub tabs: Vec<TabItemInput>,
 }
 
-pub async fn get_text(State(state): State<AppState>) -> impl IntoResponse {
-    let result = state.text.load_tabs().await.map(|rows| {
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "获取文本标签页",
+    responses((status = 200, description = "成功", body = TextResponse, headers(("ETag" = String, description = "资源当前版本"))))
+)]
+pub async fn get_text(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let result = state.text.load_tabs(claims.sub).await.map(|rows| {
+        let tag = etag::of(&rows);
         let tabs = rows
             .into_iter()
             .map(|(name, content)| TabItem { name, content })
             .collect();
-        Json(TextResponse { tabs })
+        etag::with(&tag, Json(TextResponse { tabs }))
     });
     match result {
         Ok(ok) => ok.into_response(),
@@ -39,13 +53,24 @@ pub async fn get_text(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
+#[utoipa::path(
+    put,
+    path = "/",
+    summary = "保存文本标签页",
+    params(IfMatch),
+    request_body = SaveRequest,
+    responses((status = 200, description = "成功", body = OkFlag, headers(("ETag" = String, description = "资源当前版本"))), PreconditionFailed)
+)]
 pub async fn save_text(
     State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
     Json(body): Json<SaveRequest>,
 ) -> impl IntoResponse {
     let tabs: Vec<(String, String)> = body.tabs.into_iter().map(|t| (t.name, t.content)).collect();
-    match state.text.save_tabs(&tabs).await {
-        Ok(()) => Json(serde_json::json!({"ok": true})).into_response(),
+    let if_match = IfMatch::from_headers(&headers);
+    match state.text.save_tabs(claims.sub, &tabs, &if_match).await {
+        Ok(tag) => etag::with(&tag, Json(serde_json::json!({"ok": true}))),
         Err(e) => e.into_response(),
     }
 }
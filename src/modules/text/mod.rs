@@ -3,10 +3,11 @@ mod repository;
 pub mod service;
 
 pub use service::TextService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::state::AppState;
-use axum::{Router, routing::get};
 
-pub fn routes() -> Router<AppState> {
-    Router::new().route("/", get(handler::get_text).put(handler::save_text))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(handler::get_text, handler::save_text))
 }
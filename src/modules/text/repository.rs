@@ -1,6 +1,14 @@
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
+/// [`TextRepo::save_tabs`] 的结果
+#[derive(Debug, PartialEq)]
+pub enum SaveOutcome {
+    Saved,
+    /// 前置条件不满足，未写入；附当前标签页
+    Stale(Vec<(String, String)>),
+}
+
 #[derive(Clone)]
 pub struct TextRepo {
     pool: Arc<SqlitePool>,
@@ -11,35 +19,55 @@ impl TextRepo {
         Self { pool }
     }
 
-    pub async fn load_tabs(&self) -> Result<Vec<(String, String)>, sqlx::Error> {
+    pub async fn load_tabs(&self, user_id: i32) -> Result<Vec<(String, String)>, sqlx::Error> {
         let rows = sqlx::query_as::<_, (String, String)>(
-            "SELECT name, content FROM text_note ORDER BY id",
+            "SELECT name, content FROM text_note WHERE user_id = ? ORDER BY id",
         )
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await?;
 
         Ok(rows)
     }
 
-    pub async fn save_tabs(&self, tabs: &[(String, String)]) -> Result<(), sqlx::Error> {
+    /// 整体覆盖用户的标签页。`precondition` 在同一事务内拿到当前标签页，
+    /// 返回 false 时放弃写入
+    pub async fn save_tabs(
+        &self,
+        user_id: i32,
+        tabs: &[(String, String)],
+        precondition: impl FnOnce(&[(String, String)]) -> bool,
+    ) -> Result<SaveOutcome, sqlx::Error> {
         let mut tx = self.pool.begin().await?;
 
-        sqlx::query("DELETE FROM text_note")
+        let current = sqlx::query_as::<_, (String, String)>(
+            "SELECT name, content FROM text_note WHERE user_id = ? ORDER BY id",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        if !precondition(&current) {
+            return Ok(SaveOutcome::Stale(current));
+        }
+
+        sqlx::query("DELETE FROM text_note WHERE user_id = ?")
+            .bind(user_id)
             .execute(&mut *tx)
             .await?;
 
         for (name, content) in tabs {
             sqlx::query(
-                "INSERT INTO text_note (name, content, created_at, updated_at) VALUES (?, ?, datetime('now'), datetime('now'))",
+                "INSERT INTO text_note (name, content, user_id, created_at, updated_at) VALUES (?, ?, ?, datetime('now'), datetime('now'))",
             )
             .bind(name)
             .bind(content)
+            .bind(user_id)
             .execute(&mut *tx)
             .await?;
         }
 
         tx.commit().await?;
-        Ok(())
+        Ok(SaveOutcome::Saved)
     }
 }
 
@@ -51,7 +79,7 @@ mod tests {
 
     async fn setup() -> TextRepo {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE text_note (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL DEFAULT '', content TEXT NOT NULL DEFAULT '', created_at TEXT, updated_at TEXT)")
+        sqlx::query("CREATE TABLE text_note (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL DEFAULT '', content TEXT NOT NULL DEFAULT '', user_id INTEGER NOT NULL, created_at TEXT, updated_at TEXT)")
             .execute(&*pool).await.unwrap();
         TextRepo { pool }
     }
@@ -63,8 +91,8 @@ mod tests {
             ("tab1".into(), "content1".into()),
             ("tab2".into(), "content2".into()),
         ];
-        repo.save_tabs(&tabs).await.unwrap();
-        let loaded = repo.load_tabs().await.unwrap();
+        repo.save_tabs(1, &tabs, |_| true).await.unwrap();
+        let loaded = repo.load_tabs(1).await.unwrap();
         assert_eq!(loaded.len(), 2);
         assert_eq!(loaded[0].0, "tab1");
         assert_eq!(loaded[1].1, "content2");
@@ -73,16 +101,49 @@ mod tests {
     #[tokio::test]
     async fn save_overwrites() {
         let repo = setup().await;
-        repo.save_tabs(&[("a".into(), "old".into())]).await.unwrap();
-        repo.save_tabs(&[("a".into(), "new".into())]).await.unwrap();
-        let loaded = repo.load_tabs().await.unwrap();
+        repo.save_tabs(1, &[("a".into(), "old".into())], |_| true)
+            .await
+            .unwrap();
+        repo.save_tabs(1, &[("a".into(), "new".into())], |_| true)
+            .await
+            .unwrap();
+        let loaded = repo.load_tabs(1).await.unwrap();
         assert_eq!(loaded[0].1, "new");
     }
 
     #[tokio::test]
     async fn load_empty() {
         let repo = setup().await;
-        let loaded = repo.load_tabs().await.unwrap();
+        let loaded = repo.load_tabs(1).await.unwrap();
         assert!(loaded.is_empty());
     }
+
+    #[tokio::test]
+    async fn failed_precondition_keeps_tabs() {
+        let repo = setup().await;
+        repo.save_tabs(1, &[("a".into(), "old".into())], |_| true)
+            .await
+            .unwrap();
+        let outcome = repo
+            .save_tabs(1, &[("a".into(), "new".into())], |_| false)
+            .await
+            .unwrap();
+        let current = vec![("a".to_string(), "old".to_string())];
+        assert_eq!(outcome, SaveOutcome::Stale(current.clone()));
+        assert_eq!(repo.load_tabs(1).await.unwrap(), current);
+    }
+
+    #[tokio::test]
+    async fn tabs_are_scoped_to_user() {
+        let repo = setup().await;
+        repo.save_tabs(1, &[("mine".into(), "a".into())], |_| true)
+            .await
+            .unwrap();
+        repo.save_tabs(2, &[("theirs".into(), "b".into())], |_| true)
+            .await
+            .unwrap();
+        // 用户 2 保存不应清空用户 1 的标签页
+        let loaded = repo.load_tabs(1).await.unwrap();
+        assert_eq!(loaded, vec![("mine".to_string(), "a".to_string())]);
+    }
 }
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use crate::error::ServiceError;
+use crate::etag::{self, IfMatch};
 
-use super::repository;
+use super::repository::{self, SaveOutcome};
 
 #[derive(Clone)]
 pub struct TextService {
@@ -14,14 +15,39 @@ impl TextService {
         Self { pool }
     }
 
-    pub async fn load_tabs(&self) -> Result<Vec<(String, String)>, ServiceError> {
+    pub async fn load_tabs(&self, user_id: i32) -> Result<Vec<(String, String)>, ServiceError> {
         let repo = repository::TextRepo::new(self.pool.clone());
-        repo.load_tabs().await.map_err(ServiceError::Db)
+        repo.load_tabs(user_id).await.map_err(ServiceError::Db)
     }
 
-    pub async fn save_tabs(&self, tabs: &[(String, String)]) -> Result<(), ServiceError> {
+    /// 覆盖保存标签页，返回新的 ETag。`If-Match` 在写入事务内校验
+    pub async fn save_tabs(
+        &self,
+        user_id: i32,
+        tabs: &[(String, String)],
+        if_match: &IfMatch,
+    ) -> Result<String, ServiceError> {
         let repo = repository::TextRepo::new(self.pool.clone());
-        repo.save_tabs(tabs).await.map_err(ServiceError::Db)
+        let outcome = repo
+            .save_tabs(user_id, tabs, |current| {
+                if_match.matches(&etag::of(current))
+            })
+            .await
+            .map_err(ServiceError::Db)?;
+        match outcome {
+            SaveOutcome::Saved => Ok(etag::of(tabs)),
+            SaveOutcome::Stale(current) => {
+                // 与 GET /text 的响应体同形
+                let tabs: Vec<_> = current
+                    .iter()
+                    .map(|(name, content)| serde_json::json!({ "name": name, "content": content }))
+                    .collect();
+                Err(ServiceError::PreconditionFailed {
+                    etag: etag::of(&current),
+                    current: serde_json::json!({ "tabs": tabs }),
+                })
+            }
+        }
     }
 }
 
@@ -33,7 +59,7 @@ mod tests {
 
     async fn setup() -> TextService {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE text_note (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL DEFAULT '', content TEXT NOT NULL DEFAULT '', created_at TEXT, updated_at TEXT)")
+        sqlx::query("CREATE TABLE text_note (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL DEFAULT '', content TEXT NOT NULL DEFAULT '', user_id INTEGER NOT NULL, created_at TEXT, updated_at TEXT)")
             .execute(&*pool).await.unwrap();
         TextService::new(pool)
     }
@@ -42,15 +68,45 @@ mod tests {
     async fn roundtrip() {
         let svc = setup().await;
         let tabs = vec![("hello".into(), "world".into())];
-        svc.save_tabs(&tabs).await.unwrap();
-        let loaded = svc.load_tabs().await.unwrap();
+        svc.save_tabs(1, &tabs, &IfMatch::Absent).await.unwrap();
+        let loaded = svc.load_tabs(1).await.unwrap();
         assert_eq!(loaded, tabs);
     }
 
+    #[tokio::test]
+    async fn stale_if_match_is_rejected() {
+        let svc = setup().await;
+        let v1 = svc
+            .save_tabs(1, &[("a".into(), "one".into())], &IfMatch::Absent)
+            .await
+            .unwrap();
+        assert_eq!(v1, etag::of(&svc.load_tabs(1).await.unwrap()));
+
+        let fresh = IfMatch::Tags(vec![v1.clone()]);
+        let v2 = svc
+            .save_tabs(1, &[("a".into(), "two".into())], &fresh)
+            .await
+            .unwrap();
+        assert_ne!(v1, v2);
+
+        // 仍持有 v1 的另一端写入被拒，返回当前内容
+        let err = svc
+            .save_tabs(1, &[("a".into(), "three".into())], &fresh)
+            .await
+            .unwrap_err();
+        match err {
+            ServiceError::PreconditionFailed { etag, current } => {
+                assert_eq!(etag, v2);
+                assert_eq!(current["tabs"][0]["content"], "two");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
     #[tokio::test]
     async fn empty_on_no_data() {
         let svc = setup().await;
-        let loaded = svc.load_tabs().await.unwrap();
+        let loaded = svc.load_tabs(1).await.unwrap();
         assert!(loaded.is_empty());
     }
 }
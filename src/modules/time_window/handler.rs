@@ -6,27 +6,30 @@ use axum::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
 use super::model::{CreateTimeWindowRequest, TimeWindow, TimeWindowType, UpdateTimeWindowRequest};
 use super::repository::TimeWindowRepository;
 use crate::error;
-use crate::pagination::Pagination;
+use crate::pagination::{PaginatedResponse, Pagination};
 use crate::state::AppState;
 
 // ==================== 查询参数结构体 ====================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TimeWindowQuery {
     pub task_id: Option<i32>,
     pub window_type: Option<TimeWindowType>,
     pub user_id: Option<i32>,
     #[serde(flatten)]
+    #[param(ignore)]
     pub pagination: Pagination,
 }
 
 // ==================== 响应结构体 ====================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TimeWindowResponse {
     pub id: i32,
     pub start_time: DateTime<Utc>,
@@ -37,7 +40,7 @@ pub struct TimeWindowResponse {
     pub recurrence_rule: Option<RecurrenceRuleResponse>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RecurrenceRuleResponse {
     pub freq: String,
     pub interval: i32,
@@ -45,6 +48,19 @@ pub struct RecurrenceRuleResponse {
     pub by_weekdays: Option<Vec<i32>>,
 }
 
+/// 任务时间窗口的时间范围与数量
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimeWindowStats {
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimeConflict {
+    pub has_conflict: bool,
+}
+
 impl From<TimeWindow> for TimeWindowResponse {
     fn from(window: TimeWindow) -> Self {
         let recurrence_rule = window.recurrence_rule().map(|rule| RecurrenceRuleResponse {
@@ -69,6 +85,13 @@ impl From<TimeWindow> for TimeWindowResponse {
 // ==================== 处理器函数 ====================
 
 /// 创建时间窗口
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建时间窗口",
+    request_body = CreateTimeWindowRequest,
+    responses((status = 200, description = "成功", body = TimeWindowResponse))
+)]
 pub async fn create_time_window_handler(
     State(state): State<AppState>,
     Json(payload): Json<CreateTimeWindowRequest>,
@@ -80,6 +103,12 @@ pub async fn create_time_window_handler(
 }
 
 /// 获取单个时间窗口
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    summary = "获取时间窗口",
+    responses((status = 200, description = "成功", body = TimeWindowResponse))
+)]
 pub async fn get_time_window_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
@@ -94,11 +123,17 @@ pub async fn get_time_window_handler(
 }
 
 /// 查询时间窗口
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "时间窗口列表",
+    params(TimeWindowQuery, Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TimeWindowResponse>))
+)]
 pub async fn get_time_windows_handler(
     Query(query): Query<TimeWindowQuery>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    use crate::pagination::PaginatedResponse;
     let repo = TimeWindowRepository::new(state.db);
     let p = &query.pagination;
 
@@ -130,7 +165,31 @@ pub async fn get_time_windows_handler(
     Json(PaginatedResponse::new(empty, 0, p)).into_response()
 }
 
+/// 查询任务的时间窗口，`task_id` 取自路径
+#[utoipa::path(
+    get,
+    path = "/task/{task_id}",
+    summary = "任务的时间窗口",
+    params(TimeWindowQuery, Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TimeWindowResponse>))
+)]
+pub async fn get_task_time_windows_handler(
+    Path(task_id): Path<i32>,
+    Query(mut query): Query<TimeWindowQuery>,
+    state: State<AppState>,
+) -> impl IntoResponse {
+    query.task_id = Some(task_id);
+    get_time_windows_handler(Query(query), state).await
+}
+
 /// 更新时间窗口
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "更新时间窗口",
+    request_body = UpdateTimeWindowRequest,
+    responses((status = 200, description = "成功", body = TimeWindowResponse))
+)]
 pub async fn update_time_window_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
@@ -143,6 +202,12 @@ pub async fn update_time_window_handler(
 }
 
 /// 删除时间窗口
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除时间窗口",
+    responses((status = 204, description = "成功"))
+)]
 pub async fn delete_time_window_handler(
     Path(id): Path<i32>,
     State(state): State<AppState>,
@@ -162,6 +227,12 @@ pub async fn delete_time_window_handler(
 }
 
 /// 获取时间窗口统计信息
+#[utoipa::path(
+    get,
+    path = "/task/{task_id}/stats",
+    summary = "任务时间窗口统计",
+    responses((status = 200, description = "成功", body = TimeWindowStats))
+)]
 pub async fn get_time_window_stats_handler(
     Path(task_id): Path<i32>,
     State(state): State<AppState>,
@@ -169,26 +240,24 @@ pub async fn get_time_window_stats_handler(
     let repo = TimeWindowRepository::new(state.db);
 
     match repo.get_task_time_stats(task_id).await {
-        Ok((earliest, latest, count)) => {
-            #[derive(Debug, Serialize)]
-            struct StatsResponse {
-                earliest: Option<DateTime<Utc>>,
-                latest: Option<DateTime<Utc>>,
-                count: i64,
-            }
-
-            Json(StatsResponse {
-                earliest,
-                latest,
-                count,
-            })
-            .into_response()
-        }
+        Ok((earliest, latest, count)) => Json(TimeWindowStats {
+            earliest,
+            latest,
+            count,
+        })
+        .into_response(),
         Err(e) => error::internal(e, "获取时间窗口统计"),
     }
 }
 
 /// 检查时间窗口冲突
+#[utoipa::path(
+    get,
+    path = "/task/{task_id}/conflict",
+    summary = "检查时间冲突",
+    params(("start_time" = DateTime<Utc>, Query), ("end_time" = DateTime<Utc>, Query), ("exclude_id" = Option<i32>, Query)),
+    responses((status = 200, description = "成功", body = TimeConflict))
+)]
 pub async fn check_time_conflict_handler(
     Path(task_id): Path<i32>,
     Query(params): Query<HashMap<String, String>>,
@@ -233,9 +302,7 @@ pub async fn check_time_conflict_handler(
         .check_time_conflict(task_id, start_time, end_time, exclude_id)
         .await
     {
-        Ok(has_conflict) => {
-            Json(serde_json::json!({ "has_conflict": has_conflict })).into_response()
-        }
+        Ok(has_conflict) => Json(TimeConflict { has_conflict }).into_response(),
         Err(e) => error::internal(e, "检查时间窗口冲突"),
     }
 }
@@ -2,9 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use std::str::FromStr;
+use utoipa::ToSchema;
 
 /// 时间窗口类型枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
 #[sqlx(type_name = "TEXT", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
 pub enum TimeWindowType {
@@ -55,7 +56,7 @@ impl FromStr for TimeWindowType {
 }
 
 /// 循环规则频率枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
 #[sqlx(type_name = "TEXT")]
 #[serde(rename_all = "snake_case")]
 pub enum RecurrenceFrequency {
@@ -95,7 +96,7 @@ impl FromStr for RecurrenceFrequency {
 }
 
 /// 循环规则
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RecurrenceRule {
     /// 频率：daily, weekly, monthly
     pub freq: RecurrenceFrequency,
@@ -111,7 +112,7 @@ pub struct RecurrenceRule {
 }
 
 /// 时间窗口实体
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct TimeWindow {
     /// 时间窗口ID
     pub id: i32,
@@ -172,7 +173,7 @@ impl TimeWindow {
 }
 
 /// 创建时间窗口请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateTimeWindowRequest {
     /// 开始时间
     pub start_time: DateTime<Utc>,
@@ -196,7 +197,7 @@ pub struct CreateTimeWindowRequest {
 }
 
 /// 更新时间窗口请求（部分更新）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateTimeWindowRequest {
     /// 开始时间（可选）
     #[serde(default)]
@@ -219,6 +220,23 @@ pub struct UpdateTimeWindowRequest {
     pub recurrence_rule: Option<Option<RecurrenceRule>>,
 }
 
+impl Default for TimeWindow {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            start_time: chrono::Utc::now(),
+            end_time: chrono::Utc::now(),
+            window_type: TimeWindowType::Feasible,
+            task_id: 0,
+            user_id: None,
+            recurrence_freq: None,
+            recurrence_interval: None,
+            recurrence_until: None,
+            recurrence_by_weekdays: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -298,20 +316,3 @@ mod tests {
         assert!(tw.recurrence_rule().is_none());
     }
 }
-
-impl Default for TimeWindow {
-    fn default() -> Self {
-        Self {
-            id: 0,
-            start_time: chrono::Utc::now(),
-            end_time: chrono::Utc::now(),
-            window_type: TimeWindowType::Feasible,
-            task_id: 0,
-            user_id: None,
-            recurrence_freq: None,
-            recurrence_interval: None,
-            recurrence_until: None,
-            recurrence_by_weekdays: None,
-        }
-    }
-}
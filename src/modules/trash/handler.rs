@@ -0,0 +1,83 @@
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use super::model::{PurgeResult, TrashItem, TrashQuery};
+use crate::auth::Claims;
+use crate::pagination::PaginatedResponse;
+use crate::state::AppState;
+
+/// GET /api/trash
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "回收站列表，按删除时间倒序",
+    params(TrashQuery),
+    responses((status = 200, description = "成功", body = PaginatedResponse<TrashItem>))
+)]
+pub async fn list_trash_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<TrashQuery>,
+) -> impl IntoResponse {
+    match state.trash.list(claims.sub, &query).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/trash — 清空回收站
+#[utoipa::path(
+    delete,
+    path = "/",
+    summary = "清空回收站",
+    responses((status = 200, description = "成功", body = PurgeResult))
+)]
+pub async fn empty_trash_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    match state.trash.empty(claims.sub).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/trash/{type}/{id}/restore
+#[utoipa::path(
+    post,
+    path = "/{type}/{id}/restore",
+    summary = "从回收站恢复",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn restore_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((entity, id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    match state.trash.restore(claims.sub, &entity, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/trash/{type}/{id} — 彻底删除
+#[utoipa::path(
+    delete,
+    path = "/{type}/{id}",
+    summary = "彻底删除（type 为 mem / task / bookmark / card / media）",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn purge_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path((entity, id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    match state.trash.purge(claims.sub, &entity, &id).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
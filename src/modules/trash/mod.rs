@@ -0,0 +1,26 @@
+//! 回收站。
+//!
+//! mem、任务、书签、卡片与媒体删除时只写入 `deleted_at`（软删除），
+//! `/api/trash` 汇总列出、恢复或彻底删除；超过 `TRASH_RETENTION_DAYS`
+//! 的记录由每日的 `trash_purge` 任务彻底删除。
+
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use service::TrashService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::list_trash_handler,
+            handler::empty_trash_handler
+        ))
+        .routes(routes!(handler::purge_handler))
+        .routes(routes!(handler::restore_handler))
+}
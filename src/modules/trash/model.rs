@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TrashRow {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub title: String,
+    pub deleted_at: String,
+    /// 归属用户；卡片等共享数据为 None
+    pub owner_id: Option<i64>,
+}
+
+/// 回收站中的一条记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TrashItem {
+    /// mem / task / bookmark / card / media
+    pub entity_type: String,
+    /// 媒体为 stored_id，其余为整数主键
+    pub entity_id: String,
+    pub title: String,
+    pub deleted_at: String,
+    /// 保留期满、将被自动彻底删除的时间；未开启自动清理时为 null
+    pub purge_at: Option<String>,
+}
+
+/// GET /api/trash 查询参数
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrashQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    /// 按类型过滤：mem / task / bookmark / card / media
+    pub entity_type: Option<String>,
+}
+
+/// 清空回收站的结果
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PurgeResult {
+    pub purged: u64,
+}
@@ -0,0 +1,86 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::model::TrashRow;
+
+/// 五类实体中已软删除的记录。`?1` 为用户（NULL 表示全部用户）；
+/// 任务、卡片、媒体与各自模块的列表接口一致，不按用户过滤
+const TRASH_UNION: &str = "
+    SELECT 'mem' AS entity_type, CAST(m.id AS TEXT) AS entity_id,
+           substr(c.content, 1, 100) AS title, m.deleted_at, m.user_id AS owner_id
+      FROM mem m JOIN chunk c ON c.id = m.cue_chunk_id
+     WHERE m.deleted_at IS NOT NULL AND (?1 IS NULL OR m.user_id = ?1)
+    UNION ALL
+    SELECT 'task', CAST(id AS TEXT), title, deleted_at, user_id
+      FROM task WHERE deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'bookmark', CAST(id AS TEXT), title, deleted_at, user_id
+      FROM bookmark WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR user_id = ?1)
+    UNION ALL
+    SELECT 'card', CAST(id AS TEXT), substr(COALESCE(content, ''), 1, 100), deleted_at, NULL
+      FROM card WHERE deleted_at IS NOT NULL
+    UNION ALL
+    SELECT 'media', stored_id, original_name, deleted_at, user_id
+      FROM media WHERE deleted_at IS NOT NULL";
+
+#[derive(Clone)]
+pub struct TrashRepo {
+    db: Arc<SqlitePool>,
+}
+
+impl TrashRepo {
+    pub fn new(db: Arc<SqlitePool>) -> Self {
+        Self { db }
+    }
+
+    /// 用户可见的回收站记录，按删除时间倒序
+    pub async fn find_paginated(
+        &self,
+        user_id: i32,
+        entity_type: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<TrashRow>, i64), sqlx::Error> {
+        let total: i64 = sqlx::query_scalar(sqlx::AssertSqlSafe(format!(
+            "SELECT COUNT(*) FROM ({TRASH_UNION}) WHERE ?2 IS NULL OR entity_type = ?2"
+        )))
+        .bind(user_id)
+        .bind(entity_type)
+        .fetch_one(&*self.db)
+        .await?;
+        let rows = sqlx::query_as::<_, TrashRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT * FROM ({TRASH_UNION})
+             WHERE ?2 IS NULL OR entity_type = ?2
+             ORDER BY deleted_at DESC, entity_type, entity_id
+             LIMIT ?3 OFFSET ?4"
+        )))
+        .bind(user_id)
+        .bind(entity_type)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&*self.db)
+        .await?;
+        Ok((rows, total))
+    }
+
+    /// 用户可见的全部回收站记录（清空回收站用）
+    pub async fn find_all(&self, user_id: i32) -> Result<Vec<TrashRow>, sqlx::Error> {
+        sqlx::query_as::<_, TrashRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT * FROM ({TRASH_UNION})"
+        )))
+        .bind(user_id)
+        .fetch_all(&*self.db)
+        .await
+    }
+
+    /// 所有用户中删除时间早于 `cutoff` 的记录
+    pub async fn find_expired(&self, cutoff: &str) -> Result<Vec<TrashRow>, sqlx::Error> {
+        sqlx::query_as::<_, TrashRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT * FROM ({TRASH_UNION}) WHERE deleted_at < ?2"
+        )))
+        .bind(None::<i32>)
+        .bind(cutoff)
+        .fetch_all(&*self.db)
+        .await
+    }
+}
@@ -0,0 +1,421 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tracing::warn;
+
+use super::model::{PurgeResult, TrashItem, TrashQuery, TrashRow};
+use super::repository::TrashRepo;
+use crate::error::ServiceError;
+use crate::modules::activity::Entity;
+use crate::modules::bookmark::BookmarkService;
+use crate::modules::card::CardService;
+use crate::modules::media::service::MediaService;
+use crate::modules::mem::model::AppError;
+use crate::modules::mem::service::MemService;
+use crate::modules::task::TaskService;
+use crate::pagination::{PaginatedResponse, Pagination};
+
+/// 与 `deleted_at` 相同的时间格式，便于按字典序比较
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// 回收站：汇总各模块软删除的记录，恢复与彻底删除委托给对应模块的 service
+#[derive(Clone)]
+pub struct TrashService {
+    repo: TrashRepo,
+    /// 保留天数，0 为不自动清理
+    retention_days: u32,
+    mem: MemService,
+    task: TaskService,
+    bookmark: BookmarkService,
+    card: CardService,
+    media: MediaService,
+}
+
+impl TrashService {
+    pub fn new(
+        db: Arc<SqlitePool>,
+        retention_days: u32,
+        mem: MemService,
+        task: TaskService,
+        bookmark: BookmarkService,
+        card: CardService,
+        media: MediaService,
+    ) -> Self {
+        Self {
+            repo: TrashRepo::new(db),
+            retention_days,
+            mem,
+            task,
+            bookmark,
+            card,
+            media,
+        }
+    }
+
+    pub async fn list(
+        &self,
+        user_id: i32,
+        query: &TrashQuery,
+    ) -> Result<PaginatedResponse<TrashItem>, ServiceError> {
+        let entity =
+            match query.entity_type.as_deref().filter(|s| !s.is_empty()) {
+                None => None,
+                Some(s) => Some(Entity::parse(s).ok_or_else(|| {
+                    ServiceError::InvalidInput(format!("无效的 entity_type: {s}"))
+                })?),
+            };
+        let pagination = Pagination {
+            page: query.page.unwrap_or(1),
+            page_size: query.page_size.unwrap_or(20),
+            cursor: None,
+        };
+        let (rows, total) = self
+            .repo
+            .find_paginated(
+                user_id,
+                entity.map(|e| e.as_str()),
+                pagination.limit(),
+                pagination.offset(),
+            )
+            .await?;
+        let items = rows.into_iter().map(|r| self.item(r)).collect();
+        Ok(PaginatedResponse::new(items, total, &pagination))
+    }
+
+    pub async fn restore(&self, user_id: i32, entity: &str, id: &str) -> Result<(), ServiceError> {
+        let entity = parse_entity(entity)?;
+        let restored = match entity {
+            Entity::Media => self.media.restore(user_id, id).await?,
+            Entity::Mem => found(self.mem.restore(user_id, parse_id(id)?).await)?,
+            Entity::Task => self.task.restore(user_id, parse_id(id)?).await? > 0,
+            Entity::Bookmark => self.bookmark.restore(user_id, parse_id(id)?).await? > 0,
+            Entity::Card => self.card.restore(user_id, parse_id(id)?).await? > 0,
+        };
+        if !restored {
+            return Err(not_in_trash());
+        }
+        Ok(())
+    }
+
+    pub async fn purge(&self, user_id: i32, entity: &str, id: &str) -> Result<(), ServiceError> {
+        if !self.purge_one(user_id, parse_entity(entity)?, id).await? {
+            return Err(not_in_trash());
+        }
+        Ok(())
+    }
+
+    /// 清空用户可见的回收站
+    pub async fn empty(&self, user_id: i32) -> Result<PurgeResult, ServiceError> {
+        let mut purged = 0;
+        for row in self.repo.find_all(user_id).await? {
+            if let Some(entity) = Entity::parse(&row.entity_type)
+                && self.purge_one(user_id, entity, &row.entity_id).await?
+            {
+                purged += 1;
+            }
+        }
+        Ok(PurgeResult { purged })
+    }
+
+    /// 彻底删除超过保留期的记录（`trash_purge` 任务调用），返回删除条数。
+    /// 审计记录的操作者为记录归属用户，无归属的共享数据记为 0
+    pub async fn purge_expired(&self) -> Result<u64, ServiceError> {
+        if self.retention_days == 0 {
+            return Ok(0);
+        }
+        let cutoff = (Utc::now() - Duration::days(self.retention_days.into()))
+            .format(TIME_FORMAT)
+            .to_string();
+        let mut purged = 0;
+        for row in self.repo.find_expired(&cutoff).await? {
+            let Some(entity) = Entity::parse(&row.entity_type) else {
+                continue;
+            };
+            let actor = row.owner_id.unwrap_or(0) as i32;
+            match self.purge_one(actor, entity, &row.entity_id).await {
+                Ok(true) => purged += 1,
+                Ok(false) => {}
+                Err(e) => warn!(
+                    "清理回收站 {}#{} 失败: {}",
+                    row.entity_type, row.entity_id, e
+                ),
+            }
+        }
+        Ok(purged)
+    }
+
+    async fn purge_one(
+        &self,
+        user_id: i32,
+        entity: Entity,
+        id: &str,
+    ) -> Result<bool, ServiceError> {
+        Ok(match entity {
+            Entity::Media => self.media.purge(user_id, id).await?,
+            Entity::Mem => found(self.mem.purge(user_id, parse_id(id)?).await)?,
+            Entity::Task => self.task.purge(user_id, parse_id(id)?).await? > 0,
+            Entity::Bookmark => self.bookmark.purge(user_id, parse_id(id)?).await? > 0,
+            Entity::Card => self.card.purge(user_id, parse_id(id)?).await? > 0,
+        })
+    }
+
+    fn item(&self, row: TrashRow) -> TrashItem {
+        let purge_at = (self.retention_days > 0)
+            .then(|| DateTime::parse_from_rfc3339(&row.deleted_at).ok())
+            .flatten()
+            .map(|t| {
+                (t.with_timezone(&Utc) + Duration::days(self.retention_days.into()))
+                    .format(TIME_FORMAT)
+                    .to_string()
+            });
+        TrashItem {
+            entity_type: row.entity_type,
+            entity_id: row.entity_id,
+            title: row.title,
+            deleted_at: row.deleted_at,
+            purge_at,
+        }
+    }
+}
+
+fn parse_entity(s: &str) -> Result<Entity, ServiceError> {
+    Entity::parse(s).ok_or_else(|| ServiceError::InvalidInput(format!("无效的类型: {s}")))
+}
+
+/// 非整数 id 不可能存在于回收站
+fn parse_id(s: &str) -> Result<i32, ServiceError> {
+    s.parse().map_err(|_| not_in_trash())
+}
+
+fn not_in_trash() -> ServiceError {
+    ServiceError::NotFound("回收站中没有该记录".into())
+}
+
+/// mem 模块的 NotFound 视为“不在回收站”
+fn found(result: Result<(), AppError>) -> Result<bool, ServiceError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(AppError::NotFound) => Ok(false),
+        Err(AppError::PreconditionFailed { etag, current }) => {
+            Err(ServiceError::PreconditionFailed { etag, current })
+        }
+        Err(AppError::Db(e)) => Err(ServiceError::Db(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::modules::job::JobService;
+    use crate::modules::mem::MemRepo;
+    use crate::modules::mem::model::CreateMemRequest;
+    use crate::modules::webhook::Webhooks;
+
+    struct Fixture {
+        trash: TrashService,
+        pool: Arc<SqlitePool>,
+        alice: i32,
+        bob: i32,
+    }
+
+    async fn setup(retention_days: u32) -> Fixture {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let mut users = Vec::new();
+        for name in ["alice", "bob"] {
+            let id: i32 = sqlx::query_scalar(
+                "INSERT INTO user (name, password_hash) VALUES (?, 'x') RETURNING id",
+            )
+            .bind(name)
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+            users.push(id);
+        }
+        let jobs = JobService::new(pool.clone());
+        let hooks = Webhooks::new(pool.clone(), jobs.clone());
+        let root = std::env::temp_dir().join(format!("brainbow-trash-{}", uuid::Uuid::new_v4()));
+        let storage = Arc::new(crate::storage::LocalStorage::new(root));
+        let changes = crate::modules::events::ChangeFeed::default();
+        let trash = TrashService::new(
+            pool.clone(),
+            retention_days,
+            MemService::new(
+                Arc::new(MemRepo::new(pool.clone())),
+                pool.clone(),
+                jobs,
+                hooks.clone(),
+                changes.clone(),
+            ),
+            TaskService::new(pool.clone(), hooks.clone(), changes.clone()),
+            BookmarkService::new(pool.clone(), hooks.clone(), changes.clone()),
+            CardService::new(pool.clone(), changes.clone()),
+            MediaService::new(pool.clone(), storage, hooks, changes),
+        );
+        Fixture {
+            trash,
+            pool,
+            alice: users[0],
+            bob: users[1],
+        }
+    }
+
+    fn query(entity_type: Option<&str>) -> TrashQuery {
+        TrashQuery {
+            page: None,
+            page_size: None,
+            entity_type: entity_type.map(String::from),
+        }
+    }
+
+    async fn new_mem(f: &Fixture, user_id: i32, cue: &str) -> i32 {
+        f.trash
+            .mem
+            .create(
+                user_id,
+                CreateMemRequest {
+                    cue_content: cue.into(),
+                    target_content: "target".into(),
+                    prerequisites: vec![],
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn lists_own_trash_and_restores() {
+        let f = setup(30).await;
+        let mem = new_mem(&f, f.alice, "alice cue").await;
+        let other = new_mem(&f, f.bob, "bob cue").await;
+        let bm = f
+            .trash
+            .bookmark
+            .create(f.alice, "site", "https://a.com", "", &[])
+            .await
+            .unwrap();
+        let card = f.trash.card.create(f.alice, "card".into()).await.unwrap();
+        f.trash.mem.delete(f.alice, mem).await.unwrap();
+        f.trash.mem.delete(f.bob, other).await.unwrap();
+        f.trash.bookmark.delete(f.alice, bm.id).await.unwrap();
+        f.trash.card.delete(f.alice, card.id).await.unwrap();
+
+        // 只看到自己的 mem / 书签，以及共享的卡片
+        let page = f.trash.list(f.alice, &query(None)).await.unwrap();
+        assert_eq!(page.total, 3);
+        let mut kinds: Vec<_> = page.items.iter().map(|i| i.entity_type.as_str()).collect();
+        kinds.sort();
+        assert_eq!(kinds, ["bookmark", "card", "mem"]);
+        let mem_item = page.items.iter().find(|i| i.entity_type == "mem").unwrap();
+        assert_eq!(mem_item.title, "alice cue");
+        assert!(mem_item.purge_at.as_deref() > Some(mem_item.deleted_at.as_str()));
+
+        let mems = f.trash.list(f.alice, &query(Some("mem"))).await.unwrap();
+        assert_eq!(mems.total, 1);
+        assert!(matches!(
+            f.trash.list(f.alice, &query(Some("nope"))).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+
+        // 他人的 mem 不可恢复
+        assert!(matches!(
+            f.trash.restore(f.alice, "mem", &other.to_string()).await,
+            Err(ServiceError::NotFound(_))
+        ));
+        f.trash
+            .restore(f.alice, "mem", &mem.to_string())
+            .await
+            .unwrap();
+        let deleted_at: Option<String> =
+            sqlx::query_scalar("SELECT deleted_at FROM mem WHERE id = ?")
+                .bind(mem)
+                .fetch_one(&*f.pool)
+                .await
+                .unwrap();
+        assert!(deleted_at.is_none());
+        f.trash
+            .restore(f.alice, "bookmark", &bm.id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(f.trash.list(f.alice, &query(None)).await.unwrap().total, 1);
+
+        // 不在回收站中的记录
+        assert!(matches!(
+            f.trash.restore(f.alice, "mem", &mem.to_string()).await,
+            Err(ServiceError::NotFound(_))
+        ));
+        assert!(matches!(
+            f.trash.purge(f.alice, "card", "abc").await,
+            Err(ServiceError::NotFound(_))
+        ));
+        assert!(matches!(
+            f.trash.purge(f.alice, "nope", "1").await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn purge_and_empty() {
+        let f = setup(30).await;
+        let mem = new_mem(&f, f.alice, "cue").await;
+        let keep = new_mem(&f, f.bob, "bob").await;
+        let card = f.trash.card.create(f.alice, "card".into()).await.unwrap();
+        f.trash.mem.delete(f.alice, mem).await.unwrap();
+        f.trash.mem.delete(f.bob, keep).await.unwrap();
+        f.trash.card.delete(f.alice, card.id).await.unwrap();
+
+        f.trash
+            .purge(f.alice, "card", &card.id.to_string())
+            .await
+            .unwrap();
+        assert!(matches!(
+            f.trash.restore(f.alice, "card", &card.id.to_string()).await,
+            Err(ServiceError::NotFound(_))
+        ));
+
+        assert_eq!(f.trash.empty(f.alice).await.unwrap().purged, 1);
+        assert_eq!(f.trash.list(f.alice, &query(None)).await.unwrap().total, 0);
+        // 他人的回收站不受影响
+        assert_eq!(f.trash.list(f.bob, &query(None)).await.unwrap().total, 1);
+
+        let actions: Vec<String> = sqlx::query_scalar(
+            "SELECT action FROM audit_event WHERE entity_type = 'card' ORDER BY id",
+        )
+        .fetch_all(&*f.pool)
+        .await
+        .unwrap();
+        assert_eq!(actions, ["create", "delete", "purge"]);
+    }
+
+    #[tokio::test]
+    async fn purges_only_expired_items() {
+        let f = setup(30).await;
+        let old = new_mem(&f, f.alice, "old").await;
+        let fresh = new_mem(&f, f.alice, "fresh").await;
+        f.trash.mem.delete(f.alice, old).await.unwrap();
+        f.trash.mem.delete(f.alice, fresh).await.unwrap();
+        sqlx::query("UPDATE mem SET deleted_at = '2000-01-01T00:00:00Z' WHERE id = ?")
+            .bind(old)
+            .execute(&*f.pool)
+            .await
+            .unwrap();
+
+        assert_eq!(f.trash.purge_expired().await.unwrap(), 1);
+        let left = f.trash.list(f.alice, &query(None)).await.unwrap();
+        assert_eq!(left.total, 1);
+        assert_eq!(left.items[0].entity_id, fresh.to_string());
+
+        // 保留天数为 0 时不自动清理
+        let f = setup(0).await;
+        let mem = new_mem(&f, f.alice, "cue").await;
+        f.trash.mem.delete(f.alice, mem).await.unwrap();
+        sqlx::query("UPDATE mem SET deleted_at = '2000-01-01T00:00:00Z'")
+            .execute(&*f.pool)
+            .await
+            .unwrap();
+        assert_eq!(f.trash.purge_expired().await.unwrap(), 0);
+        let page = f.trash.list(f.alice, &query(None)).await.unwrap();
+        assert!(page.items[0].purge_at.is_none());
+    }
+}
@@ -1,79 +1,134 @@
 use axum::{
     Extension,
-    extract::State,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header::USER_AGENT},
     response::{IntoResponse, Json},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use utoipa::ToSchema;
 
+use super::model::{PasswordResetResponse, UpdateUserRequest, UserListItem, UserSummary};
+use super::service::ensure_not_self;
 use crate::auth::Claims;
+use crate::error::{ServiceError, internal};
+use crate::modules::session::TokenPair;
+use crate::openapi::OkFlag;
+use crate::rate_limit::ClientIp;
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub name: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub id: i32,
     pub name: String,
     pub role: String,
+    /// access token（短期）
     pub token: String,
+    pub refresh_token: String,
+    /// access token 剩余有效秒数
+    pub expires_in: usize,
+    /// 管理员重置过密码，须先调用修改密码接口
+    pub must_change_password: bool,
 }
 
+impl LoginResponse {
+    fn new(user: super::model::User, tokens: TokenPair) -> Self {
+        Self {
+            id: user.id,
+            name: user.name,
+            role: user.role,
+            token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
+            must_change_password: user.must_change_password,
+        }
+    }
+}
+
+/// 会话列表里用于辨认设备的 User-Agent
+fn user_agent(headers: &HeaderMap) -> &str {
+    headers
+        .get(USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+}
+
+#[utoipa::path(
+    post,
+    path = "/user/register",
+    summary = "注册",
+    request_body = LoginRequest,
+    responses((status = 200, description = "成功", body = LoginResponse))
+)]
 pub async fn register_handler(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> impl IntoResponse {
     match state
         .user
-        .register(payload.name, payload.password, &state.jwt_secret)
+        .register(
+            payload.name,
+            payload.password,
+            user_agent(&headers),
+            &state.jwt_secret,
+        )
         .await
     {
-        Ok((user, token)) => Json(LoginResponse {
-            id: user.id,
-            name: user.name,
-            role: user.role,
-            token,
-        })
-        .into_response(),
+        Ok((user, tokens)) => Json(LoginResponse::new(user, tokens)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/user/login",
+    summary = "登录",
+    request_body = LoginRequest,
+    responses((status = 200, description = "成功", body = LoginResponse))
+)]
 pub async fn login_handler(
     State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> impl IntoResponse {
     match state
         .user
-        .login(&payload.name, &payload.password, &state.jwt_secret)
+        .login(
+            &payload.name,
+            &payload.password,
+            &ip,
+            user_agent(&headers),
+            &state.jwt_secret,
+        )
         .await
     {
-        Ok((user, token)) => Json(LoginResponse {
-            id: user.id,
-            name: user.name,
-            role: user.role,
-            token,
-        })
-        .into_response(),
+        Ok((user, tokens)) => Json(LoginResponse::new(user, tokens)).into_response(),
         Err(e) => e.into_response(),
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/user",
+    summary = "用户列表",
+    responses((status = 200, description = "成功", body = Vec<UserListItem>))
+)]
 pub async fn user_handler(State(state): State<AppState>) -> impl IntoResponse {
     match state.user.list_all().await {
         Ok(users) => {
-            let user_list: Vec<HashMap<String, String>> = users
+            let user_list: Vec<UserListItem> = users
                 .into_iter()
-                .map(|u| {
-                    let mut m = HashMap::new();
-                    m.insert("id".to_string(), u.id.to_string());
-                    m.insert("name".to_string(), u.name);
-                    m.insert("role".to_string(), u.role);
-                    m
+                .map(|u| UserListItem {
+                    id: u.id.to_string(),
+                    name: u.name,
+                    role: u.role,
                 })
                 .collect();
             Json(user_list).into_response()
@@ -82,16 +137,36 @@ pub async fn user_handler(State(state): State<AppState>) -> impl IntoResponse {
     }
 }
 
-pub async fn logout_handler() -> impl IntoResponse {
-    Json(serde_json::json!({ "ok": true }))
+/// 注销当前会话，其 access token 立即失效
+#[utoipa::path(
+    post,
+    path = "/user/logout",
+    summary = "注销当前会话",
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    match state.session.revoke(claims.sub, &claims.sid).await {
+        Ok(_) => Json(serde_json::json!({ "ok": true })).into_response(),
+        Err(e) => internal(e, "注销"),
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChangePasswordRequest {
     pub old_password: String,
     pub new_password: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/user/password",
+    summary = "修改密码（注销全部会话）",
+    request_body = ChangePasswordRequest,
+    responses((status = 200, description = "成功", body = OkFlag))
+)]
 pub async fn change_password_handler(
     State(state): State<AppState>,
     Extension(claims): Extension<Claims>,
@@ -106,3 +181,81 @@ pub async fn change_password_handler(
         Err(e) => e.into_response(),
     }
 }
+
+// ── 管理员 ──
+
+/// GET /api/admin/users
+#[utoipa::path(
+    get,
+    path = "/users",
+    summary = "用户列表（含用量统计）",
+    responses((status = 200, description = "成功", body = Vec<UserSummary>))
+)]
+pub async fn admin_list_users_handler(State(state): State<AppState>) -> impl IntoResponse {
+    match state.user.admin_list().await {
+        Ok(users) => Json(users).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// PATCH /api/admin/users/{id}
+#[utoipa::path(
+    patch,
+    path = "/users/{id}",
+    summary = "修改角色 / 停用或启用账号",
+    request_body = UpdateUserRequest,
+    responses((status = 200, description = "成功", body = UserSummary))
+)]
+pub async fn admin_update_user_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> impl IntoResponse {
+    match state.user.admin_update(claims.sub, id, payload).await {
+        Ok(user) => Json(user).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/admin/users/{id}/password-reset
+#[utoipa::path(
+    post,
+    path = "/users/{id}/password-reset",
+    summary = "强制重置密码（返回临时密码）",
+    responses((status = 200, description = "成功", body = PasswordResetResponse))
+)]
+pub async fn admin_reset_password_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    match state.user.admin_reset_password(claims.sub, id).await {
+        Ok(temporary_password) => {
+            Json(PasswordResetResponse { temporary_password }).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/admin/users/{id} — 连同账号的全部数据
+#[utoipa::path(
+    delete,
+    path = "/users/{id}",
+    summary = "删除用户及其全部数据",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn admin_delete_user_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    if let Err(e) = ensure_not_self(claims.sub, id) {
+        return e.into_response();
+    }
+    match state.account.delete(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ServiceError::NotFound("用户不存在".into()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -3,8 +3,35 @@ mod model;
 mod repository;
 pub mod service;
 
+pub use model::UserStatus;
 pub use service::UserService;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
-pub use handler::{
-    change_password_handler, login_handler, logout_handler, register_handler, user_handler,
-};
+use crate::state::AppState;
+
+/// 注册 / 登录，公开路由
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::register_handler))
+        .routes(routes!(handler::login_handler))
+}
+
+/// 当前账号，仅限登录会话
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::user_handler))
+        .routes(routes!(handler::logout_handler))
+        .routes(routes!(handler::change_password_handler))
+}
+
+/// 管理员的用户管理路由，挂在 `/admin` 下
+pub fn admin_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(handler::admin_list_users_handler))
+        .routes(routes!(
+            handler::admin_update_user_handler,
+            handler::admin_delete_user_handler
+        ))
+        .routes(routes!(handler::admin_reset_password_handler))
+}
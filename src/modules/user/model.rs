@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
 pub struct User {
@@ -7,6 +8,10 @@ pub struct User {
     pub name: String,
     pub password_hash: String,
     pub role: String,
+    /// 已被管理员停用
+    pub disabled: bool,
+    /// 管理员重置密码后须先修改密码
+    pub must_change_password: bool,
 }
 
 impl User {
@@ -14,3 +19,53 @@ impl User {
     //     self.role == "admin"
     // }
 }
+
+/// 角色取值
+pub const ROLES: &[&str] = &["admin", "user"];
+
+/// auth 中间件每次请求读取的账号状态
+#[derive(Debug, Clone, FromRow)]
+pub struct UserStatus {
+    pub role: String,
+    pub disabled: bool,
+    pub must_change_password: bool,
+}
+
+/// 用户列表的一项（id 为字符串，沿用旧接口）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UserListItem {
+    pub id: String,
+    pub name: String,
+    pub role: String,
+}
+
+/// 管理员用户列表的一项：账号信息 + 用量统计
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct UserSummary {
+    pub id: i32,
+    pub name: String,
+    pub role: String,
+    pub disabled: bool,
+    pub must_change_password: bool,
+    pub mems: i64,
+    /// 复习记录条数
+    pub reviews: i64,
+    pub tasks: i64,
+    pub bookmarks: i64,
+    pub media_bytes: i64,
+    /// 最近一次使用会话的时间；从未登录为 None
+    pub last_active_at: Option<String>,
+}
+
+/// 管理员修改账号，字段缺省表示不变
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateUserRequest {
+    pub role: Option<String>,
+    pub disabled: Option<bool>,
+}
+
+/// 强制重置密码的结果：临时密码仅在此返回一次
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasswordResetResponse {
+    pub temporary_password: String,
+}
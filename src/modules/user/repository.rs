@@ -1,7 +1,9 @@
-use sqlx::{Row, SqlitePool};
+use sqlx::SqlitePool;
 use std::sync::Arc;
 
-use super::model::User;
+use super::model::{User, UserStatus, UserSummary};
+
+const USER_COLUMNS: &str = "id, name, password_hash, role, disabled, must_change_password";
 
 #[derive(Clone)]
 pub struct UserRepository {
@@ -14,24 +16,29 @@ impl UserRepository {
     }
 
     pub async fn find_all(&self) -> Result<Vec<User>, sqlx::Error> {
-        sqlx::query_as::<_, User>("SELECT id, name, password_hash, role FROM user ORDER BY id")
-            .fetch_all(&*self.db)
-            .await
+        sqlx::query_as::<_, User>(sqlx::AssertSqlSafe(format!(
+            "SELECT {USER_COLUMNS} FROM user ORDER BY id"
+        )))
+        .fetch_all(&*self.db)
+        .await
     }
 
-    #[allow(dead_code)]
     pub async fn find_by_id(&self, id: i32) -> Result<Option<User>, sqlx::Error> {
-        sqlx::query_as::<_, User>("SELECT id, name, password_hash, role FROM user WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&*self.db)
-            .await
+        sqlx::query_as::<_, User>(sqlx::AssertSqlSafe(format!(
+            "SELECT {USER_COLUMNS} FROM user WHERE id = ?"
+        )))
+        .bind(id)
+        .fetch_optional(&*self.db)
+        .await
     }
 
     pub async fn find_by_name(&self, name: &str) -> Result<Option<User>, sqlx::Error> {
-        sqlx::query_as::<_, User>("SELECT id, name, password_hash, role FROM user WHERE name = ?")
-            .bind(name)
-            .fetch_optional(&*self.db)
-            .await
+        sqlx::query_as::<_, User>(sqlx::AssertSqlSafe(format!(
+            "SELECT {USER_COLUMNS} FROM user WHERE name = ?"
+        )))
+        .bind(name)
+        .fetch_optional(&*self.db)
+        .await
     }
 
     pub async fn create(
@@ -40,17 +47,14 @@ impl UserRepository {
         password_hash: &str,
         role: &str,
     ) -> Result<User, sqlx::Error> {
-        let result = sqlx::query(
-            "INSERT INTO user (name, password_hash, role) VALUES (?, ?, ?) RETURNING id, name, password_hash, role"
-        )
-            .bind(name).bind(password_hash).bind(role)
-            .fetch_one(&*self.db).await?;
-        Ok(User {
-            id: result.try_get("id")?,
-            name: result.try_get("name")?,
-            password_hash: result.try_get("password_hash")?,
-            role: result.try_get("role")?,
-        })
+        sqlx::query_as::<_, User>(sqlx::AssertSqlSafe(format!(
+            "INSERT INTO user (name, password_hash, role) VALUES (?, ?, ?) RETURNING {USER_COLUMNS}"
+        )))
+        .bind(name)
+        .bind(password_hash)
+        .bind(role)
+        .fetch_one(&*self.db)
+        .await
     }
 
     pub async fn count(&self) -> Result<i64, sqlx::Error> {
@@ -59,12 +63,68 @@ impl UserRepository {
             .await
     }
 
-    pub async fn update_password(&self, id: i32, new_hash: &str) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE user SET password_hash = ? WHERE id = ?")
+    /// 更新密码；`must_change` 为 true 表示这是管理员设置的临时密码
+    pub async fn update_password(
+        &self,
+        id: i32,
+        new_hash: &str,
+        must_change: bool,
+    ) -> Result<u64, sqlx::Error> {
+        sqlx::query("UPDATE user SET password_hash = ?, must_change_password = ? WHERE id = ?")
             .bind(new_hash)
+            .bind(must_change)
+            .bind(id)
+            .execute(&*self.db)
+            .await
+            .map(|r| r.rows_affected())
+    }
+
+    pub async fn status(&self, id: i32) -> Result<Option<UserStatus>, sqlx::Error> {
+        sqlx::query_as::<_, UserStatus>(
+            "SELECT role, disabled, must_change_password FROM user WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&*self.db)
+        .await
+    }
+
+    pub async fn update_role(&self, id: i32, role: &str) -> Result<u64, sqlx::Error> {
+        sqlx::query("UPDATE user SET role = ? WHERE id = ?")
+            .bind(role)
+            .bind(id)
+            .execute(&*self.db)
+            .await
+            .map(|r| r.rows_affected())
+    }
+
+    pub async fn set_disabled(&self, id: i32, disabled: bool) -> Result<u64, sqlx::Error> {
+        sqlx::query("UPDATE user SET disabled = ? WHERE id = ?")
+            .bind(disabled)
             .bind(id)
             .execute(&*self.db)
-            .await?;
-        Ok(())
+            .await
+            .map(|r| r.rows_affected())
+    }
+
+    /// 账号列表附带用量统计；`id` 为 Some 时只取该用户
+    pub async fn summaries(&self, id: Option<i32>) -> Result<Vec<UserSummary>, sqlx::Error> {
+        sqlx::query_as::<_, UserSummary>(
+            "SELECT u.id, u.name, u.role, u.disabled, u.must_change_password,
+                    (SELECT COUNT(*) FROM mem WHERE user_id = u.id AND deleted_at IS NULL) AS mems,
+                    (SELECT COUNT(*) FROM revlog r JOIN mem m ON m.id = r.mem_id
+                      WHERE m.user_id = u.id) AS reviews,
+                    (SELECT COUNT(*) FROM task WHERE user_id = u.id AND deleted_at IS NULL) AS tasks,
+                    (SELECT COUNT(*) FROM bookmark WHERE user_id = u.id AND deleted_at IS NULL)
+                        AS bookmarks,
+                    (SELECT COALESCE(SUM(size_bytes), 0) FROM media WHERE user_id = u.id)
+                        AS media_bytes,
+                    (SELECT MAX(last_used_at) FROM session WHERE user_id = u.id) AS last_active_at
+             FROM user u
+             WHERE ?1 IS NULL OR u.id = ?1
+             ORDER BY u.id",
+        )
+        .bind(id)
+        .fetch_all(&*self.db)
+        .await
     }
 }
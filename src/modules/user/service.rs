@@ -2,20 +2,25 @@ use std::sync::Arc;
 
 use bcrypt::{DEFAULT_COST, hash, verify};
 
-use super::model::User;
+use super::model::{ROLES, UpdateUserRequest, User, UserStatus, UserSummary};
 use super::repository::UserRepository;
-use crate::auth::create_token;
 use crate::error::ServiceError;
+use crate::modules::session::{SessionService, TokenPair};
+use crate::rate_limit::LoginGuard;
 
 #[derive(Clone)]
 pub struct UserService {
     repo: UserRepository,
+    sessions: SessionService,
+    login_guard: LoginGuard,
 }
 
 impl UserService {
-    pub fn new(db: Arc<sqlx::SqlitePool>) -> Self {
+    pub fn new(db: Arc<sqlx::SqlitePool>, login_guard: LoginGuard) -> Self {
         Self {
-            repo: UserRepository::new(db),
+            repo: UserRepository::new(db.clone()),
+            sessions: SessionService::new(db),
+            login_guard,
         }
     }
 
@@ -23,8 +28,24 @@ impl UserService {
         &self,
         name: String,
         password: String,
+        user_agent: &str,
         jwt_secret: &str,
-    ) -> Result<(User, String), ServiceError> {
+    ) -> Result<(User, TokenPair), ServiceError> {
+        let user = self.create(name, password, None).await?;
+        let tokens = self
+            .sessions
+            .issue(user.id, &user.role, user_agent, jwt_secret)
+            .await?;
+        Ok((user, tokens))
+    }
+
+    /// 创建账号但不签发会话。`role` 省略时首个用户为管理员，其余为普通用户
+    pub async fn create(
+        &self,
+        name: String,
+        password: String,
+        role: Option<&str>,
+    ) -> Result<User, ServiceError> {
         let name = name.trim().to_string();
         let password = password.trim().to_string();
 
@@ -39,43 +60,67 @@ impl UserService {
             return Err(ServiceError::AlreadyExists("用户名已存在".into()));
         }
 
-        let role = match self.repo.count().await {
-            Ok(0) => "admin",
-            _ => "user",
+        let role = match (role, self.repo.count().await) {
+            (Some(role), _) => role,
+            (None, Ok(0)) => "admin",
+            (None, _) => "user",
         };
 
         let password_hash =
             hash(&password, DEFAULT_COST).map_err(|e| ServiceError::Internal(e.to_string()))?;
 
-        let user = self
-            .repo
+        self.repo
             .create(&name, &password_hash, role)
             .await
-            .map_err(ServiceError::Db)?;
+            .map_err(ServiceError::Db)
+    }
 
-        let token = create_token(user.id, &user.role, jwt_secret);
-        Ok((user, token))
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<User>, ServiceError> {
+        Ok(self.repo.find_by_name(name).await?)
     }
 
     pub async fn login(
         &self,
         name: &str,
         password: &str,
+        ip: &str,
+        user_agent: &str,
         jwt_secret: &str,
-    ) -> Result<(User, String), ServiceError> {
-        let user = self
+    ) -> Result<(User, TokenPair), ServiceError> {
+        // 锁定期间不校验密码，避免被继续试探
+        self.login_guard
+            .check(ip, name)
+            .map_err(ServiceError::RateLimited)?;
+
+        let user = match self
             .repo
             .find_by_name(name.trim())
             .await
             .map_err(ServiceError::Db)?
-            .ok_or_else(|| ServiceError::InvalidInput("用户名或密码错误".into()))?;
+        {
+            Some(user) => user,
+            None => {
+                self.login_guard.record_failure(ip, name);
+                return Err(ServiceError::InvalidInput("用户名或密码错误".into()));
+            }
+        };
 
         match verify(password, &user.password_hash) {
             Ok(true) => {
-                let token = create_token(user.id, &user.role, jwt_secret);
-                Ok((user, token))
+                self.login_guard.record_success(ip, name);
+                if user.disabled {
+                    return Err(ServiceError::Forbidden("账号已停用".into()));
+                }
+                let tokens = self
+                    .sessions
+                    .issue(user.id, &user.role, user_agent, jwt_secret)
+                    .await?;
+                Ok((user, tokens))
+            }
+            Ok(false) => {
+                self.login_guard.record_failure(ip, name);
+                Err(ServiceError::InvalidInput("用户名或密码错误".into()))
             }
-            Ok(false) => Err(ServiceError::InvalidInput("用户名或密码错误".into())),
             Err(e) => Err(ServiceError::Internal(e.to_string())),
         }
     }
@@ -111,12 +156,92 @@ impl UserService {
             hash(new_password, DEFAULT_COST).map_err(|e| ServiceError::Internal(e.to_string()))?;
 
         self.repo
-            .update_password(user_id, &new_hash)
+            .update_password(user_id, &new_hash, false)
             .await
-            .map_err(ServiceError::Db)
+            .map_err(ServiceError::Db)?;
+
+        // 旧密码签发的会话全部作废
+        self.sessions.revoke_all(user_id).await?;
+        Ok(())
+    }
+
+    /// 账号当前的角色与状态；账号已删除时为 None
+    pub async fn status(&self, user_id: i32) -> Result<Option<UserStatus>, sqlx::Error> {
+        self.repo.status(user_id).await
+    }
+
+    // ── 管理员操作 ──
+    //
+    // 管理员不能对自己执行以下操作：require_admin 保证操作者是在用的管理员，
+    // 因此系统中始终至少保留一个可登录的管理员。
+
+    pub async fn admin_list(&self) -> Result<Vec<UserSummary>, ServiceError> {
+        Ok(self.repo.summaries(None).await?)
+    }
+
+    /// 修改角色 / 停用状态。停用时吊销全部会话；角色变更由 auth 中间件即时生效
+    pub async fn admin_update(
+        &self,
+        actor_id: i32,
+        id: i32,
+        req: UpdateUserRequest,
+    ) -> Result<UserSummary, ServiceError> {
+        ensure_not_self(actor_id, id)?;
+        if let Some(role) = &req.role
+            && !ROLES.contains(&role.as_str())
+        {
+            return Err(ServiceError::InvalidInput(format!(
+                "role 须为 {} 之一",
+                ROLES.join(" / ")
+            )));
+        }
+        if self.repo.find_by_id(id).await?.is_none() {
+            return Err(ServiceError::NotFound("用户不存在".into()));
+        }
+
+        if let Some(role) = &req.role {
+            self.repo.update_role(id, role).await?;
+        }
+        if let Some(disabled) = req.disabled {
+            self.repo.set_disabled(id, disabled).await?;
+            if disabled {
+                self.sessions.revoke_all(id).await?;
+            }
+        }
+        self.repo
+            .summaries(Some(id))
+            .await?
+            .pop()
+            .ok_or_else(|| ServiceError::NotFound("用户不存在".into()))
+    }
+
+    /// 强制重置密码：设置一次性临时密码并吊销全部会话，返回临时密码。
+    /// 用户用临时密码登录后须先修改密码
+    pub async fn admin_reset_password(
+        &self,
+        actor_id: i32,
+        id: i32,
+    ) -> Result<String, ServiceError> {
+        ensure_not_self(actor_id, id)?;
+        let temporary = nanoid::nanoid!(12);
+        let new_hash =
+            hash(&temporary, DEFAULT_COST).map_err(|e| ServiceError::Internal(e.to_string()))?;
+        if self.repo.update_password(id, &new_hash, true).await? == 0 {
+            return Err(ServiceError::NotFound("用户不存在".into()));
+        }
+        self.sessions.revoke_all(id).await?;
+        Ok(temporary)
     }
 }
 
+/// 管理员操作的目标不能是自己
+pub fn ensure_not_self(actor_id: i32, id: i32) -> Result<(), ServiceError> {
+    if actor_id == id {
+        return Err(ServiceError::InvalidInput("不能对自己执行该操作".into()));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used)]
@@ -127,30 +252,30 @@ mod tests {
 
     async fn setup() -> UserService {
         let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
-        sqlx::query("CREATE TABLE user (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, password_hash TEXT NOT NULL, role TEXT NOT NULL DEFAULT 'user')")
-            .execute(&*pool).await.unwrap();
-        UserService::new(pool)
+        crate::db::migrate::run(&pool).await.unwrap();
+        UserService::new(pool, LoginGuard::default())
     }
 
     #[tokio::test]
     async fn register_first_user_is_admin() {
         let svc = setup().await;
-        let (user, token) = svc
-            .register("admin".into(), "pass1234".into(), TEST_SECRET)
+        let (user, tokens) = svc
+            .register("admin".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         assert_eq!(user.role, "admin");
-        assert!(!token.is_empty());
+        assert!(!tokens.access_token.is_empty());
+        assert!(!tokens.refresh_token.is_empty());
     }
 
     #[tokio::test]
     async fn register_second_user_is_user() {
         let svc = setup().await;
-        svc.register("admin".into(), "pass1234".into(), TEST_SECRET)
+        svc.register("admin".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         let (user, _) = svc
-            .register("user1".into(), "pass1234".into(), TEST_SECRET)
+            .register("user1".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         assert_eq!(user.role, "user");
@@ -160,7 +285,7 @@ mod tests {
     async fn register_empty_name_rejected() {
         let svc = setup().await;
         let err = svc
-            .register("  ".into(), "pass1234".into(), TEST_SECRET)
+            .register("  ".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
@@ -170,7 +295,7 @@ mod tests {
     async fn register_short_password_rejected() {
         let svc = setup().await;
         let err = svc
-            .register("user".into(), "abc".into(), TEST_SECRET)
+            .register("user".into(), "abc".into(), "ua", TEST_SECRET)
             .await
             .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
@@ -179,11 +304,11 @@ mod tests {
     #[tokio::test]
     async fn register_duplicate_name() {
         let svc = setup().await;
-        svc.register("alice".into(), "pass1234".into(), TEST_SECRET)
+        svc.register("alice".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         let err = svc
-            .register("alice".into(), "other123".into(), TEST_SECRET)
+            .register("alice".into(), "other123".into(), "ua", TEST_SECRET)
             .await
             .unwrap_err();
         assert!(matches!(err, ServiceError::AlreadyExists(_)));
@@ -192,38 +317,72 @@ mod tests {
     #[tokio::test]
     async fn login_success() {
         let svc = setup().await;
-        svc.register("bob".into(), "secret123".into(), TEST_SECRET)
+        svc.register("bob".into(), "secret123".into(), "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        let (user, tokens) = svc
+            .login("bob", "secret123", "127.0.0.1", "ua", TEST_SECRET)
             .await
             .unwrap();
-        let (user, token) = svc.login("bob", "secret123", TEST_SECRET).await.unwrap();
         assert_eq!(user.name, "bob");
-        assert!(!token.is_empty());
+        assert!(!tokens.access_token.is_empty());
     }
 
     #[tokio::test]
     async fn login_wrong_password() {
         let svc = setup().await;
-        svc.register("bob".into(), "correct".into(), TEST_SECRET)
+        svc.register("bob".into(), "correct".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
-        let err = svc.login("bob", "wrong", TEST_SECRET).await.unwrap_err();
+        let err = svc
+            .login("bob", "wrong", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
     }
 
     #[tokio::test]
     async fn login_nonexistent_user() {
         let svc = setup().await;
-        let err = svc.login("nobody", "pass", TEST_SECRET).await.unwrap_err();
+        let err = svc
+            .login("nobody", "pass", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
     }
 
+    #[tokio::test]
+    async fn repeated_failures_lock_login() {
+        let svc = setup().await;
+        svc.register("eve".into(), "correct".into(), "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        for _ in 0..5 {
+            let err = svc
+                .login("eve", "wrong", "127.0.0.1", "ua", TEST_SECRET)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, ServiceError::InvalidInput(_)));
+        }
+        // 锁定期间即使密码正确也拒绝
+        let err = svc
+            .login("eve", "correct", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::RateLimited(secs) if secs > 0));
+        // 只锁定失败来自的 IP，账号本人换个 IP 仍可登录
+        svc.login("eve", "correct", "203.0.113.7", "ua", TEST_SECRET)
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn list_all_users() {
         let svc = setup().await;
-        svc.register("a".into(), "pass1234".into(), TEST_SECRET)
+        svc.register("a".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
-        svc.register("b".into(), "pass1234".into(), TEST_SECRET)
+        svc.register("b".into(), "pass1234".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         let users = svc.list_all().await.unwrap();
@@ -234,22 +393,45 @@ mod tests {
     async fn change_password_success() {
         let svc = setup().await;
         let (user, _) = svc
-            .register("alice".into(), "oldPass1".into(), TEST_SECRET)
+            .register("alice".into(), "oldPass1".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         svc.change_password(user.id, "oldPass1", "newPass2")
             .await
             .unwrap();
         // 用新密码登录验证
-        let (_, token) = svc.login("alice", "newPass2", TEST_SECRET).await.unwrap();
-        assert!(!token.is_empty());
+        let (_, tokens) = svc
+            .login("alice", "newPass2", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        assert!(!tokens.access_token.is_empty());
+    }
+
+    #[tokio::test]
+    async fn change_password_revokes_sessions() {
+        let svc = setup().await;
+        let (user, tokens) = svc
+            .register("alice".into(), "oldPass1".into(), "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        svc.change_password(user.id, "oldPass1", "newPass2")
+            .await
+            .unwrap();
+
+        assert!(
+            svc.sessions
+                .refresh(&tokens.refresh_token, TEST_SECRET)
+                .await
+                .is_err()
+        );
+        assert!(svc.sessions.list(user.id, "").await.unwrap().is_empty());
     }
 
     #[tokio::test]
     async fn change_password_wrong_old() {
         let svc = setup().await;
         let (user, _) = svc
-            .register("alice".into(), "realPass".into(), TEST_SECRET)
+            .register("alice".into(), "realPass".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         let err = svc
@@ -263,7 +445,7 @@ mod tests {
     async fn change_password_short_new() {
         let svc = setup().await;
         let (user, _) = svc
-            .register("alice".into(), "realPass".into(), TEST_SECRET)
+            .register("alice".into(), "realPass".into(), "ua", TEST_SECRET)
             .await
             .unwrap();
         let err = svc
@@ -272,4 +454,172 @@ mod tests {
             .unwrap_err();
         assert!(matches!(err, ServiceError::InvalidInput(_)));
     }
+
+    /// 管理员 + 普通用户
+    async fn setup_admin() -> (UserService, User, User, TokenPair) {
+        let svc = setup().await;
+        let (admin, _) = svc
+            .register("admin".into(), "pass1234".into(), "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        let (user, tokens) = svc
+            .register("alice".into(), "pass1234".into(), "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        (svc, admin, user, tokens)
+    }
+
+    #[tokio::test]
+    async fn admin_list_includes_usage() {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let svc = UserService::new(pool.clone(), LoginGuard::default());
+        for name in ["admin", "alice"] {
+            svc.register(name.into(), "pass1234".into(), "ua", TEST_SECRET)
+                .await
+                .unwrap();
+        }
+        let user = svc.repo.find_by_name("alice").await.unwrap().unwrap();
+        sqlx::query("INSERT INTO bookmark (title, url, user_id) VALUES ('b', 'https://b', ?)")
+            .bind(user.id)
+            .execute(&*pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO media (stored_id, original_name, media_type, mime_type, size_bytes, user_id)
+             VALUES ('m1', 'a.png', 'image', 'image/png', 1024, ?)",
+        )
+        .bind(user.id)
+        .execute(&*pool)
+        .await
+        .unwrap();
+
+        let users = svc.admin_list().await.unwrap();
+        assert_eq!(users.len(), 2);
+        let alice = users.iter().find(|u| u.id == user.id).unwrap();
+        assert_eq!(alice.bookmarks, 1);
+        assert_eq!(alice.media_bytes, 1024);
+        assert_eq!(alice.mems, 0);
+        assert!(alice.last_active_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn admin_update_role_and_disable() {
+        let (svc, admin, user, tokens) = setup_admin().await;
+        let updated = svc
+            .admin_update(
+                admin.id,
+                user.id,
+                UpdateUserRequest {
+                    role: Some("admin".into()),
+                    disabled: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.role, "admin");
+        assert!(!updated.disabled);
+
+        let updated = svc
+            .admin_update(
+                admin.id,
+                user.id,
+                UpdateUserRequest {
+                    role: None,
+                    disabled: Some(true),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(updated.disabled);
+        assert!(svc.status(user.id).await.unwrap().unwrap().disabled);
+        // 停用即吊销会话，且不能再登录
+        assert!(
+            svc.sessions
+                .refresh(&tokens.refresh_token, TEST_SECRET)
+                .await
+                .is_err()
+        );
+        let err = svc
+            .login("alice", "pass1234", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Forbidden(_)));
+
+        let req = UpdateUserRequest {
+            disabled: Some(false),
+            ..Default::default()
+        };
+        svc.admin_update(admin.id, user.id, req).await.unwrap();
+        svc.login("alice", "pass1234", "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn admin_update_rejects_self_unknown_role_and_missing_user() {
+        let (svc, admin, user, _) = setup_admin().await;
+        let demote = || UpdateUserRequest {
+            role: Some("user".into()),
+            disabled: None,
+        };
+        assert!(matches!(
+            svc.admin_update(admin.id, admin.id, demote()).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            svc.admin_reset_password(admin.id, admin.id).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+        let bad_role = UpdateUserRequest {
+            role: Some("root".into()),
+            disabled: None,
+        };
+        assert!(matches!(
+            svc.admin_update(admin.id, user.id, bad_role).await,
+            Err(ServiceError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            svc.admin_update(admin.id, 999, demote()).await,
+            Err(ServiceError::NotFound(_))
+        ));
+        assert!(matches!(
+            svc.admin_reset_password(admin.id, 999).await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn admin_reset_password_requires_change() {
+        let (svc, admin, user, tokens) = setup_admin().await;
+        let temporary = svc.admin_reset_password(admin.id, user.id).await.unwrap();
+        assert!(
+            svc.sessions
+                .refresh(&tokens.refresh_token, TEST_SECRET)
+                .await
+                .is_err()
+        );
+        assert!(
+            svc.login("alice", "pass1234", "127.0.0.1", "ua", TEST_SECRET)
+                .await
+                .is_err()
+        );
+
+        let (logged_in, _) = svc
+            .login("alice", &temporary, "127.0.0.1", "ua", TEST_SECRET)
+            .await
+            .unwrap();
+        assert!(logged_in.must_change_password);
+
+        svc.change_password(user.id, &temporary, "newPass1")
+            .await
+            .unwrap();
+        assert!(
+            !svc.status(user.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .must_change_password
+        );
+    }
 }
@@ -0,0 +1,120 @@
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+
+use super::model::{
+    CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, WebhookDelivery, WebhookInfo,
+};
+use crate::auth::Claims;
+use crate::error::{deleted_or, ok_or};
+use crate::pagination::{PaginatedResponse, Pagination};
+use crate::state::AppState;
+
+/// GET /api/webhooks
+#[utoipa::path(
+    get,
+    path = "/",
+    summary = "webhook 订阅列表",
+    responses((status = 200, description = "成功", body = Vec<WebhookInfo>))
+)]
+pub async fn list_webhooks_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    ok_or(state.webhook.list(claims.sub).await, "获取 webhook")
+}
+
+/// POST /api/webhooks — 密钥仅在响应中出现一次
+#[utoipa::path(
+    post,
+    path = "/",
+    summary = "创建 webhook 订阅（密钥仅返回一次）",
+    request_body = CreateWebhookRequest,
+    responses((status = 201, description = "已创建", body = CreatedWebhook))
+)]
+pub async fn create_webhook_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> impl IntoResponse {
+    match state.webhook.create(claims.sub, payload).await {
+        Ok(created) => (StatusCode::CREATED, Json(created)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// PATCH /api/webhooks/{id}
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    summary = "修改 webhook 订阅",
+    request_body = UpdateWebhookRequest,
+    responses((status = 200, description = "成功", body = WebhookInfo))
+)]
+pub async fn update_webhook_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> impl IntoResponse {
+    match state.webhook.update(claims.sub, id, payload).await {
+        Ok(hook) => Json(hook).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/webhooks/{id}
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    summary = "删除 webhook 订阅",
+    responses((status = 204, description = "成功"))
+)]
+pub async fn delete_webhook_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    deleted_or(state.webhook.delete(claims.sub, id).await, "删除 webhook")
+}
+
+/// POST /api/webhooks/{id}/ping
+#[utoipa::path(
+    post,
+    path = "/{id}/ping",
+    summary = "发送测试推送",
+    responses((status = 202, description = "已排队", body = WebhookDelivery))
+)]
+pub async fn ping_webhook_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+) -> impl IntoResponse {
+    match state.webhook.ping(claims.sub, id).await {
+        Ok(delivery) => (StatusCode::ACCEPTED, Json(delivery)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/webhooks/{id}/deliveries
+#[utoipa::path(
+    get,
+    path = "/{id}/deliveries",
+    summary = "投递记录",
+    params(Pagination),
+    responses((status = 200, description = "成功", body = PaginatedResponse<WebhookDelivery>))
+)]
+pub async fn list_deliveries_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<i32>,
+    Query(pagination): Query<Pagination>,
+) -> impl IntoResponse {
+    match state.webhook.deliveries(claims.sub, id, &pagination).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,31 @@
+//! 出站 webhook。
+//!
+//! 用户订阅领域事件（任务完成、mem 变为 leech、新建书签、上传媒体），
+//! 事件发生时经 [`Webhooks`] 写入投递记录并排队 `webhook_deliver` 任务，
+//! 以 HMAC-SHA256 签名的 JSON POST 到订阅 URL，失败按任务队列的退避重试。
+
+mod handler;
+pub mod model;
+mod repository;
+pub mod service;
+
+pub use model::WebhookEvent;
+pub use service::{WebhookService, Webhooks};
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            handler::list_webhooks_handler,
+            handler::create_webhook_handler
+        ))
+        .routes(routes!(
+            handler::update_webhook_handler,
+            handler::delete_webhook_handler
+        ))
+        .routes(routes!(handler::ping_webhook_handler))
+        .routes(routes!(handler::list_deliveries_handler))
+}
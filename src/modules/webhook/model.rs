@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// 可订阅的领域事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// 任务被标记为完成
+    TaskCompleted,
+    /// mem 连续遗忘被标记为 leech
+    MemLeeched,
+    /// 新建书签
+    BookmarkCreated,
+    /// 上传媒体文件
+    MediaUploaded,
+    /// 手动测试推送，总是投递，不需要订阅
+    Ping,
+}
+
+impl WebhookEvent {
+    /// 可出现在订阅过滤中的事件（不含 ping）
+    pub const SUBSCRIBABLE: [WebhookEvent; 4] = [
+        WebhookEvent::TaskCompleted,
+        WebhookEvent::MemLeeched,
+        WebhookEvent::BookmarkCreated,
+        WebhookEvent::MediaUploaded,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::TaskCompleted => "task.completed",
+            WebhookEvent::MemLeeched => "mem.leeched",
+            WebhookEvent::BookmarkCreated => "bookmark.created",
+            WebhookEvent::MediaUploaded => "media.uploaded",
+            WebhookEvent::Ping => "ping",
+        }
+    }
+}
+
+/// 订阅过滤：具体事件名或 `*`
+pub fn is_valid_filter(filter: &str) -> bool {
+    filter == "*"
+        || WebhookEvent::SUBSCRIBABLE
+            .iter()
+            .any(|e| e.as_str() == filter)
+}
+
+/// 空格分隔的过滤列表是否包含该事件
+pub fn filter_matches(events: &str, event: WebhookEvent) -> bool {
+    event == WebhookEvent::Ping
+        || events
+            .split_whitespace()
+            .any(|f| f == "*" || f == event.as_str())
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookRow {
+    pub id: i32,
+    pub url: String,
+    pub events: String,
+    pub active: bool,
+    pub description: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// 订阅列表项（不含密钥）
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookInfo {
+    pub id: i32,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub description: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+impl From<WebhookRow> for WebhookInfo {
+    fn from(r: WebhookRow) -> Self {
+        Self {
+            id: r.id,
+            url: r.url,
+            events: r.events.split_whitespace().map(String::from).collect(),
+            active: r.active,
+            description: r.description,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+/// 创建成功的响应：密钥只出现这一次
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub info: WebhookInfo,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    /// 不填则随机生成
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct DeliveryRow {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+/// 投递记录
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event: String,
+    pub payload: Value,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+impl From<DeliveryRow> for WebhookDelivery {
+    fn from(r: DeliveryRow) -> Self {
+        Self {
+            id: r.id,
+            webhook_id: r.webhook_id,
+            event: r.event,
+            payload: serde_json::from_str(&r.payload).unwrap_or(Value::Null),
+            status: r.status,
+            attempts: r.attempts,
+            response_status: r.response_status,
+            response_body: r.response_body,
+            error: r.error,
+            duration_ms: r.duration_ms,
+            created_at: r.created_at,
+            delivered_at: r.delivered_at,
+        }
+    }
+}
+
+/// 投递时所需的目标信息
+#[derive(Debug, Clone, FromRow)]
+pub struct DeliveryTarget {
+    pub url: String,
+    pub secret: String,
+    pub event: String,
+    pub payload: String,
+}
+
+/// 一次投递尝试的结果
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    pub status: &'static str,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters() {
+        assert!(is_valid_filter("*"));
+        assert!(is_valid_filter("task.completed"));
+        assert!(!is_valid_filter("ping"));
+        assert!(!is_valid_filter("task.deleted"));
+    }
+
+    #[test]
+    fn filter_matching() {
+        assert!(filter_matches("*", WebhookEvent::MemLeeched));
+        assert!(filter_matches(
+            "task.completed media.uploaded",
+            WebhookEvent::MediaUploaded
+        ));
+        assert!(!filter_matches(
+            "task.completed",
+            WebhookEvent::BookmarkCreated
+        ));
+        // ping 不受过滤影响
+        assert!(filter_matches("task.completed", WebhookEvent::Ping));
+    }
+}
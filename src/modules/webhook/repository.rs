@@ -0,0 +1,183 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::model::{Attempt, DeliveryRow, DeliveryTarget, UpdateWebhookRequest, WebhookRow};
+
+/// 不含密钥：只有投递时经 [`WebhookRepo::delivery_target`] 读取
+const COLUMNS: &str = "id, url, events, active, description, created_at, updated_at";
+
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event, payload, status, attempts, response_status, \
+     response_body, error, duration_ms, created_at, delivered_at";
+
+#[derive(Clone)]
+pub struct WebhookRepo {
+    pool: Arc<SqlitePool>,
+}
+
+impl WebhookRepo {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i32,
+        url: &str,
+        secret: &str,
+        events: &str,
+        description: &str,
+    ) -> Result<WebhookRow, sqlx::Error> {
+        sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "INSERT INTO webhook (user_id, url, secret, events, description)
+             VALUES (?, ?, ?, ?, ?) RETURNING {COLUMNS}"
+        )))
+        .bind(user_id)
+        .bind(url)
+        .bind(secret)
+        .bind(events)
+        .bind(description)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn list(&self, user_id: i32) -> Result<Vec<WebhookRow>, sqlx::Error> {
+        sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {COLUMNS} FROM webhook WHERE user_id = ? ORDER BY id DESC"
+        )))
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn find(&self, user_id: i32, id: i32) -> Result<Option<WebhookRow>, sqlx::Error> {
+        sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {COLUMNS} FROM webhook WHERE id = ? AND user_id = ?"
+        )))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// 只更新提供了的字段；`events` 已序列化为空格分隔
+    pub async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        req: &UpdateWebhookRequest,
+        events: Option<&str>,
+    ) -> Result<Option<WebhookRow>, sqlx::Error> {
+        sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "UPDATE webhook SET
+                 url = COALESCE(?, url),
+                 events = COALESCE(?, events),
+                 active = COALESCE(?, active),
+                 description = COALESCE(?, description),
+                 updated_at = strftime('%Y-%m-%dT%H:%M:%SZ', 'now')
+             WHERE id = ? AND user_id = ?
+             RETURNING {COLUMNS}"
+        )))
+        .bind(req.url.as_deref())
+        .bind(events)
+        .bind(req.active)
+        .bind(req.description.as_deref())
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM webhook WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 用户启用中的订阅，事件过滤由调用方完成
+    pub async fn active_for_user(&self, user_id: i32) -> Result<Vec<WebhookRow>, sqlx::Error> {
+        sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {COLUMNS} FROM webhook WHERE user_id = ? AND active = 1"
+        )))
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    // ── 投递记录 ──
+
+    pub async fn insert_delivery(
+        &self,
+        webhook_id: i32,
+        event: &str,
+        payload: &str,
+    ) -> Result<DeliveryRow, sqlx::Error> {
+        sqlx::query_as::<_, DeliveryRow>(sqlx::AssertSqlSafe(format!(
+            "INSERT INTO webhook_delivery (webhook_id, event, payload)
+             VALUES (?, ?, ?) RETURNING {DELIVERY_COLUMNS}"
+        )))
+        .bind(webhook_id)
+        .bind(event)
+        .bind(payload)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    /// 投递目标；订阅已删除或停用时为 None
+    pub async fn delivery_target(&self, id: i64) -> Result<Option<DeliveryTarget>, sqlx::Error> {
+        sqlx::query_as::<_, DeliveryTarget>(
+            "SELECT w.url, w.secret, d.event, d.payload
+             FROM webhook_delivery d JOIN webhook w ON w.id = d.webhook_id
+             WHERE d.id = ? AND w.active = 1",
+        )
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn record_attempt(&self, id: i64, attempt: &Attempt) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE webhook_delivery SET
+                 status = ?, attempts = attempts + 1, response_status = ?, response_body = ?,
+                 error = ?, duration_ms = ?,
+                 delivered_at = CASE WHEN ? = 'succeeded'
+                     THEN strftime('%Y-%m-%dT%H:%M:%SZ', 'now') END
+             WHERE id = ?",
+        )
+        .bind(attempt.status)
+        .bind(attempt.response_status)
+        .bind(attempt.response_body.as_deref())
+        .bind(attempt.error.as_deref())
+        .bind(attempt.duration_ms)
+        .bind(attempt.status)
+        .bind(id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<DeliveryRow>, i64), sqlx::Error> {
+        let rows = sqlx::query_as::<_, DeliveryRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {DELIVERY_COLUMNS} FROM webhook_delivery
+             WHERE webhook_id = ? ORDER BY id DESC LIMIT ? OFFSET ?"
+        )))
+        .bind(webhook_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&*self.pool)
+        .await?;
+        let total: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM webhook_delivery WHERE webhook_id = ?")
+                .bind(webhook_id)
+                .fetch_one(&*self.pool)
+                .await?;
+        Ok((rows, total))
+    }
+}
@@ -0,0 +1,549 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use tracing::warn;
+
+use super::model::{
+    Attempt, CreateWebhookRequest, CreatedWebhook, DeliveryRow, UpdateWebhookRequest,
+    WebhookDelivery, WebhookEvent, WebhookInfo, WebhookRow, filter_matches, is_valid_filter,
+};
+use super::repository::WebhookRepo;
+use crate::error::ServiceError;
+use crate::modules::job::{JobKind, JobService, NewJob};
+use crate::pagination::{PaginatedResponse, Pagination};
+
+/// `sha256=<hex>`，对请求体原文计算
+pub const SIGNATURE_HEADER: &str = "X-Brainbow-Signature";
+pub const EVENT_HEADER: &str = "X-Brainbow-Event";
+pub const DELIVERY_HEADER: &str = "X-Brainbow-Delivery";
+
+/// 每次投递（含首次）最多尝试次数，间隔见 [`crate::modules::job::service::backoff`]
+const MAX_ATTEMPTS: i32 = 5;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// 投递记录中保留的响应体长度
+const MAX_RESPONSE_BODY: usize = 1024;
+const MAX_URL_LEN: usize = 2048;
+
+/// HMAC-SHA256 签名，格式 `sha256=<hex>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+/// 领域事件推送入口：各 service 在操作成功后调用，为匹配的订阅排队投递。
+/// 与审计日志一样尽力而为，失败只记录日志，不影响业务操作。
+#[derive(Clone)]
+pub struct Webhooks {
+    repo: WebhookRepo,
+    jobs: JobService,
+}
+
+impl Webhooks {
+    pub fn new(db: Arc<SqlitePool>, jobs: JobService) -> Self {
+        Self {
+            repo: WebhookRepo::new(db),
+            jobs,
+        }
+    }
+
+    pub async fn emit(&self, user_id: i32, event: WebhookEvent, data: &impl Serialize) {
+        if let Err(e) = self.try_emit(user_id, event, data).await {
+            warn!("webhook 事件 {} 排队失败: {e}", event.as_str());
+        }
+    }
+
+    async fn try_emit(
+        &self,
+        user_id: i32,
+        event: WebhookEvent,
+        data: &impl Serialize,
+    ) -> Result<usize, ServiceError> {
+        let hooks: Vec<WebhookRow> = self
+            .repo
+            .active_for_user(user_id)
+            .await?
+            .into_iter()
+            .filter(|h| filter_matches(&h.events, event))
+            .collect();
+        if hooks.is_empty() {
+            return Ok(0);
+        }
+        let payload = envelope(event, data)?;
+        for hook in &hooks {
+            self.queue(user_id, hook.id, event, &payload).await?;
+        }
+        Ok(hooks.len())
+    }
+
+    /// 写投递记录并排队 `webhook_deliver` 任务
+    async fn queue(
+        &self,
+        user_id: i32,
+        webhook_id: i32,
+        event: WebhookEvent,
+        payload: &str,
+    ) -> Result<DeliveryRow, ServiceError> {
+        let delivery = self
+            .repo
+            .insert_delivery(webhook_id, event.as_str(), payload)
+            .await?;
+        let job = NewJob::new(JobKind::WebhookDeliver)
+            .payload(json!({ "delivery_id": delivery.id }))
+            .user(user_id)
+            .max_attempts(MAX_ATTEMPTS);
+        self.jobs.enqueue(job).await?;
+        Ok(delivery)
+    }
+}
+
+/// 请求体：`{"event", "created_at", "data"}`
+fn envelope(event: WebhookEvent, data: &impl Serialize) -> Result<String, ServiceError> {
+    let data = serde_json::to_value(data).map_err(|e| ServiceError::Internal(e.to_string()))?;
+    Ok(json!({
+        "event": event.as_str(),
+        "created_at": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "data": data,
+    })
+    .to_string())
+}
+
+/// 订阅管理与投递
+#[derive(Clone)]
+pub struct WebhookService {
+    repo: WebhookRepo,
+    hooks: Webhooks,
+    client: reqwest::Client,
+}
+
+impl WebhookService {
+    pub fn new(db: Arc<SqlitePool>, hooks: Webhooks) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent("Brainbow-Webhook/1.0")
+            .build()
+            .unwrap_or_default();
+        Self {
+            repo: WebhookRepo::new(db),
+            hooks,
+            client,
+        }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i32,
+        req: CreateWebhookRequest,
+    ) -> Result<CreatedWebhook, ServiceError> {
+        let url = validate_url(&req.url)?;
+        let events = normalize_events(&req.events)?;
+        let secret = match req.secret.map(|s| s.trim().to_string()) {
+            Some(s) if s.len() < 16 => {
+                return Err(ServiceError::InvalidInput("密钥至少 16 个字符".into()));
+            }
+            Some(s) => s,
+            None => nanoid::nanoid!(32),
+        };
+        let row = self
+            .repo
+            .create(user_id, url, &secret, &events, req.description.trim())
+            .await?;
+        Ok(CreatedWebhook {
+            info: row.into(),
+            secret,
+        })
+    }
+
+    pub async fn list(&self, user_id: i32) -> Result<Vec<WebhookInfo>, sqlx::Error> {
+        Ok(self
+            .repo
+            .list(user_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        mut req: UpdateWebhookRequest,
+    ) -> Result<WebhookInfo, ServiceError> {
+        if let Some(url) = &req.url {
+            req.url = Some(validate_url(url)?.to_string());
+        }
+        let events = req.events.as_deref().map(normalize_events).transpose()?;
+        self.repo
+            .update(user_id, id, &req, events.as_deref())
+            .await?
+            .map(Into::into)
+            .ok_or_else(not_found)
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> Result<u64, sqlx::Error> {
+        self.repo.delete(user_id, id).await
+    }
+
+    /// 排队一次 `ping` 推送，用于验证接收端与签名
+    pub async fn ping(&self, user_id: i32, id: i32) -> Result<WebhookDelivery, ServiceError> {
+        let hook = self.repo.find(user_id, id).await?.ok_or_else(not_found)?;
+        let payload = envelope(WebhookEvent::Ping, &json!({ "webhook_id": hook.id }))?;
+        let delivery = self
+            .hooks
+            .queue(user_id, hook.id, WebhookEvent::Ping, &payload)
+            .await?;
+        Ok(delivery.into())
+    }
+
+    pub async fn deliveries(
+        &self,
+        user_id: i32,
+        id: i32,
+        pagination: &Pagination,
+    ) -> Result<PaginatedResponse<WebhookDelivery>, ServiceError> {
+        self.repo.find(user_id, id).await?.ok_or_else(not_found)?;
+        let (rows, total) = self
+            .repo
+            .list_deliveries(id, pagination.limit(), pagination.offset())
+            .await?;
+        let items = rows.into_iter().map(Into::into).collect();
+        Ok(PaginatedResponse::new(items, total, pagination))
+    }
+
+    /// 执行一次投递尝试（`webhook_deliver` 任务）。非 2xx 或网络错误返回 Err，
+    /// 由任务队列退避重试；`last_attempt` 时把投递记录标记为 failed。
+    pub async fn deliver(&self, delivery_id: i64, last_attempt: bool) -> Result<Value, String> {
+        let Some(target) = self
+            .repo
+            .delivery_target(delivery_id)
+            .await
+            .map_err(|e| e.to_string())?
+        else {
+            // 订阅已删除或停用
+            return Ok(json!({ "skipped": true }));
+        };
+
+        let started = Instant::now();
+        let sent = self
+            .client
+            .post(&target.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, &target.event)
+            .header(DELIVERY_HEADER, delivery_id.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign(&target.secret, target.payload.as_bytes()),
+            )
+            .body(target.payload)
+            .send()
+            .await;
+        let (response_status, response_body, error) = match sent {
+            Ok(resp) => {
+                let status = resp.status();
+                let body = read_truncated(resp).await;
+                let error = (!status.is_success()).then(|| format!("接收端返回 HTTP {status}"));
+                (Some(i32::from(status.as_u16())), Some(body), error)
+            }
+            Err(e) => (None, None, Some(format!("请求失败: {e}"))),
+        };
+
+        let attempt = Attempt {
+            status: match (&error, last_attempt) {
+                (None, _) => "succeeded",
+                (Some(_), true) => "failed",
+                (Some(_), false) => "pending",
+            },
+            response_status,
+            response_body,
+            error,
+            duration_ms: started.elapsed().as_millis() as i64,
+        };
+        self.repo
+            .record_attempt(delivery_id, &attempt)
+            .await
+            .map_err(|e| e.to_string())?;
+        match attempt.error {
+            None => Ok(json!({ "delivery_id": delivery_id, "status": response_status })),
+            Some(e) => Err(e),
+        }
+    }
+}
+
+fn not_found() -> ServiceError {
+    ServiceError::NotFound("webhook 不存在".into())
+}
+
+fn validate_url(url: &str) -> Result<&str, ServiceError> {
+    let url = url.trim();
+    let invalid = || ServiceError::InvalidInput(format!("无效的 webhook URL: {url}"));
+    if url.len() > MAX_URL_LEN {
+        return Err(invalid());
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|_| invalid())?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(invalid());
+    }
+    Ok(url)
+}
+
+/// 校验并去重，序列化为空格分隔；含 `*` 时只存 `*`
+fn normalize_events(events: &[String]) -> Result<String, ServiceError> {
+    if events.is_empty() {
+        return Err(ServiceError::InvalidInput("至少需要订阅一个事件".into()));
+    }
+    if let Some(bad) = events.iter().find(|e| !is_valid_filter(e)) {
+        return Err(ServiceError::InvalidInput(format!("未知的事件类型: {bad}")));
+    }
+    if events.iter().any(|e| e == "*") {
+        return Ok("*".into());
+    }
+    let mut list: Vec<&str> = events.iter().map(String::as_str).collect();
+    list.sort_unstable();
+    list.dedup();
+    Ok(list.join(" "))
+}
+
+async fn read_truncated(mut resp: reqwest::Response) -> String {
+    let mut buf = Vec::new();
+    while buf.len() < MAX_RESPONSE_BODY {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => buf.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    buf.truncate(MAX_RESPONSE_BODY);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use axum::{Router, http::HeaderMap, http::StatusCode, routing::post};
+    use std::sync::Mutex;
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    async fn setup() -> (WebhookService, Webhooks, Arc<SqlitePool>, i32) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        crate::db::migrate::run(&pool).await.unwrap();
+        let user: i32 = sqlx::query_scalar(
+            "INSERT INTO user (name, password_hash) VALUES ('u', 'x') RETURNING id",
+        )
+        .fetch_one(&*pool)
+        .await
+        .unwrap();
+        let hooks = Webhooks::new(pool.clone(), JobService::new(pool.clone()));
+        (
+            WebhookService::new(pool.clone(), hooks.clone()),
+            hooks,
+            pool,
+            user,
+        )
+    }
+
+    /// 本地 HTTP 接收端，按给定状态码应答并记录收到的请求
+    async fn receiver(status: StatusCode) -> (String, Received) {
+        let received: Received = Arc::default();
+        let log = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| {
+                let log = log.clone();
+                async move {
+                    log.lock().unwrap().push((headers, body));
+                    (status, "ack")
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{addr}/hook"), received)
+    }
+
+    fn request(url: &str, events: &[&str]) -> CreateWebhookRequest {
+        CreateWebhookRequest {
+            url: url.into(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            description: String::new(),
+            secret: Some("0123456789abcdef".into()),
+        }
+    }
+
+    async fn delivery_ids(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_scalar("SELECT id FROM webhook_delivery ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn hmac_matches_rfc4231() {
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_validates_input() {
+        let (svc, _, _, user) = setup().await;
+        assert!(svc.create(user, request("ftp://x", &["*"])).await.is_err());
+        assert!(svc.create(user, request("http://x", &[])).await.is_err());
+        assert!(
+            svc.create(user, request("http://x", &["task.deleted"]))
+                .await
+                .is_err()
+        );
+        let mut short = request("http://x", &["*"]);
+        short.secret = Some("short".into());
+        assert!(svc.create(user, short).await.is_err());
+
+        let created = svc
+            .create(
+                user,
+                request(
+                    "http://x",
+                    &["mem.leeched", "task.completed", "mem.leeched"],
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.info.events, ["mem.leeched", "task.completed"]);
+        assert_eq!(created.secret, "0123456789abcdef");
+    }
+
+    #[tokio::test]
+    async fn emit_respects_filters_and_active_flag() {
+        let (svc, hooks, pool, user) = setup().await;
+        let tasks = svc
+            .create(user, request("http://a", &["task.completed"]))
+            .await
+            .unwrap();
+        let all = svc.create(user, request("http://b", &["*"])).await.unwrap();
+        svc.create(user, request("http://c", &["bookmark.created"]))
+            .await
+            .unwrap();
+
+        let n = hooks
+            .try_emit(user, WebhookEvent::TaskCompleted, &json!({ "id": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(n, 2);
+
+        // 停用后不再推送
+        let disable = UpdateWebhookRequest {
+            active: Some(false),
+            ..Default::default()
+        };
+        svc.update(user, all.info.id, disable).await.unwrap();
+        let n = hooks
+            .try_emit(user, WebhookEvent::TaskCompleted, &json!({ "id": 2 }))
+            .await
+            .unwrap();
+        assert_eq!(n, 1);
+        // 其他用户的事件不会推给该用户的订阅
+        let n = hooks
+            .try_emit(user + 1, WebhookEvent::TaskCompleted, &json!({}))
+            .await
+            .unwrap();
+        assert_eq!(n, 0);
+
+        let page = svc
+            .deliveries(
+                user,
+                tasks.info.id,
+                &Pagination {
+                    page: 1,
+                    page_size: 10,
+                    cursor: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].payload["event"], "task.completed");
+        assert_eq!(page.items[0].payload["data"]["id"], 2);
+        assert_eq!(delivery_ids(&pool).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn deliver_signs_and_records_success() {
+        let (svc, _, pool, user) = setup().await;
+        let (url, received) = receiver(StatusCode::OK).await;
+        let hook = svc.create(user, request(&url, &["*"])).await.unwrap();
+        let delivery = svc.ping(user, hook.info.id).await.unwrap();
+
+        let result = svc.deliver(delivery.id, false).await.unwrap();
+        assert_eq!(result["status"], 200);
+
+        let (headers, body) = received.lock().unwrap().pop().unwrap();
+        assert_eq!(headers[EVENT_HEADER], "ping");
+        assert_eq!(headers[DELIVERY_HEADER], delivery.id.to_string());
+        assert_eq!(
+            headers[SIGNATURE_HEADER].to_str().unwrap(),
+            sign(&hook.secret, body.as_bytes())
+        );
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["data"]["webhook_id"], hook.info.id);
+
+        let (status, attempts, code, delivered): (String, i32, Option<i32>, Option<String>) =
+            sqlx::query_as(
+                "SELECT status, attempts, response_status, delivered_at FROM webhook_delivery",
+            )
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            (status.as_str(), attempts, code),
+            ("succeeded", 1, Some(200))
+        );
+        assert!(delivered.is_some());
+    }
+
+    #[tokio::test]
+    async fn deliver_failure_stays_pending_until_last_attempt() {
+        let (svc, _, pool, user) = setup().await;
+        let (url, received) = receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let hook = svc.create(user, request(&url, &["*"])).await.unwrap();
+        let delivery = svc.ping(user, hook.info.id).await.unwrap();
+
+        assert!(svc.deliver(delivery.id, false).await.is_err());
+        let status: String = sqlx::query_scalar("SELECT status FROM webhook_delivery")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(status, "pending");
+
+        let err = svc.deliver(delivery.id, true).await.unwrap_err();
+        assert!(err.contains("500"));
+        let (status, attempts, body): (String, i32, Option<String>) =
+            sqlx::query_as("SELECT status, attempts, response_body FROM webhook_delivery")
+                .fetch_one(&*pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), attempts), ("failed", 2));
+        assert_eq!(body.as_deref(), Some("ack"));
+        assert_eq!(received.lock().unwrap().len(), 2);
+
+        // 删除订阅后残留的任务直接跳过
+        svc.delete(user, hook.info.id).await.unwrap();
+        let skipped = svc.deliver(delivery.id, true).await.unwrap();
+        assert_eq!(skipped["skipped"], true);
+    }
+}
@@ -0,0 +1,228 @@
+//! OpenAPI 3.1 文档生成。
+//!
+//! 接口描述写在处理函数的 `#[utoipa::path]` 上，各模块的 `routes()` 用 [`OpenApiRouter`]
+//! 与 `routes!` 注册处理函数：路径与方法只在属性中写一次，路由表与文档出自同一份登记。
+//! 请求 / 响应类型派生 `ToSchema`，query 参数派生 `IntoParams`。
+//!
+//! 各接口共有的部分在 [`finish`] 中统一补齐：以路径首段为 tag、路径参数、错误响应。
+
+use std::collections::BTreeMap;
+
+use utoipa::openapi::path::{Operation, Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::response::Response;
+use utoipa::openapi::schema::{KnownFormat, ObjectBuilder, SchemaFormat, Type};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{
+    ContentBuilder, OpenApi as Spec, Ref, RefOr, Required, ResponseBuilder, Schema,
+};
+use utoipa::{IntoParams, IntoResponses, Modify, OpenApi, PartialSchema, ToSchema};
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::error::ErrorBody;
+use crate::etag::IfMatch;
+use crate::pagination::Cursor;
+
+/// 文档骨架：基本信息、`/api` 前缀与默认的认证方式，路径在路由登记时补入
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "BRainbow API"),
+    servers((url = "/api")),
+    security(("bearer" = [])),
+    components(schemas(ErrorBody)),
+    modifiers(&BearerAuth)
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, spec: &mut Spec) {
+        let scheme = HttpBuilder::new()
+            .scheme(HttpAuthScheme::Bearer)
+            .description(Some(
+                "登录获得的 access token，或 `bb_pat_` 开头的 API 令牌",
+            ))
+            .build();
+        spec.components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme("bearer", SecurityScheme::Http(scheme));
+    }
+}
+
+/// 其中登记的接口无需认证
+pub fn public<S>(mut router: OpenApiRouter<S>) -> OpenApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    for (_, op) in operations(router.get_openapi_mut()) {
+        op.security = Some(Vec::new());
+    }
+    router
+}
+
+/// 补齐各接口共有的部分：
+/// - 未指定 tag 的以路径首段为 tag；
+/// - 未声明的路径参数（`id` / `*_task_id` 为整数，其余为字符串）；
+/// - `default` 错误响应
+pub fn finish(mut spec: Spec) -> Spec {
+    let error = ResponseBuilder::new()
+        .description("错误")
+        .content(
+            "application/json",
+            ContentBuilder::new().schema(Some(error_schema())).build(),
+        )
+        .build();
+    for (path, op) in operations(&mut spec) {
+        if op.tags.as_ref().is_none_or(Vec::is_empty) {
+            let tag = path.trim_start_matches('/').split('/').next();
+            op.tags = Some(vec![tag.unwrap_or_default().to_string()]);
+        }
+
+        let params = op.parameters.get_or_insert_with(Vec::new);
+        let missing: Vec<Parameter> = path
+            .split('/')
+            .filter_map(|seg| seg.strip_prefix('{')?.strip_suffix('}'))
+            .filter(|name| {
+                !params
+                    .iter()
+                    .any(|p| p.parameter_in == ParameterIn::Path && p.name == *name)
+            })
+            .map(path_param)
+            .collect();
+        // 路径参数排在其余参数之前
+        params.splice(0..0, missing);
+        if params.is_empty() {
+            op.parameters = None;
+        }
+
+        op.responses
+            .responses
+            .entry("default".into())
+            .or_insert_with(|| RefOr::T(error.clone()));
+    }
+    spec
+}
+
+/// 指向 `components` 中 [`ErrorBody`] 的引用
+fn error_schema() -> RefOr<Schema> {
+    Ref::from_schema_name(ErrorBody::name()).into()
+}
+
+/// 未声明的路径参数：`id` / `*_task_id` 为整数，其余为字符串
+fn path_param(name: &str) -> Parameter {
+    let schema = if name == "id" || name.ends_with("task_id") {
+        i64::schema()
+    } else {
+        String::schema()
+    };
+    ParameterBuilder::new()
+        .name(name)
+        .parameter_in(ParameterIn::Path)
+        .required(Required::True)
+        .schema(Some(schema))
+        .build()
+}
+
+/// 文档中的全部接口及其路径
+fn operations(spec: &mut Spec) -> impl Iterator<Item = (String, &mut Operation)> {
+    spec.paths.paths.iter_mut().flat_map(|(path, item)| {
+        [
+            &mut item.get,
+            &mut item.post,
+            &mut item.put,
+            &mut item.patch,
+            &mut item.delete,
+        ]
+        .into_iter()
+        .filter_map(|op| op.as_mut())
+        .map(|op| (path.clone(), op))
+    })
+}
+
+// ── 条件写入 ──
+
+/// 可选的 `If-Match` 头
+impl IntoParams for IfMatch {
+    fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![
+            ParameterBuilder::new()
+                .name("If-Match")
+                .parameter_in(ParameterIn::Header)
+                .required(Required::False)
+                .description(Some("读取时得到的 ETag；省略则无条件写入"))
+                .schema(Some(String::schema()))
+                .build(),
+        ]
+    }
+}
+
+/// `If-Match` 不满足时的 412 响应
+pub struct PreconditionFailed;
+
+impl IntoResponses for PreconditionFailed {
+    fn responses() -> BTreeMap<String, RefOr<Response>> {
+        let response = ResponseBuilder::new()
+            .description("版本已变化，details.current 为最新表示，details.etag 为最新 ETag")
+            .content(
+                "application/json",
+                ContentBuilder::new().schema(Some(error_schema())).build(),
+            )
+            .build();
+        BTreeMap::from([("412".to_string(), RefOr::T(response))])
+    }
+}
+
+// ── 文档用的 schema ──
+
+/// `{"ok": true}`
+pub struct OkFlag;
+
+impl PartialSchema for OkFlag {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .property("ok", bool::schema())
+            .required("ok")
+            .into()
+    }
+}
+
+impl ToSchema for OkFlag {}
+
+/// 非 JSON 的原始内容（文件下载等）
+pub struct Binary;
+
+impl PartialSchema for Binary {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .format(Some(SchemaFormat::KnownFormat(KnownFormat::Binary)))
+            .into()
+    }
+}
+
+impl ToSchema for Binary {}
+
+/// multipart 上传，文件放在 `file` 字段
+pub struct FileUpload;
+
+impl PartialSchema for FileUpload {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .property("file", Binary::schema())
+            .required("file")
+            .into()
+    }
+}
+
+impl ToSchema for FileUpload {}
+
+impl PartialSchema for Cursor {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .description(Some("不透明分页游标"))
+            .into()
+    }
+}
+
+impl ToSchema for Cursor {}
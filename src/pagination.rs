@@ -1,4 +1,16 @@
-use serde::{Deserialize, Serialize};
+//! 列表分页：`page` / `page_size` 偏移分页，或不透明游标的键集分页
+//!
+//! 带 `cursor` 参数即进入游标模式（首页传空串），响应里的 `next_cursor`
+//! 为下一页游标，缺省表示已到末尾。游标编码上一页最后一行的
+//! （排序键, id），列表增删时不会跳行或重复，也不需要扫描跳过的行。
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, TypeInfo, ValueRef};
+use utoipa::{IntoParams, ToSchema};
 
 fn default_page() -> i64 {
     1
@@ -10,12 +22,17 @@ fn default_page_size() -> i64 {
 /// 硬上限
 const MAX_PAGE_SIZE: i64 = 100;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct Pagination {
     #[serde(default = "default_page")]
     pub page: i64,
     #[serde(default = "default_page_size")]
     pub page_size: i64,
+    /// 游标模式：首页传空串，之后传上一页的 `next_cursor`
+    #[serde(default)]
+    #[param(value_type = Option<String>)]
+    pub cursor: Option<CursorParam>,
 }
 
 impl Pagination {
@@ -34,15 +51,209 @@ impl Pagination {
     pub fn limit(&self) -> i64 {
         self.clamp().1
     }
+
+    /// 本次查询的取数窗口
+    pub fn window(&self) -> Window {
+        let limit = self.limit();
+        match &self.cursor {
+            None => Window::Offset {
+                limit,
+                offset: self.offset(),
+            },
+            Some(CursorParam::First) => Window::Keyset { limit, after: None },
+            Some(CursorParam::After(c)) => Window::Keyset {
+                limit,
+                after: Some(c.clone()),
+            },
+        }
+    }
+}
+
+/// 键集游标：上一页最后一行的排序键与 id
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    /// 排序列的原始值（字符串 / 数字 / null）
+    pub key: Value,
+    pub id: i64,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let raw = serde_json::json!([self.key, self.id]).to_string();
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let raw = URL_SAFE_NO_PAD.decode(s).ok()?;
+        let (key, id): (Value, i64) = serde_json::from_slice(&raw).ok()?;
+        match key {
+            Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+                Some(Self { key, id })
+            }
+            _ => None,
+        }
+    }
+
+    /// 从结果行读取游标，`key_col` / `id_col` 为 SELECT 中的列名
+    pub fn from_row(row: &SqliteRow, key_col: &str, id_col: &str) -> Result<Self, sqlx::Error> {
+        let raw = row.try_get_raw(key_col)?;
+        let key = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get::<i64, _>(key_col)?),
+                "REAL" => Value::from(row.try_get::<f64, _>(key_col)?),
+                _ => Value::from(row.try_get::<String, _>(key_col)?),
+            }
+        };
+        Ok(Self {
+            key,
+            id: row.try_get(id_col)?,
+        })
+    }
+
+    fn push_key(&self, qb: &mut QueryBuilder<Sqlite>) {
+        match &self.key {
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => qb.push_bind(i),
+                None => qb.push_bind(n.as_f64().unwrap_or_default()),
+            },
+            Value::Bool(b) => qb.push_bind(*b),
+            Value::String(s) => qb.push_bind(s.clone()),
+            other => qb.push_bind(other.to_string()),
+        };
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.encode())
+    }
+}
+
+/// `cursor` 查询参数：空串为第一页，否则为上一页的 `next_cursor`。
+/// 无法解析的游标在提取阶段即返回 400
+#[derive(Debug, Clone, PartialEq)]
+pub enum CursorParam {
+    First,
+    After(Cursor),
+}
+
+impl<'de> Deserialize<'de> for CursorParam {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(Self::First);
+        }
+        Cursor::decode(s)
+            .map(Self::After)
+            .ok_or_else(|| serde::de::Error::custom("无效的分页游标"))
+    }
+}
+
+/// 一次列表查询的取数方式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Window {
+    Offset {
+        limit: i64,
+        offset: i64,
+    },
+    /// `after` 为 None 表示第一页
+    Keyset {
+        limit: i64,
+        after: Option<Cursor>,
+    },
+}
+
+impl Window {
+    pub fn is_keyset(&self) -> bool {
+        matches!(self, Self::Keyset { .. })
+    }
+
+    /// 追加键集条件 ` AND (...)`，取按 `(key, id)` 排序时排在游标之后的行。
+    /// SQLite 中 NULL 最小：升序排最前，降序排最后
+    pub fn push_after(&self, qb: &mut QueryBuilder<Sqlite>, key: &str, id: &str, desc: bool) {
+        let Self::Keyset {
+            after: Some(cursor),
+            ..
+        } = self
+        else {
+            return;
+        };
+        let cmp = if desc { " < " } else { " > " };
+        if cursor.key.is_null() {
+            if desc {
+                qb.push(format!(" AND ({key} IS NULL AND {id}{cmp}"));
+            } else {
+                qb.push(format!(" AND ({key} IS NOT NULL OR {id}{cmp}"));
+            }
+            qb.push_bind(cursor.id);
+            qb.push(")");
+            return;
+        }
+        qb.push(format!(" AND ({key}{cmp}"));
+        cursor.push_key(qb);
+        qb.push(format!(" OR ({key} = "));
+        cursor.push_key(qb);
+        qb.push(format!(" AND {id}{cmp}"));
+        qb.push_bind(cursor.id);
+        qb.push(")");
+        if desc {
+            qb.push(format!(" OR {key} IS NULL"));
+        }
+        qb.push(")");
+    }
+
+    /// 追加 ` LIMIT ? [OFFSET ?]`；游标模式多取一行用于判断是否还有下一页
+    pub fn push_limit(&self, qb: &mut QueryBuilder<Sqlite>) {
+        match self {
+            Self::Offset { limit, offset } => {
+                qb.push(" LIMIT ");
+                qb.push_bind(*limit);
+                qb.push(" OFFSET ");
+                qb.push_bind(*offset);
+            }
+            Self::Keyset { limit, .. } => {
+                qb.push(" LIMIT ");
+                qb.push_bind(*limit + 1);
+            }
+        }
+    }
+
+    /// 截掉多取的一行，并以本页最后一行生成下一页游标
+    pub fn finish(
+        &self,
+        mut rows: Vec<SqliteRow>,
+        key_col: &str,
+        id_col: &str,
+    ) -> Result<(Vec<SqliteRow>, Option<Cursor>), sqlx::Error> {
+        let Self::Keyset { limit, .. } = self else {
+            return Ok((rows, None));
+        };
+        let limit = usize::try_from(*limit).unwrap_or_default();
+        if rows.len() <= limit {
+            return Ok((rows, None));
+        }
+        rows.truncate(limit);
+        let next = match rows.last() {
+            Some(row) => Some(Cursor::from_row(row, key_col, id_col)?),
+            None => None,
+        };
+        Ok((rows, next))
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginatedResponse<T: Serialize> {
     pub items: Vec<T>,
     pub total: i64,
     pub page: i64,
     pub page_size: i64,
     pub total_pages: i64,
+    /// 游标模式下的下一页游标，缺省表示已到末尾
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Cursor>,
 }
 
 impl<T: Serialize> PaginatedResponse<T> {
@@ -59,8 +270,14 @@ impl<T: Serialize> PaginatedResponse<T> {
             page,
             page_size,
             total_pages,
+            next_cursor: None,
         }
     }
+
+    pub fn with_cursor(mut self, next_cursor: Option<Cursor>) -> Self {
+        self.next_cursor = next_cursor;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -75,6 +292,7 @@ mod tests {
         let p = Pagination {
             page: 0,
             page_size: 0,
+            cursor: None,
         };
         assert_eq!(p.clamp(), (1, 1));
     }
@@ -84,6 +302,7 @@ mod tests {
         let p = Pagination {
             page: 3,
             page_size: 25,
+            cursor: None,
         };
         assert_eq!(p.clamp(), (3, 25));
     }
@@ -93,6 +312,7 @@ mod tests {
         let p = Pagination {
             page: -5,
             page_size: 20,
+            cursor: None,
         };
         assert_eq!(p.clamp(), (1, 20));
     }
@@ -102,6 +322,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 999,
+            cursor: None,
         };
         assert_eq!(p.clamp(), (1, 100));
     }
@@ -111,6 +332,7 @@ mod tests {
         let p = Pagination {
             page: 2,
             page_size: 0,
+            cursor: None,
         };
         assert_eq!(p.clamp(), (2, 1));
     }
@@ -122,6 +344,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 20,
+            cursor: None,
         };
         assert_eq!(p.offset(), 0);
     }
@@ -131,6 +354,7 @@ mod tests {
         let p = Pagination {
             page: 3,
             page_size: 10,
+            cursor: None,
         };
         assert_eq!(p.offset(), 20);
     }
@@ -140,6 +364,7 @@ mod tests {
         let p = Pagination {
             page: 0,
             page_size: 20,
+            cursor: None,
         };
         assert_eq!(p.offset(), 0);
     }
@@ -149,6 +374,7 @@ mod tests {
         let p = Pagination {
             page: 5,
             page_size: 100,
+            cursor: None,
         };
         assert_eq!(p.offset(), 400);
     }
@@ -160,6 +386,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 15,
+            cursor: None,
         };
         assert_eq!(p.limit(), 15);
     }
@@ -169,6 +396,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 200,
+            cursor: None,
         };
         assert_eq!(p.limit(), 100);
     }
@@ -178,6 +406,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 0,
+            cursor: None,
         };
         assert_eq!(p.limit(), 1);
     }
@@ -187,6 +416,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: -10,
+            cursor: None,
         };
         assert_eq!(p.limit(), 1);
     }
@@ -199,6 +429,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 5,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 20, &p);
         assert_eq!(r.total_pages, 4);
@@ -212,6 +443,7 @@ mod tests {
         let p = Pagination {
             page: 2,
             page_size: 10,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 13, &p);
         assert_eq!(r.total_pages, 2);
@@ -224,6 +456,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 20,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 0, &p);
         assert_eq!(r.total_pages, 0);
@@ -236,6 +469,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 20,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 1, &p);
         assert_eq!(r.total_pages, 1);
@@ -247,6 +481,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 999,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 50, &p);
         // page_size should be clamped to 100
@@ -260,6 +495,7 @@ mod tests {
         let p = Pagination {
             page: 2,
             page_size: 3,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 7, &p);
         assert_eq!(r.items, vec!["a", "b", "c"]);
@@ -275,6 +511,7 @@ mod tests {
         let p = Pagination {
             page: 1,
             page_size: 50,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 3, &p);
         assert_eq!(r.total_pages, 1);
@@ -286,9 +523,117 @@ mod tests {
         let p = Pagination {
             page: 3,
             page_size: 20,
+            cursor: None,
         };
         let r = PaginatedResponse::new(items, 105, &p);
         assert_eq!(r.total_pages, 6); // ceil(105/20) = 6
         assert_eq!(r.page, 3);
     }
+
+    // ── 游标 ──
+
+    #[test]
+    fn cursor_roundtrip() {
+        for key in [
+            Value::Null,
+            Value::from(3),
+            Value::from(1.5),
+            Value::from("2024-01-01"),
+        ] {
+            let c = Cursor { key, id: 42 };
+            assert_eq!(Cursor::decode(&c.encode()), Some(c));
+        }
+        assert!(Cursor::decode("not a cursor").is_none());
+    }
+
+    #[test]
+    fn cursor_param_parses() {
+        let p: Pagination = serde_json::from_str(r#"{"cursor": ""}"#).unwrap();
+        assert_eq!(
+            p.window(),
+            Window::Keyset {
+                limit: 20,
+                after: None
+            }
+        );
+
+        let c = Cursor {
+            key: Value::from("k"),
+            id: 7,
+        };
+        let json = format!(r#"{{"cursor": "{}", "page_size": 5}}"#, c.encode());
+        let p: Pagination = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            p.window(),
+            Window::Keyset {
+                limit: 5,
+                after: Some(c)
+            }
+        );
+
+        assert!(serde_json::from_str::<Pagination>(r#"{"cursor": "%%"}"#).is_err());
+        let p: Pagination = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            p.window(),
+            Window::Offset {
+                limit: 20,
+                offset: 0
+            }
+        );
+    }
+
+    /// 逐页走完游标，结果应与一次性排序一致（含 NULL 排序键与重复键）
+    async fn walk(pool: &sqlx::SqlitePool, desc: bool) -> Vec<i64> {
+        let mut out = Vec::new();
+        let mut after = None;
+        loop {
+            let window = Window::Keyset { limit: 2, after };
+            let mut qb = QueryBuilder::new("SELECT id, k FROM t WHERE 1 = 1");
+            window.push_after(&mut qb, "k", "id", desc);
+            let dir = if desc { "DESC" } else { "ASC" };
+            qb.push(format!(" ORDER BY k {dir}, id {dir}"));
+            window.push_limit(&mut qb);
+            let rows = qb.build().fetch_all(pool).await.unwrap();
+            let (rows, next) = window.finish(rows, "k", "id").unwrap();
+            out.extend(rows.iter().map(|r| r.get::<i64, _>("id")));
+            match next {
+                Some(c) => after = Some(c),
+                None => return out,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn keyset_walk_matches_full_order() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE t (id INTEGER PRIMARY KEY, k TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t VALUES (1, NULL), (2, 'a'), (3, 'b'), (4, NULL), (5, 'a')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(walk(&pool, false).await, [1, 4, 2, 5, 3]);
+        assert_eq!(walk(&pool, true).await, [3, 5, 2, 4, 1]);
+    }
+
+    #[test]
+    fn paginated_response_skips_absent_cursor() {
+        let p = Pagination {
+            page: 1,
+            page_size: 5,
+            cursor: None,
+        };
+        let r = PaginatedResponse::new(vec![1], 1, &p);
+        let v = serde_json::to_value(&r).unwrap();
+        assert!(v.get("next_cursor").is_none());
+
+        let c = Cursor {
+            key: Value::Null,
+            id: 1,
+        };
+        let v = serde_json::to_value(r.with_cursor(Some(c.clone()))).unwrap();
+        assert_eq!(v["next_cursor"], c.encode());
+    }
 }
@@ -0,0 +1,450 @@
+//! 请求限流与登录防爆破。
+//!
+//! - [`RateLimiter`]：令牌桶，按 IP（登录后为 IP + 用户）计数，超限返回 429
+//! - [`LoginGuard`]：按客户端 IP + 用户名、以及单按用户名统计连续登录失败，达到阈值后渐进锁定
+//!
+//! 状态均在进程内存中，重启即清零。
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, FromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::auth::{Claims, drain_rejected_body};
+use crate::config::Config;
+use crate::error::too_many_requests;
+use crate::state::AppState;
+
+/// 桶数量超过该值时清理已回满（长期空闲）的桶
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// 令牌桶限流器。容量为每分钟配额，按秒匀速回填。
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// `per_minute` 为 0 表示不限流
+    pub fn per_minute(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute as f64,
+            refill_per_sec: per_minute as f64 / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 消耗一个令牌；超限时返回需等待的秒数
+    pub fn check(&self, key: &str) -> Result<(), u64> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), u64> {
+        if self.capacity <= 0.0 {
+            return Ok(());
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > PRUNE_THRESHOLD {
+            let full_after = self.capacity / self.refill_per_sec;
+            buckets.retain(|_, b| now.duration_since(b.updated).as_secs_f64() < full_after);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = (1.0 - bucket.tokens) / self.refill_per_sec;
+            Err(wait.ceil().max(1.0) as u64)
+        }
+    }
+}
+
+/// 应用级限流器集合，挂在 [`AppState`] 上
+pub struct RateLimits {
+    /// 公开接口（登录、注册、favicon、conv 等），按 IP
+    pub public: RateLimiter,
+    /// 登录后接口，按 IP + 用户
+    pub authed: RateLimiter,
+    /// 是否信任反向代理传入的客户端 IP 头
+    pub trust_proxy: bool,
+}
+
+impl RateLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            public: RateLimiter::per_minute(config.rate_limit_public_per_min),
+            authed: RateLimiter::per_minute(config.rate_limit_authed_per_min),
+            trust_proxy: config.trust_proxy,
+        }
+    }
+}
+
+// ============================================================
+// 中间件
+// ============================================================
+
+/// 公开接口限流：按客户端 IP。
+///
+/// 用法：Router::new().route(…).layer(from_fn_with_state(state, rate_limit::limit_public))
+pub async fn limit_public(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let key = client_ip(
+        request.headers(),
+        request.extensions(),
+        state.rate_limit.trust_proxy,
+    );
+    enforce(&state.rate_limit.public, &key, request, next).await
+}
+
+/// 登录后接口限流：按 IP + 用户。须在 [`crate::auth::auth`] 之后使用以拿到 Claims。
+pub async fn limit_authed(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let ip = client_ip(
+        request.headers(),
+        request.extensions(),
+        state.rate_limit.trust_proxy,
+    );
+    let key = match request.extensions().get::<Claims>() {
+        Some(c) => format!("{ip}|{}", c.sub),
+        None => ip,
+    };
+    enforce(&state.rate_limit.authed, &key, request, next).await
+}
+
+async fn enforce(limiter: &RateLimiter, key: &str, mut request: Request, next: Next) -> Response {
+    match limiter.check(key) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            drain_rejected_body(&mut request).await;
+            too_many_requests(retry_after)
+        }
+    }
+}
+
+/// 处理器中取客户端 IP 的提取器，规则与限流相同
+pub struct ClientIp(pub String);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self(client_ip(
+            &parts.headers,
+            &parts.extensions,
+            state.rate_limit.trust_proxy,
+        )))
+    }
+}
+
+/// 客户端 IP：信任代理时取 `X-Forwarded-For` 第一跳 / `X-Real-IP`，否则取 TCP 对端地址
+fn client_ip(headers: &HeaderMap, extensions: &Extensions, trust_proxy: bool) -> String {
+    if trust_proxy {
+        let forwarded = headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next())
+            .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+            .map(str::trim)
+            .filter(|s| !s.is_empty());
+        if let Some(ip) = forwarded {
+            return ip.to_string();
+        }
+    }
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// ============================================================
+// 登录失败锁定
+// ============================================================
+
+/// 首次锁定时长，此后每多失败一次翻倍
+const LOCKOUT_BASE: Duration = Duration::from_secs(30);
+/// 单次锁定上限
+const LOCKOUT_MAX: Duration = Duration::from_secs(60 * 60);
+/// 距上次失败超过该时长（且未处于锁定中）则清零计数
+const FAILURE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// 统计连续登录失败的渐进锁定器，分两级计数：
+///
+/// - (客户端 IP, 用户名)：阈值低，只锁定失败所来自的 IP，别处的猜测无法把账号本人锁在门外；
+/// - 用户名：阈值宽松得多，防止换着 IP 分散猜测同一账号，代价是触发后账号本人也需等待。
+///
+/// 单个 IP 的尝试次数另由公开接口限流约束。
+#[derive(Clone)]
+pub struct LoginGuard {
+    threshold: u32,
+    account_threshold: u32,
+    failures: Arc<Mutex<HashMap<Key, Failures>>>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Client(String, String),
+    Account(String),
+}
+
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Default for LoginGuard {
+    fn default() -> Self {
+        Self::new(5, 50)
+    }
+}
+
+impl LoginGuard {
+    /// 同一 IP 对同一用户名连续失败 `threshold` 次、或用户名累计失败 `account_threshold`
+    /// 次后开始锁定；0 表示该级不锁定
+    pub fn new(threshold: u32, account_threshold: u32) -> Self {
+        Self {
+            threshold,
+            account_threshold,
+            failures: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 处于锁定中时返回剩余秒数
+    pub fn check(&self, ip: &str, name: &str) -> Result<(), u64> {
+        self.check_at(ip, name, Instant::now())
+    }
+
+    pub fn record_failure(&self, ip: &str, name: &str) {
+        self.record_failure_at(ip, name, Instant::now());
+    }
+
+    pub fn record_success(&self, ip: &str, name: &str) {
+        let [client, account] = keys(ip, name);
+        let mut failures = self.lock();
+        failures.remove(&client);
+        failures.remove(&account);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Failures>> {
+        self.failures.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 两级中任一处于锁定即拒绝，返回较长的剩余时间
+    fn check_at(&self, ip: &str, name: &str, now: Instant) -> Result<(), u64> {
+        let failures = self.lock();
+        let until = keys(ip, name)
+            .iter()
+            .filter_map(|k| failures.get(k)?.locked_until)
+            .max();
+        match until {
+            Some(until) if until > now => Err(until.duration_since(now).as_secs().max(1)),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_failure_at(&self, ip: &str, name: &str, now: Instant) {
+        if self.threshold == 0 && self.account_threshold == 0 {
+            return;
+        }
+        let mut failures = self.lock();
+        // 顺带清理过期条目，防止随机用户名 / IP 撑大内存
+        failures.retain(|_, f| {
+            f.locked_until.is_some_and(|t| t > now) || now.duration_since(f.last) < FAILURE_WINDOW
+        });
+
+        let [client, account] = keys(ip, name);
+        for (key, threshold) in [(client, self.threshold), (account, self.account_threshold)] {
+            if threshold == 0 {
+                continue;
+            }
+            let entry = failures.entry(key).or_insert(Failures {
+                count: 0,
+                last: now,
+                locked_until: None,
+            });
+            entry.count += 1;
+            entry.last = now;
+            if entry.count >= threshold {
+                let exp = (entry.count - threshold).min(16);
+                let lock = LOCKOUT_BASE.saturating_mul(1 << exp).min(LOCKOUT_MAX);
+                entry.locked_until = Some(now + lock);
+            }
+        }
+    }
+}
+
+fn keys(ip: &str, name: &str) -> [Key; 2] {
+    let name = name.trim().to_lowercase();
+    [
+        Key::Client(ip.to_string(), name.clone()),
+        Key::Account(name),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const IP: &str = "203.0.113.7";
+
+    #[test]
+    fn bucket_allows_burst_then_limits() {
+        let limiter = RateLimiter::per_minute(3);
+        let t0 = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check_at("ip", t0).is_ok());
+        }
+        let retry = limiter.check_at("ip", t0).unwrap_err();
+        assert_eq!(retry, 20); // 3 次/分钟 → 20 秒回填一个
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limiter = RateLimiter::per_minute(60);
+        let t0 = Instant::now();
+        for _ in 0..60 {
+            limiter.check_at("ip", t0).unwrap();
+        }
+        assert!(limiter.check_at("ip", t0).is_err());
+        assert!(limiter.check_at("ip", t0 + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn buckets_are_per_key() {
+        let limiter = RateLimiter::per_minute(1);
+        let t0 = Instant::now();
+        assert!(limiter.check_at("a", t0).is_ok());
+        assert!(limiter.check_at("a", t0).is_err());
+        assert!(limiter.check_at("b", t0).is_ok());
+    }
+
+    #[test]
+    fn zero_disables_limit() {
+        let limiter = RateLimiter::per_minute(0);
+        let t0 = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.check_at("ip", t0).is_ok());
+        }
+    }
+
+    #[test]
+    fn lockout_starts_at_threshold() {
+        let guard = LoginGuard::new(3, 50);
+        let t0 = Instant::now();
+        guard.record_failure_at(IP, "alice", t0);
+        guard.record_failure_at(IP, "alice", t0);
+        assert!(guard.check_at(IP, "alice", t0).is_ok());
+
+        guard.record_failure_at(IP, "alice", t0);
+        assert_eq!(guard.check_at(IP, "alice", t0).unwrap_err(), 30);
+        assert!(guard.check_at(IP, "alice", t0 + LOCKOUT_BASE).is_ok());
+    }
+
+    #[test]
+    fn lockout_grows_progressively() {
+        let guard = LoginGuard::new(1, 50);
+        let t0 = Instant::now();
+        guard.record_failure_at(IP, "bob", t0);
+        assert_eq!(guard.check_at(IP, "bob", t0).unwrap_err(), 30);
+        guard.record_failure_at(IP, "bob", t0);
+        assert_eq!(guard.check_at(IP, "bob", t0).unwrap_err(), 60);
+        guard.record_failure_at(IP, "bob", t0);
+        assert_eq!(guard.check_at(IP, "bob", t0).unwrap_err(), 120);
+
+        for _ in 0..20 {
+            guard.record_failure_at(IP, "bob", t0);
+        }
+        assert_eq!(
+            guard.check_at(IP, "bob", t0).unwrap_err(),
+            LOCKOUT_MAX.as_secs()
+        );
+    }
+
+    #[test]
+    fn success_resets_and_names_are_case_insensitive() {
+        let guard = LoginGuard::new(1, 50);
+        guard.record_failure(IP, "Carol");
+        assert!(guard.check(IP, "carol ").is_err());
+        guard.record_success(IP, "CAROL");
+        assert!(guard.check(IP, "carol").is_ok());
+    }
+
+    #[test]
+    fn lockout_is_per_client_ip() {
+        let guard = LoginGuard::new(3, 50);
+        let t0 = Instant::now();
+        for _ in 0..10 {
+            guard.record_failure_at("198.51.100.9", "admin", t0);
+        }
+        assert!(guard.check_at("198.51.100.9", "admin", t0).is_err());
+        // 账号本人从别的 IP 登录不受影响
+        assert!(guard.check_at(IP, "admin", t0).is_ok());
+    }
+
+    #[test]
+    fn spread_guesses_lock_the_account() {
+        let guard = LoginGuard::new(3, 20);
+        let t0 = Instant::now();
+        // 每个 IP 只试两次，始终低于单 IP 阈值
+        for i in 0..10 {
+            let ip = format!("198.51.100.{i}");
+            guard.record_failure_at(&ip, "admin", t0);
+            guard.record_failure_at(&ip, "admin", t0);
+        }
+        assert_eq!(guard.check_at(IP, "admin", t0).unwrap_err(), 30);
+        assert!(guard.check_at(IP, "alice", t0).is_ok());
+        assert!(guard.check_at(IP, "admin", t0 + LOCKOUT_BASE).is_ok());
+    }
+
+    #[test]
+    fn zero_disables_account_lockout() {
+        let guard = LoginGuard::new(3, 0);
+        let t0 = Instant::now();
+        for i in 0..50 {
+            guard.record_failure_at(&format!("198.51.100.{i}"), "admin", t0);
+        }
+        assert!(guard.check_at(IP, "admin", t0).is_ok());
+    }
+
+    #[test]
+    fn client_ip_prefers_proxy_header_only_when_trusted() {
+        use axum::body::Body;
+        let mut req = Request::builder()
+            .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 9000))));
+
+        assert_eq!(
+            client_ip(req.headers(), req.extensions(), true),
+            "203.0.113.7"
+        );
+        assert_eq!(
+            client_ip(req.headers(), req.extensions(), false),
+            "127.0.0.1"
+        );
+    }
+}
@@ -1,47 +1,278 @@
-use axum::{
-    Router, middleware,
-    routing::{get, post},
-};
+use std::sync::Arc;
+
+use axum::{Extension, Json, Router, middleware};
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
 
 use crate::modules::{
-    bookmark, card, conv, db_viewer, media, mem, onto, reading, sign, task, text, time_window, user,
+    account, activity, api_token, bookmark, card, conv, db_viewer, events, job, media, mem, onto,
+    reading, search, session, sign, sync, task, text, time_window, trash, user, webhook,
 };
+use crate::openapi::{self, ApiDoc};
 use crate::state::AppState;
 
+/// 路由与 OpenAPI 文档出自 [`api_routes`] 的同一份登记，文档挂在 `/openapi.json`
 pub fn create_api_router(state: AppState) -> Router<AppState> {
-    // ── 公开路由：无需认证 ──
-    let public = Router::new()
-        .route("/user/register", post(user::register_handler))
-        .route("/user/login", post(user::login_handler))
-        .route("/bookmarks/favicon", get(bookmark::favicon_handler))
-        .nest("/text", text::routes())
-        .nest("/media", media::public_file_route())
-        .nest("/conv", conv::routes());
-
-    // ── 需登录的路由 ──
-    let authed = Router::new()
-        .route("/user", get(user::user_handler))
-        .route("/user/logout", post(user::logout_handler))
-        .route("/user/password", post(user::change_password_handler))
-        .nest("/mem", mem::routes())
-        .nest("/media", media::routes())
-        .nest("/cards", card::routes())
-        .nest("/onto", onto::routes())
-        .nest("/sign", sign::routes())
-        .nest("/reading", reading::routes())
-        .nest("/bookmarks", bookmark::routes())
-        .nest("/tasks", task::routes())
-        .nest("/time-windows", time_window::routes())
+    let (router, spec) = api_routes(state).split_for_parts();
+    router.layer(Extension(Arc::new(openapi::finish(spec))))
+}
+
+fn api_routes(state: AppState) -> OpenApiRouter<AppState> {
+    // ── 公开路由：无需认证；除媒体文件外按 IP 限流 ──
+    let public = OpenApiRouter::new()
+        .routes(routes!(openapi_handler))
+        .merge(user::public_routes())
+        .merge(session::public_routes())
+        .merge(bookmark::favicon_route())
+        .nest("/conv", conv::routes())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            crate::rate_limit::limit_public,
+        ))
+        .nest("/media", media::public_file_route());
+
+    // ── 账号管理：仅限登录会话，API 令牌不可用 ──
+    let account = OpenApiRouter::new()
+        .merge(user::routes())
+        .merge(session::routes())
+        .merge(api_token::routes())
+        .nest("/account", account::routes())
+        .merge(activity::routes())
+        .nest("/webhooks", webhook::routes())
+        .nest("/trash", trash::routes())
+        .layer(middleware::from_fn(crate::auth::require_session));
+
+    // ── 需登录的路由：登录会话或带相应作用域的 API 令牌 ──
+    let authed = OpenApiRouter::new()
+        .merge(account)
+        .nest("/text", scoped("text", text::routes()))
+        .nest("/mem", scoped("mem", mem::routes()))
+        .nest("/media", scoped("media", media::routes()))
+        .nest("/cards", scoped("cards", card::routes()))
+        .nest("/onto", scoped("onto", onto::routes()))
+        .nest("/sign", scoped("sign", sign::routes()))
+        .nest("/reading", scoped("reading", reading::routes()))
+        .nest("/bookmarks", scoped("bookmarks", bookmark::routes()))
+        .nest("/tasks", scoped("tasks", task::routes()))
+        .nest(
+            "/time-windows",
+            scoped("time-windows", time_window::routes()),
+        )
+        // 作用域按模块在 search 内部过滤
+        .nest("/search", search::routes())
+        // 同上，按实体对应的作用域过滤推送
+        .nest("/events", events::routes())
+        // 同上，按实体对应的作用域过滤拉取与推送
+        .nest("/sync", sync::routes())
+        // 任务按提交者可见，不单设作用域
+        .nest("/jobs", job::routes())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            crate::rate_limit::limit_authed,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             crate::auth::auth,
         ));
 
-    // ── 管理员路由：auth + require_admin ──
-    let admin = Router::new()
+    // ── 管理员路由：auth + require_session + require_admin（管理员不限流） ──
+    let admin = OpenApiRouter::new()
         .nest("/db", db_viewer::routes())
+        .nest("/admin", user::admin_routes())
         .layer(middleware::from_fn(crate::auth::require_admin))
+        .layer(middleware::from_fn(crate::auth::require_session))
         .layer(middleware::from_fn_with_state(state, crate::auth::auth));
 
-    Router::new().merge(public).merge(authed).merge(admin)
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .merge(openapi::public(public))
+        .merge(authed)
+        .merge(admin)
+}
+
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    summary = "本文档",
+    responses((status = 200, description = "成功", body = serde_json::Value))
+)]
+async fn openapi_handler(
+    Extension(spec): Extension<Arc<utoipa::openapi::OpenApi>>,
+) -> Json<utoipa::openapi::OpenApi> {
+    Json(spec.as_ref().clone())
+}
+
+/// 为路由组挂上 API 令牌作用域检查，`resource` 须在
+/// [`SCOPE_RESOURCES`](crate::modules::api_token::model::SCOPE_RESOURCES) 中
+fn scoped(resource: &'static str, routes: OpenApiRouter<AppState>) -> OpenApiRouter<AppState> {
+    routes.layer(middleware::from_fn_with_state(
+        resource,
+        crate::auth::require_scope,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::config::Config;
+    use crate::storage::LocalStorage;
+    use serde_json::Value;
+    use sqlx::SqlitePool;
+    use std::collections::{BTreeSet, HashMap};
+
+    async fn parts() -> (Router<AppState>, Value) {
+        let pool = Arc::new(SqlitePool::connect("sqlite::memory:").await.unwrap());
+        let storage = Arc::new(LocalStorage::new(std::env::temp_dir()));
+        let state = AppState::new(pool, storage, &Config::from_env());
+        let (router, spec) = api_routes(state).split_for_parts();
+        (router, serde_json::to_value(openapi::finish(spec)).unwrap())
+    }
+
+    async fn spec() -> Value {
+        parts().await.1
+    }
+
+    /// 路由表中实际登记的 (方法, 路径)。
+    ///
+    /// axum 不公开路由表，只能从 `Router` 的 `Debug` 输出中读取：
+    /// `RouteId(n): "/path"` 给出路径，`RouteId(n): MethodRouter(MethodRouter { get: .., .. })`
+    /// 给出各方法是否挂了处理函数；整段挂载的 service（`Route(..)`）记为方法 `*`。
+    fn registered(router: &Router<AppState>) -> BTreeSet<(String, String)> {
+        let debug = format!("{router:?}");
+        // 只看主路由表，不看 fallback
+        let debug = debug.split("fallback_router:").next().unwrap();
+        let mut paths = HashMap::new();
+        let mut methods: HashMap<&str, Vec<String>> = HashMap::new();
+        for chunk in debug.split("RouteId(").skip(1) {
+            let (id, rest) = chunk.split_once("): ").unwrap();
+            if let Some(path) = rest.strip_prefix('"') {
+                paths.insert(id, path.split('"').next().unwrap().to_string());
+            } else if let Some(fields) = rest.strip_prefix("MethodRouter(MethodRouter { ") {
+                let fields = fields.split(", fallback:").next().unwrap();
+                let found = fields
+                    .split(", ")
+                    .filter_map(|f| f.split_once(": "))
+                    .filter(|(_, endpoint)| *endpoint != "None")
+                    .map(|(method, _)| method.to_string())
+                    .collect();
+                methods.insert(id, found);
+            } else {
+                methods.insert(id, vec!["*".to_string()]);
+            }
+        }
+        methods
+            .into_iter()
+            .flat_map(|(id, methods)| {
+                let path = paths[id].clone();
+                methods.into_iter().map(move |m| (m, path.clone()))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn every_route_is_documented() {
+        let (router, spec) = parts().await;
+        let routes = registered(&router);
+        assert!(routes.len() > 100, "仅解析出 {} 个路由", routes.len());
+
+        let documented: BTreeSet<(String, String)> = spec["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, item)| {
+                item.as_object()
+                    .unwrap()
+                    .keys()
+                    .map(move |method| (method.clone(), path.clone()))
+            })
+            .collect();
+        let undocumented: Vec<_> = routes.difference(&documented).collect();
+        assert!(
+            undocumented.is_empty(),
+            "未写入文档的路由: {undocumented:#?}"
+        );
+        let unrouted: Vec<_> = documented.difference(&routes).collect();
+        assert!(unrouted.is_empty(), "文档中有但未登记的路由: {unrouted:#?}");
+    }
+
+    #[tokio::test]
+    async fn routes_are_documented_with_shared_parts() {
+        let spec = spec().await;
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(paths.len() > 100, "仅登记了 {} 个路径", paths.len());
+
+        // 嵌套前缀与路径参数
+        let task = &spec["paths"]["/tasks/{id}"];
+        for method in ["get", "patch", "delete"] {
+            let op = &task[method];
+            assert_eq!(op["tags"][0], "tasks");
+            assert_eq!(op["parameters"][0]["name"], "id");
+            assert_eq!(op["parameters"][0]["schema"]["type"], "integer");
+            assert!(op["responses"]["default"].is_object());
+        }
+        // 条件写入
+        let patch = &task["patch"];
+        assert!(
+            patch["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|p| p["name"] == "If-Match" && p["in"] == "header")
+        );
+        assert!(patch["responses"]["412"].is_object());
+        assert!(patch["responses"]["200"]["headers"]["ETag"].is_object());
+        // 展开的分页参数与模块自身的 query 参数并列
+        let names: Vec<_> = spec["paths"]["/media"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        for name in ["page", "page_size", "cursor"] {
+            assert!(names.contains(&name), "{names:?}");
+        }
+        // 公开路由不要求认证，其余沿用全局 bearer
+        assert_eq!(
+            spec["paths"]["/user/login"]["post"]["security"],
+            serde_json::json!([])
+        );
+        assert!(spec["paths"]["/tasks"]["get"]["security"].is_null());
+        assert!(spec["security"][0]["bearer"].is_array());
+    }
+
+    #[tokio::test]
+    async fn every_schema_ref_resolves() {
+        fn walk(v: &Value, refs: &mut Vec<String>) {
+            match v {
+                Value::Object(map) => {
+                    if let Some(r) = map.get("$ref").and_then(|r| r.as_str()) {
+                        refs.push(r.to_string());
+                    }
+                    map.values().for_each(|v| walk(v, refs));
+                }
+                Value::Array(items) => items.iter().for_each(|v| walk(v, refs)),
+                _ => {}
+            }
+        }
+
+        let spec = spec().await;
+        let mut refs = Vec::new();
+        walk(&spec, &mut refs);
+        assert!(!refs.is_empty());
+        for r in refs {
+            let name = r.strip_prefix("#/components/schemas/").unwrap();
+            let schema = &spec["components"]["schemas"][name];
+            assert!(schema.is_object(), "{r} 未定义");
+        }
+        // 代表性的 DTO 都已登记
+        for name in [
+            "CreateTaskRequest",
+            "MemWithChunks",
+            "BatchResponse",
+            "ErrorBody",
+        ] {
+            assert!(spec["components"]["schemas"][name].is_object(), "{name}");
+        }
+    }
 }
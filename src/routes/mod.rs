@@ -1,12 +1,13 @@
 pub mod api;
 
 use axum::extract::State;
-use axum::{Router, http::header::HeaderMap, response::Json, routing::get};
+use axum::{Router, http::header::HeaderMap, middleware, response::Json, routing::get};
 use serde::Serialize;
 use std::collections::HashMap;
 use tower_http::services::ServeDir;
 use tower_http::services::ServeFile;
 
+use crate::metrics::{metrics_handler, track};
 use crate::state::AppState;
 
 #[derive(Serialize)]
@@ -44,7 +45,10 @@ pub fn create_router(state: AppState) -> Router {
         .route("/health", get(health_check))
         .route("/api/health", get(health_check_db))
         .route("/header", get(header_handler))
+        .route("/metrics", get(metrics_handler))
         .nest("/api", api::create_api_router(state.clone()))
+        // route_layer 只作用于已命中的路由，MatchedPath 为完整模板（含 /api 前缀）
+        .route_layer(middleware::from_fn_with_state(state.metrics.clone(), track))
         .fallback_service(
             ServeDir::new("dist").not_found_service(ServeFile::new("dist/index.html")),
         )
@@ -2,13 +2,35 @@ use sqlx::SqlitePool;
 use std::sync::Arc;
 
 use crate::config::Config;
+use crate::metrics::Metrics;
 use crate::modules::{
-    bookmark::BookmarkService, card::CardService, db_viewer::DbViewerService,
-    media::service::MediaService, mem::MemRepo, mem::query::MemQueryService,
-    mem::service::MemService, onto::OntoService, reading::service::ReadingService,
-    sign::SignService, task::TaskService, text::TextService,
-    time_window::service::TimeWindowService, user::UserService,
+    account::AccountService,
+    activity::ActivityService,
+    api_token::ApiTokenService,
+    bookmark::BookmarkService,
+    card::CardService,
+    db_viewer::DbViewerService,
+    events::ChangeFeed,
+    job::JobService,
+    media::service::MediaService,
+    mem::MemRepo,
+    mem::query::MemQueryService,
+    mem::service::MemService,
+    onto::OntoService,
+    reading::service::ReadingService,
+    search::SearchService,
+    session::SessionService,
+    sign::SignService,
+    sync::SyncService,
+    task::TaskService,
+    text::TextService,
+    time_window::service::TimeWindowService,
+    trash::TrashService,
+    user::UserService,
+    webhook::{WebhookService, Webhooks},
 };
+use crate::rate_limit::{LoginGuard, RateLimits};
+use crate::storage::Storage;
 
 /// 应用级共享状态。
 ///
@@ -18,6 +40,15 @@ use crate::modules::{
 pub struct AppState {
     pub db: Arc<SqlitePool>,
     pub jwt_secret: Arc<String>,
+    pub rate_limit: Arc<RateLimits>,
+    /// Prometheus 指标，由 `metrics::track` 采集、`/metrics` 输出
+    pub metrics: Arc<Metrics>,
+    /// 文件存储：媒体、缩略图、favicon 缓存
+    pub storage: Arc<dyn Storage>,
+    /// 后台任务队列，worker 由 `job::runner::spawn` 启动
+    pub jobs: JobService,
+    /// 实时变更流：各 service 发布，`/api/events` 订阅
+    pub changes: ChangeFeed,
 
     // ── 预创建的服务实例 ──
     pub card: CardService,
@@ -25,6 +56,10 @@ pub struct AppState {
     pub onto: OntoService,
     pub sign: SignService,
     pub user: UserService,
+    pub session: SessionService,
+    pub api_token: ApiTokenService,
+    pub account: AccountService,
+    pub activity: ActivityService,
     pub text: TextService,
     pub db_viewer: DbViewerService,
     pub task: TaskService,
@@ -32,32 +67,78 @@ pub struct AppState {
     pub mem_query: MemQueryService,
     pub media: MediaService,
     pub reading: ReadingService,
+    pub search: SearchService,
+    pub sync: SyncService,
     pub time_window: TimeWindowService,
+    pub trash: TrashService,
+    pub webhook: WebhookService,
 }
 
 impl AppState {
-    pub fn new(db: Arc<SqlitePool>, config: &Config) -> Self {
-        let task = TaskService::new(db.clone());
+    pub fn new(db: Arc<SqlitePool>, storage: Arc<dyn Storage>, config: &Config) -> Self {
+        let jobs = JobService::new(db.clone());
+        let hooks = Webhooks::new(db.clone(), jobs.clone());
+        let changes = ChangeFeed::new();
+        let task = TaskService::new(db.clone(), hooks.clone(), changes.clone());
         // 构建 Repository adapter，通过 trait 分别注入命令侧和查询侧
         let mem_repo: Arc<dyn crate::modules::mem::port::MemRepository> =
             Arc::new(MemRepo::new(db.clone()));
         let mem_repo_for_query = mem_repo.clone();
+        let card = CardService::new(db.clone(), changes.clone());
+        let bookmark = BookmarkService::new(db.clone(), hooks.clone(), changes.clone());
+        let mem = MemService::new(
+            mem_repo,
+            db.clone(),
+            jobs.clone(),
+            hooks.clone(),
+            changes.clone(),
+        );
+        let media = MediaService::new(db.clone(), storage.clone(), hooks.clone(), changes.clone());
+        let sync = SyncService::new(db.clone(), mem.clone(), task.clone(), bookmark.clone());
+        let trash = TrashService::new(
+            db.clone(),
+            config.trash_retention_days,
+            mem.clone(),
+            task.clone(),
+            bookmark.clone(),
+            card.clone(),
+            media.clone(),
+        );
         Self {
             db: db.clone(),
             jwt_secret: Arc::new(config.jwt_secret.clone()),
-            card: CardService::new(db.clone()),
-            bookmark: BookmarkService::new(db.clone()),
+            rate_limit: Arc::new(RateLimits::from_config(config)),
+            metrics: Arc::new(Metrics::new(config.metrics_token.clone())),
+            storage: storage.clone(),
+            jobs: jobs.clone(),
+            changes,
+            card,
+            bookmark,
             onto: OntoService::new(db.clone()),
             sign: SignService::new(db.clone()),
-            user: UserService::new(db.clone()),
+            user: UserService::new(
+                db.clone(),
+                LoginGuard::new(
+                    config.login_lockout_threshold,
+                    config.login_account_lockout_threshold,
+                ),
+            ),
+            session: SessionService::new(db.clone()),
+            api_token: ApiTokenService::new(db.clone()),
+            account: AccountService::new(db.clone(), storage.clone()),
+            activity: ActivityService::new(db.clone()),
             text: TextService::new(db.clone()),
             db_viewer: DbViewerService::new(db.clone()),
             task: task.clone(),
-            mem: MemService::new(mem_repo, db.clone()),
+            mem,
             mem_query: MemQueryService::new(mem_repo_for_query),
-            media: MediaService::new(db.clone()),
+            media,
             reading: ReadingService::new(db.clone()),
+            search: SearchService::new(db.clone()),
+            sync,
             time_window: TimeWindowService::new(db.clone(), task),
+            trash,
+            webhook: WebhookService::new(db.clone(), hooks),
         }
     }
 }
@@ -0,0 +1,188 @@
+//! 本地文件系统后端：key 即 `root` 下的相对路径。
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+use tokio_util::io::ReaderStream;
+
+use super::{ObjectMeta, Storage, check_key, check_prefix};
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// 以 `root` 为根目录，并清理上次中断写入留下的临时文件
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        if let Err(e) = std::fs::create_dir_all(&root) {
+            tracing::warn!("创建存储目录失败 {}: {e}", root.display());
+        }
+        remove_temp_files(&root);
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+fn is_temp_file(name: &str) -> bool {
+    name.starts_with("tmp_") && name.ends_with(".tmp")
+}
+
+/// 删除 `root` 及其一级子目录下的临时文件
+fn remove_temp_files(root: &Path) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let Ok(children) = std::fs::read_dir(&path) else {
+                continue;
+            };
+            for child in children.flatten() {
+                if is_temp_file(&child.file_name().to_string_lossy()) {
+                    let _ = std::fs::remove_file(child.path());
+                }
+            }
+        } else if is_temp_file(&entry.file_name().to_string_lossy()) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 文件不存在映射为 None，其余错误原样返回
+fn not_found_as_none<T>(result: io::Result<T>) -> io::Result<Option<T>> {
+    match result {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    /// 先写同目录下的临时文件再 rename，读者不会看到写了一半的文件
+    async fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        let dir = path.parent().unwrap_or(&self.root);
+        tokio::fs::create_dir_all(dir).await?;
+        let tmp = dir.join(format!("tmp_{}.tmp", nanoid::nanoid!(12)));
+        tokio::fs::write(&tmp, data).await?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        not_found_as_none(tokio::fs::read(self.path(key)?).await)
+    }
+
+    async fn stream(&self, key: &str) -> io::Result<Option<Body>> {
+        let file = not_found_as_none(tokio::fs::File::open(self.path(key)?).await)?;
+        Ok(file.map(|f| Body::from_stream(ReaderStream::new(f))))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        not_found_as_none(tokio::fs::remove_file(self.path(key)?).await).map(|_| ())
+    }
+
+    async fn exists(&self, key: &str) -> io::Result<bool> {
+        tokio::fs::try_exists(self.path(key)?).await
+    }
+
+    async fn list(&self, prefix: &str) -> io::Result<Vec<ObjectMeta>> {
+        check_prefix(prefix)?;
+        let mut objects = Vec::new();
+        let mut pending = vec![prefix.to_string()];
+        while let Some(dir) = pending.pop() {
+            let Some(mut entries) =
+                not_found_as_none(tokio::fs::read_dir(self.root.join(&dir)).await)?
+            else {
+                continue;
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let key = format!("{dir}{}", entry.file_name().to_string_lossy());
+                let meta = entry.metadata().await?;
+                if meta.is_dir() {
+                    pending.push(format!("{key}/"));
+                } else if meta.is_file() {
+                    objects.push(ObjectMeta {
+                        key,
+                        modified: meta.modified().ok().map(DateTime::<Utc>::from),
+                    });
+                }
+            }
+        }
+        Ok(objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("brainbow-storage-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn put_get_delete_roundtrip() {
+        let root = temp_root();
+        let store = LocalStorage::new(&root);
+
+        store.put("image/abc", b"hello").await.unwrap();
+        assert_eq!(store.get("image/abc").await.unwrap().unwrap(), b"hello");
+        assert!(store.exists("image/abc").await.unwrap());
+        let body = store.stream("image/abc").await.unwrap().unwrap();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"hello");
+
+        store.put("image/abc", b"world").await.unwrap();
+        assert_eq!(store.get("image/abc").await.unwrap().unwrap(), b"world");
+
+        store.delete("image/abc").await.unwrap();
+        store.delete("image/abc").await.unwrap();
+        assert!(store.get("image/abc").await.unwrap().is_none());
+        assert!(store.stream("image/abc").await.unwrap().is_none());
+        assert!(!store.exists("image/abc").await.unwrap());
+        assert!(store.get("../escape").await.is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn list_walks_prefix_and_new_sweeps_temp_files() {
+        let root = temp_root();
+        std::fs::create_dir_all(root.join("audio")).unwrap();
+        std::fs::write(root.join("audio/tmp_x.tmp"), b"x").unwrap();
+        let store = LocalStorage::new(&root);
+        assert!(!root.join("audio/tmp_x.tmp").exists());
+
+        store.put("image/a", b"1").await.unwrap();
+        store.put("image/nested/b", b"2").await.unwrap();
+        store.put("video/c", b"3").await.unwrap();
+
+        let mut keys: Vec<String> = store
+            .list("image/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.key)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["image/a", "image/nested/b"]);
+        assert!(store.list("missing/").await.unwrap().is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+//! 文件存储后端。
+//!
+//! 媒体文件、缩略图与 favicon 缓存都通过 [`Storage`] 读写，对象以 `/` 分隔的 key 寻址：
+//!
+//! - `{image,video,audio}/{stored_id}`：上传的媒体文件
+//! - `thumbs/{stored_id}.png`：图片缩略图
+//! - `favicons/{host}.{ico,png}`：书签网站图标
+//!
+//! 后端由 `STORAGE_BACKEND` 选择：[`LocalStorage`] 写入 `UPLOAD_DIR`，
+//! [`S3Storage`] 写入 S3 兼容的对象存储（AWS S3、MinIO 等）。
+
+mod local;
+mod s3;
+
+use std::io;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use chrono::{DateTime, Utc};
+
+use crate::config::{Config, StorageBackend};
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// 对象元信息，由 [`Storage::list`] 返回
+#[derive(Debug, Clone)]
+pub struct ObjectMeta {
+    pub key: String,
+    /// 最后修改时间；后端不提供时为 None
+    pub modified: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// 写入对象，已存在时覆盖
+    async fn put(&self, key: &str, data: &[u8]) -> io::Result<()>;
+
+    /// 读取整个对象；不存在时返回 None
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// 以流的形式读取，供大文件直接转发给客户端；不存在时返回 None
+    async fn stream(&self, key: &str) -> io::Result<Option<Body>>;
+
+    /// 删除对象，不存在视为成功
+    async fn delete(&self, key: &str) -> io::Result<()>;
+
+    async fn exists(&self, key: &str) -> io::Result<bool>;
+
+    /// 列出 `prefix` 下的全部对象，`prefix` 须以 `/` 结尾（按目录列出）
+    async fn list(&self, prefix: &str) -> io::Result<Vec<ObjectMeta>>;
+}
+
+/// 按配置创建存储后端
+pub fn from_config(config: &Config) -> io::Result<Arc<dyn Storage>> {
+    Ok(match config.storage_backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(&config.upload_dir)),
+        StorageBackend::S3 => Arc::new(S3Storage::new(&config.s3)?),
+    })
+}
+
+/// 校验 key：非空、无空段、无 `.` / `..`、只含字母数字与 `-_.`。
+/// key 会拼进文件路径与 URL，不接受任何需要转义的字符
+fn check_key(key: &str) -> io::Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 512
+        && key.split('/').all(|seg| {
+            !seg.is_empty()
+                && seg != "."
+                && seg != ".."
+                && seg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("非法的存储 key: {key}"),
+        ))
+    }
+}
+
+fn check_prefix(prefix: &str) -> io::Result<()> {
+    match prefix.strip_suffix('/') {
+        Some(dir) => check_key(dir),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("前缀须以 / 结尾: {prefix}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_key_rejects_traversal() {
+        assert!(check_key("image/abc_DEF-123").is_ok());
+        assert!(check_key("favicons/www.example.com.ico").is_ok());
+        assert!(check_key("").is_err());
+        assert!(check_key("/image/a").is_err());
+        assert!(check_key("image//a").is_err());
+        assert!(check_key("image/../etc/passwd").is_err());
+        assert!(check_key("image/./a").is_err());
+        assert!(check_key("image\\a").is_err());
+        assert!(check_key("image/a b").is_err());
+        assert!(check_key("image/a?x=1").is_err());
+    }
+
+    #[test]
+    fn check_prefix_requires_trailing_slash() {
+        assert!(check_prefix("image/").is_ok());
+        assert!(check_prefix("image").is_err());
+        assert!(check_prefix("/").is_err());
+        assert!(check_prefix("../").is_err());
+    }
+}
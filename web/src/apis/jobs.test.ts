@@ -0,0 +1,46 @@
+import { beforeEach, describe, expect, it, vi } from "vitest";
+
+const requestMock = vi.hoisted(() => ({ request: vi.fn() }));
+vi.mock("@apis/request.ts", () => requestMock);
+
+import { type Job, waitForJobE } from "./jobs";
+
+const job = (status: Job["status"], extra: Partial<Job<number>> = {}): Job<number> => ({
+	id: 1,
+	kind: "bookmark_import",
+	status,
+	attempts: 0,
+	max_attempts: 1,
+	run_at: "",
+	result: null,
+	error: null,
+	created_at: "",
+	started_at: null,
+	finished_at: null,
+	...extra,
+});
+
+beforeEach(() => {
+	vi.clearAllMocks();
+});
+
+describe("waitForJobE", () => {
+	it("polls until the job succeeds", async () => {
+		requestMock.request
+			.mockResolvedValueOnce(job("running"))
+			.mockResolvedValueOnce(job("succeeded", { result: 42 }));
+		await expect(waitForJobE(job("queued"), 0)).resolves.toBe(42);
+		expect(requestMock.request).toHaveBeenCalledTimes(2);
+		expect(requestMock.request).toHaveBeenCalledWith("/jobs/1");
+	});
+
+	it("returns immediately for a finished job", async () => {
+		await expect(waitForJobE(job("succeeded", { result: 1 }), 0)).resolves.toBe(1);
+		expect(requestMock.request).not.toHaveBeenCalled();
+	});
+
+	it("throws the job error on failure", async () => {
+		requestMock.request.mockResolvedValueOnce(job("failed", { error: "boom" }));
+		await expect(waitForJobE(job("queued"), 0)).rejects.toThrow("boom");
+	});
+});
@@ -0,0 +1,40 @@
+import { request } from "@apis/request.ts";
+
+export type JobStatus = "queued" | "running" | "succeeded" | "failed";
+
+/** 后台任务（`/api/jobs/{id}`） */
+export interface Job<R = unknown> {
+	readonly id: number;
+	readonly kind: string;
+	readonly status: JobStatus;
+	readonly attempts: number;
+	readonly max_attempts: number;
+	readonly run_at: string;
+	readonly result: R | null;
+	readonly error: string | null;
+	readonly created_at: string;
+	readonly started_at: string | null;
+	readonly finished_at: string | null;
+}
+
+export const getJobE = <R = unknown>(id: number): Promise<Job<R>> =>
+	request<Job<R>>(`/jobs/${id}`);
+
+const sleep = (ms: number) =>
+	new Promise<void>((resolve) => setTimeout(resolve, ms));
+
+/** 轮询直到任务结束：成功返回 result，失败抛出任务的错误信息 */
+export const waitForJobE = async <R>(
+	job: Job<R>,
+	intervalMs = 1000,
+): Promise<R> => {
+	let current = job;
+	while (current.status === "queued" || current.status === "running") {
+		await sleep(intervalMs);
+		current = await getJobE<R>(current.id);
+	}
+	if (current.status === "failed" || current.result === null) {
+		throw new Error(current.error ?? "任务失败");
+	}
+	return current.result;
+};
@@ -1,4 +1,4 @@
-import { getToken } from "@auth/context.tsx";
+import { getRefreshToken, getToken, storeTokens } from "@auth/context.tsx";
 import { HttpError, NetworkError } from "@apis/types/index.ts";
 
 const API_BASE_URL = "/api";
@@ -153,11 +153,39 @@ export async function handleGlobalError(
 	// ── 4xx 业务错误（404/409/422 等）→ 只打日志，由组件处理 UI ──
 }
 
+// ==================== access token 续期 ====================
+
+/** 进行中的刷新请求：并发 401 共用一次刷新，避免 refresh token 被重复使用 */
+let _refreshing: Promise<boolean> | null = null;
+
+/** 用 refresh token 换新 token，成功返回 true */
+function refreshAccessToken(): Promise<boolean> {
+	const refreshToken = getRefreshToken();
+	if (!refreshToken) return Promise.resolve(false);
+	_refreshing ??= fetch(`${API_BASE_URL}/user/refresh`, {
+		method: "POST",
+		headers: { "Content-Type": "application/json" },
+		body: JSON.stringify({ refresh_token: refreshToken }),
+	})
+		.then(async (res) => {
+			if (!res.ok) return false;
+			const data = await res.json();
+			storeTokens(data.access_token, data.refresh_token);
+			return true;
+		})
+		.catch(() => false)
+		.finally(() => {
+			_refreshing = null;
+		});
+	return _refreshing;
+}
+
 // ==================== 核心请求函数 ====================
 
 export const request = async <T>(
 	endpoint: string,
 	options: RequestInit = {},
+	retried = false,
 ): Promise<T> => {
 	const url = `${API_BASE_URL}${endpoint}`;
 
@@ -186,6 +214,16 @@ export const request = async <T>(
 		throw new NetworkError({ cause });
 	}
 
+	// ── 401 → 先尝试用 refresh token 续期，成功则重放一次 ──
+	if (
+		response.status === 401 &&
+		!retried &&
+		!endpoint.startsWith("/user/login") &&
+		(await refreshAccessToken())
+	) {
+		return request<T>(endpoint, options, true);
+	}
+
 	// ── 非 2xx → 全局副作用 + 抛出 ──
 	if (!response.ok) {
 		let errorBody: { code: string; message: string; details?: unknown };
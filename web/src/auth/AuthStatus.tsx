@@ -74,8 +74,8 @@ export default function AuthStatus() {
 			(isRegister() ? registerE : loginE)(name(), password()),
 		);
 		if (result.ok) {
-			const { id, name: uname, role, token } = result.value;
-			authLogin(id, uname, role, token);
+			const { id, name: uname, role, token, refresh_token } = result.value;
+			authLogin(id, uname, role, token, refresh_token);
 			setShowForm(false);
 			// 登录后刷新页面
 			window.location.reload();
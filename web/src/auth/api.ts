@@ -5,6 +5,7 @@ export interface AuthUser {
 	name: string;
 	role: string;
 	token: string;
+	refresh_token: string;
 }
 
 export const loginE = (name: string, password: string): Promise<AuthUser> =>
@@ -19,6 +20,21 @@ export const registerE = (name: string, password: string): Promise<AuthUser> =>
 		body: JSON.stringify({ name, password }),
 	});
 
+export interface SessionInfo {
+	id: string;
+	user_agent: string;
+	created_at: string | null;
+	last_used_at: string | null;
+	expires_at: string;
+	current: boolean;
+}
+
+export const listSessionsE = (): Promise<SessionInfo[]> =>
+	request("/user/sessions");
+
+export const revokeSessionE = (id: string): Promise<void> =>
+	request(`/user/sessions/${encodeURIComponent(id)}`, { method: "DELETE" });
+
 export const logoutE = (): Promise<{ ok: boolean }> =>
 	request("/user/logout", { method: "POST" });
 
@@ -7,7 +7,13 @@ export interface AuthState {
 
 const AuthContext = createContext<{
 	auth: () => AuthState;
-	login: (id: number, name: string, role: string, token: string) => void;
+	login: (
+		id: number,
+		name: string,
+		role: string,
+		token: string,
+		refreshToken: string,
+	) => void;
 	logout: () => void;
 }>();
 
@@ -34,8 +40,14 @@ export function AuthProvider(props: { children: JSX.Element }) {
 
 	const ctxValue = {
 		auth,
-		login: (id: number, name: string, role: string, token: string) => {
-			const user = { id, name, role, token };
+		login: (
+			id: number,
+			name: string,
+			role: string,
+			token: string,
+			refreshToken: string,
+		) => {
+			const user = { id, name, role, token, refreshToken };
 			localStorage.setItem(STORAGE_KEY, JSON.stringify(user));
 			setAuth({ user, isAdmin: role === "admin" });
 		},
@@ -68,3 +80,17 @@ export function useAuth() {
 export function getToken(): string | null {
 	return loadFromStorage()?.token ?? null;
 }
+
+export function getRefreshToken(): string | null {
+	return loadFromStorage()?.refreshToken ?? null;
+}
+
+/** 刷新成功后就地替换本地保存的 token，不触发重新渲染 */
+export function storeTokens(token: string, refreshToken: string): void {
+	const user = loadFromStorage();
+	if (!user) return;
+	localStorage.setItem(
+		STORAGE_KEY,
+		JSON.stringify({ ...user, token, refreshToken }),
+	);
+}
@@ -14,8 +14,13 @@ const cacheMock = vi.hoisted(() => ({
 	tapInvalidate: vi.fn((_p: RegExp, r: unknown) => r),
 }));
 
+const jobsMock = vi.hoisted(() => ({
+	waitForJobE: vi.fn(),
+}));
+
 vi.mock("@apis/request.ts", () => requestMock);
 vi.mock("@apis/cache.ts", () => cacheMock);
+vi.mock("@apis/jobs.ts", () => jobsMock);
 
 import {
 	createBookmarkE,
@@ -144,11 +149,14 @@ describe("write operations invalidate cache", () => {
 // ── 导入 ──
 
 describe("importBookmarksE", () => {
-	it("sends multipart form and invalidates on success", async () => {
-		requestMock.request.mockImplementationOnce(() => Promise.resolve({ added: 5, merged: 2 }));
+	it("sends multipart form, waits for the job and invalidates on success", async () => {
+		const job = { id: 7, status: "queued" };
+		requestMock.request.mockImplementationOnce(() => Promise.resolve(job));
+		jobsMock.waitForJobE.mockImplementationOnce(() => Promise.resolve({ added: 5, merged: 2 }));
 		const file = new File(["<html>"], "bookmarks.html", { type: "text/html" });
 		const result = await importBookmarksE(file);
 		expect(result).toEqual({ added: 5, merged: 2 });
+		expect(jobsMock.waitForJobE).toHaveBeenCalledWith(job);
 
 		const [url, opts] = requestMock.request.mock.calls[0];
 		expect(url).toBe("/bookmarks/import");
@@ -157,4 +165,12 @@ describe("importBookmarksE", () => {
 		expect((opts.body as FormData).get("file")).toBe(file);
 		expect(cacheMock.invalidateCache).toHaveBeenCalledWith(cacheMock.CACHE.bookmarks);
 	});
+
+	it("does not invalidate when the job fails", async () => {
+		requestMock.request.mockImplementationOnce(() => Promise.resolve({ id: 8, status: "queued" }));
+		jobsMock.waitForJobE.mockImplementationOnce(() => Promise.reject(new Error("导入失败")));
+		const file = new File(["<html>"], "bookmarks.html", { type: "text/html" });
+		await expect(importBookmarksE(file)).rejects.toThrow("导入失败");
+		expect(cacheMock.invalidateCache).not.toHaveBeenCalled();
+	});
 });
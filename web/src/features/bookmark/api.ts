@@ -1,3 +1,4 @@
+import { type Job, waitForJobE } from "@apis/jobs.ts";
 import { del, patch, post, request } from "@apis/request.ts";
 import {
 	CACHE,
@@ -84,14 +85,15 @@ export const deleteBookmarkTagE = (id: number): Promise<void> =>
 		tapInvalidate(CACHE.bookmarks, r),
 	);
 
-/** 导入 Firefox 书签 HTML */
+/** 导入 Firefox 书签 HTML（后台任务，轮询至完成） */
 export const importBookmarksE = async (file: File): Promise<ImportResult> => {
 	const formData = new FormData();
 	formData.append("file", file);
-	const result = await request<ImportResult>("/bookmarks/import", {
+	const job = await request<Job<ImportResult>>("/bookmarks/import", {
 		method: "POST",
 		body: formData,
 	});
+	const result = await waitForJobE(job);
 	invalidateCache(CACHE.bookmarks);
 	return result;
 };
@@ -8,6 +8,8 @@ export type MediaType = "image" | "video" | "audio";
 export interface MediaItem {
 	stored_id: string;
 	url: string;
+	/** 缩略图（PNG）；非图片时即原文件 */
+	thumb_url: string;
 	original_name: string;
 	media_type: MediaType;
 	mime_type: string;
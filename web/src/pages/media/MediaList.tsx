@@ -142,7 +142,7 @@ const MediaListPage: Component = () => {
 															rel="noopener noreferrer"
 														>
 															<img
-																src={item.url}
+																src={item.thumb_url}
 																alt={item.original_name}
 																class={styles.thumb}
 																loading="lazy"